@@ -1,3 +1,139 @@
+use std::fs;
+
+// Commands that are not part of the generated `default` permission: a capability has to
+// grant their `allow-*` permission explicitly. Only `capabilities/sensitive.json` does, for
+// the app's own pages in the main window; dropping it from `app.security.capabilities` in
+// `tauri.conf.json` turns them off.
+const SENSITIVE_COMMANDS: &[&str] = &[
+    "add_kms_key",
+    "backup_keys",
+    "change_key_password",
+    "configure_sync",
+    "confirm_consent",
+    "confirm_key_exchange",
+    "create_master_seed",
+    "delete_key",
+    "delete_keys",
+    "delete_kms_key",
+    "delete_master_seed",
+    "delete_passkey",
+    "export_escrow_file",
+    "export_key_pair",
+    "export_master_seed",
+    "export_master_seed_mnemonic",
+    "finish_passkey_registration",
+    "import_config",
+    "import_contacts",
+    "import_key_pair",
+    "import_master_seed",
+    "import_pasted_public_key",
+    "import_public_key",
+    "import_sync_changes",
+    "install_native_messaging_host",
+    "purge_trash",
+    "recover_escrowed_key",
+    "recover_keys_from_mnemonic",
+    "remove_contact",
+    "repair_store",
+    "restore_key",
+    "restore_key_vault",
+    "rotate_local_api_token",
+    "save_key_vault",
+    "set_dual_control",
+    "set_key_unlock_policy",
+    "set_recovery_key",
+    "set_token_unwrap",
+    "update_consent_settings",
+    "update_device_binding",
+    "update_local_api",
+    "update_metadata_schema",
+    "update_oidc_settings",
+    "update_signable_input_rules",
+    "update_trust_bundle_settings",
+    "update_trusted_tsa_certificates",
+];
+
 fn main() {
-    tauri_build::build()
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=capabilities");
+    // Every registered command gets `allow-*` / `deny-*` permissions; once the app has a
+    // permission manifest, Tauri checks all commands against the capabilities.
+    let commands = registered_commands();
+    check_default_capability();
+    write_default_permission(&commands);
+    let commands: &'static [&'static str] =
+        Vec::leak(commands.into_iter().map(|c| &*String::leak(c)).collect());
+    tauri_build::try_build(
+        tauri_build::Attributes::new()
+            .app_manifest(tauri_build::AppManifest::new().commands(commands)),
+    )
+    .expect("failed to run tauri-build")
+}
+
+// 从 lib.rs 的 generate_handler! 列表中读取已注册的命令名
+fn registered_commands() -> Vec<String> {
+    let source = fs::read_to_string("src/lib.rs").expect("failed to read src/lib.rs");
+    let start = source
+        .find("generate_handler![")
+        .expect("no generate_handler! in src/lib.rs")
+        + "generate_handler![".len();
+    let end = start
+        + source[start..]
+            .find(']')
+            .expect("unterminated generate_handler!");
+    let commands: Vec<String> = source[start..end]
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .flat_map(|line| line.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+    for sensitive in SENSITIVE_COMMANDS {
+        assert!(
+            commands.iter().any(|c| c == sensitive),
+            "sensitive command {} is not registered",
+            sensitive
+        );
+    }
+    commands
+}
+
+// 默认 capability 不得授予敏感命令
+fn check_default_capability() {
+    let capability = fs::read_to_string("capabilities/default.json")
+        .expect("failed to read capabilities/default.json");
+    for sensitive in SENSITIVE_COMMANDS {
+        let permission = format!("\"allow-{}\"", sensitive.replace('_', "-"));
+        assert!(
+            !capability.contains(&permission),
+            "capabilities/default.json grants sensitive command {}; grant it in \
+             capabilities/sensitive.json",
+            sensitive
+        );
+    }
+}
+
+// 生成 default 权限：除敏感命令外的所有命令
+fn write_default_permission(commands: &[String]) {
+    let permissions: String = commands
+        .iter()
+        .filter(|c| !SENSITIVE_COMMANDS.contains(&c.as_str()))
+        .map(|c| format!("  \"allow-{}\",\n", c.replace('_', "-")))
+        .collect();
+    let content = format!(
+        "# Generated by build.rs from the commands registered in src/lib.rs; do not edit.\n\
+         [default]\n\
+         description = \"All commands except the sensitive ones, which capabilities grant one by one.\"\n\
+         permissions = [\n{}]\n",
+        permissions
+    );
+    fs::create_dir_all("permissions").expect("failed to create permissions directory");
+    if fs::read_to_string("permissions/default.toml")
+        .ok()
+        .as_deref()
+        != Some(content.as_str())
+    {
+        fs::write("permissions/default.toml", content).expect("failed to write default permission");
+    }
 }
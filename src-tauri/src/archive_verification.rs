@@ -0,0 +1,444 @@
+// src-tauri/src/archive_verification.rs
+// Scheduled re-verification of an archive: a configured folder is searched for documents
+// with signatures next to them (`<document>.sig`, or an envelope `<document>.json`), and
+// every signature is verified again against own keys, passkeys and contacts. This catches
+// documents that changed on disk (bit rot), time-stamps that no longer check out or whose
+// authority certificate has expired since, and signers whose contact was marked as
+// distrusted after signing.
+//
+// A background thread starts a run once the configured interval has passed since the last
+// one; runs can also be started by hand. The last report is kept in
+// `archive_verification.json`, and a run with failures ends with a system notification.
+use crate::audit;
+use crate::contacts::{get_contacts_path, read_contacts};
+use crate::crypto_types::{
+    ArchiveFailure, ArchiveIssue, ArchiveVerificationReport, ArchiveVerificationSettings,
+    SignatureFileReport, TrustLevel,
+};
+use crate::envelope::SignatureEnvelope;
+use crate::key_management::get_app_data_file;
+use crate::native_paths::path_string;
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use crate::signing::{signer_candidates, verify_against_candidates};
+use crate::timestamp::trusted_tsa_certificates;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, TryLockError};
+use std::time::Duration;
+use tauri_plugin_notification::NotificationExt;
+
+const REPORT_FILENAME: &str = "archive_verification.json";
+/// How often the scheduler checks whether a run is due.
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const MAX_INTERVAL_HOURS: u32 = 24 * 365;
+const SIGNATURE_EXTENSIONS: [&str; 2] = ["sig", "json"];
+
+/// Held while a run is in progress, so scheduled and manual runs do not overlap.
+static RUN_LOCK: Mutex<()> = Mutex::new(());
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_archive_verification(
+    app_handle: tauri::AppHandle,
+    archive_verification: ArchiveVerificationSettings,
+) -> Result<ArchiveVerificationSettings, String> {
+    log::info!("Updating archive verification: {:?}", archive_verification);
+    _update_archive_verification(&app_handle, archive_verification)
+        .map_err(|e| {
+            log::error!("Failed to update archive verification: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|settings| {
+            audit::record(&app_handle, "updateArchiveVerification", json!(settings))
+        })
+}
+
+fn _update_archive_verification(
+    app_handle: &tauri::AppHandle,
+    archive_verification: ArchiveVerificationSettings,
+) -> Result<ArchiveVerificationSettings> {
+    if !(1..=MAX_INTERVAL_HOURS).contains(&archive_verification.interval_hours) {
+        bail!(
+            "Interval must be between 1 and {} hours.",
+            MAX_INTERVAL_HOURS
+        );
+    }
+    if archive_verification.enabled {
+        archive_folder(&archive_verification)?;
+    }
+    update_settings(app_handle, |settings| {
+        settings.archive_verification = archive_verification
+    })
+    .map(|settings| settings.archive_verification)
+}
+
+/// Re-verifies the configured folder now.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn run_archive_verification(
+    app_handle: tauri::AppHandle,
+) -> Result<ArchiveVerificationReport, String> {
+    log::info!("Running archive verification");
+    archive_settings(&app_handle)
+        .and_then(|settings| {
+            let folder = archive_folder(&settings)?;
+            let _guard = match RUN_LOCK.try_lock() {
+                Ok(guard) => guard,
+                Err(TryLockError::WouldBlock) => bail!("Archive verification is already running."),
+                Err(TryLockError::Poisoned(_)) => bail!("Archive verification lock poisoned"),
+            };
+            verify_archive(&app_handle, &folder, false)
+        })
+        .map_err(|e| {
+            log::error!("Failed to run archive verification: {:?}", e);
+            e.to_string()
+        })
+}
+
+/// The report of the last run, if there was one.
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_archive_verification_report(
+    app_handle: tauri::AppHandle,
+) -> Result<Option<ArchiveVerificationReport>, String> {
+    log::info!("Reading the last archive verification report");
+    get_report_path(&app_handle)
+        .and_then(|path| read_report(&path))
+        .map_err(|e| {
+            log::error!("Failed to read archive verification report: {:?}", e);
+            e.to_string()
+        })
+}
+
+// --- Helper Functions ---
+
+/// Starts the scheduler thread.
+pub fn start_archive_verification(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SCHEDULE_CHECK_INTERVAL);
+        if let Err(e) = run_if_due(&app_handle) {
+            log::error!("Scheduled archive verification failed: {:?}", e);
+        }
+    });
+}
+
+// 到期才运行；手动运行进行中时跳过本轮
+fn run_if_due(app_handle: &tauri::AppHandle) -> Result<()> {
+    let settings = archive_settings(app_handle)?;
+    if !settings.enabled {
+        return Ok(());
+    }
+    let folder = archive_folder(&settings)?;
+    let last_report = read_report(&get_report_path(app_handle)?)?;
+    if !is_due(
+        last_report.as_ref(),
+        &folder,
+        settings.interval_hours,
+        Utc::now(),
+    ) {
+        return Ok(());
+    }
+    let _guard = match RUN_LOCK.try_lock() {
+        Ok(guard) => guard,
+        Err(TryLockError::WouldBlock) => return Ok(()),
+        Err(TryLockError::Poisoned(_)) => bail!("Archive verification lock poisoned"),
+    };
+    verify_archive(app_handle, &folder, true).map(|_| ())
+}
+
+fn is_due(
+    last_report: Option<&ArchiveVerificationReport>,
+    folder: &Path,
+    interval_hours: u32,
+    now: DateTime<Utc>,
+) -> bool {
+    match last_report {
+        Some(report) if Path::new(&report.folder) == folder => {
+            report.finished_at + ChronoDuration::hours(interval_hours as i64) <= now
+        }
+        _ => true,
+    }
+}
+
+fn verify_archive(
+    app_handle: &tauri::AppHandle,
+    folder: &Path,
+    scheduled: bool,
+) -> Result<ArchiveVerificationReport> {
+    let started_at = Utc::now();
+    let candidates = signer_candidates(app_handle)?;
+    let trusted_tsas = trusted_tsa_certificates(app_handle)?;
+    let distrusted: Vec<String> = read_contacts(&get_contacts_path(app_handle)?)?
+        .into_iter()
+        .filter(|contact| contact.trust == TrustLevel::Distrusted)
+        .map(|contact| contact.fingerprint)
+        .collect();
+
+    let pairs = find_signed_documents(folder)?;
+    let mut failures = Vec::new();
+    for (document_path, signature_path) in &pairs {
+        let document = path_string(document_path);
+        let signature = path_string(signature_path);
+        let failure = match verify_against_candidates(
+            document_path,
+            signature_path,
+            &candidates,
+            &trusted_tsas,
+            None,
+            true,
+        ) {
+            Ok(report) => {
+                check_signature(&report, &distrusted, Utc::now()).map(|issues| ArchiveFailure {
+                    document_path: document,
+                    signature_path: signature,
+                    signer_name: report.signer_name,
+                    issues,
+                    error_message: report.result.error_message,
+                })
+            }
+            Err(e) => Some(ArchiveFailure {
+                document_path: document,
+                signature_path: signature,
+                signer_name: None,
+                issues: vec![ArchiveIssue::SignatureInvalid],
+                error_message: Some(format!("{:#}", e)),
+            }),
+        };
+        failures.extend(failure);
+    }
+
+    let report = ArchiveVerificationReport {
+        folder: folder.to_string_lossy().into_owned(),
+        started_at,
+        finished_at: Utc::now(),
+        scheduled,
+        checked: pairs.len(),
+        failures,
+    };
+    write_report(&get_report_path(app_handle)?, &report)?;
+    log::info!(
+        "Archive verification of {:?}: {} signatures checked, {} failed",
+        folder,
+        report.checked,
+        report.failures.len()
+    );
+    audit::record(
+        app_handle,
+        "verifyArchive",
+        json!({
+            "folder": report.folder,
+            "scheduled": scheduled,
+            "checked": report.checked,
+            "failed": report.failures.len(),
+        }),
+    );
+    if !report.failures.is_empty() {
+        notify_failures(app_handle, &report);
+    }
+    Ok(report)
+}
+
+/// Problems of one verified signature; `None` if it still checks out.
+fn check_signature(
+    report: &SignatureFileReport,
+    distrusted: &[String],
+    now: DateTime<Utc>,
+) -> Option<Vec<ArchiveIssue>> {
+    let result = &report.result;
+    let mut issues = Vec::new();
+    let digest_changed = result.expected_digest.is_some()
+        && result.computed_digest.is_some()
+        && result.expected_digest != result.computed_digest;
+    if digest_changed || !result.binding_mismatches.is_empty() {
+        issues.push(ArchiveIssue::DocumentChanged);
+    } else if !result.is_valid {
+        issues.push(ArchiveIssue::SignatureInvalid);
+    }
+    if result
+        .timestamps
+        .iter()
+        .any(|timestamp| !timestamp.is_valid)
+    {
+        issues.push(ArchiveIssue::TimestampInvalid);
+    }
+    if result
+        .timestamps
+        .iter()
+        .any(|timestamp| timestamp.certificate_not_after.is_some_and(|end| end < now))
+    {
+        issues.push(ArchiveIssue::TimestampExpired);
+    }
+    if report
+        .signer_fingerprint
+        .as_ref()
+        .is_some_and(|fingerprint| distrusted.contains(fingerprint))
+    {
+        issues.push(ArchiveIssue::SignerDistrusted);
+    }
+    (!issues.is_empty()).then_some(issues)
+}
+
+// 递归查找旁边有签名文件的文档；不跟随符号链接
+fn find_signed_documents(folder: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut pairs = Vec::new();
+    let mut pending = vec![folder.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Failed to read directory {:?}", dir))?
+        {
+            let entry = entry.with_context(|| format!("Failed to read directory {:?}", dir))?;
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() && is_signature_of_sibling(&path) {
+                pairs.push((path.with_extension(""), path));
+            }
+        }
+    }
+    pairs.sort();
+    Ok(pairs)
+}
+
+// `<document>.sig`，或内容是签名信封的 `<document>.json`
+fn is_signature_of_sibling(path: &Path) -> bool {
+    let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+    if !SIGNATURE_EXTENSIONS.contains(&extension) || !path.with_extension("").is_file() {
+        return false;
+    }
+    extension != "json"
+        || fs::read(path)
+            .ok()
+            .is_some_and(|bytes| matches!(SignatureEnvelope::parse(&bytes), Ok(Some(_))))
+}
+
+fn archive_settings(app_handle: &tauri::AppHandle) -> Result<ArchiveVerificationSettings> {
+    Ok(read_settings(&get_settings_path(app_handle)?)?.archive_verification)
+}
+
+fn archive_folder(settings: &ArchiveVerificationSettings) -> Result<PathBuf> {
+    let folder = settings
+        .folder
+        .as_deref()
+        .filter(|folder| !folder.is_empty())
+        .ok_or_else(|| anyhow!("No archive folder is configured."))?;
+    let folder = PathBuf::from(folder);
+    if !folder.is_dir() {
+        bail!("Archive folder not found: {:?}", folder);
+    }
+    Ok(folder)
+}
+
+// 获取上次报告文件路径
+fn get_report_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, REPORT_FILENAME)
+}
+
+fn read_report(path: &Path) -> Result<Option<ArchiveVerificationReport>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read archive verification report: {:?}", path))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .context("Failed to parse archive verification report JSON")
+}
+
+fn write_report(path: &Path, report: &ArchiveVerificationReport) -> Result<()> {
+    let content = serde_json::to_string_pretty(report)
+        .context("Failed to serialize archive verification report")?;
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write archive verification report: {:?}", path))
+}
+
+fn notify_failures(app_handle: &tauri::AppHandle, report: &ArchiveVerificationReport) {
+    let body = format!(
+        "{} of {} signatures in {} failed verification.",
+        report.failures.len(),
+        report.checked,
+        report.folder
+    );
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title("Archive verification found problems")
+        .body(body)
+        .show()
+    {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_types::{TimestampReport, VerificationResult};
+
+    #[test]
+    fn finds_signatures_and_classifies_problems() {
+        let folder = std::env::temp_dir().join(format!("sigust-archive-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(folder.join("2024")).unwrap();
+        for (name, content) in [
+            ("contract.pdf", "document"),
+            ("contract.pdf.sig", "signature"),
+            ("2024/notes.txt", "document"),
+            ("2024/notes.txt.json", "{\"not\": \"an envelope\"}"),
+            ("orphan.pdf.sig", "signature"),
+        ] {
+            fs::write(folder.join(name), content).unwrap();
+        }
+        let pairs = find_signed_documents(&folder).unwrap();
+        assert_eq!(
+            pairs,
+            vec![(folder.join("contract.pdf"), folder.join("contract.pdf.sig"))]
+        );
+        fs::remove_dir_all(&folder).unwrap();
+
+        let now = Utc::now();
+        let report = |result: VerificationResult| SignatureFileReport {
+            signature_path: "contract.pdf.sig".to_string(),
+            signer_name: Some("Alice".to_string()),
+            signer_fingerprint: Some("ab12".to_string()),
+            result,
+        };
+        let valid = report(VerificationResult {
+            is_valid: true,
+            ..Default::default()
+        });
+        assert_eq!(check_signature(&valid, &[], now), None);
+        assert_eq!(
+            check_signature(&valid, &["ab12".to_string()], now),
+            Some(vec![ArchiveIssue::SignerDistrusted])
+        );
+
+        let rotted = report(VerificationResult {
+            is_valid: false,
+            expected_digest: Some("00".to_string()),
+            computed_digest: Some("11".to_string()),
+            timestamps: vec![TimestampReport {
+                tsa_url: "https://tsa.example".to_string(),
+                gen_time: now - ChronoDuration::days(800),
+                tsa_name: None,
+                certificate_not_after: Some(now - ChronoDuration::days(1)),
+                checked_at: None,
+                revocation_status: None,
+                is_valid: true,
+                error_message: None,
+            }],
+            ..Default::default()
+        });
+        assert_eq!(
+            check_signature(&rotted, &[], now),
+            Some(vec![
+                ArchiveIssue::DocumentChanged,
+                ArchiveIssue::TimestampExpired
+            ])
+        );
+    }
+}
@@ -0,0 +1,351 @@
+// src-tauri/src/audit.rs
+// Tamper-evident audit log of security-relevant operations (key generation, signing,
+// verification, ...).
+//
+// Entries are stored one JSON object per line in `audit.jsonl`. Each entry commits to
+// the previous one through `prevHash`, so editing or removing an entry breaks the chain.
+// After every append the chain head (last sequence number and hash) is signed with an
+// app-managed Ed25519 key and stored in `audit_head.json`, which also catches truncation
+// of the newest entries. The key lives unencrypted next to the log, so this detects
+// accidental damage and casual edits, not an attacker who controls the app data folder.
+use crate::crypto_types::{
+    AuditEntry, AuditExportFormat, AuditVerificationReport, DigestScheme, SignatureAlgorithm,
+};
+use crate::envelope::canonical_json;
+use crate::key_management::get_app_data_file;
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::resolve_output_path;
+use crate::signing::{sign_bytes, verify_bytes};
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::Utc;
+use ed25519_dalek::pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey};
+use ed25519_dalek::SigningKey;
+use rsa::rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const AUDIT_LOG_FILENAME: &str = "audit.jsonl";
+const AUDIT_HEAD_FILENAME: &str = "audit_head.json";
+const AUDIT_KEY_FILENAME: &str = "audit_key.der";
+/// `prevHash` of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Serializes appends so the chain never forks.
+static AUDIT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Signed chain head stored in `audit_head.json`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct AuditHead {
+    seq: u64,
+    hash: String,
+    /// Ed25519 signature over `"<seq>:<hash>"`, standard base64.
+    signature: String,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_audit_log(
+    app_handle: tauri::AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<AuditEntry>, String> {
+    log::info!("Loading audit log");
+    get_app_data_file(&app_handle, AUDIT_LOG_FILENAME)
+        .and_then(|path| read_entries(&path))
+        .map(|entries| match limit {
+            Some(limit) => entries[entries.len().saturating_sub(limit)..].to_vec(),
+            None => entries,
+        })
+        .map_err(|e| {
+            log::error!("Failed to load audit log: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_audit_log(
+    app_handle: tauri::AppHandle,
+    output_path: String,
+    format: AuditExportFormat,
+) -> Result<String, String> {
+    log::info!("Exporting audit log to '{}' as {:?}", output_path, format);
+    _export_audit_log(&app_handle, &output_path, format)
+        .map(|path| path_string(&path))
+        .map_err(|e| {
+            log::error!("Failed to export audit log: {:?}", e);
+            e.to_string()
+        })
+}
+
+fn _export_audit_log(
+    app_handle: &tauri::AppHandle,
+    output_path: &str,
+    format: AuditExportFormat,
+) -> Result<PathBuf> {
+    let entries = read_entries(&get_app_data_file(app_handle, AUDIT_LOG_FILENAME)?)?;
+    let content = match format {
+        AuditExportFormat::Json => {
+            serde_json::to_string_pretty(&entries).context("Failed to serialize audit log")?
+        }
+        AuditExportFormat::Csv => {
+            let mut csv = String::from("seq,timestamp,action,details,prevHash,hash\n");
+            for entry in &entries {
+                let row = [
+                    entry.seq.to_string(),
+                    entry.timestamp.to_rfc3339(),
+                    entry.action.clone(),
+                    entry.details.to_string(),
+                    entry.prev_hash.clone(),
+                    entry.hash.clone(),
+                ];
+                let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+                csv.push_str(&row.join(","));
+                csv.push('\n');
+            }
+            csv
+        }
+    };
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write audit export: {:?}", output_path))?;
+    Ok(output_path)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn verify_audit_log(app_handle: tauri::AppHandle) -> Result<AuditVerificationReport, String> {
+    log::info!("Verifying audit log");
+    _verify_audit_log(&app_handle).map_err(|e| {
+        log::error!("Failed to verify audit log: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _verify_audit_log(app_handle: &tauri::AppHandle) -> Result<AuditVerificationReport> {
+    let _guard = lock_audit()?;
+    let entries = read_entries(&get_app_data_file(app_handle, AUDIT_LOG_FILENAME)?)?;
+    let mut report = AuditVerificationReport {
+        is_valid: false,
+        entries: entries.len() as u64,
+        first_invalid_seq: None,
+        error_message: None,
+    };
+
+    // 1. Walk the chain
+    let mut prev_hash = GENESIS_HASH.to_string();
+    for (index, entry) in entries.iter().enumerate() {
+        let problem = if entry.seq != index as u64 {
+            Some(format!("expected sequence number {}", index))
+        } else if entry.prev_hash != prev_hash {
+            Some("does not follow the previous entry".to_string())
+        } else if entry.hash != entry_hash(entry)? {
+            Some("content was modified".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            report.first_invalid_seq = Some(index as u64);
+            report.error_message = Some(format!("Entry {}: {}", index, problem));
+            return Ok(report);
+        }
+        prev_hash = entry.hash.clone();
+    }
+
+    // 2. The signed head must match the last entry (detects truncation)
+    let head_path = get_app_data_file(app_handle, AUDIT_HEAD_FILENAME)?;
+    let Some(last) = entries.last() else {
+        if head_path.exists() {
+            report.error_message = Some("Audit log is empty but a signed head exists".into());
+        } else {
+            report.is_valid = true;
+        }
+        return Ok(report);
+    };
+    if !head_path.exists() {
+        report.error_message = Some("Signed head of the audit log is missing".to_string());
+        return Ok(report);
+    }
+    let head: AuditHead =
+        serde_json::from_str(&fs::read_to_string(&head_path).context("Failed to read audit head")?)
+            .context("Failed to parse audit head")?;
+    let public_key_der = audit_signing_key(app_handle)?
+        .verifying_key()
+        .to_public_key_der()
+        .map_err(|e| anyhow!("Failed to encode audit public key: {}", e))?;
+    let signature = BASE64
+        .decode(&head.signature)
+        .context("Failed to decode audit head signature")?;
+    if verify_bytes(
+        &SignatureAlgorithm::Ed25519,
+        public_key_der.as_bytes(),
+        head_message(head.seq, &head.hash).as_bytes(),
+        &signature,
+        DigestScheme::Standard,
+    )?
+    .is_err()
+    {
+        report.error_message = Some("Signature on the audit log head is invalid".to_string());
+        return Ok(report);
+    }
+    if head.seq != last.seq || head.hash != last.hash {
+        report.error_message = Some(format!(
+            "Audit log ends at entry {} but the signed head is at entry {}; \
+             entries were removed or replaced",
+            last.seq, head.seq
+        ));
+        return Ok(report);
+    }
+    report.is_valid = true;
+    Ok(report)
+}
+
+// --- Helper Functions ---
+
+/// Appends an entry to the audit log. Failures are logged, never returned, so auditing
+/// cannot break the operation being audited.
+pub(crate) fn record(app_handle: &tauri::AppHandle, action: &str, details: Value) {
+    if let Err(e) = append_entry(app_handle, action, details) {
+        log::warn!("Failed to write audit entry '{}': {:?}", action, e);
+    }
+}
+
+/// Checks that entries can be appended: the log file opens for appending and the chain
+/// key is readable. Used where a policy makes auditing mandatory.
+pub(crate) fn ensure_writable(app_handle: &tauri::AppHandle) -> Result<()> {
+    let _guard = lock_audit()?;
+    let log_path = get_app_data_file(app_handle, AUDIT_LOG_FILENAME)?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .context("Failed to open audit log for appending")?;
+    audit_signing_key(app_handle).map(|_| ())
+}
+
+fn append_entry(app_handle: &tauri::AppHandle, action: &str, details: Value) -> Result<()> {
+    let _guard = lock_audit()?;
+    let log_path = get_app_data_file(app_handle, AUDIT_LOG_FILENAME)?;
+    let last = read_entries(&log_path)?.pop();
+    let mut entry = AuditEntry {
+        seq: last.as_ref().map_or(0, |e| e.seq + 1),
+        timestamp: Utc::now(),
+        action: action.to_string(),
+        details,
+        prev_hash: last.map_or_else(|| GENESIS_HASH.to_string(), |e| e.hash),
+        hash: String::new(),
+    };
+    entry.hash = entry_hash(&entry)?;
+
+    let mut line = serde_json::to_string(&entry).context("Failed to serialize audit entry")?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .context("Failed to append to audit log")?;
+
+    // 签名新的链头
+    let private_key_der = audit_signing_key(app_handle)?
+        .to_pkcs8_der()
+        .map_err(|e| anyhow!("Failed to encode audit key: {}", e))?;
+    let signature = sign_bytes(
+        &SignatureAlgorithm::Ed25519,
+        private_key_der.as_bytes(),
+        head_message(entry.seq, &entry.hash).as_bytes(),
+        DigestScheme::Standard,
+    )?;
+    let head = AuditHead {
+        seq: entry.seq,
+        hash: entry.hash,
+        signature: BASE64.encode(signature),
+    };
+    let content = serde_json::to_string_pretty(&head).context("Failed to serialize audit head")?;
+    fs::write(get_app_data_file(app_handle, AUDIT_HEAD_FILENAME)?, content)
+        .context("Failed to write audit head")
+}
+
+/// All entries of the audit log, oldest first.
+pub(crate) fn read_log(app_handle: &tauri::AppHandle) -> Result<Vec<AuditEntry>> {
+    read_entries(&get_app_data_file(app_handle, AUDIT_LOG_FILENAME)?)
+}
+
+// 读取全部日志条目
+fn read_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = File::open(path).context("Failed to open audit log")?;
+    let mut entries = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.context("Failed to read audit log")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line)
+            .with_context(|| format!("Malformed audit log line {}", index + 1))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+// 条目哈希：除 `hash` 外所有字段的规范 JSON 的 SHA-256
+fn entry_hash(entry: &AuditEntry) -> Result<String> {
+    let mut value = serde_json::to_value(entry).context("Failed to serialize audit entry")?;
+    if let Value::Object(fields) = &mut value {
+        fields.remove("hash");
+    }
+    Ok(hex::encode(Sha256::digest(canonical_json(&value)?)))
+}
+
+fn head_message(seq: u64, hash: &str) -> String {
+    format!("{}:{}", seq, hash)
+}
+
+// 读取（不存在时生成）应用自管的审计签名密钥
+fn audit_signing_key(app_handle: &tauri::AppHandle) -> Result<SigningKey> {
+    let path = get_app_data_file(app_handle, AUDIT_KEY_FILENAME)?;
+    if path.exists() {
+        let der = fs::read(&path).context("Failed to read audit key")?;
+        return SigningKey::from_pkcs8_der(&der)
+            .map_err(|e| anyhow!("Failed to parse audit key: {}", e));
+    }
+    let key = SigningKey::generate(&mut OsRng);
+    let der = key
+        .to_pkcs8_der()
+        .map_err(|e| anyhow!("Failed to encode audit key: {}", e))?;
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&path)
+        .and_then(|mut file| file.write_all(der.as_bytes()))
+        .context("Failed to write audit key")?;
+    Ok(key)
+}
+
+fn lock_audit() -> Result<std::sync::MutexGuard<'static, ()>> {
+    AUDIT_LOCK
+        .lock()
+        .map_err(|_| anyhow!("Audit log lock poisoned"))
+}
+
+// CSV 字段转义：含逗号、引号或换行时加引号
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,544 @@
+// src-tauri/src/batch_jobs.rs
+// Resumable batch jobs: batch signing (`sign_documents_batch`) and batch verification
+// (`verify_documents_batch`) run as background tasks whose state survives a crash or a
+// restart. A job is stored as `batch_jobs/<id>.json` (what to do, written once) and
+// `batch_jobs/<id>.jsonl` (one line per processed item, flushed to disk after each). A
+// torn last line from a crash is ignored, so that item is simply processed again.
+//
+// A job that runs to the end is deleted. One that is cancelled, fails or is interrupted
+// stays listed by `list_batch_jobs` until `resume_batch_job` continues it with the items
+// not yet processed, or `discard_batch_job` drops it. Passwords are never stored: a
+// signing job asks for the key password again when resumed. A signature written just
+// before a crash, but not yet journaled, is written again on resume (with the collision
+// policy deciding its name).
+use crate::audit;
+use crate::crypto_types::{BatchJobInfo, BatchJobItem, BatchJobKind, DigestScheme};
+use crate::hashing::hash_in_pool;
+use crate::key_management::{find_key_metadata, get_app_data_file, read_private_key_der};
+use crate::native_paths::{native_path, path_string, read_file};
+use crate::output_paths::signature_output_path;
+use crate::signable_inputs::check_signable_input;
+use crate::signing::{sign_bytes, signer_candidates, verify_against_candidates};
+use crate::tasks::{spawn_task, TaskHandle};
+use crate::timestamp::trusted_tsa_certificates;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+const JOBS_DIRNAME: &str = "batch_jobs";
+
+/// Jobs being run by a task in this session, with the task's ID once it is known.
+static RUNNING_JOBS: Mutex<BTreeMap<Uuid, Option<Uuid>>> = Mutex::new(BTreeMap::new());
+
+/// What a job does, written when it is created.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct BatchJob {
+    job_id: Uuid,
+    kind: BatchJobKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output_dir: Option<String>,
+    items: Vec<BatchJobItem>,
+    created_at: DateTime<Utc>,
+}
+
+/// Outcome for one item: a line of the job's journal and a partial result of its task.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct BatchItemOutcome {
+    index: usize,
+    document_path: String,
+    /// The signature written (signing) or checked (verification).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature_path: Option<String>,
+    /// Verification only: whether the signature verified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    is_valid: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signer_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl BatchItemOutcome {
+    fn failed(&self) -> bool {
+        self.error.is_some() || self.is_valid == Some(false)
+    }
+}
+
+/// Final result of a signing job, counting items processed before a resume as well.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchSignSummary {
+    signed: usize,
+    failed: usize,
+}
+
+/// Final result of a verification job.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchVerifySummary {
+    valid: usize,
+    invalid: usize,
+}
+
+// --- Tauri Commands ---
+
+/// Verifies document / signature pairs against your keys and contacts as a background
+/// task that can be resumed. The task result is a summary; each pair is reported as a
+/// partial result.
+#[tauri::command(rename_all = "camelCase")]
+pub fn verify_documents_batch(
+    app_handle: tauri::AppHandle,
+    items: Vec<BatchJobItem>,
+) -> Result<Uuid, String> {
+    log::info!("Starting batch verification of {} documents", items.len());
+    if items.is_empty() {
+        return Err("No documents to verify.".to_string());
+    }
+    if items.iter().any(|item| item.signature_path.is_none()) {
+        return Err("Every document needs a signature file.".to_string());
+    }
+    let count = items.len();
+    start_batch_job(&app_handle, BatchJobKind::Verify, None, None, items, None)
+        .map_err(|e| {
+            log::error!("Failed to start batch verification: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|task_id| {
+            audit::record(
+                &app_handle,
+                "verifyDocumentsBatch",
+                json!({ "documents": count, "taskId": task_id }),
+            )
+        })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_batch_jobs(app_handle: tauri::AppHandle) -> Result<Vec<BatchJobInfo>, String> {
+    log::info!("Listing unfinished batch jobs");
+    _list_batch_jobs(&app_handle).map_err(|e| {
+        log::error!("Failed to list batch jobs: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// Continues an unfinished job with the items it has not processed yet. Signing jobs need
+/// the key password again. Returns the ID of the new task.
+#[tauri::command(rename_all = "camelCase")]
+pub fn resume_batch_job(
+    app_handle: tauri::AppHandle,
+    job_id: Uuid,
+    password: Option<String>,
+) -> Result<Uuid, String> {
+    log::info!("Resuming batch job {}", job_id);
+    read_job(&app_handle, job_id)
+        .and_then(|(job, outcomes)| start_job(&app_handle, job, outcomes, password))
+        .map_err(|e| {
+            log::error!("Failed to resume batch job {}: {:?}", job_id, e);
+            e.to_string()
+        })
+        .inspect(|task_id| {
+            audit::record(
+                &app_handle,
+                "resumeBatchJob",
+                json!({ "jobId": job_id, "taskId": task_id }),
+            )
+        })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn discard_batch_job(app_handle: tauri::AppHandle, job_id: Uuid) -> Result<(), String> {
+    log::info!("Discarding batch job {}", job_id);
+    _discard_batch_job(&app_handle, job_id)
+        .map_err(|e| {
+            log::error!("Failed to discard batch job {}: {:?}", job_id, e);
+            e.to_string()
+        })
+        .inspect(|_| audit::record(&app_handle, "discardBatchJob", json!({ "jobId": job_id })))
+}
+
+fn _list_batch_jobs(app_handle: &tauri::AppHandle) -> Result<Vec<BatchJobInfo>> {
+    let dir = jobs_dir(app_handle)?;
+    let running = lock_running()?.clone();
+    let mut jobs = Vec::new();
+    for entry in fs::read_dir(&dir).with_context(|| format!("Failed to read {:?}", dir))? {
+        let path = entry?.path();
+        let Some(job_id) = path
+            .extension()
+            .filter(|extension| *extension == "json")
+            .and_then(|_| path.file_stem())
+            .and_then(|stem| Uuid::parse_str(&stem.to_string_lossy()).ok())
+        else {
+            continue;
+        };
+        let (job, outcomes) = match read_job(app_handle, job_id) {
+            Ok(job) => job,
+            Err(e) => {
+                log::warn!("Skipping unreadable batch job {:?}: {:?}", path, e);
+                continue;
+            }
+        };
+        jobs.push(BatchJobInfo {
+            job_id,
+            kind: job.kind,
+            key_id: job.key_id,
+            output_dir: job.output_dir,
+            created_at: job.created_at,
+            total: job.items.len(),
+            processed: outcomes.len(),
+            failed: outcomes.values().filter(|o| o.failed()).count(),
+            task_id: running.get(&job_id).copied().flatten(),
+        });
+    }
+    jobs.sort_by_key(|job| job.created_at);
+    Ok(jobs)
+}
+
+fn _discard_batch_job(app_handle: &tauri::AppHandle, job_id: Uuid) -> Result<()> {
+    if lock_running()?.contains_key(&job_id) {
+        bail!("The job is running; cancel its task first");
+    }
+    let (definition, journal) = job_paths(app_handle, job_id)?;
+    if !definition.exists() {
+        bail!("No batch job with ID {}", job_id);
+    }
+    remove_job_files(&definition, &journal)
+}
+
+// --- Helper Functions ---
+
+/// Creates a job and starts a task running it. `password` is needed for signing jobs.
+pub(crate) fn start_batch_job(
+    app_handle: &tauri::AppHandle,
+    kind: BatchJobKind,
+    key_id: Option<Uuid>,
+    output_dir: Option<String>,
+    items: Vec<BatchJobItem>,
+    password: Option<String>,
+) -> Result<Uuid> {
+    let job = BatchJob {
+        job_id: Uuid::new_v4(),
+        kind,
+        key_id,
+        output_dir,
+        items,
+        created_at: Utc::now(),
+    };
+    let (definition, _) = job_paths(app_handle, job.job_id)?;
+    let content = serde_json::to_string_pretty(&job).context("Failed to serialize batch job")?;
+    fs::write(&definition, content)
+        .with_context(|| format!("Failed to write batch job {:?}", definition))?;
+    start_job(app_handle, job, HashMap::new(), password)
+}
+
+// 只处理尚无结果的条目；任务正常结束后删除作业文件
+fn start_job(
+    app_handle: &tauri::AppHandle,
+    job: BatchJob,
+    outcomes: HashMap<usize, BatchItemOutcome>,
+    password: Option<String>,
+) -> Result<Uuid> {
+    let password = match (job.kind, password) {
+        (BatchJobKind::Sign, Some(password)) if !password.is_empty() => password,
+        (BatchJobKind::Sign, _) => bail!("Password cannot be empty."),
+        (BatchJobKind::Verify, _) => String::new(),
+    };
+    {
+        let mut running = lock_running()?;
+        if running.contains_key(&job.job_id) {
+            bail!("The job is already running");
+        }
+        running.insert(job.job_id, None);
+    }
+    let job_id = job.job_id;
+    let kind = match job.kind {
+        BatchJobKind::Sign => "batchSign",
+        BatchJobKind::Verify => "batchVerify",
+    };
+    let task_app_handle = app_handle.clone();
+    let started = spawn_task(app_handle, kind, move |task| {
+        let result = run_job(&task_app_handle, task, &job, outcomes, &password);
+        if let Ok(mut running) = lock_running() {
+            running.remove(&job_id);
+        }
+        if result.is_ok() {
+            let (definition, journal) = job_paths(&task_app_handle, job_id)?;
+            remove_job_files(&definition, &journal)?;
+        }
+        result
+    });
+    let mut running = lock_running()?;
+    match started {
+        Ok(task_id) => {
+            // 任务可能已经结束并移除了登记
+            if let Some(entry) = running.get_mut(&job_id) {
+                *entry = Some(task_id);
+            }
+            Ok(task_id)
+        }
+        Err(e) => {
+            running.remove(&job_id);
+            Err(e)
+        }
+    }
+}
+
+fn run_job(
+    app_handle: &tauri::AppHandle,
+    task: &TaskHandle,
+    job: &BatchJob,
+    mut outcomes: HashMap<usize, BatchItemOutcome>,
+    password: &str,
+) -> Result<serde_json::Value> {
+    let pending: Vec<usize> = (0..job.items.len())
+        .filter(|index| !outcomes.contains_key(index))
+        .collect();
+    let total = job.items.len() as u64;
+    let (_, journal_path) = job_paths(app_handle, job.job_id)?;
+    let mut journal = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&journal_path)
+        .with_context(|| format!("Failed to open {:?}", journal_path))?;
+    let mut record = |outcome: BatchItemOutcome| -> Result<()> {
+        let line = serde_json::to_string(&outcome)?;
+        writeln!(journal, "{}", line)
+            .and_then(|_| journal.sync_data())
+            .with_context(|| format!("Failed to write {:?}", journal_path))?;
+        task.progress(
+            outcomes.len() as u64 + 1,
+            Some(total),
+            Some(outcome.document_path.clone()),
+        );
+        task.partial_result(&outcome);
+        outcomes.insert(outcome.index, outcome);
+        Ok(())
+    };
+    task.progress(total - pending.len() as u64, Some(total), None);
+
+    match job.kind {
+        BatchJobKind::Sign => {
+            run_sign(app_handle, task, job, &pending, password, &mut record)?;
+            let failed = outcomes.values().filter(|o| o.failed()).count();
+            Ok(serde_json::to_value(BatchSignSummary {
+                signed: outcomes.len() - failed,
+                failed,
+            })?)
+        }
+        BatchJobKind::Verify => {
+            run_verify(app_handle, task, job, &pending, &mut record)?;
+            let invalid = outcomes.values().filter(|o| o.failed()).count();
+            Ok(serde_json::to_value(BatchVerifySummary {
+                valid: outcomes.len() - invalid,
+                invalid,
+            })?)
+        }
+    }
+}
+
+// 签名在线程池中计算；输出文件名在本线程中依次确定，避免自动编号冲突
+fn run_sign(
+    app_handle: &tauri::AppHandle,
+    task: &TaskHandle,
+    job: &BatchJob,
+    pending: &[usize],
+    password: &str,
+    record: &mut dyn FnMut(BatchItemOutcome) -> Result<()>,
+) -> Result<()> {
+    let key_id = job
+        .key_id
+        .ok_or_else(|| anyhow!("The signing job names no key"))?;
+    let output_dir = native_path(job.output_dir.as_deref().unwrap_or_default())?;
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let algorithm = metadata.algorithm.clone();
+    task.progress(
+        (job.items.len() - pending.len()) as u64,
+        Some(job.items.len() as u64),
+        Some("Unlocking key".into()),
+    );
+    let private_key_der = read_private_key_der(app_handle, &metadata, password)?;
+    fs::create_dir_all(&output_dir)
+        .with_context(|| format!("Failed to create output directory: {:?}", output_dir))?;
+
+    let sign = |&index: &usize| -> Result<(PathBuf, Vec<u8>)> {
+        let document_path = native_path(&job.items[index].document_path)?;
+        check_signable_input(app_handle, &document_path)?;
+        let bytes = read_file(&document_path)
+            .with_context(|| format!("Failed to read document file: {:?}", document_path))?;
+        let signature = sign_bytes(&algorithm, &private_key_der, &bytes, DigestScheme::Standard)?;
+        Ok((document_path, signature))
+    };
+    hash_in_pool(app_handle, pending, sign, |slot, signed: Result<_>| {
+        task.check_cancelled()?;
+        let index = pending[slot];
+        let outcome = signed.and_then(|(document_path, signature)| {
+            let signature_path =
+                signature_output_path(app_handle, &output_dir, &document_path, &algorithm)?;
+            task.output_started(&signature_path);
+            fs::write(&signature_path, signature)
+                .with_context(|| format!("Failed to write signature file: {:?}", signature_path))?;
+            task.output_written(&signature_path);
+            Ok(signature_path)
+        });
+        record(BatchItemOutcome {
+            index,
+            document_path: job.items[index].document_path.clone(),
+            signature_path: outcome.as_ref().ok().map(|path| path_string(path)),
+            is_valid: None,
+            signer_name: None,
+            error: outcome.err().map(|e| format!("{:#}", e)),
+        })
+    })
+}
+
+fn run_verify(
+    app_handle: &tauri::AppHandle,
+    task: &TaskHandle,
+    job: &BatchJob,
+    pending: &[usize],
+    record: &mut dyn FnMut(BatchItemOutcome) -> Result<()>,
+) -> Result<()> {
+    let candidates = signer_candidates(app_handle)?;
+    let trusted_tsas = trusted_tsa_certificates(app_handle)?;
+    let verify = |&index: &usize| {
+        let item = &job.items[index];
+        verify_against_candidates(
+            &native_path(&item.document_path)?,
+            &native_path(item.signature_path.as_deref().unwrap_or_default())?,
+            &candidates,
+            &trusted_tsas,
+            None,
+            true,
+        )
+    };
+    hash_in_pool(app_handle, pending, verify, |slot, report| {
+        task.check_cancelled()?;
+        let index = pending[slot];
+        let item = &job.items[index];
+        let (is_valid, signer_name, error) = match report {
+            Ok(report) => (
+                Some(report.result.is_valid),
+                report.signer_name,
+                report.result.error_message,
+            ),
+            Err(e) => (None, None, Some(format!("{:#}", e))),
+        };
+        record(BatchItemOutcome {
+            index,
+            document_path: item.document_path.clone(),
+            signature_path: item.signature_path.clone(),
+            is_valid,
+            signer_name,
+            error,
+        })
+    })
+}
+
+// 读取作业定义与日志；日志中无法解析的行（崩溃时写了一半）被忽略
+fn read_job(
+    app_handle: &tauri::AppHandle,
+    job_id: Uuid,
+) -> Result<(BatchJob, HashMap<usize, BatchItemOutcome>)> {
+    let (definition, journal) = job_paths(app_handle, job_id)?;
+    if !definition.exists() {
+        bail!("No batch job with ID {}", job_id);
+    }
+    let content = fs::read_to_string(&definition)
+        .with_context(|| format!("Failed to read batch job {:?}", definition))?;
+    let job: BatchJob = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse batch job {:?}", definition))?;
+    let outcomes = if journal.exists() {
+        let file = File::open(&journal).with_context(|| format!("Failed to open {:?}", journal))?;
+        parse_journal(BufReader::new(file), job.items.len())?
+    } else {
+        HashMap::new()
+    };
+    Ok((job, outcomes))
+}
+
+// 同一条目出现多次时以第一条为准
+fn parse_journal(
+    journal: impl BufRead,
+    item_count: usize,
+) -> Result<HashMap<usize, BatchItemOutcome>> {
+    let mut outcomes = HashMap::new();
+    for line in journal.lines() {
+        let Ok(outcome) = serde_json::from_str::<BatchItemOutcome>(&line?) else {
+            continue;
+        };
+        if outcome.index < item_count {
+            outcomes.entry(outcome.index).or_insert(outcome);
+        }
+    }
+    Ok(outcomes)
+}
+
+fn job_paths(app_handle: &tauri::AppHandle, job_id: Uuid) -> Result<(PathBuf, PathBuf)> {
+    let dir = jobs_dir(app_handle)?;
+    Ok((
+        dir.join(format!("{}.json", job_id)),
+        dir.join(format!("{}.jsonl", job_id)),
+    ))
+}
+
+fn jobs_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    let dir = get_app_data_file(app_handle, JOBS_DIRNAME)?;
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    Ok(dir)
+}
+
+fn remove_job_files(definition: &Path, journal: &Path) -> Result<()> {
+    if journal.exists() {
+        fs::remove_file(journal).with_context(|| format!("Failed to remove {:?}", journal))?;
+    }
+    fs::remove_file(definition).with_context(|| format!("Failed to remove {:?}", definition))
+}
+
+// 获取运行中作业表的锁
+fn lock_running() -> Result<MutexGuard<'static, BTreeMap<Uuid, Option<Uuid>>>> {
+    RUNNING_JOBS
+        .lock()
+        .map_err(|_| anyhow!("Batch job table lock poisoned"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn torn_journal_lines_are_processed_again() {
+        let outcome = BatchItemOutcome {
+            index: 1,
+            document_path: "/docs/b.pdf".into(),
+            signature_path: Some("/out/b.pdf.sig".into()),
+            is_valid: None,
+            signer_name: None,
+            error: None,
+        };
+        let line = serde_json::to_string(&outcome).unwrap();
+        let stale = serde_json::to_string(&BatchItemOutcome {
+            index: 7,
+            ..outcome.clone()
+        })
+        .unwrap();
+        let journal = format!("{}\n{}\n{}", line, stale, &line[..line.len() / 2]);
+        let parsed = parse_journal(journal.as_bytes(), 3).unwrap();
+        assert_eq!(parsed.keys().collect::<Vec<_>>(), vec![&1]);
+        assert!(!outcome.failed());
+        assert!(BatchItemOutcome {
+            is_valid: Some(false),
+            ..outcome
+        }
+        .failed());
+    }
+}
@@ -0,0 +1,173 @@
+// src-tauri/src/benchmarks.rs
+// Performance benchmark of the current machine: key generation, the key-wrapping KDFs with
+// the parameters new keys would get, and signing and verifying documents of a few sizes
+// with every algorithm. Each measurement repeats its operation until a small time budget
+// is used up, so the whole run takes a few seconds. Signing uses the fixed self-test keys
+// and the standard digest scheme; no user key is touched.
+use crate::crypto_types::{
+    BenchmarkOperation, BenchmarkReport, BenchmarkResult, DigestScheme, SignatureAlgorithm,
+};
+use crate::key_management::RSA_KEY_BITS;
+use crate::self_tests::known_answer_key_pair;
+use crate::settings::{
+    get_settings_path, read_settings, recommended_pbkdf2_iterations, time_argon2, time_pbkdf2,
+    MIN_ARGON2_ITERATIONS, MIN_ARGON2_MEMORY_KIB,
+};
+use crate::signing::{sign_bytes, verify_bytes};
+use aead::OsRng;
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use ml_dsa::KeyGen;
+use std::time::{Duration, Instant};
+
+/// Document sizes signed and verified.
+const DATA_SIZES: [usize; 3] = [1024, 1024 * 1024, 16 * 1024 * 1024];
+/// Time spent repeating one measurement (at least one run is always made).
+const TIME_BUDGET: Duration = Duration::from_millis(300);
+const MAX_RUNS: u32 = 1_000;
+const ALGORITHMS: [SignatureAlgorithm; 6] = [
+    SignatureAlgorithm::Ed25519,
+    SignatureAlgorithm::EcdsaP256Sha256,
+    SignatureAlgorithm::EcdsaP384Sha384,
+    SignatureAlgorithm::EcdsaP521Sha512,
+    SignatureAlgorithm::RsaPkcs1Sha256,
+    SignatureAlgorithm::MlDsa65,
+];
+
+// --- Tauri Commands ---
+
+/// Runs all benchmarks. Takes a few seconds, so it runs off the main thread.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn run_benchmarks(app_handle: tauri::AppHandle) -> Result<BenchmarkReport, String> {
+    log::info!("Running performance benchmarks");
+    _run_benchmarks(&app_handle).map_err(|e| {
+        log::error!("Failed to run benchmarks: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _run_benchmarks(app_handle: &tauri::AppHandle) -> Result<BenchmarkReport> {
+    let started = Instant::now();
+    let mut results = Vec::new();
+
+    for algorithm in &ALGORITHMS {
+        results.push(measure(
+            BenchmarkOperation::KeyGeneration,
+            algorithm.to_string(),
+            None,
+            || generate_key(algorithm),
+        )?);
+    }
+
+    let iterations = recommended_pbkdf2_iterations(app_handle);
+    results.push(measure(
+        BenchmarkOperation::Kdf,
+        format!("PBKDF2-SHA256, {} iterations", iterations),
+        None,
+        || {
+            time_pbkdf2(iterations);
+            Ok(())
+        },
+    )?);
+    let (memory_kib, passes) = read_settings(&get_settings_path(app_handle)?)?
+        .kdf_calibration
+        .map(|c| (c.argon2_memory_kib, c.argon2_iterations))
+        .unwrap_or((MIN_ARGON2_MEMORY_KIB, MIN_ARGON2_ITERATIONS));
+    results.push(measure(
+        BenchmarkOperation::Kdf,
+        format!("Argon2id, {} MiB, {} passes", memory_kib / 1024, passes),
+        None,
+        || time_argon2(memory_kib, passes).map(|_| ()),
+    )?);
+
+    for algorithm in &ALGORITHMS {
+        let (private_der, public_der) = known_answer_key_pair(algorithm)?;
+        for size in DATA_SIZES {
+            let data = vec![0x5a; size];
+            let signature = sign_bytes(algorithm, &private_der, &data, DigestScheme::Standard)?;
+            results.push(measure(
+                BenchmarkOperation::Sign,
+                algorithm.to_string(),
+                Some(size),
+                || sign_bytes(algorithm, &private_der, &data, DigestScheme::Standard).map(|_| ()),
+            )?);
+            results.push(measure(
+                BenchmarkOperation::Verify,
+                algorithm.to_string(),
+                Some(size),
+                || {
+                    verify_bytes(
+                        algorithm,
+                        &public_der,
+                        &data,
+                        &signature,
+                        DigestScheme::Standard,
+                    )?
+                    .map_err(|e| anyhow!("Benchmark signature rejected: {}", e))
+                },
+            )?);
+        }
+    }
+
+    log::info!("Benchmarks finished in {:?}", started.elapsed());
+    Ok(BenchmarkReport {
+        results,
+        threads: std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+        duration_ms: started.elapsed().as_millis() as u64,
+        ran_at: Utc::now(),
+    })
+}
+
+// --- Helper Functions ---
+
+// 重复执行直到用完时间预算，取平均耗时
+fn measure(
+    operation: BenchmarkOperation,
+    subject: String,
+    data_size: Option<usize>,
+    mut op: impl FnMut() -> Result<()>,
+) -> Result<BenchmarkResult> {
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || (start.elapsed() < TIME_BUDGET && runs < MAX_RUNS) {
+        op()?;
+        runs += 1;
+    }
+    let mean = start.elapsed().as_secs_f64() / runs as f64;
+    Ok(BenchmarkResult {
+        operation,
+        subject,
+        data_size: data_size.map(|size| size as u64),
+        runs,
+        mean_ms: mean * 1000.0,
+        ops_per_second: 1.0 / mean,
+        mib_per_second: data_size.map(|size| size as f64 / (1024.0 * 1024.0) / mean),
+    })
+}
+
+fn generate_key(algorithm: &SignatureAlgorithm) -> Result<()> {
+    match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => {
+            rsa::RsaPrivateKey::new(&mut OsRng, RSA_KEY_BITS)
+                .context("Failed to generate RSA private key")?;
+        }
+        SignatureAlgorithm::EcdsaP256Sha256 => {
+            p256::ecdsa::SigningKey::random(&mut OsRng);
+        }
+        SignatureAlgorithm::EcdsaP384Sha384 => {
+            p384::SecretKey::random(&mut OsRng);
+        }
+        SignatureAlgorithm::EcdsaP521Sha512 => {
+            p521::SecretKey::random(&mut OsRng);
+        }
+        SignatureAlgorithm::Ed25519 => {
+            ed25519_dalek::SigningKey::generate(&mut OsRng);
+        }
+        SignatureAlgorithm::MlDsa65 => {
+            ml_dsa::MlDsa65::key_gen(&mut OsRng);
+        }
+    }
+    Ok(())
+}
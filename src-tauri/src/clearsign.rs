@@ -0,0 +1,370 @@
+// src-tauri/src/clearsign.rs
+// Clearsigned text: a short statement wrapped with an inline signature block, laid out like
+// an OpenPGP cleartext signature (RFC 4880, section 7) so it survives being posted in an
+// e-mail or a forum and can be checked by pasting the whole block back in:
+//
+//   -----BEGIN SIGUST SIGNED MESSAGE-----
+//
+//   <text, lines starting with '-' escaped as "- -">
+//   -----BEGIN SIGUST SIGNATURE-----
+//
+//   <signature envelope JSON, base64>
+//   -----END SIGUST SIGNATURE-----
+//
+// The signature is an ordinary sigust envelope, not an OpenPGP signature.
+// As in OpenPGP, the digest is taken over the canonical text (trailing spaces and tabs
+// removed, CRLF line endings, no final line ending), so re-wrapped line endings and
+// trailing whitespace added by mail clients do not break the signature.
+use crate::audit;
+use crate::crypto_types::{ClearsignedVerification, KeyMetadata, TimeSource, VerificationResult};
+use crate::envelope::{
+    SignatureEnvelope, SignedAttributes, DIGEST_SHA256, ENVELOPE_FORMAT, ENVELOPE_VERSION,
+};
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, find_key_metadata, read_private_key_der,
+    read_public_key_pem,
+};
+use crate::policy::check_timestamping;
+use crate::signature_inspect::known_signer_name;
+use crate::signature_sequence::next_signature_sequence;
+use crate::signing::{check_envelope_attributes, resolve_envelope_signer};
+use crate::timestamp::trusted_tsa_certificates;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::Utc;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+const BEGIN_MESSAGE: &str = "-----BEGIN SIGUST SIGNED MESSAGE-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN SIGUST SIGNATURE-----";
+const END_SIGNATURE: &str = "-----END SIGUST SIGNATURE-----";
+/// Longest text that can be clearsigned, in bytes.
+const MAX_TEXT_SIZE: usize = 64 * 1024;
+/// Longest block accepted for verification, in bytes.
+const MAX_BLOCK_SIZE: usize = 256 * 1024;
+const ARMOR_LINE_LEN: usize = 64;
+
+// --- Tauri Commands ---
+
+/// Signs `text` with a local key and returns it wrapped in a clearsigned block.
+#[tauri::command(rename_all = "camelCase")]
+pub fn clearsign_text(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    password: String,
+    text: String,
+) -> Result<String, String> {
+    log::info!("Clearsigning text with key ID {}", key_id);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    check_timestamping(false).map_err(|e| e.to_string())?;
+    _clearsign_text(&app_handle, key_id, &password, &text)
+        .map_err(|e| {
+            log::error!("Failed to clearsign text: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|_| {
+            audit::record(
+                &app_handle,
+                "clearsignText",
+                json!({ "keyId": key_id, "digest": text_digest(&text) }),
+            )
+        })
+}
+
+/// Checks a pasted clearsigned block and returns the text it carries.
+#[tauri::command(rename_all = "camelCase")]
+pub fn verify_clearsigned_text(
+    app_handle: tauri::AppHandle,
+    block: String,
+) -> Result<ClearsignedVerification, String> {
+    log::info!("Verifying clearsigned text");
+    _verify_clearsigned_text(&app_handle, &block)
+        .map_err(|e| {
+            log::error!("Failed to verify clearsigned text: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|verification| {
+            audit::record(
+                &app_handle,
+                "verifyClearsignedText",
+                json!({
+                    "keyFingerprint": verification.key_fingerprint,
+                    "digest": verification.result.computed_digest,
+                    "valid": verification.result.is_valid,
+                }),
+            )
+        })
+}
+
+// --- Helper Functions ---
+
+fn _clearsign_text(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    password: &str,
+    text: &str,
+) -> Result<String> {
+    if text.trim().is_empty() {
+        bail!("The text is empty");
+    }
+    if text.len() > MAX_TEXT_SIZE {
+        bail!("Clearsigned text can be at most {} bytes", MAX_TEXT_SIZE);
+    }
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let public_key_der = decode_public_key_pem(&read_public_key_pem(app_handle, &metadata)?)?;
+    let private_key_der = Zeroizing::new(read_private_key_der(app_handle, &metadata, password)?);
+    clearsign_with_key(
+        app_handle,
+        &metadata,
+        &public_key_der,
+        &private_key_der,
+        text,
+    )
+}
+
+/// Clearsigns `text` with an already unlocked key.
+pub(crate) fn clearsign_with_key(
+    app_handle: &tauri::AppHandle,
+    metadata: &KeyMetadata,
+    public_key_der: &[u8],
+    private_key_der: &[u8],
+    text: &str,
+) -> Result<String> {
+    let key_id = metadata.key_id;
+    let attributes = SignedAttributes {
+        format: ENVELOPE_FORMAT.to_string(),
+        version: ENVELOPE_VERSION,
+        algorithm: metadata.algorithm.to_string(),
+        key_id,
+        key_fingerprint: compute_fingerprint(public_key_der),
+        digest_algorithm: DIGEST_SHA256.to_string(),
+        document_digest: text_digest(text),
+        signed_at: Utc::now(),
+        time_source: TimeSource::LocalClock,
+        time_token: None,
+        file_name: None,
+        file_size: None,
+        file_modified_at: None,
+        previous_envelope: None,
+        metadata: BTreeMap::new(),
+        approval: None,
+        sequence: Some(next_signature_sequence(app_handle, key_id)?),
+        oidc_identity: None,
+        pq_cosigner: None,
+    };
+    let envelope = SignatureEnvelope::seal(&attributes, private_key_der)?;
+    armor(text, &envelope)
+}
+
+fn _verify_clearsigned_text(
+    app_handle: &tauri::AppHandle,
+    block: &str,
+) -> Result<ClearsignedVerification> {
+    if block.len() > MAX_BLOCK_SIZE {
+        bail!("The block is too large (at most {} bytes)", MAX_BLOCK_SIZE);
+    }
+    let (text, envelope) = dearmor(block)?;
+    let attributes = envelope.attributes()?;
+    let mut report = VerificationResult {
+        digest_algorithm: Some(DIGEST_SHA256.to_string()),
+        computed_digest: Some(text_digest(&text)),
+        expected_digest: Some(attributes.document_digest.clone()),
+        signed_at: Some(attributes.signed_at),
+        signing_time_source: Some(attributes.time_source),
+        metadata: attributes.metadata.clone(),
+        sequence: attributes.sequence,
+        ..Default::default()
+    };
+
+    // 签名者必须是本地密钥或联系人；未知签名者报告为无效而不是报错
+    let trusted_tsas = trusted_tsa_certificates(app_handle)?;
+    let error_message = match resolve_envelope_signer(app_handle, &attributes) {
+        Err(e) => Some(e.to_string()),
+        Ok((algorithm, public_key_der)) => match check_envelope_attributes(
+            &attributes,
+            &algorithm,
+            &public_key_der,
+            report.computed_digest.as_deref().unwrap_or_default(),
+        )? {
+            Some(message) => Some(message),
+            None => envelope
+                .verify_signature(&algorithm, &public_key_der)?
+                .err()
+                .map(|e| format!("Signature is invalid: {}", e))
+                .or_else(
+                    || match attributes.check_signing_time(None, &trusted_tsas) {
+                        Ok(time_source) => {
+                            report.signing_time_source = Some(time_source);
+                            None
+                        }
+                        Err(e) => Some(format!("Signing time does not verify: {:#}", e)),
+                    },
+                ),
+        },
+    };
+    if let Some(message) = &error_message {
+        log::warn!("Verification failed for clearsigned text: {}", message);
+    }
+    Ok(ClearsignedVerification {
+        text,
+        signer_name: known_signer_name(app_handle, &attributes),
+        key_fingerprint: attributes.key_fingerprint,
+        result: VerificationResult {
+            is_valid: error_message.is_none(),
+            error_message,
+            ..report
+        },
+    })
+}
+
+// 规范文本的 SHA-256：去除行尾空格与制表符，CRLF 换行，末尾不带换行
+fn text_digest(text: &str) -> String {
+    let canonical = text
+        .lines()
+        .map(|line| line.trim_end_matches([' ', '\t']))
+        .collect::<Vec<_>>()
+        .join("\r\n");
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+fn armor(text: &str, envelope: &SignatureEnvelope) -> Result<String> {
+    let envelope_json = serde_json::to_vec(envelope).context("Failed to serialize envelope")?;
+    let encoded = BASE64.encode(envelope_json);
+    let mut block = format!("{}\n\n", BEGIN_MESSAGE);
+    for line in text.lines() {
+        // 以 '-' 开头的行转义为 "- -"，避免与分隔行混淆
+        if line.starts_with('-') {
+            block.push_str("- ");
+        }
+        block.push_str(line);
+        block.push('\n');
+    }
+    block.push_str(BEGIN_SIGNATURE);
+    block.push_str("\n\n");
+    for chunk in encoded.as_bytes().chunks(ARMOR_LINE_LEN) {
+        block.push_str(std::str::from_utf8(chunk)?);
+        block.push('\n');
+    }
+    block.push_str(END_SIGNATURE);
+    block.push('\n');
+    Ok(block)
+}
+
+// 解析粘贴的块：块前后的内容被忽略，正文按行反转义
+fn dearmor(block: &str) -> Result<(String, SignatureEnvelope)> {
+    let mut lines = block
+        .lines()
+        .map(|line| line.trim_end_matches(['\r', ' ', '\t']));
+    lines
+        .by_ref()
+        .find(|line| line.trim() == BEGIN_MESSAGE)
+        .ok_or_else(|| anyhow!("No clearsigned block found"))?;
+    // 跳过头部行，直到第一个空行
+    for line in lines.by_ref() {
+        if line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut text_lines = Vec::new();
+    let mut found_signature = false;
+    for line in lines.by_ref() {
+        if line == BEGIN_SIGNATURE {
+            found_signature = true;
+            break;
+        }
+        match line.strip_prefix("- ") {
+            Some(unescaped) => text_lines.push(unescaped),
+            None if line.starts_with('-') => bail!("Malformed clearsigned text: {}", line),
+            None => text_lines.push(line),
+        }
+    }
+    if !found_signature {
+        bail!("The clearsigned block has no signature");
+    }
+
+    let mut encoded = String::new();
+    let mut found_end = false;
+    for line in lines {
+        let line = line.trim();
+        if line == END_SIGNATURE {
+            found_end = true;
+            break;
+        }
+        if line.contains(": ") {
+            continue;
+        }
+        encoded.push_str(line);
+    }
+    if !found_end {
+        bail!("The clearsigned block is incomplete");
+    }
+    let envelope_json = BASE64
+        .decode(encoded)
+        .context("Malformed signature in the clearsigned block")?;
+    let envelope = SignatureEnvelope::parse(&envelope_json)?
+        .ok_or_else(|| anyhow!("The clearsigned block does not carry a sigust signature"))?;
+    Ok((text_lines.join("\n"), envelope))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_types::SignatureAlgorithm;
+    use ed25519_dalek::SigningKey;
+    use pkcs8::{EncodePrivateKey, EncodePublicKey};
+    use rsa::rand_core::OsRng;
+
+    #[test]
+    fn armors_and_reads_back_text() {
+        let key = SigningKey::generate(&mut OsRng);
+        let private_key_der = key.to_pkcs8_der().unwrap().to_bytes().to_vec();
+        let public_key_der = key.verifying_key().to_public_key_der().unwrap().into_vec();
+        let text = "I approve PO #123.\n-- Alice\n\n- item one";
+        let attributes = SignedAttributes {
+            format: ENVELOPE_FORMAT.to_string(),
+            version: ENVELOPE_VERSION,
+            algorithm: SignatureAlgorithm::Ed25519.to_string(),
+            key_id: Uuid::new_v4(),
+            key_fingerprint: compute_fingerprint(&public_key_der),
+            digest_algorithm: DIGEST_SHA256.to_string(),
+            document_digest: text_digest(text),
+            signed_at: Utc::now(),
+            time_source: TimeSource::LocalClock,
+            time_token: None,
+            file_name: None,
+            file_size: None,
+            file_modified_at: None,
+            previous_envelope: None,
+            metadata: BTreeMap::new(),
+            approval: None,
+            sequence: None,
+            oidc_identity: None,
+            pq_cosigner: None,
+        };
+        let envelope = SignatureEnvelope::seal(&attributes, &private_key_der).unwrap();
+        let block = armor(text, &envelope).unwrap();
+        assert!(block.contains("\n- -- Alice\n"));
+
+        // 邮件客户端加入的 CRLF、行尾空白和块外内容不影响结果
+        let pasted = format!("Forwarded:\r\n{}\r\nThanks", block.replace('\n', "  \r\n"));
+        let (read_text, read_envelope) = dearmor(&pasted).unwrap();
+        assert_eq!(text_digest(&read_text), attributes.document_digest);
+        assert_eq!(read_text.lines().nth(1), Some("-- Alice"));
+        assert!(read_envelope
+            .verify_signature(&SignatureAlgorithm::Ed25519, &public_key_der)
+            .unwrap()
+            .is_ok());
+
+        let tampered = block.replace("PO #123", "PO #124");
+        let (tampered_text, _) = dearmor(&tampered).unwrap();
+        assert_ne!(text_digest(&tampered_text), attributes.document_digest);
+        assert!(dearmor("no block here").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+// src-tauri/src/completion_certificate.rs
+// Certificates of signing: an optional human-readable summary written next to a signature
+// (`<signature>.certificate.txt`) saying who signed which document and when, with the key
+// fingerprint and how to check the signature. The certificate is itself clearsigned with
+// the same key (see clearsign.rs), so pasting it into sigust shows whether it was altered.
+// It is a convenience for people receiving the document; the signature file remains the
+// evidence.
+use crate::clearsign::clearsign_with_key;
+use crate::crypto_types::{KeyMetadata, SignatureFormat, TimeSource};
+use crate::key_management::compute_fingerprint;
+use crate::output_paths::resolve_output_path;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CERTIFICATE_SUFFIX: &str = ".certificate.txt";
+
+/// What a certificate of signing states about one signature.
+pub(crate) struct SigningRecord<'a> {
+    pub document_path: &'a Path,
+    pub document_size: u64,
+    /// SHA-256 of the document, lowercase hex.
+    pub document_digest: &'a str,
+    pub signature_path: &'a Path,
+    pub format: SignatureFormat,
+    pub signed_at: DateTime<Utc>,
+    pub time_source: TimeSource,
+}
+
+// --- Helper Functions ---
+
+/// Writes the clearsigned certificate for `record` and returns its path.
+pub(crate) fn write_completion_certificate(
+    app_handle: &tauri::AppHandle,
+    metadata: &KeyMetadata,
+    public_key_der: &[u8],
+    private_key_der: &[u8],
+    record: &SigningRecord,
+) -> Result<PathBuf> {
+    let text = certificate_text(metadata, &compute_fingerprint(public_key_der), record);
+    let block = clearsign_with_key(app_handle, metadata, public_key_der, private_key_der, &text)?;
+    let mut requested = record.signature_path.as_os_str().to_owned();
+    requested.push(CERTIFICATE_SUFFIX);
+    let path = resolve_output_path(app_handle, Path::new(&requested))?;
+    fs::write(&path, block)
+        .with_context(|| format!("Failed to write certificate of signing: {:?}", path))?;
+    log::info!("Certificate of signing saved to {:?}", path);
+    Ok(path)
+}
+
+fn certificate_text(metadata: &KeyMetadata, fingerprint: &str, record: &SigningRecord) -> String {
+    let file_name = |path: &Path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string_lossy().into_owned())
+    };
+    let document = file_name(record.document_path);
+    let signature = file_name(record.signature_path);
+
+    let mut signer = metadata.name.clone();
+    if let Some(identity) = metadata.identity.as_ref().filter(|i| !i.is_empty()) {
+        let details: Vec<&str> = [&identity.full_name, &identity.email, &identity.organization]
+            .into_iter()
+            .filter_map(|field| field.as_deref())
+            .collect();
+        signer = format!("{} ({})", details.join(", "), metadata.name);
+    }
+    let (format, verify_hint) = match record.format {
+        SignatureFormat::Envelope => (
+            "sigust signature envelope",
+            "open sigust, choose Verify, and select both files. sigust finds the signer \
+             among your keys and contacts.",
+        ),
+        SignatureFormat::Hybrid => (
+            "sigust hybrid signature envelope (classical and post-quantum)",
+            "open sigust, choose Verify, and select both files. Both signatures must be \
+             valid.",
+        ),
+        SignatureFormat::Detached => (
+            "detached signature",
+            "open sigust, choose Verify, select both files and the signer's public key.",
+        ),
+    };
+    let time_source = match record.time_source {
+        TimeSource::LocalClock => "as stated by the signer's clock",
+        TimeSource::Tsa => "confirmed by a time-stamping authority",
+    };
+
+    format!(
+        "CERTIFICATE OF SIGNING\n\
+         \n\
+         Document:         {document}\n\
+         Size:             {size} bytes\n\
+         SHA-256:          {digest}\n\
+         Signature file:   {signature} ({format})\n\
+         Signed by:        {signer}\n\
+         Key algorithm:    {algorithm}\n\
+         Key fingerprint:  {fingerprint}\n\
+         Signed at:        {signed_at} ({time_source})\n\
+         \n\
+         HOW TO VERIFY\n\
+         \n\
+         1. Confirm the key fingerprint above with the signer through a channel you trust,\n\
+         \x20  e.g. in person or by phone.\n\
+         2. Check that your copy of {document} has the SHA-256 digest above\n\
+         \x20  (for example: sha256sum \"{document}\").\n\
+         3. To check the signature, {verify_hint}\n\
+         4. This certificate is signed with the same key. To check that it was not altered,\n\
+         \x20  paste this whole text into sigust's clearsigned text verification.\n",
+        size = record.document_size,
+        digest = record.document_digest,
+        algorithm = metadata.algorithm,
+        signed_at = record.signed_at.format("%Y-%m-%d %H:%M:%S UTC"),
+    )
+}
@@ -0,0 +1,813 @@
+// src-tauri/src/compliance_report.rs
+// Verification reports for legal records. A signature is verified the way
+// `verify_signatures` does it (against own keys, passkeys and contacts) and the outcome is
+// written as a printable file: document digest, signer and how far they are trusted,
+// algorithm, signing time and time-stamps, embedded certificates, and how the signature
+// fares against the enterprise policy.
+//
+// Two formats are offered. HTML is a single self-contained page. The PDF is written
+// directly (PDF 1.4, A4, the standard Helvetica fonts), so no font files are needed; the
+// standard fonts only cover WinAnsi (Latin-1) text, other characters are printed as '?',
+// so the HTML report is the better choice for non-Latin names.
+use crate::audit;
+use crate::contacts::{get_contacts_path, read_contacts};
+use crate::crypto_types::{
+    FileKind, ReportFormat, RevocationStatus, SignatureAlgorithm, SignatureInspection, TimeSource,
+    TrustLevel,
+};
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::resolve_output_path;
+use crate::policy::current_status;
+use crate::signature_inspect::inspect_signature_file;
+use crate::signing::{signer_candidates, verify_against_candidates};
+use crate::timestamp::trusted_tsa_certificates;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::traits::PublicKeyParts;
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+pub(crate) const MARGIN: f32 = 50.0;
+/// Characters per wrapped line at the body font size (Helvetica averages about half an em).
+pub(crate) const WRAP_COLUMNS: usize = 95;
+
+/// The report content, shared by both renderers.
+struct Report {
+    generated_at: DateTime<Utc>,
+    valid: bool,
+    sections: Vec<Section>,
+}
+
+struct Section {
+    title: &'static str,
+    rows: Vec<(String, String)>,
+}
+
+// --- Tauri Commands ---
+
+/// Verifies `signature_path` over `document_path` and writes the report to `output_path`.
+/// Returns the path written, which may differ from the requested one (see output settings).
+#[tauri::command(rename_all = "camelCase")]
+pub async fn generate_verification_report(
+    app_handle: tauri::AppHandle,
+    document_path: String,
+    signature_path: String,
+    output_path: String,
+    format: ReportFormat,
+) -> Result<String, String> {
+    log::info!(
+        "Generating {:?} verification report for '{}' and '{}'",
+        format,
+        document_path,
+        signature_path
+    );
+    _generate_verification_report(
+        &app_handle,
+        &document_path,
+        &signature_path,
+        &output_path,
+        format,
+    )
+    .map_err(|e| {
+        log::error!("Failed to generate verification report: {:?}", e);
+        e.to_string()
+    })
+    .map(|(path, is_valid)| {
+        let path = path_string(&path);
+        audit::record(
+            &app_handle,
+            "generateVerificationReport",
+            json!({
+                "documentPath": document_path,
+                "signaturePath": signature_path,
+                "outputPath": path,
+                "format": format,
+                "isValid": is_valid,
+            }),
+        );
+        path
+    })
+}
+
+// --- Helper Functions ---
+
+fn _generate_verification_report(
+    app_handle: &tauri::AppHandle,
+    document_path: &str,
+    signature_path: &str,
+    output_path: &str,
+    format: ReportFormat,
+) -> Result<(PathBuf, bool)> {
+    let report = build_report(app_handle, document_path, signature_path)?;
+    let content = match format {
+        ReportFormat::Html => render_html(&report).into_bytes(),
+        ReportFormat::Pdf => render_pdf(&report),
+    };
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write verification report: {:?}", output_path))?;
+    Ok((output_path, report.valid))
+}
+
+fn build_report(
+    app_handle: &tauri::AppHandle,
+    document_path: &str,
+    signature_path: &str,
+) -> Result<Report> {
+    let candidates = signer_candidates(app_handle)?;
+    let trusted_tsas = trusted_tsa_certificates(app_handle)?;
+    let (document_file, signature_file) =
+        (native_path(document_path)?, native_path(signature_path)?);
+    let verification = verify_against_candidates(
+        &document_file,
+        &signature_file,
+        &candidates,
+        &trusted_tsas,
+        None,
+        true,
+    )?;
+    // 无法解析的签名文件仍然出报告，只是缺少描述信息
+    let inspection = inspect_signature_file(app_handle, signature_path).unwrap_or_else(|e| {
+        log::warn!("Failed to inspect signature for report: {:?}", e);
+        SignatureInspection {
+            path: signature_path.to_string(),
+            ..Default::default()
+        }
+    });
+    let result = &verification.result;
+    let signer = verification
+        .signer_fingerprint
+        .as_ref()
+        .and_then(|fingerprint| candidates.iter().find(|c| c.fingerprint == *fingerprint));
+
+    let mut outcome = vec![(
+        "Result".to_string(),
+        if result.is_valid {
+            "Valid"
+        } else {
+            "Not valid"
+        }
+        .to_string(),
+    )];
+    push_optional(&mut outcome, "Reason", result.error_message.clone());
+    for mismatch in &result.binding_mismatches {
+        outcome.push((
+            format!("Bound {} differs", mismatch.field),
+            format!("signed {}, file has {}", mismatch.expected, mismatch.actual),
+        ));
+    }
+
+    let mut document = vec![("Path".to_string(), document_path.to_string())];
+    if let Ok(metadata) = fs::metadata(&document_file) {
+        document.push(("Size".to_string(), format!("{} bytes", metadata.len())));
+    }
+    push_optional(
+        &mut document,
+        "Digest algorithm",
+        result.digest_algorithm.clone(),
+    );
+    push_optional(
+        &mut document,
+        "Computed digest",
+        result.computed_digest.clone(),
+    );
+    push_optional(
+        &mut document,
+        "Signed digest",
+        result.expected_digest.clone(),
+    );
+    push_optional(
+        &mut document,
+        "Signed file name",
+        inspection.document_name.clone(),
+    );
+
+    let mut signature = vec![
+        ("Path".to_string(), signature_path.to_string()),
+        ("Type".to_string(), kind_label(inspection.kind).to_string()),
+    ];
+    push_optional(&mut signature, "Format", inspection.format.clone());
+    push_optional(
+        &mut signature,
+        "Algorithm",
+        inspection
+            .algorithm
+            .clone()
+            .or_else(|| signer.map(|s| s.algorithm.to_string())),
+    );
+    push_optional(
+        &mut signature,
+        "Digest algorithm",
+        inspection.digest_algorithm.clone(),
+    );
+    if let Some(signed_at) = result.signed_at.or(inspection.signed_at) {
+        let source = match result.signing_time_source.or(inspection.time_source) {
+            Some(TimeSource::Tsa) => " (time-stamping authority)",
+            Some(TimeSource::LocalClock) => " (signer's clock)",
+            None => "",
+        };
+        signature.push(("Signed at".to_string(), format!("{}{}", signed_at, source)));
+    }
+    push_optional(
+        &mut signature,
+        "Sequence number",
+        result
+            .sequence
+            .or(inspection.sequence)
+            .map(|s| s.to_string()),
+    );
+    for (name, value) in &result.metadata {
+        signature.push((format!("Metadata: {}", name), value.clone()));
+    }
+    if let Some(approval) = &result.approval {
+        signature.push((
+            "Approved by".to_string(),
+            format!(
+                "{} (key {}) at {}",
+                approval.approver_name, approval.fingerprint, approval.approved_at
+            ),
+        ));
+    }
+
+    let mut signer_rows = Vec::new();
+    push_optional(
+        &mut signer_rows,
+        "Name",
+        verification
+            .signer_name
+            .clone()
+            .or(inspection.signer_name.clone()),
+    );
+    push_optional(
+        &mut signer_rows,
+        "Key fingerprint",
+        verification
+            .signer_fingerprint
+            .clone()
+            .or(inspection.signer_fingerprint.clone()),
+    );
+    push_optional(&mut signer_rows, "Issuer", inspection.issuer.clone());
+    signer_rows.push((
+        "Trust".to_string(),
+        trust_description(
+            app_handle,
+            verification.signer_fingerprint.as_deref(),
+            signer.is_some(),
+        )?,
+    ));
+
+    let mut timestamps = Vec::new();
+    for timestamp in &result.timestamps {
+        timestamps.push((
+            "Authority".to_string(),
+            timestamp
+                .tsa_name
+                .clone()
+                .unwrap_or_else(|| timestamp.tsa_url.clone()),
+        ));
+        timestamps.push(("Time".to_string(), timestamp.gen_time.to_string()));
+        push_optional(
+            &mut timestamps,
+            "Authority certificate valid until",
+            timestamp.certificate_not_after.map(|t| t.to_string()),
+        );
+        push_optional(
+            &mut timestamps,
+            "Authority certificate revocation",
+            timestamp.revocation_status.map(|status| {
+                match status {
+                    RevocationStatus::Unknown => "Unknown (no revocation list in the time-stamp)",
+                    RevocationStatus::NotRevoked => "Not revoked",
+                }
+                .to_string()
+            }),
+        );
+        timestamps.push((
+            "Status".to_string(),
+            match &timestamp.error_message {
+                None if timestamp.is_valid => "Valid".to_string(),
+                None => "Not valid".to_string(),
+                Some(error) => format!("Not valid: {}", error),
+            },
+        ));
+    }
+    // 非信封签名没有校验结果，只列出其中的时间戳
+    if result.timestamps.is_empty() {
+        for timestamp in &inspection.timestamps {
+            timestamps.push((
+                "Authority".to_string(),
+                timestamp
+                    .tsa_name
+                    .clone()
+                    .or(timestamp.tsa_url.clone())
+                    .unwrap_or_else(|| "Unknown".to_string()),
+            ));
+            push_optional(
+                &mut timestamps,
+                "Time (not checked)",
+                timestamp.gen_time.map(|t| t.to_string()),
+            );
+        }
+    }
+
+    let mut certificates = Vec::new();
+    for certificate in &inspection.certificates {
+        certificates.push(("Subject".to_string(), certificate.subject.clone()));
+        certificates.push(("Issuer".to_string(), certificate.issuer.clone()));
+        certificates.push((
+            "Serial number".to_string(),
+            certificate.serial_number.clone(),
+        ));
+        certificates.push((
+            "Valid".to_string(),
+            format!("{} to {}", certificate.not_before, certificate.not_after),
+        ));
+    }
+
+    let algorithm = signer.map(|s| s.algorithm.clone());
+    let rsa_bits = match (&algorithm, signer) {
+        (Some(SignatureAlgorithm::RsaPkcs1Sha256), Some(signer)) => {
+            rsa::RsaPublicKey::from_public_key_der(&signer.public_key_der)
+                .ok()
+                .map(|key| key.size() * 8)
+        }
+        _ => None,
+    };
+    let timestamped = result.timestamps.iter().any(|t| t.is_valid)
+        || (result.is_valid && result.signing_time_source == Some(TimeSource::Tsa));
+    let policy = policy_rows(algorithm.as_ref(), rsa_bits, timestamped)?;
+
+    let mut sections = vec![
+        Section {
+            title: "Outcome",
+            rows: outcome,
+        },
+        Section {
+            title: "Document",
+            rows: document,
+        },
+        Section {
+            title: "Signature",
+            rows: signature,
+        },
+        Section {
+            title: "Signer",
+            rows: signer_rows,
+        },
+    ];
+    if !timestamps.is_empty() {
+        sections.push(Section {
+            title: "Time-stamps",
+            rows: timestamps,
+        });
+    }
+    if !certificates.is_empty() {
+        sections.push(Section {
+            title: "Certificates",
+            rows: certificates,
+        });
+    }
+    sections.push(Section {
+        title: "Enterprise policy",
+        rows: policy,
+    });
+    Ok(Report {
+        generated_at: Utc::now(),
+        valid: result.is_valid,
+        sections,
+    })
+}
+
+fn push_optional(rows: &mut Vec<(String, String)>, label: &str, value: Option<String>) {
+    if let Some(value) = value {
+        rows.push((label.to_string(), value));
+    }
+}
+
+fn kind_label(kind: FileKind) -> &'static str {
+    match kind {
+        FileKind::SigustSignature => "sigust signature",
+        FileKind::CmsSignature => "CMS / PKCS#7 signature",
+        FileKind::PgpSignature => "OpenPGP signature",
+        FileKind::MinisignSignature => "minisign signature",
+        FileKind::Document | FileKind::PublicKey | FileKind::BackupArchive => "Unrecognized",
+    }
+}
+
+// 签名者的信任来源：联系人的信任级别，或本地密钥
+fn trust_description(
+    app_handle: &tauri::AppHandle,
+    fingerprint: Option<&str>,
+    known: bool,
+) -> Result<String> {
+    let Some(fingerprint) = fingerprint else {
+        return Ok("Unknown signer".to_string());
+    };
+    let contacts = read_contacts(&get_contacts_path(app_handle)?)?;
+    Ok(
+        match contacts.iter().find(|c| c.fingerprint == fingerprint) {
+            Some(contact) => match contact.trust {
+                TrustLevel::Verified => "Contact, fingerprint verified with the owner",
+                TrustLevel::Unverified => "Contact, fingerprint not verified",
+                TrustLevel::Distrusted => "Contact marked as distrusted",
+            },
+            None if known => "Own key",
+            None => "Unknown signer (not a contact or own key)",
+        }
+        .to_string(),
+    )
+}
+
+// 按企业策略逐条评估签名
+fn policy_rows(
+    algorithm: Option<&SignatureAlgorithm>,
+    rsa_bits: Option<usize>,
+    timestamped: bool,
+) -> Result<Vec<(String, String)>> {
+    let status = current_status()?;
+    if !status.managed {
+        return Ok(vec![("Policy".to_string(), "None installed".to_string())]);
+    }
+    if let Some(error) = status.error {
+        return Ok(vec![(
+            "Policy".to_string(),
+            format!("Installed but could not be loaded: {}", error),
+        )]);
+    }
+    let mut rows = Vec::new();
+    push_optional(&mut rows, "Organization", status.organization);
+    push_optional(
+        &mut rows,
+        "Issued at",
+        status.issued_at.map(|t| t.to_string()),
+    );
+    push_optional(&mut rows, "Policy signer", status.signer_fingerprint);
+    let Some(rules) = status.rules else {
+        return Ok(rows);
+    };
+
+    let mut compliant = true;
+    let mut check = |rule: String, passed: Option<bool>| {
+        let outcome = match passed {
+            Some(true) => "Met",
+            Some(false) => {
+                compliant = false;
+                "Not met"
+            }
+            None => "Not evaluated (signer key unknown)",
+        };
+        rows.push((rule, outcome.to_string()));
+    };
+    if let Some(allowed) = &rules.allowed_algorithms {
+        check(
+            format!("Algorithm one of {}", allowed.join(", ")),
+            algorithm.map(|a| allowed.contains(&a.to_string())),
+        );
+    }
+    if let Some(min_bits) = rules.min_rsa_key_bits {
+        if algorithm.is_none() || rsa_bits.is_some() {
+            check(
+                format!("RSA keys of at least {} bits", min_bits),
+                rsa_bits.map(|bits| bits >= min_bits),
+            );
+        }
+    }
+    if rules.require_timestamp {
+        check("Valid RFC 3161 time-stamp".to_string(), Some(timestamped));
+    }
+    rows.push((
+        "Overall".to_string(),
+        if compliant {
+            "Meets the policy"
+        } else {
+            "Does not meet the policy"
+        }
+        .to_string(),
+    ));
+    Ok(rows)
+}
+
+fn render_html(report: &Report) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Signature verification report</title>\n<style>\n\
+         body { font-family: Helvetica, Arial, sans-serif; font-size: 10pt; margin: 2em; color: #111; }\n\
+         h1 { font-size: 16pt; margin-bottom: 0.2em; }\n\
+         h2 { font-size: 12pt; margin-top: 1.5em; border-bottom: 1px solid #999; }\n\
+         table { border-collapse: collapse; width: 100%; }\n\
+         th { text-align: left; vertical-align: top; width: 30%; padding: 2px 8px 2px 0; }\n\
+         td { word-break: break-all; padding: 2px 0; }\n\
+         .valid { color: #060; } .invalid { color: #a00; }\n\
+         @media print { body { margin: 0; } }\n</style>\n</head>\n<body>\n",
+    );
+    html.push_str("<h1>Signature verification report</h1>\n");
+    html.push_str(&format!(
+        "<p>Generated {} by sigust {}. <strong class=\"{}\">{}</strong></p>\n",
+        report.generated_at,
+        env!("CARGO_PKG_VERSION"),
+        if report.valid { "valid" } else { "invalid" },
+        if report.valid {
+            "The signature is valid."
+        } else {
+            "The signature is not valid."
+        }
+    ));
+    for section in &report.sections {
+        html.push_str(&format!("<h2>{}</h2>\n<table>\n", section.title));
+        for (label, value) in &section.rows {
+            html.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>\n",
+                escape_html(label),
+                escape_html(value)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Lays text out on A4 pages, one content stream per page.
+pub(crate) struct PdfPages {
+    pages: Vec<String>,
+    y: f32,
+}
+
+impl PdfPages {
+    pub(crate) fn new() -> Self {
+        PdfPages {
+            pages: vec![String::new()],
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    // 在当前页写一行（粗体标签可选），空间不足时换页
+    pub(crate) fn line(&mut self, x: f32, size: f32, runs: &[(bool, &str)]) {
+        let leading = size * 1.4;
+        if self.y - leading < MARGIN + 20.0 {
+            self.pages.push(String::new());
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.y -= leading;
+        let page = self.pages.last_mut().expect("at least one page");
+        page.push_str(&format!("BT {:.1} {:.1} Td ", x, self.y));
+        for (bold, text) in runs {
+            page.push_str(&format!(
+                "/{} {:.1} Tf ({}) Tj ",
+                if *bold { "F2" } else { "F1" },
+                size,
+                pdf_string(text)
+            ));
+        }
+        page.push_str("ET\n");
+    }
+
+    pub(crate) fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// Reserves `height` points on the current page (starting a new page if they do not
+    /// fit) and returns the bottom edge of the reserved area, for use with `draw`.
+    pub(crate) fn reserve(&mut self, height: f32) -> f32 {
+        if self.y - height < MARGIN + 20.0 {
+            self.pages.push(String::new());
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.y -= height;
+        self.y
+    }
+
+    /// Appends raw content stream operators to the current page.
+    pub(crate) fn draw(&mut self, operators: &str) {
+        let page = self.pages.last_mut().expect("at least one page");
+        page.push_str(operators);
+    }
+
+    /// Adds page numbers and assembles the PDF file.
+    pub(crate) fn finish(mut self, title: &str, created_at: DateTime<Utc>) -> Vec<u8> {
+        let page_count = self.pages.len();
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            page.push_str(&format!(
+                "BT {:.1} {:.1} Td /F1 8.0 Tf ({}) Tj ET\n",
+                MARGIN,
+                MARGIN - 20.0,
+                pdf_string(&format!("Page {} of {}", index + 1, page_count))
+            ));
+        }
+
+        // 对象编号：1 目录，2 页面树，3/4 字体，5 文档信息，之后每页两个对象（页面、内容流）
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                (0..page_count)
+                    .map(|i| format!("{} 0 R", 6 + 2 * i))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                page_count
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+            format!(
+                "<< /Title ({}) /Producer (sigust {}) /CreationDate (D:{}Z) >>",
+                pdf_string(title),
+                env!("CARGO_PKG_VERSION"),
+                created_at.format("%Y%m%d%H%M%S")
+            ),
+        ];
+        for (index, page) in self.pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                7 + 2 * index
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                page.len(),
+                page
+            ));
+        }
+
+        // 内容全部为 ASCII（见 pdf_string），无需二进制注释行
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).as_bytes());
+        }
+        let xref_offset = pdf.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            trailer.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        trailer.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        ));
+        pdf.extend_from_slice(trailer.as_bytes());
+        pdf
+    }
+}
+
+fn render_pdf(report: &Report) -> Vec<u8> {
+    let mut layout = PdfPages::new();
+    layout.line(MARGIN, 16.0, &[(true, "Signature verification report")]);
+    let summary = format!(
+        "Generated {} by sigust {}. The signature is {}.",
+        report.generated_at,
+        env!("CARGO_PKG_VERSION"),
+        if report.valid { "valid" } else { "not valid" }
+    );
+    layout.line(MARGIN, 9.0, &[(false, &summary)]);
+    for section in &report.sections {
+        layout.gap(8.0);
+        layout.line(MARGIN, 12.0, &[(true, section.title)]);
+        for (label, value) in &section.rows {
+            let label = format!("{}: ", label);
+            let width = WRAP_COLUMNS.saturating_sub(label.chars().count()).max(30);
+            let lines = wrap(value, width);
+            layout.line(MARGIN, 9.0, &[(true, &label), (false, &lines[0])]);
+            for line in &lines[1..] {
+                layout.line(MARGIN + 20.0, 9.0, &[(false, line)]);
+            }
+        }
+    }
+
+    layout.finish("Signature verification report", report.generated_at)
+}
+
+// 按单词折行，超长的单词（如摘要）硬断开
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        if !current.is_empty() && current.chars().count() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        while word.len() > width {
+            let rest = word.split_off(width);
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.extend(word);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+// PDF 字符串：WinAnsi 编码（Latin-1 加常用标点），其他字符替换为 '?'，并转义括号与反斜杠。
+// 结果为 ASCII：非 ASCII 字节写成八进制转义，内容流长度即字节数
+fn pdf_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        let byte = match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+                continue;
+            }
+            ' '..='~' => {
+                out.push(c);
+                continue;
+            }
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '€' => 0x80,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            _ => b'?',
+        };
+        if byte.is_ascii() {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("\\{:03o}", byte));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_html_and_pdf() {
+        let report = Report {
+            generated_at: Utc::now(),
+            valid: false,
+            sections: vec![Section {
+                title: "Outcome",
+                rows: vec![
+                    ("Result".to_string(), "Not valid".to_string()),
+                    (
+                        "Reason".to_string(),
+                        "<digest> (ünchanged) 日本".to_string(),
+                    ),
+                    ("Digest".to_string(), "ab".repeat(64)),
+                ],
+            }],
+        };
+        let html = render_html(&report);
+        assert!(html.contains("&lt;digest&gt; (ünchanged) 日本"));
+
+        let pdf = String::from_utf8(render_pdf(&report)).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        // Latin-1 字符写成八进制转义，其他字符替换为 '?'
+        assert!(pdf.contains("(<digest> \\(\\374nchanged\\) ??) Tj"));
+        assert!(pdf.contains("(Page 1 of 1) Tj"));
+
+        // xref 中记录的偏移量指向各对象的起始位置
+        let xref = pdf.find("\nxref\n").unwrap() + 1;
+        let startxref = pdf.rsplit("startxref\n").next().unwrap();
+        assert_eq!(
+            startxref.lines().next().unwrap().parse::<usize>().unwrap(),
+            xref
+        );
+        let entries = pdf[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "));
+        for (index, line) in entries.enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", index + 1)));
+        }
+
+        assert_eq!(wrap(&"x".repeat(70), 30).len(), 3);
+        assert_eq!(wrap("a b c", 3), vec!["a b", "c"]);
+    }
+}
@@ -0,0 +1,210 @@
+// src-tauri/src/config_transfer.rs
+// Moving configuration between machines, so a team can share one setup. The export file
+// holds the naming templates and collision handling, signing profiles, and the trust
+// policy: the organization's recovery key, the metadata schema and which operations ask
+// for confirmation. Settings that only make sense on the device they were made on (KDF
+// calibration, hashing threads, device binding) are left out.
+//
+// Importing replaces those settings and merges the profiles by ID or name. Changes that
+// weaken protection, i.e. turning confirmation prompts off or switching the recovery key
+// new keys are escrowed to, are confirmed in a native dialog first.
+use crate::audit;
+use crate::consent::{ask_user, turns_off_prompts};
+use crate::crypto_types::{
+    ConfigImportReport, ConsentSettings, MetadataSchema, OutputSettings, RecoveryKeyConfig,
+    SigningProfile,
+};
+use crate::escrow::recovery_key_config;
+use crate::key_management::{get_metadata_path, read_metadata};
+use crate::metadata_schema::check_schema;
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::{resolve_output_path, validate_template};
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use crate::signing_profiles::{
+    get_signing_profiles_path, read_signing_profiles, write_signing_profiles,
+};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+
+const CONFIG_EXPORT_FORMAT: &str = "sigust-config";
+const CONFIG_EXPORT_VERSION: u32 = 1;
+
+/// Contents of an exported configuration file.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigExport {
+    format: String,
+    version: u32,
+    exported_at: DateTime<Utc>,
+    output: OutputSettings,
+    signing_profiles: Vec<SigningProfile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recovery_key: Option<RecoveryKeyConfig>,
+    metadata_schema: MetadataSchema,
+    consent: ConsentSettings,
+}
+
+// --- Tauri Commands ---
+
+/// Writes the shareable configuration to `output_path`. Returns the path written, which
+/// can differ from the requested one under the collision policy.
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_config(app_handle: tauri::AppHandle, output_path: String) -> Result<String, String> {
+    log::info!("Exporting configuration to '{}'", output_path);
+    _export_config(&app_handle, &output_path)
+        .map_err(|e| {
+            log::error!("Failed to export configuration: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|path| audit::record(&app_handle, "exportConfig", json!({ "outputPath": path })))
+}
+
+fn _export_config(app_handle: &tauri::AppHandle, output_path: &str) -> Result<String> {
+    let settings = read_settings(&get_settings_path(app_handle)?)?;
+    let export = ConfigExport {
+        format: CONFIG_EXPORT_FORMAT.to_string(),
+        version: CONFIG_EXPORT_VERSION,
+        exported_at: Utc::now(),
+        output: settings.output,
+        signing_profiles: read_signing_profiles(&get_signing_profiles_path(app_handle)?)?,
+        recovery_key: settings.recovery_key,
+        metadata_schema: settings.metadata_schema,
+        consent: settings.consent,
+    };
+    let content =
+        serde_json::to_string_pretty(&export).context("Failed to serialize configuration")?;
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write configuration file: {:?}", output_path))?;
+    Ok(path_string(&output_path))
+}
+
+/// Applies a configuration file exported with `export_config`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn import_config(
+    app_handle: tauri::AppHandle,
+    input_path: String,
+) -> Result<ConfigImportReport, String> {
+    log::info!("Importing configuration from '{}'", input_path);
+    _import_config(&app_handle, &input_path)
+        .map_err(|e| {
+            log::error!("Failed to import configuration: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|report| {
+            audit::record(
+                &app_handle,
+                "importConfig",
+                json!({
+                    "inputPath": input_path,
+                    "profilesAdded": report.profiles_added,
+                    "profilesUpdated": report.profiles_updated,
+                    "recoveryKeyChanged": report.recovery_key_changed,
+                }),
+            )
+        })
+}
+
+fn _import_config(app_handle: &tauri::AppHandle, input_path: &str) -> Result<ConfigImportReport> {
+    let content = fs::read_to_string(input_path)
+        .with_context(|| format!("Failed to read configuration file: {}", input_path))?;
+    let import: ConfigExport =
+        serde_json::from_str(&content).context("Failed to parse configuration file")?;
+    if import.format != CONFIG_EXPORT_FORMAT || import.version != CONFIG_EXPORT_VERSION {
+        bail!(
+            "Unsupported configuration file: {} version {}",
+            import.format,
+            import.version
+        );
+    }
+
+    // 与各自的设置命令做同样的校验
+    validate_template(&import.output.signature_file_template)?;
+    check_schema(&import.metadata_schema)?;
+    for profile in &import.signing_profiles {
+        if let Some(template) = &profile.output_template {
+            validate_template(template)?;
+        }
+    }
+    let recovery_key = import
+        .recovery_key
+        .as_ref()
+        .map(|config| recovery_key_config(&config.public_key_pem))
+        .transpose()?;
+
+    let current = read_settings(&get_settings_path(app_handle)?)?;
+    let recovery_key_changed = current.recovery_key.as_ref().map(|c| &c.fingerprint)
+        != recovery_key.as_ref().map(|c| &c.fingerprint);
+    let mut warnings = Vec::new();
+    if turns_off_prompts(&current.consent, &import.consent) {
+        warnings.push("Some sensitive operations will no longer ask for confirmation.");
+    }
+    if recovery_key_changed {
+        warnings.push("New keys will be escrowed to a different recovery key, or to none.");
+    }
+    if !warnings.is_empty()
+        && !ask_user(
+            app_handle,
+            "Import configuration",
+            &format!("{}\nContinue?", warnings.join("\n")),
+        )
+    {
+        bail!("Import not confirmed");
+    }
+
+    let mut report = merge_profiles(app_handle, import.signing_profiles)?;
+    report.recovery_key_changed = recovery_key_changed;
+    update_settings(app_handle, |settings| {
+        settings.output = import.output;
+        settings.recovery_key = recovery_key;
+        settings.metadata_schema = import.metadata_schema;
+        settings.consent = import.consent;
+    })?;
+    Ok(report)
+}
+
+// --- Helper Functions ---
+
+// 按 ID 或名称合并签名配置；记录引用本机没有的密钥的配置
+fn merge_profiles(
+    app_handle: &tauri::AppHandle,
+    imported: Vec<SigningProfile>,
+) -> Result<ConfigImportReport> {
+    let path = get_signing_profiles_path(app_handle)?;
+    let mut profiles = read_signing_profiles(&path)?;
+    let local_keys = read_metadata(&get_metadata_path(app_handle)?)?;
+    let mut report = ConfigImportReport {
+        profiles_added: 0,
+        profiles_updated: 0,
+        profiles_without_key: Vec::new(),
+        recovery_key_changed: false,
+    };
+    for profile in imported {
+        if !local_keys.iter().any(|m| m.key_id == profile.key_id) {
+            report.profiles_without_key.push(profile.name.clone());
+        }
+        let existing = profiles.iter_mut().find(|p| {
+            p.profile_id == profile.profile_id || p.name.eq_ignore_ascii_case(&profile.name)
+        });
+        match existing {
+            Some(existing) => {
+                *existing = SigningProfile {
+                    profile_id: existing.profile_id,
+                    created_at: existing.created_at,
+                    updated_at: Utc::now(),
+                    ..profile
+                };
+                report.profiles_updated += 1;
+            }
+            None => {
+                profiles.push(profile);
+                report.profiles_added += 1;
+            }
+        }
+    }
+    write_signing_profiles(&path, &profiles)?;
+    Ok(report)
+}
@@ -0,0 +1,248 @@
+// src-tauri/src/consent.rs
+// Runtime confirmation of sensitive operations: exporting private key material, deleting
+// keys, the first use of a key's private half in this run and, when the signable input
+// rules ask for it, signing an executable. When a command reaches one
+// of them without a confirmation, it fails with a "Confirmation required" error and emits
+// a `consent-requested` event with the request. The webview then calls `confirm_consent`,
+// which asks the user in a native dialog; a confirmed request is used once, by the next
+// attempt of the same operation, and only within a short time.
+//
+// The answer comes from the OS dialog, never from the webview, so a compromised frontend
+// cannot approve anything itself. Which prompts are on is a user setting; turning one off
+// is confirmed the same way. The commands themselves additionally need their `allow-*`
+// permission, which only `capabilities/sensitive.json` grants (see `build.rs`).
+use crate::audit;
+use crate::crypto_types::{ConsentAction, ConsentRequest, ConsentSettings};
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use std::sync::Mutex;
+use tauri::Emitter;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use uuid::Uuid;
+
+/// Event emitted when an operation needs the user's confirmation.
+pub const CONSENT_REQUESTED_EVENT: &str = "consent-requested";
+/// Requests not confirmed, and confirmations not used, within this time expire.
+const CONSENT_TIMEOUT_SECONDS: i64 = 120;
+
+/// Requests waiting for confirmation or for the operation to be retried.
+static CONSENT_REQUESTS: Mutex<Vec<PendingConsent>> = Mutex::new(Vec::new());
+/// Keys whose first use in this run was confirmed.
+static CONFIRMED_KEYS: Mutex<Vec<Uuid>> = Mutex::new(Vec::new());
+
+struct PendingConsent {
+    request: ConsentRequest,
+    /// What the confirmation is for (key ID, seed ID, list of key IDs, ...).
+    subject: String,
+    confirmed: bool,
+    expires_at: DateTime<Utc>,
+}
+
+// --- Tauri Commands ---
+
+/// Asks the user to confirm a pending request in a native dialog. Returns whether it was
+/// confirmed; the operation has to be retried afterwards.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn confirm_consent(
+    app_handle: tauri::AppHandle,
+    request_id: Uuid,
+) -> Result<bool, String> {
+    log::info!("Asking for confirmation of request {}", request_id);
+    _confirm_consent(&app_handle, request_id)
+        .map_err(|e| {
+            log::error!("Failed to confirm request {}: {:?}", request_id, e);
+            e.to_string()
+        })
+        .inspect(|confirmed| {
+            audit::record(
+                &app_handle,
+                "confirmConsent",
+                json!({ "requestId": request_id, "confirmed": confirmed }),
+            )
+        })
+}
+
+fn _confirm_consent(app_handle: &tauri::AppHandle, request_id: Uuid) -> Result<bool> {
+    let request = lock_requests()?
+        .iter()
+        .find(|p| p.request.request_id == request_id && !p.confirmed)
+        .map(|p| p.request.clone())
+        .ok_or_else(|| anyhow!("The confirmation request has expired; try again"))?;
+    let confirmed = ask_user(app_handle, &request.title, &request.description);
+    let mut requests = lock_requests()?;
+    if confirmed {
+        let pending = requests
+            .iter_mut()
+            .find(|p| p.request.request_id == request_id)
+            .ok_or_else(|| anyhow!("The confirmation request has expired; try again"))?;
+        pending.confirmed = true;
+        pending.expires_at = Utc::now() + Duration::seconds(CONSENT_TIMEOUT_SECONDS);
+    } else {
+        requests.retain(|p| p.request.request_id != request_id);
+    }
+    Ok(confirmed)
+}
+
+/// Changes which operations ask for confirmation. Turning a prompt off is itself confirmed
+/// in a native dialog.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn update_consent_settings(
+    app_handle: tauri::AppHandle,
+    consent: ConsentSettings,
+) -> Result<ConsentSettings, String> {
+    log::info!("Updating confirmation prompts: {:?}", consent);
+    _update_consent_settings(&app_handle, consent)
+        .map_err(|e| {
+            log::error!("Failed to update confirmation prompts: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|consent| audit::record(&app_handle, "updateConsentSettings", json!(consent)))
+}
+
+fn _update_consent_settings(
+    app_handle: &tauri::AppHandle,
+    consent: ConsentSettings,
+) -> Result<ConsentSettings> {
+    let current = consent_settings(app_handle)?;
+    if turns_off_prompts(&current, &consent)
+        && !ask_user(
+            app_handle,
+            "Turn off confirmations",
+            "Sensitive operations will no longer ask for confirmation. Continue?",
+        )
+    {
+        bail!("Change not confirmed");
+    }
+    update_settings(app_handle, |settings| settings.consent = consent)
+        .map(|settings| settings.consent)
+}
+
+// --- Helper Functions ---
+
+/// Fails with a "Confirmation required" error unless the user confirmed `action` on
+/// `subject` (see `confirm_consent`) or its prompt is turned off. `description` is shown
+/// in the dialog.
+pub(crate) fn require_consent(
+    app_handle: &tauri::AppHandle,
+    action: ConsentAction,
+    subject: &str,
+    description: String,
+) -> Result<()> {
+    let settings = consent_settings(app_handle)?;
+    let enabled = match action {
+        ConsentAction::ExportPrivateKey => settings.export_private_key,
+        ConsentAction::DeleteKey => settings.delete_key,
+        ConsentAction::FirstKeyUse => settings.first_key_use,
+        ConsentAction::SignExecutable => true,
+    };
+    if !enabled {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let mut requests = lock_requests()?;
+    requests.retain(|p| p.expires_at > now);
+    let existing = requests
+        .iter()
+        .position(|p| p.request.action == action && p.subject == subject);
+    if let Some(index) = existing.filter(|&i| requests[i].confirmed) {
+        requests.remove(index);
+        return Ok(());
+    }
+    let request = match existing {
+        Some(index) => requests[index].request.clone(),
+        None => {
+            let request = ConsentRequest {
+                request_id: Uuid::new_v4(),
+                action,
+                title: action_title(action).to_string(),
+                description,
+                expires_at: now + Duration::seconds(CONSENT_TIMEOUT_SECONDS),
+            };
+            requests.push(PendingConsent {
+                request: request.clone(),
+                subject: subject.to_string(),
+                confirmed: false,
+                expires_at: request.expires_at,
+            });
+            request
+        }
+    };
+    drop(requests);
+    if let Err(e) = app_handle.emit(CONSENT_REQUESTED_EVENT, &request) {
+        log::warn!("Failed to emit confirmation request: {}", e);
+    }
+    bail!(
+        "Confirmation required (request {}): {}",
+        request.request_id,
+        request.description
+    )
+}
+
+/// `require_consent` for the first use of a key's private half in this run.
+pub(crate) fn require_key_use_consent(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    key_name: &str,
+) -> Result<()> {
+    let mut confirmed = CONFIRMED_KEYS
+        .lock()
+        .map_err(|_| anyhow!("Confirmed keys lock poisoned"))?;
+    if confirmed.contains(&key_id) {
+        return Ok(());
+    }
+    require_consent(
+        app_handle,
+        ConsentAction::FirstKeyUse,
+        &key_id.to_string(),
+        format!(
+            "Use the private key '{}' for the first time since the app started.",
+            key_name
+        ),
+    )?;
+    confirmed.push(key_id);
+    Ok(())
+}
+
+/// Whether going from `current` to `new` turns any prompt off.
+pub(crate) fn turns_off_prompts(current: &ConsentSettings, new: &ConsentSettings) -> bool {
+    (current.export_private_key && !new.export_private_key)
+        || (current.delete_key && !new.delete_key)
+        || (current.first_key_use && !new.first_key_use)
+}
+
+// 读取确认提示设置
+fn consent_settings(app_handle: &tauri::AppHandle) -> Result<ConsentSettings> {
+    Ok(read_settings(&get_settings_path(app_handle)?)?.consent)
+}
+
+fn lock_requests() -> Result<std::sync::MutexGuard<'static, Vec<PendingConsent>>> {
+    CONSENT_REQUESTS
+        .lock()
+        .map_err(|_| anyhow!("Confirmation request lock poisoned"))
+}
+
+fn action_title(action: ConsentAction) -> &'static str {
+    match action {
+        ConsentAction::ExportPrivateKey => "Export private key material",
+        ConsentAction::DeleteKey => "Delete keys",
+        ConsentAction::FirstKeyUse => "Use a private key",
+        ConsentAction::SignExecutable => "Sign an executable",
+    }
+}
+
+// 弹出系统原生确认对话框（阻塞，不能在主线程调用）
+pub(crate) fn ask_user(app_handle: &tauri::AppHandle, title: &str, description: &str) -> bool {
+    app_handle
+        .dialog()
+        .message(description)
+        .title(title)
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Allow".to_string(),
+            "Cancel".to_string(),
+        ))
+        .blocking_show()
+}
@@ -0,0 +1,360 @@
+// src-tauri/src/contacts.rs
+use crate::audit;
+use crate::crypto_types::{
+    Contact, ContactImportReport, DigestScheme, SignatureAlgorithm, TrustLevel,
+};
+use crate::envelope::canonical_json;
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, detect_public_key_algorithm, find_key_metadata,
+    get_app_data_file, get_metadata_path, read_metadata, read_private_key_der, read_public_key_pem,
+};
+use crate::native_paths::{native_path, read_file};
+use crate::output_paths::resolve_output_path;
+use crate::signing::{sign_bytes, verify_bytes};
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const CONTACTS_FILENAME: &str = "contacts.json";
+pub(crate) const CONTACTS_EXPORT_FORMAT: &str = "sigust-contacts";
+const CONTACTS_EXPORT_VERSION: u32 = 1;
+
+/// The signed portion of an exported contacts file.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContactsExportBody {
+    format: String,
+    version: u32,
+    exported_at: DateTime<Utc>,
+    signer: ContactsSigner,
+    contacts: Vec<Contact>,
+}
+
+/// The key that signed an exported contacts file.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContactsSigner {
+    name: String,
+    algorithm: String,
+    public_key_pem: String,
+    fingerprint: String,
+}
+
+/// An exported contacts file: the body plus a base64 signature over its canonical JSON form.
+#[derive(Serialize, Deserialize)]
+struct SignedContactsFile {
+    signed: serde_json::Value,
+    signature: String,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_contacts(app_handle: tauri::AppHandle) -> Result<Vec<Contact>, String> {
+    log::info!("Listing contacts");
+    let contacts_path = get_contacts_path(&app_handle).map_err(|e| e.to_string())?;
+    read_contacts(&contacts_path).map_err(|e| {
+        log::error!("Failed to list contacts: {:?}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn remove_contact(app_handle: tauri::AppHandle, contact_id: Uuid) -> Result<(), String> {
+    log::info!("Removing contact ID: {}", contact_id);
+    _remove_contact(&app_handle, contact_id).map_err(|e| {
+        log::error!("Failed to remove contact {}: {:?}", contact_id, e);
+        e.to_string()
+    })
+}
+
+fn _remove_contact(app_handle: &tauri::AppHandle, contact_id: Uuid) -> Result<()> {
+    let contacts_path = get_contacts_path(app_handle)?;
+    let mut contacts = read_contacts(&contacts_path)?;
+    let before = contacts.len();
+    contacts.retain(|c| c.contact_id != contact_id);
+    if contacts.len() == before {
+        anyhow::bail!("Contact with ID {} not found", contact_id);
+    }
+    write_contacts(&contacts_path, &contacts)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_contacts(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    password: String,
+    output_path: String,
+    contact_ids: Option<Vec<Uuid>>,
+) -> Result<usize, String> {
+    log::info!(
+        "Exporting contacts to '{}' signed by key {}",
+        output_path,
+        key_id
+    );
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _export_contacts(&app_handle, key_id, &password, &output_path, contact_ids).map_err(|e| {
+        log::error!("Failed to export contacts: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _export_contacts(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    password: &str,
+    output_path: &str,
+    contact_ids: Option<Vec<Uuid>>,
+) -> Result<usize> {
+    let mut contacts = read_contacts(&get_contacts_path(app_handle)?)?;
+    if let Some(contact_ids) = contact_ids {
+        contacts.retain(|c| contact_ids.contains(&c.contact_id));
+    }
+
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let algorithm = metadata.algorithm.clone();
+    let public_key_pem = read_public_key_pem(app_handle, &metadata)?;
+    let fingerprint = compute_fingerprint(&decode_public_key_pem(&public_key_pem)?);
+    let private_key_der = read_private_key_der(app_handle, &metadata, password)?;
+
+    let count = contacts.len();
+    let body = ContactsExportBody {
+        format: CONTACTS_EXPORT_FORMAT.to_string(),
+        version: CONTACTS_EXPORT_VERSION,
+        exported_at: Utc::now(),
+        signer: ContactsSigner {
+            name: metadata.name,
+            algorithm: algorithm.to_string(),
+            public_key_pem,
+            fingerprint,
+        },
+        contacts,
+    };
+    let signed = serde_json::to_value(&body).context("Failed to serialize contacts")?;
+    let signed_bytes = canonical_json(&signed)?;
+    let signature = sign_bytes(
+        &algorithm,
+        &private_key_der,
+        &signed_bytes,
+        DigestScheme::Standard,
+    )?;
+
+    let file = SignedContactsFile {
+        signed,
+        signature: BASE64.encode(signature),
+    };
+    let content =
+        serde_json::to_string_pretty(&file).context("Failed to serialize contacts file")?;
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write contacts file: {:?}", output_path))?;
+
+    log::info!("Exported {} contacts to {:?}", count, output_path);
+    Ok(count)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn import_contacts(
+    app_handle: tauri::AppHandle,
+    input_path: String,
+) -> Result<ContactImportReport, String> {
+    log::info!("Importing contacts from '{}'", input_path);
+    _import_contacts(&app_handle, &input_path)
+        .map_err(|e| {
+            log::error!("Failed to import contacts: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|report| {
+            audit::record(
+                &app_handle,
+                "importContacts",
+                json!({ "inputPath": input_path, "signerFingerprint": report.signer_fingerprint }),
+            )
+        })
+}
+
+fn _import_contacts(
+    app_handle: &tauri::AppHandle,
+    input_path: &str,
+) -> Result<ContactImportReport> {
+    let content = read_file(&native_path(input_path)?)
+        .with_context(|| format!("Failed to read contacts file: {}", input_path))?;
+    let file: SignedContactsFile =
+        serde_json::from_slice(&content).context("Failed to parse contacts file")?;
+    let body: ContactsExportBody =
+        serde_json::from_value(file.signed.clone()).context("Malformed contacts file body")?;
+    if body.format != CONTACTS_EXPORT_FORMAT || body.version != CONTACTS_EXPORT_VERSION {
+        bail!(
+            "Unsupported contacts file format: {} v{}",
+            body.format,
+            body.version
+        );
+    }
+
+    // 1. Verify the file signature against the embedded signer key
+    let signer_der = decode_public_key_pem(&body.signer.public_key_pem)?;
+    let signer_algorithm = detect_public_key_algorithm(&signer_der)?;
+    let signer_fingerprint = compute_fingerprint(&signer_der);
+    let signature = BASE64
+        .decode(&file.signature)
+        .context("Failed to decode contacts file signature")?;
+    let signed_bytes = canonical_json(&file.signed)?;
+    if verify_bytes(
+        &signer_algorithm,
+        &signer_der,
+        &signed_bytes,
+        &signature,
+        DigestScheme::Standard,
+    )?
+    .is_err()
+    {
+        bail!("Contacts file signature is invalid; the file may have been modified");
+    }
+
+    // 2. Decide whether the signer's trust decisions can be adopted
+    let contacts_path = get_contacts_path(app_handle)?;
+    let mut contacts = read_contacts(&contacts_path)?;
+    let signer_is_own_key = read_metadata(&get_metadata_path(app_handle)?)?
+        .iter()
+        .any(|m| {
+            read_public_key_pem(app_handle, m)
+                .ok()
+                .and_then(|pem| decode_public_key_pem(&pem).ok())
+                .is_some_and(|der| compute_fingerprint(&der) == signer_fingerprint)
+        });
+    let signer_trusted = signer_is_own_key
+        || contacts
+            .iter()
+            .any(|c| c.fingerprint == signer_fingerprint && c.trust == TrustLevel::Verified);
+
+    // 3. Merge contacts by fingerprint
+    let mut report = ContactImportReport {
+        signer_name: body.signer.name,
+        signer_fingerprint,
+        signer_trusted,
+        added: Vec::new(),
+        updated: Vec::new(),
+        unchanged: 0,
+    };
+    let now = Utc::now();
+    for imported in body.contacts {
+        // Never rely on fingerprints or algorithms recorded in the file
+        let der = decode_public_key_pem(&imported.public_key_pem)
+            .with_context(|| format!("Invalid public key for contact '{}'", imported.name))?;
+        let fingerprint = compute_fingerprint(&der);
+        let algorithm = detect_public_key_algorithm(&der)?;
+        let trust = match imported.trust {
+            TrustLevel::Distrusted => TrustLevel::Distrusted,
+            trust if signer_trusted => trust,
+            _ => TrustLevel::Unverified,
+        };
+
+        match contacts.iter_mut().find(|c| c.fingerprint == fingerprint) {
+            Some(existing) if signer_trusted && existing.trust != trust => {
+                existing.trust = trust;
+                existing.updated_at = now;
+                report.updated.push(existing.contact_id);
+            }
+            Some(existing) if trust == TrustLevel::Distrusted && existing.trust != trust => {
+                // A distrust decision is always safe to adopt
+                existing.trust = trust;
+                existing.updated_at = now;
+                report.updated.push(existing.contact_id);
+            }
+            Some(_) => report.unchanged += 1,
+            None => {
+                let contact = Contact {
+                    contact_id: Uuid::new_v4(),
+                    name: imported.name,
+                    algorithm,
+                    public_key_pem: imported.public_key_pem,
+                    fingerprint,
+                    trust,
+                    added_at: now,
+                    updated_at: now,
+                };
+                report.added.push(contact.contact_id);
+                contacts.push(contact);
+            }
+        }
+    }
+    write_contacts(&contacts_path, &contacts)?;
+
+    log::info!(
+        "Imported contacts: {} added, {} updated, {} unchanged (signer trusted: {})",
+        report.added.len(),
+        report.updated.len(),
+        report.unchanged,
+        report.signer_trusted
+    );
+    Ok(report)
+}
+
+// --- Helper Functions ---
+
+// 获取联系人（信任库）文件路径
+pub fn get_contacts_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, CONTACTS_FILENAME)
+}
+
+// 读取联系人列表
+pub fn read_contacts(path: &Path) -> Result<Vec<Contact>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).context("Failed to read contacts file")?;
+    serde_json::from_str(&content).context("Failed to parse contacts JSON")
+}
+
+// 写入联系人列表
+pub fn write_contacts(path: &Path, contacts: &[Contact]) -> Result<()> {
+    let content =
+        serde_json::to_string_pretty(contacts).context("Failed to serialize contacts to JSON")?;
+    fs::write(path, content).context("Failed to write contacts file")
+}
+
+/// Inserts a contact or, if a contact with the same fingerprint already exists,
+/// updates its name and trust decision. Returns the stored entry.
+pub(crate) fn upsert_contact(
+    path: &Path,
+    name: String,
+    algorithm: SignatureAlgorithm,
+    public_key_pem: String,
+    fingerprint: String,
+    trust: TrustLevel,
+) -> Result<Contact> {
+    let mut contacts = read_contacts(path)?;
+    let now = Utc::now();
+    let contact = match contacts.iter_mut().find(|c| c.fingerprint == fingerprint) {
+        Some(existing) => {
+            existing.name = name;
+            existing.trust = trust;
+            existing.updated_at = now;
+            existing.clone()
+        }
+        None => {
+            let contact = Contact {
+                contact_id: Uuid::new_v4(),
+                name,
+                algorithm,
+                public_key_pem,
+                fingerprint,
+                trust,
+                added_at: now,
+                updated_at: now,
+            };
+            contacts.push(contact.clone());
+            contact
+        }
+    };
+    write_contacts(path, &contacts)?;
+    Ok(contact)
+}
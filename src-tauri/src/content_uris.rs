@@ -0,0 +1,262 @@
+// src-tauri/src/content_uris.rs
+// Documents picked on Android and iOS. The system pickers hand the frontend a URI instead
+// of a path: `content://...` from the Android Storage Access Framework (including cloud
+// providers), a `file://` URL from the iOS document picker. Commands that take a document
+// accept these next to plain paths.
+//
+// A `file://` URL is simply converted to its path. A content URI is opened through the fs
+// plugin, which asks the provider for a file descriptor; while the descriptor is held the
+// document is reachable as `/proc/self/fd/<n>`. A symlink to that path, named after the
+// document, is placed in an app-private folder, so the rest of the pipeline (signable input
+// rules, streamed hashing, file-name binding) reads the document like any other file and no
+// copy is made. The link is removed when the handle is dropped, leftovers at startup.
+//
+// The pipeline may read a document more than once, which each time reopens the link.
+// Providers that hand out a pipe instead of a file can only be read once and are refused.
+use crate::native_paths::{native_path, path_string};
+use anyhow::{anyhow, bail, Context, Result};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tauri::Manager;
+use tauri_plugin_fs::{FilePath, FsExt, OpenOptions};
+use uuid::Uuid;
+
+const CONTENT_SCHEME: &str = "content://";
+const FILE_SCHEME: &str = "file://";
+const LINKS_DIR: &str = "document-links";
+/// Where the open descriptors of this process are visible as files.
+const DESCRIPTOR_DIR: &str = "/proc/self/fd";
+/// Link name when the URI does not end in a usable file name.
+const FALLBACK_NAME: &str = "document";
+
+/// A document named by the webview, as a path the pipeline can open.
+pub(crate) struct DocumentHandle {
+    location: String,
+    path: PathBuf,
+    /// Folder holding the link to the descriptor, for content URIs.
+    link_dir: Option<PathBuf>,
+    /// Keeps the descriptor behind the link open.
+    _descriptor: Option<File>,
+}
+
+impl DocumentHandle {
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the document was opened through a content URI.
+    pub(crate) fn is_content_uri(&self) -> bool {
+        self.link_dir.is_some()
+    }
+
+    /// String for `path` sent back to the webview: the original URI when `path` is this
+    /// document's link, `path_string` otherwise.
+    pub(crate) fn describe(&self, path: &Path) -> String {
+        if self.is_content_uri() && path == self.path {
+            self.location.clone()
+        } else {
+            path_string(path)
+        }
+    }
+}
+
+impl Drop for DocumentHandle {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.link_dir {
+            if let Err(e) = fs::remove_dir_all(dir) {
+                log::warn!("Failed to remove document link {:?}: {:?}", dir, e);
+            }
+        }
+    }
+}
+
+// --- Helper Functions ---
+
+/// Opens a document to read: a plain or encoded path, a `file://` URL or a content URI.
+pub(crate) fn open_document(
+    app_handle: &tauri::AppHandle,
+    location: &str,
+) -> Result<DocumentHandle> {
+    let mut options = OpenOptions::new();
+    options.read(true);
+    open_location(app_handle, location, options, true)
+}
+
+/// Opens an output file to write, like `open_document`. A content URI must name a
+/// document the picker created; it is truncated and written in place.
+pub(crate) fn open_output(app_handle: &tauri::AppHandle, location: &str) -> Result<DocumentHandle> {
+    let mut options = OpenOptions::new();
+    options.write(true).truncate(true);
+    open_location(app_handle, location, options, false)
+}
+
+/// Whether a location from the webview is a content URI.
+pub(crate) fn is_content_uri(location: &str) -> bool {
+    location.starts_with(CONTENT_SCHEME)
+}
+
+/// Whether `path` is the link of an open content URI. Such outputs already exist and are
+/// written in place, whatever the collision policy.
+pub(crate) fn is_document_link(path: &Path) -> bool {
+    fs::read_link(path).is_ok_and(|target| target.starts_with(DESCRIPTOR_DIR))
+}
+
+/// Removes document links left behind by a previous run (run at startup).
+pub(crate) fn clear_stale_document_links(app_handle: &tauri::AppHandle) -> Result<()> {
+    let dir = links_dir(app_handle)?;
+    if dir.exists() {
+        // remove_dir_all 不跟随符号链接，只删除链接本身
+        fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {:?}", dir))?;
+    }
+    Ok(())
+}
+
+fn open_location(
+    app_handle: &tauri::AppHandle,
+    location: &str,
+    options: OpenOptions,
+    read: bool,
+) -> Result<DocumentHandle> {
+    let plain = |path: PathBuf| DocumentHandle {
+        location: location.to_string(),
+        path,
+        link_dir: None,
+        _descriptor: None,
+    };
+    if location.starts_with(FILE_SCHEME) {
+        let path = FilePath::from_str(location)
+            .map_err(|e| anyhow!("Invalid URI: {:?}", e))?
+            .into_path()
+            .map_err(|e| anyhow!("Not a local file URL: {} ({})", location, e))?;
+        return Ok(plain(path));
+    }
+    if !is_content_uri(location) {
+        return Ok(plain(native_path(location)?));
+    }
+
+    let file_path = FilePath::from_str(location).map_err(|e| anyhow!("Invalid URI: {:?}", e))?;
+    let descriptor = app_handle
+        .fs()
+        .open(file_path, options)
+        .with_context(|| format!("Failed to open document: {}", location))?;
+    if read && !descriptor.metadata()?.is_file() {
+        bail!(
+            "The provider of {} streams the document and it cannot be read more than once",
+            location
+        );
+    }
+    let link_dir = links_dir(app_handle)?.join(Uuid::new_v4().to_string());
+    fs::create_dir_all(&link_dir)
+        .with_context(|| format!("Failed to create directory: {:?}", link_dir))?;
+    let path = link_dir.join(uri_file_name(location));
+    // 出错时 handle 被丢弃，链接目录随之删除
+    let mut handle = DocumentHandle {
+        location: location.to_string(),
+        path,
+        link_dir: Some(link_dir),
+        _descriptor: None,
+    };
+    link_descriptor(&descriptor, &handle.path)?;
+    handle._descriptor = Some(descriptor);
+    Ok(handle)
+}
+
+#[cfg(unix)]
+fn link_descriptor(descriptor: &File, link: &Path) -> Result<()> {
+    use std::os::fd::AsRawFd;
+    let target = Path::new(DESCRIPTOR_DIR).join(descriptor.as_raw_fd().to_string());
+    std::os::unix::fs::symlink(&target, link)
+        .with_context(|| format!("Failed to link {:?} to {:?}", link, target))
+}
+
+#[cfg(not(unix))]
+fn link_descriptor(_descriptor: &File, _link: &Path) -> Result<()> {
+    bail!("Content URIs are only supported on Android")
+}
+
+// 获取应用缓存目录下存放文档链接的目录
+fn links_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    Ok(app_handle
+        .path()
+        .app_cache_dir()
+        .context("Failed to get app cache directory")?
+        .join(LINKS_DIR))
+}
+
+// 从 URI 最后一段推导文件名：文档 ID 形如 `primary%3ADocuments%2Fcontract.pdf`，
+// 解码后取最后一个 `/` 或 `:` 之后的部分
+fn uri_file_name(uri: &str) -> String {
+    let last = uri
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    let decoded = percent_decode(last);
+    let name: String = decoded
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && *c != '\\')
+        .collect();
+    let name = name.trim().trim_start_matches('.');
+    if name.is_empty() {
+        FALLBACK_NAME.to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+// `%XX` 解码；不合法的转义原样保留
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_links_after_the_document() {
+        assert_eq!(
+            uri_file_name(
+                "content://com.android.externalstorage.documents/document/primary%3ADocuments%2Fcontract.pdf"
+            ),
+            "contract.pdf"
+        );
+        assert_eq!(
+            uri_file_name(
+                "content://com.google.android.apps.docs.storage/document/acc%3D1%3Bdoc%3D42"
+            ),
+            "acc=1;doc=42"
+        );
+        assert_eq!(
+            uri_file_name("content://provider/files/..%2F"),
+            FALLBACK_NAME
+        );
+        assert_eq!(percent_decode("50%25%2x%E2%9C%93"), "50%%2x✓");
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::bail;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::{fmt, str::FromStr}; // Import necessary traits
 use uuid::Uuid;
 
@@ -25,15 +26,185 @@ pub struct KeyInfo {
     /// User-defined name for the key.
     pub name: String,
     /// The signature algorithm associated with this key.
-    /// Matches the `Display` representation of `SignatureAlgorithm`.
-    pub algorithm: String,
+    pub algorithm: SignatureAlgorithm,
     /// Timestamp (UTC) when the key was generated or imported.
     pub created_at: DateTime<Utc>,
+    /// Identity of the key owner, used for certificates, CMS signer info and signature envelopes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<SignerIdentity>,
+    /// When the key was moved to the trash (only set for trashed keys).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trashed_at: Option<DateTime<Utc>>,
+    /// Folder the key is filed under (see `list_key_folders`); absent for the root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder_id: Option<Uuid>,
+    /// Free-form labels attached with `tag_keys`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Whether an escrow copy was sealed to the organization's recovery key.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub escrowed: bool,
+    /// Key that must approve every signature made with this key (two-person control).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approver_key_id: Option<Uuid>,
+    /// ML-DSA key that co-signs hybrid signatures made with this key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pq_key_id: Option<Uuid>,
+    /// Passkey whose security key must be touched to decrypt this key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_passkey_id: Option<Uuid>,
+    /// Master seed and path the key was derived from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation: Option<KeyDerivation>,
+    /// Stricter unlock rule; absent when the key may be held in a key session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unlock_policy: Option<KeyUnlockPolicy>,
+    /// Whether the private key file can only be decrypted on this device.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub device_bound: bool,
+    /// KDF the private key file is wrapped with; absent for keys stored before it was
+    /// recorded, which use PBKDF2-HMAC-SHA256.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kdf: Option<KdfParameters>,
+    /// Whether the entry holds a key pair or only a public key for verification.
+    #[serde(default)]
+    pub kind: KeyKind,
+    /// SHA-256 of the SPKI DER public key, hex-encoded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    /// The key's fingerprint as `ssh-keygen -l` shows it (`SHA256:` and base64).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_fingerprint: Option<String>,
+    /// Modulus size of RSA keys, in bits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_bits: Option<usize>,
+}
+
+/// Fingerprints of a stored public key, to compare it with its owner out-of-band.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyFingerprint {
+    pub key_id: Uuid,
+    /// SHA-256 of the SPKI DER public key, hex-encoded.
+    pub fingerprint: String,
+    /// The key's fingerprint as `ssh-keygen -l` shows it (`SHA256:` and base64); `None`
+    /// for ML-DSA keys, which OpenSSH cannot hold.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_fingerprint: Option<String>,
+}
+
+/// What a key store entry holds.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyKind {
+    /// A private key (wrapped with a password) and its public key.
+    #[default]
+    KeyPair,
+    /// Someone else's public key, imported to verify their signatures.
+    PublicOnly,
+}
+
+impl KeyKind {
+    pub fn is_key_pair(&self) -> bool {
+        *self == KeyKind::KeyPair
+    }
+}
+
+/// Password-based key derivation function protecting a stored private key.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyKdf {
+    /// PBKDF2-HMAC-SHA256.
+    #[default]
+    Pbkdf2,
+    /// Argon2id; memory-hard, so much costlier to attack with GPUs.
+    Argon2id,
+}
+
+/// Name and parameters of the KDF a private key file is wrapped with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "name", rename_all = "camelCase")]
+pub enum KdfParameters {
+    Pbkdf2Sha256 {
+        iterations: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    Argon2id {
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+}
+
+impl KdfParameters {
+    pub fn kind(&self) -> KeyKdf {
+        match self {
+            KdfParameters::Pbkdf2Sha256 { .. } => KeyKdf::Pbkdf2,
+            KdfParameters::Argon2id { .. } => KeyKdf::Argon2id,
+        }
+    }
+}
+
+/// Parameters of a newly generated key.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyGenerationOptions {
+    /// Modulus size of RSA keys: 2048 (the default), 3072 or 4096 bits.
+    pub rsa_key_bits: Option<usize>,
+}
+
+/// How a generated or imported private key is stored.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyStorageOptions {
+    /// Seal a copy to the organization's recovery key.
+    pub escrow: bool,
+    /// Do not bind the key to this device, even when device binding is on.
+    pub portable: bool,
+    /// KDF that derives the wrapping key from the password.
+    pub kdf: KeyKdf,
+}
+
+/// How a key has to be unlocked (see `unlock_policy.rs`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum KeyUnlockPolicy {
+    /// The key cannot be held in a key session; every use asks for the password.
+    EveryUse,
+    /// Like `EveryUse`, and every use also needs a user-verifying assertion from the passkey.
+    #[serde(rename_all = "camelCase")]
+    Biometric { passkey_key_id: Uuid },
+}
+
+/// Owner identity attached to a key pair.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SignerIdentity {
+    /// Full name of the signer (certificate common name).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_name: Option<String>,
+    /// E-mail address of the signer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Organization the signer acts on behalf of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+}
+
+impl SignerIdentity {
+    /// Returns `true` if no identity field is set.
+    pub fn is_empty(&self) -> bool {
+        self.full_name.is_none() && self.email.is_none() && self.organization.is_none()
+    }
 }
 
 /// Enumerates the supported signature algorithms within the application.
+///
+/// Serialized as its camelCase identifier (e.g. `ecdsaP256Sha256`); use
+/// `get_display_names` for text to show. Deserializing also accepts the `Display` form
+/// and the aliases of `FromStr`, which files written by earlier releases contain.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-#[serde(rename_all = "camelCase")] // Optional: use camelCase for JSON if preferred by frontend
+#[serde(rename_all = "camelCase", try_from = "String")]
 pub enum SignatureAlgorithm {
     /// RSA signature scheme with PKCS#1 v1.5 padding and SHA-256 hashing.
     /// Typically used with 2048-bit keys or larger.
@@ -41,9 +212,36 @@ pub enum SignatureAlgorithm {
     /// Elliptic Curve Digital Signature Algorithm (ECDSA) using the NIST P-256 curve
     /// and SHA-256 hashing.
     EcdsaP256Sha256,
+    /// ECDSA using the NIST P-384 curve and SHA-384 hashing.
+    EcdsaP384Sha384,
+    /// ECDSA using the NIST P-521 curve and SHA-512 hashing.
+    EcdsaP521Sha512,
     /// Edwards-curve Digital Signature Algorithm (EdDSA) using the Ed25519 curve.
     /// Hashing is implicitly defined by the Ed25519 scheme.
     Ed25519,
+    /// Post-quantum ML-DSA-65 (FIPS 204, formerly Dilithium3), signing the whole message
+    /// with an empty context. Keys and signatures are far larger than the classical ones.
+    MlDsa65,
+}
+
+impl SignatureAlgorithm {
+    /// Whether this is one of the ECDSA algorithms.
+    pub(crate) fn is_ecdsa(&self) -> bool {
+        matches!(
+            self,
+            SignatureAlgorithm::EcdsaP256Sha256
+                | SignatureAlgorithm::EcdsaP384Sha384
+                | SignatureAlgorithm::EcdsaP521Sha512
+        )
+    }
+
+    /// Whether a signature can be made over a SHA-256 digest alone, as KMS signers do.
+    pub(crate) fn signs_sha256_digest(&self) -> bool {
+        matches!(
+            self,
+            SignatureAlgorithm::RsaPkcs1Sha256 | SignatureAlgorithm::EcdsaP256Sha256
+        )
+    }
 }
 
 impl fmt::Display for SignatureAlgorithm {
@@ -51,7 +249,10 @@ impl fmt::Display for SignatureAlgorithm {
         match self {
             SignatureAlgorithm::RsaPkcs1Sha256 => write!(f, "RSA-PKCS1-SHA256"),
             SignatureAlgorithm::EcdsaP256Sha256 => write!(f, "ECDSA-P256-SHA256"),
+            SignatureAlgorithm::EcdsaP384Sha384 => write!(f, "ECDSA-P384-SHA384"),
+            SignatureAlgorithm::EcdsaP521Sha512 => write!(f, "ECDSA-P521-SHA512"),
             SignatureAlgorithm::Ed25519 => write!(f, "Ed25519"),
+            SignatureAlgorithm::MlDsa65 => write!(f, "ML-DSA-65"),
         }
     }
 }
@@ -72,22 +273,48 @@ impl FromStr for SignatureAlgorithm {
             "ECDSAP256SHA256" | "P256" | "ECDSAP256" | "ECP256" => {
                 Ok(SignatureAlgorithm::EcdsaP256Sha256)
             }
+            "ECDSAP384SHA384" | "P384" | "ECDSAP384" | "ECP384" => {
+                Ok(SignatureAlgorithm::EcdsaP384Sha384)
+            }
+            "ECDSAP521SHA512" | "P521" | "ECDSAP521" | "ECP521" => {
+                Ok(SignatureAlgorithm::EcdsaP521Sha512)
+            }
             // Ed25519 Aliases
             "ED25519" => Ok(SignatureAlgorithm::Ed25519),
+            // ML-DSA Aliases
+            "MLDSA65" | "MLDSA" | "DILITHIUM3" => Ok(SignatureAlgorithm::MlDsa65),
             // Unrecognized
             _ => bail!("Unsupported or unrecognized signature algorithm: {}", s),
         }
     }
 }
 
+impl TryFrom<String> for SignatureAlgorithm {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 /// Specifies the desired format for the output signature.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")] // Optional: use camelCase for JSON if preferred by frontend
 pub enum SignatureFormat {
     /// Signature is stored in a separate file (e.g., `.sig`).
     Detached,
+    /// Signed attributes (signer key, document digest, time) and the signature over their
+    /// canonical encoding, stored together as a JSON envelope.
+    Envelope,
+    /// An envelope signed twice over the same attributes: by the signing key and by the
+    /// post-quantum (ML-DSA) key linked to it. It only verifies if both signatures do.
+    Hybrid,
     // /// Signature is embedded within the document (e.g., PDF PAdES).
-    // /// Not currently implemented.
+    // /// Not currently implemented. There is no PDF parser/writer in the tree, so PAdES
+    // /// B-LT/B-LTA (embedding certificates, OCSP/CRL responses in a DSS dictionary and a
+    // /// document time-stamp) has to wait for it; keys are also bare key pairs without
+    // /// certificates, so there is no chain to collect validation data for. For long-term
+    // /// evidence today, use `Envelope` with `refresh_signature` time-stamps.
     // Embedded,
 }
 
@@ -97,14 +324,109 @@ pub enum SignatureFormat {
 pub struct SigningOptions {
     /// The desired output format for the signature.
     pub format: SignatureFormat,
-    // --- Future Extensions ---
-    // pub use_timestamp: bool,
-    // pub tsa_url: Option<String>,
-    // pub signature_level: Option<SignatureLevel>, // e.g., for PAdES B-B, B-T, B-LT, B-LTA
+    /// Include the document's file name in the signed attributes (envelope format only),
+    /// so the signature no longer verifies for a renamed file.
+    #[serde(default)]
+    pub bind_file_name: bool,
+    /// Also include the document's size and modification time (envelope format only).
+    #[serde(default)]
+    pub bind_file_attributes: bool,
+    /// Baseline level of the signature; levels above `B` need the envelope format.
+    #[serde(default)]
+    pub signature_level: SignatureLevel,
+    /// RFC 3161 time-stamping authority used for level `T` and `trusted_time`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tsa_url: Option<String>,
+    /// Take the signing time from the TSA instead of the local clock (envelope format only).
+    #[serde(default)]
+    pub trusted_time: bool,
+    /// Encoding of ECDSA signatures in detached files.
+    #[serde(default)]
+    pub ecdsa_encoding: EcdsaEncoding,
+    /// How detached signature files are written; text encodings can be pasted into emails
+    /// and tickets.
+    #[serde(default)]
+    pub signature_encoding: SignatureEncoding,
+    /// How detached RSA/ECDSA signatures digest the document.
+    #[serde(default)]
+    pub digest_scheme: DigestScheme,
+    /// Custom fields (invoice number, case ID, ...) signed into the envelope, checked
+    /// against the configured `MetadataSchema` (envelope format only).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Also write a clearsigned certificate of signing next to the signature.
+    #[serde(default)]
+    pub completion_certificate: bool,
+    /// Log in with the configured OpenID Connect provider and sign the resulting identity
+    /// into the envelope (envelope format only).
+    #[serde(default)]
+    pub oidc_identity: bool,
+}
+
+/// How RSA and ECDSA signatures digest the signed data (Ed25519 is unaffected).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum DigestScheme {
+    /// The data is hashed once with SHA-256, as `openssl dgst -sha256 -sign` does.
+    #[default]
+    Standard,
+    /// Earlier releases signed the SHA-256 digest as the message, hashing it twice; such
+    /// signatures only verify in sigust. Kept so their detached signatures still verify.
+    Legacy,
+}
+
+/// Byte encoding of ECDSA signatures written to detached signature files.
+/// Verification accepts either encoding.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum EcdsaEncoding {
+    /// Fixed-size `r || s` (64, 96 or 132 bytes for P-256, P-384 or P-521), as produced
+    /// by WebCrypto.
+    #[default]
+    Raw,
+    /// ASN.1 DER `ECDSA-Sig-Value`, as produced by OpenSSL.
+    Der,
+}
+
+/// File encoding of detached signatures. Verification detects the encoding by itself.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureEncoding {
+    /// The signature bytes as they are.
+    #[default]
+    Binary,
+    /// Standard base64 on a single line.
+    Base64,
+    /// Base64 between `-----BEGIN SIGNATURE-----` and `-----END SIGNATURE-----` lines.
+    Pem,
+}
+
+/// Where the signing time recorded in an envelope came from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeSource {
+    /// The signer's own clock; nothing backs it.
+    #[default]
+    LocalClock,
+    /// An RFC 3161 time-stamp over the document digest, stored in the envelope.
+    Tsa,
+}
+
+/// Signature baseline level, named after the ETSI (XAdES/PAdES) baseline profiles.
+/// There is no XML or PDF signing path, so the levels apply to envelope signatures; the
+/// validation-data levels (LT/LTA) need certificates and are not offered.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignatureLevel {
+    /// Signature only.
+    #[default]
+    B,
+    /// Signature plus an RFC 3161 time-stamp over it, proving it existed at that time. The
+    /// TSA has to chain to one of the trusted TSA certificates in the settings.
+    T,
 }
 
 /// Represents the outcome of a signature verification attempt.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")] // Optional: use camelCase for JSON
 pub struct VerificationResult {
     /// Indicates whether the cryptographic verification was successful.
@@ -115,6 +437,51 @@ pub struct VerificationResult {
     /// message or a processing error message.
     #[serde(skip_serializing_if = "Option::is_none")] // Don't include in JSON if None
     pub error_message: Option<String>,
+    /// File attributes bound into the signature that differ from the verified file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binding_mismatches: Vec<FileBindingMismatch>,
+    /// Hash algorithm used for the document digests below (e.g., "sha256").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest_algorithm: Option<String>,
+    /// Digest of the document as read during verification, lowercase hex.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub computed_digest: Option<String>,
+    /// Digest recorded in the signature envelope, if the signature is an envelope.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_digest: Option<String>,
+    /// RFC 3161 time-stamps attached to the envelope, with their check results.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timestamps: Vec<TimestampReport>,
+    /// Signing time recorded in the envelope.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_at: Option<DateTime<Utc>>,
+    /// Source of `signed_at`; `localClock` means the time is only the signer's claim.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_time_source: Option<TimeSource>,
+    /// The detached signature only verified with the legacy double-hash scheme.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub legacy_digest: bool,
+    /// The detached Ed25519 signature is Ed25519ph (over the SHA-512 of the document).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prehashed: bool,
+    /// Custom metadata signed into the envelope.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Second-person approval recorded in the envelope (checked as part of verification).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<SigningApproval>,
+    /// Per-key sequence number recorded in the envelope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    /// Signer identity from an OpenID Connect login, recorded in the envelope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oidc_identity: Option<OidcIdentity>,
+    /// Post-quantum co-signer of a hybrid envelope (its signature is checked as well).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pq_cosigner: Option<PqCosigner>,
+    /// How long reading, hashing and checking took.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<OperationMetrics>,
     // --- Future Extensions ---
     // /// Information extracted from the signer's certificate (if available).
     // pub signer_info: Option<SignerDetails>,
@@ -122,6 +489,1876 @@ pub struct VerificationResult {
     // pub timestamp_info: Option<TimestampDetails>,
 }
 
+/// Timing of a sign or verify operation, e.g. for "signed 2.3 GB in 4.1 s" or to tell a slow
+/// disk from slow key unlocking. Times are in milliseconds.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationMetrics {
+    /// Size of the document read.
+    pub bytes_processed: u64,
+    /// Unlocking the private key, mostly the password KDF. Signing only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kdf_ms: Option<u64>,
+    /// Reading and hashing the document. Detached signatures over the whole document hash
+    /// while signing, so for them this is only the read.
+    pub hash_ms: u64,
+    /// The rest: creating or checking the signature, time-stamps and the output file.
+    pub signature_ms: u64,
+    pub total_ms: u64,
+    /// Document bytes per second over the whole operation, in MiB/s.
+    pub mib_per_second: f64,
+}
+
+/// Result of `sign_document`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningResult {
+    /// Path the signature was written to; can differ from the requested one under the
+    /// collision policy.
+    pub output_path: String,
+    /// Certificate of signing, when one was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate_path: Option<String>,
+    pub metrics: OperationMetrics,
+}
+
+/// Outcome for one signature file of `verify_signatures`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureFileReport {
+    pub signature_path: String,
+    /// Name of the key or contact whose key validated (or is named by) the signature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_name: Option<String>,
+    /// Fingerprint of that key, lowercase hex.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_fingerprint: Option<String>,
+    pub result: VerificationResult,
+}
+
+/// Aggregated report for several signature files over one document.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiVerificationReport {
+    pub document_path: String,
+    /// Number of signature files that verified.
+    pub valid_count: usize,
+    pub signatures: Vec<SignatureFileReport>,
+}
+
+/// A file attribute recorded in the signature that does not match the verified file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileBindingMismatch {
+    /// The attribute: "fileName", "fileSize" or "fileModifiedAt".
+    pub field: String,
+    /// Value recorded at signing time.
+    pub expected: String,
+    /// Value found on the verified file.
+    pub actual: String,
+}
+
+/// Result of checking one RFC 3161 time-stamp attached to a signature envelope.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TimestampReport {
+    pub tsa_url: String,
+    /// Time asserted by the time-stamping authority.
+    pub gen_time: DateTime<Utc>,
+    /// Name of the time-stamping authority from the token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tsa_name: Option<String>,
+    /// End of the TSA certificate's validity period.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate_not_after: Option<DateTime<Utc>>,
+    /// Instant the TSA certificate was judged at (the requested date or the token time).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked_at: Option<DateTime<Utc>>,
+    /// Whether the TSA certificate was revoked at `checked_at`, as far as is known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revocation_status: Option<RevocationStatus>,
+    pub is_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+/// Revocation status of a TSA certificate at the instant it was judged at. Only CRLs that
+/// the time-stamp token carries are consulted; nothing is fetched (no CRL download or OCSP).
+/// A revoked certificate makes the time-stamp invalid.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RevocationStatus {
+    /// No CRL from the issuer covering that instant: the certificate may have been revoked.
+    Unknown,
+    /// A CRL from the issuer, current at that instant, does not list the certificate.
+    NotRevoked,
+}
+
+/// Trust decision recorded for a contact's public key.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TrustLevel {
+    /// The key was imported but its fingerprint has not been confirmed out-of-band.
+    Unverified,
+    /// The fingerprint was confirmed with the owner (e.g., in person via QR exchange).
+    Verified,
+    /// The key must not be trusted (e.g., reported as compromised).
+    Distrusted,
+}
+
+/// A public key belonging to someone else, stored in the contacts/trust store.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Contact {
+    /// Unique identifier for the contact entry.
+    pub contact_id: Uuid,
+    /// Display name of the key owner.
+    pub name: String,
+    /// The signature algorithm of the contact's key.
+    pub algorithm: SignatureAlgorithm,
+    /// The contact's public key encoded in PEM (SPKI) format.
+    pub public_key_pem: String,
+    /// SHA-256 fingerprint of the SPKI DER, hex-encoded.
+    pub fingerprint: String,
+    /// The current trust decision for this key.
+    pub trust: TrustLevel,
+    /// Timestamp (UTC) when the contact was added.
+    pub added_at: DateTime<Utc>,
+    /// Timestamp (UTC) of the last change to this entry (e.g., trust update).
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Summary of importing a signed contacts file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactImportReport {
+    /// Name of the key that signed the file, as recorded in the file.
+    pub signer_name: String,
+    /// SHA-256 fingerprint of the signing key.
+    pub signer_fingerprint: String,
+    /// Whether the signer is one of our own keys or a verified contact. Trust decisions
+    /// from the file are only applied when the signer is trusted.
+    pub signer_trusted: bool,
+    /// Contacts that were not present before the import.
+    pub added: Vec<Uuid>,
+    /// Existing contacts whose trust decision was updated from the file.
+    pub updated: Vec<Uuid>,
+    /// Number of entries left unchanged.
+    pub unchanged: usize,
+}
+
+/// A QR code for sharing one of the user's own public keys with another device.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyExchangeQr {
+    /// The text payload encoded in the QR code.
+    pub payload: String,
+    /// The QR code rendered as an SVG document.
+    pub svg: String,
+    /// SHA-256 fingerprint of the shared key, for side-by-side confirmation.
+    pub fingerprint: String,
+}
+
+/// The decoded contents of a scanned key exchange payload, shown to the user
+/// for fingerprint confirmation before anything is imported.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyExchangePreview {
+    /// Name suggested by the sender (absent for fingerprint-only payloads).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Algorithm of the scanned key (absent for fingerprint-only payloads).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<SignatureAlgorithm>,
+    /// The public key in PEM (SPKI) format (absent for fingerprint-only payloads).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_pem: Option<String>,
+    /// SHA-256 fingerprint computed from the scanned key, or carried by a fingerprint-only payload.
+    pub fingerprint: String,
+    /// ID of an existing contact with the same fingerprint, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub existing_contact_id: Option<Uuid>,
+}
+
+/// User configuration for the opt-in key store sync between devices.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConfig {
+    /// Whether sync is enabled. Sync commands refuse to run when disabled.
+    pub enabled: bool,
+    /// Folder shared between devices (e.g., a Dropbox, Syncthing or mounted WebDAV directory).
+    pub folder: Option<String>,
+    /// Stable identifier of this device, used to name exported delta files.
+    pub device_id: Uuid,
+    /// Timestamp (UTC) of the last successful export from this device.
+    pub last_exported_at: Option<DateTime<Utc>>,
+}
+
+/// How to resolve a key that was changed both locally and on another device.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncConflictPolicy {
+    /// Keep the local entry and ignore the remote change.
+    PreferLocal,
+    /// Replace the local entry with the remote one.
+    PreferRemote,
+    /// Keep the local entry and import the remote one as a new key.
+    KeepBoth,
+}
+
+/// A conflict detected while merging a remote delta, identified by `key_id`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    pub key_id: Uuid,
+    /// Name of the key as stored locally.
+    pub local_name: String,
+    /// The delta file the conflicting change came from.
+    pub source_file: String,
+    /// Human-readable description of how the conflict was resolved.
+    pub resolution: String,
+}
+
+/// Summary of a sync export or import run.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    /// Delta files written (export) or read (import).
+    pub files: Vec<String>,
+    /// Keys added to the local store, or exported as new/changed.
+    pub added: Vec<Uuid>,
+    /// Keys replaced by a newer remote version.
+    pub updated: Vec<Uuid>,
+    /// Keys moved to the trash locally (import) or exported as deletions.
+    pub deleted: Vec<Uuid>,
+    /// Conflicts detected and how they were resolved.
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// Kinds of inconsistencies detected by the key store integrity check.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum StoreIssueKind {
+    /// A file in the key storage directory that no metadata entry refers to.
+    OrphanedFile,
+    /// A metadata entry whose public key file is missing.
+    MissingPublicKey,
+    /// A metadata entry whose encrypted private key file is missing.
+    MissingPrivateKey,
+    /// The stored salt is not valid hex of the expected length.
+    InvalidSalt,
+    /// The encrypted private key file is too short to contain a ciphertext and nonce.
+    MalformedPrivateKey,
+    /// The public key file is not a readable PEM (SPKI) public key.
+    UnreadablePublicKey,
+    /// The public key does not match the algorithm recorded in metadata.
+    AlgorithmMismatch,
+    /// Several metadata entries share the same key ID.
+    DuplicateKeyId,
+}
+
+/// Repair actions that can be applied to a store issue.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum StoreRepairAction {
+    /// Point the metadata entry at a matching orphaned file.
+    Relink,
+    /// Move the affected files to the quarantine directory and drop the metadata entry.
+    Quarantine,
+    /// Permanently remove the affected files and metadata entry.
+    Delete,
+}
+
+/// A single problem found by `check_store`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreIssue {
+    /// Stable identifier of the issue, used to request a repair.
+    pub issue_id: String,
+    pub kind: StoreIssueKind,
+    /// The affected key, if the issue concerns a metadata entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<Uuid>,
+    /// The affected file (relative to the key storage directory), if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Human-readable description of the problem.
+    pub description: String,
+    /// Repair actions applicable to this issue.
+    pub repairs: Vec<StoreRepairAction>,
+}
+
+/// Result of a key store integrity check.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreCheckReport {
+    /// Timestamp (UTC) when the check ran.
+    pub checked_at: DateTime<Utc>,
+    /// Number of metadata entries examined.
+    pub keys_checked: usize,
+    /// Number of files examined in the key storage directory.
+    pub files_checked: usize,
+    pub issues: Vec<StoreIssue>,
+}
+
+/// A repair requested by the user for one issue.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreRepair {
+    pub issue_id: String,
+    pub action: StoreRepairAction,
+}
+
+/// Outcome of applying repairs, followed by a fresh check of the store.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreRepairReport {
+    /// Descriptions of repairs that were applied.
+    pub applied: Vec<String>,
+    /// Repairs that could not be applied, with the reason.
+    pub failed: Vec<String>,
+    /// The state of the store after the repairs.
+    pub report: StoreCheckReport,
+}
+
+/// A file received from another app through the system share sheet, staged for signing.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedItem {
+    /// Handle used to refer to the staged copy in later calls.
+    pub share_id: Uuid,
+    /// File name to show to the user (from the share intent, or derived from the URI).
+    pub display_name: String,
+    /// Size of the staged copy in bytes.
+    pub size: u64,
+    /// SHA-256 of the staged copy, lowercase hex.
+    pub sha256: String,
+    /// When the file was received.
+    pub received_at: DateTime<Utc>,
+}
+
+/// Lifecycle state of a background task.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskState {
+    Running,
+    Completed,
+    Failed,
+    /// The task stopped early after `cancel_task` was called.
+    Cancelled,
+    /// The task reported no progress within its timeout and was stopped by the watchdog;
+    /// `error` says where it got stuck.
+    TimedOut,
+}
+
+/// Snapshot of a background task, returned by `get_task_status` and emitted with
+/// every `task-progress` event.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskStatus {
+    pub task_id: Uuid,
+    /// The operation being run (e.g., "batchSign", "hashDirectory").
+    pub kind: String,
+    pub state: TaskState,
+    /// Units of work finished so far.
+    pub done: u64,
+    /// Total units of work, once known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    /// Short description of the current step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Error message if the task failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Per-item results reported while the task runs (kept on failure or cancellation).
+    pub partial_results: Vec<serde_json::Value>,
+    /// Final result of a completed task.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// When the task last reported progress.
+    pub last_activity_at: DateTime<Utc>,
+    /// The task has not reported anything for a while and may be stuck.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stalled: bool,
+    /// Time without progress after which the task is timed out.
+    pub idle_timeout_seconds: u64,
+}
+
+/// Operation carried out by a resumable batch job.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchJobKind {
+    /// Detached signatures for a list of documents (`sign_documents_batch`).
+    Sign,
+    /// Document / signature pairs checked against the known keys (`verify_documents_batch`).
+    Verify,
+}
+
+/// One document of a batch job.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchJobItem {
+    pub document_path: String,
+    /// The signature to check; required for verification jobs, unused for signing jobs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature_path: Option<String>,
+}
+
+/// A batch job that has not run to the end, returned by `list_batch_jobs`. Jobs interrupted
+/// by a crash, a restart or `cancel_task` are kept until resumed or discarded.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchJobInfo {
+    pub job_id: Uuid,
+    pub kind: BatchJobKind,
+    /// Signing key of a signing job.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<Uuid>,
+    /// Where a signing job writes its signatures.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub total: usize,
+    /// Items processed so far, successfully or not.
+    pub processed: usize,
+    /// Processed items that failed (or, for verification, did not verify).
+    pub failed: usize,
+    /// The background task currently running the job, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<Uuid>,
+}
+
+/// User preferences persisted in `settings.json`. Missing fields fall back to defaults so
+/// settings written by older versions keep loading.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    /// KDF parameters recommended by the last `calibrate_kdf` run on this device.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kdf_calibration: Option<KdfCalibration>,
+    /// Naming and collision handling for files written by commands.
+    pub output: OutputSettings,
+    /// Organization recovery key that new keys can be escrowed to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_key: Option<RecoveryKeyConfig>,
+    /// Rules for custom metadata signed into envelopes.
+    pub metadata_schema: MetadataSchema,
+    /// Files hashed at once by batch operations; unset means half the CPUs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_threads: Option<u32>,
+    /// Which sensitive operations ask for confirmation in a native dialog.
+    pub consent: ConsentSettings,
+    /// Bind new keys to this device unless they are generated as portable.
+    pub bind_keys_to_device: bool,
+    /// Loopback HTTP API for local tools.
+    pub local_api: LocalApiSettings,
+    /// Scheduled re-verification of a folder of signed documents.
+    pub archive_verification: ArchiveVerificationSettings,
+    /// OpenID Connect provider for binding the signer's identity into envelopes.
+    pub oidc: OidcSettings,
+    /// Organization trust bundle fetched on a schedule.
+    pub trust_bundle: TrustBundleSettings,
+    /// The user's own limits on the files that may be signed.
+    pub signable_inputs: SignableInputRules,
+    /// Certificates (PEM) that time-stamping authorities must chain to; time-stamps from
+    /// other TSAs are not trusted.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trusted_tsa_certificates: Vec<String>,
+}
+
+/// Where the organization's trust bundle is fetched from (see `trust_bundle.rs`).
+/// Off by default.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrustBundleSettings {
+    pub enabled: bool,
+    /// HTTPS URL of the signed bundle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Organization key the bundle must be signed with, in PEM (SPKI) format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_public_key_pem: Option<String>,
+    /// Hours between two fetches.
+    pub interval_hours: u32,
+}
+
+impl Default for TrustBundleSettings {
+    fn default() -> Self {
+        TrustBundleSettings {
+            enabled: false,
+            url: None,
+            signer_public_key_pem: None,
+            interval_hours: 24,
+        }
+    }
+}
+
+/// Outcome of the last trust bundle fetch and the bundle currently applied.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustBundleStatus {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_checked_at: Option<DateTime<Utc>>,
+    /// Error of the last fetch; the previously applied bundle stays in effect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    /// `issuedAt` of the applied bundle; older bundles are rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issued_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applied_at: Option<DateTime<Utc>>,
+    /// Keys and certificates of the applied bundle.
+    #[serde(default)]
+    pub key_count: usize,
+    #[serde(default)]
+    pub certificate_count: usize,
+    /// Whether the applied bundle set the metadata schema.
+    #[serde(default)]
+    pub metadata_schema_applied: bool,
+}
+
+/// OpenID Connect provider used at signing time (see `oidc_identity.rs`). Unset by default.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OidcSettings {
+    /// Issuer URL; its discovery document is read from `/.well-known/openid-configuration`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    /// Client ID of a public (native app) client that allows loopback redirects.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Embed the ID token itself, not only its verified claims.
+    pub embed_token: bool,
+}
+
+/// Settings of the loopback HTTP API (see `local_api.rs`). Off by default.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocalApiSettings {
+    pub enabled: bool,
+    /// Port on 127.0.0.1; 0 picks a free one at each start.
+    pub port: u16,
+    /// Bearer token clients must send; created when the API is first enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl Default for LocalApiSettings {
+    fn default() -> Self {
+        LocalApiSettings {
+            enabled: false,
+            port: 47615,
+            token: None,
+        }
+    }
+}
+
+/// Scheduled re-verification of an archive folder (see `archive_verification.rs`).
+/// Off by default.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ArchiveVerificationSettings {
+    pub enabled: bool,
+    /// Folder searched recursively for documents with signatures next to them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    /// Hours between two runs.
+    pub interval_hours: u32,
+}
+
+impl Default for ArchiveVerificationSettings {
+    fn default() -> Self {
+        ArchiveVerificationSettings {
+            enabled: false,
+            folder: None,
+            interval_hours: 24,
+        }
+    }
+}
+
+/// Operations that ask for confirmation (see `consent.rs`). All prompts are on by default.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConsentSettings {
+    /// Exporting master seeds and backing keys up into vault files.
+    pub export_private_key: bool,
+    /// Moving keys to the trash, purging it and deleting master seeds.
+    pub delete_key: bool,
+    /// The first use of each private key after the app starts.
+    pub first_key_use: bool,
+}
+
+impl Default for ConsentSettings {
+    fn default() -> Self {
+        ConsentSettings {
+            export_private_key: true,
+            delete_key: true,
+            first_key_use: true,
+        }
+    }
+}
+
+/// Schema that custom envelope metadata is checked against at signing time.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MetadataSchema {
+    pub fields: Vec<MetadataField>,
+    /// Accept keys that are not listed in `fields`.
+    pub allow_unknown_fields: bool,
+}
+
+impl Default for MetadataSchema {
+    fn default() -> Self {
+        MetadataSchema {
+            fields: Vec::new(),
+            allow_unknown_fields: true,
+        }
+    }
+}
+
+/// One field of a `MetadataSchema`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataField {
+    /// Key of the field in the envelope.
+    pub name: String,
+    /// Display name for forms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub kind: MetadataFieldKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    /// If not empty, the value must be one of these.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_values: Vec<String>,
+}
+
+/// Value type of a `MetadataField`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum MetadataFieldKind {
+    #[default]
+    Text,
+    /// Decimal integer, optionally signed.
+    Integer,
+    /// Calendar date, `YYYY-MM-DD`.
+    Date,
+}
+
+/// Recovery (escrow) public key configured with `set_recovery_key`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryKeyConfig {
+    /// RSA public key, SPKI PEM.
+    pub public_key_pem: String,
+    /// SHA-256 fingerprint of the public key, lowercase hex.
+    pub fingerprint: String,
+    pub configured_at: DateTime<Utc>,
+}
+
+/// How commands name and write their output files.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OutputSettings {
+    /// File name for signatures whose name the app chooses (batch signing, shared files).
+    /// Placeholders: `{name}`, `{stem}`, `{ext}`, `{alg}`, `{date}`.
+    pub signature_file_template: String,
+    /// What to do when an output file already exists.
+    pub collision_policy: CollisionPolicy,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        OutputSettings {
+            signature_file_template: "{name}.sig".to_string(),
+            collision_policy: CollisionPolicy::default(),
+        }
+    }
+}
+
+/// Handling of an output path that already exists.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum CollisionPolicy {
+    /// Fail instead of touching the existing file.
+    Error,
+    /// Write to the first free `name (n).ext` next to it.
+    AutoNumber,
+    /// Replace the existing file.
+    #[default]
+    Overwrite,
+}
+
+/// KDF parameters tuned so that unlocking a key takes about `target_ms` on this device.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KdfCalibration {
+    /// Unlock latency the parameters were tuned for, in milliseconds.
+    pub target_ms: u32,
+    /// Recommended PBKDF2-HMAC-SHA256 iteration count.
+    pub pbkdf2_iterations: u32,
+    /// Measured PBKDF2 time with the recommended iterations, in milliseconds.
+    pub pbkdf2_ms: u64,
+    /// Recommended Argon2id memory cost in KiB.
+    pub argon2_memory_kib: u32,
+    /// Recommended Argon2id time cost (passes over memory).
+    pub argon2_iterations: u32,
+    /// Recommended Argon2id parallelism (lanes).
+    pub argon2_parallelism: u32,
+    /// Measured Argon2id time with the recommended parameters, in milliseconds.
+    pub argon2_ms: u64,
+    pub calibrated_at: DateTime<Utc>,
+}
+
+/// Outcome of a single cryptographic self-test.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestResult {
+    /// Name of the test (e.g., "ed25519-kat").
+    pub name: String,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of a full self-test run. Signing is refused while `passed` is `false`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub results: Vec<SelfTestResult>,
+    pub ran_at: DateTime<Utc>,
+}
+
+/// One entry of the hash-chained audit log.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    /// Position in the log, starting at 0.
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    /// What happened, e.g. `"signDocument"`.
+    pub action: String,
+    /// Action-specific details (paths, key IDs, outcome).
+    pub details: serde_json::Value,
+    /// `hash` of the previous entry (all zeros for the first one).
+    pub prev_hash: String,
+    /// SHA-256 over the canonical JSON of all other fields, lowercase hex.
+    pub hash: String,
+}
+
+/// File format for `export_audit_log`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditExportFormat {
+    Json,
+    Csv,
+}
+
+/// File format for `generate_verification_report` and `export_keycard`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum ReportFormat {
+    Html,
+    Pdf,
+}
+
+/// External tool a verification kit is prepared for (see `export_verification_kit`).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum VerificationTool {
+    Openssl,
+    /// `ssh-keygen -Y verify`; the kit carries a new SSH signature by the same key.
+    SshKeygen,
+    /// `gpgv` / `gpg --verify`; the kit carries an OpenPGP key and a new signature by it.
+    Gpg,
+}
+
+/// Files and commands written by `export_verification_kit`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationKit {
+    /// Folder the kit was written to.
+    pub kit_dir: String,
+    /// File names in the kit folder, including the `VERIFY.txt` instructions.
+    pub files: Vec<String>,
+    /// Shell commands that check the document, to be run in the kit folder.
+    pub commands: Vec<String>,
+}
+
+/// Summary of one re-verification of the archive folder.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveVerificationReport {
+    pub folder: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Whether the run was started by the scheduler rather than by the user.
+    pub scheduled: bool,
+    /// Number of signatures checked.
+    pub checked: usize,
+    /// Signatures with at least one problem; the others passed.
+    pub failures: Vec<ArchiveFailure>,
+}
+
+/// A signature in the archive that no longer checks out.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveFailure {
+    pub document_path: String,
+    pub signature_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_name: Option<String>,
+    pub issues: Vec<ArchiveIssue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+/// Problem found by archive re-verification.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ArchiveIssue {
+    /// The document no longer matches the digest recorded in the signature (bit rot or edits).
+    DocumentChanged,
+    /// The signature does not verify with any known key.
+    SignatureInvalid,
+    /// An attached time-stamp no longer verifies.
+    TimestampInvalid,
+    /// The certificate of an attached time-stamp's authority has expired since.
+    TimestampExpired,
+    /// The signing key belongs to a contact now marked as distrusted (revoked).
+    SignerDistrusted,
+}
+
+/// Result of `verify_audit_log`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditVerificationReport {
+    pub is_valid: bool,
+    /// Number of entries in the log.
+    pub entries: u64,
+    /// First entry that breaks the chain, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_invalid_seq: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+/// Time window for `get_statistics`; a missing bound leaves that side open.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct StatisticsRange {
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Signing and verification counts for one bucket of `UsageStatistics`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageCounts {
+    /// Documents signed.
+    pub signatures: u64,
+    /// Signatures checked, including failed ones.
+    pub verifications: u64,
+    /// Signatures that did not verify.
+    pub failed_verifications: u64,
+}
+
+/// Usage of one key (own, passkey or KMS).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyUsage {
+    pub key_id: Uuid,
+    /// `None` once the key has been purged.
+    pub name: Option<String>,
+    pub algorithm: Option<SignatureAlgorithm>,
+    pub counts: UsageCounts,
+}
+
+/// Usage per signature algorithm.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AlgorithmUsage {
+    /// `None` for operations whose key is unknown, e.g. batch verification.
+    pub algorithm: Option<SignatureAlgorithm>,
+    pub counts: UsageCounts,
+}
+
+/// Usage on one UTC day.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    pub day: NaiveDate,
+    pub counts: UsageCounts,
+}
+
+/// Result of `get_statistics`, aggregated from the audit log.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStatistics {
+    pub range: StatisticsRange,
+    pub totals: UsageCounts,
+    /// Most used first.
+    pub by_key: Vec<KeyUsage>,
+    pub by_algorithm: Vec<AlgorithmUsage>,
+    /// Oldest first; days without activity are left out.
+    pub by_day: Vec<DailyUsage>,
+}
+
+/// One parsed line of the application log.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// UTC time as written in the log (RFC 3339).
+    pub timestamp: String,
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`.
+    pub level: String,
+    /// Module that wrote the entry.
+    pub target: String,
+    pub message: String,
+}
+
+/// Result of saving the key store to, or restoring it from, a key vault file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyVaultReport {
+    /// Keys in the local store (save) or in the vault (restore).
+    pub key_count: usize,
+    /// Records in the vault file after the operation.
+    pub record_count: usize,
+    /// Records appended by this save.
+    pub appended_records: usize,
+    /// Keys written to the local store by this restore.
+    pub restored: Vec<Uuid>,
+}
+
+/// A folder for organizing keys. Folders nest through `parent_id`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyFolder {
+    pub folder_id: Uuid,
+    pub name: String,
+    /// Enclosing folder; `None` for top-level folders.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Outcome of a bulk key operation for one key.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyOperationResult {
+    pub key_id: Uuid,
+    /// File written for this key (exports only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_path: Option<String>,
+    /// Why the operation failed for this key; `None` on success.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Password-based key derivation for `export_key_pair` (PBES2, AES-256-CBC).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Pkcs8Kdf {
+    /// scrypt with OpenSSL's defaults (N = 16384, r = 8, p = 1).
+    #[default]
+    Scrypt,
+    /// PBKDF2-HMAC-SHA256 with the configured iteration count, for older tools.
+    Pbkdf2,
+}
+
+/// Files written by `export_key_pair`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyPairExport {
+    /// Encrypted PKCS#8 PEM of the private key.
+    pub private_key_path: String,
+    /// SPKI PEM of the public key.
+    pub public_key_path: String,
+}
+
+/// A key unlocked for quick signing (see `unlock_key_session`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeySessionInfo {
+    pub key_id: Uuid,
+    pub name: String,
+    /// When the cached key is discarded and the password is needed again.
+    pub expires_at: DateTime<Utc>,
+}
+
+/// What a dropped file appears to be, judged from its content and extension.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FileKind {
+    /// Anything not recognized as one of the formats below.
+    #[default]
+    Document,
+    /// A sigust envelope or raw detached signature.
+    SigustSignature,
+    /// A CMS / PKCS#7 signature (`.p7s`).
+    CmsSignature,
+    /// An OpenPGP signature, armored or binary.
+    PgpSignature,
+    /// A minisign signature (`.minisig`).
+    MinisignSignature,
+    /// A public key (PEM, OpenSSH, OpenPGP, minisign) or an exported contacts file.
+    PublicKey,
+    /// A sigust key vault.
+    BackupArchive,
+}
+
+/// Classification of one dropped file (see `classify_files`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileClassification {
+    pub path: String,
+    /// `None` if the file could not be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<FileKind>,
+    /// Sub-format or algorithm, e.g. "envelope", "detached", "Ed25519", "ssh".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What a pasted text is (see `preview_pasted_text`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PastedTextPreview {
+    pub kind: FileKind,
+    /// Sub-format, e.g. "ssh", "openpgp", "armored".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// The decoded key, for public keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<KeyExchangePreview>,
+}
+
+/// A pasted signature written to a temporary file (see `stage_pasted_signature`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StagedSignature {
+    pub staged_id: Uuid,
+    /// Path of the staged file, to pass to verification.
+    pub path: String,
+    pub kind: FileKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+/// Unverified description of a signature file (see `inspect_signature`).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInspection {
+    pub path: String,
+    pub kind: FileKind,
+    /// Sub-format, e.g. "envelope", "detached", "attached", "armored".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest_algorithm: Option<String>,
+    /// sigust key ID of the signer (envelopes).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_key_id: Option<Uuid>,
+    /// Fingerprint of the signing key as recorded in the signature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_fingerprint: Option<String>,
+    /// Issuer reference: OpenPGP or minisign key ID, CMS issuer and serial number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    /// Name of a matching own key or contact, or the CMS signer certificate subject.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_name: Option<String>,
+    /// Signing time claimed by the signature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_source: Option<TimeSource>,
+    /// Name of the signed document, if recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_name: Option<String>,
+    /// Free-text comment (minisign trusted comment).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Custom metadata signed into an envelope.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Per-key sequence number signed into an envelope.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    pub timestamps: Vec<InspectedTimestamp>,
+    /// Certificates embedded in the signature or its time-stamps.
+    pub certificates: Vec<CertificateSummary>,
+}
+
+/// A time-stamp found in a signature, not checked.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectedTimestamp {
+    /// Time in the token; `None` if the token could not be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gen_time: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tsa_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tsa_url: Option<String>,
+}
+
+/// Identifying fields of an X.509 certificate.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateSummary {
+    pub subject: String,
+    pub issuer: String,
+    /// Serial number, uppercase hex.
+    pub serial_number: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+/// Rules of an enterprise policy file (see `get_enterprise_policy`).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EnterprisePolicy {
+    /// Algorithms keys may use (`SignatureAlgorithm` display names); all when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_algorithms: Option<Vec<String>>,
+    /// Minimum RSA modulus size in bits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_rsa_key_bits: Option<usize>,
+    /// Signatures must carry an RFC 3161 time-stamp.
+    #[serde(default)]
+    pub require_timestamp: bool,
+    /// Private keys may not leave the key store (vaults, backups, sync).
+    #[serde(default)]
+    pub forbid_private_key_export: bool,
+    /// Keys may only be used while the audit log can be written.
+    #[serde(default)]
+    pub require_audit_log: bool,
+    /// Only verification and trust-store commands work; no private keys on this device.
+    #[serde(default)]
+    pub verification_only: bool,
+    /// Limits on the files that may be signed, on top of the user's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signable_inputs: Option<SignableInputRules>,
+}
+
+/// Limits on the files that may be signed (see `signable_inputs.rs`). Extensions are given
+/// without the dot; MIME types may end in `/*`. Nothing is limited by default.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SignableInputRules {
+    /// Largest file that may be signed, in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_file_size: Option<u64>,
+    /// Only files with these extensions may be signed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_extensions: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocked_extensions: Vec<String>,
+    /// Only files of these (detected) MIME types may be signed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_mime_types: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocked_mime_types: Vec<String>,
+    /// Signing an executable or script needs a confirmation that shows its SHA-256 digest.
+    pub confirm_executables: bool,
+}
+
+/// What signing a file would involve (see `preview_signable_input`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignableInputPreview {
+    pub path: String,
+    pub size: u64,
+    /// MIME type detected from the content (and the extension for container formats).
+    pub mime_type: String,
+    /// The file is a native executable or a script.
+    pub executable: bool,
+    /// SHA-256 of the file, lowercase hex.
+    pub sha256: String,
+    /// Rules the file breaks; it cannot be signed while this is not empty.
+    pub violations: Vec<String>,
+    /// Signing it will ask for a confirmation showing `sha256`.
+    pub requires_confirmation: bool,
+}
+
+/// The enterprise policy in effect on this device.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyStatus {
+    /// A policy file is installed (even if it failed to load).
+    pub managed: bool,
+    /// Verification-only mode is in effect, by policy or because of a verification-only
+    /// build. Key generation, import and signing are unavailable.
+    pub verification_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issued_at: Option<DateTime<Utc>>,
+    /// Fingerprint of the key the policy was signed with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<EnterprisePolicy>,
+    /// Why an installed policy could not be loaded; key use is locked until it is fixed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A saved signing preset (see `sign_with_profile`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningProfile {
+    pub profile_id: Uuid,
+    pub name: String,
+    /// Key the profile signs with.
+    pub key_id: Uuid,
+    /// Format, digest scheme, level and time-stamping of the signature.
+    pub options: SigningOptions,
+    /// Signature file name template (placeholders as in `OutputSettings`); the configured
+    /// template when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_template: Option<String>,
+    /// Folder signatures are written to; next to the document when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Fields of a signing profile as edited by the user; `profile_id` is set when updating.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningProfileInput {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_id: Option<Uuid>,
+    pub name: String,
+    pub key_id: Uuid,
+    pub options: SigningOptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+}
+
+/// A signing request: documents digested on one machine, to be signed on another
+/// (see `create_signing_request`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningRequest {
+    pub request_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    /// Context for the signer, e.g. what the documents are for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Bind each document's file name and size into its envelope.
+    #[serde(default)]
+    pub bind_file_name: bool,
+    /// Custom metadata signed into every envelope.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    pub documents: Vec<RequestedDocument>,
+}
+
+/// A document listed in a signing request.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestedDocument {
+    /// File name of the document (no directories).
+    pub name: String,
+    pub size: u64,
+    /// SHA-256 digest, lowercase hex.
+    pub digest: String,
+}
+
+/// Envelope written for one document of a signing request.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedRequestDocument {
+    pub name: String,
+    pub digest: String,
+    pub signature_path: String,
+}
+
+/// A signature from a signature bundle, written next to its original document.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachedSignature {
+    /// Document name as listed in the signing request.
+    pub name: String,
+    pub document_path: String,
+    pub signature_path: String,
+    /// Own key or contact that made the signatures in the bundle.
+    pub signer_name: String,
+}
+
+/// Approval of a signature by the signing key's approver (two-person control), stored in
+/// the envelope. The approver signs the signer's fingerprint, the document digest and
+/// `approved_at`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningApproval {
+    pub approver_key_id: Uuid,
+    /// Approver's full name, or the key name if the key has no identity.
+    pub approver_name: String,
+    pub algorithm: String,
+    /// For offline checks; verifiers in the app use their own copy of the approver key.
+    pub public_key_pem: String,
+    /// SHA-256 fingerprint of the approver's public key (SPKI DER), lowercase hex.
+    pub fingerprint: String,
+    pub approved_at: DateTime<Utc>,
+    /// Approver's signature over the approval statement, standard base64.
+    pub signature: String,
+}
+
+/// The post-quantum key that co-signs a hybrid envelope, stored in its signed attributes
+/// so that the classical signature covers it as well.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PqCosigner {
+    pub key_id: Uuid,
+    pub algorithm: String,
+    /// Lets third parties check the envelope offline; verifiers in the app use their own
+    /// copy of the key with this fingerprint instead.
+    pub public_key_pem: String,
+    /// SHA-256 fingerprint of the co-signer's public key (SPKI DER), lowercase hex.
+    pub fingerprint: String,
+}
+
+/// Who the signer authenticated as with the OpenID Connect provider at signing time.
+/// The claims were checked against the provider's keys when the envelope was made.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcIdentity {
+    pub issuer: String,
+    pub subject: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    /// Organization claim (`org`, `organization` or Google's hosted domain `hd`), if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    pub authenticated_at: DateTime<Utc>,
+    /// The ID token, when embedded; its nonce starts with the signer's key fingerprint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
+
+/// A passkey (resident WebAuthn credential, ES256) registered as a signing key.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyInfo {
+    /// Key ID used in signature envelopes; also the WebAuthn user handle.
+    pub key_id: Uuid,
+    pub name: String,
+    /// WebAuthn credential ID, base64url.
+    pub credential_id: String,
+    /// Relying party ID the credential was created for.
+    pub rp_id: String,
+    /// The credential's public key encoded in PEM (SPKI) format.
+    pub public_key_pem: String,
+    /// SHA-256 fingerprint of the SPKI DER, hex-encoded.
+    pub fingerprint: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A WebAuthn challenge for the webview to pass to the authenticator.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyChallenge {
+    /// Challenge, base64url.
+    pub challenge: String,
+    /// Key ID of the passkey (the user handle when registering).
+    pub key_id: Uuid,
+    /// Credential to ask for when signing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<String>,
+    /// Relying party ID of the credential when signing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rp_id: Option<String>,
+}
+
+/// Cloud key management service holding a `KmsKeyInfo`'s private key.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum KmsProvider {
+    /// AWS KMS; the key is referenced by its key or alias ARN.
+    Aws,
+    /// Google Cloud KMS; the key is referenced by its crypto key version name.
+    Gcp,
+    /// Azure Key Vault or Managed HSM; the key is referenced by its versioned key identifier.
+    Azure,
+    /// HashiCorp Vault's transit secrets engine; the key is referenced by its name.
+    Vault,
+}
+
+/// How to log in to Vault. The credentials themselves come from the environment.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum VaultAuthMethod {
+    /// A token in `VAULT_TOKEN`.
+    Token,
+    /// AppRole login with `VAULT_ROLE_ID` and `VAULT_SECRET_ID`.
+    AppRole,
+}
+
+/// Where a Vault transit key lives and how to reach it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultTransitSettings {
+    /// Vault address, e.g. `https://vault.example.com:8200`.
+    pub address: String,
+    /// Mount path of the transit engine.
+    #[serde(default = "default_vault_transit_mount")]
+    pub mount: String,
+    pub auth_method: VaultAuthMethod,
+    /// Mount path of the AppRole auth method.
+    #[serde(default = "default_vault_approle_mount")]
+    pub auth_mount: String,
+    /// Vault Enterprise namespace, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Key version signed with; set to the latest version when the key is added.
+    #[serde(default)]
+    pub key_version: u32,
+}
+
+fn default_vault_transit_mount() -> String {
+    "transit".to_string()
+}
+
+fn default_vault_approle_mount() -> String {
+    "approle".to_string()
+}
+
+/// A signing key kept in a cloud KMS (see `kms.rs`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KmsKeyInfo {
+    /// Key ID used in signature envelopes.
+    pub key_id: Uuid,
+    pub name: String,
+    pub provider: KmsProvider,
+    /// The key in the provider's own notation (ARN, resource name, key identifier URL or
+    /// transit key name).
+    pub key_reference: String,
+    /// Connection settings for `KmsProvider::Vault` keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<VaultTransitSettings>,
+    pub algorithm: SignatureAlgorithm,
+    /// Public key fetched from the KMS when the key was added, in PEM (SPKI) format.
+    pub public_key_pem: String,
+    /// SHA-256 fingerprint of the SPKI DER, hex-encoded.
+    pub fingerprint: String,
+    pub added_at: DateTime<Utc>,
+}
+
+/// What the webview needs to ask a security key for the secret that unwraps a key.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUnwrapRequest {
+    pub key_id: Uuid,
+    /// Credential of the passkey on the security key.
+    pub credential_id: String,
+    /// Relying party ID of the credential.
+    pub rp_id: String,
+    /// Salt to evaluate with the `prf` (hmac-secret) extension, base64url.
+    pub salt: String,
+}
+
+/// A master seed that signing keys are derived from.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MasterSeedInfo {
+    pub seed_id: Uuid,
+    pub name: String,
+    /// Short hash identifying the seed; the same seed always has the same fingerprint.
+    pub fingerprint: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Where a derived key comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyDerivation {
+    /// Fingerprint of the master seed.
+    pub seed_fingerprint: String,
+    /// Hardened derivation path, e.g. `m/1'/0'`.
+    pub path: String,
+}
+
+/// A key derived from a master seed, as listed for recovery.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivedKeyRecord {
+    pub name: String,
+    pub algorithm: SignatureAlgorithm,
+    /// Hardened derivation path, e.g. `m/1'/0'`.
+    pub path: String,
+    /// SHA-256 fingerprint of the public key.
+    pub fingerprint: String,
+}
+
+/// An update or plugin whose publisher signature checked out.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedArtifact {
+    pub file_name: String,
+    pub size: u64,
+    /// SHA-256 of the artifact, hex.
+    pub sha256: String,
+    /// minisign key ID of the publisher key.
+    pub key_id: String,
+    /// Signed comment from the signature file.
+    pub trusted_comment: String,
+    /// Signing time from the trusted comment, when present.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_at: Option<DateTime<Utc>>,
+}
+
+/// A short signed statement kept in the app.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedNote {
+    pub note_id: Uuid,
+    pub text: String,
+    pub key_id: Uuid,
+    /// Name of the signing key when the note was signed.
+    pub key_name: String,
+    pub algorithm: SignatureAlgorithm,
+    /// Public key of the signer, so the note can be verified after the key is gone.
+    pub public_key_pem: String,
+    /// SHA-256 of the note text (UTF-8), hex.
+    pub digest: String,
+    pub signed_at: DateTime<Utc>,
+}
+
+/// Signed list of the files in a directory, for drift detection (see
+/// `directory_baseline.rs`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryBaseline {
+    /// Absolute path of the directory; one baseline is kept per directory.
+    pub directory: String,
+    pub key_id: Uuid,
+    /// Name of the signing key when the baseline was signed.
+    pub key_name: String,
+    pub algorithm: SignatureAlgorithm,
+    /// Public key of the signer, so the baseline can be checked after the key is gone.
+    pub public_key_pem: String,
+    pub file_count: usize,
+    pub total_bytes: u64,
+    /// SHA-256 of the canonical file list, hex.
+    pub digest: String,
+    pub signed_at: DateTime<Utc>,
+}
+
+/// Changes in a directory since its signed baseline (`check_drift`). Paths are relative to
+/// the directory, with `/` separators.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftReport {
+    pub baseline: DirectoryBaseline,
+    /// Whether the baseline's signature still verifies. If not, the changes below are
+    /// relative to a baseline that cannot be trusted.
+    pub baseline_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    pub unchanged: usize,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Kind of operation a `ConsentRequest` is about.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConsentAction {
+    ExportPrivateKey,
+    DeleteKey,
+    FirstKeyUse,
+    /// Signing an executable; always asked when the signable input rules require it.
+    SignExecutable,
+}
+
+/// An operation waiting for the user's confirmation, emitted with `consent-requested`.
+/// Confirm it with `confirm_consent`, then retry the operation.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsentRequest {
+    pub request_id: Uuid,
+    pub action: ConsentAction,
+    pub title: String,
+    /// What is about to happen, as shown in the dialog.
+    pub description: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Why the store was locked.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum StoreLockReason {
+    /// The machine is going to sleep, or woke up from it.
+    Suspend,
+    /// The user's session was locked.
+    SessionLock,
+    Screensaver,
+}
+
+/// Payload of the `store://locked` event: key sessions ended and cached unlock material
+/// was dropped.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreLocked {
+    pub reason: StoreLockReason,
+    pub locked_at: DateTime<Utc>,
+}
+
+/// Summary of `import_config`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigImportReport {
+    pub profiles_added: usize,
+    /// Local profiles replaced by one with the same ID or name.
+    pub profiles_updated: usize,
+    /// Names of imported profiles whose key is not on this device; they fail until a key
+    /// with that ID is added.
+    pub profiles_without_key: Vec<String>,
+    /// Whether new keys are now escrowed to a different recovery key (or to none).
+    pub recovery_key_changed: bool,
+}
+
+/// A sign/verify interoperability vector (see `generate_test_vectors`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TestVector {
+    pub name: String,
+    /// Algorithm display name, e.g. "ECDSA-P256-SHA256".
+    pub algorithm: String,
+    pub format: SignatureFormat,
+    /// How RSA and ECDSA digest the message (detached format; envelopes use `legacy`).
+    pub digest_scheme: DigestScheme,
+    /// Encoding of ECDSA signatures in the detached format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ecdsa_encoding: Option<EcdsaEncoding>,
+    pub public_key_pem: String,
+    /// The signed message, hex.
+    pub message_hex: String,
+    /// Detached: the signature, standard base64. Envelope: the envelope JSON.
+    pub signature: String,
+    /// The signature is expected to be rejected.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub invalid: bool,
+}
+
+/// Outcome of checking one `TestVector`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TestVectorCheck {
+    pub name: String,
+    /// Whether verification agreed with the vector's expectation.
+    pub passed: bool,
+    /// Why verification failed (or, for invalid vectors, why it rejected the signature).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What a benchmark measured.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BenchmarkOperation {
+    KeyGeneration,
+    /// Deriving a wrapping key from a password.
+    Kdf,
+    Sign,
+    Verify,
+}
+
+/// Timing of one benchmarked operation.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    pub operation: BenchmarkOperation,
+    /// The algorithm, or for KDFs the function and its parameters.
+    pub subject: String,
+    /// Size of the signed document in bytes, for sign and verify.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_size: Option<u64>,
+    /// How often the operation ran.
+    pub runs: u32,
+    /// Mean time of one run in milliseconds.
+    pub mean_ms: f64,
+    pub ops_per_second: f64,
+    /// Document throughput, for sign and verify.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mib_per_second: Option<f64>,
+}
+
+/// Results of `run_benchmarks` on this machine.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReport {
+    pub results: Vec<BenchmarkResult>,
+    /// Hardware threads available, for comparing machines.
+    pub threads: usize,
+    /// Total time the run took, in milliseconds.
+    pub duration_ms: u64,
+    pub ran_at: DateTime<Utc>,
+}
+
+/// Display name and description of a signature algorithm.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AlgorithmDisplayName {
+    pub algorithm: SignatureAlgorithm,
+    pub name: String,
+    pub description: String,
+}
+
+/// Display name and description of a signature format.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatDisplayName {
+    pub format: SignatureFormat,
+    pub name: String,
+    pub description: String,
+}
+
+/// Display names in one language, from `get_display_names`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayNames {
+    /// Language the names are in, e.g. "en"; falls back to English for unknown locales.
+    pub locale: String,
+    pub algorithms: Vec<AlgorithmDisplayName>,
+    pub formats: Vec<FormatDisplayName>,
+}
+
+/// What a `sigust://` link asks for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DeepLinkAction {
+    Sign,
+    Verify,
+}
+
+/// A checked `sigust://` link, handed to the window to start the signing or verification
+/// flow. Nothing has been signed or verified yet.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepLinkRequest {
+    pub request_id: Uuid,
+    pub action: DeepLinkAction,
+    /// Documents to sign or verify; absolute paths of existing files.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    /// Expected SHA-256 of the document, lowercase hex. The window compares it with the
+    /// digest of the document it signs or verifies, which the user picks if `files` is empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Signature file to verify.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_path: Option<String>,
+    /// Key suggested by the link; the user can still pick another.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<Uuid>,
+    pub received_at: DateTime<Utc>,
+}
+
+/// State of the loopback HTTP API, for the settings page.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalApiStatus {
+    pub enabled: bool,
+    pub running: bool,
+    /// Base URL while running, e.g. `http://127.0.0.1:47615`.
+    pub url: Option<String>,
+    pub token: Option<String>,
+    /// File holding the URL and token while running, for tools to pick up.
+    pub discovery_path: Option<String>,
+}
+
+/// Browser a native messaging host manifest is installed for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum NativeMessagingBrowser {
+    Chrome,
+    Chromium,
+    Edge,
+    Firefox,
+}
+
+/// Result of checking a clearsigned text block (see `verify_clearsigned_text`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearsignedVerification {
+    /// The signed text, unescaped, with `\n` line endings.
+    pub text: String,
+    /// Fingerprint of the signing key as recorded in the signature.
+    pub key_fingerprint: String,
+    /// Name of the matching own key or contact.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_name: Option<String>,
+    pub result: VerificationResult,
+}
+
+/// Result of checking an S/MIME signed e-mail (see `verify_smime_email`).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SmimeVerification {
+    /// The signature checks with the signer certificate and covers this exact body.
+    pub signature_valid: bool,
+    /// The body no longer matches the digest the signer signed.
+    pub body_altered: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_subject: Option<String>,
+    /// E-mail address in the signer certificate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_email: Option<String>,
+    /// Address in the `From` header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Whether `from` is the signer certificate's address; `None` if either is missing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_matches_signer: Option<bool>,
+    /// `Subject` header as written in the message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Signing time claimed by the signer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_at: Option<DateTime<Utc>>,
+    /// Certificates from the signer upwards, as far as each link checks.
+    pub chain: Vec<CertificateSummary>,
+    /// The chain ends in a self-signed certificate.
+    pub chain_complete: bool,
+    /// A certificate in the chain belongs to a verified contact or an own key.
+    pub chain_trusted: bool,
+    /// Name of that contact or key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trusted_by: Option<String>,
+    /// Reasons the signature, chain or sender should not be relied on.
+    pub problems: Vec<String>,
+}
+
 // --- Internal Metadata Struct ---
 // This struct is used internally by the backend to manage key storage details.
 // It is NOT directly exposed to the frontend via Tauri commands. Marked `pub(crate)`.
@@ -136,11 +2373,93 @@ pub(crate) struct KeyMetadata {
     /// Path (relative to the key storage directory) to the PEM-encoded public key file.
     pub(crate) public_key_pem_path: String,
     /// Path (relative to the key storage directory) to the file containing the encrypted private key.
+    /// Empty for public-only entries.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub(crate) encrypted_private_key_path: String,
-    /// The signature algorithm associated with this key (matches `SignatureAlgorithm::to_string()`).
-    pub(crate) algorithm: String,
+    /// The signature algorithm associated with this key.
+    pub(crate) algorithm: SignatureAlgorithm,
     /// Timestamp (UTC) when the key was generated or imported.
     pub(crate) created_at: DateTime<Utc>,
     /// The salt used for deriving the encryption key from the password, hex-encoded.
-    pub(crate) salt_hex: String,
+    /// Only present for legacy key files; newer files carry the salt in their header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) salt_hex: Option<String>,
+    /// Identity of the key owner, if provided.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) identity: Option<SignerIdentity>,
+    /// When the key was moved to the trash; trashed keys cannot be used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) trashed_at: Option<DateTime<Utc>>,
+    /// Folder the key is filed under; `None` for the root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) folder_id: Option<Uuid>,
+    /// Free-form labels, kept sorted and unique.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tags: Vec<String>,
+    /// Path (relative to the key storage directory) to the escrow copy sealed to the
+    /// organization's recovery key, if one was made.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) escrow_path: Option<String>,
+    /// Key whose holder must approve every signature made with this key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) approver_key_id: Option<Uuid>,
+    /// ML-DSA key pair linked to this classical key for hybrid signatures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) pq_key_id: Option<Uuid>,
+    /// Passkey whose hmac-secret output is mixed into the wrapping key of the private key file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) token_passkey_id: Option<Uuid>,
+    /// Master seed and path the key was derived from; re-deriving gives the same key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) derivation: Option<KeyDerivation>,
+    /// Stricter unlock rule; `None` lets the key be held in a key session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) unlock_policy: Option<KeyUnlockPolicy>,
+    /// The wrapping key of the private key file includes this device's secret.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) device_bound: bool,
+    /// KDF the private key file is wrapped with; `None` for files written before it was
+    /// recorded (PBKDF2, parameters in the file header).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) kdf: Option<KdfParameters>,
+    /// Public-only entries have no private key file and cannot sign.
+    #[serde(default, skip_serializing_if = "KeyKind::is_key_pair")]
+    pub(crate) kind: KeyKind,
+    /// SHA-256 of the SPKI DER public key, hex-encoded. Recorded at startup for entries
+    /// stored by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) fingerprint: Option<String>,
+    /// OpenSSH-style fingerprint of the public key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ssh_fingerprint: Option<String>,
+    /// Modulus size of RSA keys, in bits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) key_bits: Option<usize>,
+}
+
+impl From<&KeyMetadata> for KeyInfo {
+    fn from(meta: &KeyMetadata) -> Self {
+        KeyInfo {
+            key_id: meta.key_id,
+            name: meta.name.clone(),
+            algorithm: meta.algorithm.clone(),
+            created_at: meta.created_at,
+            identity: meta.identity.clone(),
+            trashed_at: meta.trashed_at,
+            folder_id: meta.folder_id,
+            tags: meta.tags.clone(),
+            escrowed: meta.escrow_path.is_some(),
+            approver_key_id: meta.approver_key_id,
+            pq_key_id: meta.pq_key_id,
+            token_passkey_id: meta.token_passkey_id,
+            derivation: meta.derivation.clone(),
+            unlock_policy: meta.unlock_policy.clone(),
+            device_bound: meta.device_bound,
+            kdf: meta.kdf.clone(),
+            kind: meta.kind,
+            fingerprint: meta.fingerprint.clone(),
+            ssh_fingerprint: meta.ssh_fingerprint.clone(),
+            key_bits: meta.key_bits,
+        }
+    }
 }
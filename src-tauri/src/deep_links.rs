@@ -0,0 +1,242 @@
+// src-tauri/src/deep_links.rs
+// `sigust://` links, so other applications and web pages can hand work to sigust:
+//
+//   sigust://sign?file=<path>[&file=<path>...][&sha256=<hex>][&key=<key id>]
+//   sigust://verify?[file=<path>][&signature=<path>][&sha256=<hex>][&key=<key id>]
+//
+// Paths are absolute paths or `file:` URLs of existing files; `sha256` pins the expected
+// digest of the document. A link only starts a flow: it is checked, queued and emitted to
+// the window (`deep-link://request`), where the user picks the key, enters the password and
+// confirms. Nothing is signed or verified from the link alone, since any web page can open
+// one. On Windows and Linux a link starts a second process, which hands it to the running
+// instance through the single-instance plugin.
+use crate::audit;
+use crate::crypto_types::{DeepLinkAction, DeepLinkRequest};
+use crate::policy::verification_only;
+use crate::tray::show_main_window;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{Emitter, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+use uuid::Uuid;
+
+pub const DEEP_LINK_SCHEME: &str = "sigust";
+/// Event carrying each accepted `DeepLinkRequest`.
+pub const DEEP_LINK_EVENT: &str = "deep-link://request";
+const MAX_LINK_FILES: usize = 100;
+/// Requests not yet taken by the window, oldest first.
+const MAX_PENDING_REQUESTS: usize = 20;
+
+static PENDING_REQUESTS: Mutex<Vec<DeepLinkRequest>> = Mutex::new(Vec::new());
+
+// --- Tauri Commands ---
+
+/// Returns and clears the queued requests. The window calls this when it starts, for
+/// links that opened the app, and can skip requests it already got through the event by
+/// `requestId`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn take_deep_link_requests() -> Result<Vec<DeepLinkRequest>, String> {
+    log::info!("Taking queued deep link requests");
+    PENDING_REQUESTS
+        .lock()
+        .map(|mut pending| std::mem::take(&mut *pending))
+        .map_err(|_| "Deep link queue lock poisoned".to_string())
+}
+
+// --- Helper Functions ---
+
+/// Starts handling links: the one the app was launched with and any opened later.
+pub fn start_deep_links(app_handle: &tauri::AppHandle) {
+    // 安装包会注册协议；Linux（AppImage）和 Windows 调试运行时需要自行注册
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = app_handle.deep_link().register_all() {
+        log::warn!(
+            "Failed to register the {}:// scheme: {}",
+            DEEP_LINK_SCHEME,
+            e
+        );
+    }
+    let event_handle = app_handle.clone();
+    app_handle
+        .deep_link()
+        .on_open_url(move |event| receive_urls(&event_handle, event.urls()));
+    match app_handle.deep_link().get_current() {
+        Ok(Some(urls)) => receive_urls(app_handle, urls),
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to read the launch link: {}", e),
+    }
+}
+
+fn receive_urls(app_handle: &tauri::AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        // 链接内容来自外部，日志中不记录查询参数
+        log::info!(
+            "Received deep link: {}://{}",
+            url.scheme(),
+            link_action(&url)
+        );
+        let request = match parse_deep_link(&url) {
+            Ok(request) => request,
+            Err(e) => {
+                log::warn!("Rejected deep link: {:#}", e);
+                continue;
+            }
+        };
+        audit::record(
+            app_handle,
+            "receiveDeepLink",
+            json!({
+                "requestId": request.request_id,
+                "action": request.action,
+                "files": request.files,
+                "signaturePath": request.signature_path,
+            }),
+        );
+        if let Err(e) = queue_request(request.clone()) {
+            log::error!("Failed to queue deep link request: {:?}", e);
+        }
+        show_main_window(app_handle);
+        if let Err(e) = app_handle.emit(DEEP_LINK_EVENT, &request) {
+            log::warn!("Failed to emit deep link request: {}", e);
+        }
+    }
+}
+
+// 队列满时丢弃最旧的请求
+fn queue_request(request: DeepLinkRequest) -> Result<()> {
+    let mut pending = PENDING_REQUESTS
+        .lock()
+        .map_err(|_| anyhow!("Deep link queue lock poisoned"))?;
+    if pending.len() >= MAX_PENDING_REQUESTS {
+        pending.remove(0);
+    }
+    pending.push(request);
+    Ok(())
+}
+
+/// Checks a link and turns it into a request for the window.
+fn parse_deep_link(url: &Url) -> Result<DeepLinkRequest> {
+    if url.scheme() != DEEP_LINK_SCHEME {
+        bail!("Not a {}:// link", DEEP_LINK_SCHEME);
+    }
+    let action = match link_action(url) {
+        "sign" => DeepLinkAction::Sign,
+        "verify" => DeepLinkAction::Verify,
+        other => bail!("Unknown link action '{}'", other),
+    };
+    if action == DeepLinkAction::Sign && verification_only() {
+        bail!("Signing is disabled in this installation");
+    }
+
+    let mut request = DeepLinkRequest {
+        request_id: Uuid::new_v4(),
+        action,
+        files: Vec::new(),
+        sha256: None,
+        signature_path: None,
+        key_id: None,
+        received_at: Utc::now(),
+    };
+    for (name, value) in url.query_pairs() {
+        match name.as_ref() {
+            "file" => {
+                if request.files.len() >= MAX_LINK_FILES {
+                    bail!("A link can name at most {} files", MAX_LINK_FILES);
+                }
+                request.files.push(link_file(&value)?);
+            }
+            "signature" if action == DeepLinkAction::Verify => {
+                request.signature_path = Some(link_file(&value)?);
+            }
+            "sha256" => {
+                let digest = value.to_ascii_lowercase();
+                if digest.len() != 64 || !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    bail!("sha256 must be 64 hex digits");
+                }
+                request.sha256 = Some(digest);
+            }
+            "key" => {
+                request.key_id = Some(Uuid::parse_str(&value).context("Invalid key ID")?);
+            }
+            other => bail!("Unknown link parameter '{}'", other),
+        }
+    }
+
+    if request.files.is_empty() && request.sha256.is_none() && request.signature_path.is_none() {
+        bail!("The link names no document, digest or signature");
+    }
+    if request.sha256.is_some() && request.files.len() > 1 {
+        bail!("A digest can only be given for a single document");
+    }
+    Ok(request)
+}
+
+// 动作在主机部分（sigust://sign?...），也接受 sigust:sign?... 形式
+fn link_action(url: &Url) -> &str {
+    url.host_str().unwrap_or(url.path()).trim_matches('/')
+}
+
+// 链接中的路径必须是已存在文件的绝对路径（或 file: URL）
+fn link_file(value: &str) -> Result<String> {
+    let path = match Url::parse(value) {
+        Ok(url) if url.scheme() == "file" => url
+            .to_file_path()
+            .map_err(|_| anyhow!("Invalid file URL: {}", value))?,
+        _ => PathBuf::from(value),
+    };
+    if !path.is_absolute() {
+        bail!("Link paths must be absolute: {}", value);
+    }
+    if !path.is_file() {
+        bail!("No such file: {}", value);
+    }
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sign_and_verify_links() {
+        let document = std::env::temp_dir().join("sigust-deep-link-test.txt");
+        std::fs::write(&document, b"document").unwrap();
+        let document_url = Url::from_file_path(&document).unwrap();
+        let digest = "AB".repeat(32);
+
+        let sign = Url::parse(&format!(
+            "sigust://sign?file={}&sha256={}",
+            document_url, digest
+        ))
+        .unwrap();
+        let request = parse_deep_link(&sign).unwrap();
+        assert_eq!(request.action, DeepLinkAction::Sign);
+        assert_eq!(request.files, vec![document.to_string_lossy().into_owned()]);
+        assert_eq!(request.sha256, Some(digest.to_lowercase()));
+
+        let verify = Url::parse(&format!("sigust:verify?sha256={}", digest)).unwrap();
+        assert_eq!(
+            parse_deep_link(&verify).unwrap().action,
+            DeepLinkAction::Verify
+        );
+
+        // 签名文件只用于验证；相对路径、未知参数和动作都被拒绝
+        for link in [
+            format!("sigust://sign?signature={}", document_url),
+            "sigust://verify?file=relative.txt".to_string(),
+            format!("sigust://verify?file={}&run=1", document_url),
+            format!("sigust://delete?file={}", document_url),
+            "sigust://verify".to_string(),
+        ] {
+            assert!(
+                parse_deep_link(&Url::parse(&link).unwrap()).is_err(),
+                "{}",
+                link
+            );
+        }
+        std::fs::remove_file(&document).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+// src-tauri/src/device_binding.rs
+// Device-bound private key files. A random secret is kept in the OS keystore (Keychain on
+// macOS/iOS, Credential Manager/DPAPI on Windows, the Secret Service/libsecret on Linux)
+// and mixed into the wrapping key of bound keys, so a copied `.key.enc` file cannot be
+// decrypted on another machine even with the right password.
+//
+// Binding is a setting: when it is on, new keys are bound unless they are generated as
+// portable. Bound keys are left out of sync, and a key recovered from escrow is portable
+// again. Losing the keystore entry (OS reinstall, new user profile) makes bound keys
+// unusable, so keep an escrow copy or a vault backup of keys that matter.
+use crate::audit;
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use aead::OsRng;
+use anyhow::{anyhow, bail, Context, Result};
+use rsa::rand_core::RngCore;
+use serde_json::json;
+use zeroize::Zeroizing;
+
+const KEYRING_SERVICE: &str = "sigust";
+const KEYRING_ACCOUNT: &str = "device-key-binding";
+const DEVICE_SECRET_LEN: usize = 32;
+
+// --- Tauri Commands ---
+
+/// Turns binding of new keys to this device on or off. Turning it on creates the device
+/// secret, so it fails where no OS keystore is available. Keys already stored keep their
+/// binding either way.
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_device_binding(app_handle: tauri::AppHandle, enabled: bool) -> Result<bool, String> {
+    log::info!("Setting device binding of new keys to {}", enabled);
+    _update_device_binding(&app_handle, enabled)
+        .map_err(|e| {
+            log::error!("Failed to update device binding: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|enabled| {
+            audit::record(
+                &app_handle,
+                "updateDeviceBinding",
+                json!({ "enabled": enabled }),
+            )
+        })
+}
+
+fn _update_device_binding(app_handle: &tauri::AppHandle, enabled: bool) -> Result<bool> {
+    if enabled {
+        device_secret_or_create()?;
+    }
+    update_settings(app_handle, |settings| {
+        settings.bind_keys_to_device = enabled
+    })
+    .map(|settings| settings.bind_keys_to_device)
+}
+
+// --- Helper Functions ---
+
+/// Whether a new key is bound to this device: binding is on and it was not asked for a
+/// portable key.
+pub(crate) fn binds_new_key(app_handle: &tauri::AppHandle, portable: bool) -> Result<bool> {
+    Ok(!portable && read_settings(&get_settings_path(app_handle)?)?.bind_keys_to_device)
+}
+
+/// This device's secret for unwrapping a bound key.
+pub(crate) fn device_secret() -> Result<Zeroizing<Vec<u8>>> {
+    match keyring_entry()?.get_secret() {
+        Ok(secret) => Ok(Zeroizing::new(secret)),
+        Err(keyring::Error::NoEntry) => {
+            bail!("This key is bound to another device and cannot be used here")
+        }
+        Err(e) => Err(anyhow!(e).context("Failed to read the device secret")),
+    }
+}
+
+/// This device's secret, created on first use.
+pub(crate) fn device_secret_or_create() -> Result<Zeroizing<Vec<u8>>> {
+    let entry = keyring_entry()?;
+    match entry.get_secret() {
+        Ok(secret) => return Ok(Zeroizing::new(secret)),
+        Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(anyhow!(e).context("Failed to read the device secret")),
+    }
+    let mut secret = Zeroizing::new(vec![0u8; DEVICE_SECRET_LEN]);
+    OsRng.fill_bytes(&mut secret);
+    entry
+        .set_secret(&secret)
+        .context("Failed to store the device secret in the OS keystore")?;
+    log::info!("Created the device secret for binding keys");
+    Ok(secret)
+}
+
+// 系统密钥库中的条目；Android 上 keyring 只有内存存储，不能用于绑定
+fn keyring_entry() -> Result<keyring::Entry> {
+    if cfg!(target_os = "android") {
+        bail!("Binding keys to the device is not available on this platform");
+    }
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_ACCOUNT).context("Failed to open the OS keystore")
+}
@@ -0,0 +1,147 @@
+// src-tauri/src/dialogs.rs
+// Signing and verification flows that open the native file dialogs from the backend, so
+// the webview never needs filesystem access to the chosen paths.
+//
+// The commands are async: blocking dialogs must not run on the main thread, which is
+// where synchronous commands execute. A cancelled dialog ends the flow with `None`.
+use crate::crypto_types::{SigningOptions, SigningResult, VerificationResult};
+use crate::key_management::find_key_metadata;
+use crate::output_paths::signature_file_name;
+use crate::signing::{sign_document, verify_signature};
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+use tauri_plugin_dialog::{DialogExt, FilePath};
+use uuid::Uuid;
+
+const SIGNATURE_EXTENSIONS: [&str; 2] = ["sig", "json"];
+
+// --- Tauri Commands ---
+
+/// Asks for a document, then for where to save its signature, and signs it.
+/// Returns the signature path and timings, or `None` if a dialog was cancelled.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn pick_document_and_sign(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    password: String,
+    options: SigningOptions,
+    approver_password: Option<String>,
+) -> Result<Option<SigningResult>, String> {
+    log::info!("Picking a document to sign with key ID {}", key_id);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    let paths = pick_sign_paths(&app_handle, key_id).map_err(|e| {
+        log::error!("Failed to choose files to sign: {:?}", e);
+        e.to_string()
+    })?;
+    let Some((document_path, output_path)) = paths else {
+        log::info!("Signing cancelled in file dialog");
+        return Ok(None);
+    };
+    sign_document(
+        app_handle,
+        document_path.to_string_lossy().into_owned(),
+        key_id,
+        password,
+        output_path.to_string_lossy().into_owned(),
+        options,
+        approver_password,
+    )
+    .map(Some)
+}
+
+/// Asks for a document and its signature file, and verifies them with the given key.
+/// Returns `None` if a dialog was cancelled.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn pick_files_and_verify(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+) -> Result<Option<VerificationResult>, String> {
+    log::info!("Picking files to verify with key ID {}", key_id);
+    let paths = pick_verify_paths(&app_handle).map_err(|e| {
+        log::error!("Failed to choose files to verify: {:?}", e);
+        e.to_string()
+    })?;
+    let Some((document_path, signature_path)) = paths else {
+        log::info!("Verification cancelled in file dialog");
+        return Ok(None);
+    };
+    verify_signature(
+        app_handle,
+        document_path.to_string_lossy().into_owned(),
+        signature_path.to_string_lossy().into_owned(),
+        Some(key_id),
+        None,
+        None,
+    )
+    .map(Some)
+}
+
+// --- Helper Functions ---
+
+// 打开文档选择框，再打开签名保存框（默认放在文档旁边，按命名模板取名）
+fn pick_sign_paths(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+) -> Result<Option<(PathBuf, PathBuf)>> {
+    let algorithm = find_key_metadata(app_handle, key_id)?.algorithm;
+    let Some(document) = app_handle
+        .dialog()
+        .file()
+        .set_title("Choose a document to sign")
+        .blocking_pick_file()
+    else {
+        return Ok(None);
+    };
+    let document_path = into_path(document)?;
+
+    let mut save = app_handle
+        .dialog()
+        .file()
+        .set_title("Save signature as")
+        .add_filter("Signature", &SIGNATURE_EXTENSIONS)
+        .set_file_name(signature_file_name(app_handle, &document_path, &algorithm)?);
+    if let Some(dir) = document_path.parent() {
+        save = save.set_directory(dir);
+    }
+    let Some(output) = save.blocking_save_file() else {
+        return Ok(None);
+    };
+    Ok(Some((document_path, into_path(output)?)))
+}
+
+// 依次选择文档和签名文件
+fn pick_verify_paths(app_handle: &tauri::AppHandle) -> Result<Option<(PathBuf, PathBuf)>> {
+    let Some(document) = app_handle
+        .dialog()
+        .file()
+        .set_title("Choose the signed document")
+        .blocking_pick_file()
+    else {
+        return Ok(None);
+    };
+    let document_path = into_path(document)?;
+
+    let mut pick = app_handle
+        .dialog()
+        .file()
+        .set_title("Choose the signature file")
+        .add_filter("Signature", &SIGNATURE_EXTENSIONS);
+    if let Some(dir) = document_path.parent() {
+        pick = pick.set_directory(dir);
+    }
+    let Some(signature) = pick.blocking_pick_file() else {
+        return Ok(None);
+    };
+    Ok(Some((document_path, into_path(signature)?)))
+}
+
+// 对话框在移动端可能返回 content URI，这些流程只处理本地路径
+fn into_path(file: FilePath) -> Result<PathBuf> {
+    let description = file.to_string();
+    file.into_path()
+        .ok()
+        .filter(|path| path.is_absolute())
+        .ok_or_else(|| anyhow!("Not a local file: {}", description))
+}
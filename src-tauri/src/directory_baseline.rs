@@ -0,0 +1,388 @@
+// src-tauri/src/directory_baseline.rs
+// File-integrity baselines: `create_directory_baseline` hashes every file in a directory
+// and signs the list (relative path, size, SHA-256), and `check_drift` later hashes the
+// directory again and reports files added, removed or modified since. The canonical JSON
+// of the list is the document: its SHA-256 goes into an ordinary signature envelope, which
+// is stored with the list and the signer's public key in `directory_baselines.json`, one
+// baseline per directory. Signing a new baseline replaces the old one, e.g. after the
+// reported changes were reviewed.
+use crate::audit;
+use crate::crypto_types::{DirectoryBaseline, DriftReport, TimeSource};
+use crate::envelope::{
+    canonical_json, SignatureEnvelope, SignedAttributes, DIGEST_SHA256, ENVELOPE_FORMAT,
+    ENVELOPE_VERSION,
+};
+use crate::hashing::{collect_files, hash_files, relative_display};
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, find_key_metadata, get_app_data_file,
+    read_private_key_der, read_public_key_pem,
+};
+use crate::policy::check_timestamping;
+use crate::signature_sequence::next_signature_sequence;
+use crate::signing::check_envelope_attributes;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+const BASELINES_FILENAME: &str = "directory_baselines.json";
+
+/// One file of a baseline.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct BaselineFile {
+    /// Path relative to the directory, with `/` separators.
+    path: String,
+    size: u64,
+    sha256: String,
+}
+
+/// A baseline as stored, with its file list and envelope.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredBaseline {
+    #[serde(flatten)]
+    baseline: DirectoryBaseline,
+    files: Vec<BaselineFile>,
+    envelope: SignatureEnvelope,
+}
+
+// --- Tauri Commands ---
+
+/// Signs the current contents of `directory` as its baseline, replacing any earlier one.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn create_directory_baseline(
+    app_handle: tauri::AppHandle,
+    directory: String,
+    key_id: Uuid,
+    password: String,
+) -> Result<DirectoryBaseline, String> {
+    log::info!(
+        "Creating baseline of directory '{}' with key ID {}",
+        directory,
+        key_id
+    );
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    check_timestamping(false).map_err(|e| e.to_string())?;
+    _create_directory_baseline(&app_handle, &directory, key_id, &password)
+        .map_err(|e| {
+            log::error!("Failed to create directory baseline: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|baseline| {
+            audit::record(
+                &app_handle,
+                "createDirectoryBaseline",
+                json!({
+                    "directory": baseline.directory,
+                    "keyId": key_id,
+                    "fileCount": baseline.file_count,
+                    "digest": baseline.digest,
+                }),
+            )
+        })
+}
+
+fn _create_directory_baseline(
+    app_handle: &tauri::AppHandle,
+    directory: &str,
+    key_id: Uuid,
+    password: &str,
+) -> Result<DirectoryBaseline> {
+    let root = canonical_directory(directory)?;
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let algorithm = metadata.algorithm.clone();
+    let public_key_pem = read_public_key_pem(app_handle, &metadata)?;
+    let public_key_der = decode_public_key_pem(&public_key_pem)?;
+    let private_key_der = Zeroizing::new(read_private_key_der(app_handle, &metadata, password)?);
+
+    let files = scan_directory(app_handle, &root)?;
+    let directory = root.to_string_lossy().into_owned();
+    let digest = list_digest(&directory, &files)?;
+    let attributes = SignedAttributes {
+        format: ENVELOPE_FORMAT.to_string(),
+        version: ENVELOPE_VERSION,
+        algorithm: algorithm.to_string(),
+        key_id,
+        key_fingerprint: compute_fingerprint(&public_key_der),
+        digest_algorithm: DIGEST_SHA256.to_string(),
+        document_digest: digest.clone(),
+        signed_at: Utc::now(),
+        time_source: TimeSource::LocalClock,
+        time_token: None,
+        file_name: None,
+        file_size: None,
+        file_modified_at: None,
+        previous_envelope: None,
+        metadata: BTreeMap::new(),
+        approval: None,
+        sequence: Some(next_signature_sequence(app_handle, key_id)?),
+        oidc_identity: None,
+        pq_cosigner: None,
+    };
+    let envelope = SignatureEnvelope::seal(&attributes, &private_key_der)?;
+
+    let baseline = DirectoryBaseline {
+        directory,
+        key_id,
+        key_name: metadata.name,
+        algorithm,
+        public_key_pem,
+        file_count: files.len(),
+        total_bytes: files.iter().map(|f| f.size).sum(),
+        digest,
+        signed_at: attributes.signed_at,
+    };
+    let path = get_baselines_path(app_handle)?;
+    let mut baselines = read_baselines(&path)?;
+    baselines.retain(|b| b.baseline.directory != baseline.directory);
+    baselines.push(StoredBaseline {
+        baseline: baseline.clone(),
+        files,
+        envelope,
+    });
+    write_baselines(&path, &baselines)?;
+    Ok(baseline)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_directory_baselines(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<DirectoryBaseline>, String> {
+    log::info!("Listing directory baselines");
+    get_baselines_path(&app_handle)
+        .and_then(|path| read_baselines(&path))
+        .map(|baselines| baselines.into_iter().map(|b| b.baseline).collect())
+        .map_err(|e| {
+            log::error!("Failed to list directory baselines: {:?}", e);
+            e.to_string()
+        })
+}
+
+/// Hashes `directory` again and compares it with its signed baseline.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn check_drift(
+    app_handle: tauri::AppHandle,
+    directory: String,
+) -> Result<DriftReport, String> {
+    log::info!("Checking directory '{}' for drift", directory);
+    _check_drift(&app_handle, &directory)
+        .map_err(|e| {
+            log::error!("Failed to check drift: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|report| {
+            audit::record(
+                &app_handle,
+                "checkDrift",
+                json!({
+                    "directory": report.baseline.directory,
+                    "baselineValid": report.baseline_valid,
+                    "added": report.added.len(),
+                    "removed": report.removed.len(),
+                    "modified": report.modified.len(),
+                }),
+            )
+        })
+}
+
+fn _check_drift(app_handle: &tauri::AppHandle, directory: &str) -> Result<DriftReport> {
+    let root = canonical_directory(directory)?;
+    let stored = find_baseline(app_handle, &root)?;
+    let error_message = verify_baseline(&stored)?;
+    if let Some(message) = &error_message {
+        log::warn!("Baseline of {:?} failed verification: {}", root, message);
+    }
+    let current = scan_directory(app_handle, &root)?;
+    let (added, removed, modified, unchanged) = compare(&stored.files, &current);
+    Ok(DriftReport {
+        baseline: stored.baseline,
+        baseline_valid: error_message.is_none(),
+        error_message,
+        added,
+        removed,
+        modified,
+        unchanged,
+        checked_at: Utc::now(),
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_directory_baseline(
+    app_handle: tauri::AppHandle,
+    directory: String,
+) -> Result<(), String> {
+    log::info!("Deleting baseline of directory '{}'", directory);
+    _delete_directory_baseline(&app_handle, &directory)
+        .map_err(|e| {
+            log::error!("Failed to delete directory baseline: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|_| {
+            audit::record(
+                &app_handle,
+                "deleteDirectoryBaseline",
+                json!({ "directory": directory }),
+            )
+        })
+}
+
+fn _delete_directory_baseline(app_handle: &tauri::AppHandle, directory: &str) -> Result<()> {
+    let path = get_baselines_path(app_handle)?;
+    let mut baselines = read_baselines(&path)?;
+    let count = baselines.len();
+    // 目录可能已被删除，此时按记录的路径匹配
+    let directory = canonical_directory(directory)
+        .map(|root| root.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| directory.to_string());
+    baselines.retain(|b| b.baseline.directory != directory);
+    if baselines.len() == count {
+        bail!("No baseline for directory {}", directory);
+    }
+    write_baselines(&path, &baselines)
+}
+
+// --- Helper Functions ---
+
+// 规范化目录路径，使同一目录总是对应同一条基线
+fn canonical_directory(directory: &str) -> Result<PathBuf> {
+    let root = fs::canonicalize(directory)
+        .with_context(|| format!("Directory not found: {}", directory))?;
+    if !root.is_dir() {
+        bail!("Not a directory: {}", directory);
+    }
+    Ok(root)
+}
+
+fn scan_directory(app_handle: &tauri::AppHandle, root: &Path) -> Result<Vec<BaselineFile>> {
+    let paths = collect_files(root)?;
+    let path_refs: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+    let digests = hash_files(app_handle, &path_refs)?;
+    Ok(paths
+        .iter()
+        .zip(digests)
+        .map(|(path, (size, sha256))| BaselineFile {
+            path: relative_display(root, path),
+            size,
+            sha256,
+        })
+        .collect())
+}
+
+// 目录路径与文件列表的规范化 JSON 的 SHA-256
+fn list_digest(directory: &str, files: &[BaselineFile]) -> Result<String> {
+    let value = json!({ "directory": directory, "files": files });
+    Ok(hex::encode(Sha256::digest(canonical_json(&value)?)))
+}
+
+fn find_baseline(app_handle: &tauri::AppHandle, root: &Path) -> Result<StoredBaseline> {
+    let directory = root.to_string_lossy();
+    read_baselines(&get_baselines_path(app_handle)?)?
+        .into_iter()
+        .find(|b| b.baseline.directory == directory)
+        .ok_or_else(|| anyhow!("No baseline for directory {}", directory))
+}
+
+/// Checks the stored list against the envelope; returns why it fails, if it does.
+fn verify_baseline(stored: &StoredBaseline) -> Result<Option<String>> {
+    let algorithm = &stored.baseline.algorithm;
+    let public_key_der = decode_public_key_pem(&stored.baseline.public_key_pem)?;
+    let attributes = stored.envelope.attributes()?;
+    let digest = list_digest(&stored.baseline.directory, &stored.files)?;
+    let mismatch = check_envelope_attributes(&attributes, algorithm, &public_key_der, &digest)?;
+    Ok(match mismatch {
+        Some(message) => Some(message),
+        None => stored
+            .envelope
+            .verify_signature(algorithm, &public_key_der)?
+            .err()
+            .map(|e| format!("Signature is invalid: {}", e)),
+    })
+}
+
+// 按相对路径比较两个列表：新增、删除、内容或大小改变
+fn compare(
+    baseline: &[BaselineFile],
+    current: &[BaselineFile],
+) -> (Vec<String>, Vec<String>, Vec<String>, usize) {
+    let before: BTreeMap<&str, &BaselineFile> =
+        baseline.iter().map(|f| (f.path.as_str(), f)).collect();
+    let after: BTreeMap<&str, &BaselineFile> =
+        current.iter().map(|f| (f.path.as_str(), f)).collect();
+    let (mut added, mut modified, mut unchanged) = (Vec::new(), Vec::new(), 0);
+    for (path, file) in &after {
+        match before.get(path) {
+            None => added.push(path.to_string()),
+            Some(old) if old != file => modified.push(path.to_string()),
+            Some(_) => unchanged += 1,
+        }
+    }
+    let removed = before
+        .keys()
+        .filter(|path| !after.contains_key(*path))
+        .map(|path| path.to_string())
+        .collect();
+    (added, removed, modified, unchanged)
+}
+
+// 获取目录基线文件路径
+fn get_baselines_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, BASELINES_FILENAME)
+}
+
+fn read_baselines(path: &Path) -> Result<Vec<StoredBaseline>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read directory baselines: {:?}", path))?;
+    serde_json::from_str(&content).context("Failed to parse directory baselines JSON")
+}
+
+fn write_baselines(path: &Path, baselines: &[StoredBaseline]) -> Result<()> {
+    let content = serde_json::to_string_pretty(baselines)
+        .context("Failed to serialize directory baselines")?;
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write directory baselines: {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_added_removed_and_modified_files() {
+        let file = |path: &str, sha256: &str| BaselineFile {
+            path: path.to_string(),
+            size: 3,
+            sha256: sha256.to_string(),
+        };
+        let baseline = [
+            file("a.txt", "aa"),
+            file("b.txt", "bb"),
+            file("c/d.txt", "dd"),
+        ];
+        let current = [
+            file("a.txt", "aa"),
+            file("c/d.txt", "ee"),
+            file("e.txt", "ff"),
+        ];
+        let (added, removed, modified, unchanged) = compare(&baseline, &current);
+        assert_eq!(added, ["e.txt"]);
+        assert_eq!(removed, ["b.txt"]);
+        assert_eq!(modified, ["c/d.txt"]);
+        assert_eq!(unchanged, 1);
+
+        let digest = list_digest("/data", &baseline).unwrap();
+        assert_ne!(digest, list_digest("/data", &current).unwrap());
+        assert_ne!(digest, list_digest("/other", &baseline).unwrap());
+    }
+}
@@ -0,0 +1,185 @@
+// src-tauri/src/display_names.rs
+// Text for showing algorithms and signature formats. DTOs carry only the identifiers
+// (`SignatureAlgorithm`, `SignatureFormat`); the UI looks up names and descriptions here,
+// in English or Chinese, instead of parsing or printing `Display` strings.
+use crate::crypto_types::{
+    AlgorithmDisplayName, DisplayNames, FormatDisplayName, SignatureAlgorithm, SignatureFormat,
+};
+
+/// Languages with translations; the first is the fallback.
+const LOCALES: [&str; 2] = ["en", "zh"];
+
+// --- Tauri Commands ---
+
+/// Returns display names for `locale` (a BCP 47 tag such as "zh-CN"; English if omitted or
+/// not translated).
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_display_names(locale: Option<String>) -> DisplayNames {
+    let locale = resolve_locale(locale.as_deref());
+    log::debug!("Getting display names for locale {}", locale);
+    let algorithms = [
+        SignatureAlgorithm::Ed25519,
+        SignatureAlgorithm::EcdsaP256Sha256,
+        SignatureAlgorithm::EcdsaP384Sha384,
+        SignatureAlgorithm::EcdsaP521Sha512,
+        SignatureAlgorithm::RsaPkcs1Sha256,
+        SignatureAlgorithm::MlDsa65,
+    ];
+    let formats = [
+        SignatureFormat::Envelope,
+        SignatureFormat::Hybrid,
+        SignatureFormat::Detached,
+    ];
+    DisplayNames {
+        locale: locale.to_string(),
+        algorithms: algorithms
+            .into_iter()
+            .map(|algorithm| {
+                let (name, description) = algorithm_text(&algorithm, locale);
+                AlgorithmDisplayName {
+                    algorithm,
+                    name: name.to_string(),
+                    description: description.to_string(),
+                }
+            })
+            .collect(),
+        formats: formats
+            .into_iter()
+            .map(|format| {
+                let (name, description) = format_text(&format, locale);
+                FormatDisplayName {
+                    format,
+                    name: name.to_string(),
+                    description: description.to_string(),
+                }
+            })
+            .collect(),
+    }
+}
+
+// --- Helper Functions ---
+
+// 取语言标签的主语言部分（"zh-Hans-CN" -> "zh"），没有翻译时用英文
+fn resolve_locale(locale: Option<&str>) -> &'static str {
+    let language = locale
+        .and_then(|tag| tag.split(['-', '_']).next())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    LOCALES
+        .into_iter()
+        .find(|l| *l == language)
+        .unwrap_or(LOCALES[0])
+}
+
+fn algorithm_text(algorithm: &SignatureAlgorithm, locale: &str) -> (&'static str, &'static str) {
+    match (algorithm, locale) {
+        (SignatureAlgorithm::Ed25519, "zh") => (
+            "Ed25519",
+            "现代、快速的 Edwards 曲线签名，密钥很小。除非验证方要求 RSA 或 P-256，推荐使用。",
+        ),
+        (SignatureAlgorithm::Ed25519, _) => (
+            "Ed25519",
+            "Modern, fast Edwards-curve signatures with small keys. Recommended unless a \
+             verifier requires RSA or P-256.",
+        ),
+        (SignatureAlgorithm::EcdsaP256Sha256, "zh") => (
+            "ECDSA P-256（SHA-256）",
+            "NIST 曲线，密钥和签名较小；多数现代工具、通行密钥和硬件令牌都支持。",
+        ),
+        (SignatureAlgorithm::EcdsaP256Sha256, _) => (
+            "ECDSA P-256 (SHA-256)",
+            "NIST curve with small keys and signatures, supported by most modern tools, \
+             passkeys and hardware tokens.",
+        ),
+        (SignatureAlgorithm::EcdsaP384Sha384, "zh") => (
+            "ECDSA P-384（SHA-384）",
+            "更高安全强度的 NIST 曲线，常见于政府和企业的 PKI 要求（如 CNSA）。",
+        ),
+        (SignatureAlgorithm::EcdsaP384Sha384, _) => (
+            "ECDSA P-384 (SHA-384)",
+            "Stronger NIST curve, often required by government and enterprise PKI policies \
+             such as CNSA.",
+        ),
+        (SignatureAlgorithm::EcdsaP521Sha512, "zh") => (
+            "ECDSA P-521（SHA-512）",
+            "强度最高的 NIST 曲线，签名较大、速度较慢，只在验证方要求时使用。",
+        ),
+        (SignatureAlgorithm::EcdsaP521Sha512, _) => (
+            "ECDSA P-521 (SHA-512)",
+            "Strongest NIST curve, with larger and slower signatures. Use it when a verifier \
+             requires it.",
+        ),
+        (SignatureAlgorithm::RsaPkcs1Sha256, "zh") => (
+            "RSA 2048（PKCS#1 v1.5，SHA-256）",
+            "兼容旧的工具和系统。密钥和签名较大，生成密钥较慢。",
+        ),
+        (SignatureAlgorithm::RsaPkcs1Sha256, _) => (
+            "RSA 2048 (PKCS#1 v1.5, SHA-256)",
+            "Works with older tools and systems. Larger keys and signatures, and slow key \
+             generation.",
+        ),
+        (SignatureAlgorithm::MlDsa65, "zh") => (
+            "ML-DSA-65（后量子）",
+            "FIPS 204 后量子签名，可抵御量子计算机的攻击。公钥约 2 KB、签名约 3.3 KB，\
+             OpenSSH 和 OpenPGP 尚不支持。",
+        ),
+        (SignatureAlgorithm::MlDsa65, _) => (
+            "ML-DSA-65 (post-quantum)",
+            "FIPS 204 post-quantum signatures that resist attacks by quantum computers. \
+             Public keys are about 2 KB and signatures about 3.3 KB; OpenSSH and OpenPGP \
+             do not support them yet.",
+        ),
+    }
+}
+
+fn format_text(format: &SignatureFormat, locale: &str) -> (&'static str, &'static str) {
+    match (format, locale) {
+        (SignatureFormat::Envelope, "zh") => (
+            "签名信封",
+            "JSON 文件，包含签名以及签名者、文档摘要、签名时间，和可选的时间戳与元数据。",
+        ),
+        (SignatureFormat::Envelope, _) => (
+            "Signature envelope",
+            "A JSON file holding the signature with the signer, document digest and signing \
+             time, plus optional time-stamps and metadata.",
+        ),
+        (SignatureFormat::Hybrid, "zh") => (
+            "混合签名信封",
+            "由签名密钥和与之关联的 ML-DSA 后量子密钥共同签名的信封，两个签名都有效才算通过。",
+        ),
+        (SignatureFormat::Hybrid, _) => (
+            "Hybrid signature envelope",
+            "An envelope signed by the key and by the ML-DSA post-quantum key linked to it; \
+             it is only valid if both signatures are.",
+        ),
+        (SignatureFormat::Detached, "zh") => (
+            "分离签名",
+            "只有签名本身，保存在单独的文件中，可用 OpenSSL 等其他工具校验。",
+        ),
+        (SignatureFormat::Detached, _) => (
+            "Detached signature",
+            "Only the signature bytes, in a separate file that other tools such as OpenSSL \
+             can check.",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_locales_and_reads_legacy_algorithm_names() {
+        assert_eq!(resolve_locale(Some("zh-Hans-CN")), "zh");
+        assert_eq!(resolve_locale(Some("de_DE")), "en");
+        assert_eq!(resolve_locale(None), "en");
+
+        // 旧版本在元数据中保存的是 Display 字符串
+        let legacy: SignatureAlgorithm = serde_json::from_str("\"ECDSA-P256-SHA256\"").unwrap();
+        assert_eq!(legacy, SignatureAlgorithm::EcdsaP256Sha256);
+        assert_eq!(
+            serde_json::to_string(&legacy).unwrap(),
+            "\"ecdsaP256Sha256\""
+        );
+    }
+}
@@ -0,0 +1,276 @@
+// src-tauri/src/dual_control.rs
+// Two-person control for high-value keys. A key can name a second local key as its
+// approver; from then on it only signs through `sign_document` in the envelope format, and
+// only when the approver's passphrase is given as well. The approver key signs a statement
+// over the signer's fingerprint and the document digest; the statement is stored in the
+// envelope and both key IDs go to the audit log. Verification checks the statement with
+// the verifier's own copy of the approver key (a local key or contact), never with the
+// key carried in the envelope, which anyone could have made.
+//
+// Every other use of such a key's private half (detached signatures, batches, sessions,
+// signing requests, exports) is refused, since it would bypass the approval.
+use crate::audit;
+use crate::crypto_types::{DigestScheme, KeyInfo, KeyMetadata, SigningApproval};
+use crate::envelope::canonical_json;
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, find_key_metadata, get_metadata_path,
+    read_metadata, read_private_key_der, read_private_key_der_approved, read_public_key_pem,
+    write_metadata,
+};
+use crate::signing::{sign_bytes, verify_bytes, SignerCandidate};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+const APPROVAL_FORMAT: &str = "sigust-approval";
+
+// --- Tauri Commands ---
+
+/// Turns two-person control on (`approver_key_id` set) or off (`None`) for a key. Both the
+/// key owner and the approver confirm with their passphrases; to change the approver, turn
+/// the control off first.
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_dual_control(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    password: String,
+    approver_key_id: Option<Uuid>,
+    approver_password: String,
+) -> Result<KeyInfo, String> {
+    log::info!(
+        "Setting two-person approval for key ID {} to {:?}",
+        key_id,
+        approver_key_id
+    );
+    if password.is_empty() || approver_password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _set_dual_control(
+        &app_handle,
+        key_id,
+        &password,
+        approver_key_id,
+        &approver_password,
+    )
+    .map_err(|e| {
+        log::error!("Failed to set two-person approval for {}: {:?}", key_id, e);
+        e.to_string()
+    })
+    .inspect(|_| {
+        audit::record(
+            &app_handle,
+            "setDualControl",
+            json!({ "keyId": key_id, "approverKeyId": approver_key_id }),
+        )
+    })
+}
+
+fn _set_dual_control(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    password: &str,
+    approver_key_id: Option<Uuid>,
+    approver_password: &str,
+) -> Result<KeyInfo> {
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let confirming_approver = match (metadata.approver_key_id, approver_key_id) {
+        (Some(current), Some(new)) if current != new => {
+            bail!("Turn off two-person approval before choosing a different approver")
+        }
+        (Some(current), _) => current,
+        (None, Some(new)) => new,
+        (None, None) => bail!(
+            "Key '{}' does not require two-person approval",
+            metadata.name
+        ),
+    };
+
+    // 密钥所有者和审批者都要确认
+    read_private_key_der_approved(app_handle, &metadata, password)
+        .context("The key owner's passphrase is not correct")?;
+    let approver = find_approver(app_handle, &metadata, confirming_approver)?;
+    read_private_key_der(app_handle, &approver, approver_password)
+        .context("The approver's passphrase is not correct")?;
+
+    let metadata_path = get_metadata_path(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+    let entry = all_metadata
+        .iter_mut()
+        .find(|m| m.key_id == key_id)
+        .ok_or_else(|| anyhow!("Key with ID {} not found", key_id))?;
+    entry.approver_key_id = approver_key_id;
+    let info = KeyInfo::from(&*entry);
+    write_metadata(&metadata_path, &all_metadata)?;
+    Ok(info)
+}
+
+// --- Helper Functions ---
+
+/// Refuses keys under two-person control; used wherever a private key is decrypted
+/// without an approval.
+pub(crate) fn check_single_control(metadata: &KeyMetadata) -> Result<()> {
+    if metadata.approver_key_id.is_some() {
+        bail!(
+            "Key '{}' requires two-person approval; sign in the envelope format with the \
+             approver's passphrase",
+            metadata.name
+        );
+    }
+    Ok(())
+}
+
+/// Has the approver of `metadata` sign off on signing `document_digest`. Returns `None`
+/// for keys without two-person control.
+pub(crate) fn approve_signing(
+    app_handle: &tauri::AppHandle,
+    metadata: &KeyMetadata,
+    key_fingerprint: &str,
+    document_digest: &str,
+    approver_password: Option<&str>,
+) -> Result<Option<SigningApproval>> {
+    let Some(approver_key_id) = metadata.approver_key_id else {
+        return Ok(None);
+    };
+    let approver_password = approver_password.filter(|p| !p.is_empty()).ok_or_else(|| {
+        anyhow!(
+            "Key '{}' requires two-person approval; enter the approver's passphrase",
+            metadata.name
+        )
+    })?;
+    let approver = find_approver(app_handle, metadata, approver_key_id)?;
+    let algorithm = approver.algorithm.clone();
+    let public_key_pem = read_public_key_pem(app_handle, &approver)?;
+    let public_key_der = decode_public_key_pem(&public_key_pem)?;
+    let private_key_der = Zeroizing::new(
+        read_private_key_der(app_handle, &approver, approver_password)
+            .context("The approver's passphrase is not correct")?,
+    );
+
+    let approved_at = Utc::now();
+    let statement = approval_statement(key_fingerprint, document_digest, approved_at)?;
+    let signature = sign_bytes(
+        &algorithm,
+        &private_key_der,
+        &statement,
+        DigestScheme::Standard,
+    )?;
+    Ok(Some(SigningApproval {
+        approver_key_id,
+        approver_name: approver
+            .identity
+            .and_then(|identity| identity.full_name)
+            .unwrap_or(approver.name),
+        algorithm: algorithm.to_string(),
+        public_key_pem,
+        fingerprint: compute_fingerprint(&public_key_der),
+        approved_at,
+        signature: BASE64.encode(signature),
+    }))
+}
+
+/// Checks an approval found in an envelope signed by `key_fingerprint`; the approver must
+/// be one of `trusted_keys` (own keys and contacts that are not distrusted).
+pub(crate) fn verify_approval(
+    approval: &SigningApproval,
+    key_fingerprint: &str,
+    document_digest: &str,
+    trusted_keys: &[SignerCandidate],
+) -> Result<()> {
+    if approval.fingerprint == key_fingerprint {
+        bail!("The signer approved their own signature");
+    }
+    let approver = trusted_keys
+        .iter()
+        .find(|key| !key.distrusted && key.fingerprint == approval.fingerprint)
+        .ok_or_else(|| {
+            anyhow!(
+                "Approver key {} is neither one of your keys nor a contact",
+                approval.fingerprint
+            )
+        })?;
+    let signature = BASE64
+        .decode(&approval.signature)
+        .context("Failed to decode approval signature")?;
+    verify_bytes(
+        &approver.algorithm,
+        &approver.public_key_der,
+        &approval_statement(key_fingerprint, document_digest, approval.approved_at)?,
+        &signature,
+        DigestScheme::Standard,
+    )?
+    .map_err(|_| anyhow!("The approval signature is not valid"))
+}
+
+// 审批者必须是另一把、自身不受双人控制的本地密钥
+fn find_approver(
+    app_handle: &tauri::AppHandle,
+    metadata: &KeyMetadata,
+    approver_key_id: Uuid,
+) -> Result<KeyMetadata> {
+    if approver_key_id == metadata.key_id {
+        bail!("A key cannot approve its own signatures");
+    }
+    let approver = find_key_metadata(app_handle, approver_key_id)
+        .with_context(|| format!("Approver of key '{}' is not available", metadata.name))?;
+    if approver.approver_key_id.is_some() {
+        bail!("The approver key itself requires two-person approval");
+    }
+    Ok(approver)
+}
+
+// 审批者签名的内容：签名者指纹、文档摘要和审批时间
+fn approval_statement(
+    key_fingerprint: &str,
+    document_digest: &str,
+    approved_at: DateTime<Utc>,
+) -> Result<Vec<u8>> {
+    canonical_json(&json!({
+        "format": APPROVAL_FORMAT,
+        "keyFingerprint": key_fingerprint,
+        "documentDigest": document_digest,
+        "approvedAt": approved_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_types::SignatureAlgorithm;
+    use crate::self_tests::known_answer_key_pair;
+
+    #[test]
+    fn approvals_need_a_known_approver() {
+        let algorithm = SignatureAlgorithm::Ed25519;
+        let (private_der, public_der) = known_answer_key_pair(&algorithm).unwrap();
+        let fingerprint = compute_fingerprint(&public_der);
+        let approved_at = Utc::now();
+        let statement = approval_statement("signer", "00", approved_at).unwrap();
+        let approval = SigningApproval {
+            approver_key_id: Uuid::nil(),
+            approver_name: "Bob".to_string(),
+            algorithm: algorithm.to_string(),
+            public_key_pem: String::new(),
+            fingerprint: fingerprint.clone(),
+            approved_at,
+            signature: BASE64.encode(
+                sign_bytes(&algorithm, &private_der, &statement, DigestScheme::Standard).unwrap(),
+            ),
+        };
+        let approver = |distrusted: bool| SignerCandidate {
+            name: "Bob".to_string(),
+            fingerprint: fingerprint.clone(),
+            algorithm: algorithm.clone(),
+            public_key_der: public_der.clone(),
+            distrusted,
+        };
+
+        assert!(verify_approval(&approval, "signer", "00", &[approver(false)]).is_ok());
+        assert!(verify_approval(&approval, "signer", "11", &[approver(false)]).is_err());
+        assert!(verify_approval(&approval, "signer", "00", &[]).is_err());
+        assert!(verify_approval(&approval, "signer", "00", &[approver(true)]).is_err());
+    }
+}
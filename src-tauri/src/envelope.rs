@@ -0,0 +1,674 @@
+// src-tauri/src/envelope.rs
+// Signature envelopes: a JSON document carrying the signed attributes (algorithm, signer
+// key, document digest, ...) next to the signature over them.
+//
+// The signature covers the canonical encoding of the `signed` object, not the bytes of
+// the file, so envelopes can be pretty-printed or re-serialized freely. The canonical
+// form follows RFC 8785 (JCS) for the values we allow in signed data: object keys sorted
+// by UTF-16 code units, no insignificant whitespace, minimal string escaping and integers
+// only (floating point numbers are rejected because their text form is ambiguous).
+//
+// Hybrid envelopes carry a second, post-quantum (ML-DSA) signature over the same canonical
+// attributes. The attributes name the co-signer and use their own format string, so the
+// classical signature breaks if the post-quantum one is stripped, and releases that do not
+// know hybrid envelopes reject them instead of checking only one signature. The co-signer's
+// public key travels along for offline checks, but verifiers use their own copy.
+use crate::crypto_types::{
+    DigestScheme, FileBindingMismatch, OidcIdentity, PqCosigner, SignatureAlgorithm,
+    SigningApproval, TimeSource, TimestampReport,
+};
+use crate::key_management::compute_fingerprint;
+use crate::passkeys::verify_assertion;
+use crate::signing::{sign_bytes, verify_bytes};
+use crate::timestamp::{check_timestamp_token, request_timestamp, verify_timestamp_token};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use uuid::Uuid;
+use x509_cert::Certificate;
+
+pub const ENVELOPE_FORMAT: &str = "sigust-envelope";
+/// Format of envelopes signed with both a classical and a post-quantum key.
+pub const HYBRID_ENVELOPE_FORMAT: &str = "sigust-hybrid-envelope";
+pub const ENVELOPE_VERSION: u32 = 1;
+/// Digest algorithm used for `documentDigest`.
+pub const DIGEST_SHA256: &str = "sha256";
+
+/// Attributes covered by an envelope signature.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedAttributes {
+    pub format: String,
+    pub version: u32,
+    pub algorithm: String,
+    pub key_id: Uuid,
+    /// SHA-256 fingerprint of the signer's public key (SPKI DER), lowercase hex.
+    pub key_fingerprint: String,
+    pub digest_algorithm: String,
+    /// Digest of the signed document, lowercase hex.
+    pub document_digest: String,
+    pub signed_at: DateTime<Utc>,
+    /// Where `signed_at` came from; envelopes without this field used the local clock.
+    #[serde(default)]
+    pub time_source: TimeSource,
+    /// RFC 3161 token over the document digest whose time is `signed_at`, standard base64.
+    /// Present when `time_source` is the TSA.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_token: Option<String>,
+    /// Original file name of the document, if bound at signing time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    /// Document size in bytes, if bound at signing time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_size: Option<u64>,
+    /// Document modification time (whole seconds), if bound at signing time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_modified_at: Option<DateTime<Utc>>,
+    /// The envelope this one replaces when a signature was refreshed with a new key, kept
+    /// verbatim (with its time-stamps) so the original evidence stays covered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_envelope: Option<Value>,
+    /// Custom key–value metadata supplied by the signer.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Approval by the key's second person, for keys under two-person control.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<SigningApproval>,
+    /// Per-key signature counter (see signature_sequence.rs); absent in older envelopes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    /// Signer identity from an OpenID Connect login at signing time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oidc_identity: Option<OidcIdentity>,
+    /// Post-quantum key whose signature is `pqSignature`; present exactly in hybrid envelopes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pq_cosigner: Option<PqCosigner>,
+}
+
+impl SignedAttributes {
+    /// Checks that a TSA-sourced `signed_at` is backed by its token over the document digest
+    /// and returns where the time effectively comes from: a token from a TSA that does not
+    /// chain to one of `trusted_tsas` leaves `signed_at` as only the signer's claim.
+    pub fn check_signing_time(
+        &self,
+        as_of: Option<DateTime<Utc>>,
+        trusted_tsas: &[Certificate],
+    ) -> Result<TimeSource> {
+        if self.time_source == TimeSource::LocalClock {
+            return Ok(TimeSource::LocalClock);
+        }
+        let token = self
+            .time_token
+            .as_deref()
+            .ok_or_else(|| anyhow!("Signing time claims a TSA source but has no token"))?;
+        let token = BASE64
+            .decode(token)
+            .context("Failed to decode signing time token")?;
+        let digest = hex::decode(&self.document_digest).context("Malformed document digest")?;
+        let verified = match check_timestamp_token(&token, &digest, as_of, trusted_tsas)? {
+            Ok(verified) => verified,
+            Err(reason) => {
+                log::warn!("Signing time is only the signer's claim: {}", reason);
+                return Ok(TimeSource::LocalClock);
+            }
+        };
+        if verified.gen_time != self.signed_at {
+            bail!("Signing time does not match its time-stamp token");
+        }
+        Ok(TimeSource::Tsa)
+    }
+}
+
+/// File attributes that can be bound into an envelope.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileBinding {
+    pub file_name: Option<String>,
+    pub file_size: Option<u64>,
+    pub file_modified_at: Option<DateTime<Utc>>,
+}
+
+impl FileBinding {
+    /// Reads the attributes of `path`; `attributes` adds size and modification time.
+    pub fn read(path: &Path, attributes: bool) -> Result<Self> {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        if !attributes {
+            return Ok(FileBinding {
+                file_name,
+                ..Default::default()
+            });
+        }
+        let metadata =
+            fs::metadata(path).with_context(|| format!("Failed to read metadata of {:?}", path))?;
+        let modified: DateTime<Utc> = metadata
+            .modified()
+            .context("File modification time is not available on this platform")?
+            .into();
+        // 只保留整秒，不同文件系统的时间精度不同
+        let modified = DateTime::from_timestamp(modified.timestamp(), 0).unwrap_or(modified);
+        Ok(FileBinding {
+            file_name,
+            file_size: Some(metadata.len()),
+            file_modified_at: Some(modified),
+        })
+    }
+
+    /// Compares the attributes bound in `attributes` with the file at `path`.
+    pub fn mismatches(
+        attributes: &SignedAttributes,
+        path: &Path,
+    ) -> Result<Vec<FileBindingMismatch>> {
+        let bound_attributes =
+            attributes.file_size.is_some() || attributes.file_modified_at.is_some();
+        if attributes.file_name.is_none() && !bound_attributes {
+            return Ok(Vec::new());
+        }
+        let actual = FileBinding::read(path, bound_attributes)?;
+        let mut mismatches = Vec::new();
+        let mut compare = |field: &str, expected: Option<String>, actual: Option<String>| {
+            if let Some(expected) = expected {
+                let actual = actual.unwrap_or_default();
+                if expected != actual {
+                    mismatches.push(FileBindingMismatch {
+                        field: field.to_string(),
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        };
+        compare("fileName", attributes.file_name.clone(), actual.file_name);
+        compare(
+            "fileSize",
+            attributes.file_size.map(|v| v.to_string()),
+            actual.file_size.map(|v| v.to_string()),
+        );
+        compare(
+            "fileModifiedAt",
+            attributes.file_modified_at.map(|v| v.to_rfc3339()),
+            actual.file_modified_at.map(|v| v.to_rfc3339()),
+        );
+        Ok(mismatches)
+    }
+}
+
+/// An envelope as stored on disk.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureEnvelope {
+    /// The signed attributes, kept as a JSON value so that fields added by newer versions
+    /// are still covered when this version verifies the envelope.
+    pub signed: Value,
+    /// Signature over the canonical encoding of `signed`, standard base64.
+    pub signature: String,
+    /// RFC 3161 time-stamps added after signing. Each one covers the envelope as it was
+    /// before that time-stamp was added, including all earlier time-stamps.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timestamps: Vec<EnvelopeTimestamp>,
+    /// For passkey signatures: what the authenticator signed besides the attributes. The
+    /// signature is then a WebAuthn assertion whose challenge is the SHA-256 of the canonical
+    /// attributes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webauthn: Option<WebAuthnAssertion>,
+    /// For hybrid envelopes: the co-signer's signature over the canonical encoding of
+    /// `signed`, standard base64.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pq_signature: Option<String>,
+}
+
+/// The WebAuthn assertion data of a passkey signature.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebAuthnAssertion {
+    /// Relying party ID the passkey is registered for.
+    pub rp_id: String,
+    /// Authenticator data, base64url.
+    pub authenticator_data: String,
+    /// `clientDataJSON` as sent to the authenticator, base64url.
+    pub client_data_json: String,
+}
+
+/// An RFC 3161 time-stamp token attached to an envelope.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvelopeTimestamp {
+    /// The time-stamping authority the token was requested from.
+    pub tsa_url: String,
+    /// Time asserted by the TSA, copied from the token for display.
+    pub gen_time: DateTime<Utc>,
+    /// DER-encoded time-stamp token (CMS `ContentInfo`), standard base64.
+    pub token: String,
+}
+
+impl SignatureEnvelope {
+    /// Signs `attributes` and wraps them into an envelope.
+    pub fn seal(attributes: &SignedAttributes, private_key_der: &[u8]) -> Result<Self> {
+        let algorithm = SignatureAlgorithm::from_str(&attributes.algorithm)?;
+        let signed = serde_json::to_value(attributes).context("Failed to serialize envelope")?;
+        let signature = sign_bytes(
+            &algorithm,
+            private_key_der,
+            &canonical_json(&signed)?,
+            DigestScheme::Standard,
+        )?;
+        Ok(SignatureEnvelope {
+            signed,
+            signature: BASE64.encode(signature),
+            timestamps: Vec::new(),
+            webauthn: None,
+            pq_signature: None,
+        })
+    }
+
+    /// Like `seal`, for hybrid envelopes: `pq_private_key_der` is the key of the co-signer
+    /// named in the attributes.
+    pub fn seal_hybrid(
+        attributes: &SignedAttributes,
+        private_key_der: &[u8],
+        pq_private_key_der: &[u8],
+    ) -> Result<Self> {
+        let cosigner = attributes
+            .pq_cosigner
+            .as_ref()
+            .ok_or_else(|| anyhow!("Hybrid envelopes need a post-quantum co-signer"))?;
+        let pq_algorithm = SignatureAlgorithm::from_str(&cosigner.algorithm)?;
+        let mut envelope = Self::seal(attributes, private_key_der)?;
+        let pq_signature = sign_bytes(
+            &pq_algorithm,
+            pq_private_key_der,
+            &canonical_json(&envelope.signed)?,
+            DigestScheme::Standard,
+        )?;
+        envelope.pq_signature = Some(BASE64.encode(pq_signature));
+        Ok(envelope)
+    }
+
+    /// Like `seal`, for RSA and ECDSA P-256 keys held elsewhere (e.g. a cloud KMS):
+    /// `sign_digest` gets the SHA-256 digest the signer has to sign and returns the signature.
+    pub fn seal_with_digest_signer(
+        attributes: &SignedAttributes,
+        sign_digest: impl FnOnce(&[u8]) -> Result<Vec<u8>>,
+    ) -> Result<Self> {
+        let algorithm = SignatureAlgorithm::from_str(&attributes.algorithm)?;
+        if !algorithm.signs_sha256_digest() {
+            bail!(
+                "{} signatures cannot be made over a SHA-256 digest",
+                algorithm
+            );
+        }
+        let signed = serde_json::to_value(attributes).context("Failed to serialize envelope")?;
+        let signature = sign_digest(&Sha256::digest(canonical_json(&signed)?))?;
+        Ok(SignatureEnvelope {
+            signed,
+            signature: BASE64.encode(signature),
+            timestamps: Vec::new(),
+            webauthn: None,
+            pq_signature: None,
+        })
+    }
+
+    /// SHA-256 over the canonical encoding of the envelope with only its first `count`
+    /// time-stamps; this is what time-stamp number `count` covers.
+    pub fn timestamp_imprint(&self, count: usize) -> Result<Vec<u8>> {
+        let covered = SignatureEnvelope {
+            signed: self.signed.clone(),
+            signature: self.signature.clone(),
+            timestamps: self.timestamps[..count.min(self.timestamps.len())].to_vec(),
+            webauthn: self.webauthn.clone(),
+            pq_signature: self.pq_signature.clone(),
+        };
+        let value = serde_json::to_value(&covered).context("Failed to serialize envelope")?;
+        Ok(Sha256::digest(canonical_json(&value)?).to_vec())
+    }
+
+    /// Requests a time-stamp over the whole envelope (including earlier time-stamps) and
+    /// appends it; the TSA has to chain to one of `trusted_tsas`.
+    pub fn add_timestamp(
+        &mut self,
+        tsa_url: &str,
+        trusted_tsas: &[Certificate],
+    ) -> Result<&EnvelopeTimestamp> {
+        let imprint = self.timestamp_imprint(self.timestamps.len())?;
+        let token = request_timestamp(tsa_url, &imprint)?;
+        let verified = verify_timestamp_token(&token, &imprint, None, trusted_tsas)?;
+        self.timestamps.push(EnvelopeTimestamp {
+            tsa_url: tsa_url.to_string(),
+            gen_time: verified.gen_time,
+            token: BASE64.encode(token),
+        });
+        Ok(&self.timestamps[self.timestamps.len() - 1])
+    }
+
+    /// Checks every attached time-stamp token against the part of the envelope it covers,
+    /// judging TSA certificates as of `as_of` (default: each token's own time). Tokens from
+    /// TSAs that do not chain to one of `trusted_tsas` are invalid.
+    pub fn verify_timestamps(
+        &self,
+        as_of: Option<DateTime<Utc>>,
+        trusted_tsas: &[Certificate],
+    ) -> Vec<TimestampReport> {
+        self.timestamps
+            .iter()
+            .enumerate()
+            .map(|(index, timestamp)| {
+                let outcome = self.timestamp_imprint(index).and_then(|imprint| {
+                    let token = BASE64
+                        .decode(&timestamp.token)
+                        .context("Failed to decode time-stamp token")?;
+                    let verified = verify_timestamp_token(&token, &imprint, as_of, trusted_tsas)?;
+                    if verified.gen_time != timestamp.gen_time {
+                        bail!("Recorded time does not match the time-stamp token");
+                    }
+                    Ok(verified)
+                });
+                match outcome {
+                    Ok(verified) => TimestampReport {
+                        tsa_url: timestamp.tsa_url.clone(),
+                        gen_time: verified.gen_time,
+                        tsa_name: verified.tsa_name,
+                        certificate_not_after: Some(verified.certificate_not_after),
+                        checked_at: Some(verified.checked_at),
+                        revocation_status: Some(verified.revocation_status),
+                        is_valid: true,
+                        error_message: None,
+                    },
+                    Err(e) => TimestampReport {
+                        tsa_url: timestamp.tsa_url.clone(),
+                        gen_time: timestamp.gen_time,
+                        tsa_name: None,
+                        certificate_not_after: None,
+                        checked_at: None,
+                        revocation_status: None,
+                        is_valid: false,
+                        error_message: Some(format!("{:#}", e)),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Parses an envelope file, returning `None` if the bytes are not an envelope at all
+    /// (e.g. a raw detached signature).
+    pub fn parse(bytes: &[u8]) -> Result<Option<Self>> {
+        let first = bytes.iter().find(|b| !b.is_ascii_whitespace());
+        if first != Some(&b'{') {
+            return Ok(None);
+        }
+        let Ok(envelope) = serde_json::from_slice::<SignatureEnvelope>(bytes) else {
+            return Ok(None);
+        };
+        let format = envelope.signed.get("format").and_then(Value::as_str);
+        if format != Some(ENVELOPE_FORMAT) && format != Some(HYBRID_ENVELOPE_FORMAT) {
+            return Ok(None);
+        }
+        Ok(Some(envelope))
+    }
+
+    /// Decodes the signed attributes, rejecting unsupported versions.
+    pub fn attributes(&self) -> Result<SignedAttributes> {
+        let attributes: SignedAttributes = serde_json::from_value(self.signed.clone())
+            .context("Malformed signed attributes in envelope")?;
+        if attributes.version != ENVELOPE_VERSION {
+            bail!("Unsupported envelope version: {}", attributes.version);
+        }
+        if attributes.digest_algorithm != DIGEST_SHA256 {
+            bail!(
+                "Unsupported envelope digest algorithm: {}",
+                attributes.digest_algorithm
+            );
+        }
+        if (attributes.format == HYBRID_ENVELOPE_FORMAT) != attributes.pq_cosigner.is_some() {
+            bail!("Only hybrid envelopes name a post-quantum co-signer, and they must name one");
+        }
+        Ok(attributes)
+    }
+
+    /// Checks the envelope signature with the signer's public key (SPKI DER). Hybrid
+    /// envelopes are refused here: they need the co-signer's key as well, see
+    /// [`Self::verify_hybrid_signature`].
+    pub fn verify_signature(
+        &self,
+        algorithm: &SignatureAlgorithm,
+        public_key_der: &[u8],
+    ) -> Result<Result<(), signature::Error>> {
+        if self.attributes()?.pq_cosigner.is_some() {
+            bail!("Hybrid envelopes must be checked against the post-quantum co-signer's key too");
+        }
+        self.verify_classical_signature(algorithm, public_key_der)
+    }
+
+    /// Checks both signatures of a hybrid envelope. The co-signer's key (SPKI DER) has to
+    /// come from the verifier's own keys or contacts; the key embedded in the envelope only
+    /// says which one to look for.
+    pub fn verify_hybrid_signature(
+        &self,
+        algorithm: &SignatureAlgorithm,
+        public_key_der: &[u8],
+        pq_public_key_der: &[u8],
+    ) -> Result<Result<(), signature::Error>> {
+        let cosigner = self
+            .attributes()?
+            .pq_cosigner
+            .ok_or_else(|| anyhow!("Not a hybrid envelope"))?;
+        let pq_algorithm = SignatureAlgorithm::from_str(&cosigner.algorithm)?;
+        if pq_algorithm != SignatureAlgorithm::MlDsa65 {
+            bail!(
+                "Hybrid envelopes must be co-signed with {}, not {}",
+                SignatureAlgorithm::MlDsa65,
+                pq_algorithm
+            );
+        }
+        if compute_fingerprint(pq_public_key_der) != cosigner.fingerprint {
+            bail!("The post-quantum key is not the co-signer of this envelope");
+        }
+        let verified = self.verify_classical_signature(algorithm, public_key_der)?;
+        if verified.is_err() {
+            return Ok(verified);
+        }
+        let signature = self.pq_signature.as_deref().ok_or_else(|| {
+            anyhow!("The post-quantum signature of the hybrid envelope is missing")
+        })?;
+        let signature = BASE64
+            .decode(signature)
+            .context("Failed to decode post-quantum signature")?;
+        verify_bytes(
+            &pq_algorithm,
+            pq_public_key_der,
+            &canonical_json(&self.signed)?,
+            &signature,
+            DigestScheme::Standard,
+        )
+    }
+
+    // 校验经典签名（口令密钥或普通密钥）
+    fn verify_classical_signature(
+        &self,
+        algorithm: &SignatureAlgorithm,
+        public_key_der: &[u8],
+    ) -> Result<Result<(), signature::Error>> {
+        let signature = BASE64
+            .decode(&self.signature)
+            .context("Failed to decode envelope signature")?;
+        let canonical = canonical_json(&self.signed)?;
+        match &self.webauthn {
+            Some(assertion) => {
+                if *algorithm != SignatureAlgorithm::EcdsaP256Sha256 {
+                    bail!(
+                        "Passkey signatures must use {}",
+                        SignatureAlgorithm::EcdsaP256Sha256
+                    );
+                }
+                let challenge = Sha256::digest(&canonical);
+                verify_assertion(public_key_der, assertion, &signature, &challenge)
+            }
+            None => verify_bytes(
+                algorithm,
+                public_key_der,
+                &canonical,
+                &signature,
+                DigestScheme::Standard,
+            ),
+        }
+    }
+}
+
+/// Encodes a JSON value in canonical form (see the module comment).
+pub fn canonical_json(value: &Value) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    write_canonical(value, &mut out)?;
+    Ok(out)
+}
+
+// 递归写出规范化 JSON
+fn write_canonical(value: &Value, out: &mut Vec<u8>) -> Result<()> {
+    match value {
+        Value::Null | Value::Bool(_) | Value::String(_) => {
+            serde_json::to_writer(&mut *out, value)?;
+        }
+        Value::Number(number) => {
+            if number.is_f64() {
+                bail!(
+                    "Floating point numbers are not allowed in signed data: {}",
+                    number
+                );
+            }
+            serde_json::to_writer(&mut *out, number)?;
+        }
+        Value::Array(items) => {
+            out.push(b'[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_canonical(item, out)?;
+            }
+            out.push(b']');
+        }
+        Value::Object(map) => {
+            // JCS 按 UTF-16 码元排序键名
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push(b'{');
+            for (index, (key, item)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                serde_json::to_writer(&mut *out, key)?;
+                out.push(b':');
+                write_canonical(item, out)?;
+            }
+            out.push(b'}');
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_exchange::encode_public_key_pem;
+    use crate::self_tests::known_answer_key_pair;
+    use serde_json::json;
+
+    #[test]
+    fn canonical_json_is_sorted_and_compact() {
+        let value = json!({
+            "b": [1, "two", null, true],
+            "a": { "z": -3, "\u{e9}": "x\ny" },
+            "\u{1f600}": 0,
+            "\u{ff61}": 1
+        });
+        let encoded = String::from_utf8(canonical_json(&value).unwrap()).unwrap();
+        // U+1F600 is a surrogate pair (0xD83D...), so it sorts before U+FF61 in UTF-16 order.
+        assert_eq!(
+            encoded,
+            "{\"a\":{\"z\":-3,\"\u{e9}\":\"x\\ny\"},\"b\":[1,\"two\",null,true],\"\u{1f600}\":0,\"\u{ff61}\":1}"
+        );
+
+        let reordered: Value =
+            serde_json::from_str(&serde_json::to_string_pretty(&value).unwrap()).unwrap();
+        assert_eq!(canonical_json(&reordered).unwrap(), encoded.into_bytes());
+        assert!(canonical_json(&json!({ "x": 1.5 })).is_err());
+    }
+
+    #[test]
+    fn hybrid_envelopes_need_both_signatures() {
+        let ed25519 = SignatureAlgorithm::Ed25519;
+        let ml_dsa = SignatureAlgorithm::MlDsa65;
+        let (private_der, public_der) = known_answer_key_pair(&ed25519).unwrap();
+        let (pq_private_der, pq_public_der) = known_answer_key_pair(&ml_dsa).unwrap();
+        let attributes = SignedAttributes {
+            format: HYBRID_ENVELOPE_FORMAT.to_string(),
+            version: ENVELOPE_VERSION,
+            algorithm: ed25519.to_string(),
+            key_id: Uuid::nil(),
+            key_fingerprint: compute_fingerprint(&public_der),
+            digest_algorithm: DIGEST_SHA256.to_string(),
+            document_digest: hex::encode(Sha256::digest(b"contract")),
+            signed_at: Utc::now(),
+            time_source: TimeSource::LocalClock,
+            time_token: None,
+            file_name: None,
+            file_size: None,
+            file_modified_at: None,
+            previous_envelope: None,
+            metadata: BTreeMap::new(),
+            approval: None,
+            sequence: None,
+            oidc_identity: None,
+            pq_cosigner: Some(PqCosigner {
+                key_id: Uuid::nil(),
+                algorithm: ml_dsa.to_string(),
+                public_key_pem: encode_public_key_pem(&pq_public_der).unwrap(),
+                fingerprint: compute_fingerprint(&pq_public_der),
+            }),
+        };
+        let envelope =
+            SignatureEnvelope::seal_hybrid(&attributes, &private_der, &pq_private_der).unwrap();
+        let bytes = serde_json::to_vec(&envelope).unwrap();
+        let parsed = SignatureEnvelope::parse(&bytes).unwrap().unwrap();
+        assert!(parsed.attributes().unwrap().pq_cosigner.is_some());
+        let verify = |envelope: &SignatureEnvelope| {
+            envelope.verify_hybrid_signature(&ed25519, &public_der, &pq_public_der)
+        };
+        assert!(verify(&parsed).unwrap().is_ok());
+        // 混合信封不能只校验经典签名，也不能用其他后量子公钥校验
+        assert!(parsed.verify_signature(&ed25519, &public_der).is_err());
+        assert!(parsed
+            .verify_hybrid_signature(&ed25519, &public_der, &public_der)
+            .is_err());
+
+        // 只有经典签名有效时不通过
+        let mut forged = envelope.clone();
+        let mut pq_signature = BASE64.decode(forged.pq_signature.unwrap()).unwrap();
+        pq_signature[0] ^= 1;
+        forged.pq_signature = Some(BASE64.encode(pq_signature));
+        assert!(verify(&forged).unwrap().is_err());
+        let mut stripped = envelope.clone();
+        stripped.pq_signature = None;
+        assert!(verify(&stripped).is_err());
+
+        // 去掉后量子签名者后经典签名也失效
+        let mut downgraded = envelope;
+        downgraded.signed["format"] = json!(ENVELOPE_FORMAT);
+        downgraded
+            .signed
+            .as_object_mut()
+            .unwrap()
+            .remove("pqCosigner");
+        downgraded.pq_signature = None;
+        assert!(downgraded
+            .verify_signature(&ed25519, &public_der)
+            .unwrap()
+            .is_err());
+    }
+}
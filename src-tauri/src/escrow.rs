@@ -0,0 +1,572 @@
+// src-tauri/src/escrow.rs
+// Organizational key recovery. When a recovery public key (RSA) is configured, key
+// generation can store an escrow copy of the new private key, sealed with a random
+// AES-256-GCM key that is itself encrypted to the recovery key with RSA-OAEP (SHA-256).
+// An administrator holding the recovery private key (kept as an RSA key in their own
+// sigust) can open the escrow file and re-protect the key under a new password.
+use crate::audit;
+use crate::consent::ask_user;
+use crate::crypto_types::{
+    DigestScheme, KeyInfo, KeyKind, KeyMetadata, RecoveryKeyConfig, SignatureAlgorithm,
+};
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, find_key_metadata, get_key_storage_dir,
+    get_metadata_path, metadata_json, read_metadata, read_private_key_der, rsa_key_bits,
+};
+use crate::key_wrapping::{wrap_private_key, WrapKdf};
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::resolve_output_path;
+use crate::settings::{
+    get_settings_path, read_settings, recommended_pbkdf2_iterations, update_settings,
+};
+use crate::signing::{sign_bytes, verify_bytes};
+use crate::sshsig::optional_ssh_fingerprint;
+use crate::store_journal::StoreTransaction;
+use aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::rand_core::RngCore;
+use rsa::traits::PublicKeyParts;
+use rsa::{Oaep, RsaPrivateKey, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+const ESCROW_FORMAT: &str = "sigust-escrow";
+const ESCROW_VERSION: u32 = 1;
+const MIN_RECOVERY_KEY_BITS: usize = 2048;
+/// Escrow files are small JSON documents; anything larger is not one.
+const MAX_ESCROW_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Escrow copy of a private key, stored as `<key id>.escrow` next to the key files.
+/// Carries the public key and name so the key can be restored on another machine.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EscrowFile {
+    format: String,
+    version: u32,
+    key_id: Uuid,
+    name: String,
+    algorithm: String,
+    public_key_pem: String,
+    /// Fingerprint of the recovery public key the content key was encrypted to.
+    recovery_key_fingerprint: String,
+    created_at: DateTime<Utc>,
+    /// AES-256-GCM content key, RSA-OAEP encrypted, base64.
+    wrapped_key: String,
+    nonce: String,
+    /// PKCS#8 private key sealed with the content key, base64.
+    ciphertext: String,
+}
+
+// --- Tauri Commands ---
+
+/// Configures the organization's recovery public key (RSA SPKI PEM), or removes it when
+/// `public_key_pem` is `None`. Existing escrow files are not affected. A change is confirmed
+/// in a native dialog first.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_recovery_key(
+    app_handle: tauri::AppHandle,
+    public_key_pem: Option<String>,
+) -> Result<Option<RecoveryKeyConfig>, String> {
+    log::info!(
+        "{} the recovery public key",
+        if public_key_pem.is_some() {
+            "Configuring"
+        } else {
+            "Removing"
+        }
+    );
+    _set_recovery_key(&app_handle, public_key_pem)
+        .map_err(|e| {
+            log::error!("Failed to set recovery key: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|config| {
+            audit::record(
+                &app_handle,
+                "setRecoveryKey",
+                json!({ "fingerprint": config.as_ref().map(|c| &c.fingerprint) }),
+            )
+        })
+}
+
+fn _set_recovery_key(
+    app_handle: &tauri::AppHandle,
+    public_key_pem: Option<String>,
+) -> Result<Option<RecoveryKeyConfig>> {
+    let config = public_key_pem
+        .map(|pem| recovery_key_config(&pem))
+        .transpose()?;
+    let current = read_settings(&get_settings_path(app_handle)?)?;
+    if current.recovery_key.as_ref().map(|c| &c.fingerprint)
+        != config.as_ref().map(|c| &c.fingerprint)
+        && !ask_user(
+            app_handle,
+            "Recovery key",
+            "New keys will be escrowed to a different recovery key, or to none.\nContinue?",
+        )
+    {
+        bail!("Recovery key change not confirmed");
+    }
+    update_settings(app_handle, |settings| {
+        settings.recovery_key = config.clone()
+    })?;
+    Ok(config)
+}
+
+/// Copies the escrow file of a key to `output_path`, for handing it to an administrator.
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_escrow_file(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    output_path: String,
+) -> Result<String, String> {
+    log::info!("Exporting escrow file of key ID {}", key_id);
+    _export_escrow_file(&app_handle, key_id, &output_path)
+        .map_err(|e| {
+            log::error!("Failed to export escrow file: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|path| {
+            audit::record(
+                &app_handle,
+                "exportEscrowFile",
+                json!({ "keyId": key_id, "outputPath": path }),
+            )
+        })
+}
+
+fn _export_escrow_file(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    output_path: &str,
+) -> Result<String> {
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let escrow_path = metadata
+        .escrow_path
+        .as_ref()
+        .ok_or_else(|| anyhow!("Key {} has no escrow copy", key_id))?;
+    let source = escrow_file_path(&get_key_storage_dir(app_handle)?, escrow_path)?;
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::copy(&source, &output_path)
+        .with_context(|| format!("Failed to copy escrow file to {:?}", output_path))?;
+    Ok(path_string(&output_path))
+}
+
+/// Opens an escrow file with a local recovery key (RSA) and protects the recovered private
+/// key with `new_password`. If the key exists locally its private key file is replaced;
+/// otherwise the key is added to this device's keys.
+#[tauri::command(rename_all = "camelCase")]
+pub fn recover_escrowed_key(
+    app_handle: tauri::AppHandle,
+    escrow_path: String,
+    recovery_key_id: Uuid,
+    recovery_password: String,
+    new_password: String,
+) -> Result<KeyInfo, String> {
+    log::info!(
+        "Recovering escrowed key from '{}' with recovery key {}",
+        escrow_path,
+        recovery_key_id
+    );
+    if recovery_password.is_empty() || new_password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    native_path(&escrow_path)
+        .and_then(|path| {
+            _recover_escrowed_key(
+                &app_handle,
+                &path,
+                recovery_key_id,
+                &recovery_password,
+                &new_password,
+            )
+        })
+        .map_err(|e| {
+            log::error!("Failed to recover escrowed key: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|info| {
+            audit::record(
+                &app_handle,
+                "recoverEscrowedKey",
+                json!({ "keyId": info.key_id, "recoveryKeyId": recovery_key_id }),
+            )
+        })
+}
+
+fn _recover_escrowed_key(
+    app_handle: &tauri::AppHandle,
+    escrow_path: &Path,
+    recovery_key_id: Uuid,
+    recovery_password: &str,
+    new_password: &str,
+) -> Result<KeyInfo> {
+    let size = fs::metadata(escrow_path)
+        .with_context(|| format!("Failed to read escrow file: {:?}", escrow_path))?
+        .len();
+    if size > MAX_ESCROW_FILE_SIZE {
+        bail!("Not an escrow file: {:?}", escrow_path);
+    }
+    let content = fs::read(escrow_path)
+        .with_context(|| format!("Failed to read escrow file: {:?}", escrow_path))?;
+    let escrow: EscrowFile =
+        serde_json::from_slice(&content).context("Not a sigust escrow file")?;
+    if escrow.format != ESCROW_FORMAT || escrow.version != ESCROW_VERSION {
+        bail!(
+            "Unsupported escrow file: {} v{}",
+            escrow.format,
+            escrow.version
+        );
+    }
+
+    // 恢复密钥必须是与托管文件匹配的 RSA 密钥
+    let recovery_metadata = find_key_metadata(app_handle, recovery_key_id)?;
+    if recovery_metadata.algorithm != SignatureAlgorithm::RsaPkcs1Sha256 {
+        bail!("The recovery key must be an RSA key");
+    }
+    let recovery_public_der = decode_public_key_pem(&fs::read_to_string(
+        get_key_storage_dir(app_handle)?.join(&recovery_metadata.public_key_pem_path),
+    )?)?;
+    if compute_fingerprint(&recovery_public_der) != escrow.recovery_key_fingerprint {
+        bail!("The escrow file was sealed for a different recovery key");
+    }
+    let recovery_der = Zeroizing::new(read_private_key_der(
+        app_handle,
+        &recovery_metadata,
+        recovery_password,
+    )?);
+    let recovery_key = RsaPrivateKey::from_pkcs8_der(&recovery_der)
+        .context("Failed to parse the recovery private key")?;
+
+    let algorithm = SignatureAlgorithm::from_str(&escrow.algorithm)?;
+    let private_key_der = open_escrow(&escrow, &recovery_key)?;
+
+    // 确认私钥与托管文件中的公钥匹配，防止被篡改的文件覆盖现有密钥
+    let public_key_der = decode_public_key_pem(&escrow.public_key_pem)?;
+    let probe = b"sigust escrow recovery check";
+    let signature = sign_bytes(&algorithm, &private_key_der, probe, DigestScheme::Standard)?;
+    verify_bytes(
+        &algorithm,
+        &public_key_der,
+        probe,
+        &signature,
+        DigestScheme::Standard,
+    )?
+    .map_err(|_| anyhow!("The recovered private key does not match its public key"))?;
+
+    let kdf = WrapKdf::Pbkdf2Sha256 {
+        iterations: recommended_pbkdf2_iterations(app_handle),
+    };
+    let wrapped = wrap_private_key(&private_key_der, new_password, kdf.clone())?;
+
+    let key_storage_dir = get_key_storage_dir(app_handle)?;
+    let metadata_path = get_metadata_path(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+    let mut transaction = StoreTransaction::begin(app_handle, "recoverEscrowedKey")?;
+    let info = match all_metadata.iter().find(|m| m.key_id == escrow.key_id) {
+        Some(existing) => {
+            let existing_public_der = decode_public_key_pem(&fs::read_to_string(
+                key_storage_dir.join(&existing.public_key_pem_path),
+            )?)?;
+            if existing_public_der != public_key_der {
+                bail!(
+                    "The escrow file does not match the local key {}",
+                    escrow.key_id
+                );
+            }
+            // 仅含公钥的条目恢复后成为完整密钥对
+            let private_key_filename = match existing.kind {
+                KeyKind::KeyPair => existing.encrypted_private_key_path.clone(),
+                KeyKind::PublicOnly => format!("{}.key.enc", escrow.key_id),
+            };
+            let private_key_path = key_storage_dir.join(&private_key_filename);
+            // 替换的私钥文件用旧口令加密，擦除它及其备份
+            transaction
+                .write_wiped(&private_key_path, &wrapped)
+                .with_context(|| {
+                    format!(
+                        "Failed to write encrypted private key to {:?}",
+                        private_key_path
+                    )
+                })?;
+            // 新文件的盐在文件头中，旧版盐不再需要
+            let metadata = all_metadata
+                .iter_mut()
+                .find(|m| m.key_id == escrow.key_id)
+                .expect("key found above");
+            metadata.salt_hex = None;
+            // 恢复后的文件只用新密码保护
+            metadata.token_passkey_id = None;
+            metadata.device_bound = false;
+            metadata.kdf = Some((&kdf).into());
+            metadata.encrypted_private_key_path = private_key_filename;
+            metadata.kind = KeyKind::KeyPair;
+            let info = KeyInfo::from(&*metadata);
+            transaction.write(&metadata_path, metadata_json(&all_metadata)?)?;
+            info
+        }
+        None => {
+            let public_key_filename = format!("{}.pub.pem", escrow.key_id);
+            let private_key_filename = format!("{}.key.enc", escrow.key_id);
+            let public_key_path = key_storage_dir.join(&public_key_filename);
+            let private_key_path = key_storage_dir.join(&private_key_filename);
+            transaction
+                .write(&public_key_path, &escrow.public_key_pem)
+                .with_context(|| format!("Failed to write public key to {:?}", public_key_path))?;
+            transaction
+                .write(&private_key_path, &wrapped)
+                .with_context(|| {
+                    format!(
+                        "Failed to write encrypted private key to {:?}",
+                        private_key_path
+                    )
+                })?;
+            let metadata = KeyMetadata {
+                key_id: escrow.key_id,
+                name: escrow.name.clone(),
+                public_key_pem_path: public_key_filename,
+                encrypted_private_key_path: private_key_filename,
+                algorithm: algorithm.clone(),
+                created_at: escrow.created_at,
+                salt_hex: None,
+                identity: None,
+                trashed_at: None,
+                folder_id: None,
+                tags: Vec::new(),
+                escrow_path: None,
+                approver_key_id: None,
+                pq_key_id: None,
+                token_passkey_id: None,
+                derivation: None,
+                unlock_policy: None,
+                device_bound: false,
+                kdf: Some((&kdf).into()),
+                kind: KeyKind::KeyPair,
+                fingerprint: Some(compute_fingerprint(&public_key_der)),
+                ssh_fingerprint: optional_ssh_fingerprint(&algorithm, &public_key_der)?,
+                key_bits: rsa_key_bits(&algorithm, &public_key_der),
+            };
+            let info = KeyInfo::from(&metadata);
+            all_metadata.push(metadata);
+            transaction.write(&metadata_path, metadata_json(&all_metadata)?)?;
+            info
+        }
+    };
+    transaction.commit()?;
+    log::info!("Recovered escrowed key {} ({})", info.key_id, info.name);
+    Ok(info)
+}
+
+// --- Helper Functions ---
+
+/// Checks a recovery public key (RSA, SPKI PEM) and describes it for the settings.
+pub(crate) fn recovery_key_config(public_key_pem: &str) -> Result<RecoveryKeyConfig> {
+    let public_key_der = decode_public_key_pem(public_key_pem.trim())?;
+    let public_key = RsaPublicKey::from_public_key_der(&public_key_der)
+        .context("The recovery key must be an RSA public key")?;
+    if public_key.size() * 8 < MIN_RECOVERY_KEY_BITS {
+        bail!(
+            "The recovery key must be at least {} bits long",
+            MIN_RECOVERY_KEY_BITS
+        );
+    }
+    Ok(RecoveryKeyConfig {
+        public_key_pem: public_key_pem.trim().to_string(),
+        fingerprint: compute_fingerprint(&public_key_der),
+        configured_at: Utc::now(),
+    })
+}
+
+/// Seals an escrow copy of a freshly generated key to the configured recovery key and
+/// writes it to the key storage directory, as part of `transaction`. Returns the file name (relative to that
+/// directory), to be recorded in the key's metadata.
+pub(crate) fn write_escrow_file(
+    app_handle: &tauri::AppHandle,
+    transaction: &mut StoreTransaction,
+    key_id: Uuid,
+    name: &str,
+    algorithm: &SignatureAlgorithm,
+    public_key_pem: &str,
+    private_key_der: &[u8],
+) -> Result<String> {
+    let recovery = read_settings(&get_settings_path(app_handle)?)?
+        .recovery_key
+        .ok_or_else(|| anyhow!("No recovery key is configured"))?;
+    let escrow = seal_escrow(
+        &recovery,
+        key_id,
+        name,
+        algorithm,
+        public_key_pem,
+        private_key_der,
+    )?;
+    let file_name = format!("{}.escrow", key_id);
+    let path = get_key_storage_dir(app_handle)?.join(&file_name);
+    let content =
+        serde_json::to_string_pretty(&escrow).context("Failed to serialize escrow file")?;
+    transaction
+        .write(&path, content)
+        .with_context(|| format!("Failed to write escrow file {:?}", path))?;
+    Ok(file_name)
+}
+
+// 用随机内容密钥加密私钥，内容密钥再用恢复公钥（RSA-OAEP）加密
+fn seal_escrow(
+    recovery: &RecoveryKeyConfig,
+    key_id: Uuid,
+    name: &str,
+    algorithm: &SignatureAlgorithm,
+    public_key_pem: &str,
+    private_key_der: &[u8],
+) -> Result<EscrowFile> {
+    let recovery_key =
+        RsaPublicKey::from_public_key_der(&decode_public_key_pem(&recovery.public_key_pem)?)
+            .context("Failed to parse the recovery public key")?;
+
+    let mut content_key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(content_key.as_mut());
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let algorithm = algorithm.to_string();
+    let cipher = Aes256Gcm::new_from_slice(content_key.as_ref())
+        .map_err(|e| anyhow!("Invalid escrow content key: {}", e))?;
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: private_key_der,
+                aad: escrow_aad(key_id, &algorithm).as_bytes(),
+            },
+        )
+        .map_err(|e| anyhow!("Failed to seal escrow copy: {}", e))?;
+    let wrapped_key = recovery_key
+        .encrypt(&mut OsRng, Oaep::new::<Sha256>(), content_key.as_ref())
+        .context("Failed to encrypt escrow key to the recovery key")?;
+
+    Ok(EscrowFile {
+        format: ESCROW_FORMAT.to_string(),
+        version: ESCROW_VERSION,
+        key_id,
+        name: name.to_string(),
+        algorithm,
+        public_key_pem: public_key_pem.to_string(),
+        recovery_key_fingerprint: recovery.fingerprint.clone(),
+        created_at: Utc::now(),
+        wrapped_key: BASE64.encode(wrapped_key),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+/// Full path of a key's escrow copy. Metadata can come from a sync or vault file, so only
+/// a plain `<key id>.escrow` file name in the key storage directory is accepted.
+pub(crate) fn escrow_file_path(key_storage_dir: &Path, escrow_path: &str) -> Result<PathBuf> {
+    let valid = escrow_path
+        .strip_suffix(".escrow")
+        .and_then(|id| {
+            Uuid::try_parse(id)
+                .ok()
+                .filter(|uuid| uuid.to_string() == id)
+        })
+        .is_some();
+    if !valid {
+        bail!("Invalid escrow file name: {:?}", escrow_path);
+    }
+    Ok(key_storage_dir.join(escrow_path))
+}
+
+// 用恢复私钥解开内容密钥，再解密私钥
+fn open_escrow(escrow: &EscrowFile, recovery_key: &RsaPrivateKey) -> Result<Zeroizing<Vec<u8>>> {
+    let wrapped_key = BASE64
+        .decode(&escrow.wrapped_key)
+        .context("Malformed escrow file")?;
+    let content_key = Zeroizing::new(
+        recovery_key
+            .decrypt(Oaep::new::<Sha256>(), &wrapped_key)
+            .context("Failed to decrypt the escrow key with the recovery key")?,
+    );
+    let nonce = BASE64
+        .decode(&escrow.nonce)
+        .context("Malformed escrow file")?;
+    let ciphertext = BASE64
+        .decode(&escrow.ciphertext)
+        .context("Malformed escrow file")?;
+    if nonce.len() != 12 {
+        bail!("Malformed escrow file");
+    }
+    let cipher =
+        Aes256Gcm::new_from_slice(&content_key).map_err(|_| anyhow!("Malformed escrow file"))?;
+    cipher
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: escrow_aad(escrow.key_id, &escrow.algorithm).as_bytes(),
+            },
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| anyhow!("The escrow file is corrupted or was modified"))
+}
+
+// 密钥 ID 和算法作为附加数据，防止托管内容被挪用到其他密钥
+fn escrow_aad(key_id: Uuid, algorithm: &str) -> String {
+    format!("{}:{}", key_id, algorithm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_exchange::encode_public_key_pem;
+    use crate::self_tests::known_answer_key_pair;
+
+    #[test]
+    fn escrow_copies_open_only_with_the_recovery_key() {
+        let (recovery_private_der, recovery_public_der) =
+            known_answer_key_pair(&SignatureAlgorithm::RsaPkcs1Sha256).unwrap();
+        let recovery =
+            recovery_key_config(&encode_public_key_pem(&recovery_public_der).unwrap()).unwrap();
+        let recovery_key = RsaPrivateKey::from_pkcs8_der(&recovery_private_der).unwrap();
+
+        let algorithm = SignatureAlgorithm::Ed25519;
+        let (private_der, public_der) = known_answer_key_pair(&algorithm).unwrap();
+        let key_id = Uuid::new_v4();
+        let escrow = seal_escrow(
+            &recovery,
+            key_id,
+            "Alice",
+            &algorithm,
+            &encode_public_key_pem(&public_der).unwrap(),
+            &private_der,
+        )
+        .unwrap();
+        assert_eq!(escrow.recovery_key_fingerprint, recovery.fingerprint);
+        // 经过文件格式往返后再打开
+        let escrow: EscrowFile =
+            serde_json::from_str(&serde_json::to_string(&escrow).unwrap()).unwrap();
+        assert_eq!(*open_escrow(&escrow, &recovery_key).unwrap(), private_der);
+
+        let wrong_key = RsaPrivateKey::new(&mut OsRng, 1024).unwrap();
+        assert!(open_escrow(&escrow, &wrong_key).is_err());
+
+        let dir = Path::new("keys");
+        let file_name = format!("{}.escrow", key_id);
+        assert_eq!(
+            escrow_file_path(dir, &file_name).unwrap(),
+            dir.join(&file_name)
+        );
+        for hostile in ["../secret.escrow", "/etc/passwd", "notes.txt", ".escrow"] {
+            assert!(escrow_file_path(dir, hostile).is_err(), "{}", hostile);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+// src-tauri/src/file_classify.rs
+// Classification of dropped files, so the frontend can route drag-and-drop: documents go
+// to signing, signatures to verification, public keys to contact import and key vaults to
+// restore. Only the beginning of a file is inspected, except for small JSON files, which
+// are parsed to tell envelopes, contacts exports and vaults apart.
+use crate::contacts::CONTACTS_EXPORT_FORMAT;
+use crate::crypto_types::{FileClassification, FileKind};
+use crate::envelope::{ENVELOPE_FORMAT, HYBRID_ENVELOPE_FORMAT};
+use crate::key_management::{decode_public_key_pem, detect_public_key_algorithm};
+use crate::signing::SIGNATURE_PEM_LABEL;
+use crate::vault::VAULT_FORMAT;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read to recognize a format by its header.
+const SNIFF_LEN: usize = 4096;
+/// Larger JSON files are treated as documents without parsing them.
+const MAX_JSON_SIZE: u64 = 1024 * 1024;
+/// DER encoding of the CMS `signedData` content type (1.2.840.113549.1.7.2).
+const CMS_SIGNED_DATA_OID: [u8; 11] = [
+    0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02,
+];
+
+// --- Tauri Commands ---
+
+/// Reports what each of the given files appears to be. Unreadable files are reported with
+/// an error instead of failing the whole request.
+#[tauri::command(rename_all = "camelCase")]
+pub fn classify_files(paths: Vec<String>) -> Result<Vec<FileClassification>, String> {
+    log::info!("Classifying {} dropped files", paths.len());
+    Ok(paths
+        .into_iter()
+        .map(|path| match classify_path(Path::new(&path)) {
+            Ok((kind, detail)) => FileClassification {
+                path,
+                kind: Some(kind),
+                detail,
+                error: None,
+            },
+            Err(e) => {
+                log::warn!("Failed to classify {}: {:?}", path, e);
+                FileClassification {
+                    path,
+                    kind: None,
+                    detail: None,
+                    error: Some(format!("{:#}", e)),
+                }
+            }
+        })
+        .collect())
+}
+
+// --- Helper Functions ---
+
+fn classify_path(path: &Path) -> Result<(FileKind, Option<String>)> {
+    let metadata = fs::metadata(path).with_context(|| format!("Failed to read {:?}", path))?;
+    if !metadata.is_file() {
+        anyhow::bail!("Not a regular file: {:?}", path);
+    }
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    File::open(path)
+        .and_then(|file| file.take(SNIFF_LEN as u64).read_to_end(&mut head))
+        .with_context(|| format!("Failed to read {:?}", path))?;
+
+    // JSON 文件需要完整解析才能区分信封、联系人导出和保管库
+    if head.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{')
+        && metadata.len() <= MAX_JSON_SIZE
+    {
+        let content = fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+        if let Some(classified) = classify_json(&content) {
+            return Ok(classified);
+        }
+    }
+    Ok(classify_bytes(&head, &extension, metadata.len()))
+}
+
+/// Classifies content held in memory (e.g. pasted text) the same way as a file.
+pub(crate) fn classify_content(content: &[u8], extension: &str) -> (FileKind, Option<String>) {
+    if content.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{') {
+        if let Some(classified) = classify_json(content) {
+            return classified;
+        }
+    }
+    classify_bytes(content, extension, content.len() as u64)
+}
+
+// sigust 自有的 JSON 格式：保管库（首行是文件头）、签名信封、联系人导出
+fn classify_json(content: &[u8]) -> Option<(FileKind, Option<String>)> {
+    let first_line = content.split(|&b| b == b'\n').next().unwrap_or_default();
+    if let Ok(header) = serde_json::from_slice::<Value>(first_line) {
+        if header.get("format").and_then(Value::as_str) == Some(VAULT_FORMAT) {
+            return Some((FileKind::BackupArchive, Some(VAULT_FORMAT.to_string())));
+        }
+    }
+    let value: Value = serde_json::from_slice(content).ok()?;
+    let signed = value.get("signed")?;
+    match signed.get("format").and_then(Value::as_str)? {
+        ENVELOPE_FORMAT | HYBRID_ENVELOPE_FORMAT => Some((
+            FileKind::SigustSignature,
+            Some(
+                signed
+                    .get("algorithm")
+                    .and_then(Value::as_str)
+                    .unwrap_or("envelope")
+                    .to_string(),
+            ),
+        )),
+        CONTACTS_EXPORT_FORMAT => Some((
+            FileKind::PublicKey,
+            Some(CONTACTS_EXPORT_FORMAT.to_string()),
+        )),
+        _ => None,
+    }
+}
+
+// 根据文件头（以及扩展名、大小）判断格式，无法识别的一律视为文档
+fn classify_bytes(head: &[u8], extension: &str, size: u64) -> (FileKind, Option<String>) {
+    let detail = |s: &str| Some(s.to_string());
+    let text = String::from_utf8_lossy(head);
+    let text = text.trim_start();
+
+    if text.starts_with("-----BEGIN PGP SIGNATURE-----") {
+        return (FileKind::PgpSignature, detail("armored"));
+    }
+    if text.starts_with("-----BEGIN PGP SIGNED MESSAGE-----") {
+        return (FileKind::PgpSignature, detail("cleartext"));
+    }
+    if text.starts_with("-----BEGIN PGP PUBLIC KEY BLOCK-----") {
+        return (FileKind::PublicKey, detail("openpgp"));
+    }
+    if text.starts_with("-----BEGIN PKCS7-----") || text.starts_with("-----BEGIN CMS-----") {
+        return (FileKind::CmsSignature, detail("pem"));
+    }
+    if text.starts_with(&format!("-----BEGIN {}-----", SIGNATURE_PEM_LABEL)) {
+        return (FileKind::SigustSignature, detail("detached"));
+    }
+    if text.starts_with("-----BEGIN PUBLIC KEY-----") {
+        let algorithm = decode_public_key_pem(text)
+            .and_then(|der| detect_public_key_algorithm(&der))
+            .map(|algorithm| algorithm.to_string())
+            .unwrap_or_else(|_| "pem".to_string());
+        return (FileKind::PublicKey, Some(algorithm));
+    }
+    if [
+        "ssh-ed25519 ",
+        "ssh-rsa ",
+        "ecdsa-sha2-nistp256 ",
+        "ecdsa-sha2-nistp384 ",
+        "ecdsa-sha2-nistp521 ",
+    ]
+    .iter()
+    .any(|prefix| text.starts_with(prefix))
+    {
+        return (FileKind::PublicKey, detail("ssh"));
+    }
+    if let Some(comment) = text.strip_prefix("untrusted comment:") {
+        let first_line = comment.lines().next().unwrap_or_default();
+        return if first_line.contains("public key") {
+            (FileKind::PublicKey, detail("minisign"))
+        } else {
+            (FileKind::MinisignSignature, None)
+        };
+    }
+
+    if head.first() == Some(&0x30)
+        && head[..head.len().min(32)]
+            .windows(CMS_SIGNED_DATA_OID.len())
+            .any(|w| w == CMS_SIGNED_DATA_OID)
+    {
+        return (FileKind::CmsSignature, detail("der"));
+    }
+    if matches!(extension, "sig" | "gpg") && is_openpgp_signature_packet(head, size) {
+        return (FileKind::PgpSignature, detail("binary"));
+    }
+    // 原始分离签名：ECDSA 最多 139 字节（P-521 DER），RSA 为模长，ML-DSA-65 为 3309 字节
+    if extension == "sig" && matches!(size, 8..=139 | 256 | 384 | 512 | 3309) {
+        return (FileKind::SigustSignature, detail("detached"));
+    }
+    (FileKind::Document, None)
+}
+
+// 二进制 OpenPGP 签名包：包头标签为 2，且包长度与文件大小一致
+fn is_openpgp_signature_packet(head: &[u8], size: u64) -> bool {
+    let (header_len, body_len) = match head {
+        // 旧格式，1 或 2 字节长度
+        [0x88, len, ..] => (2, *len as u64),
+        [0x89, hi, lo, ..] => (3, u16::from_be_bytes([*hi, *lo]) as u64),
+        // 新格式
+        [0xc2, len, ..] if *len < 192 => (2, *len as u64),
+        [0xc2, hi, lo, ..] if *hi < 224 => (3, ((*hi as u64 - 192) << 8) + *lo as u64 + 192),
+        _ => return false,
+    };
+    let version = head.get(header_len).copied();
+    header_len as u64 + body_len == size && matches!(version, Some(3..=6))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_signature_and_key_formats() {
+        let kind = |head: &[u8], ext: &str| classify_bytes(head, ext, head.len() as u64).0;
+        assert_eq!(
+            kind(b"-----BEGIN PGP SIGNATURE-----\n\n", "asc"),
+            FileKind::PgpSignature
+        );
+        assert_eq!(
+            kind(b"untrusted comment: signature from minisign\nRU", "minisig"),
+            FileKind::MinisignSignature
+        );
+        assert_eq!(
+            kind(b"untrusted comment: minisign public key 1\nRW", "pub"),
+            FileKind::PublicKey
+        );
+        assert_eq!(
+            kind(b"ssh-ed25519 AAAA me@host", "pub"),
+            FileKind::PublicKey
+        );
+        assert_eq!(
+            kind(&[0x88, 0x02, 0x04, 0x00], "sig"),
+            FileKind::PgpSignature
+        );
+        assert_eq!(kind(&[0x55; 64], "sig"), FileKind::SigustSignature);
+        assert_eq!(kind(&[0x55; 64], "bin"), FileKind::Document);
+        assert_eq!(kind(b"%PDF-1.7", "pdf"), FileKind::Document);
+
+        let envelope =
+            br#"{"signed":{"format":"sigust-envelope","algorithm":"Ed25519"},"signature":""}"#;
+        assert_eq!(
+            classify_json(envelope),
+            Some((FileKind::SigustSignature, Some("Ed25519".to_string())))
+        );
+        let vault = b"{\"format\":\"sigust-vault\",\"version\":1}\nAAAA\n";
+        assert_eq!(classify_json(vault).unwrap().0, FileKind::BackupArchive);
+        assert_eq!(classify_json(b"{\"a\":1}"), None);
+    }
+}
@@ -0,0 +1,49 @@
+// src-tauri/src/file_manager.rs
+// Jump from the app to files on disk: reveal a signature or document in the system file
+// manager, or open the key storage directory.
+use crate::key_management::get_key_storage_dir;
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use tauri_plugin_opener::OpenerExt;
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn reveal_in_file_manager(app_handle: tauri::AppHandle, path: String) -> Result<(), String> {
+    log::info!("Revealing '{}' in the file manager", path);
+    _reveal_in_file_manager(&app_handle, &path).map_err(|e| {
+        log::error!("Failed to reveal {}: {:?}", path, e);
+        e.to_string()
+    })
+}
+
+fn _reveal_in_file_manager(app_handle: &tauri::AppHandle, path: &str) -> Result<()> {
+    let path = Path::new(path);
+    if !path.exists() {
+        bail!("File not found: {}", path.display());
+    }
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", path.display()))?;
+    app_handle
+        .opener()
+        .reveal_item_in_dir(&path)
+        .with_context(|| format!("Failed to reveal {}", path.display()))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn open_key_storage_dir(app_handle: tauri::AppHandle) -> Result<(), String> {
+    log::info!("Opening the key storage directory");
+    _open_key_storage_dir(&app_handle).map_err(|e| {
+        log::error!("Failed to open key storage directory: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _open_key_storage_dir(app_handle: &tauri::AppHandle) -> Result<()> {
+    let dir = get_key_storage_dir(app_handle)?;
+    app_handle
+        .opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .with_context(|| format!("Failed to open {}", dir.display()))
+}
@@ -0,0 +1,277 @@
+// src-tauri/src/hashing.rs
+// File and directory hashing (SHA-256), run as background tasks for large inputs.
+//
+// Work over many files (directory hashes, batch signing and verification, signing request
+// manifests) goes through `hash_in_pool`: a few worker threads, with the number of files
+// processed at once limited across all running operations by the `hashThreads` setting.
+use crate::native_paths::open_file;
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use crate::tasks::spawn_task;
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+use std::thread;
+use uuid::Uuid;
+
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+const MAX_HASH_THREADS: u32 = 64;
+
+/// Files being processed by pool workers right now, across all operations.
+static BUSY_WORKERS: Mutex<usize> = Mutex::new(0);
+static WORKER_FREED: Condvar = Condvar::new();
+
+/// Digest of one file, reported as a partial result of `hash_directory`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileDigest {
+    /// Path relative to the hashed directory, with `/` separators.
+    path: String,
+    size: u64,
+    sha256: String,
+}
+
+/// Final result of a `hash_directory` task.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DirectoryDigest {
+    files: u64,
+    total_bytes: u64,
+    /// Files that could not be read, with the reason.
+    failed: Vec<String>,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn hash_directory(app_handle: tauri::AppHandle, directory: String) -> Result<Uuid, String> {
+    log::info!("Starting hash of directory '{}'", directory);
+    let root = PathBuf::from(&directory);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", directory));
+    }
+    let task_app_handle = app_handle.clone();
+    spawn_task(&app_handle, "hashDirectory", move |task| {
+        let files = collect_files(&root)?;
+        if files.is_empty() {
+            bail!("Directory contains no files: {:?}", root);
+        }
+        let total = files.len() as u64;
+        let mut result = DirectoryDigest {
+            files: 0,
+            total_bytes: 0,
+            failed: Vec::new(),
+        };
+        task.progress(0, Some(total), None);
+        let mut done = 0;
+        hash_in_pool(
+            &task_app_handle,
+            &files,
+            |path| hash_file(path),
+            |index, outcome| {
+                task.check_cancelled()?;
+                let relative = relative_display(&root, &files[index]);
+                match outcome {
+                    Ok((size, sha256)) => {
+                        result.files += 1;
+                        result.total_bytes += size;
+                        task.partial_result(FileDigest {
+                            path: relative.clone(),
+                            size,
+                            sha256,
+                        });
+                    }
+                    Err(e) => result.failed.push(format!("{}: {}", relative, e)),
+                }
+                done += 1;
+                task.progress(done, Some(total), Some(relative));
+                Ok(())
+            },
+        )?;
+        task.progress(total, Some(total), None);
+        Ok(result)
+    })
+    .map_err(|e| {
+        log::error!("Failed to start directory hash: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// Sets how many files are hashed at once (`None` restores the default, half the CPUs).
+/// Returns the limit now in effect.
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_hash_threads(
+    app_handle: tauri::AppHandle,
+    threads: Option<u32>,
+) -> Result<u32, String> {
+    log::info!("Updating hashing thread limit: {:?}", threads);
+    if let Some(threads) = threads {
+        if !(1..=MAX_HASH_THREADS).contains(&threads) {
+            return Err(format!(
+                "Thread count must be between 1 and {}.",
+                MAX_HASH_THREADS
+            ));
+        }
+    }
+    update_settings(&app_handle, |settings| settings.hash_threads = threads)
+        .map(|_| hash_thread_limit(&app_handle) as u32)
+        .map_err(|e| {
+            log::error!("Failed to update hashing thread limit: {:?}", e);
+            e.to_string()
+        })
+}
+
+// --- Helper Functions ---
+
+/// Runs `work` for each item on the hashing pool and passes the results to `on_result`
+/// on the calling thread, in completion order. An error from `on_result` (e.g. a
+/// cancelled task) stops the items not yet started and is returned.
+pub(crate) fn hash_in_pool<T: Sync, R: Send>(
+    app_handle: &tauri::AppHandle,
+    items: &[T],
+    work: impl Fn(&T) -> R + Sync,
+    mut on_result: impl FnMut(usize, R) -> Result<()>,
+) -> Result<()> {
+    let limit = hash_thread_limit(app_handle);
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..limit.min(items.len()) {
+            let sender = sender.clone();
+            let (next, stop, work) = (&next, &stop, &work);
+            scope.spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let result = {
+                        let _slot = WorkerSlot::acquire(limit);
+                        work(item)
+                    };
+                    if sender.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        for (index, result) in receiver {
+            if let Err(e) = on_result(index, result) {
+                stop.store(true, Ordering::Relaxed);
+                return Err(e);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Hashes `paths` on the pool; results are in the order of `paths`. Fails on the first
+/// file that cannot be read.
+pub(crate) fn hash_files(
+    app_handle: &tauri::AppHandle,
+    paths: &[&Path],
+) -> Result<Vec<(u64, String)>> {
+    let mut digests = vec![(0, String::new()); paths.len()];
+    hash_in_pool(
+        app_handle,
+        paths,
+        |path| hash_file(path),
+        |index, outcome| {
+            digests[index] = outcome?;
+            Ok(())
+        },
+    )?;
+    Ok(digests)
+}
+
+// 同时处理的文件数：设置值，默认为 CPU 数的一半
+fn hash_thread_limit(app_handle: &tauri::AppHandle) -> usize {
+    get_settings_path(app_handle)
+        .and_then(|path| read_settings(&path))
+        .ok()
+        .and_then(|settings| settings.hash_threads)
+        .map(|threads| threads.clamp(1, MAX_HASH_THREADS) as usize)
+        .unwrap_or_else(|| {
+            thread::available_parallelism()
+                .map(|n| n.get() / 2)
+                .unwrap_or(1)
+                .max(1)
+        })
+}
+
+/// One of the files processed at once; waits while `limit` workers are busy.
+struct WorkerSlot;
+
+impl WorkerSlot {
+    fn acquire(limit: usize) -> WorkerSlot {
+        let mut busy = BUSY_WORKERS.lock().unwrap_or_else(|e| e.into_inner());
+        while *busy >= limit {
+            busy = WORKER_FREED.wait(busy).unwrap_or_else(|e| e.into_inner());
+        }
+        *busy += 1;
+        WorkerSlot
+    }
+}
+
+impl Drop for WorkerSlot {
+    fn drop(&mut self) {
+        *BUSY_WORKERS.lock().unwrap_or_else(|e| e.into_inner()) -= 1;
+        WORKER_FREED.notify_all();
+    }
+}
+
+/// Streams a file through SHA-256, returning its size and lowercase hex digest.
+pub(crate) fn hash_file(path: &Path) -> Result<(u64, String)> {
+    let mut file = open_file(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    let mut size: u64 = 0;
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((size, hex::encode(hasher.finalize())))
+}
+
+// 递归收集目录下的普通文件（不跟随符号链接），按路径排序
+pub(crate) fn collect_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries =
+            fs::read_dir(&dir).with_context(|| format!("Failed to read directory {:?}", dir))?;
+        for entry in entries {
+            let entry = entry.with_context(|| format!("Failed to read entry in {:?}", dir))?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// 以 `/` 分隔的相对路径，便于跨平台比较
+pub(crate) fn relative_display(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
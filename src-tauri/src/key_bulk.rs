@@ -0,0 +1,245 @@
+// src-tauri/src/key_bulk.rs
+// Operations on several keys at once (multi-select in the key list). Every command
+// processes each key independently and reports a result per key instead of failing the
+// whole request on the first error.
+use crate::audit;
+use crate::consent::require_consent;
+use crate::crypto_types::{ConsentAction, KeyOperationResult};
+use crate::key_management::{
+    find_key_metadata, get_metadata_path, read_metadata, read_public_key_pem, set_trashed,
+    write_metadata,
+};
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::resolve_output_path;
+use crate::policy::check_private_key_export;
+use crate::sync::read_local_keys;
+use crate::vault::put_keys_in_vault;
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::fs;
+use uuid::Uuid;
+
+const MAX_TAG_LEN: usize = 64;
+
+// --- Tauri Commands ---
+
+/// Moves the given keys to the trash.
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_keys(
+    app_handle: tauri::AppHandle,
+    key_ids: Vec<Uuid>,
+) -> Result<Vec<KeyOperationResult>, String> {
+    log::info!("Moving {} keys to the trash", key_ids.len());
+    require_consent(
+        &app_handle,
+        ConsentAction::DeleteKey,
+        &format!("{:?}", key_ids),
+        format!("Move {} keys to the trash.", key_ids.len()),
+    )
+    .map_err(|e| e.to_string())?;
+    let results = for_each_key(&key_ids, |key_id| {
+        set_trashed(&app_handle, key_id, true).map(|_| None)
+    });
+    audit::record(
+        &app_handle,
+        "deleteKeys",
+        json!({ "keyIds": succeeded(&results) }),
+    );
+    Ok(results)
+}
+
+/// Writes the public key of each key to `<output_dir>/<key id>.pub.pem`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_public_keys(
+    app_handle: tauri::AppHandle,
+    key_ids: Vec<Uuid>,
+    output_dir: String,
+) -> Result<Vec<KeyOperationResult>, String> {
+    log::info!(
+        "Exporting {} public keys to '{}'",
+        key_ids.len(),
+        output_dir
+    );
+    let output_dir = native_path(&output_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&output_dir).map_err(|e| {
+        log::error!(
+            "Failed to create export directory {:?}: {:?}",
+            output_dir,
+            e
+        );
+        format!("Failed to create output directory: {}", e)
+    })?;
+    Ok(for_each_key(&key_ids, |key_id| {
+        let metadata = find_key_metadata(&app_handle, key_id)?;
+        let public_key_pem = read_public_key_pem(&app_handle, &metadata)?;
+        let path =
+            resolve_output_path(&app_handle, &output_dir.join(format!("{}.pub.pem", key_id)))?;
+        fs::write(&path, public_key_pem)
+            .with_context(|| format!("Failed to write public key: {:?}", path))?;
+        Ok(Some(path_string(&path)))
+    }))
+}
+
+/// Adds and removes tags on the given keys.
+#[tauri::command(rename_all = "camelCase")]
+pub fn tag_keys(
+    app_handle: tauri::AppHandle,
+    key_ids: Vec<Uuid>,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Vec<KeyOperationResult>, String> {
+    log::info!(
+        "Tagging {} keys (add {:?}, remove {:?})",
+        key_ids.len(),
+        add,
+        remove
+    );
+    let add = normalize_tags(add)?;
+    let remove = normalize_tags(remove)?;
+    _tag_keys(&app_handle, &key_ids, &add, &remove).map_err(|e| {
+        log::error!("Failed to tag keys: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _tag_keys(
+    app_handle: &tauri::AppHandle,
+    key_ids: &[Uuid],
+    add: &[String],
+    remove: &[String],
+) -> Result<Vec<KeyOperationResult>> {
+    let metadata_path = get_metadata_path(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+    let results = for_each_key(key_ids, |key_id| {
+        let metadata = all_metadata
+            .iter_mut()
+            .find(|m| m.key_id == key_id && m.trashed_at.is_none())
+            .ok_or_else(|| anyhow::anyhow!("Key with ID {} not found", key_id))?;
+        metadata.tags.retain(|tag| !remove.contains(tag));
+        metadata.tags.extend(add.iter().cloned());
+        metadata.tags.sort();
+        metadata.tags.dedup();
+        Ok(None)
+    });
+    write_metadata(&metadata_path, &all_metadata)?;
+    Ok(results)
+}
+
+/// Backs the given keys up into a key vault file (see `save_key_vault`), creating it if
+/// needed. Other keys already in the vault are kept.
+#[tauri::command(rename_all = "camelCase")]
+pub fn backup_keys(
+    app_handle: tauri::AppHandle,
+    key_ids: Vec<Uuid>,
+    vault_path: String,
+    vault_password: String,
+) -> Result<Vec<KeyOperationResult>, String> {
+    log::info!(
+        "Backing up {} keys to vault '{}'",
+        key_ids.len(),
+        vault_path
+    );
+    if vault_password.is_empty() {
+        return Err("Vault password cannot be empty.".to_string());
+    }
+    let results =
+        _backup_keys(&app_handle, &key_ids, &vault_path, &vault_password).map_err(|e| {
+            log::error!("Failed to back up keys: {:?}", e);
+            e.to_string()
+        })?;
+    audit::record(
+        &app_handle,
+        "backupKeys",
+        json!({ "keyIds": succeeded(&results), "vaultPath": vault_path }),
+    );
+    Ok(results)
+}
+
+fn _backup_keys(
+    app_handle: &tauri::AppHandle,
+    key_ids: &[Uuid],
+    vault_path: &str,
+    vault_password: &str,
+) -> Result<Vec<KeyOperationResult>> {
+    check_private_key_export()?;
+    let native_vault_path = native_path(vault_path)?;
+    require_consent(
+        app_handle,
+        ConsentAction::ExportPrivateKey,
+        &format!("{:?} {}", key_ids, vault_path),
+        format!(
+            "Back up {} private keys to '{}'.",
+            key_ids.len(),
+            vault_path
+        ),
+    )?;
+    let mut local = read_local_keys(app_handle)?;
+    let mut selected = Vec::new();
+    let mut results = for_each_key(key_ids, |key_id| {
+        let key = local
+            .remove(&key_id)
+            .ok_or_else(|| anyhow::anyhow!("Key with ID {} not found", key_id))?;
+        selected.push(key);
+        Ok(None)
+    });
+    // 保管库写入失败时，所有已选密钥都算失败
+    if let Err(e) = put_keys_in_vault(&native_vault_path, vault_password, &selected) {
+        log::error!("Failed to write key vault {}: {:?}", vault_path, e);
+        for result in results.iter_mut().filter(|r| r.error.is_none()) {
+            result.error = Some(format!("{:#}", e));
+        }
+    }
+    Ok(results)
+}
+
+// --- Helper Functions ---
+
+// 对每个密钥执行操作并收集结果；操作成功时可返回输出路径
+fn for_each_key(
+    key_ids: &[Uuid],
+    mut op: impl FnMut(Uuid) -> Result<Option<String>>,
+) -> Vec<KeyOperationResult> {
+    key_ids
+        .iter()
+        .map(|&key_id| match op(key_id) {
+            Ok(output_path) => KeyOperationResult {
+                key_id,
+                output_path,
+                error: None,
+            },
+            Err(e) => {
+                log::warn!("Bulk operation failed for key {}: {:?}", key_id, e);
+                KeyOperationResult {
+                    key_id,
+                    output_path: None,
+                    error: Some(format!("{:#}", e)),
+                }
+            }
+        })
+        .collect()
+}
+
+fn succeeded(results: &[KeyOperationResult]) -> Vec<Uuid> {
+    results
+        .iter()
+        .filter(|r| r.error.is_none())
+        .map(|r| r.key_id)
+        .collect()
+}
+
+// 去除空白，拒绝空标签或过长标签
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, String> {
+    tags.into_iter()
+        .map(|tag| {
+            let tag = tag.trim().to_string();
+            if tag.is_empty() || tag.len() > MAX_TAG_LEN {
+                Err(format!(
+                    "Tags must be non-blank and at most {} characters.",
+                    MAX_TAG_LEN
+                ))
+            } else {
+                Ok(tag)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,854 @@
+// src-tauri/src/key_derivation.rs
+// Deterministic keys from a master seed (SLIP-0010). A master seed is random bytes wrapped
+// with a password like a private key file; Ed25519 and ECDSA P-256 keys are derived from it
+// along hardened paths such as `m/1'/0'` (one path per project or client) and then stored
+// as ordinary key files. Backing up the seed and remembering the paths is enough to
+// recover every derived key: deriving the same path again yields the same key pair.
+//
+// Only hardened derivation is supported, as SLIP-0010 requires for Ed25519; RSA keys
+// cannot be derived.
+//
+// Seeds of 16 to 32 bytes (a multiple of 4) can also be written down as a BIP-39 mnemonic.
+// The mnemonic encodes the seed bytes themselves (they are the BIP-39 entropy; there is no
+// PBKDF2 stretching as in wallets). `recover_keys_from_mnemonic` restores the seed on a new
+// device and re-derives the keys named in a derivation list (`list_derived_keys`), checking
+// each against its recorded fingerprint.
+use crate::audit;
+use crate::consent::require_consent;
+use crate::crypto_types::{
+    ConsentAction, DerivedKeyRecord, KeyDerivation, KeyDetails, KeyInfo, KeyKdf, KeyMetadata,
+    MasterSeedInfo, SignatureAlgorithm,
+};
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, get_app_data_file, get_metadata_path,
+    normalize_fingerprint, read_metadata, read_public_key_pem, store_key_pair, NewKeyPair,
+};
+use crate::key_wrapping::{wrap_private_key, WrappedKey};
+use crate::policy::{check_key_use, check_private_key_export, check_private_keys_allowed};
+use crate::settings::recommended_wrap_kdf;
+use crate::store_journal::StoreTransaction;
+use aead::OsRng;
+use anyhow::{anyhow, bail, Context, Result};
+use bip39::Mnemonic;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use p256::elliptic_curve::{Field, PrimeField};
+use pkcs8::{EncodePrivateKey, EncodePublicKey};
+use rsa::rand_core::RngCore;
+use serde_json::json;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+const MASTER_SEEDS_FILENAME: &str = "master_seeds.json";
+/// Length of generated seeds; imported seeds may be 16 to 64 bytes (SLIP-0010).
+const SEED_LEN: usize = 32;
+const MIN_SEED_LEN: usize = 16;
+const MAX_SEED_LEN: usize = 64;
+const MAX_PATH_DEPTH: usize = 10;
+const HARDENED: u32 = 0x8000_0000;
+const SEED_FINGERPRINT_PREFIX: &[u8] = b"sigust-master-seed:";
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_master_seeds(app_handle: tauri::AppHandle) -> Result<Vec<MasterSeedInfo>, String> {
+    log::info!("Listing master seeds");
+    get_master_seeds_path(&app_handle)
+        .and_then(|path| read_master_seeds(&path))
+        .map_err(|e| {
+            log::error!("Failed to list master seeds: {:?}", e);
+            e.to_string()
+        })
+}
+
+/// Generates a new random master seed protected by `password` (wrapped with `kdf`,
+/// PBKDF2 by default).
+#[tauri::command(rename_all = "camelCase")]
+pub fn create_master_seed(
+    app_handle: tauri::AppHandle,
+    name: String,
+    password: String,
+    kdf: Option<KeyKdf>,
+) -> Result<MasterSeedInfo, String> {
+    log::info!("Creating master seed '{}'", name);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    let mut seed = Zeroizing::new(vec![0u8; SEED_LEN]);
+    OsRng.fill_bytes(&mut seed);
+    store_master_seed(
+        &app_handle,
+        &name,
+        &seed,
+        &password,
+        kdf.unwrap_or_default(),
+    )
+    .map_err(|e| {
+        log::error!("Failed to create master seed: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|info| {
+        audit::record(
+            &app_handle,
+            "createMasterSeed",
+            json!({ "seedId": info.seed_id, "fingerprint": info.fingerprint }),
+        )
+    })
+}
+
+/// Restores a master seed from its backup (hex, as returned by `export_master_seed`).
+#[tauri::command(rename_all = "camelCase")]
+pub fn import_master_seed(
+    app_handle: tauri::AppHandle,
+    name: String,
+    seed: String,
+    password: String,
+    kdf: Option<KeyKdf>,
+) -> Result<MasterSeedInfo, String> {
+    log::info!("Importing master seed '{}'", name);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    hex::decode(seed.trim())
+        .context("The master seed backup is not valid hex")
+        .and_then(|seed| {
+            store_master_seed(
+                &app_handle,
+                &name,
+                &Zeroizing::new(seed),
+                &password,
+                kdf.unwrap_or_default(),
+            )
+        })
+        .map_err(|e| {
+            log::error!("Failed to import master seed: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|info| {
+            audit::record(
+                &app_handle,
+                "importMasterSeed",
+                json!({ "seedId": info.seed_id, "fingerprint": info.fingerprint }),
+            )
+        })
+}
+
+/// Returns the master seed as hex for an offline backup.
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_master_seed(
+    app_handle: tauri::AppHandle,
+    seed_id: Uuid,
+    password: String,
+) -> Result<String, String> {
+    log::info!("Exporting master seed {}", seed_id);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    check_private_key_export()
+        .and_then(|_| require_seed_export_consent(&app_handle, seed_id, "hex"))
+        .and_then(|_| read_master_seed(&app_handle, seed_id, &password))
+        .map(|(_, seed)| hex::encode(&*seed))
+        .map_err(|e| {
+            log::error!("Failed to export master seed {}: {:?}", seed_id, e);
+            e.to_string()
+        })
+        .inspect(|_| {
+            audit::record(
+                &app_handle,
+                "exportMasterSeed",
+                json!({ "seedId": seed_id }),
+            )
+        })
+}
+
+/// Returns the master seed as a BIP-39 mnemonic (12 to 24 words) for a written backup.
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_master_seed_mnemonic(
+    app_handle: tauri::AppHandle,
+    seed_id: Uuid,
+    password: String,
+) -> Result<String, String> {
+    log::info!("Exporting master seed {} as mnemonic", seed_id);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    check_private_key_export()
+        .and_then(|_| require_seed_export_consent(&app_handle, seed_id, "mnemonic"))
+        .and_then(|_| read_master_seed(&app_handle, seed_id, &password))
+        .and_then(|(_, seed)| {
+            Mnemonic::from_entropy(&seed)
+                .map(|mnemonic| mnemonic.to_string())
+                .map_err(|e| anyhow!("This master seed has no mnemonic form: {}", e))
+        })
+        .map_err(|e| {
+            log::error!(
+                "Failed to export master seed {} as mnemonic: {:?}",
+                seed_id,
+                e
+            );
+            e.to_string()
+        })
+        .inspect(|_| {
+            audit::record(
+                &app_handle,
+                "exportMasterSeedMnemonic",
+                json!({ "seedId": seed_id }),
+            )
+        })
+}
+
+/// Lists the keys derived from a master seed, to keep next to its mnemonic. Contains only
+/// public information.
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_derived_keys(
+    app_handle: tauri::AppHandle,
+    seed_id: Uuid,
+) -> Result<Vec<DerivedKeyRecord>, String> {
+    log::info!("Listing keys derived from master seed {}", seed_id);
+    _list_derived_keys(&app_handle, seed_id).map_err(|e| {
+        log::error!(
+            "Failed to list keys derived from master seed {}: {:?}",
+            seed_id,
+            e
+        );
+        e.to_string()
+    })
+}
+
+fn _list_derived_keys(
+    app_handle: &tauri::AppHandle,
+    seed_id: Uuid,
+) -> Result<Vec<DerivedKeyRecord>> {
+    let seed = find_master_seed(app_handle, seed_id)?;
+    read_metadata(&get_metadata_path(app_handle)?)?
+        .into_iter()
+        .filter_map(|m| {
+            let derivation = m.derivation.clone()?;
+            (derivation.seed_fingerprint == seed.fingerprint).then_some((m, derivation))
+        })
+        .map(|(m, derivation)| {
+            let public_key_der = decode_public_key_pem(&read_public_key_pem(app_handle, &m)?)?;
+            Ok(DerivedKeyRecord {
+                name: m.name,
+                algorithm: m.algorithm,
+                path: derivation.path,
+                fingerprint: compute_fingerprint(&public_key_der),
+            })
+        })
+        .collect()
+}
+
+/// Restores a master seed from its mnemonic and re-derives the listed keys, protecting the
+/// seed with `seed_password` and the keys with `password`. Every key must match its
+/// recorded fingerprint; keys already in the store are skipped. Returns the restored keys.
+#[tauri::command(rename_all = "camelCase")]
+pub fn recover_keys_from_mnemonic(
+    app_handle: tauri::AppHandle,
+    name: String,
+    mnemonic: String,
+    seed_password: String,
+    password: String,
+    keys: Vec<DerivedKeyRecord>,
+) -> Result<Vec<KeyInfo>, String> {
+    log::info!(
+        "Recovering {} keys from mnemonic for master seed '{}'",
+        keys.len(),
+        name
+    );
+    if seed_password.is_empty() || password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _recover_keys_from_mnemonic(
+        &app_handle,
+        &name,
+        &mnemonic,
+        &seed_password,
+        &password,
+        &keys,
+    )
+    .map_err(|e| {
+        log::error!("Failed to recover keys from mnemonic: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|recovered| {
+        audit::record(
+            &app_handle,
+            "recoverKeysFromMnemonic",
+            json!({
+                "keyIds": recovered.iter().map(|k| k.key_id).collect::<Vec<_>>(),
+            }),
+        )
+    })
+}
+
+fn _recover_keys_from_mnemonic(
+    app_handle: &tauri::AppHandle,
+    name: &str,
+    mnemonic: &str,
+    seed_password: &str,
+    password: &str,
+    keys: &[DerivedKeyRecord],
+) -> Result<Vec<KeyInfo>> {
+    let words = Zeroizing::new(
+        mnemonic
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase(),
+    );
+    let seed = Zeroizing::new(
+        Mnemonic::parse_normalized(&words)
+            .map_err(|e| anyhow!("Invalid mnemonic: {}", e))?
+            .to_entropy(),
+    );
+    let fingerprint = seed_fingerprint(&seed);
+
+    // 先全部派生并核对指纹，助记词不对时什么都不写
+    let mut derived = Vec::with_capacity(keys.len());
+    for record in keys {
+        let algorithm = record.algorithm.clone();
+        let indices = parse_path(&record.path)?;
+        check_key_use(app_handle, &algorithm, None)?;
+        let (private_key_der, public_key_der) =
+            derive_key(Curve::for_algorithm(&algorithm)?, &seed, &indices)?;
+        if compute_fingerprint(&public_key_der) != normalize_fingerprint(&record.fingerprint) {
+            bail!(
+                "Key '{}' at {} does not match its fingerprint; check the mnemonic",
+                record.name,
+                record.path
+            );
+        }
+        let derivation = KeyDerivation {
+            seed_fingerprint: fingerprint.clone(),
+            path: format_path(&indices),
+        };
+        derived.push((
+            record,
+            algorithm,
+            derivation,
+            private_key_der,
+            public_key_der,
+        ));
+    }
+
+    let seeds = read_master_seeds(&get_master_seeds_path(app_handle)?)?;
+    if !seeds.iter().any(|s| s.fingerprint == fingerprint) {
+        store_master_seed(app_handle, name, &seed, seed_password, KeyKdf::default())?;
+    }
+    let mut recovered = Vec::new();
+    for (record, algorithm, derivation, private_key_der, public_key_der) in derived {
+        if let Some(existing) = find_derived_key(app_handle, &derivation, &algorithm)? {
+            log::info!(
+                "Key '{}' at {} is already present as {}",
+                record.name,
+                derivation.path,
+                existing.key_id
+            );
+            continue;
+        }
+        let details = store_key_pair(
+            app_handle,
+            NewKeyPair {
+                name: record.name.clone(),
+                algorithm,
+                private_key_der: &private_key_der,
+                public_key_der: &public_key_der,
+                identity: None,
+                escrow: false,
+                derivation: Some(derivation),
+                device_bound: false,
+                kdf: KeyKdf::default(),
+            },
+            password,
+        )?;
+        recovered.push(details.info);
+    }
+    Ok(recovered)
+}
+
+/// Forgets a master seed. Keys derived from it stay in the key store.
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_master_seed(app_handle: tauri::AppHandle, seed_id: Uuid) -> Result<(), String> {
+    log::info!("Deleting master seed {}", seed_id);
+    _delete_master_seed(&app_handle, seed_id)
+        .map_err(|e| {
+            log::error!("Failed to delete master seed {}: {:?}", seed_id, e);
+            e.to_string()
+        })
+        .inspect(|_| {
+            audit::record(
+                &app_handle,
+                "deleteMasterSeed",
+                json!({ "seedId": seed_id }),
+            )
+        })
+}
+
+fn _delete_master_seed(app_handle: &tauri::AppHandle, seed_id: Uuid) -> Result<()> {
+    let info = find_master_seed(app_handle, seed_id)?;
+    require_consent(
+        app_handle,
+        ConsentAction::DeleteKey,
+        &format!("seed:{}", seed_id),
+        format!("Permanently delete the master seed '{}'.", info.name),
+    )?;
+    let path = get_master_seeds_path(app_handle)?;
+    let mut seeds = read_master_seeds(&path)?;
+    let count = seeds.len();
+    seeds.retain(|s| s.seed_id != seed_id);
+    if seeds.len() == count {
+        bail!("Master seed with ID {} not found", seed_id);
+    }
+    // 主种子可派生出全部密钥：覆盖后删除，与种子列表在同一事务中更新
+    let seed_path = get_seed_file_path(app_handle, seed_id)?;
+    let mut transaction = StoreTransaction::begin(app_handle, "deleteMasterSeed")?;
+    transaction.write(
+        &path,
+        serde_json::to_string_pretty(&seeds).context("Failed to serialize master seeds")?,
+    )?;
+    transaction
+        .remove_wiped(&seed_path)
+        .with_context(|| format!("Failed to delete master seed file {:?}", seed_path))?;
+    transaction.commit()
+}
+
+/// Derives the key at `path` from a master seed and adds it to the key store, protected by
+/// `password`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn derive_key_pair(
+    app_handle: tauri::AppHandle,
+    seed_id: Uuid,
+    seed_password: String,
+    path: String,
+    name: String,
+    alg_str: String,
+    password: String,
+) -> Result<KeyDetails, String> {
+    log::info!(
+        "Deriving {} key '{}' at {} from master seed {}",
+        alg_str,
+        name,
+        path,
+        seed_id
+    );
+    if seed_password.is_empty() || password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _derive_key_pair(
+        &app_handle,
+        seed_id,
+        &seed_password,
+        &path,
+        name,
+        &alg_str,
+        &password,
+    )
+    .map_err(|e| {
+        log::error!("Failed to derive key pair: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|details| {
+        audit::record(
+            &app_handle,
+            "deriveKeyPair",
+            json!({
+                "keyId": details.info.key_id,
+                "name": details.info.name,
+                "algorithm": details.info.algorithm,
+                "derivation": details.info.derivation,
+            }),
+        )
+    })
+}
+
+fn _derive_key_pair(
+    app_handle: &tauri::AppHandle,
+    seed_id: Uuid,
+    seed_password: &str,
+    path: &str,
+    name: String,
+    alg_str: &str,
+    password: &str,
+) -> Result<KeyDetails> {
+    let algorithm = SignatureAlgorithm::from_str(alg_str)?;
+    let curve = Curve::for_algorithm(&algorithm)?;
+    let indices = parse_path(path)?;
+    check_key_use(app_handle, &algorithm, None)?;
+
+    let (info, seed) = read_master_seed(app_handle, seed_id, seed_password)?;
+    let derivation = KeyDerivation {
+        seed_fingerprint: info.fingerprint,
+        path: format_path(&indices),
+    };
+    if let Some(existing) = find_derived_key(app_handle, &derivation, &algorithm)? {
+        bail!(
+            "This key was already derived as '{}' ({})",
+            existing.name,
+            existing.key_id
+        );
+    }
+
+    let (private_key_der, public_key_der) = derive_key(curve, &seed, &indices)?;
+    store_key_pair(
+        app_handle,
+        NewKeyPair {
+            name,
+            algorithm,
+            private_key_der: &private_key_der,
+            public_key_der: &public_key_der,
+            identity: None,
+            escrow: false,
+            derivation: Some(derivation),
+            device_bound: false,
+            kdf: KeyKdf::default(),
+        },
+        password,
+    )
+}
+
+// --- Helper Functions ---
+
+// 查找已从同一种子、路径和算法派生的密钥
+fn find_derived_key(
+    app_handle: &tauri::AppHandle,
+    derivation: &KeyDerivation,
+    algorithm: &SignatureAlgorithm,
+) -> Result<Option<KeyMetadata>> {
+    Ok(read_metadata(&get_metadata_path(app_handle)?)?
+        .into_iter()
+        .find(|m| m.derivation.as_ref() == Some(derivation) && m.algorithm == *algorithm))
+}
+
+#[derive(Clone, Copy)]
+enum Curve {
+    Ed25519,
+    NistP256,
+}
+
+impl Curve {
+    fn for_algorithm(algorithm: &SignatureAlgorithm) -> Result<Self> {
+        match algorithm {
+            SignatureAlgorithm::Ed25519 => Ok(Curve::Ed25519),
+            SignatureAlgorithm::EcdsaP256Sha256 => Ok(Curve::NistP256),
+            SignatureAlgorithm::RsaPkcs1Sha256 => {
+                bail!("RSA keys cannot be derived from a master seed")
+            }
+            // SLIP-0010 only defines derivation for P-256 among the NIST curves
+            SignatureAlgorithm::EcdsaP384Sha384 | SignatureAlgorithm::EcdsaP521Sha512 => {
+                bail!("ECDSA P-384 and P-521 keys cannot be derived from a master seed")
+            }
+            SignatureAlgorithm::MlDsa65 => {
+                bail!("ML-DSA-65 keys cannot be derived from a master seed")
+            }
+        }
+    }
+
+    /// HMAC key for the master node, as defined by SLIP-0010.
+    fn seed_key(self) -> &'static [u8] {
+        match self {
+            Curve::Ed25519 => b"ed25519 seed",
+            Curve::NistP256 => b"Nist256p1 seed",
+        }
+    }
+}
+
+/// A private key with its chain code.
+struct Node {
+    key: Zeroizing<[u8; 32]>,
+    chain_code: Zeroizing<[u8; 32]>,
+}
+
+/// Derives the key at `indices` (hardened) and returns its PKCS#8 and SPKI DER.
+fn derive_key(curve: Curve, seed: &[u8], indices: &[u32]) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>)> {
+    let node = derive_node(curve, seed, indices)?;
+    match curve {
+        Curve::Ed25519 => {
+            let signing_key = ed25519_dalek::SigningKey::from_bytes(&node.key);
+            Ok((
+                Zeroizing::new(signing_key.to_pkcs8_der()?.as_bytes().to_vec()),
+                signing_key.verifying_key().to_public_key_der()?.into_vec(),
+            ))
+        }
+        Curve::NistP256 => {
+            let signing_key = p256::ecdsa::SigningKey::from_bytes(&(*node.key).into())
+                .context("Derived an invalid P-256 key")?;
+            Ok((
+                Zeroizing::new(signing_key.to_pkcs8_der()?.as_bytes().to_vec()),
+                signing_key.verifying_key().to_public_key_der()?.into_vec(),
+            ))
+        }
+    }
+}
+
+fn derive_node(curve: Curve, seed: &[u8], indices: &[u32]) -> Result<Node> {
+    // 主节点：无效的 P-256 私钥时对结果再做 HMAC
+    let mut i = hmac_sha512(curve.seed_key(), seed)?;
+    while !is_valid_key(curve, &i[..32]) {
+        i = hmac_sha512(curve.seed_key(), &i[..])?;
+    }
+    let mut node = split_node(&i);
+    for &index in indices {
+        node = child_node(curve, &node, index)?;
+    }
+    Ok(node)
+}
+
+// 硬化子节点：data = 0x00 || k || ser32(i)
+fn child_node(curve: Curve, parent: &Node, index: u32) -> Result<Node> {
+    let index = (index | HARDENED).to_be_bytes();
+    let mut data = Zeroizing::new(Vec::with_capacity(37));
+    data.push(0);
+    data.extend_from_slice(&parent.key[..]);
+    data.extend_from_slice(&index);
+    loop {
+        let i = hmac_sha512(&parent.chain_code[..], &data)?;
+        let child = split_node(&i);
+        match curve {
+            Curve::Ed25519 => return Ok(child),
+            Curve::NistP256 => {
+                if let Some(key) = add_p256_scalars(&child.key[..], &parent.key[..]) {
+                    return Ok(Node {
+                        key,
+                        chain_code: child.chain_code,
+                    });
+                }
+                // 结果无效时改用 0x01 || IR || ser32(i) 重试
+                data.clear();
+                data.push(1);
+                data.extend_from_slice(&i[32..]);
+                data.extend_from_slice(&index);
+            }
+        }
+    }
+}
+
+fn is_valid_key(curve: Curve, key: &[u8]) -> bool {
+    match curve {
+        Curve::Ed25519 => true,
+        Curve::NistP256 => p256_scalar(key).is_some_and(|s| !bool::from(s.is_zero())),
+    }
+}
+
+// (IL + k) mod n；IL 超出范围或结果为零时返回 None
+fn add_p256_scalars(tweak: &[u8], parent: &[u8]) -> Option<Zeroizing<[u8; 32]>> {
+    let sum = p256_scalar(tweak)? + p256_scalar(parent)?;
+    if bool::from(sum.is_zero()) {
+        return None;
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&sum.to_repr());
+    Some(key)
+}
+
+fn p256_scalar(bytes: &[u8]) -> Option<p256::Scalar> {
+    p256::Scalar::from_repr(*p256::FieldBytes::from_slice(bytes)).into()
+}
+
+fn split_node(i: &[u8; 64]) -> Node {
+    let mut key = Zeroizing::new([0u8; 32]);
+    let mut chain_code = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&i[..32]);
+    chain_code.copy_from_slice(&i[32..]);
+    Node { key, chain_code }
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> Result<Zeroizing<[u8; 64]>> {
+    let mut mac = <Hmac<Sha512> as Mac>::new_from_slice(key)
+        .map_err(|e| anyhow!("Failed to create HMAC: {}", e))?;
+    mac.update(data);
+    Ok(Zeroizing::new(mac.finalize().into_bytes().into()))
+}
+
+// 解析 m/1'/0' 形式的路径（只接受硬化索引，' 或 h 均可）
+fn parse_path(path: &str) -> Result<Vec<u32>> {
+    let mut segments = path.trim().split('/');
+    if segments.next() != Some("m") {
+        bail!("A derivation path starts with 'm', e.g. m/1'/0'");
+    }
+    let indices = segments
+        .map(|segment| {
+            let index = segment
+                .strip_suffix('\'')
+                .or_else(|| segment.strip_suffix('h'))
+                .ok_or_else(|| {
+                    anyhow!("Only hardened path segments are supported: '{}'", segment)
+                })?;
+            index
+                .parse::<u32>()
+                .ok()
+                .filter(|index| *index < HARDENED)
+                .ok_or_else(|| anyhow!("Invalid derivation path segment: '{}'", segment))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if indices.is_empty() || indices.len() > MAX_PATH_DEPTH {
+        bail!(
+            "A derivation path needs between 1 and {} segments",
+            MAX_PATH_DEPTH
+        );
+    }
+    Ok(indices)
+}
+
+fn format_path(indices: &[u32]) -> String {
+    indices.iter().fold("m".to_string(), |path, index| {
+        format!("{}/{}'", path, index)
+    })
+}
+
+// 种子指纹：加前缀的 SHA-256，取前 8 字节
+fn seed_fingerprint(seed: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(SEED_FINGERPRINT_PREFIX);
+    hasher.update(seed);
+    hex::encode(&hasher.finalize()[..8])
+}
+
+fn store_master_seed(
+    app_handle: &tauri::AppHandle,
+    name: &str,
+    seed: &[u8],
+    password: &str,
+    kdf: KeyKdf,
+) -> Result<MasterSeedInfo> {
+    // 主种子可派生出私钥，仅验证安装不能创建或导入
+    check_private_keys_allowed()?;
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("Master seed name cannot be empty");
+    }
+    if !(MIN_SEED_LEN..=MAX_SEED_LEN).contains(&seed.len()) {
+        bail!(
+            "A master seed has {} to {} bytes",
+            MIN_SEED_LEN,
+            MAX_SEED_LEN
+        );
+    }
+    let path = get_master_seeds_path(app_handle)?;
+    let mut seeds = read_master_seeds(&path)?;
+    let fingerprint = seed_fingerprint(seed);
+    if let Some(existing) = seeds.iter().find(|s| s.fingerprint == fingerprint) {
+        bail!("This master seed is already stored as '{}'", existing.name);
+    }
+
+    let info = MasterSeedInfo {
+        seed_id: Uuid::new_v4(),
+        name: name.to_string(),
+        fingerprint,
+        created_at: Utc::now(),
+    };
+    let wrapped = wrap_private_key(seed, password, recommended_wrap_kdf(app_handle, kdf))?;
+    // 种子文件与种子列表在同一事务中写入
+    let seed_path = get_seed_file_path(app_handle, info.seed_id)?;
+    let mut transaction = StoreTransaction::begin(app_handle, "addMasterSeed")?;
+    transaction
+        .write(&seed_path, wrapped)
+        .with_context(|| format!("Failed to write master seed to {:?}", seed_path))?;
+    seeds.push(info.clone());
+    transaction.write(
+        &path,
+        serde_json::to_string_pretty(&seeds).context("Failed to serialize master seeds")?,
+    )?;
+    transaction.commit()?;
+    Ok(info)
+}
+
+fn find_master_seed(app_handle: &tauri::AppHandle, seed_id: Uuid) -> Result<MasterSeedInfo> {
+    read_master_seeds(&get_master_seeds_path(app_handle)?)?
+        .into_iter()
+        .find(|s| s.seed_id == seed_id)
+        .ok_or_else(|| anyhow!("Master seed with ID {} not found", seed_id))
+}
+
+// 导出主种子前需要用户确认（form 为 "hex" 或 "mnemonic"）
+fn require_seed_export_consent(
+    app_handle: &tauri::AppHandle,
+    seed_id: Uuid,
+    form: &str,
+) -> Result<()> {
+    let info = find_master_seed(app_handle, seed_id)?;
+    require_consent(
+        app_handle,
+        ConsentAction::ExportPrivateKey,
+        &format!("seed-{}:{}", form, seed_id),
+        format!("Show the master seed '{}' ({}).", info.name, form),
+    )
+}
+
+fn read_master_seed(
+    app_handle: &tauri::AppHandle,
+    seed_id: Uuid,
+    password: &str,
+) -> Result<(MasterSeedInfo, Zeroizing<Vec<u8>>)> {
+    let info = find_master_seed(app_handle, seed_id)?;
+    let seed_path = get_seed_file_path(app_handle, seed_id)?;
+    let bytes = fs::read(&seed_path)
+        .with_context(|| format!("Failed to read master seed file {:?}", seed_path))?;
+    let seed = Zeroizing::new(
+        WrappedKey::parse(&bytes)?
+            .unwrap_key(password)
+            .context("Failed to decrypt master seed (check password)")?,
+    );
+    Ok((info, seed))
+}
+
+// 种子文件不放在密钥目录，免得被存储检查当作孤立文件
+fn get_seed_file_path(app_handle: &tauri::AppHandle, seed_id: Uuid) -> Result<PathBuf> {
+    get_app_data_file(app_handle, &format!("{}.seed.enc", seed_id))
+}
+
+// 获取主种子列表文件路径
+fn get_master_seeds_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, MASTER_SEEDS_FILENAME)
+}
+
+fn read_master_seeds(path: &Path) -> Result<Vec<MasterSeedInfo>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read master seeds file: {:?}", path))?;
+    serde_json::from_str(&content).context("Failed to parse master seeds JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_slip10_test_vectors() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let node = derive_node(Curve::Ed25519, &seed, &[0]).unwrap();
+        assert_eq!(
+            hex::encode(*node.key),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+        assert_eq!(
+            hex::encode(*node.chain_code),
+            "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69"
+        );
+        let node = derive_node(Curve::NistP256, &seed, &[0]).unwrap();
+        assert_eq!(
+            hex::encode(*node.key),
+            "6939694369114c67917a182c59ddb8cafc3004e63ca5d3b84403ba8613debc0c"
+        );
+        assert_eq!(
+            hex::encode(*node.chain_code),
+            "3460cea53e6a6bb5fb391eeef3237ffd8724bf0a40e94943c98b83825342ee11"
+        );
+
+        assert_eq!(format_path(&parse_path("m/44h/0'").unwrap()), "m/44'/0'");
+        assert!(parse_path("m/0").is_err());
+        assert!(parse_path("m").is_err());
+
+        let mnemonic = Mnemonic::from_entropy(&seed).unwrap().to_string();
+        assert_eq!(mnemonic.split(' ').count(), 12);
+        assert_eq!(
+            Mnemonic::parse_normalized(&mnemonic).unwrap().to_entropy(),
+            seed
+        );
+    }
+}
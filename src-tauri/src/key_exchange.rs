@@ -0,0 +1,279 @@
+// src-tauri/src/key_exchange.rs
+// Offline, in-person public key exchange via QR codes: one device renders a QR code
+// for its own key, the other scans it (mobile barcode scanner plugin), previews it,
+// and stores it as a verified contact once both users have compared fingerprints.
+// ML-DSA public keys do not fit in a QR code; they are shared as files and only their
+// fingerprint is exchanged this way.
+use crate::contacts::{get_contacts_path, read_contacts, upsert_contact, write_contacts};
+use crate::crypto_types::{Contact, KeyExchangePreview, KeyExchangeQr, KeyMetadata, TrustLevel};
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, detect_public_key_algorithm, find_key_metadata,
+    normalize_fingerprint, read_public_key_pem,
+};
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
+use pkcs8::der::pem::PemLabel;
+use pkcs8::{LineEnding, SubjectPublicKeyInfoRef};
+use qrcode::render::svg;
+use qrcode::types::QrError;
+use qrcode::QrCode;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Prefix of a payload carrying a full public key.
+const KEY_PAYLOAD_PREFIX: &str = "SIGUST-KEY:";
+/// Prefix of a payload carrying only a fingerprint (to confirm a key received another way).
+const FINGERPRINT_PAYLOAD_PREFIX: &str = "SIGUST-FPR:";
+const KEY_EXCHANGE_VERSION: u8 = 1;
+const QR_MIN_DIMENSION: u32 = 256;
+
+/// Body of a `SIGUST-KEY:` payload, serialized as JSON and encoded as unpadded base64url.
+#[derive(Serialize, Deserialize)]
+struct KeyPayloadBody {
+    v: u8,
+    name: String,
+    alg: String,
+    /// SPKI DER of the public key, unpadded base64url.
+    pk: String,
+}
+
+/// A decoded key exchange payload.
+enum ScannedPayload {
+    Key {
+        name: String,
+        public_key_der: Vec<u8>,
+    },
+    Fingerprint(String),
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_key_exchange_qr(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    fingerprint_only: bool,
+) -> Result<KeyExchangeQr, String> {
+    log::info!("Rendering key exchange QR code for key ID: {}", key_id);
+    _get_key_exchange_qr(&app_handle, key_id, fingerprint_only).map_err(|e| {
+        log::error!("Failed to render key exchange QR for {}: {:?}", key_id, e);
+        e.to_string()
+    })
+}
+
+fn _get_key_exchange_qr(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    fingerprint_only: bool,
+) -> Result<KeyExchangeQr> {
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let public_key_pem = read_public_key_pem(app_handle, &metadata)?;
+    let public_key_der = decode_public_key_pem(&public_key_pem)?;
+    let fingerprint = compute_fingerprint(&public_key_der);
+
+    let payload = if fingerprint_only {
+        fingerprint_payload(&fingerprint)
+    } else {
+        key_payload(&metadata, &public_key_der)?
+    };
+
+    let code = match QrCode::new(payload.as_bytes()) {
+        Err(QrError::DataTooLong) => bail!(
+            "{} public keys are too large for a QR code; send the public key file and \
+             confirm it with the fingerprint code",
+            metadata.algorithm
+        ),
+        code => code.context("Failed to encode payload as QR code")?,
+    };
+    let svg = code
+        .render::<svg::Color<'_>>()
+        .min_dimensions(QR_MIN_DIMENSION, QR_MIN_DIMENSION)
+        .build();
+
+    Ok(KeyExchangeQr {
+        payload,
+        svg,
+        fingerprint,
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn parse_key_exchange_payload(
+    app_handle: tauri::AppHandle,
+    payload: String,
+) -> Result<KeyExchangePreview, String> {
+    log::info!("Parsing scanned key exchange payload");
+    _parse_key_exchange_payload(&app_handle, &payload).map_err(|e| {
+        log::error!("Failed to parse key exchange payload: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _parse_key_exchange_payload(
+    app_handle: &tauri::AppHandle,
+    payload: &str,
+) -> Result<KeyExchangePreview> {
+    let contacts = read_contacts(&get_contacts_path(app_handle)?)?;
+    let preview = match decode_payload(payload)? {
+        ScannedPayload::Key {
+            name,
+            public_key_der,
+        } => {
+            let algorithm = detect_public_key_algorithm(&public_key_der)?;
+            let fingerprint = compute_fingerprint(&public_key_der);
+            KeyExchangePreview {
+                name: Some(name),
+                algorithm: Some(algorithm),
+                public_key_pem: Some(encode_public_key_pem(&public_key_der)?),
+                existing_contact_id: contacts
+                    .iter()
+                    .find(|c| c.fingerprint == fingerprint)
+                    .map(|c| c.contact_id),
+                fingerprint,
+            }
+        }
+        ScannedPayload::Fingerprint(fingerprint) => KeyExchangePreview {
+            name: None,
+            algorithm: None,
+            public_key_pem: None,
+            existing_contact_id: contacts
+                .iter()
+                .find(|c| c.fingerprint == fingerprint)
+                .map(|c| c.contact_id),
+            fingerprint,
+        },
+    };
+    Ok(preview)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn confirm_key_exchange(
+    app_handle: tauri::AppHandle,
+    payload: String,
+    confirmed_fingerprint: String,
+    name: Option<String>,
+) -> Result<Contact, String> {
+    log::info!("Confirming key exchange");
+    _confirm_key_exchange(&app_handle, &payload, &confirmed_fingerprint, name).map_err(|e| {
+        log::error!("Failed to confirm key exchange: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _confirm_key_exchange(
+    app_handle: &tauri::AppHandle,
+    payload: &str,
+    confirmed_fingerprint: &str,
+    name: Option<String>,
+) -> Result<Contact> {
+    let contacts_path = get_contacts_path(app_handle)?;
+    let confirmed_fingerprint = normalize_fingerprint(confirmed_fingerprint);
+
+    match decode_payload(payload)? {
+        ScannedPayload::Key {
+            name: sender_name,
+            public_key_der,
+        } => {
+            let fingerprint = compute_fingerprint(&public_key_der);
+            if fingerprint != confirmed_fingerprint {
+                bail!("Fingerprint confirmation does not match the scanned key");
+            }
+            let algorithm = detect_public_key_algorithm(&public_key_der)?;
+            let contact = upsert_contact(
+                &contacts_path,
+                name.unwrap_or(sender_name),
+                algorithm,
+                encode_public_key_pem(&public_key_der)?,
+                fingerprint,
+                TrustLevel::Verified,
+            )?;
+            log::info!("Stored verified contact {}", contact.contact_id);
+            Ok(contact)
+        }
+        ScannedPayload::Fingerprint(fingerprint) => {
+            if fingerprint != confirmed_fingerprint {
+                bail!("Fingerprint confirmation does not match the scanned fingerprint");
+            }
+            let mut contacts = read_contacts(&contacts_path)?;
+            let contact = contacts
+                .iter_mut()
+                .find(|c| c.fingerprint == fingerprint)
+                .ok_or_else(|| anyhow::anyhow!("No contact with fingerprint {}", fingerprint))?;
+            contact.trust = TrustLevel::Verified;
+            contact.updated_at = Utc::now();
+            if let Some(name) = name {
+                contact.name = name;
+            }
+            let contact = contact.clone();
+            write_contacts(&contacts_path, &contacts)?;
+            log::info!("Marked contact {} as verified", contact.contact_id);
+            Ok(contact)
+        }
+    }
+}
+
+// --- Helper Functions ---
+
+/// `SIGUST-KEY:` payload carrying the public key of `metadata`; scanning it in sigust
+/// offers to add the key as a contact.
+pub(crate) fn key_payload(metadata: &KeyMetadata, public_key_der: &[u8]) -> Result<String> {
+    let body = KeyPayloadBody {
+        v: KEY_EXCHANGE_VERSION,
+        name: metadata.name.clone(),
+        alg: metadata.algorithm.to_string(),
+        pk: URL_SAFE_NO_PAD.encode(public_key_der),
+    };
+    let json = serde_json::to_vec(&body).context("Failed to serialize key payload")?;
+    Ok(format!(
+        "{}{}",
+        KEY_PAYLOAD_PREFIX,
+        URL_SAFE_NO_PAD.encode(json)
+    ))
+}
+
+/// The `SIGUST-FPR:` payload confirming a key received another way.
+pub(crate) fn fingerprint_payload(fingerprint: &str) -> String {
+    format!("{}{}", FINGERPRINT_PAYLOAD_PREFIX, fingerprint)
+}
+
+// 解析扫描得到的文本载荷
+fn decode_payload(payload: &str) -> Result<ScannedPayload> {
+    let payload = payload.trim();
+    if let Some(encoded) = payload.strip_prefix(KEY_PAYLOAD_PREFIX) {
+        let json = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .context("Failed to decode key payload")?;
+        let body: KeyPayloadBody =
+            serde_json::from_slice(&json).context("Failed to parse key payload")?;
+        if body.v != KEY_EXCHANGE_VERSION {
+            bail!("Unsupported key exchange payload version: {}", body.v);
+        }
+        let public_key_der = URL_SAFE_NO_PAD
+            .decode(&body.pk)
+            .context("Failed to decode public key in payload")?;
+        Ok(ScannedPayload::Key {
+            name: body.name,
+            public_key_der,
+        })
+    } else if let Some(fingerprint) = payload.strip_prefix(FINGERPRINT_PAYLOAD_PREFIX) {
+        let fingerprint = normalize_fingerprint(fingerprint);
+        if fingerprint.len() != 64 || !fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("Malformed fingerprint in payload");
+        }
+        Ok(ScannedPayload::Fingerprint(fingerprint))
+    } else {
+        bail!("Not a sigust key exchange code")
+    }
+}
+
+// 将 SPKI DER 公钥编码为 PEM
+pub(crate) fn encode_public_key_pem(public_key_der: &[u8]) -> Result<String> {
+    pem_rfc7468::encode_string(
+        SubjectPublicKeyInfoRef::PEM_LABEL,
+        LineEnding::LF,
+        public_key_der,
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to encode public key to PEM: {}", e))
+}
@@ -0,0 +1,181 @@
+// src-tauri/src/key_folders.rs
+// Folders for organizing keys (by client, project, ...). Folders are stored in
+// `key_folders.json` and may be nested; each key refers to at most one folder through
+// `KeyMetadata::folder_id`. Keys without a folder, or whose folder no longer exists,
+// belong to the root.
+use crate::crypto_types::{KeyFolder, KeyInfo};
+use crate::key_management::{get_app_data_file, get_metadata_path, read_metadata, write_metadata};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const KEY_FOLDERS_FILENAME: &str = "key_folders.json";
+const MAX_FOLDER_NAME_LEN: usize = 128;
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_key_folders(app_handle: tauri::AppHandle) -> Result<Vec<KeyFolder>, String> {
+    log::info!("Listing key folders");
+    get_key_folders_path(&app_handle)
+        .and_then(|path| read_key_folders(&path))
+        .map_err(|e| {
+            log::error!("Failed to list key folders: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn create_key_folder(
+    app_handle: tauri::AppHandle,
+    name: String,
+    parent_id: Option<Uuid>,
+) -> Result<KeyFolder, String> {
+    log::info!("Creating key folder '{}' under {:?}", name, parent_id);
+    let name = name.trim().to_string();
+    if name.is_empty() || name.len() > MAX_FOLDER_NAME_LEN {
+        return Err(format!(
+            "Folder name must be non-blank and at most {} characters.",
+            MAX_FOLDER_NAME_LEN
+        ));
+    }
+    _create_key_folder(&app_handle, name, parent_id).map_err(|e| {
+        log::error!("Failed to create key folder: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _create_key_folder(
+    app_handle: &tauri::AppHandle,
+    name: String,
+    parent_id: Option<Uuid>,
+) -> Result<KeyFolder> {
+    let path = get_key_folders_path(app_handle)?;
+    let mut folders = read_key_folders(&path)?;
+    if let Some(parent_id) = parent_id {
+        find_folder(&folders, parent_id)?;
+    }
+    if folders
+        .iter()
+        .any(|f| f.parent_id == parent_id && f.name.eq_ignore_ascii_case(&name))
+    {
+        bail!("A folder named '{}' already exists here", name);
+    }
+    let folder = KeyFolder {
+        folder_id: Uuid::new_v4(),
+        name,
+        parent_id,
+        created_at: Utc::now(),
+    };
+    folders.push(folder.clone());
+    write_key_folders(&path, &folders)?;
+    Ok(folder)
+}
+
+/// Deletes a folder. Its keys and subfolders move to the folder's parent.
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_key_folder(app_handle: tauri::AppHandle, folder_id: Uuid) -> Result<(), String> {
+    log::info!("Deleting key folder {}", folder_id);
+    _delete_key_folder(&app_handle, folder_id).map_err(|e| {
+        log::error!("Failed to delete key folder {}: {:?}", folder_id, e);
+        e.to_string()
+    })
+}
+
+fn _delete_key_folder(app_handle: &tauri::AppHandle, folder_id: Uuid) -> Result<()> {
+    let path = get_key_folders_path(app_handle)?;
+    let mut folders = read_key_folders(&path)?;
+    let parent_id = find_folder(&folders, folder_id)?.parent_id;
+
+    let metadata_path = get_metadata_path(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+    let mut moved = false;
+    for metadata in all_metadata
+        .iter_mut()
+        .filter(|m| m.folder_id == Some(folder_id))
+    {
+        metadata.folder_id = parent_id;
+        moved = true;
+    }
+    if moved {
+        write_metadata(&metadata_path, &all_metadata)?;
+    }
+
+    folders.retain(|f| f.folder_id != folder_id);
+    for child in folders
+        .iter_mut()
+        .filter(|f| f.parent_id == Some(folder_id))
+    {
+        child.parent_id = parent_id;
+    }
+    write_key_folders(&path, &folders)
+}
+
+/// Moves a key into a folder, or to the root when `folder_id` is `None`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn move_key_to_folder(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    folder_id: Option<Uuid>,
+) -> Result<KeyInfo, String> {
+    log::info!("Moving key {} to folder {:?}", key_id, folder_id);
+    _move_key_to_folder(&app_handle, key_id, folder_id).map_err(|e| {
+        log::error!("Failed to move key {}: {:?}", key_id, e);
+        e.to_string()
+    })
+}
+
+fn _move_key_to_folder(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    folder_id: Option<Uuid>,
+) -> Result<KeyInfo> {
+    if let Some(folder_id) = folder_id {
+        find_folder(
+            &read_key_folders(&get_key_folders_path(app_handle)?)?,
+            folder_id,
+        )?;
+    }
+    let metadata_path = get_metadata_path(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+    let metadata = all_metadata
+        .iter_mut()
+        .find(|m| m.key_id == key_id && m.trashed_at.is_none())
+        .ok_or_else(|| anyhow::anyhow!("Key with ID {} not found", key_id))?;
+    metadata.folder_id = folder_id;
+    let info = KeyInfo::from(&*metadata);
+    write_metadata(&metadata_path, &all_metadata)?;
+    Ok(info)
+}
+
+// --- Helper Functions ---
+
+// 获取文件夹列表文件路径
+fn get_key_folders_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, KEY_FOLDERS_FILENAME)
+}
+
+// 读取文件夹列表
+pub fn read_key_folders(path: &Path) -> Result<Vec<KeyFolder>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).context("Failed to read key folders file")?;
+    serde_json::from_str(&content).context("Failed to parse key folders JSON")
+}
+
+// 写入文件夹列表
+pub fn write_key_folders(path: &Path, folders: &[KeyFolder]) -> Result<()> {
+    let content =
+        serde_json::to_string_pretty(folders).context("Failed to serialize key folders to JSON")?;
+    fs::write(path, content).context("Failed to write key folders file")
+}
+
+fn find_folder(folders: &[KeyFolder], folder_id: Uuid) -> Result<&KeyFolder> {
+    folders
+        .iter()
+        .find(|f| f.folder_id == folder_id)
+        .ok_or_else(|| anyhow::anyhow!("Folder with ID {} not found", folder_id))
+}
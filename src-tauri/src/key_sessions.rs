@@ -0,0 +1,200 @@
+// src-tauri/src/key_sessions.rs
+// Short-lived key sessions: a key unlocked once with its password stays usable for a
+// limited time, so quick actions (e.g. tray quick-sign) can sign without asking again.
+// The decrypted key lives only in memory and is zeroed when the session ends.
+use crate::audit;
+use crate::crypto_types::{KeySessionInfo, SignatureAlgorithm};
+use crate::key_management::{find_key_metadata, read_private_key_der};
+use crate::unlock_policy::allows_session;
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::Manager;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+const DEFAULT_SESSION_MINUTES: u32 = 15;
+const MAX_SESSION_MINUTES: u32 = 8 * 60;
+
+/// Keys unlocked during this run, keyed by key ID.
+#[derive(Default)]
+pub struct KeySessions(Mutex<HashMap<Uuid, KeySession>>);
+
+struct KeySession {
+    name: String,
+    algorithm: SignatureAlgorithm,
+    private_key_der: Zeroizing<Vec<u8>>,
+    expires_at: DateTime<Utc>,
+}
+
+impl KeySession {
+    fn info(&self, key_id: Uuid) -> KeySessionInfo {
+        KeySessionInfo {
+            key_id,
+            name: self.name.clone(),
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn unlock_key_session(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    password: String,
+    minutes: Option<u32>,
+) -> Result<KeySessionInfo, String> {
+    log::info!("Unlocking a session for key ID {}", key_id);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    let minutes = minutes.unwrap_or(DEFAULT_SESSION_MINUTES);
+    if !(1..=MAX_SESSION_MINUTES).contains(&minutes) {
+        return Err(format!(
+            "Session length must be between 1 and {} minutes.",
+            MAX_SESSION_MINUTES
+        ));
+    }
+    _unlock_key_session(&app_handle, key_id, &password, minutes)
+        .map_err(|e| {
+            log::error!("Failed to unlock session for {}: {:?}", key_id, e);
+            e.to_string()
+        })
+        .inspect(|info| {
+            audit::record(
+                &app_handle,
+                "unlockKeySession",
+                json!({ "keyId": key_id, "expiresAt": info.expires_at }),
+            );
+            sessions_changed(&app_handle);
+        })
+}
+
+fn _unlock_key_session(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    password: &str,
+    minutes: u32,
+) -> Result<KeySessionInfo> {
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    if !allows_session(&metadata) {
+        bail!(
+            "Key '{}' must be unlocked with its password for every use",
+            metadata.name
+        );
+    }
+    let session = KeySession {
+        algorithm: metadata.algorithm.clone(),
+        private_key_der: Zeroizing::new(read_private_key_der(app_handle, &metadata, password)?),
+        name: metadata.name,
+        expires_at: Utc::now() + Duration::minutes(minutes as i64),
+    };
+    let info = session.info(key_id);
+    lock_sessions(&app_handle.state::<KeySessions>())?.insert(key_id, session);
+
+    // 到期后移除会话并刷新托盘菜单
+    let expiry_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_secs(minutes as u64 * 60));
+        if let Ok(mut sessions) = lock_sessions(&expiry_handle.state::<KeySessions>()) {
+            sessions.retain(|_, s| s.expires_at > Utc::now());
+        }
+        sessions_changed(&expiry_handle);
+    });
+    Ok(info)
+}
+
+/// Ends the session of one key, or of all keys when `key_id` is `None`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn lock_key_session(
+    app_handle: tauri::AppHandle,
+    sessions: tauri::State<'_, KeySessions>,
+    key_id: Option<Uuid>,
+) -> Result<(), String> {
+    log::info!("Locking key session {:?}", key_id);
+    match key_id {
+        Some(key_id) => end_key_session(&app_handle, &sessions, key_id),
+        None => end_all_key_sessions(&app_handle, &sessions),
+    }
+    .map_err(|e| {
+        log::error!("Failed to lock key session: {:?}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_key_sessions(
+    sessions: tauri::State<'_, KeySessions>,
+) -> Result<Vec<KeySessionInfo>, String> {
+    log::debug!("Listing key sessions");
+    active_sessions(&sessions).map_err(|e| {
+        log::error!("Failed to list key sessions: {:?}", e);
+        e.to_string()
+    })
+}
+
+// --- Helper Functions ---
+
+/// Unexpired sessions, sorted by key name.
+pub(crate) fn active_sessions(sessions: &KeySessions) -> Result<Vec<KeySessionInfo>> {
+    let mut sessions = lock_sessions(sessions)?;
+    sessions.retain(|_, s| s.expires_at > Utc::now());
+    let mut infos: Vec<KeySessionInfo> = sessions.iter().map(|(id, s)| s.info(*id)).collect();
+    infos.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(infos)
+}
+
+/// Ends the session of `key_id`, if there is one.
+pub(crate) fn end_key_session(
+    app_handle: &tauri::AppHandle,
+    sessions: &KeySessions,
+    key_id: Uuid,
+) -> Result<()> {
+    lock_sessions(sessions)?.remove(&key_id);
+    sessions_changed(app_handle);
+    Ok(())
+}
+
+/// Ends every key session.
+pub(crate) fn end_all_key_sessions(
+    app_handle: &tauri::AppHandle,
+    sessions: &KeySessions,
+) -> Result<()> {
+    lock_sessions(sessions)?.clear();
+    sessions_changed(app_handle);
+    Ok(())
+}
+
+/// Algorithm and decrypted key of an unexpired session.
+pub(crate) fn session_key(
+    sessions: &KeySessions,
+    key_id: Uuid,
+) -> Result<(SignatureAlgorithm, Zeroizing<Vec<u8>>)> {
+    let sessions = lock_sessions(sessions)?;
+    let session = sessions
+        .get(&key_id)
+        .filter(|s| s.expires_at > Utc::now())
+        .ok_or_else(|| anyhow!("Key is locked; unlock it in the app first"))?;
+    Ok((session.algorithm.clone(), session.private_key_der.clone()))
+}
+
+// 会话变化时刷新托盘菜单（仅桌面端有托盘）
+fn sessions_changed(app_handle: &tauri::AppHandle) {
+    #[cfg(desktop)]
+    crate::tray::refresh_tray_menu(app_handle);
+    #[cfg(not(desktop))]
+    let _ = app_handle;
+}
+
+fn lock_sessions(
+    sessions: &KeySessions,
+) -> Result<std::sync::MutexGuard<'_, HashMap<Uuid, KeySession>>> {
+    sessions
+        .0
+        .lock()
+        .map_err(|_| anyhow!("Key session lock poisoned"))
+}
@@ -0,0 +1,549 @@
+// src-tauri/src/key_wrapping.rs
+// Self-describing container for password-wrapped private keys (`.key.enc` files).
+//
+// Layout (all integers big-endian):
+//   magic "SGKW" | version u8 | flags u8 | cipher u8 | kdf u8
+//   | kdf params len u16 | kdf params | salt len u8 | salt | nonce len u8 | nonce
+//   | ciphertext (including the AEAD tag)
+//
+// With the token flag the wrapping key is HMAC-SHA256(KDF(password), token secret), where
+// the token secret comes from a FIDO2 security key (see `token_unwrap`). With the device
+// flag the result is further keyed with this machine's secret (see `device_binding`).
+//
+// Files written before this format existed are `ciphertext || nonce` with the salt
+// kept in `KeyMetadata::salt_hex`; `migrate_legacy_key_files` converts them in place
+// without needing the password.
+use crate::crypto_types::{KdfParameters, KeyMetadata};
+use crate::key_management::{
+    get_key_storage_dir, get_metadata_path, read_metadata, write_metadata, NONCE_LEN, SALT_LEN,
+};
+use crate::settings::{
+    MAX_ARGON2_ITERATIONS, MAX_ARGON2_MEMORY_KIB, MAX_ARGON2_PARALLELISM, MAX_PBKDF2_ITERATIONS,
+};
+use aead::{AeadMutInPlace, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{bail, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2_hmac;
+use rsa::rand_core::RngCore;
+use sha2::Sha256;
+use std::fs;
+
+const WRAP_MAGIC: &[u8; 4] = b"SGKW";
+const WRAP_VERSION: u8 = 1;
+/// The header bytes are authenticated as AEAD associated data.
+const FLAG_HEADER_AAD: u8 = 0x01;
+/// The wrapping key also depends on a security key's secret.
+const FLAG_TOKEN_SECRET: u8 = 0x02;
+/// The wrapping key also depends on a secret kept in this device's OS keystore.
+const FLAG_DEVICE_SECRET: u8 = 0x04;
+const AES_KEY_LEN: usize = 32;
+
+/// Default PBKDF2 iteration count for newly wrapped keys.
+pub const DEFAULT_PBKDF2_ITERATIONS: u32 = 100_000;
+
+/// Symmetric ciphers that can wrap a private key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapCipher {
+    Aes256Gcm = 1,
+}
+
+/// Key derivation functions (with their parameters) that turn a password into a wrapping key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WrapKdf {
+    Pbkdf2Sha256 {
+        iterations: u32,
+    },
+    Argon2id {
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+}
+
+impl WrapKdf {
+    fn id(&self) -> u8 {
+        match self {
+            WrapKdf::Pbkdf2Sha256 { .. } => 1,
+            WrapKdf::Argon2id { .. } => 2,
+        }
+    }
+
+    fn encode_params(&self) -> Vec<u8> {
+        match self {
+            WrapKdf::Pbkdf2Sha256 { iterations } => iterations.to_be_bytes().to_vec(),
+            WrapKdf::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => [memory_kib, iterations, parallelism]
+                .iter()
+                .flat_map(|value| value.to_be_bytes())
+                .collect(),
+        }
+    }
+
+    fn decode(id: u8, params: &[u8]) -> Result<Self> {
+        match id {
+            1 => {
+                let iterations: [u8; 4] = params
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Invalid PBKDF2 parameters"))?;
+                let iterations = u32::from_be_bytes(iterations);
+                // 文件头不可信，限制计算开销
+                if iterations > MAX_PBKDF2_ITERATIONS {
+                    bail!("PBKDF2 iteration count of {} is too high", iterations);
+                }
+                Ok(WrapKdf::Pbkdf2Sha256 { iterations })
+            }
+            2 => {
+                if params.len() != 12 {
+                    bail!("Invalid Argon2id parameters");
+                }
+                let value = |i: usize| u32::from_be_bytes(params[i..i + 4].try_into().unwrap());
+                let (memory_kib, iterations, parallelism) = (value(0), value(4), value(8));
+                // 文件头不可信，限制内存与计算开销
+                if memory_kib > MAX_ARGON2_MEMORY_KIB {
+                    bail!("Argon2id memory cost of {} KiB is too high", memory_kib);
+                }
+                if iterations > MAX_ARGON2_ITERATIONS {
+                    bail!("Argon2id iteration count of {} is too high", iterations);
+                }
+                if parallelism > MAX_ARGON2_PARALLELISM {
+                    bail!("Argon2id parallelism of {} is too high", parallelism);
+                }
+                Ok(WrapKdf::Argon2id {
+                    memory_kib,
+                    iterations,
+                    parallelism,
+                })
+            }
+            _ => bail!("Unsupported key derivation function ID: {}", id),
+        }
+    }
+
+    fn derive_key(&self, password: &str, salt: &[u8]) -> Result<[u8; AES_KEY_LEN]> {
+        let mut key = [0u8; AES_KEY_LEN];
+        match self {
+            WrapKdf::Pbkdf2Sha256 { iterations } => {
+                if *iterations == 0 {
+                    bail!("PBKDF2 iteration count must be positive");
+                }
+                pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, *iterations, &mut key);
+            }
+            WrapKdf::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => {
+                let params = Params::new(*memory_kib, *iterations, *parallelism, Some(AES_KEY_LEN))
+                    .map_err(|e| anyhow::anyhow!("Invalid Argon2id parameters: {}", e))?;
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(password.as_bytes(), salt, &mut key)
+                    .map_err(|e| anyhow::anyhow!("Failed to derive key with Argon2id: {}", e))?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+impl From<&WrapKdf> for KdfParameters {
+    fn from(kdf: &WrapKdf) -> Self {
+        match *kdf {
+            WrapKdf::Pbkdf2Sha256 { iterations } => KdfParameters::Pbkdf2Sha256 { iterations },
+            WrapKdf::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => KdfParameters::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            },
+        }
+    }
+}
+
+/// Secrets mixed into the wrapping key besides the password.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WrapSecrets<'a> {
+    /// hmac-secret output of the security key the file is tied to.
+    pub token: Option<&'a [u8]>,
+    /// Secret of the device the file is bound to.
+    pub device: Option<&'a [u8]>,
+}
+
+/// A parsed wrapped-key container.
+#[derive(Debug, Clone)]
+pub struct WrappedKey {
+    pub version: u8,
+    pub flags: u8,
+    pub cipher: WrapCipher,
+    pub kdf: WrapKdf,
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+impl WrappedKey {
+    /// Serializes the header (everything before the ciphertext).
+    fn header_bytes(&self) -> Vec<u8> {
+        let params = self.kdf.encode_params();
+        let mut header = Vec::with_capacity(16 + params.len() + self.salt.len() + self.nonce.len());
+        header.extend_from_slice(WRAP_MAGIC);
+        header.push(self.version);
+        header.push(self.flags);
+        header.push(self.cipher as u8);
+        header.push(self.kdf.id());
+        header.extend_from_slice(&(params.len() as u16).to_be_bytes());
+        header.extend_from_slice(&params);
+        header.push(self.salt.len() as u8);
+        header.extend_from_slice(&self.salt);
+        header.push(self.nonce.len() as u8);
+        header.extend_from_slice(&self.nonce);
+        header
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.header_bytes();
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader(bytes);
+        if reader.take(WRAP_MAGIC.len())? != WRAP_MAGIC {
+            bail!("Not a wrapped key file (missing header)");
+        }
+        let version = reader.u8()?;
+        if version != WRAP_VERSION {
+            bail!("Unsupported wrapped key format version: {}", version);
+        }
+        let flags = reader.u8()?;
+        let cipher = match reader.u8()? {
+            1 => WrapCipher::Aes256Gcm,
+            id => bail!("Unsupported wrapping cipher ID: {}", id),
+        };
+        let kdf_id = reader.u8()?;
+        let params_len = u16::from_be_bytes(reader.take(2)?.try_into()?) as usize;
+        let kdf = WrapKdf::decode(kdf_id, reader.take(params_len)?)?;
+        let salt_len = reader.u8()? as usize;
+        let salt = reader.take(salt_len)?.to_vec();
+        let nonce_len = reader.u8()? as usize;
+        if nonce_len != NONCE_LEN {
+            bail!("Unexpected nonce length: {}", nonce_len);
+        }
+        let nonce = reader.take(nonce_len)?.to_vec();
+        Ok(WrappedKey {
+            version,
+            flags,
+            cipher,
+            kdf,
+            salt,
+            nonce,
+            ciphertext: reader.0.to_vec(),
+        })
+    }
+
+    /// Whether unwrapping needs a security key's secret besides the password.
+    pub fn needs_token_secret(&self) -> bool {
+        self.flags & FLAG_TOKEN_SECRET != 0
+    }
+
+    /// Whether unwrapping needs the secret of the device the file is bound to.
+    pub fn needs_device_secret(&self) -> bool {
+        self.flags & FLAG_DEVICE_SECRET != 0
+    }
+
+    /// Decrypts the wrapped private key with the given password.
+    pub fn unwrap_key(&self, password: &str) -> Result<Vec<u8>> {
+        self.unwrap_key_with_secrets(password, WrapSecrets::default())
+    }
+
+    /// Decrypts the wrapped private key with the password and the secrets the file needs
+    /// (security key, device).
+    pub fn unwrap_key_with_secrets(
+        &self,
+        password: &str,
+        secrets: WrapSecrets<'_>,
+    ) -> Result<Vec<u8>> {
+        let key_bytes = self.wrapping_key(password, secrets)?;
+        let aad = if self.flags & FLAG_HEADER_AAD != 0 {
+            self.header_bytes()
+        } else {
+            Vec::new()
+        };
+        let mut data = self.ciphertext.clone();
+        match self.cipher {
+            WrapCipher::Aes256Gcm => {
+                let mut cipher = Aes256Gcm::new_from_slice(&key_bytes)
+                    .map_err(|e| anyhow::anyhow!("Failed to create AES cipher: {}", e))?;
+                cipher
+                    .decrypt_in_place(Nonce::from_slice(&self.nonce), &aad, &mut data)
+                    .map_err(|e| anyhow::anyhow!("Failed to decrypt data: {}", e))?;
+            }
+        }
+        Ok(data)
+    }
+
+    // 由密码（和安全密钥、设备的秘密）得到包装密钥
+    fn wrapping_key(&self, password: &str, secrets: WrapSecrets<'_>) -> Result<[u8; AES_KEY_LEN]> {
+        let key = self.kdf.derive_key(password, &self.salt)?;
+        let key = match (self.needs_token_secret(), secrets.token) {
+            (false, _) => key,
+            (true, Some(secret)) => mix_secret(&key, secret)?,
+            (true, None) => bail!("This key is also protected by a security key"),
+        };
+        match (self.needs_device_secret(), secrets.device) {
+            (false, _) => Ok(key),
+            (true, Some(secret)) => mix_secret(&key, secret),
+            (true, None) => bail!("This key is bound to a device"),
+        }
+    }
+}
+
+// HMAC-SHA256(密钥, 秘密)
+fn mix_secret(key: &[u8; AES_KEY_LEN], secret: &[u8]) -> Result<[u8; AES_KEY_LEN]> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key)
+        .map_err(|e| anyhow::anyhow!("Failed to create HMAC: {}", e))?;
+    mac.update(secret);
+    Ok(mac.finalize().into_bytes().into())
+}
+
+/// Encrypts a PKCS#8 private key with a fresh salt and nonce, returning the container bytes.
+pub fn wrap_private_key(private_key_der: &[u8], password: &str, kdf: WrapKdf) -> Result<Vec<u8>> {
+    wrap_private_key_with_secrets(private_key_der, password, kdf, WrapSecrets::default())
+}
+
+/// Like `wrap_private_key`; the key can only be unwrapped with the given secrets as well.
+pub fn wrap_private_key_with_secrets(
+    private_key_der: &[u8],
+    password: &str,
+    kdf: WrapKdf,
+    secrets: WrapSecrets<'_>,
+) -> Result<Vec<u8>> {
+    let mut salt = vec![0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce = vec![0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut flags = FLAG_HEADER_AAD;
+    if secrets.token.is_some() {
+        flags |= FLAG_TOKEN_SECRET;
+    }
+    if secrets.device.is_some() {
+        flags |= FLAG_DEVICE_SECRET;
+    }
+    let mut wrapped = WrappedKey {
+        version: WRAP_VERSION,
+        flags,
+        cipher: WrapCipher::Aes256Gcm,
+        kdf,
+        salt,
+        nonce,
+        ciphertext: private_key_der.to_vec(),
+    };
+    let key_bytes = wrapped.wrapping_key(password, secrets)?;
+    let aad = wrapped.header_bytes();
+    let mut cipher = Aes256Gcm::new_from_slice(&key_bytes)
+        .map_err(|e| anyhow::anyhow!("Failed to create AES cipher: {}", e))?;
+    cipher
+        .encrypt_in_place(
+            Nonce::from_slice(&wrapped.nonce),
+            &aad,
+            &mut wrapped.ciphertext,
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to encrypt data: {}", e))?;
+    Ok(wrapped.to_bytes())
+}
+
+/// Returns `true` if the bytes start with the wrapped key header magic.
+pub fn is_wrapped_key(bytes: &[u8]) -> bool {
+    bytes.starts_with(WRAP_MAGIC)
+}
+
+/// Converts a legacy `ciphertext || nonce` file into the header format (no password needed).
+pub fn convert_legacy_key(legacy_bytes: &[u8], salt_hex: &str) -> Result<Vec<u8>> {
+    if legacy_bytes.len() < NONCE_LEN {
+        bail!("Encrypted data is too short (missing nonce)");
+    }
+    let salt = hex::decode(salt_hex).context("Failed to decode salt from hex")?;
+    if salt.len() != SALT_LEN {
+        bail!("Invalid salt length: {}", salt.len());
+    }
+    let (ciphertext, nonce) = legacy_bytes.split_at(legacy_bytes.len() - NONCE_LEN);
+    let wrapped = WrappedKey {
+        version: WRAP_VERSION,
+        // Legacy ciphertexts were produced without associated data.
+        flags: 0,
+        cipher: WrapCipher::Aes256Gcm,
+        kdf: WrapKdf::Pbkdf2Sha256 {
+            iterations: DEFAULT_PBKDF2_ITERATIONS,
+        },
+        salt,
+        nonce: nonce.to_vec(),
+        ciphertext: ciphertext.to_vec(),
+    };
+    Ok(wrapped.to_bytes())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn migrate_key_storage(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    log::info!("Migrating legacy private key files");
+    migrate_legacy_key_files(&app_handle).map_err(|e| {
+        log::error!("Failed to migrate private key files: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// Migrates every legacy `.key.enc` file in the key store to the header format and
+/// clears the now-redundant `salt_hex` from metadata. Returns the number of migrated keys.
+pub fn migrate_legacy_key_files(app_handle: &tauri::AppHandle) -> Result<usize> {
+    let metadata_path = get_metadata_path(app_handle)?;
+    let key_storage_dir = get_key_storage_dir(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+    let mut migrated = 0;
+
+    for metadata in all_metadata.iter_mut() {
+        if let Err(e) = migrate_entry(&key_storage_dir, metadata).map(|done| {
+            if done {
+                migrated += 1;
+            }
+        }) {
+            // Leave broken entries for `check_store` to report.
+            log::warn!("Could not migrate key {}: {:?}", metadata.key_id, e);
+        }
+    }
+    if migrated > 0 {
+        write_metadata(&metadata_path, &all_metadata)?;
+        log::info!(
+            "Migrated {} private key files to the versioned format",
+            migrated
+        );
+    }
+    Ok(migrated)
+}
+
+fn migrate_entry(key_storage_dir: &std::path::Path, metadata: &mut KeyMetadata) -> Result<bool> {
+    if !metadata.kind.is_key_pair() {
+        return Ok(false);
+    }
+    let private_key_path = key_storage_dir.join(&metadata.encrypted_private_key_path);
+    let bytes = fs::read(&private_key_path)
+        .with_context(|| format!("Failed to read {:?}", private_key_path))?;
+    if is_wrapped_key(&bytes) {
+        // Already migrated; drop a stale salt left behind by an interrupted migration.
+        return Ok(metadata.salt_hex.take().is_some());
+    }
+    let salt_hex = metadata
+        .salt_hex
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("Legacy key file without salt"))?;
+    let converted = convert_legacy_key(&bytes, salt_hex)?;
+
+    // Write to a temporary file first so a crash never leaves a half-written key.
+    let tmp_path = private_key_path.with_extension("enc.tmp");
+    fs::write(&tmp_path, &converted).with_context(|| format!("Failed to write {:?}", tmp_path))?;
+    fs::rename(&tmp_path, &private_key_path)
+        .with_context(|| format!("Failed to replace {:?}", private_key_path))?;
+    metadata.salt_hex = None;
+    Ok(true)
+}
+
+/// Minimal cursor over a byte slice used by the header parser.
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            bail!("Wrapped key header is truncated");
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_management::encrypt_data;
+
+    #[test]
+    fn wrap_unwrap_and_legacy_conversion() {
+        let secret = b"private key bytes".to_vec();
+        let kdf = WrapKdf::Pbkdf2Sha256 { iterations: 1_000 };
+
+        let wrapped = wrap_private_key(&secret, "pw", kdf).unwrap();
+        let parsed = WrappedKey::parse(&wrapped).unwrap();
+        assert_eq!(parsed.unwrap_key("pw").unwrap(), secret);
+        assert!(parsed.unwrap_key("wrong").is_err());
+
+        let salt = [7u8; SALT_LEN];
+        let mut legacy = secret.clone();
+        encrypt_data(&mut legacy, "pw", &salt).unwrap();
+        let converted = convert_legacy_key(&legacy, &hex::encode(salt)).unwrap();
+        let parsed = WrappedKey::parse(&converted).unwrap();
+        assert_eq!(parsed.unwrap_key("pw").unwrap(), secret);
+
+        let kdf = WrapKdf::Argon2id {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let wrapped = wrap_private_key(&secret, "pw", kdf.clone()).unwrap();
+        let parsed = WrappedKey::parse(&wrapped).unwrap();
+        assert_eq!(parsed.kdf, kdf);
+        assert_eq!(parsed.unwrap_key("pw").unwrap(), secret);
+        assert!(parsed.unwrap_key("wrong").is_err());
+
+        let kdf = WrapKdf::Pbkdf2Sha256 { iterations: 1_000 };
+        let secrets = |token, device| WrapSecrets { token, device };
+        let wrapped = wrap_private_key_with_secrets(
+            &secret,
+            "pw",
+            kdf,
+            secrets(Some(&[1; 32]), Some(&[3; 32])),
+        )
+        .unwrap();
+        let parsed = WrappedKey::parse(&wrapped).unwrap();
+        assert!(parsed.needs_token_secret());
+        assert!(parsed.needs_device_secret());
+        assert!(parsed.unwrap_key("pw").is_err());
+        assert!(parsed
+            .unwrap_key_with_secrets("pw", secrets(Some(&[1; 32]), None))
+            .is_err());
+        assert!(parsed
+            .unwrap_key_with_secrets("pw", secrets(Some(&[1; 32]), Some(&[4; 32])))
+            .is_err());
+        assert_eq!(
+            parsed
+                .unwrap_key_with_secrets("pw", secrets(Some(&[1; 32]), Some(&[3; 32])))
+                .unwrap(),
+            secret
+        );
+    }
+
+    #[test]
+    fn rejects_excessive_kdf_parameters_from_headers() {
+        let decode = |kdf: WrapKdf| WrapKdf::decode(kdf.id(), &kdf.encode_params());
+        let argon2 = |memory_kib, iterations, parallelism| WrapKdf::Argon2id {
+            memory_kib,
+            iterations,
+            parallelism,
+        };
+        let pbkdf2 = |iterations| WrapKdf::Pbkdf2Sha256 { iterations };
+
+        assert!(decode(pbkdf2(MAX_PBKDF2_ITERATIONS)).is_ok());
+        assert!(decode(pbkdf2(MAX_PBKDF2_ITERATIONS + 1)).is_err());
+        assert!(decode(argon2(MAX_ARGON2_MEMORY_KIB, MAX_ARGON2_ITERATIONS, 4)).is_ok());
+        assert!(decode(argon2(MAX_ARGON2_MEMORY_KIB + 1, 2, 1)).is_err());
+        assert!(decode(argon2(64 * 1024, MAX_ARGON2_ITERATIONS + 1, 1)).is_err());
+        assert!(decode(argon2(64 * 1024, 2, MAX_ARGON2_PARALLELISM + 1)).is_err());
+    }
+}
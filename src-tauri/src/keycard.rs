@@ -0,0 +1,349 @@
+// src-tauri/src/keycard.rs
+// Public keycards: a one-page, self-contained description of one of the user's own keys,
+// meant to be attached to a contract or published on a website so that others can check
+// signatures made with it. The card shows the owner's identity, the key fingerprint as
+// hex, as 24 words (the fingerprint's 32 bytes written with the BIP-39 English word list,
+// easier to compare aloud) and as a QR code carrying the key (the same `SIGUST-KEY:`
+// payload as the key exchange, so scanning it in sigust offers to add the key; ML-DSA keys
+// are too large and the code carries the fingerprint instead), the PEM public key, and
+// instructions for verifying.
+//
+// Both formats of the verification reports are offered and share their renderers (see
+// compliance_report.rs): HTML with the QR code as inline SVG, and PDF with the QR code
+// drawn as filled squares.
+use crate::audit;
+use crate::compliance_report::{escape_html, wrap, PdfPages, MARGIN, WRAP_COLUMNS};
+use crate::crypto_types::{KeyMetadata, ReportFormat};
+use crate::key_exchange::{fingerprint_payload, key_payload};
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, find_key_metadata, read_public_key_pem,
+};
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::resolve_output_path;
+use anyhow::{Context, Result};
+use bip39::Mnemonic;
+use chrono::{DateTime, Utc};
+use qrcode::render::svg;
+use qrcode::types::QrError;
+use qrcode::{Color, QrCode};
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Side of the QR code, in pixels (HTML) or points (PDF).
+const QR_SIZE: u32 = 220;
+/// Light modules around the QR code required by scanners.
+const QR_QUIET_ZONE: usize = 4;
+
+/// The keycard content, shared by both renderers.
+struct Keycard {
+    generated_at: DateTime<Utc>,
+    name: String,
+    /// Owner and key details as label/value rows.
+    rows: Vec<(String, String)>,
+    fingerprint: String,
+    words: Vec<String>,
+    public_key_pem: String,
+    qr: QrCode,
+}
+
+// --- Tauri Commands ---
+
+/// Writes the keycard of `key_id` to `output_path`. Returns the path written, which may
+/// differ from the requested one (see output settings).
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_keycard(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    output_path: String,
+    format: ReportFormat,
+) -> Result<String, String> {
+    log::info!("Exporting {:?} keycard for key ID: {}", format, key_id);
+    _export_keycard(&app_handle, key_id, &output_path, format)
+        .map_err(|e| {
+            log::error!("Failed to export keycard for {}: {:?}", key_id, e);
+            e.to_string()
+        })
+        .map(|path| path_string(&path))
+        .inspect(|path| {
+            audit::record(
+                &app_handle,
+                "exportKeycard",
+                json!({ "keyId": key_id, "outputPath": path, "format": format }),
+            )
+        })
+}
+
+// --- Helper Functions ---
+
+fn _export_keycard(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    output_path: &str,
+    format: ReportFormat,
+) -> Result<PathBuf> {
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let public_key_pem = read_public_key_pem(app_handle, &metadata)?;
+    let card = build_keycard(&metadata, &public_key_pem)?;
+    let content = match format {
+        ReportFormat::Html => render_html(&card).into_bytes(),
+        ReportFormat::Pdf => render_pdf(&card),
+    };
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write keycard: {:?}", output_path))?;
+    Ok(output_path)
+}
+
+fn build_keycard(metadata: &KeyMetadata, public_key_pem: &str) -> Result<Keycard> {
+    let public_key_der = decode_public_key_pem(public_key_pem)?;
+    let fingerprint = compute_fingerprint(&public_key_der);
+    let qr = match QrCode::new(key_payload(metadata, &public_key_der)?.as_bytes()) {
+        Err(QrError::DataTooLong) => QrCode::new(fingerprint_payload(&fingerprint).as_bytes()),
+        qr => qr,
+    }
+    .context("Failed to encode public key as QR code")?;
+
+    let mut rows = Vec::new();
+    if let Some(identity) = &metadata.identity {
+        for (label, value) in [
+            ("Name", &identity.full_name),
+            ("E-mail", &identity.email),
+            ("Organization", &identity.organization),
+        ] {
+            if let Some(value) = value {
+                rows.push((label.to_string(), value.clone()));
+            }
+        }
+    }
+    rows.push(("Key name".to_string(), metadata.name.clone()));
+    rows.push(("Algorithm".to_string(), metadata.algorithm.to_string()));
+    rows.push((
+        "Created".to_string(),
+        metadata.created_at.format("%Y-%m-%d").to_string(),
+    ));
+
+    Ok(Keycard {
+        generated_at: Utc::now(),
+        name: metadata.name.clone(),
+        rows,
+        words: fingerprint_words(&fingerprint)?,
+        fingerprint,
+        public_key_pem: public_key_pem.trim().to_string(),
+        qr,
+    })
+}
+
+// 指纹 32 字节按 BIP-39 英文词表写成 24 个词（最后一个词含校验位）
+fn fingerprint_words(fingerprint: &str) -> Result<Vec<String>> {
+    let bytes = hex::decode(fingerprint).context("Malformed fingerprint")?;
+    let mnemonic = Mnemonic::from_entropy(&bytes).context("Failed to encode fingerprint")?;
+    Ok(mnemonic.words().map(str::to_string).collect())
+}
+
+// 十六进制指纹每 4 个字符一组，便于逐组核对
+fn grouped_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .as_bytes()
+        .chunks(4)
+        .map(|group| String::from_utf8_lossy(group).to_uppercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn instructions(card: &Keycard) -> [String; 4] {
+    [
+        "Confirm the fingerprint with the key owner through a channel you trust (in person, \
+         by phone, or on a second website). Reading the words aloud is enough."
+            .to_string(),
+        format!(
+            "Add the key: scan the QR code with sigust's key exchange, or save the public key \
+             below as {}.pub.pem and import it as a contact.",
+            card.name.replace(char::is_whitespace, "_")
+        ),
+        "Check that the fingerprint sigust shows for the key matches this card. Without \
+         sigust, the fingerprint is the SHA-256 of the key in DER form: \
+         openssl pkey -pubin -in key.pub.pem -outform DER | sha256sum"
+            .to_string(),
+        "To verify a signed document, open sigust, choose Verify and select the document and \
+         its signature; sigust finds the signer among your contacts."
+            .to_string(),
+    ]
+}
+
+fn render_html(card: &Keycard) -> String {
+    let qr_svg = card
+        .qr
+        .render::<svg::Color<'_>>()
+        .quiet_zone(true)
+        .min_dimensions(QR_SIZE, QR_SIZE)
+        .build();
+    // 去掉 XML 声明，作为内联 SVG 嵌入
+    let qr_svg = qr_svg
+        .find("<svg")
+        .map_or(qr_svg.as_str(), |start| &qr_svg[start..]);
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Public key: {}</title>\n<style>\n\
+         body {{ font-family: Helvetica, Arial, sans-serif; font-size: 10pt; margin: 2em; color: #111; }}\n\
+         h1 {{ font-size: 16pt; margin-bottom: 0.2em; }}\n\
+         h2 {{ font-size: 12pt; margin-top: 1.5em; border-bottom: 1px solid #999; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th {{ text-align: left; vertical-align: top; padding: 2px 16px 2px 0; }}\n\
+         .fingerprint {{ font-family: monospace; font-size: 12pt; }}\n\
+         .qr {{ float: right; margin-left: 1em; }}\n\
+         pre {{ font-size: 8pt; }}\n\
+         @media print {{ body {{ margin: 0; }} }}\n</style>\n</head>\n<body>\n",
+        escape_html(&card.name)
+    );
+    html.push_str(&format!(
+        "<div class=\"qr\">{}</div>\n<h1>Public key: {}</h1>\n<p>Generated {} by sigust {}.</p>\n",
+        qr_svg,
+        escape_html(&card.name),
+        card.generated_at.format("%Y-%m-%d %H:%M UTC"),
+        env!("CARGO_PKG_VERSION")
+    ));
+    html.push_str("<h2>Owner and key</h2>\n<table>\n");
+    for (label, value) in &card.rows {
+        html.push_str(&format!(
+            "<tr><th>{}</th><td>{}</td></tr>\n",
+            escape_html(label),
+            escape_html(value)
+        ));
+    }
+    html.push_str("</table>\n<h2>Fingerprint (SHA-256)</h2>\n");
+    html.push_str(&format!(
+        "<p class=\"fingerprint\">{}</p>\n<p>{}</p>\n",
+        grouped_fingerprint(&card.fingerprint),
+        card.words.join(" ")
+    ));
+    html.push_str("<h2>How to verify</h2>\n<ol>\n");
+    for step in instructions(card) {
+        html.push_str(&format!("<li>{}</li>\n", escape_html(&step)));
+    }
+    html.push_str(&format!(
+        "</ol>\n<h2>Public key</h2>\n<pre>{}</pre>\n</body>\n</html>\n",
+        escape_html(&card.public_key_pem)
+    ));
+    html
+}
+
+fn render_pdf(card: &Keycard) -> Vec<u8> {
+    let mut layout = PdfPages::new();
+    let title = format!("Public key: {}", card.name);
+    layout.line(MARGIN, 16.0, &[(true, &title)]);
+    let summary = format!(
+        "Generated {} by sigust {}.",
+        card.generated_at.format("%Y-%m-%d %H:%M UTC"),
+        env!("CARGO_PKG_VERSION")
+    );
+    layout.line(MARGIN, 9.0, &[(false, &summary)]);
+
+    layout.gap(8.0);
+    let bottom = layout.reserve(QR_SIZE as f32);
+    layout.draw(&qr_operators(&card.qr, MARGIN, bottom, QR_SIZE as f32));
+
+    layout.gap(8.0);
+    layout.line(MARGIN, 12.0, &[(true, "Owner and key")]);
+    for (label, value) in &card.rows {
+        let label = format!("{}: ", label);
+        layout.line(MARGIN, 9.0, &[(true, &label), (false, value)]);
+    }
+
+    layout.gap(8.0);
+    layout.line(MARGIN, 12.0, &[(true, "Fingerprint (SHA-256)")]);
+    layout.line(
+        MARGIN,
+        11.0,
+        &[(false, &grouped_fingerprint(&card.fingerprint))],
+    );
+    for line in wrap(&card.words.join(" "), WRAP_COLUMNS) {
+        layout.line(MARGIN, 9.0, &[(false, &line)]);
+    }
+
+    layout.gap(8.0);
+    layout.line(MARGIN, 12.0, &[(true, "How to verify")]);
+    for (index, step) in instructions(card).iter().enumerate() {
+        let number = format!("{}. ", index + 1);
+        let lines = wrap(step, WRAP_COLUMNS - 5);
+        layout.line(MARGIN, 9.0, &[(true, &number), (false, &lines[0])]);
+        for line in &lines[1..] {
+            layout.line(MARGIN + 12.0, 9.0, &[(false, line)]);
+        }
+    }
+
+    layout.gap(8.0);
+    layout.line(MARGIN, 12.0, &[(true, "Public key")]);
+    for line in card.public_key_pem.lines() {
+        layout.line(MARGIN, 8.0, &[(false, line)]);
+    }
+
+    layout.finish(&title, card.generated_at)
+}
+
+// 将 QR 码画成黑色方块（同一行相邻的深色模块合并为一个矩形），左下角位于 (x, y)
+fn qr_operators(qr: &QrCode, x: f32, y: f32, size: f32) -> String {
+    let width = qr.width();
+    let module = size / (width + 2 * QR_QUIET_ZONE) as f32;
+    let colors = qr.to_colors();
+    let mut operators = String::from("0 g\n");
+    for (row, cells) in colors.chunks(width).enumerate() {
+        let top = y + size - (row + QR_QUIET_ZONE) as f32 * module;
+        let mut column = 0;
+        while column < width {
+            if cells[column] != Color::Dark {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < width && cells[column] == Color::Dark {
+                column += 1;
+            }
+            operators.push_str(&format!(
+                "{:.2} {:.2} {:.2} {:.2} re\n",
+                x + (start + QR_QUIET_ZONE) as f32 * module,
+                top - module,
+                (column - start) as f32 * module,
+                module
+            ));
+        }
+    }
+    operators.push_str("f\n");
+    operators
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_fingerprint_words_and_qr() {
+        let fingerprint = "00".repeat(32);
+        let words = fingerprint_words(&fingerprint).unwrap();
+        assert_eq!(words.len(), 24);
+        assert_eq!(words[0], "abandon");
+        assert_eq!(grouped_fingerprint("0a1b2c3d4e5f"), "0A1B 2C3D 4E5F");
+
+        let card = Keycard {
+            generated_at: Utc::now(),
+            name: "Work <2026>".to_string(),
+            rows: vec![("E-mail".to_string(), "a@example.com".to_string())],
+            fingerprint,
+            words,
+            public_key_pem: "-----BEGIN PUBLIC KEY-----\nAAAA\n-----END PUBLIC KEY-----"
+                .to_string(),
+            qr: QrCode::new(b"SIGUST-KEY:test").unwrap(),
+        };
+        let html = render_html(&card);
+        assert!(html.contains("<h1>Public key: Work &lt;2026&gt;</h1>"));
+        assert!(html.contains("<div class=\"qr\"><svg"));
+        assert!(!html.contains("<?xml"));
+
+        let pdf = String::from_utf8(render_pdf(&card)).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.contains(" re\n"));
+        assert!(pdf.contains("/Title (Public key: Work <2026>)"));
+    }
+}
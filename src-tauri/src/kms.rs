@@ -0,0 +1,952 @@
+// src-tauri/src/kms.rs
+// Signing keys held in a cloud KMS: AWS KMS, Google Cloud KMS, Azure Key Vault / Managed
+// HSM, or a HashiCorp Vault transit engine. A KMS key entry names the key in the provider's notation; its public key is fetched
+// when the key is added and kept with the entry in `kms_keys.json`, so verifying works
+// offline like with any other key. Signing builds the envelope locally and sends only its
+// digest to the KMS; the private key never leaves the service. Only envelopes are made this
+// way, with RSA PKCS#1 v1.5 / SHA-256 or ECDSA P-256 / SHA-256 keys.
+//
+// Credentials come from each provider's usual environment variables and are never stored:
+//
+//   AWS    AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY, AWS_SESSION_TOKEN (optional)
+//   GCP    GOOGLE_OAUTH_ACCESS_TOKEN, or a service account key file named by
+//          GOOGLE_APPLICATION_CREDENTIALS
+//   Azure  AZURE_ACCESS_TOKEN, or AZURE_TENANT_ID, AZURE_CLIENT_ID and AZURE_CLIENT_SECRET
+//          (AZURE_AUTHORITY_HOST for other clouds)
+//   Vault  VAULT_TOKEN, or VAULT_ROLE_ID and VAULT_SECRET_ID for AppRole login
+use crate::audit;
+use crate::crypto_types::{
+    KmsKeyInfo, KmsProvider, SignatureAlgorithm, TimeSource, VaultAuthMethod, VaultTransitSettings,
+};
+use crate::envelope::{
+    FileBinding, SignatureEnvelope, SignedAttributes, DIGEST_SHA256, ENVELOPE_FORMAT,
+    ENVELOPE_VERSION,
+};
+use crate::hashing::hash_file;
+use crate::key_exchange::encode_public_key_pem;
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, detect_public_key_algorithm, get_app_data_file,
+};
+use crate::metadata_schema::validate_metadata;
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::resolve_output_path;
+use crate::paste_import::{p256_spki, rsa_spki};
+use crate::policy::{check_key_use, check_timestamping};
+use crate::signable_inputs::check_signable_input;
+use crate::signature_sequence::next_signature_sequence;
+use crate::timestamp::trusted_tsa_certificates;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rsa::pkcs1v15::SigningKey as RsaSigningKey;
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::traits::PublicKeyParts;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use uuid::Uuid;
+
+const KMS_KEYS_FILENAME: &str = "kms_keys.json";
+const KMS_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_RESPONSE_SIZE: u64 = 1024 * 1024;
+const AWS_CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+const GCP_KMS_ENDPOINT: &str = "https://cloudkms.googleapis.com/v1/";
+const GCP_KMS_SCOPE: &str = "https://www.googleapis.com/auth/cloudkms";
+const AZURE_API_VERSION: &str = "7.4";
+const AZURE_AUTHORITY_HOST: &str = "https://login.microsoftonline.com";
+
+// --- Tauri Commands ---
+
+/// Adds a key kept in a cloud KMS; its public key is fetched from the service now.
+/// `vault` is required for, and only used with, `KmsProvider::Vault`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn add_kms_key(
+    app_handle: tauri::AppHandle,
+    name: String,
+    provider: KmsProvider,
+    key_reference: String,
+    vault: Option<VaultTransitSettings>,
+) -> Result<KmsKeyInfo, String> {
+    log::info!("Adding {:?} KMS key '{}'", provider, key_reference);
+    _add_kms_key(&app_handle, &name, provider, key_reference.trim(), vault)
+        .map_err(|e| {
+            log::error!("Failed to add KMS key: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|key| {
+            audit::record(
+                &app_handle,
+                "addKmsKey",
+                json!({
+                    "keyId": key.key_id,
+                    "provider": key.provider,
+                    "keyReference": key.key_reference,
+                    "fingerprint": key.fingerprint,
+                }),
+            )
+        })
+}
+
+fn _add_kms_key(
+    app_handle: &tauri::AppHandle,
+    name: &str,
+    provider: KmsProvider,
+    key_reference: &str,
+    vault: Option<VaultTransitSettings>,
+) -> Result<KmsKeyInfo> {
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("Key name cannot be empty");
+    }
+    let mut vault = match (provider, vault) {
+        (KmsProvider::Vault, Some(settings)) => Some(normalize_vault_settings(settings)?),
+        (KmsProvider::Vault, None) => bail!("Vault keys need the Vault address and login method"),
+        _ => None,
+    };
+    let path = get_kms_keys_path(app_handle)?;
+    let mut keys = read_kms_keys(&path)?;
+    if keys.iter().any(|k| {
+        k.provider == provider
+            && k.key_reference == key_reference
+            && k.vault.as_ref().map(|v| (&v.address, &v.mount))
+                == vault.as_ref().map(|v| (&v.address, &v.mount))
+    }) {
+        bail!("This KMS key has already been added");
+    }
+
+    let public_key_der = match (provider, vault.as_mut()) {
+        (KmsProvider::Aws, _) => aws_public_key(key_reference)?,
+        (KmsProvider::Gcp, _) => gcp_public_key(key_reference)?,
+        (KmsProvider::Azure, _) => azure_public_key(key_reference)?,
+        (KmsProvider::Vault, Some(settings)) => vault_public_key(settings, key_reference)?,
+        (KmsProvider::Vault, None) => unreachable!(),
+    };
+    let algorithm = detect_public_key_algorithm(&public_key_der)?;
+    check_kms_algorithm(&algorithm)?;
+    let key = KmsKeyInfo {
+        key_id: Uuid::new_v4(),
+        name: name.to_string(),
+        provider,
+        key_reference: key_reference.to_string(),
+        vault,
+        algorithm,
+        public_key_pem: encode_public_key_pem(&public_key_der)?,
+        fingerprint: compute_fingerprint(&public_key_der),
+        added_at: Utc::now(),
+    };
+    keys.push(key.clone());
+    write_kms_keys(&path, &keys)?;
+    Ok(key)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_kms_keys(app_handle: tauri::AppHandle) -> Result<Vec<KmsKeyInfo>, String> {
+    log::info!("Listing KMS keys");
+    get_kms_keys_path(&app_handle)
+        .and_then(|path| read_kms_keys(&path))
+        .map_err(|e| {
+            log::error!("Failed to list KMS keys: {:?}", e);
+            e.to_string()
+        })
+}
+
+/// Removes the entry; the key itself stays in the KMS.
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_kms_key(app_handle: tauri::AppHandle, key_id: Uuid) -> Result<(), String> {
+    log::info!("Deleting KMS key {}", key_id);
+    _delete_kms_key(&app_handle, key_id)
+        .map_err(|e| {
+            log::error!("Failed to delete KMS key {}: {:?}", key_id, e);
+            e.to_string()
+        })
+        .inspect(|_| audit::record(&app_handle, "deleteKmsKey", json!({ "keyId": key_id })))
+}
+
+fn _delete_kms_key(app_handle: &tauri::AppHandle, key_id: Uuid) -> Result<()> {
+    let path = get_kms_keys_path(app_handle)?;
+    let mut keys = read_kms_keys(&path)?;
+    let count = keys.len();
+    keys.retain(|k| k.key_id != key_id);
+    if keys.len() == count {
+        bail!("KMS key with ID {} not found", key_id);
+    }
+    write_kms_keys(&path, &keys)
+}
+
+/// Signs `document_path` with a KMS key and writes the envelope to `output_path`.
+/// Returns the path written.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn sign_document_with_kms(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    document_path: String,
+    output_path: String,
+    bind_file_name: Option<bool>,
+    tsa_url: Option<String>,
+    metadata: Option<BTreeMap<String, String>>,
+) -> Result<String, String> {
+    log::info!(
+        "Signing document '{}' with KMS key ID {}",
+        document_path,
+        key_id
+    );
+    _sign_document_with_kms(
+        &app_handle,
+        key_id,
+        &document_path,
+        &output_path,
+        bind_file_name.unwrap_or(false),
+        tsa_url.as_deref(),
+        metadata.unwrap_or_default(),
+    )
+    .map_err(|e| {
+        log::error!("Failed to sign with KMS key: {:?}", e);
+        e.to_string()
+    })
+    .map(|(provider, path)| {
+        let path = path_string(&path);
+        audit::record(
+            &app_handle,
+            "signWithKms",
+            json!({
+                "documentPath": document_path,
+                "keyId": key_id,
+                "provider": provider,
+                "outputPath": path,
+            }),
+        );
+        path
+    })
+}
+
+fn _sign_document_with_kms(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    document_path: &str,
+    output_path: &str,
+    bind_file_name: bool,
+    tsa_url: Option<&str>,
+    metadata: BTreeMap<String, String>,
+) -> Result<(KmsProvider, PathBuf)> {
+    let key = find_kms_key(app_handle, key_id)?
+        .ok_or_else(|| anyhow!("KMS key with ID {} not found", key_id))?;
+    let public_key_der = decode_public_key_pem(&key.public_key_pem)?;
+    let rsa_bits = match key.algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => {
+            Some(rsa::RsaPublicKey::from_public_key_der(&public_key_der)?.size() * 8)
+        }
+        _ => None,
+    };
+    check_key_use(app_handle, &key.algorithm, rsa_bits)?;
+    check_timestamping(tsa_url.is_some())?;
+    validate_metadata(app_handle, &metadata)?;
+
+    let path = native_path(document_path)?;
+    check_signable_input(app_handle, &path)?;
+    let (_, document_digest) = hash_file(&path)?;
+    let binding = if bind_file_name {
+        FileBinding::read(&path, false)?
+    } else {
+        FileBinding::default()
+    };
+    let attributes = SignedAttributes {
+        format: ENVELOPE_FORMAT.to_string(),
+        version: ENVELOPE_VERSION,
+        algorithm: key.algorithm.to_string(),
+        key_id,
+        key_fingerprint: key.fingerprint.clone(),
+        digest_algorithm: DIGEST_SHA256.to_string(),
+        document_digest,
+        signed_at: Utc::now(),
+        time_source: TimeSource::LocalClock,
+        time_token: None,
+        file_name: binding.file_name,
+        file_size: None,
+        file_modified_at: None,
+        previous_envelope: None,
+        metadata,
+        approval: None,
+        sequence: Some(next_signature_sequence(app_handle, key_id)?),
+        oidc_identity: None,
+        pq_cosigner: None,
+    };
+    let mut envelope = SignatureEnvelope::seal_with_digest_signer(&attributes, |digest| {
+        sign_digest(&key, digest)
+    })?;
+    // KMS 返回的签名须与登记的公钥匹配（密钥被轮换或替换时会失败）
+    envelope
+        .verify_signature(&key.algorithm, &public_key_der)?
+        .map_err(|_| anyhow!("The KMS signature does not match the key's public key"))?;
+    if let Some(tsa_url) = tsa_url {
+        envelope.add_timestamp(tsa_url, &trusted_tsa_certificates(app_handle)?)?;
+    }
+
+    let content =
+        serde_json::to_string_pretty(&envelope).context("Failed to serialize envelope")?;
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write signature file: {:?}", output_path))?;
+    log::info!(
+        "Document signed with KMS key '{}'. Envelope saved to {:?}",
+        key.name,
+        output_path
+    );
+    Ok((key.provider, output_path))
+}
+
+// --- Helper Functions ---
+
+/// All KMS keys, as candidate signers when verifying.
+pub(crate) fn list_kms_keys_for_verification(
+    app_handle: &tauri::AppHandle,
+) -> Result<Vec<KmsKeyInfo>> {
+    read_kms_keys(&get_kms_keys_path(app_handle)?)
+}
+
+/// Looks up a KMS key by key ID.
+pub(crate) fn find_kms_key(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+) -> Result<Option<KmsKeyInfo>> {
+    Ok(read_kms_keys(&get_kms_keys_path(app_handle)?)?
+        .into_iter()
+        .find(|k| k.key_id == key_id))
+}
+
+// KMS 只对 SHA-256 摘要签名，P-384/P-521 需要 SHA-384/512 摘要，不支持
+fn check_kms_algorithm(algorithm: &SignatureAlgorithm) -> Result<()> {
+    if !algorithm.signs_sha256_digest() {
+        bail!("{} KMS keys are not supported", algorithm);
+    }
+    Ok(())
+}
+
+// 由 KMS 对摘要签名：RSA 为 PKCS#1 v1.5，ECDSA 为 DER（AWS、GCP）或 r||s（Azure）
+fn sign_digest(key: &KmsKeyInfo, digest: &[u8]) -> Result<Vec<u8>> {
+    let rsa = key.algorithm == SignatureAlgorithm::RsaPkcs1Sha256;
+    match key.provider {
+        KmsProvider::Aws => {
+            let reply = aws_call(
+                &key.key_reference,
+                "Sign",
+                &json!({
+                    "KeyId": key.key_reference,
+                    "Message": BASE64.encode(digest),
+                    "MessageType": "DIGEST",
+                    "SigningAlgorithm": if rsa { "RSASSA_PKCS1_V1_5_SHA_256" } else { "ECDSA_SHA_256" },
+                }),
+            )?;
+            decode_field(&reply, "Signature", false)
+        }
+        KmsProvider::Gcp => {
+            let url = format!(
+                "{}{}:asymmetricSign",
+                GCP_KMS_ENDPOINT,
+                gcp_key_name(&key.key_reference)?
+            );
+            let reply = read_reply(
+                agent()
+                    .post(&url)
+                    .header("Authorization", &format!("Bearer {}", gcp_access_token()?))
+                    .header("Content-Type", "application/json")
+                    .send(
+                        &serde_json::to_vec(
+                            &json!({ "digest": { "sha256": BASE64.encode(digest) } }),
+                        )?[..],
+                    ),
+                "Google Cloud KMS sign",
+            )?;
+            decode_field(&reply, "signature", false)
+        }
+        KmsProvider::Azure => {
+            let (key_url, scope) = azure_key(&key.key_reference)?;
+            let url = format!("{}/sign?api-version={}", key_url, AZURE_API_VERSION);
+            let reply = read_reply(
+                agent()
+                    .post(&url)
+                    .header(
+                        "Authorization",
+                        &format!("Bearer {}", azure_access_token(&scope)?),
+                    )
+                    .header("Content-Type", "application/json")
+                    .send(
+                        &serde_json::to_vec(&json!({
+                            "alg": if rsa { "RS256" } else { "ES256" },
+                            "value": URL_SAFE_NO_PAD.encode(digest),
+                        }))?[..],
+                    ),
+                "Azure Key Vault sign",
+            )?;
+            decode_field(&reply, "value", true)
+        }
+        KmsProvider::Vault => {
+            let settings = key
+                .vault
+                .as_ref()
+                .ok_or_else(|| anyhow!("The Vault key has no connection settings"))?;
+            let url = format!(
+                "{}/v1/{}/sign/{}/sha2-256",
+                settings.address, settings.mount, key.key_reference
+            );
+            let mut body = json!({
+                "input": BASE64.encode(digest),
+                "prehashed": true,
+                "key_version": settings.key_version,
+                "marshaling_algorithm": "asn1",
+            });
+            if rsa {
+                body["signature_algorithm"] = json!("pkcs1v15");
+            }
+            let reply = read_reply(
+                vault_request(agent().post(&url), settings)?
+                    .header("Content-Type", "application/json")
+                    .send(&serde_json::to_vec(&body)?[..]),
+                "Vault transit sign",
+            )?;
+            // 签名格式为 vault:v<版本>:<base64>
+            let signature = reply_str(&reply["data"], "signature")?;
+            let encoded = signature
+                .splitn(3, ':')
+                .nth(2)
+                .ok_or_else(|| anyhow!("Malformed signature in the Vault reply"))?;
+            BASE64
+                .decode(encoded)
+                .context("Malformed signature in the Vault reply")
+        }
+    }
+}
+
+fn aws_public_key(key_reference: &str) -> Result<Vec<u8>> {
+    let reply = aws_call(
+        key_reference,
+        "GetPublicKey",
+        &json!({ "KeyId": key_reference }),
+    )?;
+    decode_field(&reply, "PublicKey", false)
+}
+
+fn gcp_public_key(key_reference: &str) -> Result<Vec<u8>> {
+    let url = format!(
+        "{}{}/publicKey",
+        GCP_KMS_ENDPOINT,
+        gcp_key_name(key_reference)?
+    );
+    let reply = read_reply(
+        agent()
+            .get(&url)
+            .header("Authorization", &format!("Bearer {}", gcp_access_token()?))
+            .call(),
+        "Google Cloud KMS public key request",
+    )?;
+    let algorithm = reply_str(&reply, "algorithm")?;
+    if !(algorithm.starts_with("RSA_SIGN_PKCS1_") && algorithm.ends_with("_SHA256")
+        || algorithm == "EC_SIGN_P256_SHA256")
+    {
+        bail!("Unsupported Google Cloud KMS key algorithm: {}", algorithm);
+    }
+    decode_public_key_pem(reply_str(&reply, "pem")?)
+}
+
+fn azure_public_key(key_reference: &str) -> Result<Vec<u8>> {
+    let (key_url, scope) = azure_key(key_reference)?;
+    let url = format!("{}?api-version={}", key_url, AZURE_API_VERSION);
+    let reply = read_reply(
+        agent()
+            .get(&url)
+            .header(
+                "Authorization",
+                &format!("Bearer {}", azure_access_token(&scope)?),
+            )
+            .call(),
+        "Azure Key Vault key request",
+    )?;
+    let jwk = &reply["key"];
+    let component = |name: &str| decode_field(jwk, name, true);
+    match (reply_str(jwk, "kty")?, jwk["crv"].as_str()) {
+        ("RSA" | "RSA-HSM", _) => rsa_spki(&component("n")?, &component("e")?),
+        ("EC" | "EC-HSM", Some("P-256")) => {
+            let mut point = vec![0x04];
+            point.extend(component("x")?);
+            point.extend(component("y")?);
+            p256_spki(&point)
+        }
+        (kty, crv) => bail!(
+            "Unsupported Azure Key Vault key type: {} {}",
+            kty,
+            crv.unwrap_or_default()
+        ),
+    }
+}
+
+// 读取 transit 密钥；记下最新版本，之后都用该版本签名
+fn vault_public_key(settings: &mut VaultTransitSettings, key_reference: &str) -> Result<Vec<u8>> {
+    if key_reference.is_empty() || key_reference.contains('/') {
+        bail!("Vault transit keys are referenced by name");
+    }
+    let url = format!(
+        "{}/v1/{}/keys/{}",
+        settings.address, settings.mount, key_reference
+    );
+    let reply = read_reply(
+        vault_request(agent().get(&url), settings)?.call(),
+        "Vault transit key request",
+    )?;
+    let data = &reply["data"];
+    let key_type = reply_str(data, "type")?;
+    if !(key_type.starts_with("rsa-") || key_type == "ecdsa-p256") {
+        bail!("Unsupported Vault transit key type: {}", key_type);
+    }
+    let version = data["latest_version"]
+        .as_u64()
+        .ok_or_else(|| anyhow!("The reply has no 'latest_version'"))?;
+    settings.key_version = u32::try_from(version)?;
+    decode_public_key_pem(reply_str(&data["keys"][version.to_string()], "public_key")?)
+}
+
+fn normalize_vault_settings(mut settings: VaultTransitSettings) -> Result<VaultTransitSettings> {
+    settings.address = settings.address.trim().trim_end_matches('/').to_string();
+    if !(settings.address.starts_with("https://") || settings.address.starts_with("http://")) {
+        bail!("The Vault address must be an http(s) URL");
+    }
+    settings.mount = settings.mount.trim().trim_matches('/').to_string();
+    settings.auth_mount = settings.auth_mount.trim().trim_matches('/').to_string();
+    if settings.mount.is_empty() || settings.auth_mount.is_empty() {
+        bail!("Vault mount paths cannot be empty");
+    }
+    settings.namespace = settings
+        .namespace
+        .map(|namespace| namespace.trim().to_string())
+        .filter(|namespace| !namespace.is_empty());
+    Ok(settings)
+}
+
+// 为请求加上 Vault 令牌与命名空间
+fn vault_request<B>(
+    request: ureq::RequestBuilder<B>,
+    settings: &VaultTransitSettings,
+) -> Result<ureq::RequestBuilder<B>> {
+    let request = request.header("X-Vault-Token", &vault_token(settings)?);
+    Ok(match &settings.namespace {
+        Some(namespace) => request.header("X-Vault-Namespace", namespace),
+        None => request,
+    })
+}
+
+fn vault_token(settings: &VaultTransitSettings) -> Result<String> {
+    match settings.auth_method {
+        VaultAuthMethod::Token => env_var("VAULT_TOKEN"),
+        VaultAuthMethod::AppRole => {
+            let url = format!("{}/v1/auth/{}/login", settings.address, settings.auth_mount);
+            let mut request = agent()
+                .post(&url)
+                .header("Content-Type", "application/json");
+            if let Some(namespace) = &settings.namespace {
+                request = request.header("X-Vault-Namespace", namespace);
+            }
+            let reply = read_reply(
+                request.send(
+                    &serde_json::to_vec(&json!({
+                        "role_id": env_var("VAULT_ROLE_ID")?,
+                        "secret_id": env_var("VAULT_SECRET_ID")?,
+                    }))?[..],
+                ),
+                "Vault AppRole login",
+            )?;
+            Ok(reply_str(&reply["auth"], "client_token")?.to_string())
+        }
+    }
+}
+
+// AWS KMS JSON 协议，请求按 Signature Version 4 签名
+fn aws_call(key_reference: &str, action: &str, body: &Value) -> Result<Value> {
+    let (region, host) = aws_endpoint(key_reference)?;
+    let access_key = env_var("AWS_ACCESS_KEY_ID")?;
+    let secret_key = env_var("AWS_SECRET_ACCESS_KEY")?;
+    let session_token = env_var("AWS_SESSION_TOKEN").ok();
+    let payload = serde_json::to_vec(body)?;
+    let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let target = format!("TrentService.{}", action);
+
+    let mut headers = vec![
+        ("content-type", AWS_CONTENT_TYPE),
+        ("host", host.as_str()),
+        ("x-amz-date", amz_date.as_str()),
+        ("x-amz-target", target.as_str()),
+    ];
+    if let Some(token) = &session_token {
+        headers.push(("x-amz-security-token", token.as_str()));
+    }
+    headers.sort();
+    let authorization = sigv4_authorization(
+        &access_key,
+        &secret_key,
+        region,
+        "kms",
+        &amz_date,
+        &headers,
+        &payload,
+    )?;
+    let mut request = agent()
+        .post(&format!("https://{}/", host))
+        .header("Authorization", &authorization);
+    for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+        request = request.header(*name, *value);
+    }
+    read_reply(request.send(&payload[..]), &format!("AWS KMS {}", action))
+}
+
+// 从密钥或别名 ARN 中取出区域和服务主机名
+fn aws_endpoint(key_reference: &str) -> Result<(&str, String)> {
+    let parts: Vec<&str> = key_reference.splitn(6, ':').collect();
+    match parts.as_slice() {
+        ["arn", partition, "kms", region, _account, resource]
+            if !region.is_empty()
+                && (resource.starts_with("key/") || resource.starts_with("alias/")) =>
+        {
+            let suffix = if *partition == "aws-cn" {
+                "amazonaws.com.cn"
+            } else {
+                "amazonaws.com"
+            };
+            Ok((region, format!("kms.{}.{}", region, suffix)))
+        }
+        _ => bail!(
+            "AWS KMS keys are referenced by key or alias ARN \
+             (arn:aws:kms:<region>:<account>:key/<key-id>)"
+        ),
+    }
+}
+
+fn sigv4_authorization(
+    access_key: &str,
+    secret_key: &str,
+    region: &str,
+    service: &str,
+    amz_date: &str,
+    headers: &[(&str, &str)],
+    payload: &[u8],
+) -> Result<String> {
+    let date = &amz_date[..8];
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(payload))
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let signing_key = sigv4_signing_key(secret_key, date, region, service)?;
+    Ok(format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key,
+        scope,
+        signed_headers,
+        hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes())?)
+    ))
+}
+
+fn sigv4_signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Result<Vec<u8>> {
+    let date_key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes())?;
+    let region_key = hmac_sha256(&date_key, region.as_bytes())?;
+    let service_key = hmac_sha256(&region_key, service.as_bytes())?;
+    hmac_sha256(&service_key, b"aws4_request")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key)
+        .map_err(|e| anyhow!("Failed to create HMAC: {}", e))?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn gcp_key_name(key_reference: &str) -> Result<&str> {
+    let name = key_reference.trim_start_matches('/');
+    if !name.starts_with("projects/") || !name.contains("/cryptoKeyVersions/") {
+        bail!(
+            "Google Cloud KMS keys are referenced by key version name \
+             (projects/<p>/locations/<l>/keyRings/<r>/cryptoKeys/<k>/cryptoKeyVersions/<v>)"
+        );
+    }
+    Ok(name)
+}
+
+// 优先使用现成的访问令牌，否则用服务账号密钥签发 JWT 换取令牌
+fn gcp_access_token() -> Result<String> {
+    if let Ok(token) = env_var("GOOGLE_OAUTH_ACCESS_TOKEN") {
+        return Ok(token);
+    }
+    let credentials_path = env_var("GOOGLE_APPLICATION_CREDENTIALS").map_err(|_| {
+        anyhow!(
+            "No Google Cloud credentials: set GOOGLE_OAUTH_ACCESS_TOKEN or \
+             GOOGLE_APPLICATION_CREDENTIALS"
+        )
+    })?;
+    let credentials: Value = serde_json::from_slice(
+        &fs::read(&credentials_path)
+            .with_context(|| format!("Failed to read {}", credentials_path))?,
+    )
+    .context("Invalid Google Cloud credentials file")?;
+    if credentials["type"] != "service_account" {
+        bail!("GOOGLE_APPLICATION_CREDENTIALS must name a service account key file");
+    }
+    let token_uri = credentials["token_uri"]
+        .as_str()
+        .unwrap_or("https://oauth2.googleapis.com/token");
+    let now = Utc::now().timestamp();
+    let claims = json!({
+        "iss": reply_str(&credentials, "client_email")?,
+        "scope": GCP_KMS_SCOPE,
+        "aud": token_uri,
+        "iat": now,
+        "exp": now + 3600,
+    });
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(br#"{"alg":"RS256","typ":"JWT"}"#),
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims)?)
+    );
+    let private_key = rsa::RsaPrivateKey::from_pkcs8_pem(reply_str(&credentials, "private_key")?)
+        .context("Invalid private key in the service account file")?;
+    let signature = RsaSigningKey::<rsa::sha2::Sha256>::new(private_key)
+        .sign(signing_input.as_bytes())
+        .to_vec();
+    let assertion = format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature));
+    let reply = read_reply(
+        agent().post(token_uri).send_form([
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", assertion.as_str()),
+        ]),
+        "Google Cloud token request",
+    )?;
+    Ok(reply_str(&reply, "access_token")?.to_string())
+}
+
+// 带版本的密钥标识 URL，以及对应的令牌范围（去掉保管库名的主机名）
+fn azure_key(key_reference: &str) -> Result<(String, String)> {
+    let malformed = || {
+        anyhow!(
+            "Azure keys are referenced by versioned key identifier \
+             (https://<vault>.vault.azure.net/keys/<name>/<version>)"
+        )
+    };
+    let rest = key_reference
+        .strip_prefix("https://")
+        .ok_or_else(malformed)?;
+    let (host, path) = rest.split_once('/').ok_or_else(malformed)?;
+    let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    let ["keys", name, version] = segments.as_slice() else {
+        return Err(malformed());
+    };
+    let (_, service_host) = host.split_once('.').ok_or_else(malformed)?;
+    if name.is_empty() || version.is_empty() || !service_host.contains('.') {
+        return Err(malformed());
+    }
+    Ok((
+        format!("https://{}/keys/{}/{}", host, name, version),
+        format!("https://{}/.default", service_host),
+    ))
+}
+
+fn azure_access_token(scope: &str) -> Result<String> {
+    if let Ok(token) = env_var("AZURE_ACCESS_TOKEN") {
+        return Ok(token);
+    }
+    let (tenant, client_id, client_secret) = match (
+        env_var("AZURE_TENANT_ID"),
+        env_var("AZURE_CLIENT_ID"),
+        env_var("AZURE_CLIENT_SECRET"),
+    ) {
+        (Ok(tenant), Ok(client_id), Ok(client_secret)) => (tenant, client_id, client_secret),
+        _ => bail!(
+            "No Azure credentials: set AZURE_ACCESS_TOKEN, or AZURE_TENANT_ID, \
+             AZURE_CLIENT_ID and AZURE_CLIENT_SECRET"
+        ),
+    };
+    let authority =
+        env_var("AZURE_AUTHORITY_HOST").unwrap_or_else(|_| AZURE_AUTHORITY_HOST.to_string());
+    let url = format!(
+        "{}/{}/oauth2/v2.0/token",
+        authority.trim_end_matches('/'),
+        tenant
+    );
+    let reply = read_reply(
+        agent().post(&url).send_form([
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+            ("scope", scope),
+        ]),
+        "Azure token request",
+    )?;
+    Ok(reply_str(&reply, "access_token")?.to_string())
+}
+
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(KMS_TIMEOUT))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+// 读取 JSON 回复；失败时带上服务返回的错误信息（各服务字段不同）
+fn read_reply(
+    response: Result<ureq::http::Response<ureq::Body>, ureq::Error>,
+    what: &str,
+) -> Result<Value> {
+    let mut response = response.with_context(|| format!("{} failed", what))?;
+    let status = response.status();
+    let bytes = response
+        .body_mut()
+        .with_config()
+        .limit(MAX_RESPONSE_SIZE)
+        .read_to_vec()
+        .with_context(|| format!("Failed to read the reply to {}", what))?;
+    let body: Value = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Invalid reply to {} (HTTP {})", what, status))?;
+    if !status.is_success() {
+        let message = [
+            &body["error"]["message"],
+            &body["error_description"],
+            &body["errors"][0],
+            &body["message"],
+            &body["Message"],
+        ]
+        .into_iter()
+        .find_map(Value::as_str)
+        .unwrap_or("no details");
+        bail!("{} failed (HTTP {}): {}", what, status, message);
+    }
+    Ok(body)
+}
+
+fn reply_str<'a>(value: &'a Value, field: &str) -> Result<&'a str> {
+    value[field]
+        .as_str()
+        .ok_or_else(|| anyhow!("The reply has no '{}'", field))
+}
+
+fn decode_field(value: &Value, field: &str, url_safe: bool) -> Result<Vec<u8>> {
+    let text = reply_str(value, field)?;
+    if url_safe {
+        URL_SAFE_NO_PAD.decode(text.trim_end_matches('='))
+    } else {
+        BASE64.decode(text)
+    }
+    .with_context(|| format!("Malformed '{}' in the reply", field))
+}
+
+fn env_var(name: &str) -> Result<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("{} is not set", name))
+}
+
+// 获取 KMS 密钥文件路径
+fn get_kms_keys_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, KMS_KEYS_FILENAME)
+}
+
+fn read_kms_keys(path: &Path) -> Result<Vec<KmsKeyInfo>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read KMS keys file: {:?}", path))?;
+    serde_json::from_str(&content).context("Failed to parse KMS keys JSON")
+}
+
+fn write_kms_keys(path: &Path, keys: &[KmsKeyInfo]) -> Result<()> {
+    let content = serde_json::to_string_pretty(keys).context("Failed to serialize KMS keys")?;
+    fs::write(path, content).with_context(|| format!("Failed to write KMS keys file: {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_key_references_and_derives_sigv4_keys() {
+        // AWS 文档中的签名密钥派生示例
+        let signing_key = sigv4_signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20150830",
+            "us-east-1",
+            "iam",
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(signing_key),
+            "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+        );
+
+        let (region, host) = aws_endpoint(
+            "arn:aws:kms:eu-central-1:111122223333:key/1234abcd-12ab-34cd-56ef-1234567890ab",
+        )
+        .unwrap();
+        assert_eq!(
+            (region, host.as_str()),
+            ("eu-central-1", "kms.eu-central-1.amazonaws.com")
+        );
+        assert!(aws_endpoint("1234abcd-12ab-34cd-56ef-1234567890ab").is_err());
+
+        let (key_url, scope) =
+            azure_key("https://contoso.vault.azure.net/keys/signing/0123456789abcdef/").unwrap();
+        assert_eq!(
+            key_url,
+            "https://contoso.vault.azure.net/keys/signing/0123456789abcdef"
+        );
+        assert_eq!(scope, "https://vault.azure.net/.default");
+        assert!(azure_key("https://contoso.vault.azure.net/keys/signing").is_err());
+
+        assert!(gcp_key_name("projects/p/locations/global/keyRings/r/cryptoKeys/k").is_err());
+
+        let settings = normalize_vault_settings(VaultTransitSettings {
+            address: " https://vault.example.com:8200/ ".to_string(),
+            mount: "/transit/".to_string(),
+            auth_method: VaultAuthMethod::AppRole,
+            auth_mount: "approle".to_string(),
+            namespace: Some(" ".to_string()),
+            key_version: 0,
+        })
+        .unwrap();
+        assert_eq!(settings.address, "https://vault.example.com:8200");
+        assert_eq!(settings.mount, "transit");
+        assert!(settings.namespace.is_none());
+    }
+
+    #[test]
+    fn only_sha256_digest_algorithms_are_accepted() {
+        assert!(check_kms_algorithm(&SignatureAlgorithm::RsaPkcs1Sha256).is_ok());
+        assert!(check_kms_algorithm(&SignatureAlgorithm::EcdsaP256Sha256).is_ok());
+        for algorithm in [
+            SignatureAlgorithm::EcdsaP384Sha384,
+            SignatureAlgorithm::EcdsaP521Sha512,
+            SignatureAlgorithm::Ed25519,
+            SignatureAlgorithm::MlDsa65,
+        ] {
+            assert!(check_kms_algorithm(&algorithm).is_err(), "{}", algorithm);
+        }
+    }
+}
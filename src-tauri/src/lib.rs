@@ -1,33 +1,416 @@
+mod archive_verification;
+mod audit;
+mod batch_jobs;
+mod benchmarks;
+mod clearsign;
+mod completion_certificate;
+mod compliance_report;
+mod config_transfer;
+mod consent;
+mod contacts;
+mod content_uris;
 mod crypto_types;
+mod deep_links;
+mod device_binding;
+mod dialogs;
+mod directory_baseline;
+mod display_names;
+mod dual_control;
+mod envelope;
+mod escrow;
+mod file_classify;
+mod file_manager;
+mod hashing;
+mod key_bulk;
+mod key_derivation;
+mod key_exchange;
+mod key_folders;
 mod key_management;
+mod key_sessions;
+mod key_wrapping;
+mod keycard;
+mod kms;
+mod local_api;
+mod logs;
+mod metadata_schema;
+mod native_messaging;
+mod native_paths;
+mod odf_signing;
+mod oidc_identity;
+mod ooxml_signing;
+mod openpgp;
+mod output_paths;
+mod passkeys;
+mod paste_import;
+mod policy;
+mod secure_temp;
+mod self_tests;
+mod settings;
+mod share_intake;
+mod signable_inputs;
+mod signature_inspect;
+mod signature_sequence;
+mod signed_notes;
 mod signing;
+mod signing_profiles;
+mod signing_requests;
+mod smime;
+mod sshsig;
+mod statistics;
+mod store_check;
+mod store_journal;
+mod store_lock;
+mod sync;
+mod tasks;
+mod test_vectors;
+mod timestamp;
+mod token_unwrap;
+#[cfg(desktop)]
+mod tray;
+mod trust_bundle;
+mod trusted_artifacts;
+mod unlock_policy;
+mod vault;
+mod verification_kit;
 
+use archive_verification::*;
+use audit::*;
+use batch_jobs::*;
+use benchmarks::*;
+use clearsign::*;
+use compliance_report::*;
+use config_transfer::*;
+use consent::*;
+use contacts::*;
+use content_uris::*;
+use deep_links::*;
+use device_binding::*;
+use dialogs::*;
+use directory_baseline::*;
+use display_names::*;
+use dual_control::*;
+use escrow::*;
+use file_classify::*;
+use file_manager::*;
+use hashing::*;
+use key_bulk::*;
+use key_derivation::*;
+use key_exchange::*;
+use key_folders::*;
 use key_management::*;
+use key_sessions::*;
+use key_wrapping::*;
+use keycard::*;
+use kms::*;
+use local_api::*;
+use logs::*;
+use metadata_schema::*;
+use native_messaging::*;
+use odf_signing::*;
+use oidc_identity::*;
+use ooxml_signing::*;
+use output_paths::*;
+use passkeys::*;
+use paste_import::*;
+use policy::*;
+use secure_temp::*;
+use self_tests::*;
+use settings::*;
+use share_intake::*;
+use signable_inputs::*;
+use signature_inspect::*;
+use signature_sequence::*;
+use signed_notes::*;
 use signing::*;
+use signing_profiles::*;
+use signing_requests::*;
+use smime::*;
+use statistics::*;
+use store_check::*;
+use store_journal::*;
+use store_lock::*;
+use sync::*;
+use tasks::*;
+use test_vectors::*;
+use timestamp::*;
+use token_unwrap::*;
+use trust_bundle::*;
+use trusted_artifacts::*;
+use unlock_policy::*;
+use vault::*;
+use verification_kit::*;
+
+pub use native_messaging::{native_messaging_caller, run_native_messaging_host};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    // Must come first: a second launch (e.g. from a sigust:// link) hands over to this one
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+        tray::show_main_window(app)
+    }));
+    let builder = builder
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_deep_link::init());
+    #[cfg(mobile)]
+    let builder = builder
+        .plugin(tauri_plugin_barcode_scanner::init())
+        .plugin(tauri_plugin_sharetarget::init())
+        .plugin(tauri_plugin_share::init());
+
+    builder
+        .manage(ShareInbox::default())
+        .manage(TaskManager::default())
+        .manage(KeySessions::default())
+        .manage(PastedSignatures::default())
         .setup(|app| {
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Debug)
-                        .build(),
-                )?;
+            app.handle().plugin(log_plugin())?;
+            if let Err(e) = prune_rotated_logs(app.handle()) {
+                log::warn!("Failed to prune old log files: {:?}", e);
+            }
+            // Known-answer tests; signing stays disabled if any of them fail
+            let self_test_report = run_and_record();
+            if !self_test_report.passed {
+                log::error!(
+                    "Cryptographic self-tests failed, signing is disabled: {:?}",
+                    self_test_report.results
+                );
+            }
+            // Enterprise policy for managed deployments (enforced by key and signing commands)
+            load_and_record();
+            // Roll back key store changes interrupted by a crash, before anything reads the store
+            match recover_store_transactions(app.handle()) {
+                Ok(0) => {}
+                Ok(count) => log::warn!("Rolled back {} interrupted key store operations", count),
+                Err(e) => log::error!(
+                    "Failed to recover interrupted key store operations: {:?}",
+                    e
+                ),
+            }
+            // Bring private key files written by older versions into the versioned format
+            if let Err(e) = migrate_legacy_key_files(app.handle()) {
+                log::error!("Failed to migrate legacy private key files: {:?}", e);
+            }
+            // Record fingerprints and key sizes of keys stored by older versions
+            if let Err(e) = record_public_key_details(app.handle()) {
+                log::error!("Failed to record public key details: {:?}", e);
+            }
+            // Remove links to documents opened through content URIs before a crash
+            if let Err(e) = clear_stale_document_links(app.handle()) {
+                log::error!("Failed to remove stale document links: {:?}", e);
+            }
+            // Wipe sensitive temporary files left behind by a crash
+            if let Err(e) = clear_stale_temp_files(app.handle()) {
+                log::error!("Failed to clear stale temporary files: {:?}", e);
+            }
+            // Permanently delete keys that stayed in the trash past the retention period
+            if let Err(e) = purge_expired_trash(app.handle()) {
+                log::error!("Failed to purge expired keys from the trash: {:?}", e);
+            }
+            // Flags stalled background tasks and times out stuck ones
+            start_task_watchdog(app.handle());
+            // Ends key sessions when the machine sleeps or the session is locked
+            start_lock_monitor(app.handle());
+            // Starts signing / verification flows from sigust:// links
+            start_deep_links(app.handle());
+            // Loopback HTTP API for local tools, if enabled
+            start_local_api(app.handle());
+            // Re-verifies the archive folder on schedule, if configured
+            start_archive_verification(app.handle());
+            // Fetches the organization trust bundle on schedule, if configured
+            start_trust_bundle_sync(app.handle());
+            // Tray icon with quick sign / verify actions
+            #[cfg(desktop)]
+            if let Err(e) = tray::setup_tray(app.handle()) {
+                log::error!("Failed to create tray icon: {:?}", e);
             }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // Key Management
             generate_key_pair,
+            import_key_pair,
+            import_public_key,
+            export_key_pair,
+            change_key_password,
             list_keys,
             get_key_details,
+            get_key_fingerprint,
+            update_key_identity,
+            set_dual_control,
+            link_pq_key,
+            list_passkeys,
+            begin_passkey_registration,
+            finish_passkey_registration,
+            delete_passkey,
+            begin_passkey_signing,
+            finish_passkey_signing,
+            add_kms_key,
+            list_kms_keys,
+            delete_kms_key,
+            sign_document_with_kms,
+            update_oidc_settings,
+            test_oidc_login,
+            list_master_seeds,
+            create_master_seed,
+            import_master_seed,
+            export_master_seed,
+            export_master_seed_mnemonic,
+            list_derived_keys,
+            recover_keys_from_mnemonic,
+            delete_master_seed,
+            derive_key_pair,
+            get_token_unwrap_request,
+            provide_token_secret,
+            set_token_unwrap,
+            set_key_unlock_policy,
+            begin_unlock_confirmation,
+            finish_unlock_confirmation,
+            verify_trusted_artifact,
+            create_signed_note,
+            list_signed_notes,
+            verify_signed_note,
+            delete_signed_note,
+            update_signable_input_rules,
+            preview_signable_input,
+            clearsign_text,
+            verify_clearsigned_text,
+            delete_key,
+            list_trashed_keys,
+            restore_key,
+            purge_trash,
+            list_key_folders,
+            create_key_folder,
+            delete_key_folder,
+            move_key_to_folder,
+            delete_keys,
+            export_public_keys,
+            tag_keys,
+            backup_keys,
+            unlock_key_session,
+            lock_key_session,
+            list_key_sessions,
+            check_store,
+            repair_store,
+            migrate_key_storage,
+            open_key_storage_dir,
             // Signing & Verification
             sign_document,
+            sign_documents_batch,
+            verify_documents_batch,
+            list_batch_jobs,
+            resume_batch_job,
+            discard_batch_job,
+            sign_office_document,
+            sign_odf_document,
+            verify_smime_email,
+            generate_verification_report,
+            export_verification_kit,
+            update_archive_verification,
+            run_archive_verification,
+            get_archive_verification_report,
+            update_trust_bundle_settings,
+            sync_trust_bundle,
+            get_trust_bundle_status,
             verify_signature,
+            verify_signature_with_progress,
+            verify_signatures,
+            refresh_signature,
+            update_trusted_tsa_certificates,
+            receive_shared_file,
+            sign_shared_file,
+            discard_shared_file,
+            take_deep_link_requests,
+            reveal_in_file_manager,
+            pick_document_and_sign,
+            pick_files_and_verify,
+            classify_files,
+            inspect_signature,
+            get_signature_sequence,
+            set_recovery_key,
+            export_escrow_file,
+            recover_escrowed_key,
+            get_enterprise_policy,
+            reload_enterprise_policy,
+            list_signing_profiles,
+            save_signing_profile,
+            delete_signing_profile,
+            sign_with_profile,
+            update_metadata_schema,
+            create_signing_request,
+            read_signing_request,
+            sign_signing_request,
+            sign_signing_request_bundle,
+            attach_signature_bundle,
+            // Contacts & Key Exchange
+            list_contacts,
+            remove_contact,
+            export_contacts,
+            import_contacts,
+            get_key_exchange_qr,
+            export_keycard,
+            parse_key_exchange_payload,
+            confirm_key_exchange,
+            preview_pasted_text,
+            import_pasted_public_key,
+            stage_pasted_signature,
+            discard_pasted_signature,
+            // Sync
+            get_sync_config,
+            configure_sync,
+            export_sync_delta,
+            import_sync_changes,
+            // Key Vault
+            save_key_vault,
+            restore_key_vault,
+            // Settings
+            run_self_tests,
+            run_benchmarks,
+            generate_test_vectors,
+            check_test_vectors,
+            get_settings,
+            get_display_names,
+            calibrate_kdf,
+            update_device_binding,
+            update_output_settings,
+            export_config,
+            import_config,
+            update_consent_settings,
+            confirm_consent,
+            get_local_api_status,
+            update_local_api,
+            rotate_local_api_token,
+            install_native_messaging_host,
+            // Audit Log
+            get_audit_log,
+            export_audit_log,
+            verify_audit_log,
+            get_statistics,
+            // Diagnostics
+            get_recent_logs,
+            export_logs,
+            // Background Tasks
+            get_task_status,
+            list_tasks,
+            cancel_task,
+            hash_directory,
+            update_hash_threads,
+            // File Integrity
+            create_directory_baseline,
+            list_directory_baselines,
+            check_drift,
+            delete_directory_baseline,
         ])
-        .run(tauri::generate_context!())
+        .run(context())
         .expect("error while running tauri application");
 }
+
+/// The application context built from tauri.conf.json; the native messaging host reads
+/// the app identifier from it.
+pub(crate) fn context() -> tauri::Context {
+    tauri::generate_context!()
+}
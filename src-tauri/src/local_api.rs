@@ -0,0 +1,494 @@
+// src-tauri/src/local_api.rs
+// Opt-in HTTP API on 127.0.0.1, so local tools (IDEs, build scripts, office macros) can use
+// the running app without Tauri bindings. Bodies and responses are JSON (camelCase):
+//
+//   GET  /v1/keys    keys in the store, as `KeyInfo`
+//   POST /v1/sign    {documentPath, keyId, outputPath?} -> {outputPath, keyId}
+//   POST /v1/verify  {documentPath, signaturePath, keyId?} -> `VerificationResult` with a
+//                    key ID, otherwise `MultiVerificationReport` (our keys and contacts)
+//   POST /v1/browser/sign  requests relayed by the browser extension's native messaging
+//                    host, each approved by the user (see native_messaging.rs)
+//
+// Every request needs `Authorization: Bearer <token>`. Requests from browsers (with an
+// `Origin` header) or for another `Host` are refused, so web pages cannot reach the API
+// through the user's browser. The API never takes passwords: it signs only with keys that
+// have an unlocked session (see key_sessions.rs), like the tray's quick sign. While it runs,
+// `local-api.json` in the app data directory holds the URL and token (owner-only on Unix).
+use crate::audit;
+use crate::crypto_types::{DigestScheme, LocalApiSettings, LocalApiStatus};
+use crate::key_management::{get_app_data_file, list_keys};
+use crate::key_sessions::{session_key, KeySessions};
+use crate::native_messaging::browser_sign;
+use crate::native_paths::{native_path, path_string, read_file};
+use crate::output_paths::{resolve_output_path, signature_output_path};
+use crate::policy::{check_timestamping, verification_only};
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use crate::signable_inputs::check_signable_input;
+use crate::signing::{sign_bytes, verify_signature, verify_signatures};
+use aead::OsRng;
+use anyhow::{anyhow, bail, Context, Result};
+use rsa::rand_core::RngCore;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+use tiny_http::{Header, Method, Request, Response, Server};
+use uuid::Uuid;
+
+pub(crate) const DISCOVERY_FILE: &str = "local-api.json";
+const TOKEN_BYTES: usize = 32;
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+struct RunningApi {
+    server: Arc<Server>,
+    port: u16,
+}
+
+static RUNNING_API: Mutex<Option<RunningApi>> = Mutex::new(None);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct SignRequest {
+    document_path: String,
+    key_id: Uuid,
+    /// Defaults to the configured signature file name next to the document.
+    output_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct VerifyRequest {
+    document_path: String,
+    signature_path: String,
+    key_id: Option<Uuid>,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_local_api_status(app_handle: tauri::AppHandle) -> Result<LocalApiStatus, String> {
+    log::info!("Getting local API status");
+    local_api_status(&app_handle).map_err(|e| {
+        log::error!("Failed to get local API status: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// Turns the API on or off, optionally on another port. A token is created the first time
+/// the API is enabled.
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_local_api(
+    app_handle: tauri::AppHandle,
+    enabled: bool,
+    port: Option<u16>,
+) -> Result<LocalApiStatus, String> {
+    log::info!("Updating local API: enabled={}, port={:?}", enabled, port);
+    _update_local_api(&app_handle, enabled, port, false)
+        .map_err(|e| {
+            log::error!("Failed to update local API: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|status| {
+            audit::record(
+                &app_handle,
+                "updateLocalApi",
+                json!({ "enabled": status.enabled, "url": status.url }),
+            )
+        })
+}
+
+/// Replaces the token; clients using the old one are refused from now on.
+#[tauri::command(rename_all = "camelCase")]
+pub fn rotate_local_api_token(app_handle: tauri::AppHandle) -> Result<LocalApiStatus, String> {
+    log::info!("Rotating local API token");
+    local_api_settings(&app_handle)
+        .and_then(|settings| _update_local_api(&app_handle, settings.enabled, None, true))
+        .map_err(|e| {
+            log::error!("Failed to rotate local API token: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|_| audit::record(&app_handle, "rotateLocalApiToken", json!({})))
+}
+
+fn _update_local_api(
+    app_handle: &tauri::AppHandle,
+    enabled: bool,
+    port: Option<u16>,
+    new_token: bool,
+) -> Result<LocalApiStatus> {
+    stop_server(app_handle)?;
+    let settings = update_settings(app_handle, |settings| {
+        let local_api = &mut settings.local_api;
+        local_api.enabled = enabled;
+        if let Some(port) = port {
+            local_api.port = port;
+        }
+        if new_token || (enabled && local_api.token.is_none()) {
+            local_api.token = Some(generate_token());
+        }
+    })?;
+    if enabled {
+        start_server(app_handle, &settings.local_api)?;
+    }
+    local_api_status(app_handle)
+}
+
+// --- Helper Functions ---
+
+/// Starts the API at startup if it is enabled.
+pub fn start_local_api(app_handle: &tauri::AppHandle) {
+    let result = local_api_settings(app_handle).and_then(|settings| {
+        if settings.enabled {
+            start_server(app_handle, &settings)?;
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::error!("Failed to start the local API: {:?}", e);
+    }
+}
+
+fn local_api_settings(app_handle: &tauri::AppHandle) -> Result<LocalApiSettings> {
+    Ok(read_settings(&get_settings_path(app_handle)?)?.local_api)
+}
+
+fn local_api_status(app_handle: &tauri::AppHandle) -> Result<LocalApiStatus> {
+    let settings = local_api_settings(app_handle)?;
+    let port = running_port()?;
+    Ok(LocalApiStatus {
+        enabled: settings.enabled,
+        running: port.is_some(),
+        url: port.map(base_url),
+        token: settings.token,
+        discovery_path: match port {
+            Some(_) => Some(path_string(&get_app_data_file(app_handle, DISCOVERY_FILE)?)),
+            None => None,
+        },
+    })
+}
+
+fn running_port() -> Result<Option<u16>> {
+    Ok(lock_running_api()?.as_ref().map(|api| api.port))
+}
+
+fn lock_running_api() -> Result<std::sync::MutexGuard<'static, Option<RunningApi>>> {
+    RUNNING_API
+        .lock()
+        .map_err(|_| anyhow!("Local API lock poisoned"))
+}
+
+// 只监听回环地址；每个请求在单独的线程中处理
+fn start_server(app_handle: &tauri::AppHandle, settings: &LocalApiSettings) -> Result<()> {
+    let token = settings
+        .token
+        .clone()
+        .ok_or_else(|| anyhow!("The local API has no token"))?;
+    let server = Server::http(("127.0.0.1", settings.port))
+        .map_err(|e| anyhow!("Failed to listen on 127.0.0.1:{}: {}", settings.port, e))?;
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.port())
+        .ok_or_else(|| anyhow!("The local API is not listening on an IP address"))?;
+    let server = Arc::new(server);
+    write_discovery_file(app_handle, port, &token)?;
+    *lock_running_api()? = Some(RunningApi {
+        server: server.clone(),
+        port,
+    });
+    log::info!("Local API listening on {}", base_url(port));
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let app_handle = app_handle.clone();
+            let token = token.clone();
+            std::thread::spawn(move || handle_request(&app_handle, &token, port, request));
+        }
+        log::info!("Local API stopped");
+    });
+    Ok(())
+}
+
+fn stop_server(app_handle: &tauri::AppHandle) -> Result<()> {
+    let Some(api) = lock_running_api()?.take() else {
+        return Ok(());
+    };
+    api.server.unblock();
+    let path = get_app_data_file(app_handle, DISCOVERY_FILE)?;
+    if path.exists() {
+        fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+    }
+    Ok(())
+}
+
+// 发现文件：本机工具从这里读取地址和令牌，仅所有者可读
+fn write_discovery_file(app_handle: &tauri::AppHandle, port: u16, token: &str) -> Result<()> {
+    let path = get_app_data_file(app_handle, DISCOVERY_FILE)?;
+    let content = serde_json::to_vec_pretty(&json!({
+        "url": base_url(port),
+        "token": token,
+        "pid": std::process::id(),
+    }))?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&path)
+        .and_then(|mut file| file.write_all(&content))
+        .with_context(|| format!("Failed to write {:?}", path))
+}
+
+fn base_url(port: u16) -> String {
+    format!("http://127.0.0.1:{}", port)
+}
+
+fn generate_token() -> String {
+    let mut token = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut token);
+    hex::encode(token)
+}
+
+fn handle_request(app_handle: &tauri::AppHandle, token: &str, port: u16, mut request: Request) {
+    let (status, body) = match route(app_handle, token, port, &mut request) {
+        Ok(body) => (200, body),
+        Err((status, message)) => {
+            log::warn!(
+                "Local API request {} {} refused: {}",
+                request.method(),
+                request.url(),
+                message
+            );
+            (status, json!({ "error": message }))
+        }
+    };
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("static header is valid");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        log::warn!("Failed to answer local API request: {}", e);
+    }
+}
+
+fn route(
+    app_handle: &tauri::AppHandle,
+    token: &str,
+    port: u16,
+    request: &mut Request,
+) -> std::result::Result<Value, (u16, String)> {
+    check_headers(request.headers(), token, port).map_err(|(status, e)| (status, e.to_string()))?;
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let result = match (request.method(), path.as_str()) {
+        (Method::Get, "/v1/keys") => list_keys(app_handle.clone())
+            .map_err(|e| anyhow!(e))
+            .and_then(|keys| Ok(serde_json::to_value(keys)?)),
+        (Method::Post, "/v1/sign") => {
+            read_body(request).and_then(|body| api_sign(app_handle, body))
+        }
+        (Method::Post, "/v1/verify") => {
+            read_body(request).and_then(|body| api_verify(app_handle, body))
+        }
+        (Method::Post, "/v1/browser/sign") => {
+            read_body(request).and_then(|body| browser_sign(app_handle, body))
+        }
+        (_, "/v1/keys" | "/v1/sign" | "/v1/verify" | "/v1/browser/sign") => {
+            return Err((405, "Method not allowed".to_string()))
+        }
+        _ => return Err((404, "Not found".to_string())),
+    };
+    result.map_err(|e| (400, format!("{:#}", e)))
+}
+
+/// Checks the token, and that the request comes from a local tool rather than a browser.
+fn check_headers(
+    headers: &[Header],
+    token: &str,
+    port: u16,
+) -> std::result::Result<(), (u16, &'static str)> {
+    let header = |name: &'static str| {
+        headers
+            .iter()
+            .find(|h| h.field.equiv(name))
+            .map(|h| h.value.as_str())
+    };
+    // 浏览器发起的跨站请求总带有 Origin；Host 检查防止 DNS 重绑定
+    if header("Origin").is_some() {
+        return Err((403, "Browser requests are not accepted"));
+    }
+    let host = header("Host").unwrap_or_default();
+    if host != format!("127.0.0.1:{}", port) && host != format!("localhost:{}", port) {
+        return Err((403, "Unexpected Host header"));
+    }
+    match header("Authorization").and_then(|value| value.strip_prefix("Bearer ")) {
+        Some(given) if tokens_match(given.trim(), token) => Ok(()),
+        _ => Err((401, "Missing or invalid token")),
+    }
+}
+
+// 常量时间比较，避免通过响应时间猜测令牌
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn read_body<T: serde::de::DeserializeOwned>(request: &mut Request) -> Result<T> {
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_end(&mut body)
+        .context("Failed to read the request body")?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        bail!("Request body is too large");
+    }
+    serde_json::from_slice(&body).context("Invalid request body")
+}
+
+// API 的工作目录没有意义，只接受绝对路径
+fn api_path(path: &str) -> Result<PathBuf> {
+    let path = native_path(path)?;
+    if !path.is_absolute() {
+        bail!("Paths must be absolute: {:?}", path);
+    }
+    Ok(path)
+}
+
+// 与托盘快速签名相同：只使用已解锁会话的密钥
+fn api_sign(app_handle: &tauri::AppHandle, request: SignRequest) -> Result<Value> {
+    if verification_only() {
+        bail!("Signing is disabled in this installation");
+    }
+    check_timestamping(false)?;
+    let (algorithm, private_key_der) =
+        session_key(&app_handle.state::<KeySessions>(), request.key_id)?;
+    let document_path = api_path(&request.document_path)?;
+    check_signable_input(app_handle, &document_path)?;
+    let document_bytes = read_file(&document_path)
+        .with_context(|| format!("Failed to read document file: {:?}", document_path))?;
+    let signature_bytes = sign_bytes(
+        &algorithm,
+        &private_key_der,
+        &document_bytes,
+        DigestScheme::Standard,
+    )?;
+    let output_path = match &request.output_path {
+        Some(path) => resolve_output_path(app_handle, &api_path(path)?)?,
+        None => {
+            let output_dir = document_path.parent().unwrap_or(Path::new("."));
+            signature_output_path(app_handle, output_dir, &document_path, &algorithm)?
+        }
+    };
+    fs::write(&output_path, &signature_bytes)
+        .with_context(|| format!("Failed to write signature file: {:?}", output_path))?;
+    log::info!(
+        "Local API signed {:?} with key {}, signature saved to {:?}",
+        document_path,
+        request.key_id,
+        output_path
+    );
+    audit::record(
+        app_handle,
+        "localApiSign",
+        json!({
+            "keyId": request.key_id,
+            "documentPath": path_string(&document_path),
+            "outputPath": path_string(&output_path),
+        }),
+    );
+    Ok(json!({ "outputPath": path_string(&output_path), "keyId": request.key_id }))
+}
+
+fn api_verify(app_handle: &tauri::AppHandle, request: VerifyRequest) -> Result<Value> {
+    let document_path = api_path(&request.document_path)?;
+    let signature_path = api_path(&request.signature_path)?;
+    let document_path = path_string(&document_path);
+    let signature_path = path_string(&signature_path);
+    let result = match request.key_id {
+        Some(key_id) => verify_signature(
+            app_handle.clone(),
+            document_path,
+            signature_path,
+            Some(key_id),
+            None,
+            None,
+        )
+        .map(serde_json::to_value),
+        None => verify_signatures(
+            app_handle.clone(),
+            document_path,
+            vec![signature_path],
+            None,
+            None,
+        )
+        .map(serde_json::to_value),
+    };
+    Ok(result.map_err(|e| anyhow!(e))??)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_token_host_and_origin() {
+        let header = |name: &str, value: &str| {
+            Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+        };
+        let token = "a".repeat(64);
+        let host = header("Host", "127.0.0.1:47615");
+        let auth = header("Authorization", &format!("Bearer {}", token));
+        assert!(check_headers(&[host.clone(), auth.clone()], &token, 47615).is_ok());
+        assert!(check_headers(
+            &[header("Host", "localhost:47615"), auth.clone()],
+            &token,
+            47615
+        )
+        .is_ok());
+
+        let wrong = header("Authorization", &format!("Bearer {}", "b".repeat(64)));
+        assert_eq!(
+            check_headers(&[host.clone(), wrong], &token, 47615)
+                .unwrap_err()
+                .0,
+            401
+        );
+        assert_eq!(
+            check_headers(std::slice::from_ref(&host), &token, 47615)
+                .unwrap_err()
+                .0,
+            401
+        );
+        // 浏览器请求和 DNS 重绑定（其他主机名）被拒绝
+        let origin = header("Origin", "https://example.com");
+        assert_eq!(
+            check_headers(&[host, auth.clone(), origin], &token, 47615)
+                .unwrap_err()
+                .0,
+            403
+        );
+        assert_eq!(
+            check_headers(&[header("Host", "evil.example:47615"), auth], &token, 47615)
+                .unwrap_err()
+                .0,
+            403
+        );
+    }
+}
@@ -0,0 +1,260 @@
+// src-tauri/src/logs.rs
+// Application logging: a size-rotated log file in the OS log directory (release and debug
+// builds), plus commands to read recent entries and export the logs for support.
+//
+// Every message passes through `redact` before it is written, so key material, tokens or
+// passwords that end up in a log call do not reach the file.
+use crate::crypto_types::LogEntry;
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::resolve_output_path;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tauri::Manager;
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+
+const LOG_FILE_NAME: &str = "sigust";
+/// A log file is rotated at startup once it grows past this size.
+const MAX_LOG_FILE_SIZE: u128 = 2 * 1024 * 1024;
+/// Rotated log files kept in addition to the current one.
+const MAX_ROTATED_LOG_FILES: usize = 5;
+const DEFAULT_RECENT_LOGS: usize = 200;
+const MAX_RECENT_LOGS: usize = 5_000;
+/// Runs of token characters at least this long are treated as secrets.
+const REDACT_MIN_LEN: usize = 40;
+const REDACTED: &str = "[redacted]";
+
+/// Builds the log plugin: file target always, stdout too in debug builds.
+pub fn log_plugin() -> tauri::plugin::TauriPlugin<tauri::Wry> {
+    let mut targets = vec![Target::new(TargetKind::LogDir {
+        file_name: Some(LOG_FILE_NAME.to_string()),
+    })];
+    if cfg!(debug_assertions) {
+        targets.push(Target::new(TargetKind::Stdout));
+    }
+    tauri_plugin_log::Builder::new()
+        .clear_targets()
+        .targets(targets)
+        .level(if cfg!(debug_assertions) {
+            log::LevelFilter::Debug
+        } else {
+            log::LevelFilter::Info
+        })
+        .max_file_size(MAX_LOG_FILE_SIZE)
+        .rotation_strategy(RotationStrategy::KeepAll)
+        .format(|out, message, record| {
+            out.finish(format_args!(
+                "[{}][{}][{}] {}",
+                Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+                record.level(),
+                record.target(),
+                redact(&message.to_string())
+            ))
+        })
+        .build()
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_recent_logs(
+    app_handle: tauri::AppHandle,
+    level: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    log::debug!("Reading recent logs (level {:?}, limit {:?})", level, limit);
+    _get_recent_logs(&app_handle, level.as_deref(), limit).map_err(|e| {
+        log::error!("Failed to read logs: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _get_recent_logs(
+    app_handle: &tauri::AppHandle,
+    level: Option<&str>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>> {
+    let min_level = match level {
+        Some(level) => log::Level::from_str(level)
+            .map_err(|_| anyhow::anyhow!("Unknown log level: {}", level))?,
+        None => log::Level::Trace,
+    };
+    let limit = limit.unwrap_or(DEFAULT_RECENT_LOGS).min(MAX_RECENT_LOGS);
+
+    let mut entries = Vec::new();
+    for path in log_files(app_handle)? {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read log file {:?}", path))?;
+        parse_log_lines(&content, &mut entries);
+    }
+    entries.retain(|entry| {
+        log::Level::from_str(&entry.level).is_ok_and(|entry_level| entry_level <= min_level)
+    });
+    Ok(entries.split_off(entries.len().saturating_sub(limit)))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_logs(app_handle: tauri::AppHandle, output_path: String) -> Result<String, String> {
+    log::info!("Exporting logs to '{}'", output_path);
+    _export_logs(&app_handle, &output_path)
+        .map(|path| path_string(&path))
+        .map_err(|e| {
+            log::error!("Failed to export logs: {:?}", e);
+            e.to_string()
+        })
+}
+
+fn _export_logs(app_handle: &tauri::AppHandle, output_path: &str) -> Result<PathBuf> {
+    let files = log_files(app_handle)?;
+    if files.is_empty() {
+        bail!("No log files found");
+    }
+    let mut content = String::new();
+    for path in files {
+        content.push_str(
+            &fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read log file {:?}", path))?,
+        );
+    }
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write log export: {:?}", output_path))?;
+    Ok(output_path)
+}
+
+// --- Helper Functions ---
+
+/// Deletes the oldest rotated log files beyond `MAX_ROTATED_LOG_FILES`.
+pub fn prune_rotated_logs(app_handle: &tauri::AppHandle) -> Result<()> {
+    let mut rotated = log_files(app_handle)?;
+    // 最后一个是当前日志文件
+    rotated.pop();
+    let excess = rotated.len().saturating_sub(MAX_ROTATED_LOG_FILES);
+    for path in rotated.iter().take(excess) {
+        fs::remove_file(path).with_context(|| format!("Failed to remove old log {:?}", path))?;
+    }
+    Ok(())
+}
+
+// 日志文件按时间从旧到新排列（轮转文件名带时间戳，当前文件在最后）
+fn log_files(app_handle: &tauri::AppHandle) -> Result<Vec<PathBuf>> {
+    let dir = app_handle
+        .path()
+        .app_log_dir()
+        .context("Failed to get log directory")?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let current = dir.join(format!("{}.log", LOG_FILE_NAME));
+    let rotated_prefix = format!("{}_", LOG_FILE_NAME);
+    let mut rotated: Vec<PathBuf> = fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read log directory {:?}", dir))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| is_rotated_log(path, &rotated_prefix))
+        .collect();
+    rotated.sort();
+    if current.exists() {
+        rotated.push(current);
+    }
+    Ok(rotated)
+}
+
+fn is_rotated_log(path: &Path, prefix: &str) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy())
+        .is_some_and(|name| name.starts_with(prefix) && name.ends_with(".log"))
+}
+
+// 解析 `[时间][级别][模块] 消息` 格式的日志行；不以 `[` 开头的行属于上一条消息
+fn parse_log_lines(content: &str, entries: &mut Vec<LogEntry>) {
+    for line in content.lines() {
+        let parsed = line.strip_prefix('[').and_then(|rest| {
+            let (timestamp, rest) = rest.split_once("][")?;
+            let (level, rest) = rest.split_once("][")?;
+            let (target, message) = rest.split_once("] ")?;
+            Some(LogEntry {
+                timestamp: timestamp.to_string(),
+                level: level.to_string(),
+                target: target.to_string(),
+                message: message.to_string(),
+            })
+        });
+        match (parsed, entries.last_mut()) {
+            (Some(entry), _) => entries.push(entry),
+            (None, Some(previous)) => {
+                previous.message.push('\n');
+                previous.message.push_str(line);
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Masks likely secrets: long base64/hex runs (keys, tokens, signatures) and values
+/// written as `password: ...` or `password=...`. 64-character hex runs are kept; they are
+/// digests and fingerprints.
+pub(crate) fn redact(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut run = String::new();
+    // after_password: 刚写出 "password"；armed: 其后紧跟 `:` 或 `=`，下一个词需要屏蔽
+    let (mut after_password, mut armed) = (false, false);
+    for c in message.chars().chain(std::iter::once('\n')) {
+        if c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '_' | '-') {
+            run.push(c);
+            continue;
+        }
+        if !run.is_empty() {
+            if armed {
+                out.push_str(REDACTED);
+            } else if run.starts_with('/') {
+                // 绝对路径：只屏蔽其中过长的片段
+                let segments: Vec<&str> = run.split('/').map(redact_token).collect();
+                out.push_str(&segments.join("/"));
+            } else {
+                out.push_str(redact_token(&run));
+            }
+            after_password = !armed && run.to_ascii_lowercase().ends_with("password");
+            armed = false;
+            run.clear();
+        }
+        if after_password {
+            armed = c == ':' || c == '=';
+            after_password = false;
+        }
+        out.push(c);
+    }
+    out.pop();
+    out
+}
+
+fn redact_token(token: &str) -> &str {
+    let is_digest = token.len() == 64 && token.chars().all(|c| c.is_ascii_hexdigit());
+    if token.len() >= REDACT_MIN_LEN && !is_digest {
+        REDACTED
+    } else {
+        token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_secrets_but_keeps_digests() {
+        let digest = "a".repeat(64);
+        let blob = "MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEA1b2c3d4e5f6g7h8";
+        assert_eq!(
+            redact(&format!("digest {}", digest)),
+            format!("digest {}", digest)
+        );
+        assert_eq!(redact(&format!("key {}", blob)), "key [redacted]");
+        assert_eq!(redact("password: hunter2 ok"), "password: [redacted] ok");
+        assert_eq!(redact("password=hunter2"), "password=[redacted]");
+        assert_eq!(redact("Wrong password for key"), "Wrong password for key");
+        let path = "/home/user/Documents/Contracts/2024/signed-agreements/final.pdf";
+        assert_eq!(redact(path), path);
+    }
+}
@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // Started by a browser as the native messaging host of the sigust extension
+    if let Some(extension) = app_lib::native_messaging_caller(std::env::args()) {
+        std::process::exit(app_lib::run_native_messaging_host(&extension));
+    }
     app_lib::run();
 }
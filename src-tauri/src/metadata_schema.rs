@@ -0,0 +1,190 @@
+// src-tauri/src/metadata_schema.rs
+// Custom metadata for signature envelopes (invoice number, case ID, ...). Callers pass
+// string key–value pairs with the signing options; they are signed together with the other
+// envelope attributes and reported back by verification. The schema in the settings says
+// which fields exist, which are required and what their values look like.
+use crate::crypto_types::{MetadataField, MetadataFieldKind, MetadataSchema};
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashSet};
+
+const MAX_METADATA_ENTRIES: usize = 32;
+const MAX_KEY_LEN: usize = 64;
+const MAX_VALUE_LEN: usize = 1024;
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_metadata_schema(
+    app_handle: tauri::AppHandle,
+    schema: MetadataSchema,
+) -> Result<MetadataSchema, String> {
+    log::info!("Updating metadata schema ({} fields)", schema.fields.len());
+    check_schema(&schema).map_err(|e| e.to_string())?;
+    update_settings(&app_handle, |settings| settings.metadata_schema = schema)
+        .map(|settings| settings.metadata_schema)
+        .map_err(|e| {
+            log::error!("Failed to update metadata schema: {:?}", e);
+            e.to_string()
+        })
+}
+
+// --- Helper Functions ---
+
+/// Checks metadata about to be signed against the configured schema.
+pub(crate) fn validate_metadata(
+    app_handle: &tauri::AppHandle,
+    metadata: &BTreeMap<String, String>,
+) -> Result<()> {
+    let schema = read_settings(&get_settings_path(app_handle)?)?.metadata_schema;
+    check_metadata(&schema, metadata)
+}
+
+// 校验字段名是否合法、是否重复
+pub(crate) fn check_schema(schema: &MetadataSchema) -> Result<()> {
+    let mut names = HashSet::new();
+    for field in &schema.fields {
+        check_key(&field.name)?;
+        if !names.insert(field.name.as_str()) {
+            bail!("Metadata field '{}' is defined twice", field.name);
+        }
+        if let Some(value) = field
+            .allowed_values
+            .iter()
+            .find(|value| check_value(field, value).is_err())
+        {
+            bail!(
+                "Allowed value '{}' does not fit metadata field '{}'",
+                value,
+                field.name
+            );
+        }
+    }
+    Ok(())
+}
+
+fn check_metadata(schema: &MetadataSchema, metadata: &BTreeMap<String, String>) -> Result<()> {
+    if metadata.len() > MAX_METADATA_ENTRIES {
+        bail!(
+            "At most {} metadata fields are allowed",
+            MAX_METADATA_ENTRIES
+        );
+    }
+    for (key, value) in metadata {
+        check_key(key)?;
+        if value.len() > MAX_VALUE_LEN {
+            bail!(
+                "Metadata value of '{}' is longer than {} characters",
+                key,
+                MAX_VALUE_LEN
+            );
+        }
+        match schema.fields.iter().find(|f| f.name == *key) {
+            Some(field) => check_value(field, value)?,
+            None if schema.allow_unknown_fields => {}
+            None => bail!("Unknown metadata field: {}", key),
+        }
+    }
+    if let Some(missing) = schema
+        .fields
+        .iter()
+        .find(|f| f.required && !metadata.contains_key(&f.name))
+    {
+        bail!(
+            "Required metadata field is missing: {}",
+            missing.label.as_deref().unwrap_or(&missing.name)
+        );
+    }
+    Ok(())
+}
+
+// 字段名：字母、数字、下划线、连字符和点
+fn check_key(key: &str) -> Result<()> {
+    if key.is_empty()
+        || key.len() > MAX_KEY_LEN
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        bail!(
+            "Invalid metadata field name '{}': use up to {} letters, digits, '_', '-' or '.'",
+            key,
+            MAX_KEY_LEN
+        );
+    }
+    Ok(())
+}
+
+fn check_value(field: &MetadataField, value: &str) -> Result<()> {
+    let name = field.label.as_deref().unwrap_or(&field.name);
+    if value.trim().is_empty() {
+        bail!("Metadata field '{}' is empty", name);
+    }
+    if field
+        .max_length
+        .is_some_and(|max| value.chars().count() > max)
+    {
+        bail!("Metadata field '{}' is too long", name);
+    }
+    let well_formed = match field.kind {
+        MetadataFieldKind::Text => true,
+        MetadataFieldKind::Integer => value.parse::<i64>().is_ok(),
+        MetadataFieldKind::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+    };
+    if !well_formed {
+        bail!("Metadata field '{}' is not a valid {:?}", name, field.kind);
+    }
+    if !field.allowed_values.is_empty() && !field.allowed_values.iter().any(|v| v == value) {
+        bail!("Metadata field '{}' has a value that is not allowed", name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_metadata_against_schema() {
+        let schema = MetadataSchema {
+            fields: vec![
+                MetadataField {
+                    name: "invoice".to_string(),
+                    label: None,
+                    required: true,
+                    kind: MetadataFieldKind::Integer,
+                    max_length: None,
+                    allowed_values: Vec::new(),
+                },
+                MetadataField {
+                    name: "due".to_string(),
+                    label: None,
+                    required: false,
+                    kind: MetadataFieldKind::Date,
+                    max_length: None,
+                    allowed_values: Vec::new(),
+                },
+            ],
+            allow_unknown_fields: false,
+        };
+        let metadata = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        assert!(check_metadata(&schema, &metadata(&[("invoice", "42")])).is_ok());
+        assert!(check_metadata(
+            &schema,
+            &metadata(&[("invoice", "42"), ("due", "2026-02-30")])
+        )
+        .is_err());
+        assert!(check_metadata(&schema, &metadata(&[("invoice", "x")])).is_err());
+        assert!(check_metadata(&schema, &metadata(&[("due", "2026-03-01")])).is_err());
+        assert!(check_metadata(&schema, &metadata(&[("invoice", "1"), ("case", "A")])).is_err());
+        assert!(
+            check_metadata(&MetadataSchema::default(), &metadata(&[("case id", "A")])).is_err()
+        );
+    }
+}
@@ -0,0 +1,438 @@
+// src-tauri/src/native_messaging.rs
+// Native messaging host for the sigust browser extension. The browser starts this binary
+// with the extension's origin as argument and talks to it over stdio: each message is a
+// 4-byte length in native byte order followed by that many bytes of UTF-8 JSON.
+//
+//   {"id": .., "type": "ping"}
+//   {"id": .., "type": "listKeys"}
+//   {"id": .., "type": "signDownload", "path": "/abs/file", "keyId"?: ..}
+//   {"id": .., "type": "signPayload", "payload": "<base64>", "description"?: .., "keyId"?: ..}
+//
+// Answers are {"id", "ok": true, "result"} or {"id", "ok": false, "error"}. The host process
+// holds no keys: it relays requests to the running app through the loopback API (see
+// local_api.rs), where each signing request is shown in an approval prompt and signed with
+// a key that has an unlocked session. Downloads get a detached signature next to the file;
+// for payloads (e.g. web form data) only the signature is returned.
+use crate::audit;
+use crate::consent::ask_user;
+use crate::crypto_types::{DigestScheme, NativeMessagingBrowser};
+use crate::key_sessions::{active_sessions, session_key, KeySessions};
+use crate::local_api::DISCOVERY_FILE;
+use crate::native_paths::{native_path, read_file};
+use crate::output_paths::signature_output_path;
+use crate::policy::{check_timestamping, verification_only};
+use crate::signable_inputs::check_signable_input;
+use crate::signing::sign_bytes;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::Manager;
+use uuid::Uuid;
+
+/// Name the extension connects to (`runtime.connectNative`).
+pub const NATIVE_HOST_NAME: &str = "com.sigust.native_host";
+/// Browsers accept at most 1 MiB per message from a host; requests are held to the same.
+const MAX_MESSAGE_BYTES: u32 = 1024 * 1024;
+
+/// A signing request relayed by the host (`POST /v1/browser/sign`).
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct BrowserSignRequest {
+    /// Extension origin or ID, as passed by the browser.
+    extension: String,
+    /// Defaults to the only key with an unlocked session.
+    key_id: Option<Uuid>,
+    /// A downloaded file; signed to a detached signature next to it.
+    document_path: Option<String>,
+    /// Base64 payload such as form data; the signature is only returned.
+    payload: Option<String>,
+    /// What the payload is, shown in the prompt.
+    description: Option<String>,
+}
+
+// --- Tauri Commands ---
+
+/// Installs the host manifest that lets `extension_id` start sigust from `browser`, and
+/// returns its path. The extension still needs the local API to be enabled.
+#[tauri::command(rename_all = "camelCase")]
+pub fn install_native_messaging_host(
+    app_handle: tauri::AppHandle,
+    browser: NativeMessagingBrowser,
+    extension_id: String,
+) -> Result<String, String> {
+    log::info!(
+        "Installing native messaging host for {:?}, extension {}",
+        browser,
+        extension_id
+    );
+    _install_native_messaging_host(browser, &extension_id)
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(|e| {
+            log::error!("Failed to install native messaging host: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|path| {
+            audit::record(
+                &app_handle,
+                "installNativeMessagingHost",
+                json!({ "browser": browser, "extensionId": extension_id, "manifestPath": path }),
+            )
+        })
+}
+
+fn _install_native_messaging_host(
+    browser: NativeMessagingBrowser,
+    extension_id: &str,
+) -> Result<PathBuf> {
+    if extension_id.is_empty()
+        || !extension_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "@._-{}".contains(c))
+    {
+        bail!("Invalid extension ID: {}", extension_id);
+    }
+    let exe = std::env::current_exe().context("Failed to locate the sigust executable")?;
+    let mut manifest = json!({
+        "name": NATIVE_HOST_NAME,
+        "description": "sigust signing",
+        "path": exe,
+        "type": "stdio",
+    });
+    if browser == NativeMessagingBrowser::Firefox {
+        manifest["allowed_extensions"] = json!([extension_id]);
+    } else {
+        manifest["allowed_origins"] = json!([format!("chrome-extension://{}/", extension_id)]);
+    }
+    let dir = manifest_dir(browser)?;
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let path = dir.join(format!("{}.json", NATIVE_HOST_NAME));
+    fs::write(&path, serde_json::to_vec_pretty(&manifest)?)
+        .with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(path)
+}
+
+// --- Helper Functions ---
+
+/// The extension that started this process as its native messaging host, if any.
+/// Chromium browsers pass the extension origin; Firefox passes the manifest path and the
+/// extension ID.
+pub fn native_messaging_caller(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let args: Vec<String> = args.into_iter().skip(1).collect();
+    match args.as_slice() {
+        [origin, ..] if origin.starts_with("chrome-extension://") => Some(origin.clone()),
+        [manifest, extension_id, ..]
+            if Path::new(manifest).file_name()
+                == Some(format!("{}.json", NATIVE_HOST_NAME).as_ref()) =>
+        {
+            Some(extension_id.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Runs the host until the browser closes stdin; returns the process exit code.
+pub fn run_native_messaging_host(extension: &str) -> i32 {
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    loop {
+        let message = match read_message(&mut stdin) {
+            Ok(Some(message)) => message,
+            Ok(None) => return 0,
+            Err(e) => {
+                // stderr 会出现在浏览器的日志中
+                eprintln!("sigust native host: {:#}", e);
+                return 1;
+            }
+        };
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        let reply = match handle_message(extension, message) {
+            Ok(result) => json!({ "id": id, "ok": true, "result": result }),
+            Err(e) => json!({ "id": id, "ok": false, "error": format!("{:#}", e) }),
+        };
+        if let Err(e) = write_message(&mut stdout, &reply) {
+            eprintln!("sigust native host: {:#}", e);
+            return 1;
+        }
+    }
+}
+
+// 长度前缀（本机字节序）+ JSON；stdin 关闭时返回 None
+fn read_message(reader: &mut impl Read) -> Result<Option<Value>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(anyhow!(e).context("Failed to read message length")),
+    }
+    let length = u32::from_ne_bytes(length);
+    if length > MAX_MESSAGE_BYTES {
+        bail!("Message of {} bytes is too large", length);
+    }
+    let mut body = vec![0u8; length as usize];
+    reader
+        .read_exact(&mut body)
+        .context("Failed to read message")?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .context("Message is not valid JSON")
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    let length = u32::try_from(body.len())
+        .ok()
+        .filter(|length| *length <= MAX_MESSAGE_BYTES)
+        .ok_or_else(|| anyhow!("Reply of {} bytes is too large", body.len()))?;
+    writer.write_all(&length.to_ne_bytes())?;
+    writer.write_all(&body)?;
+    writer.flush().context("Failed to write reply")
+}
+
+fn handle_message(extension: &str, message: Value) -> Result<Value> {
+    let text = |name: &str| {
+        message
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let key_id = message.get("keyId").cloned().unwrap_or(Value::Null);
+    match text("type").as_deref() {
+        Some("ping") => Ok(json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "appRunning": discovery_file()?.exists(),
+        })),
+        Some("listKeys") => call_app("GET", "/v1/keys", None),
+        Some("signDownload") => {
+            let path = text("path").ok_or_else(|| anyhow!("signDownload needs a path"))?;
+            let request = json!({ "extension": extension, "keyId": key_id, "documentPath": path });
+            call_app("POST", "/v1/browser/sign", Some(request))
+        }
+        Some("signPayload") => {
+            let payload = text("payload").ok_or_else(|| anyhow!("signPayload needs a payload"))?;
+            let request = json!({
+                "extension": extension,
+                "keyId": key_id,
+                "payload": payload,
+                "description": text("description"),
+            });
+            call_app("POST", "/v1/browser/sign", Some(request))
+        }
+        Some(other) => bail!("Unknown message type '{}'", other),
+        None => bail!("Message has no type"),
+    }
+}
+
+// 通过本机 HTTP 接口转发给正在运行的应用（签名请求会等待用户批准，不设超时）
+fn call_app(method: &str, path: &str, body: Option<Value>) -> Result<Value> {
+    let discovery = fs::read(discovery_file()?)
+        .map_err(|_| anyhow!("sigust is not running or its local API is turned off"))?;
+    let discovery: Value =
+        serde_json::from_slice(&discovery).context("Invalid local API discovery file")?;
+    let (Some(url), Some(token)) = (discovery["url"].as_str(), discovery["token"].as_str()) else {
+        bail!("Invalid local API discovery file");
+    };
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let url = format!("{}{}", url, path);
+    let authorization = format!("Bearer {}", token);
+    let mut response = match body {
+        Some(body) => agent
+            .post(&url)
+            .header("Authorization", &authorization)
+            .header("Content-Type", "application/json")
+            .send(&serde_json::to_vec(&body)?[..]),
+        None => agent
+            .get(&url)
+            .header("Authorization", &authorization)
+            .call(),
+    }
+    .with_context(|| format!("{} {} failed", method, path))?;
+    let status = response.status();
+    let body: Value = serde_json::from_slice(
+        &response
+            .body_mut()
+            .read_to_vec()
+            .context("Failed to read the app's reply")?,
+    )
+    .context("Invalid reply from the app")?;
+    if !status.is_success() {
+        bail!(
+            "{}",
+            body["error"]
+                .as_str()
+                .unwrap_or("Request failed in the app")
+        );
+    }
+    Ok(body)
+}
+
+// 宿主进程没有 AppHandle，按 Tauri 的规则（数据目录/标识符）找到发现文件；
+// 标识符取自程序内置的配置，与应用中 `app_handle.config().identifier` 相同
+fn discovery_file() -> Result<PathBuf> {
+    static IDENTIFIER: OnceLock<String> = OnceLock::new();
+    let identifier = IDENTIFIER.get_or_init(|| crate::context().config().identifier.clone());
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("No data directory"))?;
+    Ok(data_dir.join(identifier).join(DISCOVERY_FILE))
+}
+
+// 各浏览器读取用户级宿主清单的目录；Windows 需要注册表项，由安装程序完成
+fn manifest_dir(browser: NativeMessagingBrowser) -> Result<PathBuf> {
+    use NativeMessagingBrowser::*;
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("No home directory"))?;
+    let relative = if cfg!(target_os = "linux") {
+        match browser {
+            Chrome => ".config/google-chrome/NativeMessagingHosts",
+            Chromium => ".config/chromium/NativeMessagingHosts",
+            Edge => ".config/microsoft-edge/NativeMessagingHosts",
+            Firefox => ".mozilla/native-messaging-hosts",
+        }
+    } else if cfg!(target_os = "macos") {
+        match browser {
+            Chrome => "Library/Application Support/Google/Chrome/NativeMessagingHosts",
+            Chromium => "Library/Application Support/Chromium/NativeMessagingHosts",
+            Edge => "Library/Application Support/Microsoft Edge/NativeMessagingHosts",
+            Firefox => "Library/Application Support/Mozilla/NativeMessagingHosts",
+        }
+    } else {
+        bail!("The native messaging host is registered by the installer on this platform");
+    };
+    Ok(home.join(relative))
+}
+
+/// Handles a relayed signing request: asks the user, then signs with a session key.
+pub(crate) fn browser_sign(
+    app_handle: &tauri::AppHandle,
+    request: BrowserSignRequest,
+) -> Result<Value> {
+    if verification_only() {
+        bail!("Signing is disabled in this installation");
+    }
+    check_timestamping(false)?;
+    let sessions = active_sessions(&app_handle.state::<KeySessions>())?;
+    let session = match request.key_id {
+        Some(key_id) => sessions.iter().find(|s| s.key_id == key_id),
+        None if sessions.len() == 1 => sessions.first(),
+        None if sessions.is_empty() => None,
+        None => bail!("Several keys are unlocked; the request must name one"),
+    }
+    .ok_or_else(|| anyhow!("Unlock the signing key in sigust first"))?;
+
+    let (data, what, document_path) = match (&request.document_path, &request.payload) {
+        (Some(path), None) => {
+            let path = native_path(path)?;
+            if !path.is_absolute() {
+                bail!("Paths must be absolute: {:?}", path);
+            }
+            check_signable_input(app_handle, &path)?;
+            let data = read_file(&path)
+                .with_context(|| format!("Failed to read document file: {:?}", path))?;
+            (data, format!("the file {:?}", path), Some(path))
+        }
+        (None, Some(payload)) => {
+            let data = BASE64
+                .decode(payload)
+                .context("The payload is not valid base64")?;
+            let what = match &request.description {
+                Some(description) => format!("{} ({} bytes)", description, data.len()),
+                None => format!("{} bytes of page data", data.len()),
+            };
+            (data, what, None)
+        }
+        _ => bail!("A request signs either a file or a payload"),
+    };
+
+    // 每个请求都需要用户在应用内批准
+    if !ask_user(
+        app_handle,
+        "Browser signing request",
+        &format!(
+            "The browser extension {} asks to sign {} with the key \"{}\". Allow?",
+            request.extension, what, session.name
+        ),
+    ) {
+        bail!("The request was declined");
+    }
+
+    let (algorithm, private_key_der) =
+        session_key(&app_handle.state::<KeySessions>(), session.key_id)?;
+    let signature = sign_bytes(&algorithm, &private_key_der, &data, DigestScheme::Standard)?;
+    let output_path = match &document_path {
+        Some(path) => {
+            let output_dir = path.parent().unwrap_or(Path::new("."));
+            let output_path = signature_output_path(app_handle, output_dir, path, &algorithm)?;
+            fs::write(&output_path, &signature)
+                .with_context(|| format!("Failed to write signature file: {:?}", output_path))?;
+            Some(output_path)
+        }
+        None => None,
+    };
+    log::info!(
+        "Signed a browser request from {} with key {}",
+        request.extension,
+        session.key_id
+    );
+    audit::record(
+        app_handle,
+        "browserSign",
+        json!({
+            "extension": request.extension,
+            "keyId": session.key_id,
+            "documentPath": document_path,
+            "outputPath": output_path,
+            "sha256": hex::encode(Sha256::digest(&data)),
+        }),
+    );
+    Ok(json!({
+        "keyId": session.key_id,
+        "algorithm": algorithm,
+        "signature": BASE64.encode(&signature),
+        "outputPath": output_path,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_messages_and_detects_callers() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &json!({ "type": "ping" })).unwrap();
+        assert_eq!(buffer[..4], (buffer.len() as u32 - 4).to_ne_bytes());
+        let mut reader = &buffer[..];
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Some(json!({ "type": "ping" }))
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+        let oversized = (MAX_MESSAGE_BYTES + 1).to_ne_bytes();
+        assert!(read_message(&mut &oversized[..]).is_err());
+
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            native_messaging_caller(args(&["sigust", "chrome-extension://abc/"])),
+            Some("chrome-extension://abc/".to_string())
+        );
+        let manifest = format!(
+            "/home/u/.mozilla/native-messaging-hosts/{}.json",
+            NATIVE_HOST_NAME
+        );
+        assert_eq!(
+            native_messaging_caller(args(&["sigust", &manifest, "sign@sigust"])),
+            Some("sign@sigust".to_string())
+        );
+        assert_eq!(native_messaging_caller(args(&["sigust"])), None);
+        assert_eq!(
+            native_messaging_caller(args(&["sigust", "sigust://verify?sha256=00"])),
+            None
+        );
+    }
+}
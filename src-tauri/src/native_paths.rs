@@ -0,0 +1,306 @@
+// src-tauri/src/native_paths.rs
+// Conversion between the path strings exchanged with the webview and native paths.
+//
+// JavaScript strings cannot hold every file name: on Unix a name is any byte sequence and
+// on Windows any sequence of UTF-16 code units, paired or not. Such paths travel as
+// `sigust-path:` followed by the URL-safe base64 of the native bytes (UTF-16LE on
+// Windows); `path_string` produces that form only when a path is not valid Unicode, and
+// `native_path` accepts both. On Windows, absolute paths get the `\\?\` prefix (`\\?\UNC\`
+// for network shares) so paths over 260 characters work; the prefix is removed again
+// before a path is shown. Reads from network shares are retried when the share is briefly
+// unavailable.
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine as _};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Prefix of a path carried as base64 of its native bytes.
+pub(crate) const ENCODED_PATH_PREFIX: &str = "sigust-path:";
+/// Attempts made for an I/O operation on a network share.
+const NETWORK_ATTEMPTS: u32 = 4;
+/// Wait before the first retry; doubled for each following one.
+const NETWORK_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+// --- Helper Functions ---
+
+/// Native path for a path string received from the webview (plain or encoded).
+pub(crate) fn native_path(path: &str) -> Result<PathBuf> {
+    let path = match path.strip_prefix(ENCODED_PATH_PREFIX) {
+        Some(encoded) => {
+            decode_path(encoded).with_context(|| format!("Malformed encoded path: {}", path))?
+        }
+        None => PathBuf::from(path),
+    };
+    Ok(extended_length_path(&path))
+}
+
+/// String for a path sent to the webview: the plain path without the `\\?\` prefix, or
+/// the encoded form when the path is not valid Unicode.
+pub(crate) fn path_string(path: &Path) -> String {
+    let path = display_path(path);
+    match path.to_str() {
+        Some(path) => path.to_string(),
+        None => format!(
+            "{}{}",
+            ENCODED_PATH_PREFIX,
+            BASE64_URL.encode(native_bytes(&path))
+        ),
+    }
+}
+
+/// `path` in the `\\?\` form on Windows, which lifts the 260-character limit and accepts
+/// UNC shares; unchanged elsewhere, and for relative paths.
+pub(crate) fn extended_length_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) || !path.is_absolute() {
+        return path.to_path_buf();
+    }
+    let Some(text) = path.to_str() else {
+        return path.to_path_buf();
+    };
+    if text.starts_with(r"\\?\") || text.starts_with(r"\\.\") {
+        return path.to_path_buf();
+    }
+    // `\\?\` 路径不再做规范化，需要先统一分隔符并去掉 `.` 与 `..`
+    let normalized = normalize_components(&text.replace('/', "\\"));
+    match normalized.strip_prefix(r"\\") {
+        Some(share) => PathBuf::from(format!(r"\\?\UNC\{}", share)),
+        None => PathBuf::from(format!(r"\\?\{}", normalized)),
+    }
+}
+
+/// Whether `path` is on a network share: a UNC path on Windows, or an NFS, SMB or
+/// similar mount on Linux.
+pub(crate) fn is_network_path(path: &Path) -> bool {
+    let text = path.to_string_lossy();
+    if cfg!(windows) {
+        return text.starts_with(r"\\?\UNC\")
+            || (text.starts_with(r"\\")
+                && !text.starts_with(r"\\?\")
+                && !text.starts_with(r"\\.\"));
+    }
+    network_mount_points()
+        .iter()
+        .any(|mount_point| path.starts_with(mount_point))
+}
+
+/// Runs `operation`, retrying a few times with a growing delay when `path` is on a network
+/// share and the error looks transient (time-outs, dropped connections).
+pub(crate) fn with_network_retry<T>(
+    path: &Path,
+    mut operation: impl FnMut() -> io::Result<T>,
+) -> io::Result<T> {
+    let network = is_network_path(path);
+    let mut delay = NETWORK_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Err(e) if network && attempt < NETWORK_ATTEMPTS && is_transient(&e) => {
+                log::warn!(
+                    "Retrying access to {:?} on a network share (attempt {}): {}",
+                    path,
+                    attempt,
+                    e
+                );
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// `fs::read` with `with_network_retry`.
+pub(crate) fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+    with_network_retry(path, || fs::read(path))
+}
+
+/// `File::open` with `with_network_retry`.
+pub(crate) fn open_file(path: &Path) -> io::Result<File> {
+    with_network_retry(path, || File::open(path))
+}
+
+// 去掉 `\\?\` 前缀，`\\?\UNC\server` 还原为 `\\server`
+fn display_path(path: &Path) -> PathBuf {
+    let Some(text) = path.to_str() else {
+        return path.to_path_buf();
+    };
+    if let Some(share) = text.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", share))
+    } else if let Some(local) = text.strip_prefix(r"\\?\") {
+        PathBuf::from(local)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+// 处理 Windows 路径中的 `.` 与 `..`（不越过盘符或共享根）
+fn normalize_components(path: &str) -> String {
+    let (root, rest) = match path.strip_prefix(r"\\") {
+        Some(share) => {
+            // `\\server\share` 是根，不能被 `..` 去掉
+            let mut parts = share.splitn(3, '\\');
+            let server = parts.next().unwrap_or_default();
+            let name = parts.next().unwrap_or_default();
+            (
+                format!(r"\\{}\{}", server, name),
+                parts.next().unwrap_or_default(),
+            )
+        }
+        None => match path.split_once('\\') {
+            Some((drive, rest)) => (drive.to_string(), rest),
+            None => return path.to_string(),
+        },
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split('\\') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    let mut normalized = root;
+    for part in parts {
+        normalized.push('\\');
+        normalized.push_str(part);
+    }
+    if normalized.ends_with(':') {
+        normalized.push('\\');
+    }
+    normalized
+}
+
+fn is_transient(error: &io::Error) -> bool {
+    // Windows：ERROR_BAD_NETPATH、ERROR_UNEXP_NET_ERR、ERROR_NETNAME_DELETED、
+    // ERROR_SEM_TIMEOUT、ERROR_NETWORK_UNREACHABLE
+    const WINDOWS_NETWORK_ERRORS: [i32; 5] = [53, 59, 64, 121, 1231];
+    matches!(
+        error.kind(),
+        io::ErrorKind::TimedOut | io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock
+    ) || (cfg!(windows)
+        && error
+            .raw_os_error()
+            .is_some_and(|code| WINDOWS_NETWORK_ERRORS.contains(&code)))
+}
+
+// Linux 上从 /proc/self/mounts 读取网络文件系统的挂载点
+fn network_mount_points() -> Vec<PathBuf> {
+    const NETWORK_FILESYSTEMS: [&str; 8] = [
+        "nfs",
+        "nfs4",
+        "cifs",
+        "smb3",
+        "smbfs",
+        "9p",
+        "afs",
+        "fuse.sshfs",
+    ];
+    let Ok(mounts) = fs::read_to_string("/proc/self/mounts") else {
+        return Vec::new();
+    };
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?;
+            let filesystem = fields.next()?;
+            NETWORK_FILESYSTEMS
+                .contains(&filesystem)
+                // 挂载点中的空格等字符以八进制转义
+                .then(|| PathBuf::from(mount_point.replace("\\040", " ")))
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn native_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(windows)]
+fn native_bytes(path: &Path) -> Vec<u8> {
+    use std::os::windows::ffi::OsStrExt;
+    path.as_os_str()
+        .encode_wide()
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn native_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+#[cfg(unix)]
+fn decode_path(encoded: &str) -> Result<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+    let bytes = BASE64_URL.decode(encoded)?;
+    Ok(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+}
+
+#[cfg(windows)]
+fn decode_path(encoded: &str) -> Result<PathBuf> {
+    use std::os::windows::ffi::OsStringExt;
+    let bytes = BASE64_URL.decode(encoded)?;
+    if bytes.len() % 2 != 0 {
+        anyhow::bail!("UTF-16 path has an odd number of bytes");
+    }
+    let wide: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    Ok(PathBuf::from(std::ffi::OsString::from_wide(&wide)))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn decode_path(encoded: &str) -> Result<PathBuf> {
+    let bytes = BASE64_URL.decode(encoded)?;
+    String::from_utf8(bytes)
+        .map(PathBuf::from)
+        .map_err(|_| anyhow::anyhow!("Path is not valid UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_paths_and_normalizes_windows_forms() {
+        let plain = native_path("/tmp/report.pdf").unwrap();
+        assert_eq!(path_string(&plain), "/tmp/report.pdf");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let raw = Path::new(std::ffi::OsStr::from_bytes(b"/tmp/caf\xe9.pdf"));
+            let encoded = path_string(raw);
+            assert!(encoded.starts_with(ENCODED_PATH_PREFIX));
+            assert_eq!(native_path(&encoded).unwrap(), raw);
+        }
+        assert!(native_path("sigust-path:not base64!").is_err());
+
+        assert_eq!(
+            normalize_components(r"C:\docs\.\old\..\contract.pdf"),
+            r"C:\docs\contract.pdf"
+        );
+        assert_eq!(
+            normalize_components(r"\\server\share\..\..\a.pdf"),
+            r"\\server\share\a.pdf"
+        );
+        assert_eq!(
+            display_path(Path::new(r"\\?\UNC\server\share\a.pdf")),
+            Path::new(r"\\server\share\a.pdf")
+        );
+        assert_eq!(
+            display_path(Path::new(r"\\?\C:\docs\a.pdf")),
+            Path::new(r"C:\docs\a.pdf")
+        );
+    }
+}
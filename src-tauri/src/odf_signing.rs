@@ -0,0 +1,473 @@
+// src-tauri/src/odf_signing.rs
+// Signs OpenDocument files (.odt / .ods / .odp ...) as LibreOffice does (ODF 1.2 Part 3,
+// digital signatures): an XML-DSig `Signature` is added to
+// `META-INF/documentsignatures.xml`, with a reference to every file in the package except
+// `mimetype` and the signature file itself. XML streams are referenced through the C14N
+// transform (canonical XML 1.0 without comments, implemented below); other streams are
+// digested as stored. Signatures already in the file stay valid, since the signature file is
+// not signed. As for Office packages (see ooxml_signing.rs), KeyInfo carries a self-signed
+// certificate, so LibreOffice shows the signature as valid but the certificate as not
+// trusted. Encrypted documents cannot be signed.
+use crate::audit;
+use crate::crypto_types::{DigestScheme, OperationMetrics, SignatureAlgorithm, SigningResult};
+use crate::key_management::{find_key_metadata, read_private_key_der};
+use crate::ooxml_signing::{
+    elements, escape_attribute, escape_text, insert_before_end, read_package, reference,
+    self_signed_certificate, signature_method, write_package, C14N, DSIG_NS,
+};
+use crate::native_paths::{native_path, path_string, read_file};
+use crate::output_paths::resolve_output_path;
+use crate::policy::check_timestamping;
+use crate::signable_inputs::check_signable_input;
+use crate::signing::{finish_metrics, millis, sign_bytes};
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use der::Decode;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::time::Instant;
+use uuid::Uuid;
+use x509_cert::Certificate;
+
+const MIMETYPE_PART: &str = "mimetype";
+const MANIFEST_PART: &str = "META-INF/manifest.xml";
+const SIGNATURES_PART: &str = "META-INF/documentsignatures.xml";
+const ODF_MIMETYPE_PREFIX: &str = "application/vnd.oasis.opendocument.";
+const ODF_DSIG_NS: &str = "urn:oasis:names:tc:opendocument:xmlns:digitalsignature:1.0";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+const SIGNATURE_PROPERTIES_TYPE: &str = "http://www.w3.org/2000/09/xmldsig#SignatureProperties";
+
+// --- Tauri Commands ---
+
+/// Writes a copy of the OpenDocument file at `document_path` with an added signature to
+/// `output_path`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn sign_odf_document(
+    app_handle: tauri::AppHandle,
+    document_path: String,
+    key_id: Uuid,
+    password: String,
+    output_path: String,
+) -> Result<SigningResult, String> {
+    log::info!(
+        "Signing OpenDocument file '{}' with key ID {}",
+        document_path,
+        key_id
+    );
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _sign_odf_document(
+        &app_handle,
+        &document_path,
+        key_id,
+        &password,
+        &output_path,
+    )
+    .map_err(|e| {
+        log::error!("Failed to sign OpenDocument file: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|result| {
+        audit::record(
+            &app_handle,
+            "signOdfDocument",
+            json!({
+                "keyId": key_id,
+                "documentPath": document_path,
+                "outputPath": result.output_path,
+            }),
+        )
+    })
+}
+
+fn _sign_odf_document(
+    app_handle: &tauri::AppHandle,
+    document_path: &str,
+    key_id: Uuid,
+    password: &str,
+    output_path: &str,
+) -> Result<SigningResult> {
+    let started = Instant::now();
+    let document_path = &native_path(document_path)?;
+    check_timestamping(false)?;
+    check_signable_input(app_handle, document_path)?;
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    signature_method(&metadata.algorithm)?;
+    let package = read_file(document_path)
+        .with_context(|| format!("Failed to read document file: {:?}", document_path))?;
+    let parts = read_package(&package)?;
+
+    let kdf_started = Instant::now();
+    let private_key_der = read_private_key_der(app_handle, &metadata, password)?;
+    let mut metrics = OperationMetrics {
+        bytes_processed: package.len() as u64,
+        kdf_ms: Some(millis(kdf_started.elapsed())),
+        ..Default::default()
+    };
+
+    let parts = sign_odf_package(
+        parts,
+        &metadata.algorithm,
+        &private_key_der,
+        &metadata.name,
+        Utc::now(),
+    )?;
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::write(&output_path, write_package(&parts)?)
+        .with_context(|| format!("Failed to write signed document: {:?}", output_path))?;
+    log::info!(
+        "OpenDocument file signed with {}, saved to {:?}",
+        metadata.algorithm,
+        output_path
+    );
+    finish_metrics(&mut metrics, started);
+    Ok(SigningResult {
+        output_path: path_string(&output_path),
+        certificate_path: None,
+        metrics,
+    })
+}
+
+// --- Helper Functions ---
+
+/// Adds a signature to the package parts (zip entry names and contents).
+fn sign_odf_package(
+    mut parts: Vec<(String, Vec<u8>)>,
+    algorithm: &SignatureAlgorithm,
+    private_key_der: &[u8],
+    signer_name: &str,
+    signed_at: DateTime<Utc>,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    let method = signature_method(algorithm)?;
+    let part = |name: &str| parts.iter().find(|(n, _)| n == name).map(|(_, data)| data);
+    if !part(MIMETYPE_PART)
+        .is_some_and(|mimetype| mimetype.starts_with(ODF_MIMETYPE_PREFIX.as_bytes()))
+    {
+        bail!("Not an OpenDocument file");
+    }
+    let manifest = part(MANIFEST_PART).context("The document has no manifest")?;
+    if !elements(manifest, "encryption-data")?.is_empty() {
+        bail!("Encrypted OpenDocument files cannot be signed");
+    }
+
+    // 1. 每个文件一个引用；XML 流经 C14N 变换后计算摘要
+    let mut references = String::new();
+    for (name, data) in &parts {
+        if name == MIMETYPE_PART || name == SIGNATURES_PART {
+            continue;
+        }
+        let uri = encode_uri(name);
+        if name.ends_with(".xml") {
+            let canonical =
+                canonicalize(data).with_context(|| format!("Failed to canonicalize {}", name))?;
+            let transforms = format!(
+                "<Transforms><Transform Algorithm=\"{}\"></Transform></Transforms>",
+                C14N
+            );
+            references.push_str(&reference(&uri, None, &transforms, &canonical));
+        } else {
+            references.push_str(&reference(&uri, None, "", data));
+        }
+    }
+
+    // 2. 签名时间属性（在签名内按规范化形式生成）
+    let signature_id = format!("ID_{}", Uuid::new_v4().simple());
+    let property_id = format!("idSignatureProperties_{}", Uuid::new_v4().simple());
+    let property = format!(
+        "<SignatureProperty Id=\"{}\" Target=\"#{}\"><dc:date xmlns:dc=\"{}\">{}</dc:date></SignatureProperty>",
+        property_id,
+        signature_id,
+        DC_NS,
+        signed_at.format("%Y-%m-%dT%H:%M:%SZ")
+    );
+    let property_c14n = property.replacen(
+        "<SignatureProperty ",
+        &format!("<SignatureProperty xmlns=\"{}\" ", DSIG_NS),
+        1,
+    );
+    references.push_str(&reference(
+        &format!("#{}", property_id),
+        Some(SIGNATURE_PROPERTIES_TYPE),
+        "",
+        property_c14n.as_bytes(),
+    ));
+
+    // 3. SignedInfo 与签名值
+    let signed_info = format!(
+        "<SignedInfo><CanonicalizationMethod Algorithm=\"{}\"></CanonicalizationMethod><SignatureMethod Algorithm=\"{}\"></SignatureMethod>{}</SignedInfo>",
+        C14N, method, references
+    );
+    let signed_info_c14n = signed_info.replacen(
+        "<SignedInfo>",
+        &format!("<SignedInfo xmlns=\"{}\">", DSIG_NS),
+        1,
+    );
+    let signature_value = sign_bytes(
+        algorithm,
+        private_key_der,
+        signed_info_c14n.as_bytes(),
+        DigestScheme::Standard,
+    )?;
+    let certificate = self_signed_certificate(algorithm, private_key_der, signer_name)?;
+    let serial = Certificate::from_der(&certificate)?
+        .tbs_certificate
+        .serial_number
+        .as_bytes()
+        .iter()
+        .fold(0u128, |acc, byte| (acc << 8) | u128::from(*byte));
+    let signature = format!(
+        "<Signature xmlns=\"{}\" Id=\"{}\">{}<SignatureValue>{}</SignatureValue><KeyInfo><X509Data><X509IssuerSerial><X509IssuerName>{}</X509IssuerName><X509SerialNumber>{}</X509SerialNumber></X509IssuerSerial><X509Certificate>{}</X509Certificate></X509Data></KeyInfo><Object><SignatureProperties>{}</SignatureProperties></Object></Signature>",
+        DSIG_NS,
+        signature_id,
+        signed_info,
+        BASE64.encode(&signature_value),
+        escape_text(&issuer_name(signer_name)),
+        serial,
+        BASE64.encode(&certificate),
+        property
+    );
+
+    // 4. 追加到已有的签名文件，或新建
+    match parts.iter_mut().find(|(name, _)| name == SIGNATURES_PART) {
+        Some((_, data)) => insert_before_end(data, "document-signatures", &signature)?,
+        None => parts.push((
+            SIGNATURES_PART.to_string(),
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<document-signatures xmlns=\"{}\" version=\"1.2\">{}</document-signatures>",
+                ODF_DSIG_NS, signature
+            )
+            .into_bytes(),
+        )),
+    }
+    Ok(parts)
+}
+
+// 引用 URI：路径中除非保留字符和 '/' 外按百分号编码
+fn encode_uri(name: &str) -> String {
+    name.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// 证书颁发者（自签名，即签名者本身）的 RFC 4514 字符串
+fn issuer_name(signer_name: &str) -> String {
+    let name = if signer_name.trim().is_empty() {
+        "sigust key"
+    } else {
+        signer_name
+    };
+    let mut escaped = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if matches!(c, ',' | '+' | '"' | '\\' | '<' | '>' | ';' | '=')
+            || (index == 0 && matches!(c, '#' | ' '))
+        {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    if escaped.ends_with(' ') {
+        escaped.insert(escaped.len() - 1, '\\');
+    }
+    format!("CN={}", escaped)
+}
+
+/// Canonical XML 1.0 without comments of a whole document, i.e. the output of the C14N
+/// transform for a stream reference. Documents with a DTD are refused.
+fn canonicalize(xml: &[u8]) -> Result<Vec<u8>> {
+    let mut reader = Reader::from_reader(xml);
+    reader.config_mut().expand_empty_elements = true;
+    let mut out = String::new();
+    // 每层元素生效的命名空间绑定（前缀 -> URI，默认命名空间的前缀为空）
+    let mut scopes: Vec<HashMap<String, String>> = vec![HashMap::new()];
+    let mut depth = 0usize;
+    let mut seen_root = false;
+    let mut buffer = Vec::new();
+    loop {
+        match reader
+            .read_event_into(&mut buffer)
+            .context("Malformed XML stream")?
+        {
+            Event::Start(element) => {
+                let parent = scopes.last().cloned().unwrap_or_default();
+                let mut scope = parent.clone();
+                let mut namespaces = Vec::new();
+                let mut attributes = Vec::new();
+                for attribute in element.attributes() {
+                    let attribute = attribute.context("Malformed XML attribute")?;
+                    let key = std::str::from_utf8(attribute.key.as_ref())?.to_string();
+                    let value = attribute_value(&attribute.value)?;
+                    match key.strip_prefix("xmlns") {
+                        Some(prefix) if prefix.is_empty() || prefix.starts_with(':') => {
+                            let prefix = prefix.trim_start_matches(':').to_string();
+                            // 与父元素相同的声明是多余的
+                            if parent.get(&prefix).map(String::as_str).unwrap_or_default() != value
+                            {
+                                namespaces.push((prefix.clone(), value.clone()));
+                            }
+                            scope.insert(prefix, value);
+                        }
+                        _ => attributes.push((key, value)),
+                    }
+                }
+                let mut attributes = attributes
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let namespace = match key.split_once(':') {
+                            Some(("xml", _)) => XML_NS.to_string(),
+                            Some((prefix, _)) => scope.get(prefix).cloned().unwrap_or_default(),
+                            None => String::new(),
+                        };
+                        let local = key.rsplit(':').next().unwrap_or_default().to_string();
+                        (namespace, local, key, value)
+                    })
+                    .collect::<Vec<_>>();
+                namespaces.sort();
+                attributes.sort();
+
+                out.push('<');
+                out.push_str(std::str::from_utf8(element.name().as_ref())?);
+                for (prefix, uri) in &namespaces {
+                    match prefix.as_str() {
+                        "" => out.push_str(" xmlns=\""),
+                        prefix => out.push_str(&format!(" xmlns:{}=\"", prefix)),
+                    }
+                    out.push_str(&escape_attribute(uri));
+                    out.push('"');
+                }
+                for (_, _, key, value) in &attributes {
+                    out.push_str(&format!(" {}=\"{}\"", key, escape_attribute(value)));
+                }
+                out.push('>');
+                scopes.push(scope);
+                depth += 1;
+                seen_root = true;
+            }
+            Event::End(element) => {
+                out.push_str("</");
+                out.push_str(std::str::from_utf8(element.name().as_ref())?);
+                out.push('>');
+                scopes.pop();
+                depth = depth.saturating_sub(1);
+            }
+            Event::Text(text) if depth > 0 => {
+                let raw = normalize_newlines(std::str::from_utf8(&text)?);
+                out.push_str(&escape_text(&quick_xml::escape::unescape(&raw)?));
+            }
+            Event::CData(data) if depth > 0 => {
+                out.push_str(&escape_text(&normalize_newlines(std::str::from_utf8(
+                    &data,
+                )?)));
+            }
+            Event::PI(pi) => {
+                let content = normalize_newlines(std::str::from_utf8(&pi)?);
+                let pi = match content.split_once(char::is_whitespace) {
+                    Some((target, data)) if !data.trim_start().is_empty() => {
+                        format!("<?{} {}?>", target, data.trim_start())
+                    }
+                    _ => format!("<?{}?>", content.trim_end()),
+                };
+                // 根元素外的处理指令与根元素之间以换行分隔
+                match (depth > 0, seen_root) {
+                    (true, _) => out.push_str(&pi),
+                    (false, true) => out.push_str(&format!("\n{}", pi)),
+                    (false, false) => out.push_str(&format!("{}\n", pi)),
+                }
+            }
+            Event::DocType(_) => bail!("XML streams with a DTD are not supported"),
+            Event::Eof => break,
+            // XML 声明、注释以及根元素外的空白不属于规范化输出
+            _ => {}
+        }
+        buffer.clear();
+    }
+    Ok(out.into_bytes())
+}
+
+// 属性值规范化：字面的换行和制表符变为空格，字符引用保留其字符
+fn attribute_value(raw: &[u8]) -> Result<String> {
+    let raw = std::str::from_utf8(raw)?
+        .replace("\r\n", " ")
+        .replace(['\t', '\n', '\r'], " ");
+    Ok(quick_xml::escape::unescape(&raw)?.into_owned())
+}
+
+fn normalize_newlines(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::self_tests::known_answer_key_pair;
+    use crate::signing::verify_bytes;
+
+    #[test]
+    fn canonicalizes_and_signs_documents() {
+        let xml = "<?xml version=\"1.0\"?>\r\n<!-- comment -->\r\n<doc xmlns=\"urn:a\" xmlns:b=\"urn:b\"><e b:z=\"1\" a=\"x&#10;y\tz\" xmlns:b=\"urn:b\"/><b:f xmlns=\"\">t &gt; &#13;\r\n<![CDATA[<&>]]></b:f></doc>\r\n";
+        assert_eq!(
+            String::from_utf8(canonicalize(xml.as_bytes()).unwrap()).unwrap(),
+            "<doc xmlns=\"urn:a\" xmlns:b=\"urn:b\"><e a=\"x&#xA;y z\" b:z=\"1\"></e><b:f xmlns=\"\">t &gt; &#xD;\n&lt;&amp;&gt;</b:f></doc>"
+        );
+
+        let parts = vec![
+            (
+                MIMETYPE_PART.to_string(),
+                b"application/vnd.oasis.opendocument.text".to_vec(),
+            ),
+            ("content.xml".to_string(), xml.as_bytes().to_vec()),
+            (
+                MANIFEST_PART.to_string(),
+                b"<manifest:manifest xmlns:manifest=\"urn:oasis:names:tc:opendocument:xmlns:manifest:1.0\"/>".to_vec(),
+            ),
+            ("Pictures/a b.png".to_string(), vec![0x89, b'P', b'N', b'G']),
+        ];
+        let algorithm = SignatureAlgorithm::RsaPkcs1Sha256;
+        let (private_der, public_der) = known_answer_key_pair(&algorithm).unwrap();
+        let once = sign_odf_package(parts, &algorithm, &private_der, "Alice", Utc::now()).unwrap();
+        let twice =
+            sign_odf_package(once, &algorithm, &private_der, "Bob, Inc.", Utc::now()).unwrap();
+        assert_eq!(twice.first().unwrap().0, MIMETYPE_PART);
+
+        let (_, signatures) = twice.iter().find(|(n, _)| n == SIGNATURES_PART).unwrap();
+        let signatures = String::from_utf8(signatures.clone()).unwrap();
+        assert_eq!(signatures.matches("<Signature ").count(), 2);
+        assert!(signatures.contains("URI=\"Pictures/a%20b.png\""));
+        assert!(signatures.contains("<X509IssuerName>CN=Bob\\, Inc.</X509IssuerName>"));
+        for signature in signatures.split("<Signature ").skip(1) {
+            let signed_info = signature
+                .split("<SignedInfo>")
+                .nth(1)
+                .and_then(|rest| rest.split("</SignedInfo>").next())
+                .unwrap();
+            let value = signature
+                .split("<SignatureValue>")
+                .nth(1)
+                .and_then(|rest| rest.split("</SignatureValue>").next())
+                .unwrap();
+            let signed_info = format!(
+                "<SignedInfo xmlns=\"{}\">{}</SignedInfo>",
+                DSIG_NS, signed_info
+            );
+            assert!(verify_bytes(
+                &algorithm,
+                &public_der,
+                signed_info.as_bytes(),
+                &BASE64.decode(value).unwrap(),
+                DigestScheme::Standard,
+            )
+            .unwrap()
+            .is_ok());
+        }
+    }
+}
@@ -0,0 +1,502 @@
+// src-tauri/src/oidc_identity.rs
+// Signer identities from an OpenID Connect provider. When asked to at signing time, the
+// signer logs in with the configured provider in the system browser (authorization code
+// flow with PKCE and a loopback redirect to 127.0.0.1). The ID token is checked against the
+// provider's published keys, and its claims (issuer, subject, email, organization) are
+// signed into the envelope with the other attributes, optionally together with the token.
+// The token's nonce starts with the signer's key fingerprint, so an embedded token cannot
+// be moved into an envelope made with another key.
+//
+// Providers rotate their keys, so verification does not re-check the token signature; it
+// checks that an embedded token agrees with the recorded claims and the signing key.
+use crate::audit;
+use crate::crypto_types::{DigestScheme, OidcIdentity, OidcSettings, SignatureAlgorithm};
+use crate::paste_import::{p256_spki, rsa_spki};
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use crate::signing::verify_bytes;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri_plugin_opener::OpenerExt;
+use tiny_http::{Header, Response, Server};
+
+const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+const CALLBACK_PATH: &str = "/callback";
+const SCOPES: &str = "openid email profile";
+/// How long the browser login may take.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_RESPONSE_SIZE: u64 = 1024 * 1024;
+/// Clock difference tolerated when checking the token's expiry.
+const CLOCK_SKEW_SECONDS: i64 = 5 * 60;
+const RANDOM_BYTES: usize = 32;
+/// Claims that may name the signer's organization, in order of preference.
+const ORGANIZATION_CLAIMS: [&str; 3] = ["org", "organization", "hd"];
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_oidc_settings(
+    app_handle: tauri::AppHandle,
+    oidc: OidcSettings,
+) -> Result<OidcSettings, String> {
+    log::info!("Updating OpenID Connect settings: {:?}", oidc);
+    _update_oidc_settings(&app_handle, oidc)
+        .map_err(|e| {
+            log::error!("Failed to update OpenID Connect settings: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|settings| audit::record(&app_handle, "updateOidcSettings", json!(settings)))
+}
+
+fn _update_oidc_settings(
+    app_handle: &tauri::AppHandle,
+    oidc: OidcSettings,
+) -> Result<OidcSettings> {
+    let trimmed = |value: Option<String>| {
+        value
+            .map(|value| value.trim().trim_end_matches('/').to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let oidc = OidcSettings {
+        issuer: trimmed(oidc.issuer),
+        client_id: trimmed(oidc.client_id),
+        embed_token: oidc.embed_token,
+    };
+    if let Some(issuer) = &oidc.issuer {
+        if !issuer.starts_with("https://") {
+            bail!("The issuer must be an https URL.");
+        }
+        if oidc.client_id.is_none() {
+            bail!("A client ID is required.");
+        }
+    }
+    update_settings(app_handle, |settings| settings.oidc = oidc).map(|settings| settings.oidc)
+}
+
+/// Logs in with the configured provider and returns the identity, to check the setup.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn test_oidc_login(app_handle: tauri::AppHandle) -> Result<OidcIdentity, String> {
+    log::info!("Testing the OpenID Connect login");
+    login_identity(&app_handle, None).map_err(|e| {
+        log::error!("OpenID Connect login failed: {:?}", e);
+        e.to_string()
+    })
+}
+
+// --- Helper Functions ---
+
+/// Logs in with the configured provider in the system browser and returns the verified
+/// identity. With a key fingerprint, the token's nonce is bound to that key.
+pub(crate) fn login_identity(
+    app_handle: &tauri::AppHandle,
+    key_fingerprint: Option<&str>,
+) -> Result<OidcIdentity> {
+    let settings = read_settings(&get_settings_path(app_handle)?)?.oidc;
+    let (Some(issuer), Some(client_id)) =
+        (settings.issuer.as_deref(), settings.client_id.as_deref())
+    else {
+        bail!("No OpenID Connect provider is set up");
+    };
+    let agent = agent();
+    let discovery = read_reply(
+        agent.get(&format!("{}{}", issuer, DISCOVERY_PATH)).call(),
+        "OpenID Connect discovery",
+    )?;
+    if reply_str(&discovery, "issuer")?.trim_end_matches('/') != issuer {
+        bail!("The provider's discovery document names a different issuer");
+    }
+
+    let server = Server::http("127.0.0.1:0")
+        .map_err(|e| anyhow!("Failed to listen for the login redirect: {}", e))?;
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.port())
+        .ok_or_else(|| anyhow!("The login redirect is not on an IP address"))?;
+    let redirect_uri = format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH);
+    let verifier = random_token();
+    let state = random_token();
+    let nonce = match key_fingerprint {
+        Some(fingerprint) => format!("{}.{}", fingerprint, random_token()),
+        None => random_token(),
+    };
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+    let authorization_endpoint = reply_str(&discovery, "authorization_endpoint")?;
+    let url = format!(
+        "{}{}{}",
+        authorization_endpoint,
+        if authorization_endpoint.contains('?') {
+            '&'
+        } else {
+            '?'
+        },
+        encode_query(&[
+            ("response_type", "code"),
+            ("client_id", client_id),
+            ("redirect_uri", &redirect_uri),
+            ("scope", SCOPES),
+            ("state", &state),
+            ("nonce", &nonce),
+            ("code_challenge", &challenge),
+            ("code_challenge_method", "S256"),
+        ])
+    );
+    app_handle
+        .opener()
+        .open_url(&url, None::<&str>)
+        .context("Failed to open the browser for the login")?;
+    let code = wait_for_code(&server, &state)?;
+
+    let tokens = read_reply(
+        agent
+            .post(reply_str(&discovery, "token_endpoint")?)
+            .send_form([
+                ("grant_type", "authorization_code"),
+                ("code", code.as_str()),
+                ("redirect_uri", redirect_uri.as_str()),
+                ("client_id", client_id),
+                ("code_verifier", verifier.as_str()),
+            ]),
+        "Token request",
+    )?;
+    let id_token = reply_str(&tokens, "id_token")?;
+    let claims = verify_id_token(&agent, &discovery, id_token, issuer, client_id, &nonce)?;
+    let identity =
+        identity_from_claims(&claims, settings.embed_token.then(|| id_token.to_string()))?;
+    log::info!(
+        "Logged in as {} ({})",
+        identity.email.as_deref().unwrap_or(&identity.subject),
+        identity.issuer
+    );
+    Ok(identity)
+}
+
+/// Checks that an embedded ID token agrees with the recorded claims and was issued for a
+/// login bound to `key_fingerprint`. Identities without a token have nothing to check.
+pub(crate) fn check_identity(identity: &OidcIdentity, key_fingerprint: &str) -> Result<()> {
+    let Some(token) = &identity.id_token else {
+        return Ok(());
+    };
+    let (_, claims, _, _) = split_jwt(token)?;
+    let recorded = identity_from_claims(&claims, Some(token.clone()))?;
+    if recorded.issuer != identity.issuer
+        || recorded.subject != identity.subject
+        || recorded.email != identity.email
+    {
+        bail!("The recorded identity differs from its ID token");
+    }
+    let bound = claims["nonce"]
+        .as_str()
+        .and_then(|nonce| nonce.split_once('.'))
+        .is_some_and(|(fingerprint, _)| fingerprint == key_fingerprint);
+    if !bound {
+        bail!("The ID token was not issued for the signing key");
+    }
+    Ok(())
+}
+
+// 等待浏览器重定向回本机，返回授权码；与 state 不符的请求（如 favicon）不予理会
+fn wait_for_code(server: &Server, state: &str) -> Result<String> {
+    let deadline = Instant::now() + LOGIN_TIMEOUT;
+    loop {
+        let timed_out = || anyhow!("The login was not completed in time");
+        let remaining = deadline
+            .checked_duration_since(Instant::now())
+            .ok_or_else(timed_out)?;
+        let request = server
+            .recv_timeout(remaining)
+            .context("Failed to receive the login redirect")?
+            .ok_or_else(timed_out)?;
+        let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+        let params = parse_query(query);
+        if path != CALLBACK_PATH || params.get("state").map(String::as_str) != Some(state) {
+            let _ = request.respond(Response::empty(404));
+            continue;
+        }
+        let result = match params.get("code") {
+            Some(code) => Ok(code.clone()),
+            None => Err(anyhow!(
+                "The provider refused the login: {}",
+                params
+                    .get("error_description")
+                    .or(params.get("error"))
+                    .map_or("no details", String::as_str)
+            )),
+        };
+        let page = match &result {
+            Ok(_) => "Login complete. You can close this window and return to sigust.",
+            Err(_) => "Login failed. You can close this window and return to sigust.",
+        };
+        let content_type =
+            Header::from_bytes(&b"Content-Type"[..], &b"text/plain; charset=utf-8"[..])
+                .map_err(|_| anyhow!("Invalid response header"))?;
+        let _ = request.respond(Response::from_string(page).with_header(content_type));
+        return result;
+    }
+}
+
+// 用提供方公布的密钥（JWKS）校验 ID 令牌签名，再检查签发者、受众、有效期与 nonce
+fn verify_id_token(
+    agent: &ureq::Agent,
+    discovery: &Value,
+    token: &str,
+    issuer: &str,
+    client_id: &str,
+    nonce: &str,
+) -> Result<Value> {
+    let (header, claims, signing_input, signature) = split_jwt(token)?;
+    let (algorithm, key_type) = match reply_str(&header, "alg")? {
+        "RS256" => (SignatureAlgorithm::RsaPkcs1Sha256, "RSA"),
+        "ES256" => (SignatureAlgorithm::EcdsaP256Sha256, "EC"),
+        alg => bail!("Unsupported ID token algorithm: {}", alg),
+    };
+    let jwks = read_reply(
+        agent.get(reply_str(discovery, "jwks_uri")?).call(),
+        "Provider key request",
+    )?;
+    let kid = header["kid"].as_str();
+    let jwk = jwks["keys"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|key| {
+            key["kty"].as_str() == Some(key_type) && (kid.is_none() || key["kid"].as_str() == kid)
+        })
+        .ok_or_else(|| anyhow!("The provider has no key for the ID token"))?;
+    let component = |name: &str| decode_base64url(reply_str(jwk, name)?);
+    let public_key_der = match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => rsa_spki(&component("n")?, &component("e")?)?,
+        _ => {
+            let mut point = vec![0x04];
+            point.extend(component("x")?);
+            point.extend(component("y")?);
+            p256_spki(&point)?
+        }
+    };
+    verify_bytes(
+        &algorithm,
+        &public_key_der,
+        signing_input.as_bytes(),
+        &signature,
+        DigestScheme::Standard,
+    )?
+    .map_err(|_| anyhow!("The ID token signature does not verify"))?;
+
+    if reply_str(&claims, "iss")?.trim_end_matches('/') != issuer {
+        bail!("The ID token was issued by a different provider");
+    }
+    let audience_ok = match &claims["aud"] {
+        Value::String(audience) => audience == client_id,
+        Value::Array(audiences) => audiences.iter().any(|a| a.as_str() == Some(client_id)),
+        _ => false,
+    };
+    if !audience_ok {
+        bail!("The ID token was issued for a different client");
+    }
+    let expires = claims["exp"]
+        .as_i64()
+        .ok_or_else(|| anyhow!("The ID token has no expiry"))?;
+    if expires + CLOCK_SKEW_SECONDS < Utc::now().timestamp() {
+        bail!("The ID token has expired");
+    }
+    if claims["nonce"].as_str() != Some(nonce) {
+        bail!("The ID token does not belong to this login");
+    }
+    Ok(claims)
+}
+
+fn identity_from_claims(claims: &Value, id_token: Option<String>) -> Result<OidcIdentity> {
+    let email_verified = match &claims["email_verified"] {
+        Value::Bool(verified) => *verified,
+        // 部分提供方以字符串返回
+        Value::String(verified) => verified == "true",
+        _ => false,
+    };
+    let authenticated_at = claims["auth_time"]
+        .as_i64()
+        .or(claims["iat"].as_i64())
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .unwrap_or_else(Utc::now);
+    Ok(OidcIdentity {
+        issuer: reply_str(claims, "iss")?.trim_end_matches('/').to_string(),
+        subject: reply_str(claims, "sub")?.to_string(),
+        email: claims["email"].as_str().map(str::to_string),
+        email_verified,
+        organization: ORGANIZATION_CLAIMS
+            .iter()
+            .find_map(|claim| claims[*claim].as_str())
+            .map(str::to_string),
+        authenticated_at,
+        id_token,
+    })
+}
+
+// 拆分 JWT：返回头部、声明、签名输入与签名
+fn split_jwt(token: &str) -> Result<(Value, Value, &str, Vec<u8>)> {
+    let (signing_input, signature) = token
+        .rsplit_once('.')
+        .ok_or_else(|| anyhow!("Malformed ID token"))?;
+    let (header, claims) = signing_input
+        .split_once('.')
+        .ok_or_else(|| anyhow!("Malformed ID token"))?;
+    let decode_json = |part: &str| -> Result<Value> {
+        serde_json::from_slice(&decode_base64url(part)?).context("Malformed ID token")
+    };
+    Ok((
+        decode_json(header)?,
+        decode_json(claims)?,
+        signing_input,
+        decode_base64url(signature)?,
+    ))
+}
+
+fn decode_base64url(text: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(text.trim_end_matches('='))
+        .context("Malformed base64url data")
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; RANDOM_BYTES];
+    rand::rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// application/x-www-form-urlencoded 编码（仅保留非保留字符）
+fn encode_query(params: &[(&str, &str)]) -> String {
+    let encode = |text: &str| {
+        text.bytes()
+            .map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    (b as char).to_string()
+                }
+                _ => format!("%{:02X}", b),
+            })
+            .collect::<String>()
+    };
+    params
+        .iter()
+        .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    let decode = |text: &str| {
+        let bytes = text.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'+' => decoded.push(b' '),
+                b'%' if i + 2 < bytes.len() => {
+                    let byte = std::str::from_utf8(&bytes[i + 1..i + 3])
+                        .ok()
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                    match byte {
+                        Some(byte) => {
+                            decoded.push(byte);
+                            i += 2;
+                        }
+                        None => decoded.push(b'%'),
+                    }
+                }
+                byte => decoded.push(byte),
+            }
+            i += 1;
+        }
+        String::from_utf8_lossy(&decoded).into_owned()
+    };
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+        .collect()
+}
+
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(HTTP_TIMEOUT))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+fn read_reply(
+    response: Result<ureq::http::Response<ureq::Body>, ureq::Error>,
+    what: &str,
+) -> Result<Value> {
+    let mut response = response.with_context(|| format!("{} failed", what))?;
+    let status = response.status();
+    let bytes = response
+        .body_mut()
+        .with_config()
+        .limit(MAX_RESPONSE_SIZE)
+        .read_to_vec()
+        .with_context(|| format!("Failed to read the reply to {}", what))?;
+    let body: Value = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Invalid reply to {} (HTTP {})", what, status))?;
+    if !status.is_success() {
+        let message = [&body["error_description"], &body["error"]]
+            .into_iter()
+            .find_map(Value::as_str)
+            .unwrap_or("no details");
+        bail!("{} failed (HTTP {}): {}", what, status, message);
+    }
+    Ok(body)
+}
+
+fn reply_str<'a>(value: &'a Value, field: &str) -> Result<&'a str> {
+    value[field]
+        .as_str()
+        .ok_or_else(|| anyhow!("The reply has no '{}'", field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_tokens_are_bound_to_the_signing_key() {
+        let part = |value: Value| URL_SAFE_NO_PAD.encode(value.to_string());
+        let token = format!(
+            "{}.{}.{}",
+            part(json!({ "alg": "RS256" })),
+            part(json!({
+                "iss": "https://accounts.example.com",
+                "sub": "1234",
+                "email": "alice@example.com",
+                "email_verified": "true",
+                "hd": "example.com",
+                "iat": 1_700_000_000,
+                "nonce": "ab12.random",
+            })),
+            URL_SAFE_NO_PAD.encode(b"signature"),
+        );
+        let (_, claims, _, _) = split_jwt(&token).unwrap();
+        let identity = identity_from_claims(&claims, Some(token)).unwrap();
+        assert!(identity.email_verified);
+        assert_eq!(identity.organization.as_deref(), Some("example.com"));
+        check_identity(&identity, "ab12").unwrap();
+        assert!(check_identity(&identity, "cd34").is_err());
+        let altered = OidcIdentity {
+            email: Some("mallory@example.com".to_string()),
+            ..identity
+        };
+        assert!(check_identity(&altered, "ab12").is_err());
+
+        let params = parse_query(&encode_query(&[("state", "a b/c"), ("code", "x%y")]));
+        assert_eq!(params["state"], "a b/c");
+        assert_eq!(params["code"], "x%y");
+    }
+}
@@ -0,0 +1,734 @@
+// src-tauri/src/ooxml_signing.rs
+// Signs Office Open XML packages (.docx / .xlsx / .pptx) the way Office does (ECMA-376
+// Part 2, digital signatures), so Word, Excel and PowerPoint report the document as signed.
+// The package gets:
+//
+//   _xmlsignatures/origin.sigs             signature origin, shared by all signatures
+//   _xmlsignatures/sigN.xml                XML-DSig signature over a manifest of the parts
+//   _xmlsignatures/_rels/origin.sigs.rels  origin -> signature relationships
+//
+// plus the origin relationship in `_rels/.rels` and both content types in
+// `[Content_Types].xml`. Relationship parts are signed through the OPC relationship
+// transform, so later signatures (which add relationships) do not break earlier ones; other
+// parts are digested as stored. Keys are bare key pairs, so KeyInfo carries a self-signed
+// certificate made at signing time: Office shows the signature as intact but the signer as
+// not trusted. RSA and ECDSA P-256 keys only; Office does not accept Ed25519.
+use crate::audit;
+use crate::crypto_types::{DigestScheme, OperationMetrics, SignatureAlgorithm, SigningResult};
+use crate::key_management::{find_key_metadata, read_private_key_der};
+use crate::native_paths::{native_path, path_string, read_file};
+use crate::output_paths::resolve_output_path;
+use crate::policy::check_timestamping;
+use crate::signable_inputs::check_signable_input;
+use crate::signing::{finish_metrics, millis, sign_bytes};
+use aead::OsRng;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, SecondsFormat, Utc};
+use der::asn1::{Any, SetOfVec};
+use der::{Encode, Tag};
+use p256::pkcs8::DecodePrivateKey;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use rsa::rand_core::RngCore;
+use rsa::RsaPrivateKey;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use signature::Keypair;
+use spki::SubjectPublicKeyInfoOwned;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use x509_cert::attr::AttributeTypeAndValue;
+use x509_cert::builder::{Builder, CertificateBuilder, Profile};
+use x509_cert::name::{Name, RdnSequence, RelativeDistinguishedName};
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::time::Validity;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const CONTENT_TYPES_PART: &str = "[Content_Types].xml";
+const PACKAGE_RELS_PART: &str = "_rels/.rels";
+const DEFAULT_ORIGIN_PART: &str = "_xmlsignatures/origin.sigs";
+/// Packages are unpacked in memory; larger ones (or zip bombs) are refused.
+const MAX_PACKAGE_BYTES: u64 = 512 * 1024 * 1024;
+const CERTIFICATE_VALIDITY: Duration = Duration::from_secs(3 * 365 * 24 * 60 * 60);
+
+const RELS_NS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+const ORIGIN_REL_TYPE: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships/digital-signature/origin";
+const SIGNATURE_REL_TYPE: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships/digital-signature/signature";
+const ORIGIN_CONTENT_TYPE: &str = "application/vnd.openxmlformats-package.digital-signature-origin";
+const SIGNATURE_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-package.digital-signature-xmlsignature+xml";
+pub(crate) const DSIG_NS: &str = "http://www.w3.org/2000/09/xmldsig#";
+const MDSSI_NS: &str = "http://schemas.openxmlformats.org/package/2006/digital-signature";
+const OFFICE_DIGSIG_NS: &str = "http://schemas.microsoft.com/office/2006/digsig";
+pub(crate) const C14N: &str = "http://www.w3.org/TR/2001/REC-xml-c14n-20010315";
+const RELATIONSHIP_TRANSFORM: &str =
+    "http://schemas.openxmlformats.org/package/2006/RelationshipTransform";
+pub(crate) const SHA256_URI: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+const OBJECT_TYPE: &str = "http://www.w3.org/2000/09/xmldsig#Object";
+
+/// A relationship from a `.rels` part.
+struct Relationship {
+    id: String,
+    rel_type: String,
+    target: String,
+    target_mode: Option<String>,
+}
+
+// --- Tauri Commands ---
+
+/// Writes a copy of the Office document at `document_path` with an added package signature
+/// to `output_path`. Existing signatures in the package are kept.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn sign_office_document(
+    app_handle: tauri::AppHandle,
+    document_path: String,
+    key_id: Uuid,
+    password: String,
+    output_path: String,
+    // Shown as the purpose of the signature in Office's signature details
+    comment: Option<String>,
+) -> Result<SigningResult, String> {
+    log::info!(
+        "Signing Office document '{}' with key ID {}",
+        document_path,
+        key_id
+    );
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _sign_office_document(
+        &app_handle,
+        &document_path,
+        key_id,
+        &password,
+        &output_path,
+        comment.as_deref().unwrap_or_default(),
+    )
+    .map_err(|e| {
+        log::error!("Failed to sign Office document: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|result| {
+        audit::record(
+            &app_handle,
+            "signOfficeDocument",
+            json!({
+                "keyId": key_id,
+                "documentPath": document_path,
+                "outputPath": result.output_path,
+            }),
+        )
+    })
+}
+
+fn _sign_office_document(
+    app_handle: &tauri::AppHandle,
+    document_path: &str,
+    key_id: Uuid,
+    password: &str,
+    output_path: &str,
+    comment: &str,
+) -> Result<SigningResult> {
+    let started = Instant::now();
+    let document_path = &native_path(document_path)?;
+    check_timestamping(false)?;
+    check_signable_input(app_handle, document_path)?;
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    signature_method(&metadata.algorithm)?;
+    let package = read_file(document_path)
+        .with_context(|| format!("Failed to read document file: {:?}", document_path))?;
+    let parts = read_package(&package)?;
+
+    let kdf_started = Instant::now();
+    let private_key_der = read_private_key_der(app_handle, &metadata, password)?;
+    let mut metrics = OperationMetrics {
+        bytes_processed: package.len() as u64,
+        kdf_ms: Some(millis(kdf_started.elapsed())),
+        ..Default::default()
+    };
+
+    let parts = sign_package(
+        parts,
+        &metadata.algorithm,
+        &private_key_der,
+        &metadata.name,
+        comment,
+        Utc::now(),
+    )?;
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::write(&output_path, write_package(&parts)?)
+        .with_context(|| format!("Failed to write signed document: {:?}", output_path))?;
+    log::info!(
+        "Office document signed with {}, saved to {:?}",
+        metadata.algorithm,
+        output_path
+    );
+    finish_metrics(&mut metrics, started);
+    Ok(SigningResult {
+        output_path: path_string(&output_path),
+        certificate_path: None,
+        metrics,
+    })
+}
+
+// --- Helper Functions ---
+
+/// Adds a signature to the package parts (zip entry names and contents).
+fn sign_package(
+    mut parts: Vec<(String, Vec<u8>)>,
+    algorithm: &SignatureAlgorithm,
+    private_key_der: &[u8],
+    signer_name: &str,
+    comment: &str,
+    signed_at: DateTime<Utc>,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    let method = signature_method(algorithm)?;
+    let find = |parts: &[(String, Vec<u8>)], name: &str| {
+        parts.iter().position(|(n, _)| n.eq_ignore_ascii_case(name))
+    };
+    if find(&parts, CONTENT_TYPES_PART).is_none() || find(&parts, PACKAGE_RELS_PART).is_none() {
+        bail!("Not an Office Open XML package");
+    }
+
+    // 1. 签名来源部件：沿用已有的，否则新建并从包关系指向它
+    let package_rels_index = find(&parts, PACKAGE_RELS_PART).unwrap_or_default();
+    let package_rels = parse_relationships(&parts[package_rels_index].1)?;
+    let origin_part = match package_rels.iter().find(|r| r.rel_type == ORIGIN_REL_TYPE) {
+        Some(origin) => origin.target.trim_start_matches('/').to_string(),
+        None => {
+            let id = unused_id(&package_rels, "rIdSigOrigin");
+            let relationship = format!(
+                "<Relationship Id=\"{}\" Type=\"{}\" Target=\"{}\"/>",
+                id, ORIGIN_REL_TYPE, DEFAULT_ORIGIN_PART
+            );
+            insert_before_end(
+                &mut parts[package_rels_index].1,
+                "Relationships",
+                &relationship,
+            )?;
+            DEFAULT_ORIGIN_PART.to_string()
+        }
+    };
+    if find(&parts, &origin_part).is_none() {
+        parts.push((origin_part.clone(), Vec::new()));
+    }
+    let (origin_dir, origin_file) = match origin_part.rsplit_once('/') {
+        Some((dir, file)) => (format!("{}/", dir), file.to_string()),
+        None => (String::new(), origin_part.clone()),
+    };
+    let origin_rels_part = format!("{}_rels/{}.rels", origin_dir, origin_file);
+    let signature_part = (1..)
+        .map(|n| format!("{}sig{}.xml", origin_dir, n))
+        .find(|name| find(&parts, name).is_none())
+        .unwrap_or_default();
+
+    // 2. 内容类型：来源部件和新签名部件
+    let content_types_index = find(&parts, CONTENT_TYPES_PART).unwrap_or_default();
+    let mut additions = String::new();
+    if content_type(&parts[content_types_index].1, &origin_part)?.is_none() {
+        additions.push_str(&format!(
+            "<Override PartName=\"/{}\" ContentType=\"{}\"/>",
+            escape_attribute(&origin_part),
+            ORIGIN_CONTENT_TYPE
+        ));
+    }
+    additions.push_str(&format!(
+        "<Override PartName=\"/{}\" ContentType=\"{}\"/>",
+        escape_attribute(&signature_part),
+        SIGNATURE_CONTENT_TYPE
+    ));
+    insert_before_end(&mut parts[content_types_index].1, "Types", &additions)?;
+
+    // 3. 清单：除内容类型、签名来源及签名部件外的所有部件
+    let content_types = parts[content_types_index].1.clone();
+    let mut manifest = String::new();
+    for (name, data) in &parts {
+        if name.eq_ignore_ascii_case(CONTENT_TYPES_PART)
+            || name.eq_ignore_ascii_case(&origin_part)
+            || name.eq_ignore_ascii_case(&origin_rels_part)
+        {
+            continue;
+        }
+        let content_type = content_type(&content_types, name)?
+            .ok_or_else(|| anyhow!("Part {} has no content type", name))?;
+        if content_type == SIGNATURE_CONTENT_TYPE {
+            continue;
+        }
+        let uri = format!("/{}?ContentType={}", name, content_type);
+        if name.ends_with(".rels") {
+            let relationships: Vec<Relationship> = parse_relationships(data)?
+                .into_iter()
+                .filter(|r| r.rel_type != ORIGIN_REL_TYPE)
+                .collect();
+            let references: String = relationships
+                .iter()
+                .map(|r| {
+                    format!(
+                        "<mdssi:RelationshipReference xmlns:mdssi=\"{}\" SourceId=\"{}\"></mdssi:RelationshipReference>",
+                        MDSSI_NS,
+                        escape_attribute(&r.id)
+                    )
+                })
+                .collect();
+            let transforms = format!(
+                "<Transforms><Transform Algorithm=\"{}\">{}</Transform><Transform Algorithm=\"{}\"></Transform></Transforms>",
+                RELATIONSHIP_TRANSFORM, references, C14N
+            );
+            let transformed = transform_relationships(relationships);
+            manifest.push_str(&reference(&uri, None, &transforms, transformed.as_bytes()));
+        } else {
+            manifest.push_str(&reference(&uri, None, "", data));
+        }
+    }
+
+    // 4. 两个签名对象和 SignedInfo（按规范化形式生成，摘要即对这些字节计算）
+    let package_object = format!(
+        "<Manifest>{}</Manifest><SignatureProperties><SignatureProperty Id=\"idSignatureTime\" Target=\"#idPackageSignature\"><mdssi:SignatureTime xmlns:mdssi=\"{}\"><mdssi:Format>YYYY-MM-DDThh:mm:ssTZD</mdssi:Format><mdssi:Value>{}</mdssi:Value></mdssi:SignatureTime></SignatureProperty></SignatureProperties>",
+        manifest,
+        MDSSI_NS,
+        signed_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+    let office_object = format!(
+        "<SignatureProperties><SignatureProperty Id=\"idOfficeV1Details\" Target=\"#idPackageSignature\"><SignatureInfoV1 xmlns=\"{}\"><SetupID></SetupID><SignatureText></SignatureText><SignatureImage></SignatureImage><SignatureComments>{}</SignatureComments><WindowsVersion>10.0</WindowsVersion><OfficeVersion>16.0</OfficeVersion><ApplicationVersion>16.0</ApplicationVersion><Monitors>1</Monitors><HorizontalResolution>1920</HorizontalResolution><VerticalResolution>1080</VerticalResolution><ColorDepth>32</ColorDepth><SignatureProviderId>{{00000000-0000-0000-0000-000000000000}}</SignatureProviderId><SignatureProviderUrl></SignatureProviderUrl><SignatureProviderDetails>9</SignatureProviderDetails><SignatureType>1</SignatureType><ManifestHashAlgorithm>{}</ManifestHashAlgorithm></SignatureInfoV1></SignatureProperty></SignatureProperties>",
+        OFFICE_DIGSIG_NS,
+        escape_text(comment),
+        SHA256_URI
+    );
+    let object_c14n = |id: &str, content: &str| {
+        format!(
+            "<Object xmlns=\"{}\" Id=\"{}\">{}</Object>",
+            DSIG_NS, id, content
+        )
+    };
+    let signed_info = format!(
+        "<SignedInfo><CanonicalizationMethod Algorithm=\"{}\"></CanonicalizationMethod><SignatureMethod Algorithm=\"{}\"></SignatureMethod>{}{}</SignedInfo>",
+        C14N,
+        method,
+        reference(
+            "#idPackageObject",
+            Some(OBJECT_TYPE),
+            "",
+            object_c14n("idPackageObject", &package_object).as_bytes()
+        ),
+        reference(
+            "#idOfficeObject",
+            Some(OBJECT_TYPE),
+            "",
+            object_c14n("idOfficeObject", &office_object).as_bytes()
+        ),
+    );
+    // SignedInfo 的规范化形式带有从 Signature 继承的默认命名空间
+    let signed_info_c14n = signed_info.replacen(
+        "<SignedInfo>",
+        &format!("<SignedInfo xmlns=\"{}\">", DSIG_NS),
+        1,
+    );
+    let signature_value = sign_bytes(
+        algorithm,
+        private_key_der,
+        signed_info_c14n.as_bytes(),
+        DigestScheme::Standard,
+    )?;
+    let certificate = self_signed_certificate(algorithm, private_key_der, signer_name)?;
+    let signature_xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\r\n<Signature xmlns=\"{}\" Id=\"idPackageSignature\">{}<SignatureValue>{}</SignatureValue><KeyInfo><X509Data><X509Certificate>{}</X509Certificate></X509Data></KeyInfo><Object Id=\"idPackageObject\">{}</Object><Object Id=\"idOfficeObject\">{}</Object></Signature>",
+        DSIG_NS,
+        signed_info,
+        BASE64.encode(&signature_value),
+        BASE64.encode(&certificate),
+        package_object,
+        office_object
+    );
+    parts.push((signature_part.clone(), signature_xml.into_bytes()));
+
+    // 5. 来源部件指向新签名
+    let signature_target = signature_part
+        .strip_prefix(&origin_dir)
+        .unwrap_or(&signature_part);
+    match find(&parts, &origin_rels_part) {
+        Some(index) => {
+            let id = unused_id(&parse_relationships(&parts[index].1)?, "rIdSig");
+            let relationship = format!(
+                "<Relationship Id=\"{}\" Type=\"{}\" Target=\"{}\"/>",
+                id, SIGNATURE_REL_TYPE, signature_target
+            );
+            insert_before_end(&mut parts[index].1, "Relationships", &relationship)?;
+        }
+        None => {
+            let rels = format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\r\n<Relationships xmlns=\"{}\"><Relationship Id=\"rIdSig1\" Type=\"{}\" Target=\"{}\"/></Relationships>",
+                RELS_NS, SIGNATURE_REL_TYPE, signature_target
+            );
+            parts.push((origin_rels_part, rels.into_bytes()));
+        }
+    }
+    Ok(parts)
+}
+
+pub(crate) fn signature_method(algorithm: &SignatureAlgorithm) -> Result<&'static str> {
+    match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => {
+            Ok("http://www.w3.org/2001/04/xmldsig-more#rsa-sha256")
+        }
+        SignatureAlgorithm::EcdsaP256Sha256 => {
+            Ok("http://www.w3.org/2001/04/xmldsig-more#ecdsa-sha256")
+        }
+        SignatureAlgorithm::EcdsaP384Sha384
+        | SignatureAlgorithm::EcdsaP521Sha512
+        | SignatureAlgorithm::Ed25519
+        | SignatureAlgorithm::MlDsa65 => {
+            bail!("Office documents can only be signed with RSA or ECDSA P-256 keys")
+        }
+    }
+}
+
+// 规范化形式的 Reference 元素（属性按名称排序）
+pub(crate) fn reference(
+    uri: &str,
+    reference_type: Option<&str>,
+    transforms: &str,
+    data: &[u8],
+) -> String {
+    let type_attribute = reference_type
+        .map(|t| format!(" Type=\"{}\"", t))
+        .unwrap_or_default();
+    format!(
+        "<Reference{} URI=\"{}\">{}<DigestMethod Algorithm=\"{}\"></DigestMethod><DigestValue>{}</DigestValue></Reference>",
+        type_attribute,
+        escape_attribute(uri),
+        transforms,
+        SHA256_URI,
+        BASE64.encode(Sha256::digest(data))
+    )
+}
+
+/// Output of the OPC relationship transform followed by C14N: the selected relationships
+/// sorted by ID, with `TargetMode` made explicit.
+fn transform_relationships(mut relationships: Vec<Relationship>) -> String {
+    relationships.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut out = format!("<Relationships xmlns=\"{}\">", RELS_NS);
+    for r in &relationships {
+        out.push_str(&format!(
+            "<Relationship Id=\"{}\" Target=\"{}\" TargetMode=\"{}\" Type=\"{}\"></Relationship>",
+            escape_attribute(&r.id),
+            escape_attribute(&r.target),
+            escape_attribute(r.target_mode.as_deref().unwrap_or("Internal")),
+            escape_attribute(&r.rel_type)
+        ));
+    }
+    out.push_str("</Relationships>");
+    out
+}
+
+fn parse_relationships(xml: &[u8]) -> Result<Vec<Relationship>> {
+    elements(xml, "Relationship")?
+        .into_iter()
+        .map(|mut attributes| {
+            let mut take = |name: &str| {
+                attributes
+                    .remove(name)
+                    .ok_or_else(|| anyhow!("Relationship without {}", name))
+            };
+            Ok(Relationship {
+                id: take("Id")?,
+                rel_type: take("Type")?,
+                target: take("Target")?,
+                target_mode: take("TargetMode").ok(),
+            })
+        })
+        .collect()
+}
+
+// 按 Override（部件名）再按 Default（扩展名）查找内容类型，均不区分大小写
+fn content_type(content_types: &[u8], part: &str) -> Result<Option<String>> {
+    let part_name = format!("/{}", part);
+    if let Some(mut attributes) = elements(content_types, "Override")?.into_iter().find(|a| {
+        a.get("PartName")
+            .is_some_and(|name| name.eq_ignore_ascii_case(&part_name))
+    }) {
+        return Ok(attributes.remove("ContentType"));
+    }
+    let extension = part
+        .rsplit_once('.')
+        .map(|(_, ext)| ext)
+        .unwrap_or_default();
+    Ok(elements(content_types, "Default")?
+        .into_iter()
+        .find(|a| {
+            a.get("Extension")
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+        })
+        .and_then(|mut attributes| attributes.remove("ContentType")))
+}
+
+// 读取 XML 中某个元素（按本地名）的全部属性
+pub(crate) fn elements(xml: &[u8], name: &str) -> Result<Vec<HashMap<String, String>>> {
+    let mut reader = Reader::from_reader(xml);
+    let mut buffer = Vec::new();
+    let mut found = Vec::new();
+    loop {
+        match reader
+            .read_event_into(&mut buffer)
+            .context("Malformed XML part")?
+        {
+            Event::Start(element) | Event::Empty(element)
+                if element.local_name().as_ref() == name.as_bytes() =>
+            {
+                let mut attributes = HashMap::new();
+                for attribute in element.attributes() {
+                    let attribute = attribute.context("Malformed XML attribute")?;
+                    attributes.insert(
+                        String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned(),
+                        attribute.unescape_value()?.into_owned(),
+                    );
+                }
+                found.push(attributes);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+    Ok(found)
+}
+
+// 在根元素的结束标签前插入内容
+pub(crate) fn insert_before_end(xml: &mut Vec<u8>, root: &str, content: &str) -> Result<()> {
+    let text = std::str::from_utf8(xml).context("XML part is not UTF-8")?;
+    let end_tag = format!("</{}>", root);
+    let position = text
+        .rfind(&end_tag)
+        .ok_or_else(|| anyhow!("Unsupported XML part: no {}", end_tag))?;
+    xml.splice(position..position, content.bytes());
+    Ok(())
+}
+
+fn unused_id(relationships: &[Relationship], prefix: &str) -> String {
+    (1..)
+        .map(|n| format!("{}{}", prefix, n))
+        .find(|id| relationships.iter().all(|r| &r.id != id))
+        .unwrap_or_default()
+}
+
+// 规范化 XML 的转义规则
+pub(crate) fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\r', "&#xD;")
+}
+
+pub(crate) fn escape_attribute(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+        .replace('\t', "&#x9;")
+        .replace('\n', "&#xA;")
+        .replace('\r', "&#xD;")
+}
+
+/// Self-signed certificate for the key, named after it, for the signature's KeyInfo.
+pub(crate) fn self_signed_certificate(
+    algorithm: &SignatureAlgorithm,
+    private_key_der: &[u8],
+    signer_name: &str,
+) -> Result<Vec<u8>> {
+    let subject = common_name(signer_name)?;
+    let mut serial = [0u8; 16];
+    OsRng.fill_bytes(&mut serial);
+    serial[0] &= 0x7f;
+    let serial = SerialNumber::new(&serial)?;
+    let validity = Validity::from_now(CERTIFICATE_VALIDITY)?;
+    let profile = Profile::Leaf {
+        issuer: subject.clone(),
+        enable_key_agreement: false,
+        enable_key_encipherment: false,
+    };
+    let certificate = match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => {
+            let signer = rsa::pkcs1v15::SigningKey::<Sha256>::new(
+                RsaPrivateKey::from_pkcs8_der(private_key_der)
+                    .context("Failed to parse RSA private key")?,
+            );
+            let spki = SubjectPublicKeyInfoOwned::from_key(signer.verifying_key())?;
+            CertificateBuilder::new(profile, serial, validity, subject, spki, &signer)?
+                .build::<rsa::pkcs1v15::Signature>()?
+        }
+        SignatureAlgorithm::EcdsaP256Sha256 => {
+            let signer = p256::ecdsa::SigningKey::from_pkcs8_der(private_key_der)
+                .context("Failed to parse ECDSA P-256 private key")?;
+            let spki = SubjectPublicKeyInfoOwned::from_key(*signer.verifying_key())?;
+            CertificateBuilder::new(profile, serial, validity, subject, spki, &signer)?
+                .build::<p256::ecdsa::DerSignature>()?
+        }
+        SignatureAlgorithm::EcdsaP384Sha384
+        | SignatureAlgorithm::EcdsaP521Sha512
+        | SignatureAlgorithm::Ed25519
+        | SignatureAlgorithm::MlDsa65 => {
+            bail!("{} keys cannot sign Office documents", algorithm)
+        }
+    };
+    Ok(certificate.to_der()?)
+}
+
+fn common_name(name: &str) -> Result<Name> {
+    let name = if name.trim().is_empty() {
+        "sigust key"
+    } else {
+        name
+    };
+    let common_name = AttributeTypeAndValue {
+        oid: const_oid::db::rfc4519::CN,
+        value: Any::new(Tag::Utf8String, name.as_bytes())?,
+    };
+    Ok(RdnSequence(vec![RelativeDistinguishedName(
+        SetOfVec::try_from(vec![common_name])?,
+    )]))
+}
+
+pub(crate) fn read_package(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut archive =
+        ZipArchive::new(Cursor::new(bytes)).context("The document is not a ZIP package")?;
+    let mut parts = Vec::new();
+    let mut total = 0u64;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut data = Vec::new();
+        (&mut entry)
+            .take(MAX_PACKAGE_BYTES - total + 1)
+            .read_to_end(&mut data)
+            .with_context(|| format!("Failed to read part {}", name))?;
+        total += data.len() as u64;
+        if total > MAX_PACKAGE_BYTES {
+            bail!("The document is too large to sign");
+        }
+        parts.push((name, data));
+    }
+    Ok(parts)
+}
+
+pub(crate) fn write_package(parts: &[(String, Vec<u8>)]) -> Result<Vec<u8>> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in parts {
+        // OpenDocument 要求 mimetype 不压缩（且为第一个条目）
+        let method = if name == "mimetype" {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+        writer.start_file(
+            name.as_str(),
+            SimpleFileOptions::default().compression_method(method),
+        )?;
+        writer.write_all(data)?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::self_tests::known_answer_key_pair;
+    use crate::signing::verify_bytes;
+
+    fn signed_info(signature_xml: &str) -> String {
+        let start = signature_xml.find("<SignedInfo>").unwrap();
+        let end = signature_xml.find("</SignedInfo>").unwrap() + "</SignedInfo>".len();
+        signature_xml[start..end].replacen(
+            "<SignedInfo>",
+            &format!("<SignedInfo xmlns=\"{}\">", DSIG_NS),
+            1,
+        )
+    }
+
+    #[test]
+    fn signs_packages_and_keeps_earlier_signatures() {
+        let parts = vec![
+            (
+                CONTENT_TYPES_PART.to_string(),
+                br#"<?xml version="1.0"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#.to_vec(),
+            ),
+            (
+                PACKAGE_RELS_PART.to_string(),
+                br#"<?xml version="1.0"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#.to_vec(),
+            ),
+            (
+                "word/document.xml".to_string(),
+                b"<w:document>Hello &amp; welcome</w:document>".to_vec(),
+            ),
+        ];
+        let algorithm = SignatureAlgorithm::EcdsaP256Sha256;
+        let (private_der, public_der) = known_answer_key_pair(&algorithm).unwrap();
+        let signed_at = Utc::now();
+        let once = sign_package(
+            parts,
+            &algorithm,
+            &private_der,
+            "Alice",
+            "Approved",
+            signed_at,
+        )
+        .unwrap();
+        let package = read_package(&write_package(&once).unwrap()).unwrap();
+        let twice = sign_package(package, &algorithm, &private_der, "Bob", "", signed_at).unwrap();
+
+        let part = |name: &str| {
+            let (_, data) = twice.iter().find(|(n, _)| n == name).unwrap();
+            String::from_utf8(data.clone()).unwrap()
+        };
+        for signature in ["_xmlsignatures/sig1.xml", "_xmlsignatures/sig2.xml"] {
+            let xml = part(signature);
+            let value = xml
+                .split("<SignatureValue>")
+                .nth(1)
+                .and_then(|rest| rest.split("</SignatureValue>").next())
+                .unwrap();
+            assert!(verify_bytes(
+                &algorithm,
+                &public_der,
+                signed_info(&xml).as_bytes(),
+                &BASE64.decode(value).unwrap(),
+                DigestScheme::Standard,
+            )
+            .unwrap()
+            .is_ok());
+        }
+        // 第二个签名只增加关系，原有签名的清单（经关系变换）保持不变
+        let origin_rels =
+            parse_relationships(part("_xmlsignatures/_rels/origin.sigs.rels").as_bytes()).unwrap();
+        assert_eq!(origin_rels.len(), 2);
+        assert_eq!(
+            parse_relationships(part(PACKAGE_RELS_PART).as_bytes())
+                .unwrap()
+                .iter()
+                .filter(|r| r.rel_type == ORIGIN_REL_TYPE)
+                .count(),
+            1
+        );
+        let manifest = |xml: &str| {
+            xml.split("<Manifest>")
+                .nth(1)
+                .unwrap()
+                .split("</Manifest>")
+                .next()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            manifest(&part("_xmlsignatures/sig1.xml")),
+            manifest(&part("_xmlsignatures/sig2.xml"))
+        );
+        assert!(part(CONTENT_TYPES_PART).contains("/_xmlsignatures/sig2.xml"));
+    }
+}
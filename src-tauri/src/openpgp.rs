@@ -0,0 +1,387 @@
+// src-tauri/src/openpgp.rs
+// Minimal OpenPGP (RFC 4880 / RFC 9580) support. Reading covers ASCII armor, packet
+// framing and the descriptive fields of signature packets, enough to import public keys
+// and to describe signatures to the user; sigust does not verify OpenPGP signatures.
+// Writing covers v4 public keys and v4 signatures with SHA-256, so that a document signed
+// with one of the user's keys can be checked with gpg (see verification_kit.rs).
+use crate::crypto_types::SignatureAlgorithm;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use pkcs8::DecodePublicKey;
+use rsa::traits::PublicKeyParts;
+use sha1::{Digest, Sha1};
+
+/// OpenPGP OIDs of the curves sigust can use.
+pub(crate) const OID_P256: [u8; 8] = [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+pub(crate) const OID_ED25519: [u8; 9] = [0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x0f, 0x01];
+/// Signature over a binary document.
+pub(crate) const SIGNATURE_BINARY: u8 = 0x00;
+/// Self-signature binding a user ID to its key.
+pub(crate) const SIGNATURE_POSITIVE_CERTIFICATION: u8 = 0x13;
+const HASH_SHA256: u8 = 8;
+/// Key flags of a key that certifies its user IDs and signs data.
+const KEY_FLAGS_CERTIFY_SIGN: u8 = 0x03;
+
+/// A v4 public key packet body with its algorithm ID and fingerprint.
+pub(crate) struct PublicKeyPacket {
+    pub body: Vec<u8>,
+    pub algorithm: u8,
+    pub fingerprint: [u8; 20],
+}
+
+impl PublicKeyPacket {
+    /// Encodes an SPKI public key as a v4 key created at `created_at`. The creation time
+    /// is part of the fingerprint, so the same time gives the same OpenPGP key every time.
+    pub(crate) fn new(
+        algorithm: &SignatureAlgorithm,
+        public_key_der: &[u8],
+        created_at: DateTime<Utc>,
+    ) -> Result<Self> {
+        let mut body = vec![4];
+        body.extend_from_slice(&openpgp_time(created_at)?.to_be_bytes());
+        let algorithm = match algorithm {
+            SignatureAlgorithm::RsaPkcs1Sha256 => {
+                let key = rsa::RsaPublicKey::from_public_key_der(public_key_der)
+                    .context("Failed to parse SPKI DER as RSA public key")?;
+                body.push(1);
+                body.extend(mpi(&key.n().to_bytes_be()));
+                body.extend(mpi(&key.e().to_bytes_be()));
+                1
+            }
+            SignatureAlgorithm::EcdsaP256Sha256 => {
+                let key = p256::PublicKey::from_public_key_der(public_key_der)
+                    .context("Failed to parse SPKI DER as ECDSA P-256 public key")?;
+                body.push(19);
+                body.push(OID_P256.len() as u8);
+                body.extend_from_slice(&OID_P256);
+                body.extend(mpi(key.to_encoded_point(false).as_bytes()));
+                19
+            }
+            SignatureAlgorithm::EcdsaP384Sha384
+            | SignatureAlgorithm::EcdsaP521Sha512
+            | SignatureAlgorithm::MlDsa65 => {
+                bail!("OpenPGP keys can only be made from RSA, ECDSA P-256 and Ed25519 keys")
+            }
+            SignatureAlgorithm::Ed25519 => {
+                let key = ed25519_dalek::VerifyingKey::from_public_key_der(public_key_der)
+                    .context("Failed to parse SPKI DER as Ed25519 public key")?;
+                body.push(22);
+                body.push(OID_ED25519.len() as u8);
+                body.extend_from_slice(&OID_ED25519);
+                // 点编码带 0x40 前缀
+                let mut point = vec![0x40];
+                point.extend_from_slice(key.as_bytes());
+                body.extend(mpi(&point));
+                22
+            }
+        };
+        let mut packet = PublicKeyPacket {
+            body,
+            algorithm,
+            fingerprint: [0; 20],
+        };
+        packet.fingerprint = Sha1::digest(packet.hash_prefix()).into();
+        Ok(packet)
+    }
+
+    /// The key ID: the last 8 bytes of the fingerprint.
+    pub(crate) fn key_id(&self) -> &[u8] {
+        &self.fingerprint[12..]
+    }
+
+    /// What the key contributes to the hash of a signature over it (and the fingerprint).
+    pub(crate) fn hash_prefix(&self) -> Vec<u8> {
+        let mut prefix = vec![0x99];
+        prefix.extend_from_slice(&(self.body.len() as u16).to_be_bytes());
+        prefix.extend_from_slice(&self.body);
+        prefix
+    }
+}
+
+/// Descriptive fields of a signature packet (tag 2).
+pub(crate) struct SignaturePacket {
+    pub version: u8,
+    pub public_key_algorithm: u8,
+    pub hash_algorithm: u8,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Issuer key ID, uppercase hex.
+    pub issuer_key_id: Option<String>,
+    /// Issuer fingerprint, uppercase hex.
+    pub issuer_fingerprint: Option<String>,
+}
+
+// --- Helper Functions ---
+
+/// Decodes the body of an ASCII-armored block (headers and CRC line skipped).
+pub(crate) fn decode_armor(text: &str) -> Result<Vec<u8>> {
+    let mut body = String::new();
+    let mut in_body = false;
+    for line in text.trim().lines().skip(1).map(str::trim) {
+        if line.starts_with("-----END") || (in_body && line.starts_with('=')) {
+            break;
+        }
+        if in_body {
+            body.push_str(line);
+        } else if line.is_empty() {
+            in_body = true;
+        }
+    }
+    BASE64.decode(body).context("Malformed OpenPGP armor")
+}
+
+/// Reads one packet, returning `(tag, body, rest)`.
+pub(crate) fn next_packet(data: &[u8]) -> Result<(u8, &[u8], &[u8])> {
+    let truncated = || anyhow!("Truncated OpenPGP packet");
+    let header = *data.first().ok_or_else(truncated)?;
+    if header & 0x80 == 0 {
+        bail!("Malformed OpenPGP packet header");
+    }
+    let (tag, header_len, body_len) = if header & 0x40 != 0 {
+        // 新格式
+        let tag = header & 0x3f;
+        match *data.get(1).ok_or_else(truncated)? {
+            len @ 0..=191 => (tag, 2, len as usize),
+            first @ 192..=223 => {
+                let second = *data.get(2).ok_or_else(truncated)? as usize;
+                (tag, 3, ((first as usize - 192) << 8) + second + 192)
+            }
+            255 => {
+                let len = data.get(2..6).ok_or_else(truncated)?;
+                (tag, 6, u32::from_be_bytes(len.try_into()?) as usize)
+            }
+            _ => bail!("Partial-length OpenPGP packets are not supported"),
+        }
+    } else {
+        // 旧格式
+        let tag = (header >> 2) & 0x0f;
+        match header & 0x03 {
+            0 => (tag, 2, *data.get(1).ok_or_else(truncated)? as usize),
+            1 => {
+                let len = data.get(1..3).ok_or_else(truncated)?;
+                (tag, 3, u16::from_be_bytes(len.try_into()?) as usize)
+            }
+            2 => {
+                let len = data.get(1..5).ok_or_else(truncated)?;
+                (tag, 5, u32::from_be_bytes(len.try_into()?) as usize)
+            }
+            _ => (tag, 1, data.len() - 1),
+        }
+    };
+    let body = data
+        .get(header_len..header_len + body_len)
+        .ok_or_else(truncated)?;
+    Ok((tag, body, &data[header_len + body_len..]))
+}
+
+/// Finds and parses the first signature packet in `data`.
+pub(crate) fn find_signature_packet(data: &[u8]) -> Result<SignaturePacket> {
+    let mut rest = data;
+    while !rest.is_empty() {
+        let (tag, body, next) = next_packet(rest)?;
+        if tag == 2 {
+            return parse_signature_packet(body);
+        }
+        rest = next;
+    }
+    bail!("No OpenPGP signature packet found")
+}
+
+// 签名包：v3 字段固定；v4/v6 的创建时间与签发者在子包中
+fn parse_signature_packet(body: &[u8]) -> Result<SignaturePacket> {
+    let truncated = || anyhow!("Truncated OpenPGP signature packet");
+    let version = *body.first().ok_or_else(truncated)?;
+    if version == 3 {
+        let fields = body.get(..19).ok_or_else(truncated)?;
+        let created = u32::from_be_bytes(fields[3..7].try_into()?);
+        return Ok(SignaturePacket {
+            version,
+            public_key_algorithm: fields[15],
+            hash_algorithm: fields[16],
+            created_at: DateTime::from_timestamp(created as i64, 0),
+            issuer_key_id: Some(hex::encode_upper(&fields[7..15])),
+            issuer_fingerprint: None,
+        });
+    }
+    // v4 子包区长度 2 字节，v6 为 4 字节
+    let length_size = match version {
+        4 | 5 => 2,
+        6 => 4,
+        _ => bail!("Unsupported OpenPGP signature version: {}", version),
+    };
+    let mut packet = SignaturePacket {
+        version,
+        public_key_algorithm: *body.get(2).ok_or_else(truncated)?,
+        hash_algorithm: *body.get(3).ok_or_else(truncated)?,
+        created_at: None,
+        issuer_key_id: None,
+        issuer_fingerprint: None,
+    };
+    let mut offset = 4;
+    // 先读签名覆盖的子包，再读未覆盖的子包（签发者 ID 常在后者）
+    for _ in 0..2 {
+        let len_bytes = body
+            .get(offset..offset + length_size)
+            .ok_or_else(truncated)?;
+        let len = len_bytes
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        offset += length_size;
+        let area = body.get(offset..offset + len).ok_or_else(truncated)?;
+        read_subpackets(area, &mut packet)?;
+        offset += len;
+    }
+    Ok(packet)
+}
+
+fn read_subpackets(mut area: &[u8], packet: &mut SignaturePacket) -> Result<()> {
+    let truncated = || anyhow!("Truncated OpenPGP signature subpacket");
+    while !area.is_empty() {
+        let (header_len, len) = match area[0] {
+            first @ 0..=191 => (1, first as usize),
+            first @ 192..=254 => {
+                let second = *area.get(1).ok_or_else(truncated)? as usize;
+                (2, ((first as usize - 192) << 8) + second + 192)
+            }
+            255 => {
+                let len = area.get(1..5).ok_or_else(truncated)?;
+                (5, u32::from_be_bytes(len.try_into()?) as usize)
+            }
+        };
+        let subpacket = area
+            .get(header_len..header_len + len)
+            .ok_or_else(truncated)?;
+        area = &area[header_len + len..];
+        let Some((&kind, data)) = subpacket.split_first() else {
+            continue;
+        };
+        match kind & 0x7f {
+            2 if data.len() == 4 => {
+                let created = u32::from_be_bytes(data.try_into()?);
+                packet.created_at = DateTime::from_timestamp(created as i64, 0);
+            }
+            16 if data.len() == 8 => packet.issuer_key_id = Some(hex::encode_upper(data)),
+            // 首字节为密钥版本
+            33 if data.len() > 1 => packet.issuer_fingerprint = Some(hex::encode_upper(&data[1..])),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The part of a v4 SHA-256 signature by `key` that the signature covers: type,
+/// algorithms, creation time and issuer (plus key flags on a self-signature).
+pub(crate) fn signature_hashed_part(
+    signature_type: u8,
+    key: &PublicKeyPacket,
+    created_at: DateTime<Utc>,
+) -> Result<Vec<u8>> {
+    let mut subpackets = subpacket(2, &openpgp_time(created_at)?.to_be_bytes());
+    let mut issuer = vec![4];
+    issuer.extend_from_slice(&key.fingerprint);
+    subpackets.extend(subpacket(33, &issuer));
+    if signature_type == SIGNATURE_POSITIVE_CERTIFICATION {
+        subpackets.extend(subpacket(27, &[KEY_FLAGS_CERTIFY_SIGN]));
+    }
+    let mut hashed = vec![4, signature_type, key.algorithm, HASH_SHA256];
+    hashed.extend_from_slice(&(subpackets.len() as u16).to_be_bytes());
+    hashed.extend(subpackets);
+    Ok(hashed)
+}
+
+/// The v4 trailer, hashed after the hashed part.
+pub(crate) fn signature_trailer(hashed_part: &[u8]) -> Vec<u8> {
+    let mut trailer = vec![4, 0xff];
+    trailer.extend_from_slice(&(hashed_part.len() as u32).to_be_bytes());
+    trailer
+}
+
+/// A complete signature packet: the hashed part, the issuer key ID (not covered), the
+/// first two bytes of `digest` and the signature values as MPIs.
+pub(crate) fn signature_packet(
+    hashed_part: &[u8],
+    key: &PublicKeyPacket,
+    digest: &[u8],
+    values: &[&[u8]],
+) -> Vec<u8> {
+    let unhashed = subpacket(16, key.key_id());
+    let mut body = hashed_part.to_vec();
+    body.extend_from_slice(&(unhashed.len() as u16).to_be_bytes());
+    body.extend(unhashed);
+    body.extend_from_slice(&digest[..2]);
+    for value in values {
+        body.extend(mpi(value));
+    }
+    encode_packet(2, &body)
+}
+
+/// A packet with a new-format header.
+pub(crate) fn encode_packet(tag: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0xc0 | tag];
+    match body.len() {
+        len @ 0..=191 => packet.push(len as u8),
+        len @ 192..=8383 => {
+            let len = len - 192;
+            packet.push((len >> 8) as u8 + 192);
+            packet.push(len as u8);
+        }
+        len => {
+            packet.push(0xff);
+            packet.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+// MPI：2 字节位数前缀，去掉前导零字节
+fn mpi(value: &[u8]) -> Vec<u8> {
+    let start = value.iter().position(|&b| b != 0).unwrap_or(value.len());
+    let value = &value[start..];
+    let bits = value.first().map_or(0, |&b| {
+        (value.len() - 1) * 8 + (8 - b.leading_zeros() as usize)
+    });
+    let mut encoded = (bits as u16).to_be_bytes().to_vec();
+    encoded.extend_from_slice(value);
+    encoded
+}
+
+// 子包：长度（含类型字节）、类型、数据；这里的子包都短于 192 字节
+fn subpacket(kind: u8, data: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![(data.len() + 1) as u8, kind];
+    encoded.extend_from_slice(data);
+    encoded
+}
+
+fn openpgp_time(time: DateTime<Utc>) -> Result<u32> {
+    u32::try_from(time.timestamp()).context("Time is outside the OpenPGP range")
+}
+
+/// Name of an OpenPGP public key algorithm ID.
+pub(crate) fn public_key_algorithm_name(id: u8) -> String {
+    match id {
+        1..=3 => "RSA".to_string(),
+        17 => "DSA".to_string(),
+        19 => "ECDSA".to_string(),
+        22 => "EdDSA".to_string(),
+        27 => "Ed25519".to_string(),
+        28 => "Ed448".to_string(),
+        other => format!("algorithm {}", other),
+    }
+}
+
+/// Name of an OpenPGP hash algorithm ID.
+pub(crate) fn hash_algorithm_name(id: u8) -> String {
+    match id {
+        2 => "SHA-1".to_string(),
+        8 => "SHA-256".to_string(),
+        9 => "SHA-384".to_string(),
+        10 => "SHA-512".to_string(),
+        11 => "SHA-224".to_string(),
+        12 => "SHA3-256".to_string(),
+        14 => "SHA3-512".to_string(),
+        other => format!("hash {}", other),
+    }
+}
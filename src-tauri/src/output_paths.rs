@@ -0,0 +1,193 @@
+// src-tauri/src/output_paths.rs
+// Central handling of the files commands write: naming templates for signatures whose
+// names the backend chooses (batch signing, share outbox), the collision policy for
+// existing files, and creation of missing parent directories.
+use crate::content_uris::is_document_link;
+use crate::crypto_types::{CollisionPolicy, OutputSettings, SignatureAlgorithm};
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Highest suffix tried by `CollisionPolicy::AutoNumber`.
+const MAX_AUTO_NUMBER: u32 = 9_999;
+const TEMPLATE_PLACEHOLDERS: [&str; 5] = ["{name}", "{stem}", "{ext}", "{alg}", "{date}"];
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_output_settings(
+    app_handle: tauri::AppHandle,
+    output: OutputSettings,
+) -> Result<OutputSettings, String> {
+    log::info!("Updating output settings: {:?}", output);
+    validate_template(&output.signature_file_template).map_err(|e| e.to_string())?;
+    update_settings(&app_handle, |settings| settings.output = output)
+        .map(|settings| settings.output)
+        .map_err(|e| {
+            log::error!("Failed to update output settings: {:?}", e);
+            e.to_string()
+        })
+}
+
+// --- Helper Functions ---
+
+/// Applies the configured collision policy to a path chosen by the user and creates its
+/// parent directory. Returns the path to write to; a document opened through a content
+/// URI is written in place.
+pub(crate) fn resolve_output_path(
+    app_handle: &tauri::AppHandle,
+    requested: &Path,
+) -> Result<PathBuf> {
+    if is_document_link(requested) {
+        return Ok(requested.to_path_buf());
+    }
+    let policy = output_settings(app_handle).collision_policy;
+    if let Some(parent) = requested.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create output directory: {:?}", parent))?;
+    }
+    if !requested.exists() {
+        return Ok(requested.to_path_buf());
+    }
+    match policy {
+        CollisionPolicy::Overwrite => Ok(requested.to_path_buf()),
+        CollisionPolicy::Error => bail!("Output file already exists: {:?}", requested),
+        CollisionPolicy::AutoNumber => (1..=MAX_AUTO_NUMBER)
+            .map(|n| numbered_path(requested, n))
+            .find(|candidate| !candidate.exists())
+            .ok_or_else(|| anyhow::anyhow!("No free file name for {:?}", requested)),
+    }
+}
+
+/// Path of the signature for `document_path` inside `output_dir`, named by the configured
+/// template and resolved with `resolve_output_path`.
+pub(crate) fn signature_output_path(
+    app_handle: &tauri::AppHandle,
+    output_dir: &Path,
+    document_path: &Path,
+    algorithm: &SignatureAlgorithm,
+) -> Result<PathBuf> {
+    templated_output_path(app_handle, output_dir, document_path, algorithm, None)
+}
+
+/// Like `signature_output_path`, with `template` (e.g. from a signing profile) used instead
+/// of the configured one when given.
+pub(crate) fn templated_output_path(
+    app_handle: &tauri::AppHandle,
+    output_dir: &Path,
+    document_path: &Path,
+    algorithm: &SignatureAlgorithm,
+    template: Option<&str>,
+) -> Result<PathBuf> {
+    let file_name = match template {
+        Some(template) => render_template(template, document_path, algorithm)?,
+        None => signature_file_name(app_handle, document_path, algorithm)?,
+    };
+    resolve_output_path(app_handle, &output_dir.join(file_name))
+}
+
+/// File name the configured template gives the signature of `document_path`.
+pub(crate) fn signature_file_name(
+    app_handle: &tauri::AppHandle,
+    document_path: &Path,
+    algorithm: &SignatureAlgorithm,
+) -> Result<String> {
+    let template = output_settings(app_handle).signature_file_template;
+    render_template(&template, document_path, algorithm)
+}
+
+fn output_settings(app_handle: &tauri::AppHandle) -> OutputSettings {
+    get_settings_path(app_handle)
+        .and_then(|path| read_settings(&path))
+        .map(|settings| settings.output)
+        .unwrap_or_default()
+}
+
+// 展开命名模板：{name} 文件名，{stem} 去掉扩展名，{ext} 扩展名，{alg} 算法，{date} 日期
+fn render_template(
+    template: &str,
+    document_path: &Path,
+    algorithm: &SignatureAlgorithm,
+) -> Result<String> {
+    let name_of = |s: Option<&std::ffi::OsStr>| {
+        s.map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let mut name = name_of(document_path.file_name());
+    if name.is_empty() {
+        name = "document".to_string();
+    }
+    let file_name = template
+        .replace("{name}", &name)
+        .replace("{stem}", &name_of(document_path.file_stem()))
+        .replace("{ext}", &name_of(document_path.extension()))
+        .replace("{alg}", &algorithm.to_string().to_ascii_lowercase())
+        .replace("{date}", &Utc::now().format("%Y%m%d").to_string());
+    if file_name.contains(['/', '\\']) || file_name.trim_matches('.').is_empty() {
+        bail!(
+            "Naming template produced an invalid file name: {}",
+            file_name
+        );
+    }
+    Ok(file_name)
+}
+
+/// Checks a naming template: it must name the document and use only known placeholders.
+pub(crate) fn validate_template(template: &str) -> Result<()> {
+    if !template.contains("{name}") && !template.contains("{stem}") {
+        bail!("Naming template must contain {{name}} or {{stem}}.");
+    }
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end + 1)
+            .ok_or_else(|| anyhow::anyhow!("Unclosed placeholder in naming template."))?;
+        if !TEMPLATE_PLACEHOLDERS.contains(&&rest[start..end]) {
+            bail!(
+                "Unknown placeholder in naming template: {}",
+                &rest[start..end]
+            );
+        }
+        rest = &rest[end..];
+    }
+    if template.contains(['/', '\\']) {
+        bail!("Naming template must not contain path separators.");
+    }
+    Ok(())
+}
+
+// "a.pdf.sig" -> "a.pdf (n).sig"
+fn numbered_path(path: &Path, n: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(ext) => format!("{} ({}).{}", stem, n, ext.to_string_lossy()),
+        None => format!("{} ({})", stem, n),
+    };
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_and_validates_templates() {
+        let document = Path::new("/tmp/contract.pdf");
+        let name = render_template("{stem}.{alg}.sig", document, &SignatureAlgorithm::Ed25519);
+        assert_eq!(name.unwrap(), "contract.ed25519.sig");
+        assert!(validate_template("{name}.sig").is_ok());
+        assert!(validate_template("{stem}.{bogus}.sig").is_err());
+        assert!(validate_template("{alg}.sig").is_err());
+        assert!(validate_template("{name}/x.sig").is_err());
+        assert_eq!(
+            numbered_path(Path::new("/out/contract.pdf.sig"), 2),
+            Path::new("/out/contract.pdf (2).sig")
+        );
+    }
+}
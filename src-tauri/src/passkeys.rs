@@ -0,0 +1,640 @@
+// src-tauri/src/passkeys.rs
+// Passkey-backed signing keys. The webview creates a resident WebAuthn credential (ES256,
+// i.e. ECDSA P-256) on a platform or roaming authenticator where the platform exposes
+// WebAuthn to it; the private key never leaves the authenticator and there are no key files.
+//
+// Signing is a two-step ceremony: `begin_passkey_signing` digests the document, builds the
+// envelope attributes and returns their SHA-256 as the WebAuthn challenge; the webview asks
+// the authenticator for an assertion and hands it to `finish_passkey_signing`, which checks
+// it and writes an envelope whose signature is the assertion signature (over authenticator
+// data and client data, which the envelope carries). Registration works the same way with
+// `begin_passkey_registration` / `finish_passkey_registration`.
+//
+// Passkeys are kept in `passkeys.json`, apart from the file-based keys, and are used like
+// them for verification.
+use crate::audit;
+use crate::crypto_types::{
+    DigestScheme, KeyUnlockPolicy, PasskeyChallenge, PasskeyInfo, SignatureAlgorithm, TimeSource,
+};
+use crate::envelope::{
+    canonical_json, FileBinding, SignatureEnvelope, SignedAttributes, WebAuthnAssertion,
+    DIGEST_SHA256, ENVELOPE_FORMAT, ENVELOPE_VERSION,
+};
+use crate::hashing::hash_file;
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, get_app_data_file, get_metadata_path, read_metadata,
+};
+use crate::metadata_schema::validate_metadata;
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::resolve_output_path;
+use crate::policy::{check_key_use, check_timestamping};
+use crate::signable_inputs::check_signable_input;
+use crate::signature_sequence::next_signature_sequence;
+use crate::signing::verify_bytes;
+use aead::OsRng;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use pkcs8::{DecodePublicKey, EncodePublicKey, LineEnding};
+use rsa::rand_core::RngCore;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+const PASSKEYS_FILENAME: &str = "passkeys.json";
+/// Ceremonies not finished within this time have to be started again.
+const CEREMONY_TIMEOUT_MINUTES: i64 = 5;
+const MAX_PASSKEY_NAME_LEN: usize = 128;
+/// Authenticator data flags: user present, user verified.
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+
+/// Registration and signing ceremonies waiting for the authenticator.
+static PENDING_CEREMONIES: Mutex<Vec<PendingCeremony>> = Mutex::new(Vec::new());
+
+struct PendingCeremony {
+    /// Base64url challenge handed to the webview.
+    challenge: String,
+    key_id: Uuid,
+    expires_at: DateTime<Utc>,
+    kind: Ceremony,
+}
+
+enum Ceremony {
+    Registration {
+        name: String,
+    },
+    Signing {
+        document_path: String,
+        signed: serde_json::Value,
+    },
+}
+
+/// The parts of WebAuthn `clientDataJSON` that are checked.
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_passkeys(app_handle: tauri::AppHandle) -> Result<Vec<PasskeyInfo>, String> {
+    log::info!("Listing passkeys");
+    get_passkeys_path(&app_handle)
+        .and_then(|path| read_passkeys(&path))
+        .map_err(|e| {
+            log::error!("Failed to list passkeys: {:?}", e);
+            e.to_string()
+        })
+}
+
+/// Starts registering a passkey. The webview passes the challenge and the returned key ID
+/// (as user handle) to `navigator.credentials.create` with the ES256 algorithm.
+#[tauri::command(rename_all = "camelCase")]
+pub fn begin_passkey_registration(
+    app_handle: tauri::AppHandle,
+    name: String,
+) -> Result<PasskeyChallenge, String> {
+    log::info!("Starting passkey registration for '{}'", name);
+    let name = name.trim().to_string();
+    if name.is_empty() || name.len() > MAX_PASSKEY_NAME_LEN {
+        return Err(format!(
+            "Passkey name must be non-blank and at most {} characters.",
+            MAX_PASSKEY_NAME_LEN
+        ));
+    }
+    check_key_use(&app_handle, &SignatureAlgorithm::EcdsaP256Sha256, None)
+        .and_then(|_| {
+            let mut challenge = [0u8; 32];
+            OsRng.fill_bytes(&mut challenge);
+            let challenge = URL_SAFE_NO_PAD.encode(challenge);
+            let key_id = Uuid::new_v4();
+            start_ceremony(&challenge, key_id, Ceremony::Registration { name })?;
+            Ok(PasskeyChallenge {
+                challenge,
+                key_id,
+                credential_id: None,
+                rp_id: None,
+            })
+        })
+        .map_err(|e| {
+            log::error!("Failed to start passkey registration: {:?}", e);
+            e.to_string()
+        })
+}
+
+/// Completes a registration with the new credential's ID, its `clientDataJSON` and its
+/// public key (`getPublicKey()`, SPKI DER), all base64url. `rp_id` is the relying party ID
+/// the credential was created for.
+#[tauri::command(rename_all = "camelCase")]
+pub fn finish_passkey_registration(
+    app_handle: tauri::AppHandle,
+    challenge: String,
+    credential_id: String,
+    client_data_json: String,
+    public_key: String,
+    rp_id: String,
+) -> Result<PasskeyInfo, String> {
+    log::info!("Finishing passkey registration");
+    _finish_passkey_registration(
+        &app_handle,
+        &challenge,
+        &credential_id,
+        &client_data_json,
+        &public_key,
+        &rp_id,
+    )
+    .map_err(|e| {
+        log::error!("Failed to register passkey: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|passkey| {
+        audit::record(
+            &app_handle,
+            "registerPasskey",
+            json!({ "keyId": passkey.key_id, "fingerprint": passkey.fingerprint }),
+        )
+    })
+}
+
+fn _finish_passkey_registration(
+    app_handle: &tauri::AppHandle,
+    challenge: &str,
+    credential_id: &str,
+    client_data_json: &str,
+    public_key: &str,
+    rp_id: &str,
+) -> Result<PasskeyInfo> {
+    let ceremony = take_ceremony(challenge)?;
+    let Ceremony::Registration { name } = ceremony.kind else {
+        bail!("The challenge does not belong to a passkey registration");
+    };
+    check_client_data(client_data_json, "webauthn.create", challenge)?;
+    URL_SAFE_NO_PAD
+        .decode(credential_id)
+        .context("Malformed passkey credential ID")?;
+    if rp_id.trim().is_empty() {
+        bail!("The relying party ID is missing");
+    }
+
+    // 仅支持 ES256（P-256）凭据
+    let public_key_der = URL_SAFE_NO_PAD
+        .decode(public_key)
+        .context("Malformed passkey public key")?;
+    let public_key_pem = p256::PublicKey::from_public_key_der(&public_key_der)
+        .context("The passkey is not an ES256 (P-256) credential")?
+        .to_public_key_pem(LineEnding::LF)
+        .context("Failed to encode passkey public key")?;
+
+    let path = get_passkeys_path(app_handle)?;
+    let mut passkeys = read_passkeys(&path)?;
+    if passkeys.iter().any(|p| p.credential_id == credential_id) {
+        bail!("This passkey is already registered");
+    }
+    let passkey = PasskeyInfo {
+        key_id: ceremony.key_id,
+        name,
+        credential_id: credential_id.to_string(),
+        rp_id: rp_id.to_string(),
+        public_key_pem,
+        fingerprint: compute_fingerprint(&public_key_der),
+        created_at: Utc::now(),
+    };
+    passkeys.push(passkey.clone());
+    write_passkeys(&path, &passkeys)?;
+    Ok(passkey)
+}
+
+/// Forgets a passkey. The credential itself stays on the authenticator.
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_passkey(app_handle: tauri::AppHandle, key_id: Uuid) -> Result<(), String> {
+    log::info!("Deleting passkey {}", key_id);
+    _delete_passkey(&app_handle, key_id)
+        .map_err(|e| {
+            log::error!("Failed to delete passkey {}: {:?}", key_id, e);
+            e.to_string()
+        })
+        .inspect(|_| audit::record(&app_handle, "deletePasskey", json!({ "keyId": key_id })))
+}
+
+fn _delete_passkey(app_handle: &tauri::AppHandle, key_id: Uuid) -> Result<()> {
+    for key in read_metadata(&get_metadata_path(app_handle)?)? {
+        if key.token_passkey_id == Some(key_id) {
+            bail!(
+                "Key '{}' is protected by this passkey's security key; remove the protection first",
+                key.name
+            );
+        }
+        if key.unlock_policy
+            == Some(KeyUnlockPolicy::Biometric {
+                passkey_key_id: key_id,
+            })
+        {
+            bail!(
+                "Key '{}' needs biometric confirmation with this passkey; change its unlock policy first",
+                key.name
+            );
+        }
+    }
+    let path = get_passkeys_path(app_handle)?;
+    let mut passkeys = read_passkeys(&path)?;
+    let count = passkeys.len();
+    passkeys.retain(|p| p.key_id != key_id);
+    if passkeys.len() == count {
+        bail!("Passkey with ID {} not found", key_id);
+    }
+    write_passkeys(&path, &passkeys)
+}
+
+/// Starts signing `document_path` with a passkey. The webview passes the challenge, the
+/// credential ID and the relying party ID to `navigator.credentials.get`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn begin_passkey_signing(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    document_path: String,
+    bind_file_name: Option<bool>,
+    metadata: Option<BTreeMap<String, String>>,
+) -> Result<PasskeyChallenge, String> {
+    log::info!(
+        "Starting passkey signing of '{}' with key ID {}",
+        document_path,
+        key_id
+    );
+    _begin_passkey_signing(
+        &app_handle,
+        key_id,
+        &document_path,
+        bind_file_name.unwrap_or(false),
+        metadata.unwrap_or_default(),
+    )
+    .map_err(|e| {
+        log::error!("Failed to start passkey signing: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _begin_passkey_signing(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    document_path: &str,
+    bind_file_name: bool,
+    metadata: BTreeMap<String, String>,
+) -> Result<PasskeyChallenge> {
+    check_key_use(app_handle, &SignatureAlgorithm::EcdsaP256Sha256, None)?;
+    check_timestamping(false)?;
+    validate_metadata(app_handle, &metadata)?;
+    let passkey = find_passkey(app_handle, key_id)?
+        .ok_or_else(|| anyhow!("Passkey with ID {} not found", key_id))?;
+
+    let path = native_path(document_path)?;
+    check_signable_input(app_handle, &path)?;
+    let (_, document_digest) = hash_file(&path)?;
+    let binding = if bind_file_name {
+        FileBinding::read(&path, false)?
+    } else {
+        FileBinding::default()
+    };
+    let attributes = SignedAttributes {
+        format: ENVELOPE_FORMAT.to_string(),
+        version: ENVELOPE_VERSION,
+        algorithm: SignatureAlgorithm::EcdsaP256Sha256.to_string(),
+        key_id,
+        key_fingerprint: passkey.fingerprint,
+        digest_algorithm: DIGEST_SHA256.to_string(),
+        document_digest,
+        signed_at: Utc::now(),
+        time_source: TimeSource::LocalClock,
+        time_token: None,
+        file_name: binding.file_name,
+        file_size: None,
+        file_modified_at: None,
+        previous_envelope: None,
+        metadata,
+        approval: None,
+        sequence: Some(next_signature_sequence(app_handle, key_id)?),
+        oidc_identity: None,
+        pq_cosigner: None,
+    };
+    let signed = serde_json::to_value(&attributes).context("Failed to serialize envelope")?;
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(canonical_json(&signed)?));
+    start_ceremony(
+        &challenge,
+        key_id,
+        Ceremony::Signing {
+            document_path: document_path.to_string(),
+            signed,
+        },
+    )?;
+    Ok(PasskeyChallenge {
+        challenge,
+        key_id,
+        credential_id: Some(passkey.credential_id),
+        rp_id: Some(passkey.rp_id),
+    })
+}
+
+/// Completes signing with the authenticator's assertion (credential ID, authenticator data,
+/// `clientDataJSON` and signature, all base64url) and writes the envelope to `output_path`.
+/// Returns the path of the signature.
+#[tauri::command(rename_all = "camelCase")]
+pub fn finish_passkey_signing(
+    app_handle: tauri::AppHandle,
+    challenge: String,
+    credential_id: String,
+    authenticator_data: String,
+    client_data_json: String,
+    signature: String,
+    output_path: String,
+) -> Result<String, String> {
+    log::info!("Finishing passkey signing to '{}'", output_path);
+    _finish_passkey_signing(
+        &app_handle,
+        &challenge,
+        &credential_id,
+        WebAuthnAssertion {
+            rp_id: String::new(),
+            authenticator_data,
+            client_data_json,
+        },
+        &signature,
+        &output_path,
+    )
+    .map_err(|e| {
+        log::error!("Failed to sign with passkey: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|(key_id, document_path, path)| {
+        audit::record(
+            &app_handle,
+            "signWithPasskey",
+            json!({ "documentPath": document_path, "keyId": key_id, "outputPath": path }),
+        )
+    })
+    .map(|(_, _, path)| path)
+}
+
+fn _finish_passkey_signing(
+    app_handle: &tauri::AppHandle,
+    challenge: &str,
+    credential_id: &str,
+    mut assertion: WebAuthnAssertion,
+    signature: &str,
+    output_path: &str,
+) -> Result<(Uuid, String, String)> {
+    let ceremony = take_ceremony(challenge)?;
+    let Ceremony::Signing {
+        document_path,
+        signed,
+    } = ceremony.kind
+    else {
+        bail!("The challenge does not belong to a passkey signature");
+    };
+    let passkey = find_passkey(app_handle, ceremony.key_id)?
+        .ok_or_else(|| anyhow!("Passkey with ID {} not found", ceremony.key_id))?;
+    if passkey.credential_id != credential_id {
+        bail!("The assertion comes from a different passkey");
+    }
+    assertion.rp_id = passkey.rp_id.clone();
+
+    // 签名时要求认证器验证了用户（PIN 或生物识别）
+    if !user_verified(&assertion)? {
+        bail!("The authenticator did not verify the user (PIN or biometrics)");
+    }
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .context("Malformed passkey signature")?;
+    let envelope = SignatureEnvelope {
+        signed,
+        signature: BASE64.encode(&signature),
+        timestamps: Vec::new(),
+        webauthn: Some(assertion),
+        pq_signature: None,
+    };
+    let public_key_der = decode_public_key_pem(&passkey.public_key_pem)?;
+    envelope
+        .verify_signature(&SignatureAlgorithm::EcdsaP256Sha256, &public_key_der)?
+        .map_err(|_| anyhow!("The passkey signature is not valid"))?;
+
+    let content =
+        serde_json::to_string_pretty(&envelope).context("Failed to serialize envelope")?;
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write signature file: {:?}", output_path))?;
+    log::info!(
+        "Document signed with passkey '{}'. Envelope saved to {:?}",
+        passkey.name,
+        output_path
+    );
+    Ok((passkey.key_id, document_path, path_string(&output_path)))
+}
+
+// --- Helper Functions ---
+
+/// Checks a WebAuthn assertion made over `challenge` with the passkey `public_key_der`.
+/// The outer `Result` reports malformed data, the inner one whether the signature is valid.
+pub(crate) fn verify_assertion(
+    public_key_der: &[u8],
+    assertion: &WebAuthnAssertion,
+    signature: &[u8],
+    challenge: &[u8],
+) -> Result<Result<(), signature::Error>> {
+    let authenticator_data = URL_SAFE_NO_PAD
+        .decode(&assertion.authenticator_data)
+        .context("Malformed authenticator data")?;
+    let client_data_json = URL_SAFE_NO_PAD
+        .decode(&assertion.client_data_json)
+        .context("Malformed client data")?;
+    if authenticator_data.len() < 37 {
+        bail!("Authenticator data is too short");
+    }
+    if authenticator_data[..32] != Sha256::digest(assertion.rp_id.as_bytes())[..] {
+        bail!("The assertion was made for a different relying party");
+    }
+    if authenticator_data[32] & FLAG_USER_PRESENT == 0 {
+        bail!("The authenticator did not confirm user presence");
+    }
+    check_client_data(
+        &assertion.client_data_json,
+        "webauthn.get",
+        &URL_SAFE_NO_PAD.encode(challenge),
+    )?;
+
+    // 认证器签名的是 authenticatorData || SHA-256(clientDataJSON)
+    let mut message = authenticator_data;
+    message.extend_from_slice(&Sha256::digest(&client_data_json));
+    verify_bytes(
+        &SignatureAlgorithm::EcdsaP256Sha256,
+        public_key_der,
+        &message,
+        signature,
+        DigestScheme::Standard,
+    )
+}
+
+/// Whether the authenticator verified the user (PIN or biometrics) for this assertion.
+pub(crate) fn user_verified(assertion: &WebAuthnAssertion) -> Result<bool> {
+    let authenticator_data = URL_SAFE_NO_PAD
+        .decode(&assertion.authenticator_data)
+        .context("Malformed authenticator data")?;
+    Ok(authenticator_data.len() >= 37 && authenticator_data[32] & FLAG_USER_VERIFIED != 0)
+}
+
+/// All registered passkeys, as candidate signers when verifying.
+pub(crate) fn list_passkeys_for_verification(
+    app_handle: &tauri::AppHandle,
+) -> Result<Vec<PasskeyInfo>> {
+    read_passkeys(&get_passkeys_path(app_handle)?)
+}
+
+/// Looks up a passkey by key ID.
+pub(crate) fn find_passkey(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+) -> Result<Option<PasskeyInfo>> {
+    Ok(read_passkeys(&get_passkeys_path(app_handle)?)?
+        .into_iter()
+        .find(|p| p.key_id == key_id))
+}
+
+// 校验 clientDataJSON 的类型和挑战值
+fn check_client_data(client_data_json: &str, kind: &str, challenge: &str) -> Result<()> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(client_data_json)
+        .context("Malformed client data")?;
+    let client_data: ClientData =
+        serde_json::from_slice(&bytes).context("Malformed client data")?;
+    if client_data.kind != kind {
+        bail!("Unexpected WebAuthn ceremony: {}", client_data.kind);
+    }
+    if client_data.challenge != challenge {
+        bail!("The WebAuthn response is for a different challenge");
+    }
+    Ok(())
+}
+
+// 登记等待认证器响应的流程，顺便清理过期的
+fn start_ceremony(challenge: &str, key_id: Uuid, kind: Ceremony) -> Result<()> {
+    let mut pending = PENDING_CEREMONIES
+        .lock()
+        .map_err(|_| anyhow!("Passkey ceremony lock poisoned"))?;
+    let now = Utc::now();
+    pending.retain(|c| c.expires_at > now);
+    pending.push(PendingCeremony {
+        challenge: challenge.to_string(),
+        key_id,
+        expires_at: now + Duration::minutes(CEREMONY_TIMEOUT_MINUTES),
+        kind,
+    });
+    Ok(())
+}
+
+// 取出（并移除）挑战值对应的流程；每个挑战只能用一次
+fn take_ceremony(challenge: &str) -> Result<PendingCeremony> {
+    let mut pending = PENDING_CEREMONIES
+        .lock()
+        .map_err(|_| anyhow!("Passkey ceremony lock poisoned"))?;
+    let now = Utc::now();
+    pending.retain(|c| c.expires_at > now);
+    let index = pending
+        .iter()
+        .position(|c| c.challenge == challenge)
+        .ok_or_else(|| anyhow!("The passkey request expired or was already used; start again"))?;
+    Ok(pending.remove(index))
+}
+
+// 获取通行密钥列表文件路径
+fn get_passkeys_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, PASSKEYS_FILENAME)
+}
+
+// 读取通行密钥列表
+fn read_passkeys(path: &Path) -> Result<Vec<PasskeyInfo>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).context("Failed to read passkeys file")?;
+    serde_json::from_str(&content).context("Failed to parse passkeys JSON")
+}
+
+// 写入通行密钥列表
+fn write_passkeys(path: &Path, passkeys: &[PasskeyInfo]) -> Result<()> {
+    let content =
+        serde_json::to_string_pretty(passkeys).context("Failed to serialize passkeys to JSON")?;
+    fs::write(path, content).context("Failed to write passkeys file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::signature::Signer;
+    use p256::ecdsa::{Signature, SigningKey};
+
+    #[test]
+    fn checks_webauthn_assertions() {
+        let key = SigningKey::from_slice(&[9; 32]).unwrap();
+        let public_key_der = key.verifying_key().to_public_key_der().unwrap();
+        let challenge = Sha256::digest(b"envelope attributes");
+
+        let assertion_for = |challenge: &[u8]| {
+            let mut authenticator_data = Sha256::digest(b"localhost").to_vec();
+            authenticator_data.extend_from_slice(&[
+                FLAG_USER_PRESENT | FLAG_USER_VERIFIED,
+                0,
+                0,
+                0,
+                1,
+            ]);
+            let client_data = json!({
+                "type": "webauthn.get",
+                "challenge": URL_SAFE_NO_PAD.encode(challenge),
+                "origin": "tauri://localhost",
+            })
+            .to_string();
+            let mut message = authenticator_data.clone();
+            message.extend_from_slice(&Sha256::digest(client_data.as_bytes()));
+            let signature: Signature = key.sign(&message);
+            (
+                WebAuthnAssertion {
+                    rp_id: "localhost".to_string(),
+                    authenticator_data: URL_SAFE_NO_PAD.encode(authenticator_data),
+                    client_data_json: URL_SAFE_NO_PAD.encode(client_data),
+                },
+                signature.to_der().as_bytes().to_vec(),
+            )
+        };
+
+        let (assertion, signature) = assertion_for(&challenge);
+        assert!(verify_assertion(
+            public_key_der.as_bytes(),
+            &assertion,
+            &signature,
+            &challenge
+        )
+        .unwrap()
+        .is_ok());
+
+        let (other, other_signature) = assertion_for(&Sha256::digest(b"other attributes"));
+        assert!(verify_assertion(
+            public_key_der.as_bytes(),
+            &other,
+            &other_signature,
+            &challenge
+        )
+        .is_err());
+        let mut wrong_rp = assertion.clone();
+        wrong_rp.rp_id = "example.com".to_string();
+        assert!(
+            verify_assertion(public_key_der.as_bytes(), &wrong_rp, &signature, &challenge).is_err()
+        );
+    }
+}
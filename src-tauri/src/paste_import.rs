@@ -0,0 +1,485 @@
+// src-tauri/src/paste_import.rs
+// Import of public keys and signatures pasted as text (keys often arrive via chat or
+// email rather than as files). The text type is detected automatically: public keys in
+// PEM (SPKI), OpenSSH, OpenPGP or minisign form are converted to SPKI and stored as
+// unverified contacts; signatures are staged in a secure temporary file whose path can
+// be handed to verification.
+use crate::contacts::{get_contacts_path, read_contacts, upsert_contact, CONTACTS_EXPORT_FORMAT};
+use crate::crypto_types::{
+    Contact, FileKind, KeyExchangePreview, PastedTextPreview, StagedSignature, TrustLevel,
+};
+use crate::file_classify::classify_content;
+use crate::key_exchange::encode_public_key_pem;
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, detect_public_key_algorithm,
+};
+use crate::openpgp::{decode_armor, next_packet, OID_ED25519, OID_P256};
+use crate::secure_temp::SecureTempFile;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use pkcs8::EncodePublicKey;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Pasted text longer than this is rejected.
+const MAX_PASTE_LEN: usize = 1024 * 1024;
+
+/// Signatures staged from pasted text during this session, keyed by staged ID.
+#[derive(Default)]
+pub struct PastedSignatures(Mutex<HashMap<Uuid, SecureTempFile>>);
+
+/// A public key decoded from pasted text.
+struct PastedKey {
+    format: &'static str,
+    /// Name found in the text (OpenSSH comment, OpenPGP user ID).
+    name: Option<String>,
+    public_key_der: Vec<u8>,
+}
+
+// --- Tauri Commands ---
+
+/// Detects what the pasted text is without importing anything. For public keys the
+/// preview carries the fingerprint to show before `import_pasted_public_key`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn preview_pasted_text(
+    app_handle: tauri::AppHandle,
+    text: String,
+) -> Result<PastedTextPreview, String> {
+    log::info!("Previewing pasted text ({} bytes)", text.len());
+    _preview_pasted_text(&app_handle, &text).map_err(|e| {
+        log::error!("Failed to recognize pasted text: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _preview_pasted_text(app_handle: &tauri::AppHandle, text: &str) -> Result<PastedTextPreview> {
+    let (kind, format) = classify_pasted_text(text)?;
+    if kind != FileKind::PublicKey {
+        return Ok(PastedTextPreview {
+            kind,
+            format,
+            key: None,
+        });
+    }
+    let key = decode_pasted_key(text)?;
+    let algorithm = detect_public_key_algorithm(&key.public_key_der)?;
+    let fingerprint = compute_fingerprint(&key.public_key_der);
+    let contacts = read_contacts(&get_contacts_path(app_handle)?)?;
+    Ok(PastedTextPreview {
+        kind,
+        format: Some(key.format.to_string()),
+        key: Some(KeyExchangePreview {
+            name: key.name,
+            algorithm: Some(algorithm),
+            public_key_pem: Some(encode_public_key_pem(&key.public_key_der)?),
+            existing_contact_id: contacts
+                .iter()
+                .find(|c| c.fingerprint == fingerprint)
+                .map(|c| c.contact_id),
+            fingerprint,
+        }),
+    })
+}
+
+/// Stores a pasted public key as an unverified contact. `name` overrides the name found
+/// in the text and is required if the text carries none.
+#[tauri::command(rename_all = "camelCase")]
+pub fn import_pasted_public_key(
+    app_handle: tauri::AppHandle,
+    text: String,
+    name: Option<String>,
+) -> Result<Contact, String> {
+    log::info!("Importing pasted public key");
+    _import_pasted_public_key(&app_handle, &text, name).map_err(|e| {
+        log::error!("Failed to import pasted public key: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _import_pasted_public_key(
+    app_handle: &tauri::AppHandle,
+    text: &str,
+    name: Option<String>,
+) -> Result<Contact> {
+    check_paste_len(text)?;
+    let key = decode_pasted_key(text)?;
+    let name = name
+        .or(key.name)
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| anyhow!("A name is required for this key"))?;
+    let algorithm = detect_public_key_algorithm(&key.public_key_der)?;
+    let contact = upsert_contact(
+        &get_contacts_path(app_handle)?,
+        name,
+        algorithm,
+        encode_public_key_pem(&key.public_key_der)?,
+        compute_fingerprint(&key.public_key_der),
+        TrustLevel::Unverified,
+    )?;
+    log::info!(
+        "Stored pasted {} key as contact {}",
+        key.format,
+        contact.contact_id
+    );
+    Ok(contact)
+}
+
+/// Writes a pasted signature to a secure temporary file for verification. The file is
+/// wiped by `discard_pasted_signature`, or at the next startup if the app exits first.
+#[tauri::command(rename_all = "camelCase")]
+pub fn stage_pasted_signature(
+    app_handle: tauri::AppHandle,
+    staged: tauri::State<'_, PastedSignatures>,
+    text: String,
+) -> Result<StagedSignature, String> {
+    log::info!("Staging pasted signature ({} bytes)", text.len());
+    _stage_pasted_signature(&app_handle, &staged, &text).map_err(|e| {
+        log::error!("Failed to stage pasted signature: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _stage_pasted_signature(
+    app_handle: &tauri::AppHandle,
+    staged: &PastedSignatures,
+    text: &str,
+) -> Result<StagedSignature> {
+    let (kind, format) = classify_pasted_text(text)?;
+    if matches!(kind, FileKind::PublicKey | FileKind::BackupArchive) {
+        bail!("Pasted text is not a signature");
+    }
+    let (file, mut target) = SecureTempFile::create(app_handle)?;
+    target
+        .write_all(text.trim().as_bytes())
+        .and_then(|_| target.write_all(b"\n"))
+        .context("Failed to write staged signature")?;
+    let signature = StagedSignature {
+        staged_id: Uuid::new_v4(),
+        path: file.path().to_string_lossy().into_owned(),
+        kind,
+        format,
+    };
+    lock_staged(staged)?.insert(signature.staged_id, file);
+    Ok(signature)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn discard_pasted_signature(
+    staged: tauri::State<'_, PastedSignatures>,
+    staged_id: Uuid,
+) -> Result<(), String> {
+    log::info!("Discarding pasted signature {}", staged_id);
+    lock_staged(&staged)
+        .and_then(|mut staged| {
+            staged
+                .remove(&staged_id)
+                .map(drop)
+                .ok_or_else(|| anyhow!("No pasted signature with ID {}", staged_id))
+        })
+        .map_err(|e| {
+            log::error!("Failed to discard pasted signature {}: {:?}", staged_id, e);
+            e.to_string()
+        })
+}
+
+// --- Helper Functions ---
+
+// 识别粘贴文本的类型；无法识别的文本（会被归为文档）直接拒绝
+fn classify_pasted_text(text: &str) -> Result<(FileKind, Option<String>)> {
+    check_paste_len(text)?;
+    match classify_content(text.trim().as_bytes(), "") {
+        (FileKind::Document, _) => bail!("Pasted text is not a recognized public key or signature"),
+        (FileKind::PublicKey, Some(format)) if format == CONTACTS_EXPORT_FORMAT => {
+            bail!("Contacts exports must be imported as a file")
+        }
+        classified => Ok(classified),
+    }
+}
+
+fn check_paste_len(text: &str) -> Result<()> {
+    if text.len() > MAX_PASTE_LEN {
+        bail!("Pasted text is too long");
+    }
+    Ok(())
+}
+
+// 按格式解析公钥，统一转换为 SPKI DER
+fn decode_pasted_key(text: &str) -> Result<PastedKey> {
+    let text = text.trim();
+    if text.starts_with("-----BEGIN PUBLIC KEY-----") {
+        return Ok(PastedKey {
+            format: "pem",
+            name: None,
+            public_key_der: decode_public_key_pem(text)?,
+        });
+    }
+    if text.starts_with("-----BEGIN PGP PUBLIC KEY BLOCK-----") {
+        return decode_openpgp_key(text);
+    }
+    if let Some(comment) = text.strip_prefix("untrusted comment:") {
+        return decode_minisign_key(comment);
+    }
+    decode_openssh_key(text)
+}
+
+// "ssh-ed25519 AAAA... comment"
+fn decode_openssh_key(text: &str) -> Result<PastedKey> {
+    let mut fields = text.split_whitespace();
+    let key_type = fields.next().unwrap_or_default();
+    let blob = BASE64
+        .decode(fields.next().unwrap_or_default())
+        .context("Malformed OpenSSH public key")?;
+    let comment = fields.collect::<Vec<_>>().join(" ");
+
+    let mut reader = SshReader(&blob);
+    if reader.string()? != key_type.as_bytes() {
+        bail!("OpenSSH key type does not match its encoded data");
+    }
+    let public_key_der = match key_type {
+        "ssh-ed25519" => ed25519_spki(reader.string()?)?,
+        "ssh-rsa" => {
+            let e = reader.string()?;
+            let n = reader.string()?;
+            rsa_spki(n, e)?
+        }
+        "ecdsa-sha2-nistp256" => {
+            if reader.string()? != b"nistp256" {
+                bail!("Unsupported OpenSSH ECDSA curve");
+            }
+            p256_spki(reader.string()?)?
+        }
+        "ecdsa-sha2-nistp384" => {
+            if reader.string()? != b"nistp384" {
+                bail!("Unsupported OpenSSH ECDSA curve");
+            }
+            p384::PublicKey::from_sec1_bytes(reader.string()?)
+                .context("Invalid P-384 public key")?
+                .to_public_key_der()?
+                .into_vec()
+        }
+        "ecdsa-sha2-nistp521" => {
+            if reader.string()? != b"nistp521" {
+                bail!("Unsupported OpenSSH ECDSA curve");
+            }
+            p521::PublicKey::from_sec1_bytes(reader.string()?)
+                .context("Invalid P-521 public key")?
+                .to_public_key_der()?
+                .into_vec()
+        }
+        other => bail!("Unsupported OpenSSH key type: {}", other),
+    };
+    Ok(PastedKey {
+        format: "ssh",
+        name: Some(comment).filter(|c| !c.is_empty()),
+        public_key_der,
+    })
+}
+
+// minisign 公钥：第二行为 base64("Ed" || key id (8) || Ed25519 公钥 (32))
+fn decode_minisign_key(comment: &str) -> Result<PastedKey> {
+    let encoded = comment
+        .lines()
+        .nth(1)
+        .ok_or_else(|| anyhow!("Malformed minisign public key"))?;
+    let decoded = BASE64
+        .decode(encoded.trim())
+        .context("Malformed minisign public key")?;
+    if decoded.len() != 42 || !decoded.starts_with(b"Ed") {
+        bail!("Unsupported minisign public key");
+    }
+    Ok(PastedKey {
+        format: "minisign",
+        name: None,
+        public_key_der: ed25519_spki(&decoded[10..])?,
+    })
+}
+
+// 解析 ASCII 装甲的 OpenPGP 公钥块，取主密钥和第一个用户 ID
+fn decode_openpgp_key(text: &str) -> Result<PastedKey> {
+    let data = decode_armor(text)?;
+
+    let mut public_key_der = None;
+    let mut name = None;
+    let mut rest = data.as_slice();
+    while !rest.is_empty() {
+        let (tag, packet, next) = next_packet(rest)?;
+        rest = next;
+        match tag {
+            6 if public_key_der.is_none() => public_key_der = Some(decode_pgp_public_key(packet)?),
+            13 if name.is_none() => {
+                let user_id = String::from_utf8_lossy(packet);
+                // "Name <email>" 只取名字部分
+                let display = user_id.split(" <").next().unwrap_or_default().trim();
+                name = Some(
+                    if display.is_empty() {
+                        user_id.trim()
+                    } else {
+                        display
+                    }
+                    .to_string(),
+                );
+            }
+            _ => {}
+        }
+    }
+    Ok(PastedKey {
+        format: "openpgp",
+        name,
+        public_key_der: public_key_der
+            .ok_or_else(|| anyhow!("No public key found in the OpenPGP block"))?,
+    })
+}
+
+// 公钥包（v4 / v6）：RSA、ECDSA P-256 与 Ed25519
+fn decode_pgp_public_key(packet: &[u8]) -> Result<Vec<u8>> {
+    let truncated = || anyhow!("Truncated OpenPGP public key");
+    let version = *packet.first().ok_or_else(truncated)?;
+    // 版本(1) + 创建时间(4) + 算法(1)，v6 另有 4 字节密钥材料长度
+    let material_start = match version {
+        4 => 6,
+        6 => 10,
+        _ => bail!("Unsupported OpenPGP key version: {}", version),
+    };
+    let algorithm = *packet.get(5).ok_or_else(truncated)?;
+    let mut reader = PgpReader(packet.get(material_start..).ok_or_else(truncated)?);
+    match algorithm {
+        1..=3 => {
+            let n = reader.mpi()?;
+            let e = reader.mpi()?;
+            rsa_spki(n, e)
+        }
+        19 => {
+            if reader.oid()? != OID_P256 {
+                bail!("Unsupported OpenPGP ECDSA curve");
+            }
+            p256_spki(reader.mpi()?)
+        }
+        22 => {
+            if reader.oid()? != OID_ED25519 {
+                bail!("Unsupported OpenPGP EdDSA curve");
+            }
+            // 点编码带 0x40 前缀
+            match reader.mpi()? {
+                [0x40, point @ ..] => ed25519_spki(point),
+                _ => bail!("Malformed OpenPGP Ed25519 key"),
+            }
+        }
+        27 => ed25519_spki(reader.take(32)?),
+        other => bail!("Unsupported OpenPGP public key algorithm: {}", other),
+    }
+}
+
+fn ed25519_spki(public_key: &[u8]) -> Result<Vec<u8>> {
+    let bytes: [u8; 32] = public_key
+        .try_into()
+        .map_err(|_| anyhow!("Ed25519 public keys are 32 bytes"))?;
+    let key =
+        ed25519_dalek::VerifyingKey::from_bytes(&bytes).context("Invalid Ed25519 public key")?;
+    Ok(key.to_public_key_der()?.into_vec())
+}
+
+pub(crate) fn rsa_spki(n: &[u8], e: &[u8]) -> Result<Vec<u8>> {
+    let key = rsa::RsaPublicKey::new(
+        rsa::BigUint::from_bytes_be(n),
+        rsa::BigUint::from_bytes_be(e),
+    )
+    .context("Invalid RSA public key")?;
+    Ok(key.to_public_key_der()?.into_vec())
+}
+
+pub(crate) fn p256_spki(sec1_point: &[u8]) -> Result<Vec<u8>> {
+    let key = p256::PublicKey::from_sec1_bytes(sec1_point).context("Invalid P-256 public key")?;
+    Ok(key.to_public_key_der()?.into_vec())
+}
+
+// OpenSSH 线格式：u32 长度前缀的字符串
+struct SshReader<'a>(&'a [u8]);
+
+impl<'a> SshReader<'a> {
+    fn string(&mut self) -> Result<&'a [u8]> {
+        let truncated = || anyhow!("Truncated OpenSSH public key");
+        let len = u32::from_be_bytes(self.0.get(..4).ok_or_else(truncated)?.try_into()?) as usize;
+        let value = self.0.get(4..4 + len).ok_or_else(truncated)?;
+        self.0 = &self.0[4 + len..];
+        Ok(value)
+    }
+}
+
+// OpenPGP 密钥材料：MPI（2 字节位数前缀）与曲线 OID（1 字节长度前缀）
+struct PgpReader<'a>(&'a [u8]);
+
+impl<'a> PgpReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let value = self
+            .0
+            .get(..len)
+            .ok_or_else(|| anyhow!("Truncated OpenPGP key material"))?;
+        self.0 = &self.0[len..];
+        Ok(value)
+    }
+
+    fn mpi(&mut self) -> Result<&'a [u8]> {
+        let bits = u16::from_be_bytes(self.take(2)?.try_into()?) as usize;
+        self.take(bits.div_ceil(8))
+    }
+
+    fn oid(&mut self) -> Result<&'a [u8]> {
+        let len = self.take(1)?[0] as usize;
+        self.take(len)
+    }
+}
+
+fn lock_staged(
+    staged: &PastedSignatures,
+) -> Result<std::sync::MutexGuard<'_, HashMap<Uuid, SecureTempFile>>> {
+    staged
+        .0
+        .lock()
+        .map_err(|_| anyhow!("Pasted signature lock poisoned"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_openssh_and_openpgp_ed25519_keys() {
+        let point = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32])
+            .verifying_key()
+            .to_bytes();
+        let spki = ed25519_spki(&point).unwrap();
+
+        let mut blob = Vec::new();
+        for part in [&b"ssh-ed25519"[..], &point] {
+            blob.extend_from_slice(&(part.len() as u32).to_be_bytes());
+            blob.extend_from_slice(part);
+        }
+        let ssh = format!("ssh-ed25519 {} alice@laptop", BASE64.encode(&blob));
+        let key = decode_pasted_key(&ssh).unwrap();
+        assert_eq!(key.public_key_der, spki);
+        assert_eq!(key.name.as_deref(), Some("alice@laptop"));
+
+        // v4 EdDSA 公钥包 + 用户 ID 包
+        let mut packet = vec![4, 0, 0, 0, 0, 22, OID_ED25519.len() as u8];
+        packet.extend_from_slice(&OID_ED25519);
+        packet.extend_from_slice(&263u16.to_be_bytes());
+        packet.push(0x40);
+        packet.extend_from_slice(&point);
+        let user_id = b"Alice <alice@example.com>";
+        let mut data = vec![0xc6, packet.len() as u8];
+        data.extend_from_slice(&packet);
+        data.extend_from_slice(&[0xcd, user_id.len() as u8]);
+        data.extend_from_slice(user_id);
+        let armored = format!(
+            "-----BEGIN PGP PUBLIC KEY BLOCK-----\n\n{}\n=AAAA\n-----END PGP PUBLIC KEY BLOCK-----\n",
+            BASE64.encode(&data)
+        );
+        let key = decode_pasted_key(&armored).unwrap();
+        assert_eq!(key.public_key_der, spki);
+        assert_eq!(key.name.as_deref(), Some("Alice"));
+    }
+}
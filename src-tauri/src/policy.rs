@@ -0,0 +1,365 @@
+// src-tauri/src/policy.rs
+// Enterprise policy for managed deployments. An administrator places a signed policy file
+// (`policy.json`) and the public key it is signed with (`policy_signer.pem`) in a
+// machine-wide directory that users cannot write to:
+//
+//   Windows: %ProgramData%\sigust    macOS: /Library/Application Support/sigust
+//   Linux:   /etc/sigust
+//
+// Debug builds also honor SIGUST_POLICY_DIR; release builds never take the directory from
+// the environment, since any user could point it at an empty folder and run unmanaged.
+//
+// The policy restricts algorithms and RSA key sizes and the files that may be signed, can
+// require RFC 3161 time-stamps on signatures, forbid private keys from leaving the device (vaults, backups, sync) and
+// require a working audit log. A policy file that is present but cannot be verified locks
+// key use until it is fixed, rather than silently running unmanaged.
+//
+// Verification-only mode (kiosks, receiving desks) turns off everything that involves
+// private keys: generation, import and signing. It is enabled by the policy or, for
+// dedicated builds, by the `verify-only` cargo feature.
+use crate::audit;
+use crate::crypto_types::{
+    DigestScheme, EnterprisePolicy, PolicyStatus, SignableInputRules, SignatureAlgorithm,
+};
+use crate::envelope::canonical_json;
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, detect_public_key_algorithm,
+};
+use crate::signing::verify_bytes;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+const POLICY_FILENAME: &str = "policy.json";
+const POLICY_SIGNER_FILENAME: &str = "policy_signer.pem";
+#[cfg(debug_assertions)]
+const POLICY_DIR_ENV: &str = "SIGUST_POLICY_DIR";
+const POLICY_FORMAT: &str = "sigust-policy";
+const POLICY_VERSION: u32 = 1;
+
+/// Policy loaded at startup (or by `reload_enterprise_policy`).
+static POLICY: Mutex<Option<PolicyStatus>> = Mutex::new(None);
+
+/// A policy file: the body plus a base64 signature over its canonical JSON form.
+#[derive(Deserialize)]
+struct SignedPolicyFile {
+    signed: serde_json::Value,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PolicyBody {
+    format: String,
+    version: u32,
+    #[serde(default)]
+    organization: Option<String>,
+    issued_at: DateTime<Utc>,
+    rules: EnterprisePolicy,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_enterprise_policy() -> Result<PolicyStatus, String> {
+    log::info!("Getting enterprise policy status");
+    current_status().map_err(|e| e.to_string())
+}
+
+/// Reads the policy file again, e.g. after an administrator deployed a new one.
+#[tauri::command(rename_all = "camelCase")]
+pub fn reload_enterprise_policy() -> Result<PolicyStatus, String> {
+    log::info!("Reloading enterprise policy");
+    let status = load_and_record();
+    if let Some(error) = &status.error {
+        return Err(error.clone());
+    }
+    Ok(status)
+}
+
+// --- Helper Functions ---
+
+/// Loads the policy and makes it the one enforced by the checks below.
+pub fn load_and_record() -> PolicyStatus {
+    let status = load_policy(&policy_dir());
+    match &status.error {
+        Some(error) => log::error!("Enterprise policy is invalid, key use is locked: {}", error),
+        None if status.managed => log::info!(
+            "Enterprise policy of {} loaded",
+            status
+                .organization
+                .as_deref()
+                .unwrap_or("an unnamed organization")
+        ),
+        None => log::debug!("No enterprise policy installed"),
+    }
+    match POLICY.lock() {
+        Ok(mut policy) => *policy = Some(status.clone()),
+        Err(_) => log::error!("Enterprise policy lock poisoned"),
+    }
+    status
+}
+
+/// Whether this installation is in verification-only mode (see module docs).
+pub(crate) fn verification_only() -> bool {
+    current_status().map_or(cfg!(feature = "verify-only"), |status| {
+        status.verification_only
+    })
+}
+
+/// Checks that private keys may be held on this device (generation, import, signing).
+pub(crate) fn check_private_keys_allowed() -> Result<()> {
+    if verification_only() {
+        bail!("This installation is verification-only; it cannot generate, import or use private keys");
+    }
+    Ok(())
+}
+
+/// Checks that a key may be generated or used: its algorithm must be allowed and RSA keys
+/// must be large enough. Also enforces the audit log requirement, since every key use is
+/// audited.
+pub(crate) fn check_key_use(
+    app_handle: &tauri::AppHandle,
+    algorithm: &SignatureAlgorithm,
+    rsa_bits: Option<usize>,
+) -> Result<()> {
+    check_private_keys_allowed()?;
+    let Some(rules) = active_rules()? else {
+        return Ok(());
+    };
+    if let Some(allowed) = &rules.allowed_algorithms {
+        if !algorithm_allowed(allowed, algorithm) {
+            bail!("The enterprise policy does not allow {} keys", algorithm);
+        }
+    }
+    if let (Some(min_bits), Some(bits)) = (rules.min_rsa_key_bits, rsa_bits) {
+        if bits < min_bits {
+            bail!(
+                "The enterprise policy requires RSA keys of at least {} bits (this key has {})",
+                min_bits,
+                bits
+            );
+        }
+    }
+    if rules.require_audit_log {
+        audit::ensure_writable(app_handle)
+            .context("The enterprise policy requires a working audit log")?;
+    }
+    Ok(())
+}
+
+// 策略文件里的算法名可以用任何 FromStr 认识的写法（"Ed25519"、"ECDSA-P256" 等），
+// 按枚举值比较；无法识别的条目不放行任何算法。
+fn algorithm_allowed(allowed: &[String], algorithm: &SignatureAlgorithm) -> bool {
+    allowed
+        .iter()
+        .filter_map(|entry| SignatureAlgorithm::from_str(entry).ok())
+        .any(|entry| entry == *algorithm)
+}
+
+/// Checks the time-stamping requirement for a signature about to be created.
+pub(crate) fn check_timestamping(timestamped: bool) -> Result<()> {
+    match active_rules()? {
+        Some(rules) if rules.require_timestamp && !timestamped => bail!(
+            "The enterprise policy requires signatures to carry a time-stamp \
+             (envelope format with a time-stamping authority)"
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that private keys may be written outside the key store (vaults, backups, sync).
+pub(crate) fn check_private_key_export() -> Result<()> {
+    match active_rules()? {
+        Some(rules) if rules.forbid_private_key_export => {
+            bail!("The enterprise policy forbids exporting private keys")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Limits the policy puts on the files that may be signed, if any.
+pub(crate) fn signable_input_rules() -> Result<Option<SignableInputRules>> {
+    Ok(active_rules()?.and_then(|rules| rules.signable_inputs))
+}
+
+// 当前策略状态；首次使用时加载
+pub(crate) fn current_status() -> Result<PolicyStatus> {
+    let mut policy = POLICY
+        .lock()
+        .map_err(|_| anyhow!("Enterprise policy lock poisoned"))?;
+    let mut status = policy
+        .get_or_insert_with(|| load_policy(&policy_dir()))
+        .clone();
+    status.verification_only |= cfg!(feature = "verify-only");
+    Ok(status)
+}
+
+// 有效策略的规则；策略文件无效时拒绝操作
+fn active_rules() -> Result<Option<EnterprisePolicy>> {
+    let status = current_status()?;
+    if let Some(error) = status.error {
+        bail!("The enterprise policy could not be loaded: {}", error);
+    }
+    Ok(status.rules)
+}
+
+fn policy_dir() -> PathBuf {
+    #[cfg(debug_assertions)]
+    if let Some(dir) = std::env::var_os(POLICY_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+    #[cfg(windows)]
+    {
+        std::env::var_os("ProgramData")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+            .join("sigust")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/sigust")
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        PathBuf::from("/etc/sigust")
+    }
+}
+
+fn load_policy(dir: &Path) -> PolicyStatus {
+    let policy_path = dir.join(POLICY_FILENAME);
+    if !policy_path.exists() {
+        return PolicyStatus::default();
+    }
+    match read_policy(&policy_path, &dir.join(POLICY_SIGNER_FILENAME)) {
+        Ok((body, signer_fingerprint)) => PolicyStatus {
+            managed: true,
+            verification_only: body.rules.verification_only,
+            organization: body.organization,
+            issued_at: Some(body.issued_at),
+            signer_fingerprint: Some(signer_fingerprint),
+            rules: Some(body.rules),
+            error: None,
+        },
+        Err(e) => PolicyStatus {
+            managed: true,
+            error: Some(format!("{:#}", e)),
+            ..Default::default()
+        },
+    }
+}
+
+// 读取并验证策略文件，返回策略内容和签名者指纹
+fn read_policy(policy_path: &Path, signer_path: &Path) -> Result<(PolicyBody, String)> {
+    let signer_pem = fs::read_to_string(signer_path)
+        .with_context(|| format!("Failed to read policy signer key {:?}", signer_path))?;
+    let signer_der = decode_public_key_pem(&signer_pem)?;
+    let signer_algorithm = detect_public_key_algorithm(&signer_der)?;
+
+    let content = fs::read(policy_path)
+        .with_context(|| format!("Failed to read policy file {:?}", policy_path))?;
+    let file: SignedPolicyFile =
+        serde_json::from_slice(&content).context("Malformed policy file")?;
+    let signature = BASE64
+        .decode(&file.signature)
+        .context("Malformed policy signature")?;
+    verify_bytes(
+        &signer_algorithm,
+        &signer_der,
+        &canonical_json(&file.signed)?,
+        &signature,
+        DigestScheme::Standard,
+    )?
+    .map_err(|_| anyhow!("The policy signature is not valid"))?;
+
+    let body: PolicyBody = serde_json::from_value(file.signed).context("Malformed policy file")?;
+    if body.format != POLICY_FORMAT || body.version != POLICY_VERSION {
+        bail!("Unsupported policy file: {} v{}", body.format, body.version);
+    }
+    for algorithm in body.rules.allowed_algorithms.iter().flatten() {
+        SignatureAlgorithm::from_str(algorithm)
+            .with_context(|| format!("Unknown algorithm in policy: {}", algorithm))?;
+    }
+    Ok((body, compute_fingerprint(&signer_der)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::sign_bytes;
+    use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey};
+    use pkcs8::LineEnding;
+    use serde_json::json;
+
+    #[test]
+    fn accepts_only_correctly_signed_policies() {
+        let dir = std::env::temp_dir().join(format!("sigust-policy-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        assert!(!load_policy(&dir).managed);
+
+        let key = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        let private_der = key.to_pkcs8_der().unwrap();
+        let public_pem = key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+        fs::write(dir.join(POLICY_SIGNER_FILENAME), public_pem).unwrap();
+
+        let signed = json!({
+            "format": POLICY_FORMAT,
+            "version": POLICY_VERSION,
+            "organization": "Example Corp",
+            "issuedAt": "2026-01-01T00:00:00Z",
+            "rules": { "allowedAlgorithms": ["Ed25519"], "requireTimestamp": true },
+        });
+        let signature = sign_bytes(
+            &SignatureAlgorithm::Ed25519,
+            private_der.as_bytes(),
+            &canonical_json(&signed).unwrap(),
+            DigestScheme::Standard,
+        )
+        .unwrap();
+        let write_policy = |signed: &serde_json::Value| {
+            let file = json!({ "signed": signed, "signature": BASE64.encode(&signature) });
+            fs::write(dir.join(POLICY_FILENAME), file.to_string()).unwrap();
+        };
+
+        write_policy(&signed);
+        let status = load_policy(&dir);
+        assert_eq!(status.error, None);
+        let rules = status.rules.unwrap();
+        assert!(rules.require_timestamp && !rules.forbid_private_key_export);
+        assert_eq!(rules.allowed_algorithms, Some(vec!["Ed25519".to_string()]));
+
+        let mut tampered = signed.clone();
+        tampered["rules"]["requireTimestamp"] = json!(false);
+        write_policy(&tampered);
+        let status = load_policy(&dir);
+        assert!(status.managed && status.rules.is_none() && status.error.is_some());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn matches_allowed_algorithms_by_value() {
+        let allowed = vec![
+            "ecdsaP256Sha256".to_string(),
+            "RSA".to_string(),
+            "bogus".to_string(),
+        ];
+        assert!(algorithm_allowed(
+            &allowed,
+            &SignatureAlgorithm::EcdsaP256Sha256
+        ));
+        assert!(algorithm_allowed(
+            &allowed,
+            &SignatureAlgorithm::RsaPkcs1Sha256
+        ));
+        assert!(!algorithm_allowed(&allowed, &SignatureAlgorithm::Ed25519));
+    }
+}
@@ -0,0 +1,108 @@
+// src-tauri/src/secure_temp.rs
+// Temporary files for potentially sensitive intermediates (staged documents, decrypted
+// data, ...). Files live in an app-private directory, are created owner-only with a random
+// name, and are overwritten with zeros and removed when the handle is dropped. Leftovers
+// from a crash are wiped at the next startup.
+//
+// Overwriting does not defeat copy-on-write or journaling filesystems or SSD wear
+// levelling; it only keeps the content from lingering in an ordinary file.
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+use uuid::Uuid;
+
+const SECURE_TEMP_DIR: &str = "secure-tmp";
+const WIPE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// A temporary file that is wiped and deleted on drop.
+pub(crate) struct SecureTempFile {
+    path: PathBuf,
+}
+
+impl SecureTempFile {
+    /// Creates an empty owner-only file and returns it together with a handle open for
+    /// writing.
+    pub(crate) fn create(app_handle: &tauri::AppHandle) -> Result<(Self, File)> {
+        let path = secure_temp_dir(app_handle)?.join(format!("{}.tmp", Uuid::new_v4()));
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options
+            .open(&path)
+            .with_context(|| format!("Failed to create temporary file: {:?}", path))?;
+        Ok((SecureTempFile { path }, file))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SecureTempFile {
+    fn drop(&mut self) {
+        if let Err(e) = wipe_file(&self.path) {
+            log::warn!("Failed to wipe temporary file {:?}: {:?}", self.path, e);
+        }
+    }
+}
+
+// --- Helper Functions ---
+
+/// Wipes temporary files left behind by a previous run (run at startup).
+pub(crate) fn clear_stale_temp_files(app_handle: &tauri::AppHandle) -> Result<usize> {
+    let dir = secure_temp_dir(app_handle)?;
+    let mut cleared = 0;
+    for entry in fs::read_dir(&dir).with_context(|| format!("Failed to read {:?}", dir))? {
+        let path = entry?.path();
+        if path.is_file() {
+            wipe_file(&path)?;
+            cleared += 1;
+        }
+    }
+    Ok(cleared)
+}
+
+// 获取应用私有的临时目录（必要时创建，仅所有者可访问）
+fn secure_temp_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    let dir = app_handle
+        .path()
+        .app_cache_dir()
+        .context("Failed to get app cache directory")?
+        .join(SECURE_TEMP_DIR);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("Failed to restrict permissions of {:?}", dir))?;
+    }
+    Ok(dir)
+}
+
+/// Overwrites a file with zeros, flushes it and removes it; a missing file is ignored.
+pub(crate) fn wipe_file(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {:?} for wiping", path))?;
+    let mut remaining = file.metadata()?.len();
+    file.seek(SeekFrom::Start(0))?;
+    let zeros = vec![0u8; WIPE_BUFFER_SIZE];
+    while remaining > 0 {
+        let chunk = remaining.min(WIPE_BUFFER_SIZE as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    fs::remove_file(path).with_context(|| format!("Failed to remove {:?}", path))
+}
@@ -0,0 +1,213 @@
+// src-tauri/src/settings.rs
+// Persistent user settings (`settings.json`) and the KDF calibration that tunes key
+// wrapping cost to the current device.
+use crate::crypto_types::{AppSettings, KdfCalibration, KeyKdf};
+use crate::key_management::get_app_data_file;
+use crate::key_wrapping::{WrapKdf, DEFAULT_PBKDF2_ITERATIONS};
+use anyhow::{Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chrono::Utc;
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const SETTINGS_FILENAME: &str = "settings.json";
+
+const MIN_TARGET_MS: u32 = 50;
+const MAX_TARGET_MS: u32 = 5_000;
+/// Calibration never recommends fewer iterations than the built-in default.
+const MIN_PBKDF2_ITERATIONS: u32 = DEFAULT_PBKDF2_ITERATIONS;
+pub(crate) const MAX_PBKDF2_ITERATIONS: u32 = 20_000_000;
+/// OWASP minimum for Argon2id; memory is only ever raised from here.
+pub(crate) const MIN_ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+pub(crate) const MAX_ARGON2_MEMORY_KIB: u32 = 1024 * 1024;
+pub(crate) const MIN_ARGON2_ITERATIONS: u32 = 2;
+pub(crate) const MAX_ARGON2_ITERATIONS: u32 = 10;
+const ARGON2_PARALLELISM: u32 = 1;
+/// Highest Argon2id parallelism accepted from a key file header; sigust itself uses 1.
+pub(crate) const MAX_ARGON2_PARALLELISM: u32 = 16;
+const BENCH_SALT: &[u8] = b"sigust-kdf-calibration";
+const BENCH_PASSWORD: &[u8] = b"correct horse battery staple";
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_settings(app_handle: tauri::AppHandle) -> Result<AppSettings, String> {
+    log::info!("Loading settings");
+    get_settings_path(&app_handle)
+        .and_then(|path| read_settings(&path))
+        .map_err(|e| {
+            log::error!("Failed to load settings: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn calibrate_kdf(
+    app_handle: tauri::AppHandle,
+    target_ms: u32,
+) -> Result<KdfCalibration, String> {
+    log::info!("Calibrating KDF parameters for a {} ms target", target_ms);
+    if !(MIN_TARGET_MS..=MAX_TARGET_MS).contains(&target_ms) {
+        return Err(format!(
+            "Target must be between {} and {} ms.",
+            MIN_TARGET_MS, MAX_TARGET_MS
+        ));
+    }
+    _calibrate_kdf(&app_handle, target_ms).map_err(|e| {
+        log::error!("Failed to calibrate KDF: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _calibrate_kdf(app_handle: &tauri::AppHandle, target_ms: u32) -> Result<KdfCalibration> {
+    let target = Duration::from_millis(target_ms as u64);
+    let (pbkdf2_iterations, pbkdf2_time) = calibrate_pbkdf2(target);
+    let (argon2_memory_kib, argon2_iterations, argon2_time) = calibrate_argon2(target)?;
+
+    let calibration = KdfCalibration {
+        target_ms,
+        pbkdf2_iterations,
+        pbkdf2_ms: pbkdf2_time.as_millis() as u64,
+        argon2_memory_kib,
+        argon2_iterations,
+        argon2_parallelism: ARGON2_PARALLELISM,
+        argon2_ms: argon2_time.as_millis() as u64,
+        calibrated_at: Utc::now(),
+    };
+    log::info!("KDF calibration result: {:?}", calibration);
+
+    update_settings(app_handle, |settings| {
+        settings.kdf_calibration = Some(calibration.clone())
+    })?;
+    Ok(calibration)
+}
+
+// --- Helper Functions ---
+
+// 获取设置文件路径
+pub fn get_settings_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, SETTINGS_FILENAME)
+}
+
+// 读取设置（文件不存在时返回默认值）
+pub fn read_settings(path: &Path) -> Result<AppSettings> {
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+    let content = fs::read_to_string(path).context("Failed to read settings file")?;
+    serde_json::from_str(&content).context("Failed to parse settings JSON")
+}
+
+// 写入设置
+pub fn write_settings(path: &Path, settings: &AppSettings) -> Result<()> {
+    let content =
+        serde_json::to_string_pretty(settings).context("Failed to serialize settings to JSON")?;
+    fs::write(path, content).context("Failed to write settings file")
+}
+
+/// Reads the settings, applies `f` and writes them back.
+pub(crate) fn update_settings(
+    app_handle: &tauri::AppHandle,
+    f: impl FnOnce(&mut AppSettings),
+) -> Result<AppSettings> {
+    let path = get_settings_path(app_handle)?;
+    let mut settings = read_settings(&path)?;
+    f(&mut settings);
+    write_settings(&path, &settings)?;
+    Ok(settings)
+}
+
+/// PBKDF2 iteration count for newly wrapped keys: the calibrated value if there is one.
+pub(crate) fn recommended_pbkdf2_iterations(app_handle: &tauri::AppHandle) -> u32 {
+    get_settings_path(app_handle)
+        .and_then(|path| read_settings(&path))
+        .ok()
+        .and_then(|settings| settings.kdf_calibration)
+        .map(|c| c.pbkdf2_iterations.max(MIN_PBKDF2_ITERATIONS))
+        .unwrap_or(DEFAULT_PBKDF2_ITERATIONS)
+}
+
+/// Parameters for newly wrapped keys using `kdf`: calibrated for this device if it was
+/// calibrated, the minimums otherwise.
+pub(crate) fn recommended_wrap_kdf(app_handle: &tauri::AppHandle, kdf: KeyKdf) -> WrapKdf {
+    match kdf {
+        KeyKdf::Pbkdf2 => WrapKdf::Pbkdf2Sha256 {
+            iterations: recommended_pbkdf2_iterations(app_handle),
+        },
+        KeyKdf::Argon2id => {
+            let calibration = get_settings_path(app_handle)
+                .and_then(|path| read_settings(&path))
+                .ok()
+                .and_then(|settings| settings.kdf_calibration);
+            WrapKdf::Argon2id {
+                memory_kib: calibration.as_ref().map_or(MIN_ARGON2_MEMORY_KIB, |c| {
+                    c.argon2_memory_kib
+                        .clamp(MIN_ARGON2_MEMORY_KIB, MAX_ARGON2_MEMORY_KIB)
+                }),
+                iterations: calibration.as_ref().map_or(MIN_ARGON2_ITERATIONS, |c| {
+                    c.argon2_iterations
+                        .clamp(MIN_ARGON2_ITERATIONS, MAX_ARGON2_ITERATIONS)
+                }),
+                parallelism: ARGON2_PARALLELISM,
+            }
+        }
+    }
+}
+
+// 测量 PBKDF2：先找到耗时足够长的样本，再按比例外推到目标耗时
+fn calibrate_pbkdf2(target: Duration) -> (u32, Duration) {
+    let mut sample_iterations: u32 = 10_000;
+    let mut elapsed = time_pbkdf2(sample_iterations);
+    while elapsed < Duration::from_millis(20) && sample_iterations < MAX_PBKDF2_ITERATIONS / 2 {
+        sample_iterations *= 2;
+        elapsed = time_pbkdf2(sample_iterations);
+    }
+    let per_iteration = elapsed.as_secs_f64() / sample_iterations as f64;
+    let iterations = (target.as_secs_f64() / per_iteration) as u64;
+    // 向下取整到 1000 的倍数，便于阅读
+    let iterations = ((iterations / 1_000) * 1_000)
+        .clamp(MIN_PBKDF2_ITERATIONS as u64, MAX_PBKDF2_ITERATIONS as u64)
+        as u32;
+    (iterations, time_pbkdf2(iterations))
+}
+
+pub(crate) fn time_pbkdf2(iterations: u32) -> Duration {
+    let mut key = [0u8; 32];
+    let start = Instant::now();
+    pbkdf2_hmac::<Sha256>(BENCH_PASSWORD, BENCH_SALT, iterations, &mut key);
+    start.elapsed()
+}
+
+// 测量 Argon2id：先在不超过一半目标耗时的前提下加大内存，再用迭代次数补足
+fn calibrate_argon2(target: Duration) -> Result<(u32, u32, Duration)> {
+    let mut memory_kib = MIN_ARGON2_MEMORY_KIB;
+    let mut elapsed = time_argon2(memory_kib, MIN_ARGON2_ITERATIONS)?;
+    while elapsed * 4 <= target && memory_kib * 2 <= MAX_ARGON2_MEMORY_KIB {
+        memory_kib *= 2;
+        elapsed = time_argon2(memory_kib, MIN_ARGON2_ITERATIONS)?;
+    }
+    let per_pass = elapsed.as_secs_f64() / MIN_ARGON2_ITERATIONS as f64;
+    let iterations = ((target.as_secs_f64() / per_pass) as u32)
+        .clamp(MIN_ARGON2_ITERATIONS, MAX_ARGON2_ITERATIONS);
+    let elapsed = if iterations == MIN_ARGON2_ITERATIONS {
+        elapsed
+    } else {
+        time_argon2(memory_kib, iterations)?
+    };
+    Ok((memory_kib, iterations, elapsed))
+}
+
+pub(crate) fn time_argon2(memory_kib: u32, iterations: u32) -> Result<Duration> {
+    let params = Params::new(memory_kib, iterations, ARGON2_PARALLELISM, Some(32))
+        .map_err(|e| anyhow::anyhow!("Invalid Argon2 parameters: {}", e))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    let mut key = [0u8; 32];
+    let start = Instant::now();
+    argon2
+        .hash_password_into(BENCH_PASSWORD, BENCH_SALT, &mut key)
+        .map_err(|e| anyhow::anyhow!("Argon2 benchmark failed: {}", e))?;
+    Ok(start.elapsed())
+}
@@ -0,0 +1,276 @@
+// src-tauri/src/share_intake.rs
+// Intake for files shared to the app from the system share sheet (Android/iOS). The share
+// target plugin hands the frontend a content URI; the backend streams it into a private
+// staging copy, signs that copy on request and writes the signature into an outbox the
+// frontend hands back to the share sheet. The staging copy is a `SecureTempFile`, wiped
+// when the share is discarded (or at the next startup).
+use crate::crypto_types::{DigestScheme, SharedItem};
+use crate::key_management::{find_key_metadata, read_private_key_der};
+use crate::output_paths::signature_output_path;
+use crate::policy::check_timestamping;
+use crate::secure_temp::SecureTempFile;
+use crate::signable_inputs::check_named_signable_input;
+use crate::signing::sign_bytes;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::Manager;
+use tauri_plugin_fs::{FilePath, FsExt, OpenOptions};
+use uuid::Uuid;
+
+/// Shared files larger than this are rejected while streaming.
+const MAX_SHARED_FILE_SIZE: u64 = 256 * 1024 * 1024;
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+const OUTBOX_DIR: &str = "outbox";
+
+/// Files received through the share sheet during this session, keyed by share ID.
+#[derive(Default)]
+pub struct ShareInbox(Mutex<HashMap<Uuid, StagedShare>>);
+
+struct StagedShare {
+    item: SharedItem,
+    staged: SecureTempFile,
+    signature_path: Option<PathBuf>,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn receive_shared_file(
+    app_handle: tauri::AppHandle,
+    inbox: tauri::State<'_, ShareInbox>,
+    uri: String,
+    display_name: Option<String>,
+) -> Result<SharedItem, String> {
+    log::info!("Receiving shared file: {}", uri);
+    _receive_shared_file(&app_handle, &inbox, &uri, display_name).map_err(|e| {
+        log::error!("Failed to receive shared file: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _receive_shared_file(
+    app_handle: &tauri::AppHandle,
+    inbox: &ShareInbox,
+    uri: &str,
+    display_name: Option<String>,
+) -> Result<SharedItem> {
+    let share_id = Uuid::new_v4();
+    let (staged, mut target) = SecureTempFile::create(app_handle)?;
+
+    let mut options = OpenOptions::new();
+    options.read(true);
+    let file_path = FilePath::from_str(uri).map_err(|e| anyhow!("Invalid URI: {:?}", e))?;
+    let mut source = app_handle
+        .fs()
+        .open(file_path, options)
+        .with_context(|| format!("Failed to open shared file: {}", uri))?;
+
+    // 出错时 staged 被丢弃，暂存文件随之擦除
+    let (size, sha256) = stream_to_file(&mut source, &mut target)?;
+
+    let display_name = display_name
+        .as_deref()
+        .map(sanitize_file_name)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| derive_display_name(uri));
+    let item = SharedItem {
+        share_id,
+        display_name,
+        size,
+        sha256,
+        received_at: Utc::now(),
+    };
+
+    lock_inbox(inbox)?.insert(
+        share_id,
+        StagedShare {
+            item: item.clone(),
+            staged,
+            signature_path: None,
+        },
+    );
+    log::info!("Staged shared file {} ({} bytes)", share_id, size);
+    Ok(item)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn sign_shared_file(
+    app_handle: tauri::AppHandle,
+    inbox: tauri::State<'_, ShareInbox>,
+    share_id: Uuid,
+    key_id: Uuid,
+    password: String,
+) -> Result<String, String> {
+    log::info!("Signing shared file {} with key ID {}", share_id, key_id);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _sign_shared_file(&app_handle, &inbox, share_id, key_id, &password)
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(|e| {
+            log::error!("Failed to sign shared file {}: {:?}", share_id, e);
+            e.to_string()
+        })
+}
+
+fn _sign_shared_file(
+    app_handle: &tauri::AppHandle,
+    inbox: &ShareInbox,
+    share_id: Uuid,
+    key_id: Uuid,
+    password: &str,
+) -> Result<PathBuf> {
+    check_timestamping(false)?;
+    let (item, staged_path) = {
+        let shares = lock_inbox(inbox)?;
+        let share = shares
+            .get(&share_id)
+            .ok_or_else(|| anyhow!("No shared file with ID {}", share_id))?;
+        (share.item.clone(), share.staged.path().to_path_buf())
+    };
+    check_named_signable_input(app_handle, &staged_path, Path::new(&item.display_name))?;
+
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let algorithm = metadata.algorithm.clone();
+    let private_key_der = read_private_key_der(app_handle, &metadata, password)?;
+
+    let document_bytes = fs::read(&staged_path)
+        .with_context(|| format!("Failed to read staged file: {:?}", staged_path))?;
+    if hex::encode(Sha256::digest(&document_bytes)) != item.sha256 {
+        bail!("Staged copy of the shared file was modified; share it again");
+    }
+    let signature_bytes = sign_bytes(
+        &algorithm,
+        &private_key_der,
+        &document_bytes,
+        DigestScheme::Standard,
+    )?;
+
+    // 每次分享单独一个子目录，避免同名文件互相覆盖
+    let outbox = get_cache_subdir(app_handle, OUTBOX_DIR)?.join(share_id.to_string());
+    fs::create_dir_all(&outbox).context("Failed to create outbox directory")?;
+    let signature_path = signature_output_path(
+        app_handle,
+        &outbox,
+        Path::new(&item.display_name),
+        &algorithm,
+    )?;
+    fs::write(&signature_path, &signature_bytes)
+        .with_context(|| format!("Failed to write signature file: {:?}", signature_path))?;
+
+    if let Some(share) = lock_inbox(inbox)?.get_mut(&share_id) {
+        share.signature_path = Some(signature_path.clone());
+    }
+    log::info!(
+        "Shared file {} signed with {}. Signature saved to {:?}",
+        share_id,
+        algorithm,
+        signature_path
+    );
+    Ok(signature_path)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn discard_shared_file(
+    inbox: tauri::State<'_, ShareInbox>,
+    share_id: Uuid,
+) -> Result<(), String> {
+    log::info!("Discarding shared file {}", share_id);
+    _discard_shared_file(&inbox, share_id).map_err(|e| {
+        log::error!("Failed to discard shared file {}: {:?}", share_id, e);
+        e.to_string()
+    })
+}
+
+fn _discard_shared_file(inbox: &ShareInbox, share_id: Uuid) -> Result<()> {
+    let share = lock_inbox(inbox)?
+        .remove(&share_id)
+        .ok_or_else(|| anyhow!("No shared file with ID {}", share_id))?;
+    // 暂存副本在 share 被丢弃时擦除
+    if let Some(outbox) = share.signature_path.as_deref().and_then(Path::parent) {
+        fs::remove_dir_all(outbox)
+            .with_context(|| format!("Failed to remove outbox directory: {:?}", outbox))?;
+    }
+    Ok(())
+}
+
+// --- Helper Functions ---
+
+// 获取缓存目录下的子目录（必要时创建）
+fn get_cache_subdir(app_handle: &tauri::AppHandle, name: &str) -> Result<PathBuf> {
+    let dir = app_handle
+        .path()
+        .app_cache_dir()
+        .context("Failed to get app cache directory")?
+        .join(name);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    Ok(dir)
+}
+
+// 获取分享暂存表的锁
+fn lock_inbox(inbox: &ShareInbox) -> Result<std::sync::MutexGuard<'_, HashMap<Uuid, StagedShare>>> {
+    inbox
+        .0
+        .lock()
+        .map_err(|_| anyhow!("Share inbox lock poisoned"))
+}
+
+// 流式复制到暂存文件，同时限制大小并计算 SHA-256
+fn stream_to_file(source: &mut impl Read, target: &mut fs::File) -> Result<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    let mut size: u64 = 0;
+    loop {
+        let read = source
+            .read(&mut buffer)
+            .context("Failed to read shared file")?;
+        if read == 0 {
+            break;
+        }
+        size += read as u64;
+        if size > MAX_SHARED_FILE_SIZE {
+            bail!(
+                "Shared file exceeds the {} MiB limit",
+                MAX_SHARED_FILE_SIZE / (1024 * 1024)
+            );
+        }
+        hasher.update(&buffer[..read]);
+        target
+            .write_all(&buffer[..read])
+            .context("Failed to write staged file")?;
+    }
+    target.sync_all().context("Failed to flush staged file")?;
+    Ok((size, hex::encode(hasher.finalize())))
+}
+
+// 从 URI 的最后一段推导文件名
+fn derive_display_name(uri: &str) -> String {
+    let last = uri
+        .trim_end_matches('/')
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default();
+    let name = sanitize_file_name(last);
+    if name.is_empty() {
+        "shared-file".to_string()
+    } else {
+        name
+    }
+}
+
+// 去掉路径分隔符和控制字符，避免写出暂存目录之外
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_control() && !matches!(c, '/' | '\\' | ':'))
+        .collect::<String>()
+        .trim()
+        .trim_start_matches('.')
+        .to_string()
+}
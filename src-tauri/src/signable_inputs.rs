@@ -0,0 +1,358 @@
+// src-tauri/src/signable_inputs.rs
+// Guards on what may be signed: a maximum file size, allowed or blocked extensions and
+// MIME types, and a confirmation for executables. The enterprise policy can set these
+// rules, and so can the user in the settings; a file has to pass both sets. The MIME type
+// is detected from the first bytes of the file (the extension only tells container
+// formats such as Office documents and JARs apart), so renaming a file does not get it
+// past a MIME rule.
+//
+// Native executables and scripts need a confirmation in a native dialog that shows the
+// file's SHA-256 digest (see `consent.rs`). The confirmation is for that digest, so a file
+// swapped after it was confirmed asks again. `preview_signable_input` lets the frontend
+// show the same details before signing starts.
+use crate::audit;
+use crate::consent::require_consent;
+use crate::crypto_types::{ConsentAction, SignableInputPreview, SignableInputRules};
+use crate::hashing::hash_file;
+use crate::policy::signable_input_rules;
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use anyhow::{bail, Context, Result};
+use serde_json::json;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read to detect the MIME type.
+const SNIFF_LEN: usize = 512;
+/// Extensions treated as executables even without a recognizable header.
+const EXECUTABLE_EXTENSIONS: [&str; 20] = [
+    "exe", "dll", "sys", "msi", "com", "scr", "bat", "cmd", "ps1", "vbs", "sh", "bash", "zsh",
+    "py", "pl", "rb", "jar", "apk", "app", "dmg",
+];
+/// MIME types of native executables and scripts.
+const EXECUTABLE_MIME_TYPES: [&str; 6] = [
+    "application/x-executable",
+    "application/vnd.microsoft.portable-executable",
+    "application/x-mach-binary",
+    "text/x-shellscript",
+    "application/java-archive",
+    "application/vnd.android.package-archive",
+];
+
+/// What the rules look at, read once per file.
+struct SignableInput {
+    size: u64,
+    extension: String,
+    mime_type: &'static str,
+    executable: bool,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_signable_input_rules(
+    app_handle: tauri::AppHandle,
+    rules: SignableInputRules,
+) -> Result<SignableInputRules, String> {
+    log::info!("Updating signable input rules: {:?}", rules);
+    let rules = normalize_rules(rules);
+    update_settings(&app_handle, |settings| settings.signable_inputs = rules)
+        .map(|settings| settings.signable_inputs)
+        .map_err(|e| {
+            log::error!("Failed to update signable input rules: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|rules| audit::record(&app_handle, "updateSignableInputRules", json!(rules)))
+}
+
+/// Size, type and digest of a file, and whether it can be signed under the current rules.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn preview_signable_input(
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> Result<SignableInputPreview, String> {
+    log::info!("Previewing signable input {}", path);
+    _preview_signable_input(&app_handle, Path::new(&path)).map_err(|e| {
+        log::error!("Failed to preview {}: {:?}", path, e);
+        e.to_string()
+    })
+}
+
+fn _preview_signable_input(
+    app_handle: &tauri::AppHandle,
+    path: &Path,
+) -> Result<SignableInputPreview> {
+    let input = read_input(path, path)?;
+    let rule_sets = rule_sets(app_handle)?;
+    let (_, sha256) = hash_file(path)?;
+    Ok(SignableInputPreview {
+        path: path.to_string_lossy().into_owned(),
+        size: input.size,
+        mime_type: input.mime_type.to_string(),
+        executable: input.executable,
+        sha256,
+        violations: rule_sets
+            .iter()
+            .flat_map(|rules| violations(rules, &input))
+            .collect(),
+        requires_confirmation: needs_confirmation(&rule_sets, &input),
+    })
+}
+
+// --- Helper Functions ---
+
+/// Checks that `path` may be signed under the policy's and the user's rules. Executables
+/// that need a confirmation fail with a "Confirmation required" error until it is given.
+pub(crate) fn check_signable_input(app_handle: &tauri::AppHandle, path: &Path) -> Result<()> {
+    check_named_signable_input(app_handle, path, path)
+}
+
+/// Like `check_signable_input`, for a file stored under another name (a staged share):
+/// the extension rules and the dialog use `name`.
+pub(crate) fn check_named_signable_input(
+    app_handle: &tauri::AppHandle,
+    path: &Path,
+    name: &Path,
+) -> Result<()> {
+    let rule_sets = rule_sets(app_handle)?;
+    if rule_sets
+        .iter()
+        .all(|rules| *rules == SignableInputRules::default())
+    {
+        return Ok(());
+    }
+    let input = read_input(path, name)?;
+    if let Some(violation) = rule_sets
+        .iter()
+        .flat_map(|rules| violations(rules, &input))
+        .next()
+    {
+        bail!("{:?} cannot be signed: {}", name, violation);
+    }
+    if needs_confirmation(&rule_sets, &input) {
+        let (_, sha256) = hash_file(path)?;
+        require_consent(
+            app_handle,
+            ConsentAction::SignExecutable,
+            &sha256,
+            format!(
+                "Sign the executable '{}' ({} bytes)?\n\nSHA-256: {}\n\nCompare this digest \
+                 with the one published for the program before you continue.",
+                name.file_name().unwrap_or_default().to_string_lossy(),
+                input.size,
+                sha256
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+// 策略规则（若有）在前，用户规则在后
+fn rule_sets(app_handle: &tauri::AppHandle) -> Result<Vec<SignableInputRules>> {
+    let user = read_settings(&get_settings_path(app_handle)?)?.signable_inputs;
+    Ok(signable_input_rules()?.into_iter().chain([user]).collect())
+}
+
+fn needs_confirmation(rule_sets: &[SignableInputRules], input: &SignableInput) -> bool {
+    input.executable && rule_sets.iter().any(|rules| rules.confirm_executables)
+}
+
+fn violations(rules: &SignableInputRules, input: &SignableInput) -> Vec<String> {
+    let mut violations = Vec::new();
+    if let Some(max) = rules.max_file_size {
+        if input.size > max {
+            violations.push(format!(
+                "the file is larger than {} bytes ({} bytes)",
+                max, input.size
+            ));
+        }
+    }
+    let extension_listed = |list: &[String]| {
+        list.iter().any(|e| {
+            e.trim_start_matches('.')
+                .eq_ignore_ascii_case(&input.extension)
+        })
+    };
+    if rules
+        .allowed_extensions
+        .as_deref()
+        .is_some_and(|allowed| !extension_listed(allowed))
+    {
+        violations.push(format!(
+            "files with the extension '{}' are not allowed",
+            input.extension
+        ));
+    }
+    if extension_listed(&rules.blocked_extensions) {
+        violations.push(format!(
+            "files with the extension '{}' are blocked",
+            input.extension
+        ));
+    }
+    let mime_listed = |list: &[String]| {
+        list.iter()
+            .any(|pattern| mime_matches(pattern, input.mime_type))
+    };
+    if rules
+        .allowed_mime_types
+        .as_deref()
+        .is_some_and(|allowed| !mime_listed(allowed))
+    {
+        violations.push(format!("{} files are not allowed", input.mime_type));
+    }
+    if mime_listed(&rules.blocked_mime_types) {
+        violations.push(format!("{} files are blocked", input.mime_type));
+    }
+    violations
+}
+
+// 支持 `type/*` 通配
+fn mime_matches(pattern: &str, mime_type: &str) -> bool {
+    let pattern = pattern.trim();
+    match pattern.strip_suffix("/*") {
+        Some(prefix) => mime_type
+            .split_once('/')
+            .is_some_and(|(top, _)| top.eq_ignore_ascii_case(prefix)),
+        None => pattern.eq_ignore_ascii_case(mime_type),
+    }
+}
+
+fn read_input(path: &Path, name: &Path) -> Result<SignableInput> {
+    let metadata = fs::metadata(path).with_context(|| format!("Failed to read {:?}", path))?;
+    if !metadata.is_file() {
+        bail!("Not a regular file: {:?}", path);
+    }
+    let mut header = Vec::with_capacity(SNIFF_LEN);
+    File::open(path)
+        .and_then(|file| file.take(SNIFF_LEN as u64).read_to_end(&mut header))
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    let extension = name
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mime_type = sniff_mime_type(&header, &extension);
+    Ok(SignableInput {
+        size: metadata.len(),
+        executable: EXECUTABLE_MIME_TYPES.contains(&mime_type)
+            || EXECUTABLE_EXTENSIONS.contains(&extension.as_str()),
+        extension,
+        mime_type,
+    })
+}
+
+// 按文件头识别常见类型；ZIP 容器再按扩展名区分
+fn sniff_mime_type(header: &[u8], extension: &str) -> &'static str {
+    const MACH_O: [[u8; 4]; 4] = [
+        [0xfe, 0xed, 0xfa, 0xce],
+        [0xfe, 0xed, 0xfa, 0xcf],
+        [0xce, 0xfa, 0xed, 0xfe],
+        [0xcf, 0xfa, 0xed, 0xfe],
+    ];
+    if header.starts_with(b"\x7fELF") {
+        "application/x-executable"
+    } else if header.starts_with(b"MZ") {
+        "application/vnd.microsoft.portable-executable"
+    } else if MACH_O.iter().any(|magic| header.starts_with(magic)) {
+        "application/x-mach-binary"
+    } else if header.starts_with(b"#!") {
+        "text/x-shellscript"
+    } else if header.starts_with(b"%PDF-") {
+        "application/pdf"
+    } else if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if header.starts_with(&[0xff, 0xd8, 0xff]) {
+        "image/jpeg"
+    } else if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if header.starts_with(b"PK\x03\x04") {
+        match extension {
+            "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+            "odt" => "application/vnd.oasis.opendocument.text",
+            "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+            "odp" => "application/vnd.oasis.opendocument.presentation",
+            "jar" => "application/java-archive",
+            "apk" => "application/vnd.android.package-archive",
+            _ => "application/zip",
+        }
+    } else if header.starts_with(b"\x1f\x8b") {
+        "application/gzip"
+    } else if std::str::from_utf8(header).is_ok()
+        // 截断处可能切开多字节字符
+        || header.len() == SNIFF_LEN && std::str::from_utf8(&header[..SNIFF_LEN - 3]).is_ok()
+    {
+        match extension {
+            "json" => "application/json",
+            "xml" => "application/xml",
+            "html" | "htm" => "text/html",
+            "csv" => "text/csv",
+            "md" => "text/markdown",
+            _ => "text/plain",
+        }
+    } else {
+        "application/octet-stream"
+    }
+}
+
+fn normalize_rules(rules: SignableInputRules) -> SignableInputRules {
+    let normalize = |list: Vec<String>, extensions: bool| {
+        list.into_iter()
+            .map(|item| {
+                let item = item.trim().to_lowercase();
+                match extensions {
+                    true => item.trim_start_matches('.').to_string(),
+                    false => item,
+                }
+            })
+            .filter(|item| !item.is_empty())
+            .collect::<Vec<_>>()
+    };
+    SignableInputRules {
+        allowed_extensions: rules.allowed_extensions.map(|list| normalize(list, true)),
+        blocked_extensions: normalize(rules.blocked_extensions, true),
+        allowed_mime_types: rules.allowed_mime_types.map(|list| normalize(list, false)),
+        blocked_mime_types: normalize(rules.blocked_mime_types, false),
+        ..rules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_size_type_and_extension_rules() {
+        let input = |header: &[u8], extension: &str| {
+            let mime_type = sniff_mime_type(header, extension);
+            SignableInput {
+                size: 2048,
+                extension: extension.to_string(),
+                mime_type,
+                executable: EXECUTABLE_MIME_TYPES.contains(&mime_type)
+                    || EXECUTABLE_EXTENSIONS.contains(&extension),
+            }
+        };
+        let pdf = input(b"%PDF-1.7\n", "pdf");
+        let renamed_binary = input(b"MZ\x90\x00", "pdf");
+        let script = input(b"echo hello\n", "ps1");
+        assert_eq!(pdf.mime_type, "application/pdf");
+        assert!(!pdf.executable && renamed_binary.executable && script.executable);
+
+        let rules = normalize_rules(SignableInputRules {
+            max_file_size: Some(4096),
+            allowed_mime_types: Some(vec!["application/pdf".into(), "Image/*".into()]),
+            blocked_extensions: vec![".PS1".into()],
+            ..Default::default()
+        });
+        assert!(violations(&rules, &pdf).is_empty());
+        assert_eq!(violations(&rules, &renamed_binary).len(), 1);
+        assert_eq!(violations(&rules, &script).len(), 2);
+        assert!(violations(&rules, &input(b"\x89PNG\r\n\x1a\n", "png")).is_empty());
+        let small = SignableInputRules {
+            max_file_size: Some(1024),
+            ..Default::default()
+        };
+        assert_eq!(violations(&small, &pdf).len(), 1);
+    }
+}
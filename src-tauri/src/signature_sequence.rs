@@ -0,0 +1,112 @@
+// src-tauri/src/signature_sequence.rs
+// Per-key signature sequence numbers. Every envelope made with a key carries the next value
+// of that key's counter in its signed attributes (`sequence`), so an auditor holding a
+// signer's record can spot missing signatures (gaps in the numbers) and backfilled ones (a
+// number whose signing time is earlier than that of a lower number).
+//
+// Counters are kept in `signature_sequences.json`, written to a temporary file that is synced
+// and then renamed over the old one, so a crash leaves either the old or the new counters.
+// A number is reserved before the signature is made: a signing that fails afterwards leaves
+// a gap instead of reusing the number, and each reservation is written to the audit log so
+// such gaps can be explained. Counters of deleted keys are kept so numbers are never reused.
+use crate::audit;
+use crate::key_management::get_app_data_file;
+use anyhow::{anyhow, Context, Result};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+const SEQUENCES_FILENAME: &str = "signature_sequences.json";
+
+/// Serializes reservations within the process.
+static SEQUENCE_LOCK: Mutex<()> = Mutex::new(());
+
+// --- Tauri Commands ---
+
+/// Returns the last sequence number issued for a key (0 if it has not signed yet).
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_signature_sequence(app_handle: tauri::AppHandle, key_id: Uuid) -> Result<u64, String> {
+    log::info!("Reading signature sequence of key {}", key_id);
+    get_sequences_path(&app_handle)
+        .and_then(|path| read_sequences(&path))
+        .map(|sequences| sequences.get(&key_id).copied().unwrap_or(0))
+        .map_err(|e| {
+            log::error!("Failed to read signature sequence: {:?}", e);
+            e.to_string()
+        })
+}
+
+// --- Helper Functions ---
+
+/// Reserves and persists the next sequence number of `key_id`.
+pub(crate) fn next_signature_sequence(app_handle: &tauri::AppHandle, key_id: Uuid) -> Result<u64> {
+    let _guard = SEQUENCE_LOCK
+        .lock()
+        .map_err(|_| anyhow!("Signature sequence lock poisoned"))?;
+    let sequence = advance_sequence(&get_sequences_path(app_handle)?, key_id)?;
+    audit::record(
+        app_handle,
+        "reserveSignatureSequence",
+        json!({ "keyId": key_id, "sequence": sequence }),
+    );
+    Ok(sequence)
+}
+
+// 获取签名序号文件路径
+fn get_sequences_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, SEQUENCES_FILENAME)
+}
+
+fn advance_sequence(path: &Path, key_id: Uuid) -> Result<u64> {
+    let mut sequences = read_sequences(path)?;
+    let counter = sequences.entry(key_id).or_insert(0);
+    *counter = counter
+        .checked_add(1)
+        .ok_or_else(|| anyhow!("Signature sequence of key {} is exhausted", key_id))?;
+    let sequence = *counter;
+    write_sequences(path, &sequences)?;
+    Ok(sequence)
+}
+
+fn read_sequences(path: &Path) -> Result<BTreeMap<Uuid, u64>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read signature sequences: {:?}", path))?;
+    serde_json::from_str(&content).context("Failed to parse signature sequences JSON")
+}
+
+// 先写入并同步临时文件，再原子替换
+fn write_sequences(path: &Path, sequences: &BTreeMap<Uuid, u64>) -> Result<()> {
+    let content = serde_json::to_string_pretty(sequences)
+        .context("Failed to serialize signature sequences")?;
+    let tmp_path = path.with_extension("json.tmp");
+    let mut file =
+        fs::File::create(&tmp_path).with_context(|| format!("Failed to write {:?}", tmp_path))?;
+    file.write_all(content.as_bytes())
+        .and_then(|_| file.sync_all())
+        .with_context(|| format!("Failed to write {:?}", tmp_path))?;
+    fs::rename(&tmp_path, path).with_context(|| format!("Failed to replace {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advances_each_key_separately() {
+        let path = std::env::temp_dir().join(format!("sigust-sequences-{}.json", Uuid::new_v4()));
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(advance_sequence(&path, first).unwrap(), 1);
+        assert_eq!(advance_sequence(&path, first).unwrap(), 2);
+        assert_eq!(advance_sequence(&path, second).unwrap(), 1);
+        assert_eq!(read_sequences(&path).unwrap()[&first], 2);
+        assert!(!path.with_extension("json.tmp").exists());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+// src-tauri/src/signed_notes.rs
+// Short signed statements ("I approve PO #123") kept inside the app. The note text is the
+// document: its SHA-256 goes into an ordinary signature envelope, which is stored with the
+// text and the signer's public key in `signed_notes.json`. Re-verifying checks the stored
+// text against the envelope, so the note stays verifiable after its key is deleted.
+use crate::audit;
+use crate::crypto_types::{SignedNote, TimeSource, VerificationResult};
+use crate::envelope::{
+    SignatureEnvelope, SignedAttributes, DIGEST_SHA256, ENVELOPE_FORMAT, ENVELOPE_VERSION,
+};
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, find_key_metadata, get_app_data_file,
+    read_private_key_der, read_public_key_pem,
+};
+use crate::policy::check_timestamping;
+use crate::signature_sequence::next_signature_sequence;
+use crate::signing::check_envelope_attributes;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+const SIGNED_NOTES_FILENAME: &str = "signed_notes.json";
+const MAX_NOTE_LEN: usize = 4096;
+
+/// A note as stored, with its envelope.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredNote {
+    #[serde(flatten)]
+    note: SignedNote,
+    envelope: SignatureEnvelope,
+}
+
+// --- Tauri Commands ---
+
+/// Signs `text` with a local key and keeps the note.
+#[tauri::command(rename_all = "camelCase")]
+pub fn create_signed_note(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    password: String,
+    text: String,
+) -> Result<SignedNote, String> {
+    log::info!("Creating signed note with key ID {}", key_id);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    check_timestamping(false).map_err(|e| e.to_string())?;
+    _create_signed_note(&app_handle, key_id, &password, text)
+        .map_err(|e| {
+            log::error!("Failed to create signed note: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|note| {
+            audit::record(
+                &app_handle,
+                "createSignedNote",
+                json!({ "noteId": note.note_id, "keyId": key_id, "digest": note.digest }),
+            )
+        })
+}
+
+fn _create_signed_note(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    password: &str,
+    text: String,
+) -> Result<SignedNote> {
+    if text.trim().is_empty() {
+        bail!("The note is empty");
+    }
+    if text.chars().count() > MAX_NOTE_LEN {
+        bail!("A note can have at most {} characters", MAX_NOTE_LEN);
+    }
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let algorithm = metadata.algorithm.clone();
+    let public_key_pem = read_public_key_pem(app_handle, &metadata)?;
+    let public_key_der = decode_public_key_pem(&public_key_pem)?;
+    let private_key_der = Zeroizing::new(read_private_key_der(app_handle, &metadata, password)?);
+
+    let digest = hex::encode(Sha256::digest(text.as_bytes()));
+    let attributes = SignedAttributes {
+        format: ENVELOPE_FORMAT.to_string(),
+        version: ENVELOPE_VERSION,
+        algorithm: algorithm.to_string(),
+        key_id,
+        key_fingerprint: compute_fingerprint(&public_key_der),
+        digest_algorithm: DIGEST_SHA256.to_string(),
+        document_digest: digest.clone(),
+        signed_at: Utc::now(),
+        time_source: TimeSource::LocalClock,
+        time_token: None,
+        file_name: None,
+        file_size: None,
+        file_modified_at: None,
+        previous_envelope: None,
+        metadata: BTreeMap::new(),
+        approval: None,
+        sequence: Some(next_signature_sequence(app_handle, key_id)?),
+        oidc_identity: None,
+        pq_cosigner: None,
+    };
+    let envelope = SignatureEnvelope::seal(&attributes, &private_key_der)?;
+
+    let note = SignedNote {
+        note_id: Uuid::new_v4(),
+        text,
+        key_id,
+        key_name: metadata.name,
+        algorithm,
+        public_key_pem,
+        digest,
+        signed_at: attributes.signed_at,
+    };
+    let path = get_signed_notes_path(app_handle)?;
+    let mut notes = read_signed_notes(&path)?;
+    notes.push(StoredNote {
+        note: note.clone(),
+        envelope,
+    });
+    write_signed_notes(&path, &notes)?;
+    Ok(note)
+}
+
+/// Lists the stored notes, newest first.
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_signed_notes(app_handle: tauri::AppHandle) -> Result<Vec<SignedNote>, String> {
+    log::info!("Listing signed notes");
+    get_signed_notes_path(&app_handle)
+        .and_then(|path| read_signed_notes(&path))
+        .map(|notes| {
+            let mut notes: Vec<SignedNote> = notes.into_iter().map(|n| n.note).collect();
+            notes.sort_by_key(|n| std::cmp::Reverse(n.signed_at));
+            notes
+        })
+        .map_err(|e| {
+            log::error!("Failed to list signed notes: {:?}", e);
+            e.to_string()
+        })
+}
+
+/// Checks a stored note against its signature.
+#[tauri::command(rename_all = "camelCase")]
+pub fn verify_signed_note(
+    app_handle: tauri::AppHandle,
+    note_id: Uuid,
+) -> Result<VerificationResult, String> {
+    log::info!("Verifying signed note {}", note_id);
+    _verify_signed_note(&app_handle, note_id)
+        .map_err(|e| {
+            log::error!("Failed to verify signed note {}: {:?}", note_id, e);
+            e.to_string()
+        })
+        .inspect(|result| {
+            audit::record(
+                &app_handle,
+                "verifySignedNote",
+                json!({ "noteId": note_id, "valid": result.is_valid }),
+            )
+        })
+}
+
+fn _verify_signed_note(app_handle: &tauri::AppHandle, note_id: Uuid) -> Result<VerificationResult> {
+    let stored = read_signed_notes(&get_signed_notes_path(app_handle)?)?
+        .into_iter()
+        .find(|n| n.note.note_id == note_id)
+        .ok_or_else(|| anyhow!("Signed note with ID {} not found", note_id))?;
+    let algorithm = stored.note.algorithm.clone();
+    let public_key_der = decode_public_key_pem(&stored.note.public_key_pem)?;
+    let attributes = stored.envelope.attributes()?;
+    let report = VerificationResult {
+        digest_algorithm: Some(DIGEST_SHA256.to_string()),
+        computed_digest: Some(hex::encode(Sha256::digest(stored.note.text.as_bytes()))),
+        expected_digest: Some(attributes.document_digest.clone()),
+        signed_at: Some(attributes.signed_at),
+        signing_time_source: Some(attributes.time_source),
+        sequence: attributes.sequence,
+        ..Default::default()
+    };
+
+    // 核对签名者与正文摘要，再验证信封签名
+    let mismatch = check_envelope_attributes(
+        &attributes,
+        &algorithm,
+        &public_key_der,
+        report.computed_digest.as_deref().unwrap_or_default(),
+    )?;
+    let error_message = match mismatch {
+        Some(message) => Some(message),
+        None => stored
+            .envelope
+            .verify_signature(&algorithm, &public_key_der)?
+            .err()
+            .map(|e| format!("Signature is invalid: {}", e)),
+    };
+    if let Some(message) = &error_message {
+        log::warn!(
+            "Verification failed for signed note {}: {}",
+            note_id,
+            message
+        );
+    }
+    Ok(VerificationResult {
+        is_valid: error_message.is_none(),
+        error_message,
+        ..report
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_signed_note(app_handle: tauri::AppHandle, note_id: Uuid) -> Result<(), String> {
+    log::info!("Deleting signed note {}", note_id);
+    _delete_signed_note(&app_handle, note_id)
+        .map_err(|e| {
+            log::error!("Failed to delete signed note {}: {:?}", note_id, e);
+            e.to_string()
+        })
+        .inspect(|_| {
+            audit::record(
+                &app_handle,
+                "deleteSignedNote",
+                json!({ "noteId": note_id }),
+            )
+        })
+}
+
+fn _delete_signed_note(app_handle: &tauri::AppHandle, note_id: Uuid) -> Result<()> {
+    let path = get_signed_notes_path(app_handle)?;
+    let mut notes = read_signed_notes(&path)?;
+    let count = notes.len();
+    notes.retain(|n| n.note.note_id != note_id);
+    if notes.len() == count {
+        bail!("Signed note with ID {} not found", note_id);
+    }
+    write_signed_notes(&path, &notes)
+}
+
+// --- Helper Functions ---
+
+// 获取签名便笺文件路径
+fn get_signed_notes_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, SIGNED_NOTES_FILENAME)
+}
+
+fn read_signed_notes(path: &Path) -> Result<Vec<StoredNote>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read signed notes file: {:?}", path))?;
+    serde_json::from_str(&content).context("Failed to parse signed notes JSON")
+}
+
+fn write_signed_notes(path: &Path, notes: &[StoredNote]) -> Result<()> {
+    let content =
+        serde_json::to_string_pretty(notes).context("Failed to serialize signed notes")?;
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write signed notes file: {:?}", path))
+}
@@ -1,19 +1,67 @@
 // src-tauri/src/signing.rs
+use crate::audit;
+use crate::batch_jobs::start_batch_job;
+use crate::completion_certificate::{write_completion_certificate, SigningRecord};
+use crate::contacts::{get_contacts_path, read_contacts};
+use crate::content_uris::{is_content_uri, open_document, open_output};
 use crate::crypto_types::{
-    SignatureAlgorithm, SignatureFormat, SigningOptions, VerificationResult,
+    BatchJobItem, BatchJobKind, DigestScheme, EcdsaEncoding, KeyMetadata, MultiVerificationReport,
+    OperationMetrics, PqCosigner, SignatureAlgorithm, SignatureEncoding, SignatureFileReport,
+    SignatureFormat, SignatureLevel, SigningOptions, SigningResult, TimeSource, TrustLevel,
+    VerificationResult,
 };
+use crate::dual_control::{approve_signing, verify_approval};
+use crate::envelope::{
+    FileBinding, SignatureEnvelope, SignedAttributes, DIGEST_SHA256, ENVELOPE_FORMAT,
+    ENVELOPE_VERSION, HYBRID_ENVELOPE_FORMAT,
+};
+use crate::hashing::{hash_file, hash_in_pool};
 use crate::key_management::{
-    decrypt_data, get_key_storage_dir, get_metadata_path, read_metadata, SALT_LEN,
+    compute_fingerprint, decode_public_key_pem, find_key_metadata, find_pq_key, get_metadata_path,
+    read_metadata, read_private_key_der, read_private_key_der_approved, read_public_key_pem,
+    require_key_pair,
 }; // Import necessary helpers
-use anyhow::{bail, Context, Result};
-use signature::SignatureEncoding;
+use crate::key_sessions::{session_key, KeySessions};
+use crate::kms::{find_kms_key, list_kms_keys_for_verification};
+use crate::metadata_schema::validate_metadata;
+use crate::native_paths::{native_path, open_file, path_string, read_file};
+use crate::oidc_identity::{check_identity, login_identity};
+use crate::output_paths::resolve_output_path;
+use crate::passkeys::{find_passkey, list_passkeys_for_verification};
+use crate::policy::check_timestamping;
+use crate::self_tests::ensure_self_tests_passed;
+use crate::signable_inputs::check_signable_input;
+use crate::signature_sequence::next_signature_sequence;
+use crate::tasks::spawn_task;
+use crate::timestamp::{check_timestamp_token, request_timestamp, trusted_tsa_certificates};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use signature::SignatureEncoding as _;
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 // Use Manager trait to get AppHandle features
+use tauri::Manager;
 use uuid::Uuid;
+use x509_cert::Certificate;
+use zeroize::Zeroizing;
 
 // --- Hashing ---
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use signature::hazmat::{PrehashSigner, PrehashVerifier};
+use std::io::Read;
+
+const VERIFY_BUFFER_SIZE: usize = 64 * 1024;
+/// Verification progress is reported after each this many bytes of the document.
+const PROGRESS_INTERVAL_BYTES: u64 = 16 * 1024 * 1024;
+/// PEM label of armored detached signatures.
+pub(crate) const SIGNATURE_PEM_LABEL: &str = "SIGNATURE";
 
 // --- RSA ---
 use rsa::pkcs1v15::SigningKey as RsaSigningKey;
@@ -31,22 +79,31 @@ use p256::ecdsa::{
     VerifyingKey as EcdsaVerifyingKey, // Use alias
 };
 
+// --- ECDSA P-384 / P-521 ---
+use p384::ecdsa::{
+    Signature as P384Signature, SigningKey as P384SigningKey, VerifyingKey as P384VerifyingKey,
+};
+use p521::ecdsa::{
+    Signature as P521Signature, SigningKey as P521SigningKey, VerifyingKey as P521VerifyingKey,
+};
+
 // --- Ed25519 ---
 // Use ed25519-dalek for direct signing/verification if you generated with it
 // If you used the `ed25519` crate with `pkcs8` feature:
 // use ed25519::pkcs8::{DecodePrivateKey as _, DecodePublicKey as _};
 // use ed25519::{Signature as EdSignature, SigningKey as EdSigningKey, VerifyingKey as EdVerifyingKey};
 // Using dalek here as it was in the modified generate code:
+use ed25519_dalek::hazmat::{raw_sign_byupdate, ExpandedSecretKey};
 use ed25519_dalek::{
-    Signature as EdSignature, SigningKey as EdSigningKey, VerifyingKey as EdVerifyingKey,
+    Signature as EdSignature, SigningKey as EdSigningKey, StreamVerifier,
+    VerifyingKey as EdVerifyingKey,
 };
 
-// --- PEM ---
-use pem_rfc7468::{decode_vec, PemLabel}; // Import decode_vec
-                                         // Use LineEnding from pkcs8::der::pem
-
-// Constants for PEM tags (should match key_management)
-const SPKI_PEM_TAG: &str = rsa::pkcs8::SubjectPublicKeyInfoRef::PEM_LABEL;
+// --- ML-DSA ---
+use ml_dsa::{
+    KeyPair as MlDsaKeyPair, MlDsa65, Signature as MlDsaSignature,
+    VerifyingKey as MlDsaVerifyingKey,
+};
 
 // --- Tauri Commands ---
 
@@ -58,7 +115,8 @@ pub fn sign_document(
     password: String,
     output_path: String,
     options: SigningOptions, // Keep options for future extensibility
-) -> Result<(), String> {
+    approver_password: Option<String>,
+) -> Result<SigningResult, String> {
     log::info!(
         "Signing document '{}' with key ID {}",
         document_path,
@@ -68,117 +126,912 @@ pub fn sign_document(
         return Err("Password cannot be empty.".to_string());
     }
 
-    match options.format {
+    // 企业策略可能要求签名带时间戳（仅信封格式支持）
+    let timestamped = matches!(
+        options.format,
+        SignatureFormat::Envelope | SignatureFormat::Hybrid
+    ) && options.tsa_url.is_some()
+        && (options.trusted_time || options.signature_level == SignatureLevel::T);
+    if let Err(e) = check_timestamping(timestamped) {
+        return Err(e.to_string());
+    }
+    let (document, output) = match (
+        open_document(&app_handle, &document_path),
+        open_output(&app_handle, &output_path),
+    ) {
+        (Ok(document), Ok(output)) => (document, output),
+        (Err(e), _) | (_, Err(e)) => return Err(e.to_string()),
+    };
+    if let Err(e) = check_signable_input(&app_handle, document.path()) {
+        return Err(e.to_string());
+    }
+
+    let result = match options.format {
+        SignatureFormat::Detached if options.bind_file_name || options.bind_file_attributes => {
+            Err("Binding file metadata requires the envelope signature format.".to_string())
+        }
+        SignatureFormat::Detached if options.signature_level != SignatureLevel::B => {
+            Err("Signature levels above B require the envelope signature format.".to_string())
+        }
+        SignatureFormat::Detached if !options.metadata.is_empty() => {
+            Err("Custom metadata requires the envelope signature format.".to_string())
+        }
+        SignatureFormat::Detached if options.trusted_time => {
+            Err("A trusted signing time requires the envelope signature format.".to_string())
+        }
+        SignatureFormat::Detached if options.oidc_identity => Err(
+            "Signing with an OpenID Connect identity requires the envelope signature format."
+                .to_string(),
+        ),
+        _ if options.completion_certificate && output.is_content_uri() => Err(
+            "A certificate of signing needs a folder; save the signature to a path instead."
+                .to_string(),
+        ),
+        SignatureFormat::Envelope | SignatureFormat::Hybrid
+            if options.trusted_time && options.tsa_url.is_none() =>
+        {
+            Err("A trusted signing time requires a time-stamping authority URL.".to_string())
+        }
+        SignatureFormat::Envelope | SignatureFormat::Hybrid
+            if options.signature_level == SignatureLevel::T && options.tsa_url.is_none() =>
+        {
+            Err("Signature level T requires a time-stamping authority URL.".to_string())
+        }
+        SignatureFormat::Envelope | SignatureFormat::Hybrid
+            if options.signature_level == SignatureLevel::T
+                && !trusted_tsa_certificates(&app_handle).is_ok_and(|tsas| !tsas.is_empty()) =>
+        {
+            Err(
+                "Signature level T requires a trusted time-stamping authority; add its certificate \
+                 in the settings."
+                    .to_string(),
+            )
+        }
         SignatureFormat::Detached => {
             sign_document_detached(
                 &app_handle, // Pass handle for path resolution
-                &document_path,
+                document.path(),
                 key_id,
                 &password,
-                &output_path,
+                output.path(),
+                &options,
             )
+            .map(|(path, certificate_path, metrics)| {
+                (
+                    output.describe(&path),
+                    None,
+                    certificate_path.as_deref().map(path_string),
+                    metrics,
+                )
+            })
             .map_err(|e| {
                 log::error!("Failed to sign document: {:?}", e);
                 e.to_string()
             })
-        } // SignatureFormat::Embedded => Err("Embedded signatures are not supported yet.".to_string()),
+        }
+        SignatureFormat::Envelope | SignatureFormat::Hybrid
+            if options.signature_encoding != SignatureEncoding::Binary =>
+        {
+            Err("Envelopes are JSON text; Base64 and PEM apply to detached signatures.".to_string())
+        }
+        SignatureFormat::Envelope | SignatureFormat::Hybrid => sign_document_envelope(
+            &app_handle,
+            document.path(),
+            key_id,
+            &password,
+            output.path(),
+            &options,
+            approver_password.as_deref(),
+        )
+        .map(|(path, approver_key_id, certificate_path, metrics)| {
+            (
+                output.describe(&path),
+                approver_key_id,
+                certificate_path.as_deref().map(path_string),
+                metrics,
+            )
+        })
+        .map_err(|e| {
+            log::error!("Failed to sign document: {:?}", e);
+            e.to_string()
+        }), // SignatureFormat::Embedded => Err("Embedded signatures are not supported yet.".to_string()),
+    };
+    audit::record(
+        &app_handle,
+        "signDocument",
+        json!({
+            "documentPath": document_path,
+            "keyId": key_id,
+            "outputPath": result.as_ref().map_or(&output_path, |(path, _, _, _)| path),
+            "format": options.format,
+            "approverKeyId": result.as_ref().ok().and_then(|(_, approver, _, _)| *approver),
+            "certificatePath": result.as_ref().ok().and_then(|(_, _, certificate, _)| certificate.as_ref()),
+            "error": result.as_ref().err(),
+        }),
+    );
+    result.map(|(output_path, _, certificate_path, metrics)| {
+        log::info!(
+            "Signed {} bytes in {} ms",
+            metrics.bytes_processed,
+            metrics.total_ms
+        );
+        SigningResult {
+            output_path,
+            certificate_path,
+            metrics,
+        }
+    })
+}
+
+fn sign_document_envelope(
+    app_handle: &tauri::AppHandle,
+    document_path: &Path,
+    key_id: Uuid,
+    password: &str,
+    chosen_output_path: &Path,
+    options: &SigningOptions,
+    approver_password: Option<&str>,
+) -> Result<(PathBuf, Option<Uuid>, Option<PathBuf>, OperationMetrics)> {
+    let started = Instant::now();
+    // 1. Find key metadata and the signer fingerprint
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    require_key_pair(&metadata)?;
+    let algorithm = metadata.algorithm.clone();
+    let public_key_der = decode_public_key_pem(&read_public_key_pem(app_handle, &metadata)?)?;
+    let key_fingerprint = compute_fingerprint(&public_key_der);
+
+    // 1a. Optionally log in with the OpenID Connect provider, bound to this key
+    let oidc_identity = if options.oidc_identity {
+        Some(login_identity(app_handle, Some(&key_fingerprint))?)
+    } else {
+        None
+    };
+
+    // 2. Check custom metadata against the configured schema, then digest the document
+    //    (streamed, the document itself is not loaded into memory)
+    validate_metadata(app_handle, &options.metadata)?;
+    let hash_started = Instant::now();
+    let (document_size, document_digest) = hash_file(document_path)?;
+    let mut metrics = OperationMetrics {
+        bytes_processed: document_size,
+        hash_ms: millis(hash_started.elapsed()),
+        ..Default::default()
+    };
+
+    // 2a. Two-person control: the approver signs off before the private key is decrypted
+    let approval = approve_signing(
+        app_handle,
+        &metadata,
+        &key_fingerprint,
+        &document_digest,
+        approver_password,
+    )?;
+    let kdf_started = Instant::now();
+    let private_key_der = read_private_key_der_approved(app_handle, &metadata, password)?;
+    // 2b. Hybrid: the linked ML-DSA key co-signs
+    let pq = match options.format {
+        SignatureFormat::Hybrid => Some(unlock_pq_cosigner(app_handle, &metadata, password)?),
+        _ => None,
+    };
+    metrics.kdf_ms = Some(millis(kdf_started.elapsed()));
+
+    // 3. Optionally bind the file name (and size / modification time)
+    let binding = if options.bind_file_name || options.bind_file_attributes {
+        FileBinding::read(document_path, options.bind_file_attributes)?
+    } else {
+        FileBinding::default()
+    };
+
+    // 4. Signing time: from the TSA (token over the document digest) or the local clock
+    let (signed_at, time_source, time_token) = match (options.trusted_time, &options.tsa_url) {
+        (true, Some(tsa_url)) => {
+            let digest = hex::decode(&document_digest).context("Malformed document digest")?;
+            let token = request_timestamp(tsa_url, &digest)?;
+            match check_timestamp_token(
+                &token,
+                &digest,
+                None,
+                &trusted_tsa_certificates(app_handle)?,
+            )? {
+                Ok(verified) => (
+                    verified.gen_time,
+                    TimeSource::Tsa,
+                    Some(BASE64.encode(token)),
+                ),
+                // 不受信任的 TSA 不能为签名时间作证，改用本地时钟
+                Err(reason) => {
+                    log::warn!("Not using the TSA time as signing time: {}", reason);
+                    (Utc::now(), TimeSource::LocalClock, None)
+                }
+            }
+        }
+        _ => (Utc::now(), TimeSource::LocalClock, None),
+    };
+
+    // 5. Sign the canonical form of the attributes and write the envelope
+    let attributes = SignedAttributes {
+        format: match pq {
+            Some(_) => HYBRID_ENVELOPE_FORMAT,
+            None => ENVELOPE_FORMAT,
+        }
+        .to_string(),
+        version: ENVELOPE_VERSION,
+        algorithm: algorithm.to_string(),
+        key_id,
+        key_fingerprint,
+        digest_algorithm: DIGEST_SHA256.to_string(),
+        document_digest,
+        signed_at,
+        time_source,
+        time_token,
+        file_name: binding.file_name,
+        file_size: binding.file_size,
+        file_modified_at: binding.file_modified_at,
+        previous_envelope: None,
+        metadata: options.metadata.clone(),
+        approval,
+        sequence: Some(next_signature_sequence(app_handle, key_id)?),
+        oidc_identity,
+        pq_cosigner: pq.as_ref().map(|(cosigner, _)| cosigner.clone()),
+    };
+    let mut envelope = match &pq {
+        Some((_, pq_private_key_der)) => {
+            SignatureEnvelope::seal_hybrid(&attributes, &private_key_der, pq_private_key_der)?
+        }
+        None => SignatureEnvelope::seal(&attributes, &private_key_der)?,
+    };
+
+    // 6. Level T: time-stamp the sealed envelope
+    if let (SignatureLevel::T, Some(tsa_url)) = (options.signature_level, &options.tsa_url) {
+        envelope.add_timestamp(tsa_url, &trusted_tsa_certificates(app_handle)?)?;
     }
+
+    let content =
+        serde_json::to_string_pretty(&envelope).context("Failed to serialize envelope")?;
+    let output_path = resolve_output_path(app_handle, chosen_output_path)?;
+    fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write signature file: {:?}", output_path))?;
+
+    log::info!(
+        "Document successfully signed with {}. Envelope saved to {:?}",
+        algorithm,
+        output_path
+    );
+
+    // 7. Optionally write a certificate of signing, signed with the same key
+    let certificate_path = if options.completion_certificate {
+        Some(write_completion_certificate(
+            app_handle,
+            &metadata,
+            &public_key_der,
+            &private_key_der,
+            &SigningRecord {
+                document_path,
+                document_size,
+                document_digest: &attributes.document_digest,
+                signature_path: &output_path,
+                format: options.format.clone(),
+                signed_at: attributes.signed_at,
+                time_source: attributes.time_source,
+            },
+        )?)
+    } else {
+        None
+    };
+    finish_metrics(&mut metrics, started);
+    Ok((
+        output_path,
+        metadata.approver_key_id,
+        certificate_path,
+        metrics,
+    ))
 }
 
 fn sign_document_detached(
     app_handle: &tauri::AppHandle,
-    document_path_str: &str,
+    document_path: &Path,
     key_id: Uuid,
     password: &str,
-    output_path_str: &str,
-) -> Result<()> {
+    chosen_output_path: &Path,
+    options: &SigningOptions,
+) -> Result<(PathBuf, Option<PathBuf>, OperationMetrics)> {
+    let started = Instant::now();
     // 1. Find key metadata and parse algorithm
-    let metadata_path = get_metadata_path(app_handle)?;
-    let metadata = read_metadata(&metadata_path)?
-        .into_iter()
-        .find(|m| m.key_id == key_id)
-        .ok_or_else(|| anyhow::anyhow!("Key with ID {} not found", key_id))?;
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    require_key_pair(&metadata)?;
+    let algorithm = metadata.algorithm.clone();
+
+    // 2. Read and decrypt private key DER bytes
+    let kdf_started = Instant::now();
+    let private_key_der = read_private_key_der(app_handle, &metadata, password)?;
+    let kdf_ms = millis(kdf_started.elapsed());
+
+    // 3. Hash and sign the document (streamed, except for ML-DSA)
+    let hash_started = Instant::now();
+    let (mut signature_bytes, document_size, document_digest) = sign_document_file(
+        &algorithm,
+        &private_key_der,
+        document_path,
+        options.digest_scheme,
+    )?;
+    let mut metrics = OperationMetrics {
+        bytes_processed: document_size,
+        kdf_ms: Some(kdf_ms),
+        hash_ms: millis(hash_started.elapsed()),
+        ..Default::default()
+    };
+
+    // 4. ECDSA signatures in the requested encoding
+    if algorithm.is_ecdsa() {
+        signature_bytes = encode_ecdsa_signature(&signature_bytes, options.ecdsa_encoding)?;
+    }
+
+    // 5. Write signature to output file
+    let output_path = resolve_output_path(app_handle, chosen_output_path)?;
+    fs::write(
+        &output_path,
+        encode_signature(&signature_bytes, options.signature_encoding)?,
+    )
+    .with_context(|| format!("Failed to write signature file: {:?}", output_path))?;
 
-    let algorithm = SignatureAlgorithm::from_str(&metadata.algorithm).with_context(|| {
-        format!(
-            "Invalid algorithm '{}' found in metadata for key {}",
-            metadata.algorithm, key_id
+    log::info!(
+        "Document successfully signed with {}. Signature saved to {:?}",
+        algorithm,
+        output_path
+    );
+
+    // 6. Optionally write a certificate of signing, signed with the same key
+    let certificate_path = if options.completion_certificate {
+        let public_key_der = decode_public_key_pem(&read_public_key_pem(app_handle, &metadata)?)?;
+        Some(write_completion_certificate(
+            app_handle,
+            &metadata,
+            &public_key_der,
+            &private_key_der,
+            &SigningRecord {
+                document_path,
+                document_size,
+                document_digest: &hex::encode(&document_digest),
+                signature_path: &output_path,
+                format: SignatureFormat::Detached,
+                signed_at: Utc::now(),
+                time_source: TimeSource::LocalClock,
+            },
+        )?)
+    } else {
+        None
+    };
+    finish_metrics(&mut metrics, started);
+    Ok((output_path, certificate_path, metrics))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn refresh_signature(
+    app_handle: tauri::AppHandle,
+    document_path: String,
+    signature_path: String,
+    output_path: String,
+    tsa_url: Option<String>,
+    key_id: Option<Uuid>,
+    password: Option<String>,
+) -> Result<String, String> {
+    log::info!(
+        "Refreshing signature '{}' for document '{}'",
+        signature_path,
+        document_path
+    );
+    if tsa_url.is_none() && key_id.is_none() {
+        return Err(
+            "Choose a time-stamping authority, a key to re-sign with, or both.".to_string(),
+        );
+    }
+    if key_id.is_some() && password.as_deref().unwrap_or_default().is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    let paths = [&document_path, &signature_path, &output_path].map(|path| native_path(path));
+    let [document, signature, output] = match paths {
+        [Ok(document), Ok(signature), Ok(output)] => [document, signature, output],
+        [Err(e), _, _] | [_, Err(e), _] | [_, _, Err(e)] => return Err(e.to_string()),
+    };
+    _refresh_signature(
+        &app_handle,
+        &document,
+        &signature,
+        &output,
+        tsa_url.as_deref(),
+        key_id.map(|id| (id, password.unwrap_or_default())),
+    )
+    .map(|path| path_string(&path))
+    .map_err(|e| {
+        log::error!("Failed to refresh signature: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|written_path| {
+        audit::record(
+            &app_handle,
+            "refreshSignature",
+            json!({
+                "signaturePath": signature_path,
+                "outputPath": written_path,
+                "resignedWith": key_id,
+                "tsaUrl": tsa_url,
+            }),
         )
+    })
+}
+
+fn _refresh_signature(
+    app_handle: &tauri::AppHandle,
+    document_path: &Path,
+    signature_path: &Path,
+    chosen_output_path: &Path,
+    tsa_url: Option<&str>,
+    resign_with: Option<(Uuid, String)>,
+) -> Result<PathBuf> {
+    // 1. Only envelopes can carry the added evidence
+    let signature_bytes = read_file(signature_path)
+        .with_context(|| format!("Failed to read signature file: {:?}", signature_path))?;
+    let mut envelope = SignatureEnvelope::parse(&signature_bytes)?.ok_or_else(|| {
+        anyhow!("Only envelope signatures can be refreshed; re-sign the document instead")
     })?;
+    let attributes = envelope.attributes()?;
 
-    // 2. Read and decrypt private key DER bytes
-    let key_storage_dir = get_key_storage_dir(app_handle)?;
-    let private_key_path = key_storage_dir.join(&metadata.encrypted_private_key_path);
-    let mut encrypted_private_key_bytes = fs::read(&private_key_path) // Read into mutable Vec
-        .with_context(|| {
-            format!(
-                "Failed to read encrypted private key file: {:?}",
-                private_key_path
+    // 2. The existing signature must still verify before it is extended
+    let (algorithm, public_key_der) = resolve_envelope_signer(app_handle, &attributes)?;
+    let (_, document_digest) = hash_file(document_path)?;
+    if let Some(message) =
+        check_envelope_attributes(&attributes, &algorithm, &public_key_der, &document_digest)?
+    {
+        bail!("Existing signature does not verify: {}", message);
+    }
+    let verified = match &attributes.pq_cosigner {
+        None => envelope.verify_signature(&algorithm, &public_key_der)?,
+        Some(cosigner) => envelope.verify_hybrid_signature(
+            &algorithm,
+            &public_key_der,
+            &find_pq_cosigner(&signer_candidates(app_handle)?, cosigner)?.public_key_der,
+        )?,
+    };
+    if let Err(e) = verified {
+        bail!("Existing signature does not verify: {}", e);
+    }
+    let trusted_tsas = trusted_tsa_certificates(app_handle)?;
+    attributes
+        .check_signing_time(None, &trusted_tsas)
+        .context("Existing signing time does not verify")?;
+    if let Some(invalid) = envelope
+        .verify_timestamps(None, &trusted_tsas)
+        .into_iter()
+        .find(|t| !t.is_valid)
+    {
+        bail!(
+            "Existing time-stamp from {} does not verify: {}",
+            invalid.tsa_url,
+            invalid.error_message.unwrap_or_default()
+        );
+    }
+
+    // 3. Optionally re-sign, keeping the old envelope (and its time-stamps) inside the new one
+    if let Some((key_id, password)) = resign_with {
+        let metadata = find_key_metadata(app_handle, key_id)?;
+        let new_algorithm = metadata.algorithm.clone();
+        let new_public_key_der =
+            decode_public_key_pem(&read_public_key_pem(app_handle, &metadata)?)?;
+        let private_key_der = read_private_key_der(app_handle, &metadata, &password)?;
+        let previous = serde_json::to_value(&envelope).context("Failed to serialize envelope")?;
+        // 新签名者不带后量子签名，混合信封换签后成为普通信封
+        let new_attributes = SignedAttributes {
+            format: ENVELOPE_FORMAT.to_string(),
+            algorithm: new_algorithm.to_string(),
+            key_id,
+            key_fingerprint: compute_fingerprint(&new_public_key_der),
+            signed_at: Utc::now(),
+            time_source: TimeSource::LocalClock,
+            time_token: None,
+            previous_envelope: Some(previous),
+            approval: None,
+            sequence: Some(next_signature_sequence(app_handle, key_id)?),
+            oidc_identity: None,
+            pq_cosigner: None,
+            ..attributes
+        };
+        envelope = SignatureEnvelope::seal(&new_attributes, &private_key_der)?;
+    }
+
+    // 4. Optionally add a fresh time-stamp over everything above
+    if let Some(tsa_url) = tsa_url {
+        let timestamp = envelope.add_timestamp(tsa_url, &trusted_tsas)?;
+        log::info!(
+            "Added time-stamp from {} at {}",
+            tsa_url,
+            timestamp.gen_time
+        );
+    }
+
+    let content =
+        serde_json::to_string_pretty(&envelope).context("Failed to serialize envelope")?;
+    let output_path = resolve_output_path(app_handle, chosen_output_path)?;
+    fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write signature file: {:?}", output_path))?;
+    log::info!("Refreshed signature saved to {:?}", output_path);
+    Ok(output_path)
+}
+
+// 混合签名：取出与签名密钥关联的 ML-DSA 密钥，已解锁的密钥会话优先，否则用同一口令解密
+fn unlock_pq_cosigner(
+    app_handle: &tauri::AppHandle,
+    metadata: &KeyMetadata,
+    password: &str,
+) -> Result<(PqCosigner, Zeroizing<Vec<u8>>)> {
+    let pq_key = find_pq_key(app_handle, metadata)?;
+    let public_key_pem = read_public_key_pem(app_handle, &pq_key)?;
+    let public_key_der = decode_public_key_pem(&public_key_pem)?;
+    let private_key_der = match session_key(&app_handle.state::<KeySessions>(), pq_key.key_id) {
+        Ok((_, private_key_der)) => private_key_der,
+        Err(_) => Zeroizing::new(
+            read_private_key_der(app_handle, &pq_key, password).with_context(|| {
+                format!(
+                    "Unlock the post-quantum key '{}' in a key session first, or give it the \
+                     same passphrase as '{}'",
+                    pq_key.name, metadata.name
+                )
+            })?,
+        ),
+    };
+    Ok((
+        PqCosigner {
+            key_id: pq_key.key_id,
+            algorithm: pq_key.algorithm.to_string(),
+            public_key_pem,
+            fingerprint: compute_fingerprint(&public_key_der),
+        },
+        private_key_der,
+    ))
+}
+
+// 根据信封中的密钥 ID 与指纹找到签名者公钥（先找本地密钥，再找联系人）；不信任的联系人一律拒绝
+pub(crate) fn resolve_envelope_signer(
+    app_handle: &tauri::AppHandle,
+    attributes: &SignedAttributes,
+) -> Result<(SignatureAlgorithm, Vec<u8>)> {
+    if let Ok(metadata) = find_key_metadata(app_handle, attributes.key_id) {
+        let public_key_der = decode_public_key_pem(&read_public_key_pem(app_handle, &metadata)?)?;
+        if compute_fingerprint(&public_key_der) == attributes.key_fingerprint {
+            return Ok((metadata.algorithm.clone(), public_key_der));
+        }
+    }
+    if let Some(passkey) = find_passkey(app_handle, attributes.key_id)? {
+        if passkey.fingerprint == attributes.key_fingerprint {
+            return Ok((
+                SignatureAlgorithm::EcdsaP256Sha256,
+                decode_public_key_pem(&passkey.public_key_pem)?,
+            ));
+        }
+    }
+    if let Some(kms_key) = find_kms_key(app_handle, attributes.key_id)? {
+        if kms_key.fingerprint == attributes.key_fingerprint {
+            return Ok((
+                kms_key.algorithm,
+                decode_public_key_pem(&kms_key.public_key_pem)?,
+            ));
+        }
+    }
+    let contacts = read_contacts(&get_contacts_path(app_handle)?)?;
+    let contact = contacts
+        .iter()
+        .find(|c| c.fingerprint == attributes.key_fingerprint)
+        .ok_or_else(|| {
+            anyhow!(
+                "Signer key {} is neither one of your keys nor a contact",
+                attributes.key_fingerprint
             )
         })?;
-    let mut salt = [0; SALT_LEN];
-    hex::decode_to_slice(&metadata.salt_hex, &mut salt)
-        .context("Failed to decode salt from hex")?;
+    if contact.trust == TrustLevel::Distrusted {
+        bail!(
+            "Signed with the key of '{}', a contact you marked as distrusted",
+            contact.name
+        );
+    }
+    Ok((
+        contact.algorithm.clone(),
+        decode_public_key_pem(&contact.public_key_pem)?,
+    ))
+}
 
-    // Decrypt in place - passing mutable Vec
-    decrypt_data(&mut encrypted_private_key_bytes, password, &salt)
-        .context("Failed to decrypt private key (check password)")?;
-    // encrypted_private_key_bytes now holds the decrypted DER bytes
+// 校验信封中的签名者与文档摘要，返回不匹配的原因
+pub(crate) fn check_envelope_attributes(
+    attributes: &SignedAttributes,
+    algorithm: &SignatureAlgorithm,
+    public_key_der: &[u8],
+    document_digest: &str,
+) -> Result<Option<String>> {
+    if attributes.key_fingerprint != compute_fingerprint(public_key_der) {
+        return Ok(Some(format!(
+            "Envelope was signed by a different key (fingerprint {})",
+            attributes.key_fingerprint
+        )));
+    }
+    if SignatureAlgorithm::from_str(&attributes.algorithm)? != *algorithm {
+        return Ok(Some(format!(
+            "Envelope algorithm {} does not match the key algorithm {}",
+            attributes.algorithm, algorithm
+        )));
+    }
+    if document_digest != attributes.document_digest {
+        return Ok(Some(
+            "Document digest does not match the envelope; the document was modified".to_string(),
+        ));
+    }
+    Ok(None)
+}
 
-    // 3. Read document data (needed for hashing or direct signing)
-    let document_bytes = fs::read(document_path_str)
-        .with_context(|| format!("Failed to read document file: {}", document_path_str))?;
+/// Signs documents with detached signatures as a background task. An interrupted run can
+/// be continued with `resume_batch_job`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn sign_documents_batch(
+    app_handle: tauri::AppHandle,
+    document_paths: Vec<String>,
+    key_id: Uuid,
+    password: String,
+    output_dir: String,
+) -> Result<Uuid, String> {
+    log::info!(
+        "Starting batch signing of {} documents with key ID {}",
+        document_paths.len(),
+        key_id
+    );
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    if document_paths.is_empty() {
+        return Err("No documents to sign.".to_string());
+    }
+    // 批量签名只生成分离签名，无法带时间戳
+    check_timestamping(false).map_err(|e| e.to_string())?;
+    let document_count = document_paths.len();
+    let items = document_paths
+        .into_iter()
+        .map(|document_path| BatchJobItem {
+            document_path,
+            signature_path: None,
+        })
+        .collect();
+    start_batch_job(
+        &app_handle,
+        BatchJobKind::Sign,
+        Some(key_id),
+        Some(output_dir),
+        items,
+        Some(password),
+    )
+    .map_err(|e| {
+        log::error!("Failed to start batch signing: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|task_id| {
+        audit::record(
+            &app_handle,
+            "signDocumentsBatch",
+            json!({ "keyId": key_id, "documents": document_count, "taskId": task_id }),
+        )
+    })
+}
 
-    // 4. Algorithm-specific signing
+/// Signs `data` with a decrypted PKCS#8 private key using the given algorithm.
+/// Refuses to sign if the cryptographic self-tests have failed.
+pub(crate) fn sign_bytes(
+    algorithm: &SignatureAlgorithm,
+    private_key_der: &[u8],
+    data: &[u8],
+    scheme: DigestScheme,
+) -> Result<Vec<u8>> {
+    ensure_self_tests_passed()?;
+    sign_bytes_unchecked(algorithm, private_key_der, data, scheme)
+}
 
-    log::debug!("Performing signing with algorithm: {}", algorithm);
+/// Same as `sign_bytes` without the self-test gate; only the self-tests call this directly.
+pub(crate) fn sign_bytes_unchecked(
+    algorithm: &SignatureAlgorithm,
+    private_key_der: &[u8],
+    data: &[u8],
+    scheme: DigestScheme,
+) -> Result<Vec<u8>> {
+    log::debug!(
+        "Performing signing with algorithm: {} ({:?})",
+        algorithm,
+        scheme
+    );
+    let message = signed_message(data, scheme);
 
     let signature_bytes = match algorithm {
         SignatureAlgorithm::RsaPkcs1Sha256 => {
             // Parse private key
-            let private_key = RsaPrivateKey::from_pkcs8_der(&encrypted_private_key_bytes)
+            let private_key = RsaPrivateKey::from_pkcs8_der(private_key_der)
                 .context("Failed to parse decrypted data as RSA private key")?;
-            // Create signing key with specific padding/hash
+            // Create signing key with specific padding/hash (the signer hashes `message`)
             let signing_key = RsaSigningKey::<RsaSha256>::new(private_key);
-            // Hash the document
-            let mut hasher = Sha256::new();
-            hasher.update(&document_bytes);
-            let digest = hasher.finalize();
-            // Sign the hash
-            signing_key.sign(digest.as_slice()).to_vec()
+            signing_key.sign(&message).to_vec()
         }
         SignatureAlgorithm::EcdsaP256Sha256 => {
             // Parse private key
-            let private_key = EcdsaSigningKey::from_pkcs8_der(&encrypted_private_key_bytes)
+            let private_key = EcdsaSigningKey::from_pkcs8_der(private_key_der)
                 .context("Failed to parse decrypted data as ECDSA P-256 private key")?;
-            // Hash the document
-            let mut hasher = Sha256::new();
-            hasher.update(&document_bytes);
-            let digest = hasher.finalize();
-            // Sign the hash - P256 SigningKey implements Signer trait
-            let signature: EcdsaSignature = private_key.sign(digest.as_slice());
+            // P256 SigningKey implements Signer trait and hashes `message` with SHA-256
+            let signature: EcdsaSignature = private_key.sign(&message);
+            signature.to_vec()
+        }
+        SignatureAlgorithm::EcdsaP384Sha384 => {
+            let private_key = P384SigningKey::from_pkcs8_der(private_key_der)
+                .context("Failed to parse decrypted data as ECDSA P-384 private key")?;
+            // Hashes `message` with SHA-384
+            let signature: P384Signature = private_key.sign(&message);
+            signature.to_vec()
+        }
+        SignatureAlgorithm::EcdsaP521Sha512 => {
+            // Hashes `message` with SHA-512
+            let signature: P521Signature = p521_signing_key(private_key_der)?.sign(&message);
             signature.to_vec()
         }
         SignatureAlgorithm::Ed25519 => {
             // Parse private key
-            let private_key = EdSigningKey::from_pkcs8_der(&encrypted_private_key_bytes)
+            let private_key = EdSigningKey::from_pkcs8_der(private_key_der)
                 .context("Failed to parse decrypted data as Ed25519 private key")?;
             // Sign the message directly (no pre-hashing) using dalek's Signer trait
-            let signature = private_key.sign(&document_bytes);
+            let signature = private_key.sign(data);
             signature.to_bytes().to_vec()
         }
+        SignatureAlgorithm::MlDsa65 => {
+            // The PKCS#8 key holds the 32-byte seed the key pair is expanded from
+            let key_pair = MlDsaKeyPair::<MlDsa65>::from_pkcs8_der(private_key_der)
+                .context("Failed to parse decrypted data as ML-DSA-65 private key")?;
+            // Deterministic ML-DSA over the message itself, with an empty context
+            let signature: MlDsaSignature<MlDsa65> = key_pair.sign(data);
+            signature.to_vec()
+        }
     };
+    Ok(signature_bytes)
+}
 
-    // 5. Write signature to output file
-    fs::write(output_path_str, &signature_bytes)
-        .with_context(|| format!("Failed to write signature file: {}", output_path_str))?;
+/// Signs a SHA-256 `digest` computed by the caller (RSA and ECDSA P-256 only), for formats that
+/// hash more than the document, such as OpenPGP.
+pub(crate) fn sign_prehash(
+    algorithm: &SignatureAlgorithm,
+    private_key_der: &[u8],
+    digest: &[u8],
+) -> Result<Vec<u8>> {
+    ensure_self_tests_passed()?;
+    match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 | SignatureAlgorithm::EcdsaP256Sha256 => {
+            sign_digest(algorithm, private_key_der, digest)
+        }
+        _ => bail!(
+            "{} signatures cannot be made over a SHA-256 digest",
+            algorithm
+        ),
+    }
+}
 
-    log::info!(
-        "Document successfully signed with {}. Signature saved to {}",
-        algorithm,
-        output_path_str
+// 对签名算法自身的哈希算出的摘要签名（见 prehash），与 verify_prehash 对应
+fn sign_digest(
+    algorithm: &SignatureAlgorithm,
+    private_key_der: &[u8],
+    digest: &[u8],
+) -> Result<Vec<u8>> {
+    match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => {
+            let private_key = RsaPrivateKey::from_pkcs8_der(private_key_der)
+                .context("Failed to parse decrypted data as RSA private key")?;
+            let signing_key = RsaSigningKey::<RsaSha256>::new(private_key);
+            let signature = signing_key
+                .sign_prehash(digest)
+                .context("Failed to sign digest")?;
+            Ok(signature.to_vec())
+        }
+        SignatureAlgorithm::EcdsaP256Sha256 => {
+            let private_key = EcdsaSigningKey::from_pkcs8_der(private_key_der)
+                .context("Failed to parse decrypted data as ECDSA P-256 private key")?;
+            let signature: EcdsaSignature = private_key
+                .sign_prehash(digest)
+                .context("Failed to sign digest")?;
+            Ok(signature.to_vec())
+        }
+        SignatureAlgorithm::EcdsaP384Sha384 => {
+            let private_key = P384SigningKey::from_pkcs8_der(private_key_der)
+                .context("Failed to parse decrypted data as ECDSA P-384 private key")?;
+            let signature: P384Signature = private_key
+                .sign_prehash(digest)
+                .context("Failed to sign digest")?;
+            Ok(signature.to_vec())
+        }
+        SignatureAlgorithm::EcdsaP521Sha512 => {
+            let signature: P521Signature = p521_signing_key(private_key_der)?
+                .sign_prehash(digest)
+                .context("Failed to sign digest")?;
+            Ok(signature.to_vec())
+        }
+        SignatureAlgorithm::Ed25519 | SignatureAlgorithm::MlDsa65 => {
+            bail!("{} signatures cannot be made over a digest", algorithm)
+        }
+    }
+}
+
+// 对文档文件签名，返回签名、文档大小与 SHA-256。RSA / ECDSA 对分块读取时算出的摘要签名；
+// ML-DSA 对整条消息签名，仍需把文档读入内存
+fn sign_document_file(
+    algorithm: &SignatureAlgorithm,
+    private_key_der: &[u8],
+    document_path: &Path,
+    scheme: DigestScheme,
+) -> Result<(Vec<u8>, u64, Vec<u8>)> {
+    ensure_self_tests_passed()?;
+    match algorithm {
+        SignatureAlgorithm::Ed25519 => sign_ed25519_file(private_key_der, document_path),
+        SignatureAlgorithm::MlDsa65 => {
+            let document_bytes = read_file(document_path)
+                .with_context(|| format!("Failed to read document file: {:?}", document_path))?;
+            let signature =
+                sign_bytes_unchecked(algorithm, private_key_der, &document_bytes, scheme)?;
+            Ok((
+                signature,
+                document_bytes.len() as u64,
+                Sha256::digest(&document_bytes).to_vec(),
+            ))
+        }
+        _ => {
+            let mut curve_hash = curve_hasher(algorithm);
+            let (document_digest, document_size) =
+                read_document(document_path, None, curve_hash.as_deref_mut(), &|_, _| {
+                    Ok(())
+                })?;
+            let digest = match scheme {
+                DigestScheme::Standard => curve_hash
+                    .map(|hasher| hasher.finalize().to_vec())
+                    .unwrap_or_else(|| document_digest.clone()),
+                // 旧方案先对文档做一次 SHA-256，签名算法再哈希一次
+                DigestScheme::Legacy => prehash(algorithm, &document_digest),
+            };
+            let signature = sign_digest(algorithm, private_key_der, &digest)?;
+            Ok((signature, document_size, document_digest))
+        }
+    }
+}
+
+// Ed25519 对消息本身签名：分两遍读取文档，先派生 r，再计算挑战值。两遍读到的内容不一致时
+// 丢弃签名——同一个 r 配上不同的消息会泄露私钥
+fn sign_ed25519_file(
+    private_key_der: &[u8],
+    document_path: &Path,
+) -> Result<(Vec<u8>, u64, Vec<u8>)> {
+    let signing_key = EdSigningKey::from_pkcs8_der(private_key_der)
+        .context("Failed to parse decrypted data as Ed25519 private key")?;
+    let passes = RefCell::new(Vec::new());
+    let signature = raw_sign_byupdate::<Sha512, _>(
+        &ExpandedSecretKey::from(signing_key.as_bytes()),
+        |hasher| {
+            let pass = read_document(document_path, None, Some(hasher), &|_, _| Ok(()));
+            let failed = pass.is_err();
+            passes.borrow_mut().push(pass);
+            if failed {
+                return Err(signature::Error::new());
+            }
+            Ok(())
+        },
+        &signing_key.verifying_key(),
     );
-    Ok(())
+    let passes = passes
+        .into_inner()
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    let signature = signature.context("Failed to sign document")?;
+    match passes.as_slice() {
+        [(digest, size), second] if second.0 == *digest && second.1 == *size => {
+            Ok((signature.to_bytes().to_vec(), *size, digest.clone()))
+        }
+        _ => bail!(
+            "The document changed while it was being signed: {:?}",
+            document_path
+        ),
+    }
 }
 
 #[tauri::command(rename_all="camelCase")]
@@ -186,136 +1039,1494 @@ pub fn verify_signature(
     app_handle: tauri::AppHandle,
     document_path: String,
     signature_path: String,
-    key_id: Uuid, // Key ID whose public key should be used
+    // Key ID whose public key should be used; envelopes name their signer, so it may be
+    // left out for them
+    key_id: Option<Uuid>,
+    // Judge time-stamp certificates as of this date instead of each token's own time.
+    // Signing keys carry no certificate, so only TSA certificates have a validity period.
+    as_of: Option<DateTime<Utc>>,
+    // Also accept detached signatures made by earlier releases (double SHA-256); default on.
+    accept_legacy: Option<bool>,
 ) -> Result<VerificationResult, String> {
     // Return Result<Ok, ErrString> to Tauri
     log::info!(
-        "Verifying signature for document '{}' using key ID {}",
+        "Verifying signature for document '{}' using key ID {:?}",
         document_path,
         key_id
     );
 
     // Use map_or_else for cleaner error handling back to Tauri
-    verify_signature_detached(&app_handle, &document_path, &signature_path, key_id).map_err(
-        // If internal function returns Err (process error)
-        |e| {
-            log::error!("Verification process failed upstream: {:?}", e);
-            e.to_string()
-        },
+    open_document(&app_handle, &document_path)
+        .and_then(|document| Ok((document, open_document(&app_handle, &signature_path)?)))
+        .and_then(|(document, signature)| {
+            verify_signature_detached(
+                &app_handle,
+                document.path(),
+                signature.path(),
+                key_id,
+                as_of,
+                accept_legacy.unwrap_or(true),
+                &|_, _| Ok(()),
+            )
+        })
+        .map_err(
+            // If internal function returns Err (process error)
+            |e| {
+                log::error!("Verification process failed upstream: {:?}", e);
+                e.to_string()
+            },
+        )
+        .inspect(|result| {
+            audit::record(
+                &app_handle,
+                "verifySignature",
+                json!({
+                    "documentPath": document_path,
+                    "signaturePath": signature_path,
+                    "keyId": key_id,
+                    "isValid": result.is_valid,
+                }),
+            )
+        })
+}
+
+/// Same as `verify_signature`, for very large documents: runs as a background task that
+/// reports the bytes read so far and can be cancelled. The task result is the
+/// `VerificationResult`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn verify_signature_with_progress(
+    app_handle: tauri::AppHandle,
+    document_path: String,
+    signature_path: String,
+    key_id: Option<Uuid>,
+    as_of: Option<DateTime<Utc>>,
+    accept_legacy: Option<bool>,
+) -> Result<Uuid, String> {
+    log::info!(
+        "Starting verification of document '{}' using key ID {:?}",
+        document_path,
+        key_id
+    );
+    let task_app_handle = app_handle.clone();
+    spawn_task(&app_handle, "verifySignature", move |task| {
+        let result = verify_signature_detached(
+            &task_app_handle,
+            open_document(&task_app_handle, &document_path)?.path(),
+            open_document(&task_app_handle, &signature_path)?.path(),
+            key_id,
+            as_of,
+            accept_legacy.unwrap_or(true),
+            &|done, total| {
+                task.check_cancelled()?;
+                task.progress(done, Some(total), None);
+                Ok(())
+            },
+        )?;
+        audit::record(
+            &task_app_handle,
+            "verifySignature",
+            json!({
+                "documentPath": document_path,
+                "signaturePath": signature_path,
+                "keyId": key_id,
+                "isValid": result.is_valid,
+            }),
+        );
+        Ok(result)
+    })
+    .map_err(|e| {
+        log::error!("Failed to start verification: {:?}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn verify_signatures(
+    app_handle: tauri::AppHandle,
+    document_path: String,
+    signature_paths: Vec<String>,
+    as_of: Option<DateTime<Utc>>,
+    accept_legacy: Option<bool>,
+) -> Result<MultiVerificationReport, String> {
+    log::info!(
+        "Verifying {} signature paths for document '{}'",
+        signature_paths.len(),
+        document_path
+    );
+    if signature_paths.is_empty() {
+        return Err("No signature files given.".to_string());
+    }
+    _verify_signatures(
+        &app_handle,
+        &document_path,
+        &signature_paths,
+        as_of,
+        accept_legacy.unwrap_or(true),
     )
+    .map_err(|e| {
+        log::error!("Failed to verify signatures: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _verify_signatures(
+    app_handle: &tauri::AppHandle,
+    document_path: &str,
+    signature_paths: &[String],
+    as_of: Option<DateTime<Utc>>,
+    accept_legacy: bool,
+) -> Result<MultiVerificationReport> {
+    let candidates = signer_candidates(app_handle)?;
+    let trusted_tsas = trusted_tsa_certificates(app_handle)?;
+    let document = open_document(app_handle, document_path)?;
+    // 内容 URI 不支持通配符，单独打开
+    let (uris, patterns): (Vec<String>, Vec<String>) = signature_paths
+        .iter()
+        .cloned()
+        .partition(|path| is_content_uri(path));
+    let opened = uris
+        .iter()
+        .map(|uri| open_document(app_handle, uri))
+        .collect::<Result<Vec<_>>>()?;
+    let mut signature_paths: Vec<PathBuf> = opened
+        .iter()
+        .map(|handle| handle.path().to_path_buf())
+        .collect();
+    signature_paths.extend(expand_signature_paths(&patterns)?);
+    let describe = |path: &Path| match opened.iter().find(|handle| handle.path() == path) {
+        Some(handle) => handle.describe(path),
+        None => path_string(path),
+    };
+    let verify = |signature_path: &PathBuf| {
+        verify_against_candidates(
+            document.path(),
+            signature_path,
+            &candidates,
+            &trusted_tsas,
+            as_of,
+            accept_legacy,
+        )
+        .map(|report| SignatureFileReport {
+            signature_path: describe(signature_path),
+            ..report
+        })
+        .unwrap_or_else(|e| SignatureFileReport {
+            signature_path: describe(signature_path),
+            signer_name: None,
+            signer_fingerprint: None,
+            result: VerificationResult {
+                is_valid: false,
+                error_message: Some(format!("{:#}", e)),
+                ..Default::default()
+            },
+        })
+    };
+    let mut signatures = vec![None; signature_paths.len()];
+    hash_in_pool(app_handle, &signature_paths, verify, |index, report| {
+        signatures[index] = Some(report);
+        Ok(())
+    })?;
+    let signatures: Vec<SignatureFileReport> = signatures.into_iter().flatten().collect();
+    Ok(MultiVerificationReport {
+        document_path: document.describe(document.path()),
+        valid_count: signatures.iter().filter(|s| s.result.is_valid).count(),
+        signatures,
+    })
+}
+
+/// A public key that may have produced a signature: one of our keys or a contact's.
+pub(crate) struct SignerCandidate {
+    pub(crate) name: String,
+    pub(crate) fingerprint: String,
+    pub(crate) algorithm: SignatureAlgorithm,
+    pub(crate) public_key_der: Vec<u8>,
+    /// A contact marked as distrusted: its signatures are reported, never accepted.
+    pub(crate) distrusted: bool,
+}
+
+/// What a signature file is judged against besides the signer's key.
+struct VerificationContext<'a> {
+    /// Judge time-stamp certificates as of this time instead of now.
+    as_of: Option<DateTime<Utc>>,
+    /// Also accept detached signatures that hashed the document twice.
+    accept_legacy: bool,
+    /// Own keys and contacts; the co-signer of a hybrid envelope and the approver of a
+    /// two-person signature must be one of them.
+    trusted_keys: &'a [SignerCandidate],
+    /// Certificates that time-stamping authorities must chain to.
+    trusted_tsas: &'a [Certificate],
+}
+
+// 收集本地密钥与联系人公钥作为候选签名者
+pub(crate) fn signer_candidates(app_handle: &tauri::AppHandle) -> Result<Vec<SignerCandidate>> {
+    let mut candidates = Vec::new();
+    for metadata in read_metadata(&get_metadata_path(app_handle)?)? {
+        let public_key_der = decode_public_key_pem(&read_public_key_pem(app_handle, &metadata)?)?;
+        candidates.push(SignerCandidate {
+            name: metadata.name.clone(),
+            fingerprint: compute_fingerprint(&public_key_der),
+            algorithm: metadata.algorithm.clone(),
+            public_key_der,
+            distrusted: false,
+        });
+    }
+    for passkey in list_passkeys_for_verification(app_handle)? {
+        candidates.push(SignerCandidate {
+            name: passkey.name,
+            fingerprint: passkey.fingerprint,
+            algorithm: SignatureAlgorithm::EcdsaP256Sha256,
+            public_key_der: decode_public_key_pem(&passkey.public_key_pem)?,
+            distrusted: false,
+        });
+    }
+    for kms_key in list_kms_keys_for_verification(app_handle)? {
+        candidates.push(SignerCandidate {
+            public_key_der: decode_public_key_pem(&kms_key.public_key_pem)?,
+            name: kms_key.name,
+            fingerprint: kms_key.fingerprint,
+            algorithm: kms_key.algorithm,
+            distrusted: false,
+        });
+    }
+    for contact in read_contacts(&get_contacts_path(app_handle)?)? {
+        candidates.push(SignerCandidate {
+            name: contact.name,
+            fingerprint: contact.fingerprint,
+            algorithm: contact.algorithm.clone(),
+            public_key_der: decode_public_key_pem(&contact.public_key_pem)?,
+            distrusted: contact.trust == TrustLevel::Distrusted,
+        });
+    }
+    Ok(candidates)
+}
+
+// 混合信封的后量子签名者只在本地密钥与联系人中按指纹查找，不使用信封里附带的公钥
+fn find_pq_cosigner<'a>(
+    trusted_keys: &'a [SignerCandidate],
+    cosigner: &PqCosigner,
+) -> Result<&'a SignerCandidate> {
+    trusted_keys
+        .iter()
+        .find(|key| {
+            !key.distrusted
+                && key.algorithm == SignatureAlgorithm::MlDsa65
+                && key.fingerprint == cosigner.fingerprint
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Post-quantum co-signer {} is neither one of your keys nor a contact",
+                cosigner.fingerprint
+            )
+        })
+}
+
+// 信封按指纹定位签名者；分离签名逐一尝试候选公钥
+pub(crate) fn verify_against_candidates(
+    document_path: &Path,
+    signature_path: &Path,
+    candidates: &[SignerCandidate],
+    trusted_tsas: &[Certificate],
+    as_of: Option<DateTime<Utc>>,
+    accept_legacy: bool,
+) -> Result<SignatureFileReport> {
+    let signature_bytes = read_file(signature_path)
+        .with_context(|| format!("Failed to read signature file: {:?}", signature_path))?;
+    let named_signer = match SignatureEnvelope::parse(&signature_bytes)? {
+        Some(envelope) => Some(envelope.attributes()?.key_fingerprint),
+        None => None,
+    };
+    let context = VerificationContext {
+        as_of,
+        accept_legacy,
+        trusted_keys: candidates,
+        trusted_tsas,
+    };
+    let mut last_result = None;
+    for candidate in candidates {
+        if named_signer
+            .as_ref()
+            .is_some_and(|fingerprint| *fingerprint != candidate.fingerprint)
+        {
+            continue;
+        }
+        let mut result = match verify_with_public_key(
+            document_path,
+            signature_path,
+            &candidate.algorithm,
+            &candidate.public_key_der,
+            &context,
+            &|_, _| Ok(()),
+        ) {
+            Ok(result) => result,
+            // 分离签名与该候选公钥的格式不符（例如长度不对），继续尝试下一个
+            Err(_) if named_signer.is_none() => continue,
+            Err(e) => return Err(e),
+        };
+        let distrusted_signer = result.is_valid && candidate.distrusted;
+        if distrusted_signer {
+            result.is_valid = false;
+            result.error_message = Some(format!(
+                "Signed with the key of '{}', a contact you marked as distrusted",
+                candidate.name
+            ));
+        }
+        if result.is_valid || distrusted_signer || named_signer.is_some() {
+            return Ok(SignatureFileReport {
+                signature_path: path_string(signature_path),
+                signer_name: Some(candidate.name.clone()),
+                signer_fingerprint: Some(candidate.fingerprint.clone()),
+                result,
+            });
+        }
+        last_result = Some(result);
+    }
+    let message = match &named_signer {
+        Some(fingerprint) => format!(
+            "Signer key {} is neither one of your keys nor a contact",
+            fingerprint
+        ),
+        None => "No known key validates this signature".to_string(),
+    };
+    Ok(SignatureFileReport {
+        signature_path: path_string(signature_path),
+        signer_name: None,
+        signer_fingerprint: named_signer,
+        result: VerificationResult {
+            is_valid: false,
+            error_message: Some(message),
+            ..last_result.unwrap_or_default()
+        },
+    })
+}
+
+// 展开路径中的通配符（仅支持文件名部分的 `*` 与 `?`）
+fn expand_signature_paths(patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for pattern in patterns {
+        let path = native_path(pattern)?;
+        let file_pattern = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if !file_pattern.contains(['*', '?']) {
+            paths.push(path);
+            continue;
+        }
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let mut matched: Vec<PathBuf> = fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory {:?}", dir))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+            .filter(|entry| wildcard_match(&file_pattern, &entry.file_name().to_string_lossy()))
+            .map(|entry| entry.path())
+            .collect();
+        if matched.is_empty() {
+            bail!("No signature files match {}", pattern);
+        }
+        matched.sort();
+        paths.extend(matched);
+    }
+    paths.dedup();
+    Ok(paths)
+}
+
+// 简单通配符匹配：`*` 匹配任意串，`?` 匹配单个字符
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let (mut star, mut resume) = (None, 0);
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some(p);
+            resume = n;
+            p += 1;
+        } else if let Some(star_at) = star {
+            p = star_at + 1;
+            resume += 1;
+            n = resume;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 // Internal verification function returns Result<VerificationResult>
 fn verify_signature_detached(
     app_handle: &tauri::AppHandle,
-    document_path_str: &str,
-    signature_path_str: &str,
-    key_id: Uuid,
+    document_path: &Path,
+    signature_path: &Path,
+    key_id: Option<Uuid>,
+    as_of: Option<DateTime<Utc>>,
+    accept_legacy: bool,
+    progress: &dyn Fn(u64, u64) -> Result<()>,
 ) -> Result<VerificationResult> {
     // Return internal Result
+    let trusted_keys = signer_candidates(app_handle)?;
+    let trusted_tsas = trusted_tsa_certificates(app_handle)?;
+    let context = VerificationContext {
+        as_of,
+        accept_legacy,
+        trusted_keys: &trusted_keys,
+        trusted_tsas: &trusted_tsas,
+    };
+    // 0. Without a key ID, take the signer named in the envelope (own keys, then contacts)
+    let Some(key_id) = key_id else {
+        let signature_bytes = read_file(signature_path)
+            .with_context(|| format!("Failed to read signature file: {:?}", signature_path))?;
+        let envelope = SignatureEnvelope::parse(&signature_bytes)?.ok_or_else(|| {
+            anyhow!("A detached signature does not name its signer; choose the key to verify with")
+        })?;
+        let (algorithm, public_key_der) =
+            resolve_envelope_signer(app_handle, &envelope.attributes()?)?;
+        return verify_with_public_key(
+            document_path,
+            signature_path,
+            &algorithm,
+            &public_key_der,
+            &context,
+            progress,
+        );
+    };
+    // 0a. Passkeys and KMS keys are kept apart from the file-based keys
+    if let Some(passkey) = find_passkey(app_handle, key_id)? {
+        return verify_with_public_key(
+            document_path,
+            signature_path,
+            &SignatureAlgorithm::EcdsaP256Sha256,
+            &decode_public_key_pem(&passkey.public_key_pem)?,
+            &context,
+            progress,
+        );
+    }
+    if let Some(kms_key) = find_kms_key(app_handle, key_id)? {
+        return verify_with_public_key(
+            document_path,
+            signature_path,
+            &kms_key.algorithm,
+            &decode_public_key_pem(&kms_key.public_key_pem)?,
+            &context,
+            progress,
+        );
+    }
+
     // 1. Find key metadata and parse algorithm
-    let metadata_path = get_metadata_path(app_handle)?;
-    let metadata = read_metadata(&metadata_path)?
-        .into_iter()
-        .find(|m| m.key_id == key_id)
-        .ok_or_else(|| anyhow::anyhow!("Key with ID {} not found", key_id))?;
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let algorithm = metadata.algorithm.clone();
 
-    let algorithm = SignatureAlgorithm::from_str(&metadata.algorithm).with_context(|| {
-        format!(
-            "Invalid algorithm '{}' found in metadata for key {}",
-            metadata.algorithm, key_id
-        )
-    })?;
+    // 2. Read public key PEM file and decode it to SPKI DER bytes
+    let public_key_pem = read_public_key_pem(app_handle, &metadata)?;
+    let public_key_der = decode_public_key_pem(&public_key_pem)?;
 
-    // 2. Read public key PEM file
-    let key_storage_dir = get_key_storage_dir(app_handle)?;
-    let public_key_path = key_storage_dir.join(&metadata.public_key_pem_path);
-    let public_key_pem = fs::read_to_string(&public_key_path)
-        .with_context(|| format!("Failed to read public key file: {:?}", public_key_path))?;
+    verify_with_public_key(
+        document_path,
+        signature_path,
+        &algorithm,
+        &public_key_der,
+        &context,
+        progress,
+    )
+}
 
-    // 3. Decode PEM to get SPKI DER bytes
-    let (label, public_key_der) = decode_vec(public_key_pem.as_bytes())
-        .map_err(|e| anyhow::anyhow!("Failed to decode public key PEM: {}", e))?;
-    if label != SPKI_PEM_TAG {
-        bail!(
-            "Invalid PEM label for public key: expected '{}', found '{}'",
-            SPKI_PEM_TAG,
-            label
-        );
+// 用给定公钥校验签名文件（分离签名或信封），并补全耗时统计
+fn verify_with_public_key(
+    document_path: &Path,
+    signature_path: &Path,
+    algorithm: &SignatureAlgorithm,
+    public_key_der: &[u8],
+    context: &VerificationContext,
+    progress: &dyn Fn(u64, u64) -> Result<()>,
+) -> Result<VerificationResult> {
+    let started = Instant::now();
+    let mut result = check_with_public_key(
+        document_path,
+        signature_path,
+        algorithm,
+        public_key_der,
+        context,
+        progress,
+    )?;
+    if let Some(metrics) = result.metrics.as_mut() {
+        finish_metrics(metrics, started);
     }
+    Ok(result)
+}
 
-    // 4. Read document data
-    let document_bytes = fs::read(document_path_str)
-        .with_context(|| format!("Failed to read document file: {}", document_path_str))?;
+fn check_with_public_key(
+    document_path: &Path,
+    signature_path: &Path,
+    algorithm: &SignatureAlgorithm,
+    public_key_der: &[u8],
+    context: &VerificationContext,
+    progress: &dyn Fn(u64, u64) -> Result<()>,
+) -> Result<VerificationResult> {
+    // 3. Read signature file (Base64 and PEM detached signatures are decoded)
+    let signature_bytes = read_signature_file(signature_path)?;
+    let envelope = SignatureEnvelope::parse(&signature_bytes)?;
 
-    // 5. Read signature file
-    let signature_bytes = fs::read(signature_path_str)
-        .with_context(|| format!("Failed to read signature file: {}", signature_path_str))?;
-    let signature = signature_bytes
-        .as_slice()
-        .try_into()
-        .context("Failed to convert signature bytes")?;
+    // 4. Read the document once, in chunks; detached Ed25519 signatures are checked on the way
+    let mut ed25519 = match (&envelope, algorithm) {
+        (None, SignatureAlgorithm::Ed25519) => {
+            Some(Ed25519Check::new(public_key_der, &signature_bytes)?)
+        }
+        _ => None,
+    };
+    let mut curve_hash = match &envelope {
+        None => curve_hasher(algorithm),
+        Some(_) => None,
+    };
+    let hash_started = Instant::now();
+    let (document_digest, document_size) = read_document(
+        document_path,
+        ed25519.as_mut(),
+        curve_hash.as_deref_mut(),
+        progress,
+    )?;
+    let curve_digest = curve_hash.map(|hasher| hasher.finalize());
 
-    // 6. Algorithm-specific verification
-    log::debug!("Performing verification with algorithm: {}", algorithm);
+    // The computed digest is always reported so users can compare it with published checksums
+    let mut digest_report = VerificationResult {
+        digest_algorithm: Some(DIGEST_SHA256.to_string()),
+        computed_digest: Some(hex::encode(&document_digest)),
+        metrics: Some(OperationMetrics {
+            bytes_processed: document_size,
+            hash_ms: millis(hash_started.elapsed()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
 
-    let verification_result = match algorithm {
-        SignatureAlgorithm::RsaPkcs1Sha256 => {
-            let public_key = RsaPublicKey::from_public_key_der(&public_key_der)
-                .context("Failed to parse SPKI DER as RSA public key")?;
-            let verifying_key = RsaVerifyingKey::<RsaSha256>::new(public_key);
-            let mut hasher = Sha256::new();
-            hasher.update(&document_bytes);
-            let digest = hasher.finalize();
-            // Verify the hash against the signature
-            verifying_key.verify(digest.as_slice(), &signature)
-        }
-        SignatureAlgorithm::EcdsaP256Sha256 => {
-            let public_key = EcdsaVerifyingKey::from_public_key_der(&public_key_der)
-                .context("Failed to parse SPKI DER as ECDSA P-256 public key")?;
-            let mut hasher = Sha256::new();
-            hasher.update(&document_bytes);
-            let digest = hasher.finalize();
-            // Try to parse the signature bytes into an ECDSA signature structure
-            let signature = EcdsaSignature::from_slice(&signature_bytes)
-                .context("Failed to parse signature bytes as ECDSA signature")?;
-            // Verify the hash against the signature
-            public_key.verify(digest.as_slice(), &signature)
-        }
-        SignatureAlgorithm::Ed25519 => {
-            let public_key = EdVerifyingKey::from_public_key_der(&public_key_der)
-                .context("Failed to parse SPKI DER as Ed25519 public key")?;
-            // Try to parse the signature bytes into an Ed25519 signature structure
-            let signature = EdSignature::from_slice(&signature_bytes)
-                .context("Failed to parse signature bytes as Ed25519 signature")?;
-            // Verify the original message against the signature
-            public_key.verify(&document_bytes, &signature)
+    // 5. Algorithm-specific verification (envelopes are checked against the document digest)
+    let mut binding_mismatches = Vec::new();
+    let verification_result = match envelope {
+        Some(envelope) => {
+            let attributes = envelope.attributes()?;
+            digest_report.expected_digest = Some(attributes.document_digest.clone());
+            digest_report.signed_at = Some(attributes.signed_at);
+            digest_report.signing_time_source = Some(attributes.time_source);
+            digest_report.metadata = attributes.metadata.clone();
+            digest_report.approval = attributes.approval.clone();
+            digest_report.sequence = attributes.sequence;
+            digest_report.oidc_identity = attributes.oidc_identity.clone();
+            digest_report.pq_cosigner = attributes.pq_cosigner.clone();
+            if let Some(message) = check_envelope_attributes(
+                &attributes,
+                algorithm,
+                public_key_der,
+                digest_report.computed_digest.as_deref().unwrap_or_default(),
+            )? {
+                log::warn!(
+                    "Verification failed for document {:?}: {}",
+                    document_path,
+                    message
+                );
+                return Ok(VerificationResult {
+                    is_valid: false,
+                    error_message: Some(message),
+                    ..digest_report
+                });
+            }
+            if let Some(approval) = &attributes.approval {
+                if let Err(e) = verify_approval(
+                    approval,
+                    &attributes.key_fingerprint,
+                    &attributes.document_digest,
+                    context.trusted_keys,
+                ) {
+                    let message = format!("Two-person approval does not verify: {:#}", e);
+                    log::warn!(
+                        "Verification failed for document {:?}: {}",
+                        document_path,
+                        message
+                    );
+                    return Ok(VerificationResult {
+                        is_valid: false,
+                        error_message: Some(message),
+                        ..digest_report
+                    });
+                }
+            }
+            if let Some(identity) = &attributes.oidc_identity {
+                if let Err(e) = check_identity(identity, &attributes.key_fingerprint) {
+                    let message = format!("Signer identity does not verify: {:#}", e);
+                    log::warn!(
+                        "Verification failed for document {:?}: {}",
+                        document_path,
+                        message
+                    );
+                    return Ok(VerificationResult {
+                        is_valid: false,
+                        error_message: Some(message),
+                        ..digest_report
+                    });
+                }
+            }
+            binding_mismatches = FileBinding::mismatches(&attributes, document_path)?;
+            // 令牌来自不受信任的 TSA 时，签名时间按签名者自述处理
+            match attributes.check_signing_time(context.as_of, context.trusted_tsas) {
+                Ok(time_source) => digest_report.signing_time_source = Some(time_source),
+                Err(e) => {
+                    let message = format!("Signing time is not backed by the TSA: {:#}", e);
+                    log::warn!(
+                        "Verification failed for document {:?}: {}",
+                        document_path,
+                        message
+                    );
+                    return Ok(VerificationResult {
+                        is_valid: false,
+                        error_message: Some(message),
+                        ..digest_report
+                    });
+                }
+            }
+            if digest_report.signing_time_source == Some(TimeSource::LocalClock) {
+                log::info!(
+                    "Signing time of {:?} is from the local clock",
+                    signature_path
+                );
+            }
+            digest_report.timestamps =
+                envelope.verify_timestamps(context.as_of, context.trusted_tsas);
+            if let Some(invalid) = digest_report.timestamps.iter().find(|t| !t.is_valid) {
+                let message = format!(
+                    "Time-stamp from {} is invalid: {}",
+                    invalid.tsa_url,
+                    invalid.error_message.as_deref().unwrap_or_default()
+                );
+                log::warn!(
+                    "Verification failed for document {:?}: {}",
+                    document_path,
+                    message
+                );
+                return Ok(VerificationResult {
+                    is_valid: false,
+                    error_message: Some(message),
+                    ..digest_report
+                });
+            }
+            match &attributes.pq_cosigner {
+                None => envelope.verify_signature(algorithm, public_key_der)?,
+                Some(cosigner) => match find_pq_cosigner(context.trusted_keys, cosigner) {
+                    Ok(pq_key) => envelope.verify_hybrid_signature(
+                        algorithm,
+                        public_key_der,
+                        &pq_key.public_key_der,
+                    )?,
+                    Err(e) => {
+                        let message = e.to_string();
+                        log::warn!(
+                            "Verification failed for document {:?}: {}",
+                            document_path,
+                            message
+                        );
+                        return Ok(VerificationResult {
+                            is_valid: false,
+                            error_message: Some(message),
+                            ..digest_report
+                        });
+                    }
+                },
+            }
         }
+        None => match (ed25519, algorithm) {
+            (Some(check), _) => {
+                let (pure, prehashed) = check.finish();
+                digest_report.prehashed = pure.is_err() && prehashed.is_ok();
+                pure.or(prehashed)
+            }
+            // ML-DSA signs the whole message, so (as when signing) the document is read
+            // into memory
+            (None, SignatureAlgorithm::MlDsa65) => {
+                let document_bytes = read_file(document_path).with_context(|| {
+                    format!("Failed to read document file: {:?}", document_path)
+                })?;
+                verify_bytes(
+                    algorithm,
+                    public_key_der,
+                    &document_bytes,
+                    &signature_bytes,
+                    DigestScheme::Standard,
+                )?
+            }
+            (None, _) => {
+                let standard = verify_prehash(
+                    algorithm,
+                    public_key_der,
+                    curve_digest.as_deref().unwrap_or(&document_digest),
+                    &signature_bytes,
+                )?;
+                // Signatures from earlier releases hashed the document twice
+                if standard.is_err() && context.accept_legacy {
+                    let legacy = verify_prehash(
+                        algorithm,
+                        public_key_der,
+                        &prehash(algorithm, &document_digest),
+                        &signature_bytes,
+                    )?;
+                    digest_report.legacy_digest = legacy.is_ok();
+                    legacy.or(standard)
+                } else {
+                    standard
+                }
+            }
+        },
     };
 
-    // 7. Convert verification result (Ok or signature::Error) to VerificationResult struct
+    // 6. Convert verification result (Ok or signature::Error) to VerificationResult struct
     match verification_result {
-        Ok(_) => {
-            log::info!(
-                "Verification successful for document: {}",
-                document_path_str
+        Ok(_) if !binding_mismatches.is_empty() => {
+            let fields: Vec<&str> = binding_mismatches
+                .iter()
+                .map(|m| m.field.as_str())
+                .collect();
+            log::warn!(
+                "File binding mismatch for document {:?}: {}",
+                document_path,
+                fields.join(", ")
             );
+            Ok(VerificationResult {
+                is_valid: false,
+                error_message: Some(format!(
+                    "The file does not match the file attributes bound into the signature: {}",
+                    fields.join(", ")
+                )),
+                binding_mismatches,
+                ..digest_report
+            })
+        }
+        Ok(_) => {
+            log::info!("Verification successful for document: {:?}", document_path);
             Ok(VerificationResult {
                 is_valid: true,
                 error_message: None,
+                ..digest_report
             })
         }
         Err(sig_err) => {
             // This specifically means the cryptographic verification failed
             log::warn!(
-                "Verification failed for document {}: {}",
-                document_path_str,
+                "Verification failed for document {:?}: {}",
+                document_path,
                 sig_err
             );
             Ok(VerificationResult {
                 is_valid: false,
                 error_message: Some(format!("Signature is invalid: {}", sig_err)),
+                ..digest_report
             })
         }
     }
 }
+
+/// Checks a detached Ed25519 signature while the document is read, both as pure Ed25519
+/// and as Ed25519ph (over the SHA-512 of the document, no context).
+struct Ed25519Check {
+    key: EdVerifyingKey,
+    signature: EdSignature,
+    pure: StreamVerifier,
+    prehash: Sha512,
+}
+
+impl Ed25519Check {
+    fn new(public_key_der: &[u8], signature_bytes: &[u8]) -> Result<Self> {
+        let key = EdVerifyingKey::from_public_key_der(public_key_der)
+            .context("Failed to parse SPKI DER as Ed25519 public key")?;
+        let signature = EdSignature::from_slice(signature_bytes)
+            .context("Failed to parse signature bytes as Ed25519 signature")?;
+        Ok(Ed25519Check {
+            pure: key.verify_stream(&signature)?,
+            key,
+            signature,
+            prehash: Sha512::new(),
+        })
+    }
+
+    fn update(&mut self, chunk: &[u8]) {
+        self.pure.update(chunk);
+        self.prehash.update(chunk);
+    }
+
+    /// Results as pure Ed25519 and as Ed25519ph.
+    fn finish(self) -> (Result<(), signature::Error>, Result<(), signature::Error>) {
+        (
+            self.pure.finalize_and_verify(),
+            self.key
+                .verify_prehashed(self.prehash, None, &self.signature),
+        )
+    }
+}
+
+// 分块读取文档并计算 SHA-256（内存占用恒定），每读完一段报告一次进度；
+// extra_hasher 同时计算另一个摘要（ECDSA P-384 / P-521 所用的摘要，或 Ed25519 签名的消息哈希）
+fn read_document(
+    document_path: &Path,
+    mut ed25519: Option<&mut Ed25519Check>,
+    mut extra_hasher: Option<&mut (dyn digest::DynDigest + 'static)>,
+    progress: &dyn Fn(u64, u64) -> Result<()>,
+) -> Result<(Vec<u8>, u64)> {
+    let mut file = open_file(document_path)
+        .with_context(|| format!("Failed to read document file: {:?}", document_path))?;
+    let total = file.metadata()?.len();
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; VERIFY_BUFFER_SIZE];
+    let mut done = 0u64;
+    let mut reported = 0u64;
+    progress(0, total)?;
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("Failed to read document file: {:?}", document_path))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        if let Some(check) = ed25519.as_mut() {
+            check.update(&buffer[..read]);
+        }
+        if let Some(hasher) = extra_hasher.as_mut() {
+            hasher.update(&buffer[..read]);
+        }
+        done += read as u64;
+        if done - reported >= PROGRESS_INTERVAL_BYTES {
+            progress(done, total)?;
+            reported = done;
+        }
+    }
+    progress(done, total.max(done))?;
+    Ok((hasher.finalize().to_vec(), done))
+}
+
+// 总耗时减去密钥解锁与读取哈希，剩下的记为签名/校验本身
+pub(crate) fn finish_metrics(metrics: &mut OperationMetrics, started: Instant) {
+    let total = started.elapsed();
+    metrics.total_ms = millis(total);
+    metrics.signature_ms = metrics
+        .total_ms
+        .saturating_sub(metrics.hash_ms + metrics.kdf_ms.unwrap_or_default());
+    metrics.mib_per_second =
+        metrics.bytes_processed as f64 / (1024.0 * 1024.0) / total.as_secs_f64().max(1e-6);
+}
+
+pub(crate) fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+// 用文档的摘要校验 RSA / ECDSA 分离签名（与签名时对文档本身哈希一次等价）；
+// 摘要为签名算法所用的哈希，见 prehash
+fn verify_prehash(
+    algorithm: &SignatureAlgorithm,
+    public_key_der: &[u8],
+    prehash: &[u8],
+    signature_bytes: &[u8],
+) -> Result<Result<(), signature::Error>> {
+    match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => {
+            let public_key = RsaPublicKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as RSA public key")?;
+            let verifying_key = RsaVerifyingKey::<RsaSha256>::new(public_key);
+            let signature = signature_bytes
+                .try_into()
+                .context("Failed to convert signature bytes")?;
+            Ok(verifying_key.verify_prehash(prehash, &signature))
+        }
+        SignatureAlgorithm::EcdsaP256Sha256 => {
+            let public_key = EcdsaVerifyingKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as ECDSA P-256 public key")?;
+            let signature = parse_ecdsa_signature(signature_bytes)?;
+            Ok(public_key.verify_prehash(prehash, &signature))
+        }
+        SignatureAlgorithm::EcdsaP384Sha384 => {
+            let public_key = P384VerifyingKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as ECDSA P-384 public key")?;
+            let signature = parse_p384_signature(signature_bytes)?;
+            Ok(public_key.verify_prehash(prehash, &signature))
+        }
+        SignatureAlgorithm::EcdsaP521Sha512 => {
+            let signature = parse_p521_signature(signature_bytes)?;
+            Ok(p521_verifying_key(public_key_der)?.verify_prehash(prehash, &signature))
+        }
+        SignatureAlgorithm::Ed25519 | SignatureAlgorithm::MlDsa65 => {
+            bail!("{} signatures cannot be checked from a digest", algorithm)
+        }
+    }
+}
+
+/// Verifies `signature_bytes` over `data` with an SPKI DER public key.
+///
+/// The outer `Result` reports processing errors (unparsable key or signature); the inner
+/// one reports whether the signature is cryptographically valid.
+pub(crate) fn verify_bytes(
+    algorithm: &SignatureAlgorithm,
+    public_key_der: &[u8],
+    data: &[u8],
+    signature_bytes: &[u8],
+    scheme: DigestScheme,
+) -> Result<Result<(), signature::Error>> {
+    log::debug!(
+        "Performing verification with algorithm: {} ({:?})",
+        algorithm,
+        scheme
+    );
+    let message = signed_message(data, scheme);
+
+    let verification_result = match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => {
+            let public_key = RsaPublicKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as RSA public key")?;
+            let verifying_key = RsaVerifyingKey::<RsaSha256>::new(public_key);
+            let signature = signature_bytes
+                .try_into()
+                .context("Failed to convert signature bytes")?;
+            verifying_key.verify(&message, &signature)
+        }
+        SignatureAlgorithm::EcdsaP256Sha256 => {
+            let public_key = EcdsaVerifyingKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as ECDSA P-256 public key")?;
+            // Accept both the fixed-size r||s form and ASN.1 DER (OpenSSL, Java, ...)
+            let signature = parse_ecdsa_signature(signature_bytes)?;
+            public_key.verify(&message, &signature)
+        }
+        SignatureAlgorithm::EcdsaP384Sha384 => {
+            let public_key = P384VerifyingKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as ECDSA P-384 public key")?;
+            let signature = parse_p384_signature(signature_bytes)?;
+            public_key.verify(&message, &signature)
+        }
+        SignatureAlgorithm::EcdsaP521Sha512 => {
+            let signature = parse_p521_signature(signature_bytes)?;
+            p521_verifying_key(public_key_der)?.verify(&message, &signature)
+        }
+        SignatureAlgorithm::Ed25519 => {
+            let public_key = EdVerifyingKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as Ed25519 public key")?;
+            // Try to parse the signature bytes into an Ed25519 signature structure
+            let signature = EdSignature::from_slice(signature_bytes)
+                .context("Failed to parse signature bytes as Ed25519 signature")?;
+            // Verify the original message against the signature
+            public_key.verify(data, &signature)
+        }
+        SignatureAlgorithm::MlDsa65 => {
+            let public_key = MlDsaVerifyingKey::<MlDsa65>::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as ML-DSA-65 public key")?;
+            let signature = MlDsaSignature::<MlDsa65>::try_from(signature_bytes)
+                .context("Failed to parse signature bytes as ML-DSA-65 signature")?;
+            public_key.verify(data, &signature)
+        }
+    };
+    Ok(verification_result)
+}
+
+// RSA / ECDSA 实际签名的消息：标准方案为数据本身，旧方案为数据的 SHA-256（签名时会再哈希一次）
+fn signed_message(data: &[u8], scheme: DigestScheme) -> Cow<'_, [u8]> {
+    match scheme {
+        DigestScheme::Standard => Cow::Borrowed(data),
+        DigestScheme::Legacy => Cow::Owned(Sha256::digest(data).to_vec()),
+    }
+}
+
+// 解析 ECDSA P-256 签名：64 字节为 r||s 原始编码，否则按 ASN.1 DER 解析
+fn parse_ecdsa_signature(signature_bytes: &[u8]) -> Result<EcdsaSignature> {
+    if signature_bytes.len() == 64 {
+        EcdsaSignature::from_slice(signature_bytes)
+            .context("Failed to parse signature bytes as raw ECDSA signature")
+    } else {
+        EcdsaSignature::from_der(signature_bytes)
+            .context("Failed to parse signature bytes as DER ECDSA signature")
+    }
+}
+
+// 解析 ECDSA P-384 签名：96 字节为 r||s 原始编码，否则按 ASN.1 DER 解析
+fn parse_p384_signature(signature_bytes: &[u8]) -> Result<P384Signature> {
+    if signature_bytes.len() == 96 {
+        P384Signature::from_slice(signature_bytes)
+            .context("Failed to parse signature bytes as raw ECDSA signature")
+    } else {
+        P384Signature::from_der(signature_bytes)
+            .context("Failed to parse signature bytes as DER ECDSA signature")
+    }
+}
+
+// 解析 ECDSA P-521 签名：132 字节为 r||s 原始编码，否则按 ASN.1 DER 解析
+fn parse_p521_signature(signature_bytes: &[u8]) -> Result<P521Signature> {
+    if signature_bytes.len() == 132 {
+        P521Signature::from_slice(signature_bytes)
+            .context("Failed to parse signature bytes as raw ECDSA signature")
+    } else {
+        P521Signature::from_der(signature_bytes)
+            .context("Failed to parse signature bytes as DER ECDSA signature")
+    }
+}
+
+// p521 的签名/校验密钥不直接支持 PKCS#8 与 SPKI，先解析为 SecretKey / PublicKey
+fn p521_signing_key(private_key_der: &[u8]) -> Result<P521SigningKey> {
+    let secret_key = p521::SecretKey::from_pkcs8_der(private_key_der)
+        .context("Failed to parse decrypted data as ECDSA P-521 private key")?;
+    P521SigningKey::from_bytes(&secret_key.to_bytes()).context("Invalid ECDSA P-521 private key")
+}
+
+fn p521_verifying_key(public_key_der: &[u8]) -> Result<P521VerifyingKey> {
+    let public_key = p521::PublicKey::from_public_key_der(public_key_der)
+        .context("Failed to parse SPKI DER as ECDSA P-521 public key")?;
+    P521VerifyingKey::from_affine(*public_key.as_affine()).context("Invalid ECDSA P-521 public key")
+}
+
+// ECDSA P-384 / P-521 签名时对消息所用的哈希（SHA-384 / SHA-512）；其余算法返回 None
+fn curve_hasher(algorithm: &SignatureAlgorithm) -> Option<Box<dyn digest::DynDigest>> {
+    match algorithm {
+        SignatureAlgorithm::EcdsaP384Sha384 => Some(Box::new(Sha384::new())),
+        SignatureAlgorithm::EcdsaP521Sha512 => Some(Box::new(Sha512::new())),
+        _ => None,
+    }
+}
+
+// RSA / ECDSA 签名时对 data 计算的摘要：P-384 / P-521 见 curve_hasher，其余为 SHA-256
+fn prehash(algorithm: &SignatureAlgorithm, data: &[u8]) -> Vec<u8> {
+    match curve_hasher(algorithm) {
+        Some(mut hasher) => {
+            hasher.update(data);
+            hasher.finalize().to_vec()
+        }
+        None => Sha256::digest(data).to_vec(),
+    }
+}
+
+/// Writes detached signature bytes in the requested file encoding.
+pub(crate) fn encode_signature(signature: &[u8], encoding: SignatureEncoding) -> Result<Vec<u8>> {
+    match encoding {
+        SignatureEncoding::Binary => Ok(signature.to_vec()),
+        SignatureEncoding::Base64 => Ok(format!("{}\n", BASE64.encode(signature)).into_bytes()),
+        SignatureEncoding::Pem => Ok(pem_rfc7468::encode_string(
+            SIGNATURE_PEM_LABEL,
+            pem_rfc7468::LineEnding::LF,
+            signature,
+        )
+        .map_err(|e| anyhow!("Failed to encode signature to PEM: {}", e))?
+        .into_bytes()),
+    }
+}
+
+/// Reads a signature file; Base64 and PEM-armored detached signatures are decoded, binary
+/// signatures and envelopes are returned as they are.
+pub(crate) fn read_signature_file(signature_path: &Path) -> Result<Vec<u8>> {
+    let content = read_file(signature_path)
+        .with_context(|| format!("Failed to read signature file: {:?}", signature_path))?;
+    decode_signature(&content)
+}
+
+/// Undoes `encode_signature`, telling the encodings apart by content.
+pub(crate) fn decode_signature(content: &[u8]) -> Result<Vec<u8>> {
+    let Some(text) = std::str::from_utf8(content).ok().map(str::trim) else {
+        return Ok(content.to_vec());
+    };
+    if text.starts_with(&format!("-----BEGIN {}-----", SIGNATURE_PEM_LABEL)) {
+        let (_, signature) = pem_rfc7468::decode_vec(text.as_bytes())
+            .map_err(|e| anyhow!("Malformed PEM signature: {}", e))?;
+        return Ok(signature);
+    }
+    // 二进制签名几乎不可能全由 Base64 字符组成；信封以 `{` 开头，不会被误判
+    let compact: String = text.split_ascii_whitespace().collect();
+    let base64_text = !compact.is_empty()
+        && compact
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'='));
+    match BASE64.decode(&compact) {
+        Ok(signature) if base64_text => Ok(signature),
+        _ => Ok(content.to_vec()),
+    }
+}
+
+/// Re-encodes an ECDSA signature produced by `sign_bytes` in the requested encoding.
+/// The curve is told apart by the length of the raw `r || s` signature.
+pub(crate) fn encode_ecdsa_signature(
+    raw_signature: &[u8],
+    encoding: EcdsaEncoding,
+) -> Result<Vec<u8>> {
+    let context = "Failed to parse ECDSA signature";
+    match (encoding, raw_signature.len()) {
+        (EcdsaEncoding::Raw, _) => Ok(raw_signature.to_vec()),
+        (EcdsaEncoding::Der, 96) => Ok(P384Signature::from_slice(raw_signature)
+            .context(context)?
+            .to_der()
+            .as_bytes()
+            .to_vec()),
+        (EcdsaEncoding::Der, 132) => Ok(P521Signature::from_slice(raw_signature)
+            .context(context)?
+            .to_der()
+            .as_bytes()
+            .to_vec()),
+        (EcdsaEncoding::Der, _) => Ok(EcdsaSignature::from_slice(raw_signature)
+            .context(context)?
+            .to_der()
+            .as_bytes()
+            .to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::self_tests::known_answer_key_pair;
+    use pkcs8::EncodePrivateKey;
+    use rsa::rand_core::OsRng;
+
+    #[test]
+    fn sign_and_verify_bytes_roundtrip() {
+        use pkcs8::EncodePublicKey;
+
+        let data = b"Hello, World!";
+        let ed_key = EdSigningKey::generate(&mut OsRng);
+        let ec_key = EcdsaSigningKey::random(&mut OsRng);
+        let cases = [
+            (
+                SignatureAlgorithm::Ed25519,
+                ed_key.to_pkcs8_der().unwrap().to_bytes().to_vec(),
+                ed_key
+                    .verifying_key()
+                    .to_public_key_der()
+                    .unwrap()
+                    .into_vec(),
+            ),
+            (
+                SignatureAlgorithm::EcdsaP256Sha256,
+                ec_key.to_pkcs8_der().unwrap().to_bytes().to_vec(),
+                ec_key
+                    .verifying_key()
+                    .to_public_key_der()
+                    .unwrap()
+                    .into_vec(),
+            ),
+        ];
+
+        for (algorithm, private_der, public_der) in cases {
+            let signature =
+                sign_bytes(&algorithm, &private_der, data, DigestScheme::Standard).unwrap();
+            assert!(verify_bytes(
+                &algorithm,
+                &public_der,
+                data,
+                &signature,
+                DigestScheme::Standard
+            )
+            .unwrap()
+            .is_ok());
+            assert!(verify_bytes(
+                &algorithm,
+                &public_der,
+                b"tampered",
+                &signature,
+                DigestScheme::Standard
+            )
+            .unwrap()
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn p384_and_p521_detached_signatures_verify_while_streaming() {
+        let dir = std::env::temp_dir().join(format!("sigust-curves-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let document = dir.join("document.txt");
+        fs::write(&document, b"Hello, World!").unwrap();
+        let signature_path = dir.join("document.txt.sig");
+
+        for algorithm in [
+            SignatureAlgorithm::EcdsaP384Sha384,
+            SignatureAlgorithm::EcdsaP521Sha512,
+        ] {
+            let (private_der, public_der) = known_answer_key_pair(&algorithm).unwrap();
+            let raw = sign_bytes(
+                &algorithm,
+                &private_der,
+                b"Hello, World!",
+                DigestScheme::Standard,
+            )
+            .unwrap();
+            let der = encode_ecdsa_signature(&raw, EcdsaEncoding::Der).unwrap();
+            for signature in [&raw, &der] {
+                fs::write(&signature_path, signature).unwrap();
+                let check = || {
+                    check_with_public_key(
+                        &document,
+                        &signature_path,
+                        &algorithm,
+                        &public_der,
+                        &VerificationContext {
+                            as_of: None,
+                            accept_legacy: true,
+                            trusted_keys: &[],
+                            trusted_tsas: &[],
+                        },
+                        &|_, _| Ok(()),
+                    )
+                    .unwrap()
+                };
+                assert!(check().is_valid, "{}", algorithm);
+                fs::write(&document, b"Tampered").unwrap();
+                assert!(!check().is_valid, "{}", algorithm);
+                fs::write(&document, b"Hello, World!").unwrap();
+            }
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn streamed_detached_signatures_verify() {
+        let dir = std::env::temp_dir().join(format!("sigust-streamed-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let document = dir.join("document.txt");
+        let data = vec![0x5a; VERIFY_BUFFER_SIZE * 2 + 17];
+        fs::write(&document, &data).unwrap();
+
+        for algorithm in [
+            SignatureAlgorithm::RsaPkcs1Sha256,
+            SignatureAlgorithm::EcdsaP256Sha256,
+            SignatureAlgorithm::EcdsaP384Sha384,
+            SignatureAlgorithm::EcdsaP521Sha512,
+            SignatureAlgorithm::Ed25519,
+            SignatureAlgorithm::MlDsa65,
+        ] {
+            let (private_der, public_der) = known_answer_key_pair(&algorithm).unwrap();
+            for scheme in [DigestScheme::Standard, DigestScheme::Legacy] {
+                let (signature, size, digest) =
+                    sign_document_file(&algorithm, &private_der, &document, scheme).unwrap();
+                assert!(
+                    verify_bytes(&algorithm, &public_der, &data, &signature, scheme)
+                        .unwrap()
+                        .is_ok(),
+                    "{} ({:?})",
+                    algorithm,
+                    scheme
+                );
+                assert_eq!(size, data.len() as u64);
+                assert_eq!(digest, Sha256::digest(&data).to_vec());
+            }
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn hybrid_envelopes_need_a_known_cosigner() {
+        use crate::key_exchange::encode_public_key_pem;
+        use ml_dsa::{KeyGen, B32};
+        use pkcs8::EncodePublicKey;
+        use std::collections::BTreeMap;
+
+        let dir = std::env::temp_dir().join(format!("sigust-hybrid-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let document = dir.join("contract.txt");
+        fs::write(&document, b"contract").unwrap();
+        let signature_path = dir.join("contract.txt.sig");
+
+        let ed25519 = SignatureAlgorithm::Ed25519;
+        let ml_dsa = SignatureAlgorithm::MlDsa65;
+        let (private_der, public_der) = known_answer_key_pair(&ed25519).unwrap();
+        let (_, known_pq_der) = known_answer_key_pair(&ml_dsa).unwrap();
+        // 换上另一把 ML-DSA 密钥（信封里附带的是它的公钥）重新签名
+        let foreign = MlDsa65::key_gen_internal(&B32::from([7; 32]));
+        let foreign_private_der = foreign.to_pkcs8_der().unwrap();
+        let foreign_public_der = foreign
+            .verifying_key()
+            .to_public_key_der()
+            .unwrap()
+            .into_vec();
+        let attributes = SignedAttributes {
+            format: HYBRID_ENVELOPE_FORMAT.to_string(),
+            version: ENVELOPE_VERSION,
+            algorithm: ed25519.to_string(),
+            key_id: Uuid::nil(),
+            key_fingerprint: compute_fingerprint(&public_der),
+            digest_algorithm: DIGEST_SHA256.to_string(),
+            document_digest: hex::encode(Sha256::digest(b"contract")),
+            signed_at: Utc::now(),
+            time_source: TimeSource::LocalClock,
+            time_token: None,
+            file_name: None,
+            file_size: None,
+            file_modified_at: None,
+            previous_envelope: None,
+            metadata: BTreeMap::new(),
+            approval: None,
+            sequence: None,
+            oidc_identity: None,
+            pq_cosigner: Some(PqCosigner {
+                key_id: Uuid::nil(),
+                algorithm: ml_dsa.to_string(),
+                public_key_pem: encode_public_key_pem(&foreign_public_der).unwrap(),
+                fingerprint: compute_fingerprint(&foreign_public_der),
+            }),
+        };
+        let envelope = SignatureEnvelope::seal_hybrid(
+            &attributes,
+            &private_der,
+            foreign_private_der.as_bytes(),
+        )
+        .unwrap();
+        fs::write(&signature_path, serde_json::to_vec(&envelope).unwrap()).unwrap();
+
+        let candidate = |name: &str, public_key_der: &[u8]| SignerCandidate {
+            name: name.to_string(),
+            fingerprint: compute_fingerprint(public_key_der),
+            algorithm: ml_dsa.clone(),
+            public_key_der: public_key_der.to_vec(),
+            distrusted: false,
+        };
+        let check = |trusted_keys: &[SignerCandidate]| {
+            check_with_public_key(
+                &document,
+                &signature_path,
+                &ed25519,
+                &public_der,
+                &VerificationContext {
+                    as_of: None,
+                    accept_legacy: true,
+                    trusted_keys,
+                    trusted_tsas: &[],
+                },
+                &|_, _| Ok(()),
+            )
+            .unwrap()
+        };
+        let result = check(&[candidate("Alice", &known_pq_der)]);
+        assert!(!result.is_valid);
+        assert!(result
+            .error_message
+            .unwrap()
+            .contains("neither one of your keys nor a contact"));
+        assert!(check(&[candidate("Mallory", &foreign_public_der)]).is_valid);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn distrusted_contacts_do_not_validate_signatures() {
+        let dir = std::env::temp_dir().join(format!("sigust-distrusted-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let document = dir.join("invoice.txt");
+        fs::write(&document, b"invoice").unwrap();
+        let signature_path = dir.join("invoice.txt.sig");
+
+        let algorithm = SignatureAlgorithm::Ed25519;
+        let (private_der, public_der) = known_answer_key_pair(&algorithm).unwrap();
+        let signature =
+            sign_bytes(&algorithm, &private_der, b"invoice", DigestScheme::Standard).unwrap();
+        fs::write(&signature_path, signature).unwrap();
+
+        let verify = |distrusted: bool| {
+            let candidates = [SignerCandidate {
+                name: "Mallory".to_string(),
+                fingerprint: compute_fingerprint(&public_der),
+                algorithm: algorithm.clone(),
+                public_key_der: public_der.clone(),
+                distrusted,
+            }];
+            verify_against_candidates(&document, &signature_path, &candidates, &[], None, false)
+                .unwrap()
+        };
+        assert!(verify(false).result.is_valid);
+        let report = verify(true);
+        assert!(!report.result.is_valid);
+        assert_eq!(report.signer_name.as_deref(), Some("Mallory"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn wildcard_matches_signature_names() {
+        assert!(wildcard_match("contract*.sig", "contract.pdf.alice.sig"));
+        assert!(wildcard_match("?.sig", "a.sig"));
+        assert!(!wildcard_match("*.sig", "contract.pdf"));
+        assert!(!wildcard_match("?.sig", "ab.sig"));
+    }
+
+    #[test]
+    fn legacy_and_standard_schemes_differ() {
+        use pkcs8::EncodePublicKey;
+
+        let data = b"Hello, World!";
+        let key = EcdsaSigningKey::random(&mut OsRng);
+        let private_der = key.to_pkcs8_der().unwrap().to_bytes().to_vec();
+        let public_der = key.verifying_key().to_public_key_der().unwrap().into_vec();
+        let algorithm = SignatureAlgorithm::EcdsaP256Sha256;
+
+        let legacy = sign_bytes(&algorithm, &private_der, data, DigestScheme::Legacy).unwrap();
+        let verify = |scheme| verify_bytes(&algorithm, &public_der, data, &legacy, scheme);
+        assert!(verify(DigestScheme::Legacy).unwrap().is_ok());
+        assert!(verify(DigestScheme::Standard).unwrap().is_err());
+    }
+
+    #[test]
+    fn ecdsa_verification_accepts_der_and_raw() {
+        use pkcs8::EncodePublicKey;
+
+        let data = b"Hello, World!";
+        let key = EcdsaSigningKey::random(&mut OsRng);
+        let private_der = key.to_pkcs8_der().unwrap().to_bytes().to_vec();
+        let public_der = key.verifying_key().to_public_key_der().unwrap().into_vec();
+        let algorithm = SignatureAlgorithm::EcdsaP256Sha256;
+
+        let raw = sign_bytes(&algorithm, &private_der, data, DigestScheme::Standard).unwrap();
+        let der = encode_ecdsa_signature(&raw, EcdsaEncoding::Der).unwrap();
+        assert_ne!(raw, der);
+        for signature in [raw, der] {
+            assert!(verify_bytes(
+                &algorithm,
+                &public_der,
+                data,
+                &signature,
+                DigestScheme::Standard
+            )
+            .unwrap()
+            .is_ok());
+        }
+    }
+
+    #[test]
+    fn signature_encodings_are_detected_when_reading() {
+        let algorithm = SignatureAlgorithm::Ed25519;
+        let (private_der, _) = known_answer_key_pair(&algorithm).unwrap();
+        let signature =
+            sign_bytes(&algorithm, &private_der, b"ticket", DigestScheme::Standard).unwrap();
+        for encoding in [
+            SignatureEncoding::Binary,
+            SignatureEncoding::Base64,
+            SignatureEncoding::Pem,
+        ] {
+            let encoded = encode_signature(&signature, encoding).unwrap();
+            assert_eq!(decode_signature(&encoded).unwrap(), signature);
+        }
+        let pem = encode_signature(&signature, SignatureEncoding::Pem).unwrap();
+        assert!(pem.starts_with(b"-----BEGIN SIGNATURE-----\n"));
+        // 信封和其他文本原样返回
+        let envelope = br#"{"signed":{},"signature":"AA=="}"#;
+        assert_eq!(decode_signature(envelope).unwrap(), envelope);
+    }
+
+    #[test]
+    fn ed25519_stream_check_accepts_pure_and_prehashed() {
+        use pkcs8::EncodePublicKey;
+
+        let data = b"Hello, World!";
+        let key = EdSigningKey::generate(&mut OsRng);
+        let public_der = key.verifying_key().to_public_key_der().unwrap().into_vec();
+        let pure = key.sign(data).to_bytes();
+        let prehashed = key
+            .sign_prehashed(Sha512::new_with_prefix(data), None)
+            .unwrap()
+            .to_bytes();
+
+        let check = |signature: &[u8], chunks: &[&[u8]]| {
+            let mut check = Ed25519Check::new(&public_der, signature).unwrap();
+            chunks.iter().for_each(|chunk| check.update(chunk));
+            let (pure, prehashed) = check.finish();
+            (pure.is_ok(), prehashed.is_ok())
+        };
+        assert_eq!(check(&pure, &[&data[..5], &data[5..]]), (true, false));
+        assert_eq!(check(&prehashed, &[data]), (false, true));
+        assert_eq!(check(&prehashed, &[b"tampered"]), (false, false));
+    }
+}
@@ -0,0 +1,222 @@
+// src-tauri/src/signing_profiles.rs
+// Named signing presets: a key, signing options (format, digest scheme, level and
+// time-stamping) and where the signature goes. Profiles are stored in
+// `signing_profiles.json`; `sign_with_profile` turns routine signing into one action and
+// goes through `sign_document`, so all its checks and auditing apply.
+use crate::crypto_types::{SigningProfile, SigningProfileInput, SigningResult};
+use crate::key_management::{find_key_metadata, get_app_data_file};
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::{templated_output_path, validate_template};
+use crate::signing::sign_document;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const SIGNING_PROFILES_FILENAME: &str = "signing_profiles.json";
+const MAX_PROFILE_NAME_LEN: usize = 128;
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_signing_profiles(app_handle: tauri::AppHandle) -> Result<Vec<SigningProfile>, String> {
+    log::info!("Listing signing profiles");
+    get_signing_profiles_path(&app_handle)
+        .and_then(|path| read_signing_profiles(&path))
+        .map_err(|e| {
+            log::error!("Failed to list signing profiles: {:?}", e);
+            e.to_string()
+        })
+}
+
+/// Creates a profile, or replaces the one with `profile.profile_id`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn save_signing_profile(
+    app_handle: tauri::AppHandle,
+    profile: SigningProfileInput,
+) -> Result<SigningProfile, String> {
+    log::info!("Saving signing profile '{}'", profile.name);
+    let name = profile.name.trim();
+    if name.is_empty() || name.len() > MAX_PROFILE_NAME_LEN {
+        return Err(format!(
+            "Profile name must be non-blank and at most {} characters.",
+            MAX_PROFILE_NAME_LEN
+        ));
+    }
+    if let Some(template) = &profile.output_template {
+        validate_template(template).map_err(|e| e.to_string())?;
+    }
+    _save_signing_profile(&app_handle, profile).map_err(|e| {
+        log::error!("Failed to save signing profile: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _save_signing_profile(
+    app_handle: &tauri::AppHandle,
+    input: SigningProfileInput,
+) -> Result<SigningProfile> {
+    find_key_metadata(app_handle, input.key_id)?;
+    let path = get_signing_profiles_path(app_handle)?;
+    let mut profiles = read_signing_profiles(&path)?;
+    let name = input.name.trim().to_string();
+    if profiles
+        .iter()
+        .any(|p| Some(p.profile_id) != input.profile_id && p.name.eq_ignore_ascii_case(&name))
+    {
+        bail!("A signing profile named '{}' already exists", name);
+    }
+
+    let now = Utc::now();
+    let profile = match input.profile_id {
+        Some(profile_id) => {
+            let existing = profiles
+                .iter_mut()
+                .find(|p| p.profile_id == profile_id)
+                .ok_or_else(|| anyhow!("Signing profile with ID {} not found", profile_id))?;
+            existing.name = name;
+            existing.key_id = input.key_id;
+            existing.options = input.options;
+            existing.output_template = input.output_template;
+            existing.output_dir = input.output_dir;
+            existing.updated_at = now;
+            existing.clone()
+        }
+        None => {
+            let profile = SigningProfile {
+                profile_id: Uuid::new_v4(),
+                name,
+                key_id: input.key_id,
+                options: input.options,
+                output_template: input.output_template,
+                output_dir: input.output_dir,
+                created_at: now,
+                updated_at: now,
+            };
+            profiles.push(profile.clone());
+            profile
+        }
+    };
+    write_signing_profiles(&path, &profiles)?;
+    Ok(profile)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_signing_profile(
+    app_handle: tauri::AppHandle,
+    profile_id: Uuid,
+) -> Result<(), String> {
+    log::info!("Deleting signing profile {}", profile_id);
+    _delete_signing_profile(&app_handle, profile_id).map_err(|e| {
+        log::error!("Failed to delete signing profile {}: {:?}", profile_id, e);
+        e.to_string()
+    })
+}
+
+fn _delete_signing_profile(app_handle: &tauri::AppHandle, profile_id: Uuid) -> Result<()> {
+    let path = get_signing_profiles_path(app_handle)?;
+    let mut profiles = read_signing_profiles(&path)?;
+    find_profile(&profiles, profile_id)?;
+    profiles.retain(|p| p.profile_id != profile_id);
+    write_signing_profiles(&path, &profiles)
+}
+
+/// Signs `document_path` as the profile describes. The signature is written to the
+/// profile's output folder (or next to the document), named by the profile's template (or
+/// the configured one). Returns the path of the signature and timings. `approver_password` is needed
+/// for keys under two-person control.
+#[tauri::command(rename_all = "camelCase")]
+pub fn sign_with_profile(
+    app_handle: tauri::AppHandle,
+    profile_id: Uuid,
+    document_path: String,
+    password: String,
+    approver_password: Option<String>,
+) -> Result<SigningResult, String> {
+    log::info!(
+        "Signing document '{}' with profile {}",
+        document_path,
+        profile_id
+    );
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    let (profile, output_path) = profile_output_path(&app_handle, profile_id, &document_path)
+        .map_err(|e| {
+            log::error!(
+                "Failed to prepare signing with profile {}: {:?}",
+                profile_id,
+                e
+            );
+            e.to_string()
+        })?;
+    sign_document(
+        app_handle,
+        document_path,
+        profile.key_id,
+        password,
+        path_string(&output_path),
+        profile.options,
+        approver_password,
+    )
+}
+
+// --- Helper Functions ---
+
+// 按配置确定签名输出路径（默认写在文档旁边）
+fn profile_output_path(
+    app_handle: &tauri::AppHandle,
+    profile_id: Uuid,
+    document_path: &str,
+) -> Result<(SigningProfile, PathBuf)> {
+    let profiles = read_signing_profiles(&get_signing_profiles_path(app_handle)?)?;
+    let profile = find_profile(&profiles, profile_id)?.clone();
+    let metadata = find_key_metadata(app_handle, profile.key_id)?;
+    let algorithm = metadata.algorithm.clone();
+
+    let document_path = &native_path(document_path)?;
+    let output_dir = match &profile.output_dir {
+        Some(dir) => native_path(dir)?,
+        None => document_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .to_path_buf(),
+    };
+    let output_path = templated_output_path(
+        app_handle,
+        &output_dir,
+        document_path,
+        &algorithm,
+        profile.output_template.as_deref(),
+    )?;
+    Ok((profile, output_path))
+}
+
+// 获取签名配置文件路径
+pub(crate) fn get_signing_profiles_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, SIGNING_PROFILES_FILENAME)
+}
+
+// 读取签名配置列表
+pub(crate) fn read_signing_profiles(path: &Path) -> Result<Vec<SigningProfile>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).context("Failed to read signing profiles file")?;
+    serde_json::from_str(&content).context("Failed to parse signing profiles JSON")
+}
+
+// 写入签名配置列表
+pub(crate) fn write_signing_profiles(path: &Path, profiles: &[SigningProfile]) -> Result<()> {
+    let content = serde_json::to_string_pretty(profiles)
+        .context("Failed to serialize signing profiles to JSON")?;
+    fs::write(path, content).context("Failed to write signing profiles file")
+}
+
+fn find_profile(profiles: &[SigningProfile], profile_id: Uuid) -> Result<&SigningProfile> {
+    profiles
+        .iter()
+        .find(|p| p.profile_id == profile_id)
+        .ok_or_else(|| anyhow!("Signing profile with ID {} not found", profile_id))
+}
@@ -0,0 +1,807 @@
+// src-tauri/src/signing_requests.rs
+// Hash now, sign later: `create_signing_request` digests documents on one machine and
+// writes a portable request file (names, sizes, SHA-256 digests and context);
+// `sign_signing_request` turns the request into signature envelopes on another machine
+// without access to the documents. Only envelopes can be made this way, since they sign
+// the document digest rather than the document bytes.
+//
+// Air-gapped round trip: the request file is written to removable media (and read back to
+// catch a faulty medium); the offline machine checks its checksum, signs it with
+// `sign_signing_request_bundle` and writes one signature bundle back to the medium; the
+// online machine checks the bundle with `attach_signature_bundle` against the request it
+// recorded, re-hashes the original documents and writes each signature next to its
+// document. Checksums catch damaged transfers; the envelope signatures themselves are
+// what protects against tampering.
+use crate::audit;
+use crate::crypto_types::{
+    AttachedSignature, RequestedDocument, SignatureAlgorithm, SignedRequestDocument,
+    SigningRequest, TimeSource,
+};
+use crate::envelope::{
+    canonical_json, SignatureEnvelope, SignedAttributes, DIGEST_SHA256, ENVELOPE_FORMAT,
+    ENVELOPE_VERSION,
+};
+use crate::hashing::hash_files;
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, find_key_metadata, get_app_data_file,
+    read_private_key_der, read_public_key_pem,
+};
+use crate::metadata_schema::validate_metadata;
+use crate::native_paths::{native_path, path_string};
+use crate::output_paths::{resolve_output_path, signature_output_path};
+use crate::policy::check_timestamping;
+use crate::signature_sequence::next_signature_sequence;
+use crate::signing::{check_envelope_attributes, signer_candidates, SignerCandidate};
+use crate::timestamp::trusted_tsa_certificates;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use uuid::Uuid;
+
+const SIGNING_REQUEST_FORMAT: &str = "sigust-signing-request";
+const SIGNING_REQUEST_VERSION: u32 = 1;
+const SIGNATURE_BUNDLE_FORMAT: &str = "sigust-signature-bundle";
+const SIGNATURE_BUNDLE_VERSION: u32 = 1;
+const PENDING_REQUESTS_FILENAME: &str = "pending_signing_requests.json";
+/// Request files are small JSON documents; anything larger is not one.
+const MAX_REQUEST_FILE_SIZE: u64 = 4 * 1024 * 1024;
+/// Bundles carry an envelope per document (with time-stamp tokens).
+const MAX_BUNDLE_FILE_SIZE: u64 = 64 * 1024 * 1024;
+const MAX_REQUEST_DOCUMENTS: usize = 1000;
+
+/// On-disk form of a signing request.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SigningRequestFile {
+    format: String,
+    version: u32,
+    #[serde(flatten)]
+    request: SigningRequest,
+    /// SHA-256 over the canonical JSON of the request.
+    checksum: String,
+}
+
+/// Signatures made offline for a signing request, carried back to the online machine.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignatureBundle {
+    request_id: Uuid,
+    /// Checksum of the request that was signed.
+    request_checksum: String,
+    algorithm: String,
+    /// The signer's public key; the online machine only accepts it if it is one of its own
+    /// keys or a contact.
+    public_key_pem: String,
+    signatures: Vec<BundledSignature>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundledSignature {
+    name: String,
+    digest: String,
+    envelope: SignatureEnvelope,
+}
+
+/// On-disk form of a signature bundle.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignatureBundleFile {
+    format: String,
+    version: u32,
+    #[serde(flatten)]
+    bundle: SignatureBundle,
+    /// SHA-256 over the canonical JSON of the bundle.
+    checksum: String,
+}
+
+/// A request created on this machine and not yet attached; remembers where the documents are.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingSigningRequest {
+    request_id: Uuid,
+    checksum: String,
+    document_paths: Vec<String>,
+    created_at: DateTime<Utc>,
+}
+
+// --- Tauri Commands ---
+
+/// Digests `document_paths` and writes a signing request to `output_path`. `metadata` is
+/// signed into every envelope made from the request.
+#[tauri::command(rename_all = "camelCase")]
+pub fn create_signing_request(
+    app_handle: tauri::AppHandle,
+    document_paths: Vec<String>,
+    output_path: String,
+    note: Option<String>,
+    bind_file_name: bool,
+    metadata: Option<BTreeMap<String, String>>,
+) -> Result<SigningRequest, String> {
+    log::info!(
+        "Creating signing request for {} documents at '{}'",
+        document_paths.len(),
+        output_path
+    );
+    if document_paths.is_empty() || document_paths.len() > MAX_REQUEST_DOCUMENTS {
+        return Err(format!(
+            "A signing request must contain between 1 and {} documents.",
+            MAX_REQUEST_DOCUMENTS
+        ));
+    }
+    _create_signing_request(
+        &app_handle,
+        &document_paths,
+        &output_path,
+        note.filter(|n| !n.trim().is_empty()),
+        bind_file_name,
+        metadata.unwrap_or_default(),
+    )
+    .map_err(|e| {
+        log::error!("Failed to create signing request: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|request| {
+        audit::record(
+            &app_handle,
+            "createSigningRequest",
+            json!({
+                "requestId": request.request_id,
+                "documentPaths": document_paths,
+                "outputPath": output_path,
+            }),
+        )
+    })
+}
+
+fn _create_signing_request(
+    app_handle: &tauri::AppHandle,
+    document_paths: &[String],
+    output_path: &str,
+    note: Option<String>,
+    bind_file_name: bool,
+    metadata: BTreeMap<String, String>,
+) -> Result<SigningRequest> {
+    validate_metadata(app_handle, &metadata)?;
+    let paths = native_paths(document_paths)?;
+    let paths: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+    let documents = hash_files(app_handle, &paths)?
+        .into_iter()
+        .zip(&paths)
+        .map(|((size, digest), path)| RequestedDocument {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "document".to_string()),
+            size,
+            digest,
+        })
+        .collect::<Vec<_>>();
+    let request = SigningRequest {
+        request_id: Uuid::new_v4(),
+        created_at: Utc::now(),
+        note,
+        bind_file_name,
+        metadata,
+        documents,
+    };
+    let file = SigningRequestFile {
+        format: SIGNING_REQUEST_FORMAT.to_string(),
+        version: SIGNING_REQUEST_VERSION,
+        checksum: checksum(&request)?,
+        request,
+    };
+    let content =
+        serde_json::to_string_pretty(&file).context("Failed to serialize signing request")?;
+    let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+    write_verified(&output_path, content.as_bytes())?;
+
+    let pending_path = get_pending_requests_path(app_handle)?;
+    let mut pending = read_pending_requests(&pending_path)?;
+    pending.push(PendingSigningRequest {
+        request_id: file.request.request_id,
+        checksum: file.checksum.clone(),
+        document_paths: document_paths.to_vec(),
+        created_at: file.request.created_at,
+    });
+    write_pending_requests(&pending_path, &pending)?;
+    log::info!(
+        "Signing request {} with {} documents saved to {:?}",
+        file.request.request_id,
+        file.request.documents.len(),
+        output_path
+    );
+    Ok(file.request)
+}
+
+/// Reads a signing request so the signer can review it before signing.
+#[tauri::command(rename_all = "camelCase")]
+pub fn read_signing_request(request_path: String) -> Result<SigningRequest, String> {
+    log::info!("Reading signing request '{}'", request_path);
+    native_path(&request_path)
+        .and_then(|path| load_signing_request(&path))
+        .map(|file| file.request)
+        .map_err(|e| {
+            log::error!("Failed to read signing request: {:?}", e);
+            e.to_string()
+        })
+}
+
+/// Signs every document of a request, writing one envelope per document into `output_dir`
+/// (named by the configured template). With `tsa_url` the envelopes are time-stamped.
+#[tauri::command(rename_all = "camelCase")]
+pub fn sign_signing_request(
+    app_handle: tauri::AppHandle,
+    request_path: String,
+    key_id: Uuid,
+    password: String,
+    output_dir: String,
+    tsa_url: Option<String>,
+) -> Result<Vec<SignedRequestDocument>, String> {
+    log::info!("Signing request '{}' with key ID {}", request_path, key_id);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _sign_signing_request(
+        &app_handle,
+        &request_path,
+        key_id,
+        &password,
+        &output_dir,
+        tsa_url.as_deref(),
+    )
+    .map_err(|e| {
+        log::error!("Failed to sign signing request: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|signed| {
+        audit::record(
+            &app_handle,
+            "signSigningRequest",
+            json!({
+                "requestPath": request_path,
+                "keyId": key_id,
+                "documents": signed.iter().map(|d| &d.digest).collect::<Vec<_>>(),
+                "outputDir": output_dir,
+            }),
+        )
+    })
+}
+
+fn _sign_signing_request(
+    app_handle: &tauri::AppHandle,
+    request_path: &str,
+    key_id: Uuid,
+    password: &str,
+    output_dir: &str,
+    tsa_url: Option<&str>,
+) -> Result<Vec<SignedRequestDocument>> {
+    let file = load_signing_request(&native_path(request_path)?)?;
+    let (algorithm, _, envelopes) =
+        seal_request(app_handle, &file.request, key_id, password, tsa_url)?;
+    let output_dir = &native_path(output_dir)?;
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create output directory: {:?}", output_dir))?;
+
+    let mut signed = Vec::with_capacity(envelopes.len());
+    for (document, envelope) in file.request.documents.iter().zip(envelopes) {
+        let content =
+            serde_json::to_string_pretty(&envelope).context("Failed to serialize envelope")?;
+        let signature_path = signature_output_path(
+            app_handle,
+            output_dir,
+            Path::new(&document.name),
+            &algorithm,
+        )?;
+        fs::write(&signature_path, content)
+            .with_context(|| format!("Failed to write signature file: {:?}", signature_path))?;
+        signed.push(SignedRequestDocument {
+            name: document.name.clone(),
+            digest: document.digest.clone(),
+            signature_path: path_string(&signature_path),
+        });
+    }
+    log::info!(
+        "Signed {} documents of request {} with key {}",
+        signed.len(),
+        file.request.request_id,
+        key_id
+    );
+    Ok(signed)
+}
+
+/// Offline side of the air-gapped round trip: signs every document of a request and writes
+/// all envelopes into one signature bundle at `bundle_path` (e.g. on the removable medium
+/// the request came on). Returns the documents that were signed.
+#[tauri::command(rename_all = "camelCase")]
+pub fn sign_signing_request_bundle(
+    app_handle: tauri::AppHandle,
+    request_path: String,
+    key_id: Uuid,
+    password: String,
+    bundle_path: String,
+    tsa_url: Option<String>,
+) -> Result<Vec<SignedRequestDocument>, String> {
+    log::info!(
+        "Signing request '{}' into bundle '{}' with key ID {}",
+        request_path,
+        bundle_path,
+        key_id
+    );
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _sign_signing_request_bundle(
+        &app_handle,
+        &request_path,
+        key_id,
+        &password,
+        &bundle_path,
+        tsa_url.as_deref(),
+    )
+    .map_err(|e| {
+        log::error!("Failed to sign signing request into a bundle: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|signed| {
+        audit::record(
+            &app_handle,
+            "signSigningRequestBundle",
+            json!({
+                "requestPath": request_path,
+                "keyId": key_id,
+                "documents": signed.iter().map(|d| &d.digest).collect::<Vec<_>>(),
+                "bundlePath": bundle_path,
+            }),
+        )
+    })
+}
+
+fn _sign_signing_request_bundle(
+    app_handle: &tauri::AppHandle,
+    request_path: &str,
+    key_id: Uuid,
+    password: &str,
+    bundle_path: &str,
+    tsa_url: Option<&str>,
+) -> Result<Vec<SignedRequestDocument>> {
+    let file = load_signing_request(&native_path(request_path)?)?;
+    let (algorithm, public_key_pem, envelopes) =
+        seal_request(app_handle, &file.request, key_id, password, tsa_url)?;
+    let bundle_path = resolve_output_path(app_handle, &native_path(bundle_path)?)?;
+
+    let signed = file
+        .request
+        .documents
+        .iter()
+        .map(|document| SignedRequestDocument {
+            name: document.name.clone(),
+            digest: document.digest.clone(),
+            signature_path: path_string(&bundle_path),
+        })
+        .collect();
+    let bundle = SignatureBundle {
+        request_id: file.request.request_id,
+        request_checksum: file.checksum,
+        algorithm: algorithm.to_string(),
+        public_key_pem,
+        signatures: file
+            .request
+            .documents
+            .into_iter()
+            .zip(envelopes)
+            .map(|(document, envelope)| BundledSignature {
+                name: document.name,
+                digest: document.digest,
+                envelope,
+            })
+            .collect(),
+    };
+    let bundle_file = SignatureBundleFile {
+        format: SIGNATURE_BUNDLE_FORMAT.to_string(),
+        version: SIGNATURE_BUNDLE_VERSION,
+        checksum: checksum(&bundle)?,
+        bundle,
+    };
+    let content = serde_json::to_string_pretty(&bundle_file)
+        .context("Failed to serialize signature bundle")?;
+    write_verified(&bundle_path, content.as_bytes())?;
+    log::info!(
+        "Signature bundle for request {} saved to {:?}",
+        bundle_file.bundle.request_id,
+        bundle_path
+    );
+    Ok(signed)
+}
+
+/// Online side of the air-gapped round trip: checks a signature bundle and writes each
+/// signature next to its original document. The documents are the ones recorded when the
+/// request was created here, unless `document_paths` names them (matched by digest).
+#[tauri::command(rename_all = "camelCase")]
+pub fn attach_signature_bundle(
+    app_handle: tauri::AppHandle,
+    bundle_path: String,
+    document_paths: Option<Vec<String>>,
+) -> Result<Vec<AttachedSignature>, String> {
+    log::info!("Attaching signature bundle '{}'", bundle_path);
+    _attach_signature_bundle(&app_handle, &bundle_path, document_paths)
+        .map_err(|e| {
+            log::error!("Failed to attach signature bundle: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|attached| {
+            audit::record(
+                &app_handle,
+                "attachSignatureBundle",
+                json!({
+                    "bundlePath": bundle_path,
+                    "signaturePaths": attached.iter().map(|a| &a.signature_path).collect::<Vec<_>>(),
+                }),
+            )
+        })
+}
+
+fn _attach_signature_bundle(
+    app_handle: &tauri::AppHandle,
+    bundle_path: &str,
+    document_paths: Option<Vec<String>>,
+) -> Result<Vec<AttachedSignature>> {
+    let bundle = load_signature_bundle(&native_path(bundle_path)?)?;
+    let pending_path = get_pending_requests_path(app_handle)?;
+    let mut pending = read_pending_requests(&pending_path)?;
+    let record = pending.iter().find(|p| p.request_id == bundle.request_id);
+    if let Some(record) = record {
+        if record.checksum != bundle.request_checksum {
+            bail!(
+                "The bundle was made for a different version of request {}",
+                bundle.request_id
+            );
+        }
+    }
+    let document_paths = match (document_paths, record) {
+        (Some(paths), _) => paths,
+        (None, Some(record)) => record.document_paths.clone(),
+        (None, None) => bail!(
+            "Signing request {} was not created on this machine; select the original documents",
+            bundle.request_id
+        ),
+    };
+
+    // 1. Check every envelope before writing anything
+    let candidates = signer_candidates(app_handle)?;
+    let signer = check_bundle_signatures(&bundle, &candidates)?;
+    let algorithm = signer.algorithm.clone();
+    let document_paths = native_paths(&document_paths)?;
+    let paths: Vec<&Path> = document_paths.iter().map(PathBuf::as_path).collect();
+    let digests: Vec<String> = hash_files(app_handle, &paths)?
+        .into_iter()
+        .map(|(_, digest)| digest)
+        .collect();
+    let mut matched = Vec::with_capacity(bundle.signatures.len());
+    for signature in &bundle.signatures {
+        let index = digests
+            .iter()
+            .position(|digest| *digest == signature.digest)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No original document matches '{}'; it was changed or not selected",
+                    signature.name
+                )
+            })?;
+        matched.push((signature, &document_paths[index]));
+    }
+
+    // 2. Write each signature next to its document
+    let mut attached = Vec::with_capacity(matched.len());
+    for (signature, document_path) in matched {
+        let output_dir = document_path.parent().unwrap_or(Path::new("."));
+        let signature_path =
+            signature_output_path(app_handle, output_dir, document_path, &algorithm)?;
+        let content = serde_json::to_string_pretty(&signature.envelope)
+            .context("Failed to serialize envelope")?;
+        fs::write(&signature_path, content)
+            .with_context(|| format!("Failed to write signature file: {:?}", signature_path))?;
+        attached.push(AttachedSignature {
+            name: signature.name.clone(),
+            document_path: path_string(document_path),
+            signature_path: path_string(&signature_path),
+            signer_name: signer.name.clone(),
+        });
+    }
+    pending.retain(|p| p.request_id != bundle.request_id);
+    write_pending_requests(&pending_path, &pending)?;
+    log::info!(
+        "Attached {} signatures of request {}",
+        attached.len(),
+        bundle.request_id
+    );
+    Ok(attached)
+}
+
+// --- Helper Functions ---
+
+// 按请求中的摘要逐个生成签名信封，返回算法、签名者公钥和信封（与文档顺序一致）
+fn seal_request(
+    app_handle: &tauri::AppHandle,
+    request: &SigningRequest,
+    key_id: Uuid,
+    password: &str,
+    tsa_url: Option<&str>,
+) -> Result<(SignatureAlgorithm, String, Vec<SignatureEnvelope>)> {
+    check_timestamping(tsa_url.is_some())?;
+    validate_metadata(app_handle, &request.metadata)?;
+
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let algorithm = metadata.algorithm.clone();
+    let public_key_pem = read_public_key_pem(app_handle, &metadata)?;
+    let public_key_der = decode_public_key_pem(&public_key_pem)?;
+    let private_key_der = read_private_key_der(app_handle, &metadata, password)?;
+
+    let mut envelopes = Vec::with_capacity(request.documents.len());
+    for document in &request.documents {
+        let attributes = SignedAttributes {
+            format: ENVELOPE_FORMAT.to_string(),
+            version: ENVELOPE_VERSION,
+            algorithm: algorithm.to_string(),
+            key_id,
+            key_fingerprint: compute_fingerprint(&public_key_der),
+            digest_algorithm: DIGEST_SHA256.to_string(),
+            document_digest: document.digest.clone(),
+            signed_at: Utc::now(),
+            time_source: TimeSource::LocalClock,
+            time_token: None,
+            file_name: request.bind_file_name.then(|| document.name.clone()),
+            file_size: request.bind_file_name.then_some(document.size),
+            file_modified_at: None,
+            previous_envelope: None,
+            metadata: request.metadata.clone(),
+            approval: None,
+            sequence: Some(next_signature_sequence(app_handle, key_id)?),
+            oidc_identity: None,
+            pq_cosigner: None,
+        };
+        let mut envelope = SignatureEnvelope::seal(&attributes, &private_key_der)?;
+        if let Some(tsa_url) = tsa_url {
+            envelope.add_timestamp(tsa_url, &trusted_tsa_certificates(app_handle)?)?;
+        }
+        envelopes.push(envelope);
+    }
+    Ok((algorithm, public_key_pem, envelopes))
+}
+
+// 签名者按指纹在本地密钥与联系人中查找，不信任包里附带的公钥；再逐个校验信封
+fn check_bundle_signatures<'a>(
+    bundle: &SignatureBundle,
+    candidates: &'a [SignerCandidate],
+) -> Result<&'a SignerCandidate> {
+    let fingerprint = compute_fingerprint(&decode_public_key_pem(&bundle.public_key_pem)?);
+    let matches: Vec<&SignerCandidate> = candidates
+        .iter()
+        .filter(|candidate| candidate.fingerprint == fingerprint)
+        .collect();
+    if let Some(distrusted) = matches.iter().find(|candidate| candidate.distrusted) {
+        bail!(
+            "The bundle was signed with the key of '{}', a contact you marked as distrusted",
+            distrusted.name
+        );
+    }
+    let signer = matches.first().ok_or_else(|| {
+        anyhow!(
+            "Signer key {} is neither one of your keys nor a contact",
+            fingerprint
+        )
+    })?;
+    if SignatureAlgorithm::from_str(&bundle.algorithm)? != signer.algorithm {
+        bail!(
+            "Bundle algorithm {} does not match the signer's key algorithm {}",
+            bundle.algorithm,
+            signer.algorithm
+        );
+    }
+    for signature in &bundle.signatures {
+        let attributes = signature.envelope.attributes()?;
+        if let Some(mismatch) = check_envelope_attributes(
+            &attributes,
+            &signer.algorithm,
+            &signer.public_key_der,
+            &signature.digest,
+        )? {
+            bail!("Signature for '{}': {}", signature.name, mismatch);
+        }
+        signature
+            .envelope
+            .verify_signature(&signer.algorithm, &signer.public_key_der)?
+            .map_err(|_| anyhow!("Signature for '{}' is not valid", signature.name))?;
+    }
+    Ok(signer)
+}
+
+// 把来自 webview 的路径字符串转换为本机路径
+fn native_paths(paths: &[String]) -> Result<Vec<PathBuf>> {
+    paths.iter().map(|path| native_path(path)).collect()
+}
+
+// 规范化 JSON 的 SHA-256，用于检测传输中损坏的文件
+fn checksum<T: Serialize>(value: &T) -> Result<String> {
+    let value = serde_json::to_value(value).context("Failed to serialize for checksum")?;
+    Ok(hex::encode(Sha256::digest(canonical_json(&value)?)))
+}
+
+// 写入后回读比较，发现有问题的移动介质
+fn write_verified(path: &Path, content: &[u8]) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))?;
+    let written = fs::read(path).with_context(|| format!("Failed to read back {:?}", path))?;
+    if written != content {
+        bail!(
+            "{:?} reads back differently than it was written; the medium may be faulty",
+            path
+        );
+    }
+    Ok(())
+}
+
+// 读取有大小上限的文件
+fn read_limited(path: &Path, max_size: u64, what: &str) -> Result<Vec<u8>> {
+    let size = fs::metadata(path)
+        .with_context(|| format!("Failed to read {}: {:?}", what, path))?
+        .len();
+    if size > max_size {
+        bail!("Not a {}: {:?}", what, path);
+    }
+    fs::read(path).with_context(|| format!("Failed to read {}: {:?}", what, path))
+}
+
+// 读取并校验请求文件：格式、摘要和文件名都来自另一台机器，不可信
+fn load_signing_request(path: &Path) -> Result<SigningRequestFile> {
+    let content = read_limited(path, MAX_REQUEST_FILE_SIZE, "signing request")?;
+    let file: SigningRequestFile =
+        serde_json::from_slice(&content).context("Not a sigust signing request")?;
+    if file.format != SIGNING_REQUEST_FORMAT || file.version != SIGNING_REQUEST_VERSION {
+        bail!(
+            "Unsupported signing request: {} v{}",
+            file.format,
+            file.version
+        );
+    }
+    if checksum(&file.request)? != file.checksum {
+        bail!("The signing request is damaged (checksum mismatch)");
+    }
+    let request = &file.request;
+    if request.documents.is_empty() || request.documents.len() > MAX_REQUEST_DOCUMENTS {
+        bail!("Signing request has an invalid number of documents");
+    }
+    for document in &request.documents {
+        let valid_digest = document.digest.len() == 64
+            && document
+                .digest
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+        if !valid_digest {
+            bail!("Invalid digest for '{}' in signing request", document.name);
+        }
+        // 文件名用于生成输出路径，拒绝路径分隔符
+        if document.name.is_empty()
+            || document.name.contains(['/', '\\'])
+            || document.name.trim_matches('.').is_empty()
+        {
+            bail!(
+                "Invalid document name in signing request: {}",
+                document.name
+            );
+        }
+    }
+    Ok(file)
+}
+
+fn load_signature_bundle(path: &Path) -> Result<SignatureBundle> {
+    let content = read_limited(path, MAX_BUNDLE_FILE_SIZE, "signature bundle")?;
+    let file: SignatureBundleFile =
+        serde_json::from_slice(&content).context("Not a sigust signature bundle")?;
+    if file.format != SIGNATURE_BUNDLE_FORMAT || file.version != SIGNATURE_BUNDLE_VERSION {
+        bail!(
+            "Unsupported signature bundle: {} v{}",
+            file.format,
+            file.version
+        );
+    }
+    if checksum(&file.bundle)? != file.checksum {
+        bail!("The signature bundle is damaged (checksum mismatch)");
+    }
+    Ok(file.bundle)
+}
+
+// 获取待回贴签名请求记录文件路径
+fn get_pending_requests_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, PENDING_REQUESTS_FILENAME)
+}
+
+// 读取待回贴签名请求记录
+fn read_pending_requests(path: &Path) -> Result<Vec<PendingSigningRequest>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        fs::read_to_string(path).context("Failed to read pending signing requests file")?;
+    serde_json::from_str(&content).context("Failed to parse pending signing requests JSON")
+}
+
+// 写入待回贴签名请求记录
+fn write_pending_requests(path: &Path, pending: &[PendingSigningRequest]) -> Result<()> {
+    let content = serde_json::to_string_pretty(pending)
+        .context("Failed to serialize pending signing requests to JSON")?;
+    fs::write(path, content).context("Failed to write pending signing requests file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_exchange::encode_public_key_pem;
+    use crate::self_tests::known_answer_key_pair;
+
+    #[test]
+    fn bundles_from_unknown_or_distrusted_signers_are_rejected() {
+        let algorithm = SignatureAlgorithm::Ed25519;
+        let (private_der, public_der) = known_answer_key_pair(&algorithm).unwrap();
+        let digest = hex::encode(Sha256::digest(b"invoice"));
+        let attributes = SignedAttributes {
+            format: ENVELOPE_FORMAT.to_string(),
+            version: ENVELOPE_VERSION,
+            algorithm: algorithm.to_string(),
+            key_id: Uuid::nil(),
+            key_fingerprint: compute_fingerprint(&public_der),
+            digest_algorithm: DIGEST_SHA256.to_string(),
+            document_digest: digest.clone(),
+            signed_at: Utc::now(),
+            time_source: TimeSource::LocalClock,
+            time_token: None,
+            file_name: None,
+            file_size: None,
+            file_modified_at: None,
+            previous_envelope: None,
+            metadata: BTreeMap::new(),
+            approval: None,
+            sequence: None,
+            oidc_identity: None,
+            pq_cosigner: None,
+        };
+        let bundle = SignatureBundle {
+            request_id: Uuid::new_v4(),
+            request_checksum: String::new(),
+            algorithm: algorithm.to_string(),
+            public_key_pem: encode_public_key_pem(&public_der).unwrap(),
+            signatures: vec![BundledSignature {
+                name: "invoice.txt".to_string(),
+                digest,
+                envelope: SignatureEnvelope::seal(&attributes, &private_der).unwrap(),
+            }],
+        };
+
+        let candidate = |name: &str, public_key_der: &[u8], distrusted: bool| SignerCandidate {
+            name: name.to_string(),
+            fingerprint: compute_fingerprint(public_key_der),
+            algorithm: algorithm.clone(),
+            public_key_der: public_key_der.to_vec(),
+            distrusted,
+        };
+        // 包里的公钥与签名自洽，但不是本机认识的密钥
+        let (_, known_der) = known_answer_key_pair(&SignatureAlgorithm::EcdsaP256Sha256).unwrap();
+        let error = check_bundle_signatures(&bundle, &[candidate("Alice", &known_der, false)])
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("neither one of your keys nor a contact"));
+
+        let candidates = [candidate("Mallory", &public_der, false)];
+        let signer = check_bundle_signatures(&bundle, &candidates).unwrap();
+        assert_eq!(signer.name, "Mallory");
+        let candidates = [candidate("Mallory", &public_der, true)];
+        assert!(check_bundle_signatures(&bundle, &candidates).is_err());
+    }
+}
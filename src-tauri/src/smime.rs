@@ -0,0 +1,593 @@
+// src-tauri/src/smime.rs
+// Verifies S/MIME signed e-mails (RFC 8551) from raw `.eml` files. Two forms are read:
+// `multipart/signed` with a detached `application/pkcs7-signature` part (clear-signed, the
+// common case), and `application/pkcs7-mime; smime-type=signed-data`, where the signed
+// entity travels inside the CMS structure. Encrypted messages are not supported.
+//
+// The check covers the CMS signature with the signer certificate, whether the body still
+// matches the signed digest, and the certificate chain as far as the message carries it.
+// There is no system trust store here: a chain is trusted when one of its certificates has
+// the public key of a verified contact or an own key.
+use crate::audit;
+use crate::contacts::{get_contacts_path, read_contacts};
+use crate::crypto_types::{SmimeVerification, TrustLevel};
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, get_metadata_path, read_metadata,
+    read_public_key_pem,
+};
+use crate::native_paths::{native_path, read_file, with_network_retry};
+use crate::signature_inspect::certificate_summary;
+use crate::timestamp::{
+    certificate_signed_by, digest_with, find_signer_certificate, verify_with_certificate_key,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use cms::cert::CertificateChoices;
+use cms::content_info::ContentInfo;
+use cms::signed_data::SignedData;
+use const_oid::db::rfc3280::EMAIL_ADDRESS;
+use const_oid::db::rfc5911::{ID_MESSAGE_DIGEST, ID_SIGNED_DATA, ID_SIGNING_TIME};
+use der::asn1::OctetString;
+use der::oid::AssociatedOid;
+use der::{Decode, Encode};
+use serde_json::json;
+use std::fs;
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::ext::pkix::SubjectAltName;
+use x509_cert::time::Time;
+use x509_cert::Certificate;
+
+/// Upper bound for an `.eml` file.
+const MAX_EML_SIZE: u64 = 50 * 1024 * 1024;
+/// Longest certificate chain followed from the signer.
+const MAX_CHAIN_LENGTH: usize = 10;
+
+// --- Tauri Commands ---
+
+/// Verifies the S/MIME signature of a raw e-mail file and reports the signer, whether the
+/// body was altered and how far the certificate chain checks.
+#[tauri::command(rename_all = "camelCase")]
+pub fn verify_smime_email(
+    app_handle: tauri::AppHandle,
+    eml_path: String,
+) -> Result<SmimeVerification, String> {
+    log::info!("Verifying S/MIME e-mail: {}", eml_path);
+    _verify_smime_email(&app_handle, &eml_path)
+        .map_err(|e| {
+            log::error!("Failed to verify S/MIME e-mail: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|result| {
+            audit::record(
+                &app_handle,
+                "verifySmimeEmail",
+                json!({
+                    "emlPath": eml_path,
+                    "signatureValid": result.signature_valid,
+                    "bodyAltered": result.body_altered,
+                    "signer": result.signer_subject,
+                    "chainTrusted": result.chain_trusted,
+                }),
+            )
+        })
+}
+
+// --- Helper Functions ---
+
+fn _verify_smime_email(app_handle: &tauri::AppHandle, eml_path: &str) -> Result<SmimeVerification> {
+    let path = native_path(eml_path)?;
+    let size = with_network_retry(&path, || fs::metadata(&path))
+        .with_context(|| format!("Failed to read e-mail file: {}", eml_path))?
+        .len();
+    if size > MAX_EML_SIZE {
+        bail!(
+            "The e-mail file is too large ({} bytes, at most {})",
+            size,
+            MAX_EML_SIZE
+        );
+    }
+    let raw =
+        read_file(&path).with_context(|| format!("Failed to read e-mail file: {}", eml_path))?;
+    let message = MimeEntity::parse(&raw);
+
+    let mut result = SmimeVerification {
+        from: message.header("from").and_then(mailbox_address),
+        subject: message.header("subject").map(str::to_string),
+        ..Default::default()
+    };
+    let (cms_der, detached) = signed_parts(&message)?;
+    let signer = verify_cms(&cms_der, detached.as_deref(), &mut result)?;
+
+    if let Some(signer) = signer {
+        result.signer_subject = Some(signer.certificate.tbs_certificate.subject.to_string());
+        result.signer_email = certificate_email(&signer.certificate);
+        result.from_matches_signer = match (&result.from, &result.signer_email) {
+            (Some(from), Some(email)) => Some(from.eq_ignore_ascii_case(email)),
+            _ => None,
+        };
+        if result.from_matches_signer == Some(false) {
+            result
+                .problems
+                .push("The sender address is not the signer's address".to_string());
+        }
+        let checked_at = result.signed_at.unwrap_or_else(Utc::now);
+        let chain = build_chain(signer.certificate, &signer.pool, checked_at, &mut result);
+        check_trust(app_handle, &chain, &mut result)?;
+        result.chain = chain.iter().map(certificate_summary).collect();
+    }
+    Ok(result)
+}
+
+// 取出 CMS 签名（DER）以及分离签名对应的已签名实体（规范化为 CRLF）
+fn signed_parts(message: &MimeEntity) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+    let (mime_type, params) = message.content_type();
+    match mime_type.as_str() {
+        "multipart/signed" => {
+            let boundary = params
+                .iter()
+                .find(|(name, _)| name == "boundary")
+                .map(|(_, value)| value.as_str())
+                .ok_or_else(|| anyhow!("The signed message has no MIME boundary"))?;
+            let parts = multipart_parts(message.body, boundary)?;
+            let [content, signature] = parts.as_slice() else {
+                bail!("A signed message must have exactly two parts");
+            };
+            let signature = MimeEntity::parse(signature);
+            let (signature_type, _) = signature.content_type();
+            if !matches!(
+                signature_type.as_str(),
+                "application/pkcs7-signature" | "application/x-pkcs7-signature"
+            ) {
+                bail!("The message is not S/MIME signed ({})", signature_type);
+            }
+            Ok((signature.decoded_body()?, Some(to_crlf(content))))
+        }
+        "application/pkcs7-mime" | "application/x-pkcs7-mime" => {
+            let smime_type = params
+                .iter()
+                .find(|(name, _)| name == "smime-type")
+                .map(|(_, value)| value.to_ascii_lowercase());
+            match smime_type.as_deref() {
+                Some("signed-data") | None => Ok((message.decoded_body()?, None)),
+                Some(other) => bail!("Unsupported S/MIME message type: {}", other),
+            }
+        }
+        other => bail!("The message is not S/MIME signed ({})", other),
+    }
+}
+
+struct Signer {
+    certificate: Certificate,
+    /// Certificates carried in the signature, for building the chain.
+    pool: Vec<Certificate>,
+}
+
+// 校验 CMS 签名；签名者证书缺失时返回 None 并记录问题
+fn verify_cms(
+    cms_der: &[u8],
+    detached: Option<&[u8]>,
+    result: &mut SmimeVerification,
+) -> Result<Option<Signer>> {
+    let content_info =
+        ContentInfo::from_der(cms_der).map_err(|e| anyhow!("Malformed S/MIME signature: {}", e))?;
+    if content_info.content_type != ID_SIGNED_DATA {
+        bail!("The S/MIME structure is not signed data");
+    }
+    let signed_data: SignedData = content_info
+        .content
+        .decode_as()
+        .map_err(|e| anyhow!("Malformed S/MIME SignedData: {}", e))?;
+    let content = match (detached, &signed_data.encap_content_info.econtent) {
+        (Some(content), _) => content.to_vec(),
+        (None, Some(econtent)) => econtent.value().to_vec(),
+        (None, None) => bail!("The signature does not include the signed content"),
+    };
+    let signer_info = signed_data
+        .signer_infos
+        .0
+        .iter()
+        .next()
+        .ok_or_else(|| anyhow!("The S/MIME signature has no signer"))?;
+    let certificate = match find_signer_certificate(&signed_data, signer_info) {
+        Ok(certificate) => certificate,
+        Err(e) => {
+            result.problems.push(e.to_string());
+            return Ok(None);
+        }
+    };
+
+    let content_digest = digest_with(&signer_info.digest_alg.oid, &content)?;
+    // 有签名属性时签名覆盖属性，正文由 messageDigest 属性绑定
+    let signed_data_der = match &signer_info.signed_attrs {
+        Some(signed_attrs) => {
+            let mut message_digest = None;
+            for attribute in signed_attrs.iter() {
+                let Some(value) = attribute.values.iter().next() else {
+                    continue;
+                };
+                if attribute.oid == ID_MESSAGE_DIGEST {
+                    let digest: OctetString = value
+                        .decode_as()
+                        .map_err(|e| anyhow!("Malformed messageDigest attribute: {}", e))?;
+                    message_digest = Some(digest.as_bytes().to_vec());
+                } else if attribute.oid == ID_SIGNING_TIME {
+                    result.signed_at = value
+                        .to_der()
+                        .ok()
+                        .and_then(|der| Time::from_der(&der).ok())
+                        .map(|time| DateTime::<Utc>::from(time.to_system_time()));
+                }
+            }
+            let message_digest = message_digest
+                .ok_or_else(|| anyhow!("The signature has no messageDigest attribute"))?;
+            result.body_altered = message_digest != content_digest;
+            signed_attrs
+                .to_der()
+                .map_err(|e| anyhow!("Failed to encode signed attributes: {}", e))?
+        }
+        None => content,
+    };
+
+    match verify_with_certificate_key(
+        &signer_info.signature_algorithm.oid,
+        &signer_info.digest_alg.oid,
+        &certificate,
+        &signed_data_der,
+        signer_info.signature.as_bytes(),
+    ) {
+        Ok(true) => result.signature_valid = !result.body_altered,
+        // 没有签名属性时无法区分正文被改和签名无效
+        Ok(false) if signer_info.signed_attrs.is_none() => result.body_altered = true,
+        Ok(false) => result
+            .problems
+            .push("The signature does not check with the signer certificate".to_string()),
+        Err(e) => result.problems.push(format!("{:#}", e)),
+    }
+    if result.body_altered {
+        result
+            .problems
+            .push("The body was altered after signing".to_string());
+    }
+
+    let pool = signed_data
+        .certificates
+        .iter()
+        .flat_map(|set| set.0.iter())
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(cert) => Some(cert.clone()),
+            _ => None,
+        })
+        .collect();
+    Ok(Some(Signer { certificate, pool }))
+}
+
+// 从签名者证书向上逐级查找并校验签发者，遇到自签名证书为止
+fn build_chain(
+    signer: Certificate,
+    pool: &[Certificate],
+    checked_at: DateTime<Utc>,
+    result: &mut SmimeVerification,
+) -> Vec<Certificate> {
+    let mut chain = vec![signer];
+    while let Some(current) = chain.last() {
+        let tbs = &current.tbs_certificate;
+        let not_before = DateTime::<Utc>::from(tbs.validity.not_before.to_system_time());
+        let not_after = DateTime::<Utc>::from(tbs.validity.not_after.to_system_time());
+        if checked_at < not_before || checked_at > not_after {
+            result.problems.push(format!(
+                "Certificate {} was not valid at {}",
+                tbs.subject, checked_at
+            ));
+        }
+
+        let self_signed = tbs.issuer == tbs.subject;
+        let issuer = if self_signed {
+            Some(current)
+        } else {
+            pool.iter().find(|cert| {
+                cert.tbs_certificate.subject == tbs.issuer
+                    && !chain.iter().any(|linked| linked == *cert)
+            })
+        };
+        let Some(issuer) = issuer else {
+            result.problems.push(format!(
+                "The message does not include the issuer of {}",
+                tbs.subject
+            ));
+            break;
+        };
+        if !certificate_signed_by(current, issuer) {
+            result.problems.push(format!(
+                "The signature on certificate {} does not check with its issuer",
+                tbs.subject
+            ));
+            break;
+        }
+        if self_signed {
+            result.chain_complete = true;
+            break;
+        }
+        if chain.len() >= MAX_CHAIN_LENGTH {
+            result
+                .problems
+                .push("The certificate chain is too long".to_string());
+            break;
+        }
+        let issuer = issuer.clone();
+        chain.push(issuer);
+    }
+    chain
+}
+
+// 链中任一证书的公钥属于已验证联系人或本地密钥即视为可信
+fn check_trust(
+    app_handle: &tauri::AppHandle,
+    chain: &[Certificate],
+    result: &mut SmimeVerification,
+) -> Result<()> {
+    let contacts = read_contacts(&get_contacts_path(app_handle)?)?;
+    let own_keys = read_metadata(&get_metadata_path(app_handle)?)?
+        .into_iter()
+        .filter(|metadata| metadata.trashed_at.is_none())
+        .filter_map(|metadata| {
+            let pem = read_public_key_pem(app_handle, &metadata).ok()?;
+            let der = decode_public_key_pem(&pem).ok()?;
+            Some((compute_fingerprint(&der), metadata.name))
+        })
+        .collect::<Vec<_>>();
+
+    for certificate in chain {
+        let Ok(spki_der) = certificate.tbs_certificate.subject_public_key_info.to_der() else {
+            continue;
+        };
+        let fingerprint = compute_fingerprint(&spki_der);
+        if let Some(contact) = contacts.iter().find(|c| c.fingerprint == fingerprint) {
+            match contact.trust {
+                TrustLevel::Distrusted => {
+                    result.problems.push(format!(
+                        "Certificate {} belongs to distrusted contact {}",
+                        certificate.tbs_certificate.subject, contact.name
+                    ));
+                    result.trusted_by = None;
+                    result.chain_trusted = false;
+                    return Ok(());
+                }
+                TrustLevel::Verified if result.trusted_by.is_none() => {
+                    result.trusted_by = Some(contact.name.clone());
+                }
+                _ => {}
+            }
+        } else if let Some((_, name)) = own_keys.iter().find(|(f, _)| *f == fingerprint) {
+            result.trusted_by.get_or_insert_with(|| name.clone());
+        }
+    }
+    result.chain_trusted = result.trusted_by.is_some();
+    if !result.chain_trusted {
+        result.problems.push(
+            "No certificate in the chain belongs to a verified contact or an own key".to_string(),
+        );
+    }
+    Ok(())
+}
+
+// 证书中的邮箱：优先 SAN 的 rfc822Name，其次主题中的 emailAddress
+fn certificate_email(certificate: &Certificate) -> Option<String> {
+    let tbs = &certificate.tbs_certificate;
+    let san_email = tbs
+        .extensions
+        .iter()
+        .flatten()
+        .filter(|ext| ext.extn_id == SubjectAltName::OID)
+        .filter_map(|ext| SubjectAltName::from_der(ext.extn_value.as_bytes()).ok())
+        .flat_map(|san| san.0)
+        .find_map(|name| match name {
+            GeneralName::Rfc822Name(email) => Some(email.to_string()),
+            _ => None,
+        });
+    san_email.or_else(|| {
+        tbs.subject
+            .0
+            .iter()
+            .flat_map(|rdn| rdn.0.iter())
+            .find(|atv| atv.oid == EMAIL_ADDRESS)
+            .and_then(|atv| std::str::from_utf8(atv.value.value()).ok())
+            .map(str::to_string)
+    })
+}
+
+// 从 From 头中取出地址（"Name <addr>" 或裸地址）
+fn mailbox_address(value: &str) -> Option<String> {
+    let address = match (value.rfind('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &value[start + 1..end],
+        _ => value,
+    };
+    let address = address.trim();
+    address.contains('@').then(|| address.to_string())
+}
+
+/// A MIME entity split into unfolded headers and the raw body.
+struct MimeEntity<'a> {
+    headers: Vec<(String, String)>,
+    body: &'a [u8],
+}
+
+impl<'a> MimeEntity<'a> {
+    // 头部与正文以第一个空行分隔；续行（以空白开头）并入上一行
+    fn parse(raw: &'a [u8]) -> Self {
+        let mut headers: Vec<(String, String)> = Vec::new();
+        let mut offset = 0;
+        while offset < raw.len() {
+            let end = raw[offset..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(raw.len(), |i| offset + i);
+            let line = String::from_utf8_lossy(&raw[offset..end]);
+            let line = line.trim_end_matches('\r');
+            offset = end + 1;
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+        MimeEntity {
+            headers,
+            body: &raw[offset.min(raw.len())..],
+        }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // 返回小写的 MIME 类型与参数（参数名小写，值去引号）
+    fn content_type(&self) -> (String, Vec<(String, String)>) {
+        let value = self.header("content-type").unwrap_or("text/plain");
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        for c in value.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                ';' if !quoted => fields.push(std::mem::take(&mut field)),
+                _ => field.push(c),
+            }
+        }
+        fields.push(field);
+        let mime_type = fields[0].trim().to_ascii_lowercase();
+        let params = fields[1..]
+            .iter()
+            .filter_map(|param| param.split_once('='))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+        (mime_type, params)
+    }
+
+    fn decoded_body(&self) -> Result<Vec<u8>> {
+        let encoding = self
+            .header("content-transfer-encoding")
+            .unwrap_or("7bit")
+            .to_ascii_lowercase();
+        match encoding.as_str() {
+            "base64" => {
+                let text: Vec<u8> = self
+                    .body
+                    .iter()
+                    .copied()
+                    .filter(|b| !b.is_ascii_whitespace())
+                    .collect();
+                BASE64
+                    .decode(text)
+                    .map_err(|e| anyhow!("Malformed base64 in the S/MIME part: {}", e))
+            }
+            "7bit" | "8bit" | "binary" => Ok(self.body.to_vec()),
+            other => bail!(
+                "Unsupported transfer encoding for the S/MIME part: {}",
+                other
+            ),
+        }
+    }
+}
+
+// 按边界切分 multipart 正文；分隔行前的换行属于分隔符，不属于部件
+fn multipart_parts<'a>(body: &'a [u8], boundary: &str) -> Result<Vec<&'a [u8]>> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut part_start = None;
+    let mut line_start = 0;
+    while line_start < body.len() {
+        let line_end = body[line_start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(body.len(), |i| line_start + i);
+        let mut line = &body[line_start..line_end];
+        while let [rest @ .., last] = line {
+            if !last.is_ascii_whitespace() {
+                break;
+            }
+            line = rest;
+        }
+        if let Some(rest) = line.strip_prefix(delimiter.as_bytes()) {
+            if rest.is_empty() || rest == b"--" {
+                if let Some(start) = part_start {
+                    let mut end = line_start.saturating_sub(1);
+                    if end > 0 && body[end - 1] == b'\r' {
+                        end -= 1;
+                    }
+                    parts.push(&body[start..end.max(start)]);
+                }
+                if rest == b"--" {
+                    return Ok(parts);
+                }
+                part_start = Some((line_end + 1).min(body.len()));
+            }
+        }
+        line_start = line_end + 1;
+    }
+    bail!("The multipart body has no closing boundary")
+}
+
+// S/MIME 对规范形式（CRLF 换行）签名，传输中可能变为 LF
+fn to_crlf(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 32);
+    for (i, &b) in data.iter().enumerate() {
+        if b == b'\n' && (i == 0 || data[i - 1] != b'\r') {
+            out.push(b'\r');
+        }
+        out.push(b);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_signed_message_parts() {
+        let message = b"From: Alice <alice@example.com>\n\
+Subject: Hello\n\
+Content-Type: multipart/signed; protocol=\"application/pkcs7-signature\";\n\
+\tmicalg=sha-256; boundary=\"b1;x\"\n\
+\n\
+This is an S/MIME signed message\n\
+--b1;x\n\
+Content-Type: text/plain\n\
+\n\
+Hi\n\
+--b1;x\n\
+Content-Type: application/pkcs7-signature\n\
+Content-Transfer-Encoding: base64\n\
+\n\
+AAEC\n\
+--b1;x--\n";
+        let entity = MimeEntity::parse(message);
+        let (mime_type, params) = entity.content_type();
+        assert_eq!(mime_type, "multipart/signed");
+        assert!(params.contains(&("boundary".to_string(), "b1;x".to_string())));
+        assert_eq!(
+            entity.header("from").and_then(mailbox_address).as_deref(),
+            Some("alice@example.com")
+        );
+
+        let parts = multipart_parts(entity.body, "b1;x").unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(to_crlf(parts[0]), b"Content-Type: text/plain\r\n\r\nHi");
+        assert_eq!(
+            MimeEntity::parse(parts[1]).decoded_body().unwrap(),
+            vec![0, 1, 2]
+        );
+        assert!(multipart_parts(entity.body, "other").is_err());
+    }
+}
@@ -0,0 +1,229 @@
+// src-tauri/src/sshsig.rs
+// OpenSSH signatures (the SSHSIG format of `ssh-keygen -Y sign`, see PROTOCOL.sshsig in
+// the OpenSSH sources), written so that a document signed with one of the user's keys can
+// be checked with `ssh-keygen -Y verify` (see verification_kit.rs).
+//
+// The key signs a small blob naming the namespace and carrying the SHA-512 of the
+// document, with the algorithm's usual SSH scheme: rsa-sha2-256, ecdsa-sha2-nistp256 (and
+// the P-384 and P-521 variants) or ssh-ed25519. OpenSSH has no key type for ML-DSA, so
+// those keys have neither an OpenSSH form nor an SSH fingerprint.
+use crate::crypto_types::SignatureAlgorithm;
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::{STANDARD as BASE64, STANDARD_NO_PAD};
+use base64::Engine;
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use pkcs8::DecodePublicKey;
+use rsa::traits::PublicKeyParts;
+use sha2::{Digest, Sha256};
+
+const MAGIC: &[u8] = b"SSHSIG";
+const SIGNATURE_VERSION: u32 = 1;
+/// Hash of the document named in the signature.
+pub(crate) const HASH_ALGORITHM: &str = "sha512";
+/// Line length of the armored signature, as written by ssh-keygen.
+const ARMOR_LINE_LEN: usize = 70;
+
+// --- Helper Functions ---
+
+/// The public key in OpenSSH wire format.
+pub(crate) fn public_key_blob(
+    algorithm: &SignatureAlgorithm,
+    public_key_der: &[u8],
+) -> Result<Vec<u8>> {
+    let mut blob = Vec::new();
+    match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => {
+            let key = rsa::RsaPublicKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as RSA public key")?;
+            put_string(&mut blob, b"ssh-rsa");
+            put_mpint(&mut blob, &key.e().to_bytes_be());
+            put_mpint(&mut blob, &key.n().to_bytes_be());
+        }
+        SignatureAlgorithm::EcdsaP256Sha256 => {
+            let key = p256::PublicKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as ECDSA P-256 public key")?;
+            put_string(&mut blob, b"ecdsa-sha2-nistp256");
+            put_string(&mut blob, b"nistp256");
+            put_string(&mut blob, key.to_encoded_point(false).as_bytes());
+        }
+        SignatureAlgorithm::EcdsaP384Sha384 => {
+            let key = p384::PublicKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as ECDSA P-384 public key")?;
+            put_string(&mut blob, b"ecdsa-sha2-nistp384");
+            put_string(&mut blob, b"nistp384");
+            put_string(&mut blob, key.to_encoded_point(false).as_bytes());
+        }
+        SignatureAlgorithm::EcdsaP521Sha512 => {
+            let key = p521::PublicKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as ECDSA P-521 public key")?;
+            put_string(&mut blob, b"ecdsa-sha2-nistp521");
+            put_string(&mut blob, b"nistp521");
+            put_string(&mut blob, key.to_encoded_point(false).as_bytes());
+        }
+        SignatureAlgorithm::Ed25519 => {
+            let key = ed25519_dalek::VerifyingKey::from_public_key_der(public_key_der)
+                .context("Failed to parse SPKI DER as Ed25519 public key")?;
+            put_string(&mut blob, b"ssh-ed25519");
+            put_string(&mut blob, key.as_bytes());
+        }
+        // OpenSSH 没有 ML-DSA 密钥类型，key_type 返回错误
+        SignatureAlgorithm::MlDsa65 => {
+            key_type(algorithm)?;
+        }
+    }
+    Ok(blob)
+}
+
+/// The public key as an `authorized_keys` / `allowed_signers` entry: type and base64 blob.
+pub(crate) fn public_key_line(
+    algorithm: &SignatureAlgorithm,
+    public_key_der: &[u8],
+) -> Result<String> {
+    Ok(format!(
+        "{} {}",
+        key_type(algorithm)?,
+        BASE64.encode(public_key_blob(algorithm, public_key_der)?)
+    ))
+}
+
+/// Fingerprint of the key as `ssh-keygen -l` prints it: `SHA256:` and the unpadded base64
+/// SHA-256 of the wire-format key.
+pub(crate) fn ssh_fingerprint(
+    algorithm: &SignatureAlgorithm,
+    public_key_der: &[u8],
+) -> Result<String> {
+    Ok(format!(
+        "SHA256:{}",
+        STANDARD_NO_PAD.encode(Sha256::digest(public_key_blob(algorithm, public_key_der)?))
+    ))
+}
+
+/// `ssh_fingerprint` for keys OpenSSH has a key type for, `None` for the others.
+pub(crate) fn optional_ssh_fingerprint(
+    algorithm: &SignatureAlgorithm,
+    public_key_der: &[u8],
+) -> Result<Option<String>> {
+    if has_ssh_key_type(algorithm) {
+        ssh_fingerprint(algorithm, public_key_der).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Whether OpenSSH has a key type for the algorithm (it has none for ML-DSA).
+pub(crate) fn has_ssh_key_type(algorithm: &SignatureAlgorithm) -> bool {
+    key_type(algorithm).is_ok()
+}
+
+/// The blob the key signs for a document whose SHA-512 is `document_hash`.
+pub(crate) fn signed_data(namespace: &str, document_hash: &[u8]) -> Vec<u8> {
+    let mut data = MAGIC.to_vec();
+    put_string(&mut data, namespace.as_bytes());
+    put_string(&mut data, b"");
+    put_string(&mut data, HASH_ALGORITHM.as_bytes());
+    put_string(&mut data, document_hash);
+    data
+}
+
+/// The armored signature file, from the signature over `signed_data` as returned by
+/// `sign_bytes` (ECDSA as raw `r || s`).
+pub(crate) fn armor_signature(
+    algorithm: &SignatureAlgorithm,
+    public_key_der: &[u8],
+    namespace: &str,
+    signature: &[u8],
+) -> Result<String> {
+    let mut encoded_signature = Vec::new();
+    match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => {
+            put_string(&mut encoded_signature, b"rsa-sha2-256");
+            put_string(&mut encoded_signature, signature);
+        }
+        SignatureAlgorithm::EcdsaP256Sha256
+        | SignatureAlgorithm::EcdsaP384Sha384
+        | SignatureAlgorithm::EcdsaP521Sha512 => {
+            let (r, s) = signature.split_at(signature.len() / 2);
+            let mut values = Vec::new();
+            put_mpint(&mut values, r);
+            put_mpint(&mut values, s);
+            put_string(&mut encoded_signature, key_type(algorithm)?.as_bytes());
+            put_string(&mut encoded_signature, &values);
+        }
+        SignatureAlgorithm::Ed25519 => {
+            put_string(&mut encoded_signature, b"ssh-ed25519");
+            put_string(&mut encoded_signature, signature);
+        }
+        // OpenSSH 没有 ML-DSA 密钥类型，key_type 返回错误
+        SignatureAlgorithm::MlDsa65 => {
+            key_type(algorithm)?;
+        }
+    }
+
+    let mut blob = MAGIC.to_vec();
+    blob.extend_from_slice(&SIGNATURE_VERSION.to_be_bytes());
+    put_string(&mut blob, &public_key_blob(algorithm, public_key_der)?);
+    put_string(&mut blob, namespace.as_bytes());
+    put_string(&mut blob, b"");
+    put_string(&mut blob, HASH_ALGORITHM.as_bytes());
+    put_string(&mut blob, &encoded_signature);
+
+    let encoded = BASE64.encode(blob);
+    let mut armored = String::from("-----BEGIN SSH SIGNATURE-----\n");
+    for line in encoded.as_bytes().chunks(ARMOR_LINE_LEN) {
+        armored.push_str(std::str::from_utf8(line).unwrap_or_default());
+        armored.push('\n');
+    }
+    armored.push_str("-----END SSH SIGNATURE-----\n");
+    Ok(armored)
+}
+
+fn key_type(algorithm: &SignatureAlgorithm) -> Result<&'static str> {
+    Ok(match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => "ssh-rsa",
+        SignatureAlgorithm::EcdsaP256Sha256 => "ecdsa-sha2-nistp256",
+        SignatureAlgorithm::EcdsaP384Sha384 => "ecdsa-sha2-nistp384",
+        SignatureAlgorithm::EcdsaP521Sha512 => "ecdsa-sha2-nistp521",
+        SignatureAlgorithm::Ed25519 => "ssh-ed25519",
+        SignatureAlgorithm::MlDsa65 => bail!("OpenSSH has no key type for {} keys", algorithm),
+    })
+}
+
+// SSH 线格式：u32 长度前缀的字符串
+fn put_string(out: &mut Vec<u8>, value: &[u8]) {
+    out.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+// mpint：去掉前导零，最高位为 1 时补一个零字节（非负数）
+fn put_mpint(out: &mut Vec<u8>, value: &[u8]) {
+    let start = value.iter().position(|&b| b != 0).unwrap_or(value.len());
+    let value = &value[start..];
+    if value.first().is_some_and(|&b| b & 0x80 != 0) {
+        out.extend_from_slice(&(value.len() as u32 + 1).to_be_bytes());
+        out.push(0);
+        out.extend_from_slice(value);
+    } else {
+        put_string(out, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_management::decode_public_key_pem;
+
+    #[test]
+    fn fingerprint_matches_ssh_keygen() {
+        let public_key_der = decode_public_key_pem(
+            "-----BEGIN PUBLIC KEY-----\n\
+             MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEpAUvWZV2NEdUopzVHrcDRps+ZDoV\n\
+             9rgp3M0mBFMEylFFU/ye0FZkZgABOM6/+fRLv6AHw6ky5Pw8NY0Oe5f1kQ==\n\
+             -----END PUBLIC KEY-----\n",
+        )
+        .unwrap();
+        assert_eq!(
+            ssh_fingerprint(&SignatureAlgorithm::EcdsaP256Sha256, &public_key_der).unwrap(),
+            "SHA256:sMeTRcYx+zk9YYJ/sdfmxLxM3UFOMF+lJ4PdYII0rLs"
+        );
+    }
+}
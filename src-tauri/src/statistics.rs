@@ -0,0 +1,322 @@
+// src-tauri/src/statistics.rs
+// Usage statistics for the dashboard: how many documents were signed and how many
+// signatures were checked, per key, per algorithm and per day.
+//
+// The numbers are counted from the audit log, so they cover everything the log records
+// (the app, the tray, the browser extension and the local API) and survive restarts.
+// Batch operations count the documents submitted. Operations that failed before being
+// recorded are not counted; a signing attempt recorded with an error is skipped.
+use crate::audit::read_log;
+use crate::crypto_types::{
+    AlgorithmUsage, AuditEntry, DailyUsage, KeyUsage, SignatureAlgorithm, StatisticsRange,
+    UsageCounts, UsageStatistics,
+};
+use crate::key_management::{
+    decode_public_key_pem, detect_public_key_algorithm, get_metadata_path, read_metadata,
+};
+use crate::kms::list_kms_keys_for_verification;
+use crate::passkeys::list_passkeys_for_verification;
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Audit actions that sign documents.
+const SIGNING_ACTIONS: &[&str] = &[
+    "signDocument",
+    "signDocumentsBatch",
+    "quickSign",
+    "signWithKms",
+    "signWithPasskey",
+    "browserSign",
+    "localApiSign",
+    "signOfficeDocument",
+    "signOdfDocument",
+    "signSigningRequest",
+    "signSigningRequestBundle",
+    "clearsignText",
+    "createSignedNote",
+    "refreshSignature",
+];
+
+/// Audit actions that check signatures.
+const VERIFICATION_ACTIONS: &[&str] = &[
+    "verifySignature",
+    "verifyDocumentsBatch",
+    "verifyArchive",
+    "verifySmimeEmail",
+    "verifyClearsignedText",
+    "verifySignedNote",
+    "verifyTrustedArtifact",
+];
+
+/// One counted audit entry.
+struct Activity {
+    day: NaiveDate,
+    key_id: Option<Uuid>,
+    counts: UsageCounts,
+}
+
+// --- Tauri Commands ---
+
+/// Signing and verification counts for audit entries within `range`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_statistics(
+    app_handle: tauri::AppHandle,
+    range: StatisticsRange,
+) -> Result<UsageStatistics, String> {
+    log::info!("Computing usage statistics for {:?}", range);
+    _get_statistics(&app_handle, range).map_err(|e| {
+        log::error!("Failed to compute usage statistics: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _get_statistics(
+    app_handle: &tauri::AppHandle,
+    range: StatisticsRange,
+) -> Result<UsageStatistics> {
+    if let (Some(from), Some(to)) = (range.from, range.to) {
+        if from > to {
+            bail!("The start of the range is after its end");
+        }
+    }
+    let entries = read_log(app_handle)?;
+    let keys = known_keys(app_handle)?;
+    Ok(aggregate(&entries, range, &keys))
+}
+
+// --- Helper Functions ---
+
+// 所有已知密钥（含回收站中的密钥、通行密钥和 KMS 密钥）的名称与算法
+fn known_keys(
+    app_handle: &tauri::AppHandle,
+) -> Result<HashMap<Uuid, (String, SignatureAlgorithm)>> {
+    let mut keys: HashMap<_, _> = read_metadata(&get_metadata_path(app_handle)?)?
+        .into_iter()
+        .map(|meta| (meta.key_id, (meta.name, meta.algorithm)))
+        .collect();
+    for passkey in list_passkeys_for_verification(app_handle)? {
+        let algorithm = decode_public_key_pem(&passkey.public_key_pem)
+            .and_then(|der| detect_public_key_algorithm(&der))?;
+        keys.insert(passkey.key_id, (passkey.name, algorithm));
+    }
+    for key in list_kms_keys_for_verification(app_handle)? {
+        keys.insert(key.key_id, (key.name, key.algorithm));
+    }
+    Ok(keys)
+}
+
+fn aggregate(
+    entries: &[AuditEntry],
+    range: StatisticsRange,
+    keys: &HashMap<Uuid, (String, SignatureAlgorithm)>,
+) -> UsageStatistics {
+    let mut totals = UsageCounts::default();
+    let mut by_key: HashMap<Uuid, UsageCounts> = HashMap::new();
+    let mut by_algorithm: Vec<AlgorithmUsage> = Vec::new();
+    let mut by_day: BTreeMap<NaiveDate, UsageCounts> = BTreeMap::new();
+
+    let in_range = |entry: &&AuditEntry| {
+        range.from.map_or(true, |from| entry.timestamp >= from)
+            && range.to.map_or(true, |to| entry.timestamp <= to)
+    };
+    for activity in entries.iter().filter(in_range).filter_map(activity) {
+        add(&mut totals, &activity.counts);
+        add(by_day.entry(activity.day).or_default(), &activity.counts);
+        if let Some(key_id) = activity.key_id {
+            add(by_key.entry(key_id).or_default(), &activity.counts);
+        }
+        let algorithm = activity
+            .key_id
+            .and_then(|key_id| keys.get(&key_id))
+            .map(|(_, algorithm)| algorithm.clone());
+        match by_algorithm.iter_mut().find(|a| a.algorithm == algorithm) {
+            Some(usage) => add(&mut usage.counts, &activity.counts),
+            None => by_algorithm.push(AlgorithmUsage {
+                algorithm,
+                counts: activity.counts,
+            }),
+        }
+    }
+
+    let mut by_key: Vec<KeyUsage> = by_key
+        .into_iter()
+        .map(|(key_id, counts)| {
+            let known = keys.get(&key_id);
+            KeyUsage {
+                key_id,
+                name: known.map(|(name, _)| name.clone()),
+                algorithm: known.map(|(_, algorithm)| algorithm.clone()),
+                counts,
+            }
+        })
+        .collect();
+    by_key.sort_by_key(|usage| {
+        (
+            std::cmp::Reverse(usage.counts.signatures + usage.counts.verifications),
+            usage.key_id,
+        )
+    });
+    by_algorithm.sort_by_key(|usage| {
+        std::cmp::Reverse(usage.counts.signatures + usage.counts.verifications)
+    });
+
+    UsageStatistics {
+        range,
+        totals,
+        by_key,
+        by_algorithm,
+        by_day: by_day
+            .into_iter()
+            .map(|(day, counts)| DailyUsage { day, counts })
+            .collect(),
+    }
+}
+
+// 把一条审计记录换算成签名/验证次数；与签名、验证无关的记录返回 None
+fn activity(entry: &AuditEntry) -> Option<Activity> {
+    let details = &entry.details;
+    let action = entry.action.as_str();
+    let mut counts = UsageCounts::default();
+    let key_id = if SIGNING_ACTIONS.contains(&action) {
+        if !details["error"].is_null() {
+            return None;
+        }
+        counts.signatures = document_count(details);
+        uuid_field(&details["keyId"]).or_else(|| uuid_field(&details["resignedWith"]))
+    } else if VERIFICATION_ACTIONS.contains(&action) {
+        counts.verifications = details["checked"]
+            .as_u64()
+            .unwrap_or_else(|| document_count(details));
+        counts.failed_verifications = match details["failed"].as_u64() {
+            Some(failed) => failed,
+            None => ["isValid", "valid", "signatureValid"]
+                .iter()
+                .find_map(|field| details[field].as_bool())
+                .map_or(0, |valid| u64::from(!valid)),
+        };
+        uuid_field(&details["keyId"])
+    } else {
+        return None;
+    };
+    Some(Activity {
+        day: entry.timestamp.date_naive(),
+        key_id,
+        counts,
+    })
+}
+
+// 批量操作的 `documents` 为文档数或文档摘要列表，其余操作各计一次
+fn document_count(details: &Value) -> u64 {
+    match &details["documents"] {
+        Value::Number(count) => count.as_u64().unwrap_or(1),
+        Value::Array(documents) => documents.len() as u64,
+        _ => 1,
+    }
+}
+
+fn uuid_field(value: &Value) -> Option<Uuid> {
+    value.as_str().and_then(|s| Uuid::parse_str(s).ok())
+}
+
+fn add(total: &mut UsageCounts, counts: &UsageCounts) {
+    total.signatures += counts.signatures;
+    total.verifications += counts.verifications;
+    total.failed_verifications += counts.failed_verifications;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+
+    fn entry(seq: u64, timestamp: &str, action: &str, details: Value) -> AuditEntry {
+        AuditEntry {
+            seq,
+            timestamp: timestamp.parse::<DateTime<Utc>>().unwrap(),
+            action: action.to_string(),
+            details,
+            prev_hash: String::new(),
+            hash: String::new(),
+        }
+    }
+
+    #[test]
+    fn counts_per_key_algorithm_and_day() {
+        let key_id = Uuid::new_v4();
+        let purged = Uuid::new_v4();
+        let keys = HashMap::from([(key_id, ("Work".to_string(), SignatureAlgorithm::Ed25519))]);
+        let entries = vec![
+            entry(
+                0,
+                "2026-10-01T09:00:00Z",
+                "signDocument",
+                json!({ "keyId": key_id }),
+            ),
+            entry(
+                1,
+                "2026-10-01T10:00:00Z",
+                "signDocument",
+                json!({ "keyId": key_id, "error": "Wrong password" }),
+            ),
+            entry(
+                2,
+                "2026-10-02T08:00:00Z",
+                "signDocumentsBatch",
+                json!({ "keyId": key_id, "documents": 3 }),
+            ),
+            entry(
+                3,
+                "2026-10-02T09:00:00Z",
+                "verifySignature",
+                json!({ "keyId": purged, "isValid": false }),
+            ),
+            entry(
+                4,
+                "2026-10-02T09:30:00Z",
+                "verifyArchive",
+                json!({ "checked": 5, "failed": 1 }),
+            ),
+            entry(
+                5,
+                "2026-10-02T10:00:00Z",
+                "deleteKey",
+                json!({ "keyId": key_id }),
+            ),
+            entry(
+                6,
+                "2026-10-05T10:00:00Z",
+                "quickSign",
+                json!({ "keyId": key_id }),
+            ),
+        ];
+        let range = StatisticsRange {
+            from: None,
+            to: Some("2026-10-03T00:00:00Z".parse().unwrap()),
+        };
+        let stats = aggregate(&entries, range, &keys);
+
+        let counts = |signatures, verifications, failed_verifications| UsageCounts {
+            signatures,
+            verifications,
+            failed_verifications,
+        };
+        assert_eq!(stats.totals, counts(4, 6, 2));
+        assert_eq!(stats.by_key.len(), 2);
+        assert_eq!(stats.by_key[0].key_id, key_id);
+        assert_eq!(stats.by_key[0].name.as_deref(), Some("Work"));
+        assert_eq!(stats.by_key[0].counts, counts(4, 0, 0));
+        assert_eq!(stats.by_key[1].name, None);
+        assert_eq!(stats.by_key[1].counts, counts(0, 1, 1));
+        assert_eq!(stats.by_algorithm.len(), 2);
+        assert_eq!(stats.by_algorithm[0].algorithm, None);
+        assert_eq!(stats.by_algorithm[0].counts, counts(0, 6, 2));
+        assert_eq!(stats.by_day.len(), 2);
+        assert_eq!(stats.by_day[0].day.to_string(), "2026-10-01");
+        assert_eq!(stats.by_day[0].counts, counts(1, 0, 0));
+        assert_eq!(stats.by_day[1].counts, counts(3, 6, 2));
+    }
+}
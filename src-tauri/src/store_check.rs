@@ -0,0 +1,482 @@
+// src-tauri/src/store_check.rs
+use crate::crypto_types::{
+    KeyMetadata, StoreCheckReport, StoreIssue, StoreIssueKind, StoreRepair, StoreRepairAction,
+    StoreRepairReport,
+};
+use crate::key_management::{
+    decode_public_key_pem, detect_public_key_algorithm, get_app_data_file, get_key_storage_dir,
+    get_metadata_path, metadata_json, read_metadata, remove_key, write_metadata, NONCE_LEN,
+    SALT_LEN,
+};
+use crate::key_wrapping::{is_wrapped_key, WrappedKey};
+use crate::store_journal::StoreTransaction;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+const QUARANTINE_DIR: &str = "quarantine";
+const AES_GCM_TAG_LEN: usize = 16;
+
+/// An issue together with the internal data needed to repair it.
+struct DetectedIssue {
+    issue: StoreIssue,
+    /// For missing-file issues: an orphaned file that looks like the missing one.
+    relink_target: Option<String>,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn check_store(app_handle: tauri::AppHandle) -> Result<StoreCheckReport, String> {
+    log::info!("Checking key store integrity");
+    scan_store(&app_handle)
+        .map(|(issues, report)| {
+            log::info!("Key store check found {} issues", issues.len());
+            report
+        })
+        .map_err(|e| {
+            log::error!("Failed to check key store: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn repair_store(
+    app_handle: tauri::AppHandle,
+    repairs: Vec<StoreRepair>,
+) -> Result<StoreRepairReport, String> {
+    log::info!("Applying {} key store repairs", repairs.len());
+    _repair_store(&app_handle, repairs).map_err(|e| {
+        log::error!("Failed to repair key store: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _repair_store(
+    app_handle: &tauri::AppHandle,
+    repairs: Vec<StoreRepair>,
+) -> Result<StoreRepairReport> {
+    let mut applied = Vec::new();
+    let mut failed = Vec::new();
+
+    for repair in repairs {
+        // Re-scan before each repair so earlier repairs are taken into account.
+        let (issues, _) = scan_store(app_handle)?;
+        let Some(detected) = issues.iter().find(|d| d.issue.issue_id == repair.issue_id) else {
+            failed.push(format!("{}: issue no longer present", repair.issue_id));
+            continue;
+        };
+        if !detected.issue.repairs.contains(&repair.action) {
+            failed.push(format!(
+                "{}: action {:?} is not applicable",
+                repair.issue_id, repair.action
+            ));
+            continue;
+        }
+        match apply_repair(app_handle, detected, repair.action) {
+            Ok(description) => {
+                log::info!("Repaired {}: {}", repair.issue_id, description);
+                applied.push(description);
+            }
+            Err(e) => {
+                log::warn!("Failed to repair {}: {:?}", repair.issue_id, e);
+                failed.push(format!("{}: {}", repair.issue_id, e));
+            }
+        }
+    }
+
+    let (_, report) = scan_store(app_handle)?;
+    Ok(StoreRepairReport {
+        applied,
+        failed,
+        report,
+    })
+}
+
+// --- Helper Functions ---
+
+// 扫描密钥存储，返回所有问题及汇总报告
+fn scan_store(app_handle: &tauri::AppHandle) -> Result<(Vec<DetectedIssue>, StoreCheckReport)> {
+    let metadata = read_metadata(&get_metadata_path(app_handle)?)?;
+    let key_storage_dir = get_key_storage_dir(app_handle)?;
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&key_storage_dir).context("Failed to read keys directory")? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            files.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    let referenced: HashSet<&str> = metadata
+        .iter()
+        .flat_map(|m| {
+            [
+                m.public_key_pem_path.as_str(),
+                m.encrypted_private_key_path.as_str(),
+                m.escrow_path.as_deref().unwrap_or_default(),
+            ]
+        })
+        .filter(|f| !f.is_empty())
+        .collect();
+    let orphans: Vec<&String> = files
+        .iter()
+        .filter(|f| !referenced.contains(f.as_str()))
+        .collect();
+
+    let mut issues = Vec::new();
+
+    // 1. Duplicate key IDs
+    let mut id_counts: HashMap<Uuid, usize> = HashMap::new();
+    for m in &metadata {
+        *id_counts.entry(m.key_id).or_default() += 1;
+    }
+    for (key_id, count) in id_counts.iter().filter(|(_, count)| **count > 1) {
+        issues.push(DetectedIssue {
+            issue: new_issue(
+                StoreIssueKind::DuplicateKeyId,
+                Some(*key_id),
+                None,
+                format!("{} metadata entries share key ID {}", count, key_id),
+                vec![StoreRepairAction::Delete],
+            ),
+            relink_target: None,
+        });
+    }
+
+    // 2. Per-entry checks
+    for m in &metadata {
+        let public_key_path = key_storage_dir.join(&m.public_key_pem_path);
+        let private_key_path = key_storage_dir.join(&m.encrypted_private_key_path);
+
+        if !public_key_path.is_file() {
+            let candidate = find_relink_candidate(&orphans, m.key_id, ".pem");
+            issues.push(missing_file_issue(
+                StoreIssueKind::MissingPublicKey,
+                m,
+                &m.public_key_pem_path,
+                candidate,
+            ));
+        } else {
+            check_public_key(&public_key_path, m, &mut issues);
+        }
+
+        if !m.kind.is_key_pair() {
+            continue;
+        }
+        if !private_key_path.is_file() {
+            let candidate = find_relink_candidate(&orphans, m.key_id, ".enc");
+            issues.push(missing_file_issue(
+                StoreIssueKind::MissingPrivateKey,
+                m,
+                &m.encrypted_private_key_path,
+                candidate,
+            ));
+        } else {
+            check_private_key(&private_key_path, m, &mut issues)?;
+        }
+    }
+
+    // 3. Orphaned files
+    for orphan in &orphans {
+        issues.push(DetectedIssue {
+            issue: new_issue(
+                StoreIssueKind::OrphanedFile,
+                None,
+                Some(orphan.to_string()),
+                format!("File '{}' is not referenced by any key", orphan),
+                vec![StoreRepairAction::Quarantine, StoreRepairAction::Delete],
+            ),
+            relink_target: None,
+        });
+    }
+
+    let report = StoreCheckReport {
+        checked_at: Utc::now(),
+        keys_checked: metadata.len(),
+        files_checked: files.len(),
+        issues: issues.iter().map(|d| d.issue.clone()).collect(),
+    };
+    Ok((issues, report))
+}
+
+// 检查私钥文件格式（带头部的新格式，或依赖元数据盐值的旧格式）
+fn check_private_key(
+    private_key_path: &Path,
+    m: &KeyMetadata,
+    issues: &mut Vec<DetectedIssue>,
+) -> Result<()> {
+    let repairs = vec![StoreRepairAction::Quarantine, StoreRepairAction::Delete];
+    let bytes = fs::read(private_key_path)
+        .with_context(|| format!("Failed to read {:?}", private_key_path))?;
+
+    if is_wrapped_key(&bytes) {
+        if let Err(e) = WrappedKey::parse(&bytes) {
+            issues.push(DetectedIssue {
+                issue: new_issue(
+                    StoreIssueKind::MalformedPrivateKey,
+                    Some(m.key_id),
+                    Some(m.encrypted_private_key_path.clone()),
+                    format!("Private key header of '{}' is invalid: {}", m.name, e),
+                    repairs,
+                ),
+                relink_target: None,
+            });
+        }
+        return Ok(());
+    }
+
+    if bytes.len() < NONCE_LEN + AES_GCM_TAG_LEN {
+        issues.push(DetectedIssue {
+            issue: new_issue(
+                StoreIssueKind::MalformedPrivateKey,
+                Some(m.key_id),
+                Some(m.encrypted_private_key_path.clone()),
+                format!(
+                    "Encrypted private key of '{}' is only {} bytes long",
+                    m.name,
+                    bytes.len()
+                ),
+                repairs.clone(),
+            ),
+            relink_target: None,
+        });
+    }
+    let salt_valid = m
+        .salt_hex
+        .as_deref()
+        .and_then(|salt_hex| hex::decode(salt_hex).ok())
+        .is_some_and(|salt| salt.len() == SALT_LEN);
+    if !salt_valid {
+        issues.push(DetectedIssue {
+            issue: new_issue(
+                StoreIssueKind::InvalidSalt,
+                Some(m.key_id),
+                None,
+                format!(
+                    "Legacy key '{}' has no valid {}-byte salt; it cannot be decrypted",
+                    m.name, SALT_LEN
+                ),
+                repairs,
+            ),
+            relink_target: None,
+        });
+    }
+    Ok(())
+}
+
+// 检查公钥文件是否可读且与元数据中的算法一致
+fn check_public_key(public_key_path: &Path, m: &KeyMetadata, issues: &mut Vec<DetectedIssue>) {
+    let detected = fs::read_to_string(public_key_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|pem| decode_public_key_pem(&pem))
+        .and_then(|der| detect_public_key_algorithm(&der));
+    let repairs = vec![StoreRepairAction::Quarantine, StoreRepairAction::Delete];
+    let issue = match detected {
+        Err(e) => new_issue(
+            StoreIssueKind::UnreadablePublicKey,
+            Some(m.key_id),
+            Some(m.public_key_pem_path.clone()),
+            format!("Public key of '{}' is unreadable: {}", m.name, e),
+            repairs,
+        ),
+        Ok(algorithm) if m.algorithm != algorithm => new_issue(
+            StoreIssueKind::AlgorithmMismatch,
+            Some(m.key_id),
+            Some(m.public_key_pem_path.clone()),
+            format!(
+                "Public key of '{}' is {} but metadata records '{}'",
+                m.name, algorithm, m.algorithm
+            ),
+            repairs,
+        ),
+        Ok(_) => return,
+    };
+    issues.push(DetectedIssue {
+        issue,
+        relink_target: None,
+    });
+}
+
+fn missing_file_issue(
+    kind: StoreIssueKind,
+    m: &KeyMetadata,
+    file: &str,
+    relink_target: Option<String>,
+) -> DetectedIssue {
+    let mut repairs = vec![StoreRepairAction::Quarantine, StoreRepairAction::Delete];
+    let mut description = format!("File '{}' of key '{}' is missing", file, m.name);
+    if let Some(target) = &relink_target {
+        repairs.insert(0, StoreRepairAction::Relink);
+        description.push_str(&format!(" (candidate for relinking: '{}')", target));
+    }
+    DetectedIssue {
+        issue: new_issue(
+            kind,
+            Some(m.key_id),
+            Some(file.to_string()),
+            description,
+            repairs,
+        ),
+        relink_target,
+    }
+}
+
+fn new_issue(
+    kind: StoreIssueKind,
+    key_id: Option<Uuid>,
+    file: Option<String>,
+    description: String,
+    repairs: Vec<StoreRepairAction>,
+) -> StoreIssue {
+    let subject = key_id
+        .map(|id| id.to_string())
+        .or_else(|| file.clone())
+        .unwrap_or_default();
+    let kind_tag = serde_json::to_value(&kind)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    StoreIssue {
+        issue_id: format!("{}:{}", kind_tag, subject),
+        kind,
+        key_id,
+        file,
+        description,
+        repairs,
+    }
+}
+
+// 查找文件名包含 key_id 且后缀匹配的孤立文件
+fn find_relink_candidate(orphans: &[&String], key_id: Uuid, suffix: &str) -> Option<String> {
+    let key_id = key_id.to_string();
+    orphans
+        .iter()
+        .find(|f| f.contains(&key_id) && f.ends_with(suffix))
+        .map(|f| f.to_string())
+}
+
+// 执行单个修复操作，返回描述
+fn apply_repair(
+    app_handle: &tauri::AppHandle,
+    detected: &DetectedIssue,
+    action: StoreRepairAction,
+) -> Result<String> {
+    let issue = &detected.issue;
+    let key_storage_dir = get_key_storage_dir(app_handle)?;
+    let metadata_path = get_metadata_path(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+
+    // Issues that concern a file only (orphans)
+    let Some(key_id) = issue.key_id else {
+        let file = issue
+            .file
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Issue has no subject"))?;
+        let path = key_storage_dir.join(file);
+        return match action {
+            StoreRepairAction::Quarantine => {
+                let dir = quarantine_dir(app_handle, file)?;
+                fs::rename(&path, dir.join(file))
+                    .with_context(|| format!("Failed to quarantine {:?}", path))?;
+                Ok(format!("Moved '{}' to {:?}", file, dir))
+            }
+            StoreRepairAction::Delete => {
+                // 孤立文件可能是私钥或托管副本，一律覆盖后删除
+                let mut transaction = StoreTransaction::begin(app_handle, "repairStore")?;
+                transaction
+                    .remove_wiped(&path)
+                    .with_context(|| format!("Failed to delete {:?}", path))?;
+                transaction.commit()?;
+                Ok(format!("Deleted '{}'", file))
+            }
+            StoreRepairAction::Relink => bail!("Orphaned files cannot be relinked"),
+        };
+    };
+
+    if issue.kind == StoreIssueKind::DuplicateKeyId {
+        // Keep the first entry, drop the others (their files are shared by name).
+        let mut seen = false;
+        all_metadata.retain(|m| {
+            if m.key_id != key_id {
+                return true;
+            }
+            let keep = !seen;
+            seen = true;
+            keep
+        });
+        write_metadata(&metadata_path, &all_metadata)?;
+        return Ok(format!("Removed duplicate metadata entries for {}", key_id));
+    }
+
+    let index = all_metadata
+        .iter()
+        .position(|m| m.key_id == key_id)
+        .ok_or_else(|| anyhow::anyhow!("Key with ID {} not found", key_id))?;
+
+    match action {
+        StoreRepairAction::Relink => {
+            let target = detected
+                .relink_target
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("No relink candidate"))?;
+            let entry = &mut all_metadata[index];
+            match issue.kind {
+                StoreIssueKind::MissingPublicKey => entry.public_key_pem_path = target.clone(),
+                StoreIssueKind::MissingPrivateKey => {
+                    entry.encrypted_private_key_path = target.clone()
+                }
+                _ => bail!("Only missing files can be relinked"),
+            }
+            write_metadata(&metadata_path, &all_metadata)?;
+            Ok(format!("Relinked key {} to '{}'", key_id, target))
+        }
+        StoreRepairAction::Quarantine => {
+            let entry = all_metadata.remove(index);
+            let dir = quarantine_dir(app_handle, &key_id.to_string())?;
+            for file in [
+                &entry.public_key_pem_path,
+                &entry.encrypted_private_key_path,
+            ] {
+                let path = key_storage_dir.join(file);
+                if path.is_file() {
+                    fs::rename(&path, dir.join(file))
+                        .with_context(|| format!("Failed to quarantine {:?}", path))?;
+                }
+            }
+            // Keep the metadata entry next to the files so the key can be restored by hand.
+            let entry_json = serde_json::to_string_pretty(&entry)?;
+            fs::write(dir.join("metadata.json"), entry_json)
+                .context("Failed to write quarantined metadata")?;
+            write_metadata(&metadata_path, &all_metadata)?;
+            Ok(format!("Quarantined key '{}' to {:?}", entry.name, dir))
+        }
+        StoreRepairAction::Delete => {
+            // 与删除密钥相同：私钥与托管文件覆盖后删除，元数据在同一事务中写入
+            let entry = all_metadata[index].clone();
+            let mut transaction = StoreTransaction::begin(app_handle, "repairStore")?;
+            remove_key(
+                &mut transaction,
+                &key_storage_dir,
+                &mut all_metadata,
+                &entry,
+            )?;
+            transaction.write(&metadata_path, metadata_json(&all_metadata)?)?;
+            transaction.commit()?;
+            Ok(format!("Deleted key '{}'", entry.name))
+        }
+    }
+}
+
+// 创建本次隔离使用的目录
+fn quarantine_dir(app_handle: &tauri::AppHandle, subject: &str) -> Result<std::path::PathBuf> {
+    let dir = get_app_data_file(app_handle, QUARANTINE_DIR)?.join(format!(
+        "{}-{}",
+        Utc::now().format("%Y%m%dT%H%M%S"),
+        subject
+    ));
+    fs::create_dir_all(&dir).context("Failed to create quarantine directory")?;
+    Ok(dir)
+}
@@ -0,0 +1,329 @@
+// src-tauri/src/store_journal.rs
+// Undo journal for changes to the key store that touch several files (a key's public and
+// private key files plus `keys_metadata.json`, for example). Without it a crash or error
+// halfway could leave key files without metadata or metadata pointing at missing files.
+//
+// A `StoreTransaction` backs up each file before its first change and records the change
+// in `store_journal/<id>/journal.json`, on disk before the file is touched; every write
+// goes through a temporary file and a rename. `commit` marks the journal committed and
+// removes it. A transaction dropped without `commit` (an error returned with `?`) restores
+// the backups right away. One interrupted by a crash is rolled back at the next startup by
+// `recover_store_transactions`; if it had already been committed, only its leftover
+// backups are removed.
+//
+// Files holding key material are removed with `remove_wiped` and replaced with
+// `write_wiped`: the old file is overwritten before it is unlinked, and so is its backup
+// when the transaction directory is cleared.
+use crate::key_management::get_app_data_file;
+use crate::secure_temp::wipe_file;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const JOURNAL_DIRNAME: &str = "store_journal";
+const JOURNAL_FILENAME: &str = "journal.json";
+
+/// On-disk record of a transaction.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Journal {
+    /// What the transaction does, for the log (e.g., "addKey").
+    operation: String,
+    started_at: DateTime<Utc>,
+    committed: bool,
+    /// Files changed so far, in order.
+    changes: Vec<JournalChange>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct JournalChange {
+    path: PathBuf,
+    /// Backup of the previous content, in the transaction's directory; `None` if the file
+    /// did not exist.
+    backup: Option<String>,
+    /// The backup holds key material and is overwritten before it is removed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    wipe: bool,
+}
+
+/// A multi-file change to the key store that is applied completely or not at all.
+pub(crate) struct StoreTransaction {
+    dir: PathBuf,
+    journal: Journal,
+    finished: bool,
+}
+
+impl StoreTransaction {
+    /// Starts a transaction; `operation` names it in the log.
+    pub(crate) fn begin(app_handle: &tauri::AppHandle, operation: &str) -> Result<Self> {
+        let dir = get_app_data_file(app_handle, JOURNAL_DIRNAME)?.join(Uuid::new_v4().to_string());
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        let transaction = StoreTransaction {
+            dir,
+            journal: Journal {
+                operation: operation.to_string(),
+                started_at: Utc::now(),
+                committed: false,
+                changes: Vec::new(),
+            },
+            finished: false,
+        };
+        transaction.save()?;
+        Ok(transaction)
+    }
+
+    /// Replaces the content of `path` (creating it if needed).
+    pub(crate) fn write(&mut self, path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+        self.record(path, false)?;
+        write_atomically(path, content.as_ref())
+    }
+
+    /// Removes `path` if it exists.
+    pub(crate) fn remove(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        self.record(path, false)?;
+        fs::remove_file(path).with_context(|| format!("Failed to remove {:?}", path))
+    }
+
+    /// Like `remove`, for files holding key material: the file is overwritten with zeros
+    /// before it is unlinked, and its backup once the transaction ends.
+    pub(crate) fn remove_wiped(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        self.record(path, true)?;
+        wipe_file(path)
+    }
+
+    /// Like `write`, for files holding key material: the previous content is overwritten
+    /// with zeros before the new file takes its place, and its backup once the transaction
+    /// ends.
+    pub(crate) fn write_wiped(&mut self, path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+        self.record(path, true)?;
+        wipe_file(path)?;
+        write_atomically(path, content.as_ref())
+    }
+
+    /// Keeps all changes and drops the backups.
+    pub(crate) fn commit(mut self) -> Result<()> {
+        self.journal.committed = true;
+        self.save()?;
+        self.finished = true;
+        clear_dir(&self.dir, &self.journal)
+    }
+
+    // 某文件第一次改动前备份原内容，并先把日志写入磁盘
+    fn record(&mut self, path: &Path, wipe: bool) -> Result<()> {
+        if let Some(change) = self
+            .journal
+            .changes
+            .iter_mut()
+            .find(|change| change.path == path)
+        {
+            if wipe && !change.wipe {
+                change.wipe = true;
+                return self.save();
+            }
+            return Ok(());
+        }
+        let backup = if path.exists() {
+            let name = format!("{}.bak", self.journal.changes.len());
+            let content = fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+            write_atomically(&self.dir.join(&name), &content)?;
+            Some(name)
+        } else {
+            None
+        };
+        self.journal.changes.push(JournalChange {
+            path: path.to_path_buf(),
+            backup,
+            wipe,
+        });
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let content =
+            serde_json::to_vec_pretty(&self.journal).context("Failed to serialize journal")?;
+        write_atomically(&self.dir.join(JOURNAL_FILENAME), &content)
+    }
+}
+
+impl Drop for StoreTransaction {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        log::warn!(
+            "Rolling back unfinished store operation '{}'",
+            self.journal.operation
+        );
+        if let Err(e) = roll_back(&self.dir, &self.journal) {
+            log::error!(
+                "Failed to roll back store operation '{}' (retried at next start): {:?}",
+                self.journal.operation,
+                e
+            );
+        }
+    }
+}
+
+// --- Helper Functions ---
+
+/// Rolls back store transactions interrupted by a crash and clears committed ones (run at
+/// startup). Returns the number of transactions rolled back.
+pub(crate) fn recover_store_transactions(app_handle: &tauri::AppHandle) -> Result<usize> {
+    let root = get_app_data_file(app_handle, JOURNAL_DIRNAME)?;
+    if !root.exists() {
+        return Ok(0);
+    }
+    let mut rolled_back = 0;
+    for entry in fs::read_dir(&root).with_context(|| format!("Failed to read {:?}", root))? {
+        let dir = entry?.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        // 日志不存在或不完整：第一次写入前就中断了，尚未改动任何文件
+        let journal = fs::read(dir.join(JOURNAL_FILENAME))
+            .ok()
+            .and_then(|content| serde_json::from_slice::<Journal>(&content).ok());
+        match journal {
+            Some(journal) if !journal.committed => {
+                log::warn!(
+                    "Rolling back store operation '{}' interrupted at {}",
+                    journal.operation,
+                    journal.started_at
+                );
+                roll_back(&dir, &journal)?;
+                rolled_back += 1;
+            }
+            Some(journal) => clear_dir(&dir, &journal)?,
+            None => {
+                fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {:?}", dir))?
+            }
+        }
+    }
+    Ok(rolled_back)
+}
+
+// 逆序恢复备份、删除新建的文件，最后删除事务目录
+fn roll_back(dir: &Path, journal: &Journal) -> Result<()> {
+    for change in journal.changes.iter().rev() {
+        match &change.backup {
+            Some(name) => {
+                let backup = dir.join(name);
+                let content =
+                    fs::read(&backup).with_context(|| format!("Failed to read {:?}", backup))?;
+                write_atomically(&change.path, &content)?;
+            }
+            None if change.path.exists() => fs::remove_file(&change.path)
+                .with_context(|| format!("Failed to remove {:?}", change.path))?,
+            None => {}
+        }
+    }
+    clear_dir(dir, journal)
+}
+
+// 擦除含密钥材料的备份后删除事务目录
+fn clear_dir(dir: &Path, journal: &Journal) -> Result<()> {
+    for change in journal.changes.iter().filter(|change| change.wipe) {
+        if let Some(name) = &change.backup {
+            wipe_file(&dir.join(name))?;
+        }
+    }
+    fs::remove_dir_all(dir).with_context(|| format!("Failed to remove {:?}", dir))
+}
+
+// 先写临时文件并落盘，再改名替换，避免留下写了一半的文件
+fn write_atomically(path: &Path, content: &[u8]) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    let mut file =
+        File::create(&tmp_path).with_context(|| format!("Failed to write {:?}", tmp_path))?;
+    file.write_all(content)
+        .and_then(|_| file.sync_all())
+        .with_context(|| format!("Failed to write {:?}", tmp_path))?;
+    fs::rename(&tmp_path, path).with_context(|| format!("Failed to replace {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_back_changes_in_reverse_order() {
+        let root = std::env::temp_dir().join(format!("sigust-journal-{}", Uuid::new_v4()));
+        let dir = root.join("tx");
+        fs::create_dir_all(&dir).unwrap();
+        let existing = root.join("keys_metadata.json");
+        let created = root.join("new.pub.pem");
+        let private = root.join("old.key.enc");
+        fs::write(&existing, "old").unwrap();
+        fs::write(&private, "private key").unwrap();
+
+        let mut transaction = StoreTransaction {
+            dir: dir.clone(),
+            journal: Journal {
+                operation: "test".into(),
+                started_at: Utc::now(),
+                committed: false,
+                changes: Vec::new(),
+            },
+            finished: false,
+        };
+        transaction.write(&created, "public key").unwrap();
+        transaction.write(&existing, "new").unwrap();
+        transaction.write(&existing, "newer").unwrap();
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "newer");
+        transaction.remove_wiped(&private).unwrap();
+        assert!(!private.exists());
+        drop(transaction);
+
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "old");
+        assert!(!created.exists());
+        assert_eq!(fs::read_to_string(&private).unwrap(), "private key");
+        assert!(!dir.exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn wiped_writes_replace_key_files_and_roll_back() {
+        let root = std::env::temp_dir().join(format!("sigust-journal-{}", Uuid::new_v4()));
+        let private = root.join("key.key.enc");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(&private, "old password").unwrap();
+        let transaction = |name: &str| StoreTransaction {
+            dir: root.join(name),
+            journal: Journal {
+                operation: "test".into(),
+                started_at: Utc::now(),
+                committed: false,
+                changes: Vec::new(),
+            },
+            finished: false,
+        };
+
+        let mut rolled_back = transaction("tx1");
+        fs::create_dir_all(root.join("tx1")).unwrap();
+        rolled_back.write_wiped(&private, "new password").unwrap();
+        assert!(rolled_back.journal.changes[0].wipe);
+        drop(rolled_back);
+        assert_eq!(fs::read_to_string(&private).unwrap(), "old password");
+
+        let mut committed = transaction("tx2");
+        fs::create_dir_all(root.join("tx2")).unwrap();
+        committed.write_wiped(&private, "new password").unwrap();
+        committed.commit().unwrap();
+        assert_eq!(fs::read_to_string(&private).unwrap(), "new password");
+        assert!(!root.join("tx2").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+// src-tauri/src/store_lock.rs
+// Locks the store when the machine goes to sleep or the user's session is locked: all key
+// sessions end, pending security key secrets and biometric confirmations are dropped, and
+// `store://locked` is emitted so the UI returns to its locked state.
+//
+// Two detectors run in the background. On Linux the login manager and the screensaver are
+// watched over D-Bus (`gdbus monitor`), which reports sleep before it happens and session
+// locks as they happen. On every platform a clock check notices a resume afterwards: the
+// monotonic clock stops while the machine sleeps, the wall clock does not.
+use crate::audit;
+use crate::crypto_types::{StoreLockReason, StoreLocked};
+use crate::key_sessions::{end_all_key_sessions, KeySessions};
+use crate::token_unwrap::discard_token_secrets;
+use crate::unlock_policy::discard_unlock_confirmations;
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::json;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{Emitter, Manager};
+
+/// Event emitted after the store was locked.
+pub const STORE_LOCKED_EVENT: &str = "store://locked";
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// A wall clock running ahead of the monotonic clock by more than this means the machine slept.
+const SUSPEND_GAP: Duration = Duration::from_secs(30);
+
+/// Starts the suspend and session lock detectors.
+pub fn start_lock_monitor(app_handle: &tauri::AppHandle) {
+    let clock_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        let mut last_instant = Instant::now();
+        let mut last_wall = SystemTime::now();
+        loop {
+            std::thread::sleep(CLOCK_CHECK_INTERVAL);
+            let (instant, wall) = (Instant::now(), SystemTime::now());
+            if slept(last_instant, last_wall, instant, wall) {
+                log::info!("System resumed from sleep");
+                lock_store(&clock_handle, StoreLockReason::Suspend);
+            }
+            (last_instant, last_wall) = (instant, wall);
+        }
+    });
+
+    #[cfg(target_os = "linux")]
+    {
+        watch_dbus(app_handle, "--system", "org.freedesktop.login1");
+        watch_dbus(app_handle, "--session", "org.freedesktop.ScreenSaver");
+        watch_dbus(app_handle, "--session", "org.gnome.ScreenSaver");
+    }
+}
+
+/// Ends all key sessions and drops cached unlock material, then tells the UI.
+pub(crate) fn lock_store(app_handle: &tauri::AppHandle, reason: StoreLockReason) {
+    log::info!("Locking the store: {:?}", reason);
+    if let Err(e) = discard_unlocked_material(app_handle) {
+        log::error!("Failed to lock the store: {:?}", e);
+    }
+    let event = StoreLocked {
+        reason,
+        locked_at: Utc::now(),
+    };
+    if let Err(e) = app_handle.emit(STORE_LOCKED_EVENT, &event) {
+        log::warn!("Failed to emit store lock: {}", e);
+    }
+    audit::record(app_handle, "lockStore", json!({ "reason": reason }));
+}
+
+// --- Helper Functions ---
+
+// 清除会话中的私钥、安全密钥秘密和生物识别确认
+fn discard_unlocked_material(app_handle: &tauri::AppHandle) -> Result<()> {
+    end_all_key_sessions(app_handle, &app_handle.state::<KeySessions>())?;
+    discard_token_secrets()?;
+    discard_unlock_confirmations()
+}
+
+// 墙上时间比单调时钟多走了很多：期间机器处于睡眠
+fn slept(last_instant: Instant, last_wall: SystemTime, instant: Instant, wall: SystemTime) -> bool {
+    let monotonic = instant.duration_since(last_instant);
+    wall.duration_since(last_wall)
+        .is_ok_and(|elapsed| elapsed > monotonic + SUSPEND_GAP)
+}
+
+/// Maps a line of `gdbus monitor` output to the lock it announces.
+#[cfg(any(target_os = "linux", test))]
+fn lock_signal(line: &str) -> Option<StoreLockReason> {
+    if line.contains(".PrepareForSleep (true,)") {
+        Some(StoreLockReason::Suspend)
+    } else if line.contains("org.freedesktop.login1.Session.Lock ()") {
+        Some(StoreLockReason::SessionLock)
+    } else if line.contains("ScreenSaver.ActiveChanged (true,)") {
+        Some(StoreLockReason::Screensaver)
+    } else {
+        None
+    }
+}
+
+// 通过 gdbus 监听登录管理器/屏保信号；没有 gdbus 或总线时只靠时钟检测
+#[cfg(target_os = "linux")]
+fn watch_dbus(app_handle: &tauri::AppHandle, bus: &'static str, destination: &'static str) {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        let mut child = match Command::new("gdbus")
+            .args(["monitor", bus, "--dest", destination])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                log::warn!("Cannot watch {} for session locks: {}", destination, e);
+                return;
+            }
+        };
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+                if let Some(reason) = lock_signal(&line) {
+                    lock_store(&app_handle, reason);
+                }
+            }
+        }
+        let _ = child.wait();
+        log::debug!("Stopped watching {} for session locks", destination);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_lock_signals_and_sleep() {
+        assert_eq!(
+            lock_signal(
+                "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (true,)"
+            ),
+            Some(StoreLockReason::Suspend)
+        );
+        assert_eq!(
+            lock_signal(
+                "/org/freedesktop/login1/session/_32: org.freedesktop.login1.Session.Lock ()"
+            ),
+            Some(StoreLockReason::SessionLock)
+        );
+        assert_eq!(
+            lock_signal("/org/gnome/ScreenSaver: org.gnome.ScreenSaver.ActiveChanged (true,)"),
+            Some(StoreLockReason::Screensaver)
+        );
+        assert_eq!(
+            lock_signal(
+                "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (false,)"
+            ),
+            None
+        );
+
+        let (instant, wall) = (Instant::now(), SystemTime::now());
+        let later = instant + CLOCK_CHECK_INTERVAL;
+        assert!(!slept(instant, wall, later, wall + CLOCK_CHECK_INTERVAL));
+        assert!(slept(
+            instant,
+            wall,
+            later,
+            wall + Duration::from_secs(3600)
+        ));
+    }
+}
@@ -0,0 +1,569 @@
+// src-tauri/src/sync.rs
+// Opt-in key store sync: each device writes end-to-end encrypted delta files into a
+// user-chosen shared folder and merges the deltas written by other devices.
+//
+// A key deleted on another device is only moved to the trash here, so a damaged or
+// tampered sync file cannot destroy keys. Folders, unlock policies and the trash are
+// per device and never taken from a sync file.
+use crate::crypto_types::{KeyMetadata, SyncConfig, SyncConflict, SyncConflictPolicy, SyncReport};
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, decrypt_data, encrypt_data, get_app_data_file,
+    get_key_storage_dir, get_metadata_path, metadata_json, read_metadata, rsa_key_bits, SALT_LEN,
+};
+use crate::policy::{check_private_key_export, check_private_keys_allowed};
+use crate::sshsig::optional_ssh_fingerprint;
+use crate::store_journal::StoreTransaction;
+use aead::OsRng;
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use rsa::rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const SYNC_STATE_FILENAME: &str = "sync_state.json";
+const SYNC_FILE_MAGIC: &[u8] = b"SIGUSTSYNC\x01";
+const SYNC_FILE_PREFIX: &str = "sigust-sync-";
+const SYNC_FILE_EXTENSION: &str = "sync";
+
+/// Persistent sync state: user configuration plus the bookkeeping needed to compute deltas.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct SyncState {
+    config: SyncConfig,
+    /// Content hash of each key as of the last export/import (the common ancestor).
+    baseline: HashMap<Uuid, String>,
+    /// Names of remote delta files already merged into the local store.
+    applied_files: Vec<String>,
+}
+
+/// Contents of one encrypted delta file.
+#[derive(Serialize, Deserialize)]
+struct SyncDelta {
+    device_id: Uuid,
+    created_at: DateTime<Utc>,
+    entries: Vec<SyncEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SyncEntry {
+    key_id: Uuid,
+    /// Content hash of the entry (for upserts) or of the last known version (for deletions).
+    hash: String,
+    change: SyncChange,
+}
+
+#[derive(Serialize, Deserialize)]
+enum SyncChange {
+    Upsert(Box<SyncedKey>),
+    Delete,
+}
+
+/// A complete key entry as transferred between devices. The private key stays
+/// encrypted with its own password; the delta file adds a second layer with the sync password.
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct SyncedKey {
+    pub(crate) metadata: KeyMetadata,
+    pub(crate) public_key_pem: String,
+    pub(crate) encrypted_private_key_b64: String,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_sync_config(app_handle: tauri::AppHandle) -> Result<SyncConfig, String> {
+    log::info!("Reading sync configuration");
+    load_sync_state(&app_handle)
+        .map(|state| state.config)
+        .map_err(|e| {
+            log::error!("Failed to read sync configuration: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn configure_sync(
+    app_handle: tauri::AppHandle,
+    enabled: bool,
+    folder: Option<String>,
+) -> Result<SyncConfig, String> {
+    log::info!("Configuring sync: enabled={}, folder={:?}", enabled, folder);
+    _configure_sync(&app_handle, enabled, folder).map_err(|e| {
+        log::error!("Failed to configure sync: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _configure_sync(
+    app_handle: &tauri::AppHandle,
+    enabled: bool,
+    folder: Option<String>,
+) -> Result<SyncConfig> {
+    if enabled {
+        let folder = folder
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("A sync folder is required to enable sync"))?;
+        if !Path::new(folder).is_dir() {
+            bail!(
+                "Sync folder does not exist or is not a directory: {}",
+                folder
+            );
+        }
+    }
+    let mut state = load_sync_state(app_handle)?;
+    if state.config.folder != folder {
+        // A different folder holds a different set of deltas.
+        state.applied_files.clear();
+    }
+    state.config.enabled = enabled;
+    state.config.folder = folder;
+    save_sync_state(app_handle, &state)?;
+    Ok(state.config)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_sync_delta(
+    app_handle: tauri::AppHandle,
+    sync_password: String,
+) -> Result<SyncReport, String> {
+    log::info!("Exporting key store sync delta");
+    if sync_password.is_empty() {
+        return Err("Sync password cannot be empty.".to_string());
+    }
+    _export_sync_delta(&app_handle, &sync_password).map_err(|e| {
+        log::error!("Failed to export sync delta: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _export_sync_delta(app_handle: &tauri::AppHandle, sync_password: &str) -> Result<SyncReport> {
+    check_private_key_export()?;
+    let mut state = load_sync_state(app_handle)?;
+    let folder = enabled_sync_folder(&state.config)?;
+
+    let local = read_local_keys(app_handle)?;
+    let mut report = SyncReport::default();
+    let mut entries = Vec::new();
+
+    for (key_id, (hash, key)) in &local {
+        if state.baseline.get(key_id) != Some(hash) {
+            entries.push(SyncEntry {
+                key_id: *key_id,
+                hash: hash.clone(),
+                change: SyncChange::Upsert(Box::new(key.clone())),
+            });
+            report.added.push(*key_id);
+        }
+    }
+    for (key_id, hash) in &state.baseline {
+        if !local.contains_key(key_id) {
+            entries.push(SyncEntry {
+                key_id: *key_id,
+                hash: hash.clone(),
+                change: SyncChange::Delete,
+            });
+            report.deleted.push(*key_id);
+        }
+    }
+
+    if entries.is_empty() {
+        log::info!("No local changes to export");
+        return Ok(report);
+    }
+
+    let now = Utc::now();
+    let delta = SyncDelta {
+        device_id: state.config.device_id,
+        created_at: now,
+        entries,
+    };
+    let file_name = format!(
+        "{}{}-{}.{}",
+        SYNC_FILE_PREFIX,
+        state.config.device_id,
+        now.format("%Y%m%dT%H%M%S%3fZ"),
+        SYNC_FILE_EXTENSION
+    );
+    let file_path = folder.join(&file_name);
+    write_delta_file(&file_path, &delta, sync_password)?;
+
+    state.baseline = local
+        .into_iter()
+        .map(|(id, (hash, _))| (id, hash))
+        .collect();
+    state.applied_files.push(file_name.clone());
+    state.config.last_exported_at = Some(now);
+    save_sync_state(app_handle, &state)?;
+
+    log::info!(
+        "Exported {} changed and {} deleted keys to {:?}",
+        report.added.len(),
+        report.deleted.len(),
+        file_path
+    );
+    report.files.push(file_name);
+    Ok(report)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn import_sync_changes(
+    app_handle: tauri::AppHandle,
+    sync_password: String,
+    conflict_policy: SyncConflictPolicy,
+) -> Result<SyncReport, String> {
+    log::info!(
+        "Importing key store changes from other devices (conflict policy: {:?})",
+        conflict_policy
+    );
+    if sync_password.is_empty() {
+        return Err("Sync password cannot be empty.".to_string());
+    }
+    _import_sync_changes(&app_handle, &sync_password, conflict_policy).map_err(|e| {
+        log::error!("Failed to import sync changes: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _import_sync_changes(
+    app_handle: &tauri::AppHandle,
+    sync_password: &str,
+    conflict_policy: SyncConflictPolicy,
+) -> Result<SyncReport> {
+    check_private_keys_allowed()?;
+    let mut state = load_sync_state(app_handle)?;
+    let folder = enabled_sync_folder(&state.config)?;
+    let own_prefix = format!("{}{}-", SYNC_FILE_PREFIX, state.config.device_id);
+
+    // Collect unapplied delta files from other devices, oldest first (names embed the timestamp,
+    // but devices differ, so order by the timestamp recorded inside each file).
+    let mut deltas = Vec::new();
+    for entry in fs::read_dir(&folder).context("Failed to read sync folder")? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if !file_name.starts_with(SYNC_FILE_PREFIX)
+            || file_name.starts_with(&own_prefix)
+            || path.extension().and_then(|e| e.to_str()) != Some(SYNC_FILE_EXTENSION)
+            || state.applied_files.iter().any(|f| f == file_name)
+        {
+            continue;
+        }
+        let delta = read_delta_file(&path, sync_password)
+            .with_context(|| format!("Failed to read sync file {}", file_name))?;
+        deltas.push((file_name.to_string(), delta));
+    }
+    deltas.sort_by_key(|(_, delta)| delta.created_at);
+
+    let metadata_path = get_metadata_path(app_handle)?;
+    let key_storage_dir = get_key_storage_dir(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+    let mut report = SyncReport::default();
+
+    for (file_name, delta) in deltas {
+        let local = read_local_keys(app_handle)?;
+        // 一个同步文件的改动在同一事务中写入
+        let mut transaction = StoreTransaction::begin(app_handle, "importSyncChanges")?;
+        for entry in delta.entries {
+            let local_entry = local.get(&entry.key_id);
+            let local_hash = local_entry.map(|(hash, _)| hash);
+            let locally_unchanged = local_hash == state.baseline.get(&entry.key_id);
+
+            match entry.change {
+                SyncChange::Upsert(remote) => {
+                    if local_hash == Some(&entry.hash) {
+                        state.baseline.insert(entry.key_id, entry.hash);
+                        continue;
+                    }
+                    let local_name = local_entry.map(|(_, k)| k.metadata.name.clone());
+                    match local_name {
+                        None => {
+                            store_synced_key(
+                                &mut transaction,
+                                &key_storage_dir,
+                                &mut all_metadata,
+                                &remote,
+                                None,
+                            )?;
+                            report.added.push(entry.key_id);
+                        }
+                        Some(_) if locally_unchanged => {
+                            store_synced_key(
+                                &mut transaction,
+                                &key_storage_dir,
+                                &mut all_metadata,
+                                &remote,
+                                None,
+                            )?;
+                            report.updated.push(entry.key_id);
+                        }
+                        Some(local_name) => {
+                            let resolution = match conflict_policy {
+                                SyncConflictPolicy::PreferLocal => "Kept local version".to_string(),
+                                SyncConflictPolicy::PreferRemote => {
+                                    store_synced_key(
+                                        &mut transaction,
+                                        &key_storage_dir,
+                                        &mut all_metadata,
+                                        &remote,
+                                        None,
+                                    )?;
+                                    report.updated.push(entry.key_id);
+                                    "Replaced with remote version".to_string()
+                                }
+                                SyncConflictPolicy::KeepBoth => {
+                                    let new_id = Uuid::new_v4();
+                                    store_synced_key(
+                                        &mut transaction,
+                                        &key_storage_dir,
+                                        &mut all_metadata,
+                                        &remote,
+                                        Some(new_id),
+                                    )?;
+                                    report.added.push(new_id);
+                                    format!("Imported remote version as key {}", new_id)
+                                }
+                            };
+                            report.conflicts.push(SyncConflict {
+                                key_id: entry.key_id,
+                                local_name,
+                                source_file: file_name.clone(),
+                                resolution,
+                            });
+                        }
+                    }
+                    state.baseline.insert(entry.key_id, entry.hash);
+                }
+                SyncChange::Delete => {
+                    let Some((_, local_key)) = local_entry else {
+                        state.baseline.remove(&entry.key_id);
+                        continue;
+                    };
+                    let delete = locally_unchanged
+                        || local_hash == Some(&entry.hash)
+                        || conflict_policy == SyncConflictPolicy::PreferRemote;
+                    if delete {
+                        // 移入回收站而不是直接删除，由用户决定是否清空
+                        if let Some(metadata) = all_metadata
+                            .iter_mut()
+                            .find(|m| m.key_id == entry.key_id && m.trashed_at.is_none())
+                        {
+                            metadata.trashed_at = Some(Utc::now());
+                        }
+                        state.baseline.remove(&entry.key_id);
+                        report.deleted.push(entry.key_id);
+                    } else {
+                        // Modified locally after the remote deletion: never silently drop a key.
+                        report.conflicts.push(SyncConflict {
+                            key_id: entry.key_id,
+                            local_name: local_key.metadata.name.clone(),
+                            source_file: file_name.clone(),
+                            resolution: "Kept local version modified after remote deletion"
+                                .to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        transaction.write(&metadata_path, metadata_json(&all_metadata)?)?;
+        transaction.commit()?;
+        state.applied_files.push(file_name.clone());
+        report.files.push(file_name);
+        save_sync_state(app_handle, &state)?;
+    }
+
+    log::info!(
+        "Sync import complete: {} added, {} updated, {} deleted, {} conflicts",
+        report.added.len(),
+        report.updated.len(),
+        report.deleted.len(),
+        report.conflicts.len()
+    );
+    Ok(report)
+}
+
+// --- Helper Functions ---
+
+// 读取同步状态（首次读取时生成设备 ID）
+fn load_sync_state(app_handle: &tauri::AppHandle) -> Result<SyncState> {
+    let path = get_app_data_file(app_handle, SYNC_STATE_FILENAME)?;
+    if !path.exists() {
+        let state = SyncState {
+            config: SyncConfig {
+                device_id: Uuid::new_v4(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        save_sync_state(app_handle, &state)?;
+        return Ok(state);
+    }
+    let content = fs::read_to_string(&path).context("Failed to read sync state file")?;
+    serde_json::from_str(&content).context("Failed to parse sync state JSON")
+}
+
+// 写入同步状态
+fn save_sync_state(app_handle: &tauri::AppHandle, state: &SyncState) -> Result<()> {
+    let path = get_app_data_file(app_handle, SYNC_STATE_FILENAME)?;
+    let content =
+        serde_json::to_string_pretty(state).context("Failed to serialize sync state to JSON")?;
+    fs::write(path, content).context("Failed to write sync state file")
+}
+
+// 检查同步是否已启用并返回同步目录
+fn enabled_sync_folder(config: &SyncConfig) -> Result<PathBuf> {
+    if !config.enabled {
+        bail!("Sync is not enabled");
+    }
+    let folder = config
+        .folder
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("No sync folder configured"))?;
+    let folder = PathBuf::from(folder);
+    if !folder.is_dir() {
+        bail!("Sync folder is not available: {:?}", folder);
+    }
+    Ok(folder)
+}
+
+// 读取本地所有密钥及其内容哈希
+pub(crate) fn read_local_keys(
+    app_handle: &tauri::AppHandle,
+) -> Result<HashMap<Uuid, (String, SyncedKey)>> {
+    let metadata_path = get_metadata_path(app_handle)?;
+    let key_storage_dir = get_key_storage_dir(app_handle)?;
+    let mut keys = HashMap::new();
+    for mut metadata in read_metadata(&metadata_path)? {
+        // 绑定设备的密钥在其他设备上无法解密，不参与同步
+        // 仅含公钥的条目没有私钥文件，也不参与同步
+        if metadata.device_bound || !metadata.kind.is_key_pair() {
+            continue;
+        }
+        let public_key_path = key_storage_dir.join(&metadata.public_key_pem_path);
+        let private_key_path = key_storage_dir.join(&metadata.encrypted_private_key_path);
+        let public_key_pem = fs::read_to_string(&public_key_path)
+            .with_context(|| format!("Failed to read public key file: {:?}", public_key_path))?;
+        let encrypted_private_key = fs::read(&private_key_path).with_context(|| {
+            format!(
+                "Failed to read encrypted private key file: {:?}",
+                private_key_path
+            )
+        })?;
+        clear_device_fields(&mut metadata);
+        let key = SyncedKey {
+            metadata,
+            public_key_pem,
+            encrypted_private_key_b64: BASE64.encode(encrypted_private_key),
+        };
+        keys.insert(key.metadata.key_id, (hash_synced_key(&key)?, key));
+    }
+    Ok(keys)
+}
+
+// 计算同步条目的内容哈希
+pub(crate) fn hash_synced_key(key: &SyncedKey) -> Result<String> {
+    let bytes = serde_json::to_vec(key).context("Failed to serialize key for hashing")?;
+    Ok(hex::encode(Sha256::digest(bytes)))
+}
+
+// 将远端条目写入本地存储（可选使用新的 key_id）
+pub(crate) fn store_synced_key(
+    transaction: &mut StoreTransaction,
+    key_storage_dir: &Path,
+    all_metadata: &mut Vec<KeyMetadata>,
+    remote: &SyncedKey,
+    new_key_id: Option<Uuid>,
+) -> Result<()> {
+    let mut metadata = remote.metadata.clone();
+    if let Some(new_key_id) = new_key_id {
+        metadata.key_id = new_key_id;
+        metadata.name = format!("{} (synced copy)", metadata.name);
+    }
+    // Never trust file paths from another device; derive them from the key ID.
+    metadata.public_key_pem_path = format!("{}.pub.pem", metadata.key_id);
+    metadata.encrypted_private_key_path = format!("{}.key.enc", metadata.key_id);
+    // The escrow copy is not synced, linked keys and passkeys are IDs of the other device,
+    // and folders, unlock policies and the trash are per device; keep what this device has
+    // for the key, if anything.
+    clear_device_fields(&mut metadata);
+    if let Some(local) = all_metadata.iter().find(|m| m.key_id == metadata.key_id) {
+        metadata.escrow_path = local.escrow_path.clone();
+        metadata.approver_key_id = local.approver_key_id;
+        metadata.pq_key_id = local.pq_key_id;
+        metadata.token_passkey_id = local.token_passkey_id;
+        metadata.folder_id = local.folder_id;
+        metadata.unlock_policy = local.unlock_policy.clone();
+        metadata.trashed_at = local.trashed_at;
+    }
+    let public_key_der = decode_public_key_pem(&remote.public_key_pem)?;
+    metadata.fingerprint = Some(compute_fingerprint(&public_key_der));
+    metadata.ssh_fingerprint = optional_ssh_fingerprint(&metadata.algorithm, &public_key_der)?;
+    metadata.key_bits = rsa_key_bits(&metadata.algorithm, &public_key_der);
+
+    let encrypted_private_key = BASE64
+        .decode(&remote.encrypted_private_key_b64)
+        .context("Failed to decode synced private key")?;
+    transaction
+        .write(
+            &key_storage_dir.join(&metadata.public_key_pem_path),
+            &remote.public_key_pem,
+        )
+        .context("Failed to write synced public key")?;
+    // 可能替换本地用旧口令加密的私钥文件，擦除它及其备份
+    transaction
+        .write_wiped(
+            &key_storage_dir.join(&metadata.encrypted_private_key_path),
+            encrypted_private_key,
+        )
+        .context("Failed to write synced private key")?;
+
+    all_metadata.retain(|m| m.key_id != metadata.key_id);
+    all_metadata.push(metadata);
+    Ok(())
+}
+
+// 清除只在本设备有意义的字段：托管文件路径，指向本设备其他密钥、口令密钥的 ID，
+// 以及文件夹、解锁策略和回收站状态
+fn clear_device_fields(metadata: &mut KeyMetadata) {
+    metadata.escrow_path = None;
+    metadata.approver_key_id = None;
+    metadata.pq_key_id = None;
+    metadata.token_passkey_id = None;
+    metadata.folder_id = None;
+    metadata.unlock_policy = None;
+    metadata.trashed_at = None;
+}
+
+// 加密并写入同步文件：magic || salt || AES-GCM(JSON) || nonce
+fn write_delta_file(path: &Path, delta: &SyncDelta, sync_password: &str) -> Result<()> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut data = serde_json::to_vec(delta).context("Failed to serialize sync delta")?;
+    encrypt_data(&mut data, sync_password, &salt)?;
+
+    let mut content = Vec::with_capacity(SYNC_FILE_MAGIC.len() + SALT_LEN + data.len());
+    content.extend_from_slice(SYNC_FILE_MAGIC);
+    content.extend_from_slice(&salt);
+    content.extend_from_slice(&data);
+    fs::write(path, content).with_context(|| format!("Failed to write sync file {:?}", path))
+}
+
+// 读取并解密同步文件
+fn read_delta_file(path: &Path, sync_password: &str) -> Result<SyncDelta> {
+    let content = fs::read(path)?;
+    let Some(rest) = content.strip_prefix(SYNC_FILE_MAGIC) else {
+        bail!("Not a sigust sync file");
+    };
+    if rest.len() < SALT_LEN {
+        bail!("Sync file is truncated");
+    }
+    let (salt, data) = rest.split_at(SALT_LEN);
+    let mut data = data.to_vec();
+    decrypt_data(&mut data, sync_password, salt)
+        .context("Failed to decrypt (check sync password)")?;
+    serde_json::from_slice(&data).context("Failed to parse sync delta")
+}
@@ -0,0 +1,483 @@
+// src-tauri/src/tasks.rs
+// Background task subsystem for long operations. Commands that may run for a while start a
+// task and return its ID immediately; the frontend polls `get_task_status` or listens for
+// `task-progress` events carrying the same `TaskStatus` snapshot. When a task finishes
+// while no window has focus, a system notification reports the outcome.
+//
+// A watchdog thread looks at running tasks every few seconds. A task that reports nothing
+// for a while is flagged as stalled (typically a read from an unresponsive network drive);
+// one that stays silent past its kind's timeout is ended as timed out, asked to stop, and
+// the output files it had not finished writing are removed. The frontend gets the final
+// status right away, even if the worker thread itself is still stuck.
+use crate::crypto_types::{TaskState, TaskStatus};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use uuid::Uuid;
+
+/// Event emitted whenever a task's status changes.
+pub const TASK_PROGRESS_EVENT: &str = "task-progress";
+/// Finished tasks kept for status polling before the oldest are dropped.
+const MAX_FINISHED_TASKS: usize = 100;
+const WATCHDOG_INTERVAL_SECONDS: u64 = 5;
+/// A running task that has not reported anything for this long is flagged as stalled.
+const STALLED_AFTER_SECONDS: i64 = 30;
+/// Default time a task may go without reporting anything before it is timed out.
+const DEFAULT_IDLE_TIMEOUT_SECONDS: i64 = 10 * 60;
+
+struct TaskEntry {
+    status: TaskStatus,
+    cancelled: Arc<AtomicBool>,
+    /// Output files being written and not finished; removed when the task ends.
+    partial_outputs: Vec<PathBuf>,
+}
+
+type TaskTable = Arc<Mutex<HashMap<Uuid, TaskEntry>>>;
+
+/// Registry of background tasks started during this session.
+#[derive(Default)]
+pub struct TaskManager {
+    tasks: TaskTable,
+}
+
+/// Passed to a running task to report progress and check for cancellation.
+pub struct TaskHandle {
+    app_handle: tauri::AppHandle,
+    task_id: Uuid,
+    tasks: TaskTable,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TaskHandle {
+    /// Records progress as `done` out of `total` units, with an optional step description.
+    pub fn progress(&self, done: u64, total: Option<u64>, message: Option<String>) {
+        self.update(|status| {
+            status.done = done;
+            if total.is_some() {
+                status.total = total;
+            }
+            status.message = message;
+        });
+    }
+
+    /// Appends a per-item result that stays available even if the task later fails.
+    pub fn partial_result(&self, value: impl Serialize) {
+        match serde_json::to_value(value) {
+            Ok(value) => self.update(|status| status.partial_results.push(value)),
+            Err(e) => log::warn!("Dropping unserializable partial result: {}", e),
+        }
+    }
+
+    /// Whether `cancel_task` was called; long loops should check this between items.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Records that the task is writing `path`. If `output_written` is not called for it
+    /// before the task ends (it fails, is cancelled or times out, or the write failed), the
+    /// file is removed.
+    pub fn output_started(&self, path: &Path) {
+        if let Ok(mut tasks) = lock_tasks(&self.tasks) {
+            if let Some(entry) = tasks.get_mut(&self.task_id) {
+                entry.partial_outputs.push(path.to_path_buf());
+            }
+        }
+    }
+
+    /// Marks an output file started with `output_started` as complete.
+    pub fn output_written(&self, path: &Path) {
+        if let Ok(mut tasks) = lock_tasks(&self.tasks) {
+            if let Some(entry) = tasks.get_mut(&self.task_id) {
+                entry.partial_outputs.retain(|p| p != path);
+            }
+        }
+    }
+
+    /// Returns an error if the task was cancelled, for use with `?` inside loops.
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(anyhow!("Task was cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn snapshot(&self) -> Option<TaskStatus> {
+        lock_tasks(&self.tasks)
+            .ok()
+            .and_then(|tasks| tasks.get(&self.task_id).map(|entry| entry.status.clone()))
+    }
+
+    // 只更新运行中的任务：被看门狗超时结束的任务不再改变状态
+    fn update(&self, f: impl FnOnce(&mut TaskStatus)) {
+        let snapshot = match lock_tasks(&self.tasks) {
+            Ok(mut tasks) => tasks
+                .get_mut(&self.task_id)
+                .filter(|entry| entry.status.state == TaskState::Running)
+                .map(|entry| {
+                    f(&mut entry.status);
+                    entry.status.last_activity_at = Utc::now();
+                    entry.status.stalled = false;
+                    entry.status.clone()
+                }),
+            Err(e) => {
+                log::error!("{}", e);
+                None
+            }
+        };
+        if let Some(snapshot) = snapshot {
+            emit_status(&self.app_handle, &snapshot);
+        }
+    }
+}
+
+/// Starts `job` on a blocking worker thread and returns its task ID right away.
+/// The job's `Ok` value becomes the task result; an `Err` marks it failed (or cancelled).
+pub fn spawn_task<F, T>(app_handle: &tauri::AppHandle, kind: &str, job: F) -> Result<Uuid>
+where
+    F: FnOnce(&TaskHandle) -> Result<T> + Send + 'static,
+    T: Serialize,
+{
+    let manager = app_handle.state::<TaskManager>();
+    let task_id = Uuid::new_v4();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let status = TaskStatus {
+        task_id,
+        kind: kind.to_string(),
+        state: TaskState::Running,
+        done: 0,
+        total: None,
+        message: None,
+        error: None,
+        partial_results: Vec::new(),
+        result: None,
+        started_at: Utc::now(),
+        finished_at: None,
+        last_activity_at: Utc::now(),
+        stalled: false,
+        idle_timeout_seconds: idle_timeout(kind).num_seconds() as u64,
+    };
+    {
+        let mut tasks = lock_tasks(&manager.tasks)?;
+        prune_finished(&mut tasks);
+        tasks.insert(
+            task_id,
+            TaskEntry {
+                status: status.clone(),
+                cancelled: cancelled.clone(),
+                partial_outputs: Vec::new(),
+            },
+        );
+    }
+    emit_status(app_handle, &status);
+
+    let handle = TaskHandle {
+        app_handle: app_handle.clone(),
+        task_id,
+        tasks: manager.tasks.clone(),
+        cancelled,
+    };
+    log::info!("Started {} task {}", kind, task_id);
+    tauri::async_runtime::spawn_blocking(move || {
+        let outcome = job(&handle).and_then(|value| Ok(serde_json::to_value(value)?));
+        handle.update(|status| {
+            status.finished_at = Some(Utc::now());
+            match outcome {
+                Ok(value) => {
+                    status.state = TaskState::Completed;
+                    status.result = Some(value);
+                }
+                Err(_) if handle.is_cancelled() => status.state = TaskState::Cancelled,
+                Err(e) => {
+                    log::error!("Task {} ({}) failed: {:?}", status.task_id, status.kind, e);
+                    status.state = TaskState::Failed;
+                    status.error = Some(e.to_string());
+                }
+            }
+        });
+        let Some(status) = handle.snapshot() else {
+            return;
+        };
+        // 仍登记的输出文件都没写完，无论任务结果如何都删除
+        remove_partial_outputs(&handle.tasks, handle.task_id);
+        // 超时的任务已由看门狗通知过
+        if status.state != TaskState::TimedOut {
+            notify_finished(&handle.app_handle, &status);
+        }
+    });
+    Ok(task_id)
+}
+
+/// Starts the watchdog that flags stalled tasks and times out stuck ones.
+pub fn start_task_watchdog(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(WATCHDOG_INTERVAL_SECONDS));
+        let tasks = app_handle.state::<TaskManager>().tasks.clone();
+        for (status, timed_out) in check_running_tasks(&tasks, Utc::now()) {
+            emit_status(&app_handle, &status);
+            if timed_out {
+                log::warn!(
+                    "Task {} ({}) timed out: {}",
+                    status.task_id,
+                    status.kind,
+                    status.error.as_deref().unwrap_or_default()
+                );
+                remove_partial_outputs(&tasks, status.task_id);
+                notify_finished(&app_handle, &status);
+            }
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_task_status(
+    manager: tauri::State<'_, TaskManager>,
+    task_id: Uuid,
+) -> Result<TaskStatus, String> {
+    log::debug!("Fetching status of task {}", task_id);
+    lock_tasks(&manager.tasks)
+        .and_then(|tasks| {
+            tasks
+                .get(&task_id)
+                .map(|entry| entry.status.clone())
+                .ok_or_else(|| anyhow!("No task with ID {}", task_id))
+        })
+        .map_err(|e| {
+            log::error!("Failed to get status of task {}: {:?}", task_id, e);
+            e.to_string()
+        })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_tasks(manager: tauri::State<'_, TaskManager>) -> Result<Vec<TaskStatus>, String> {
+    log::info!("Listing background tasks");
+    lock_tasks(&manager.tasks)
+        .map(|tasks| {
+            let mut statuses: Vec<TaskStatus> =
+                tasks.values().map(|entry| entry.status.clone()).collect();
+            statuses.sort_by_key(|status| status.started_at);
+            statuses
+        })
+        .map_err(|e| {
+            log::error!("Failed to list tasks: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn cancel_task(manager: tauri::State<'_, TaskManager>, task_id: Uuid) -> Result<(), String> {
+    log::info!("Cancelling task {}", task_id);
+    lock_tasks(&manager.tasks)
+        .and_then(|tasks| {
+            let entry = tasks
+                .get(&task_id)
+                .ok_or_else(|| anyhow!("No task with ID {}", task_id))?;
+            if entry.status.state == TaskState::Running {
+                entry.cancelled.store(true, Ordering::Relaxed);
+            }
+            Ok(())
+        })
+        .map_err(|e| {
+            log::error!("Failed to cancel task {}: {:?}", task_id, e);
+            e.to_string()
+        })
+}
+
+// --- Helper Functions ---
+
+// 获取任务表的锁
+fn lock_tasks(tasks: &TaskTable) -> Result<MutexGuard<'_, HashMap<Uuid, TaskEntry>>> {
+    tasks
+        .lock()
+        .map_err(|_| anyhow!("Task table lock poisoned"))
+}
+
+// 各类任务允许的无进展时间；按块报告进度的任务可以更短
+fn idle_timeout(kind: &str) -> Duration {
+    match kind {
+        "verifySignature" => Duration::minutes(2),
+        _ => Duration::seconds(DEFAULT_IDLE_TIMEOUT_SECONDS),
+    }
+}
+
+// 检查运行中的任务：标记停滞，结束超时的任务。返回状态有变化的任务及其是否超时
+fn check_running_tasks(tasks: &TaskTable, now: DateTime<Utc>) -> Vec<(TaskStatus, bool)> {
+    let mut changed = Vec::new();
+    let Ok(mut tasks) = lock_tasks(tasks) else {
+        return changed;
+    };
+    for entry in tasks.values_mut() {
+        let status = &mut entry.status;
+        if status.state != TaskState::Running {
+            continue;
+        }
+        let idle = now - status.last_activity_at;
+        if idle >= Duration::seconds(status.idle_timeout_seconds as i64) {
+            entry.cancelled.store(true, Ordering::Relaxed);
+            status.state = TaskState::TimedOut;
+            status.stalled = false;
+            status.finished_at = Some(now);
+            status.error = Some(format!(
+                "No progress for {} seconds{}",
+                idle.num_seconds(),
+                status
+                    .message
+                    .as_ref()
+                    .map(|step| format!(" while processing '{}'", step))
+                    .unwrap_or_default()
+            ));
+            changed.push((status.clone(), true));
+        } else if idle >= Duration::seconds(STALLED_AFTER_SECONDS) && !status.stalled {
+            status.stalled = true;
+            changed.push((status.clone(), false));
+        }
+    }
+    changed
+}
+
+// 删除任务未写完的输出文件
+fn remove_partial_outputs(tasks: &TaskTable, task_id: Uuid) {
+    let paths = match lock_tasks(tasks) {
+        Ok(mut tasks) => tasks
+            .get_mut(&task_id)
+            .map(|entry| std::mem::take(&mut entry.partial_outputs))
+            .unwrap_or_default(),
+        Err(_) => return,
+    };
+    for path in paths {
+        match fs::remove_file(&path) {
+            Ok(()) => log::info!("Removed partial output {:?} of task {}", path, task_id),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("Failed to remove partial output {:?}: {}", path, e),
+        }
+    }
+}
+
+// 向前端推送任务状态
+fn emit_status(app_handle: &tauri::AppHandle, status: &TaskStatus) {
+    if let Err(e) = app_handle.emit(TASK_PROGRESS_EVENT, status) {
+        log::warn!("Failed to emit task progress for {}: {}", status.task_id, e);
+    }
+}
+
+// 窗口不在前台时，用系统通知告知任务结果（取消的任务由用户发起，不再提醒）
+fn notify_finished(app_handle: &tauri::AppHandle, status: &TaskStatus) {
+    let focused = app_handle
+        .webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false));
+    if focused {
+        return;
+    }
+    let title = match status.kind.as_str() {
+        "batchSign" => "Batch signing",
+        "hashDirectory" => "Directory hashing",
+        other => other,
+    };
+    let (title, body) = match status.state {
+        TaskState::Completed => (
+            format!("{} finished", title),
+            match status.total {
+                Some(total) => format!("Processed {} of {} items.", status.done, total),
+                None => "The operation completed.".to_string(),
+            },
+        ),
+        TaskState::Failed => (
+            format!("{} failed", title),
+            status.error.clone().unwrap_or_default(),
+        ),
+        TaskState::TimedOut => (
+            format!("{} timed out", title),
+            status.error.clone().unwrap_or_default(),
+        ),
+        TaskState::Running | TaskState::Cancelled => return,
+    };
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+    {
+        log::warn!("Failed to show notification for {}: {}", status.task_id, e);
+    }
+}
+
+// 已结束的任务超过上限时，丢弃最早结束的
+fn prune_finished(tasks: &mut HashMap<Uuid, TaskEntry>) {
+    let mut finished: Vec<_> = tasks
+        .values()
+        .filter_map(|entry| entry.status.finished_at.map(|t| (t, entry.status.task_id)))
+        .collect();
+    if finished.len() < MAX_FINISHED_TASKS {
+        return;
+    }
+    finished.sort();
+    for (_, task_id) in finished
+        .iter()
+        .take(finished.len() + 1 - MAX_FINISHED_TASKS)
+    {
+        tasks.remove(task_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchdog_flags_stalled_and_times_out_idle_tasks() {
+        let started = Utc::now();
+        let task_id = Uuid::new_v4();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let tasks: TaskTable = Arc::default();
+        lock_tasks(&tasks).unwrap().insert(
+            task_id,
+            TaskEntry {
+                status: TaskStatus {
+                    task_id,
+                    kind: "hashDirectory".to_string(),
+                    state: TaskState::Running,
+                    done: 0,
+                    total: None,
+                    message: Some("share/big.iso".to_string()),
+                    error: None,
+                    partial_results: Vec::new(),
+                    result: None,
+                    started_at: started,
+                    finished_at: None,
+                    last_activity_at: started,
+                    stalled: false,
+                    idle_timeout_seconds: 120,
+                },
+                cancelled: cancelled.clone(),
+                partial_outputs: Vec::new(),
+            },
+        );
+
+        assert!(check_running_tasks(&tasks, started + Duration::seconds(10)).is_empty());
+        let changed = check_running_tasks(&tasks, started + Duration::seconds(40));
+        assert!(changed[0].0.stalled && !changed[0].1);
+        assert!(check_running_tasks(&tasks, started + Duration::seconds(50)).is_empty());
+
+        let changed = check_running_tasks(&tasks, started + Duration::seconds(120));
+        assert_eq!(changed[0].0.state, TaskState::TimedOut);
+        assert!(changed[0].1 && cancelled.load(Ordering::Relaxed));
+        assert!(changed[0]
+            .0
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("share/big.iso"));
+    }
+}
@@ -0,0 +1,264 @@
+// src-tauri/src/test_vectors.rs
+// Interoperability test vectors for developers. `generate_test_vectors` signs a fixed
+// message with the self-test keys in every supported combination: each algorithm, both
+// digest schemes, both ECDSA encodings, and the envelope format. RSA, ECDSA P-256 and
+// P-384 (RFC 6979), Ed25519 and ML-DSA signatures are deterministic, so the output is the
+// same on every run and machine and can be checked by other tools; P-521 signatures use
+// random nonces and change on every run. `check_test_vectors` does the opposite:
+// it verifies vectors produced elsewhere, including ones marked as expected to fail.
+use crate::crypto_types::{
+    DigestScheme, EcdsaEncoding, SignatureAlgorithm, SignatureFormat, TestVector, TestVectorCheck,
+    TimeSource,
+};
+use crate::envelope::{
+    SignatureEnvelope, SignedAttributes, DIGEST_SHA256, ENVELOPE_FORMAT, ENVELOPE_VERSION,
+};
+use crate::key_management::{compute_fingerprint, decode_public_key_pem};
+use crate::native_paths::native_path;
+use crate::output_paths::resolve_output_path;
+use crate::self_tests::known_answer_key_pair;
+use crate::signing::{check_envelope_attributes, encode_ecdsa_signature, sign_bytes, verify_bytes};
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{TimeZone, Utc};
+use pkcs8::der::EncodePem;
+use pkcs8::{LineEnding, SubjectPublicKeyInfoRef};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Message signed by the generated vectors.
+const VECTOR_MESSAGE: &[u8] = b"sigust interoperability test vector";
+/// Signing time recorded in envelope vectors, fixed so they are reproducible.
+const VECTOR_SIGNED_AT: i64 = 1_700_000_000;
+
+// --- Tauri Commands ---
+
+/// Returns the known-answer vectors; with `output_path` they are also written there as a
+/// JSON array.
+#[tauri::command(rename_all = "camelCase")]
+pub fn generate_test_vectors(
+    app_handle: tauri::AppHandle,
+    output_path: Option<String>,
+) -> Result<Vec<TestVector>, String> {
+    log::info!("Generating interoperability test vectors");
+    _generate_test_vectors(&app_handle, output_path.as_deref()).map_err(|e| {
+        log::error!("Failed to generate test vectors: {:?}", e);
+        e.to_string()
+    })
+}
+
+fn _generate_test_vectors(
+    app_handle: &tauri::AppHandle,
+    output_path: Option<&str>,
+) -> Result<Vec<TestVector>> {
+    let vectors = known_answer_vectors()?;
+    if let Some(output_path) = output_path {
+        let content =
+            serde_json::to_string_pretty(&vectors).context("Failed to serialize test vectors")?;
+        let output_path = resolve_output_path(app_handle, &native_path(output_path)?)?;
+        fs::write(&output_path, content)
+            .with_context(|| format!("Failed to write test vectors: {:?}", output_path))?;
+    }
+    Ok(vectors)
+}
+
+/// Verifies the vectors in a JSON file (an array in the `generate_test_vectors` format).
+#[tauri::command(rename_all = "camelCase")]
+pub fn check_test_vectors(input_path: String) -> Result<Vec<TestVectorCheck>, String> {
+    log::info!("Checking test vectors from '{}'", input_path);
+    fs::read_to_string(&input_path)
+        .with_context(|| format!("Failed to read test vectors: {}", input_path))
+        .and_then(|content| {
+            serde_json::from_str::<Vec<TestVector>>(&content)
+                .context("Failed to parse test vectors")
+        })
+        .map(|vectors| vectors.iter().map(check_vector).collect())
+        .map_err(|e| {
+            log::error!("Failed to check test vectors: {:?}", e);
+            e.to_string()
+        })
+}
+
+// --- Helper Functions ---
+
+fn known_answer_vectors() -> Result<Vec<TestVector>> {
+    let algorithms = [
+        SignatureAlgorithm::Ed25519,
+        SignatureAlgorithm::EcdsaP256Sha256,
+        SignatureAlgorithm::EcdsaP384Sha384,
+        SignatureAlgorithm::EcdsaP521Sha512,
+        SignatureAlgorithm::RsaPkcs1Sha256,
+        SignatureAlgorithm::MlDsa65,
+    ];
+    let mut vectors = Vec::new();
+    for algorithm in algorithms {
+        let (private_der, public_der) = known_answer_key_pair(&algorithm)?;
+        let public_key_pem = SubjectPublicKeyInfoRef::try_from(public_der.as_slice())?
+            .to_pem(LineEnding::LF)
+            .context("Failed to encode public key to PEM")?;
+        let vector = |name: String, format, digest_scheme, ecdsa_encoding, signature| TestVector {
+            name,
+            algorithm: algorithm.to_string(),
+            format,
+            digest_scheme,
+            ecdsa_encoding,
+            public_key_pem: public_key_pem.clone(),
+            message_hex: hex::encode(VECTOR_MESSAGE),
+            signature,
+            invalid: false,
+        };
+        let slug = algorithm.to_string().to_lowercase();
+
+        // Ed25519 与 ML-DSA 签名不受摘要方案影响，只生成一条
+        let schemes: &[DigestScheme] = match algorithm {
+            SignatureAlgorithm::Ed25519 | SignatureAlgorithm::MlDsa65 => &[DigestScheme::Standard],
+            _ => &[DigestScheme::Standard, DigestScheme::Legacy],
+        };
+        for &scheme in schemes {
+            let signature = sign_bytes(&algorithm, &private_der, VECTOR_MESSAGE, scheme)?;
+            let scheme_name = format!("{:?}", scheme).to_lowercase();
+            if algorithm.is_ecdsa() {
+                for encoding in [EcdsaEncoding::Raw, EcdsaEncoding::Der] {
+                    vectors.push(vector(
+                        format!("{}-{}-{:?}", slug, scheme_name, encoding).to_lowercase(),
+                        SignatureFormat::Detached,
+                        scheme,
+                        Some(encoding),
+                        BASE64.encode(encode_ecdsa_signature(&signature, encoding)?),
+                    ));
+                }
+            } else {
+                vectors.push(vector(
+                    format!("{}-{}", slug, scheme_name),
+                    SignatureFormat::Detached,
+                    scheme,
+                    None,
+                    BASE64.encode(signature),
+                ));
+            }
+        }
+
+        let attributes = SignedAttributes {
+            format: ENVELOPE_FORMAT.to_string(),
+            version: ENVELOPE_VERSION,
+            algorithm: algorithm.to_string(),
+            key_id: Uuid::nil(),
+            key_fingerprint: compute_fingerprint(&public_der),
+            digest_algorithm: DIGEST_SHA256.to_string(),
+            document_digest: hex::encode(Sha256::digest(VECTOR_MESSAGE)),
+            signed_at: Utc
+                .timestamp_opt(VECTOR_SIGNED_AT, 0)
+                .single()
+                .ok_or_else(|| anyhow!("Invalid vector signing time"))?,
+            time_source: TimeSource::LocalClock,
+            time_token: None,
+            file_name: None,
+            file_size: None,
+            file_modified_at: None,
+            previous_envelope: None,
+            metadata: BTreeMap::new(),
+            approval: None,
+            sequence: None,
+            oidc_identity: None,
+            pq_cosigner: None,
+        };
+        let envelope = SignatureEnvelope::seal(&attributes, &private_der)?;
+        vectors.push(vector(
+            format!("{}-envelope", slug),
+            SignatureFormat::Envelope,
+            DigestScheme::Standard,
+            None,
+            serde_json::to_string(&envelope).context("Failed to serialize envelope")?,
+        ));
+    }
+    Ok(vectors)
+}
+
+// 验证结果与期望（有效/无效）一致即通过
+fn check_vector(vector: &TestVector) -> TestVectorCheck {
+    let (passed, error) = match verify_vector(vector) {
+        Ok(Ok(())) if !vector.invalid => (true, None),
+        Ok(Err(reason)) if vector.invalid => (true, Some(reason)),
+        Ok(Ok(())) => (
+            false,
+            Some("Signature accepted but expected to fail".to_string()),
+        ),
+        Ok(Err(reason)) | Err(reason) => (false, Some(reason)),
+    };
+    TestVectorCheck {
+        name: vector.name.clone(),
+        passed,
+        error,
+    }
+}
+
+/// Outer error: the vector cannot be processed; inner: the signature was rejected.
+fn verify_vector(vector: &TestVector) -> std::result::Result<Result<(), String>, String> {
+    let inner = || -> Result<Result<(), String>> {
+        let algorithm = SignatureAlgorithm::from_str(&vector.algorithm)?;
+        let public_key_der = decode_public_key_pem(&vector.public_key_pem)?;
+        let message = hex::decode(&vector.message_hex).context("Malformed message hex")?;
+        match vector.format {
+            SignatureFormat::Detached => {
+                let signature = BASE64
+                    .decode(&vector.signature)
+                    .context("Malformed signature base64")?;
+                Ok(verify_bytes(
+                    &algorithm,
+                    &public_key_der,
+                    &message,
+                    &signature,
+                    vector.digest_scheme,
+                )?
+                .map_err(|e| format!("Signature is invalid: {}", e)))
+            }
+            SignatureFormat::Envelope | SignatureFormat::Hybrid => {
+                let envelope: SignatureEnvelope =
+                    serde_json::from_str(&vector.signature).context("Malformed envelope")?;
+                let attributes = envelope.attributes()?;
+                let digest = hex::encode(Sha256::digest(&message));
+                if let Some(mismatch) =
+                    check_envelope_attributes(&attributes, &algorithm, &public_key_der, &digest)?
+                {
+                    return Ok(Err(mismatch));
+                }
+                // 测试向量自带全部公钥，混合信封的后量子公钥同样取自向量本身
+                let verified = match &attributes.pq_cosigner {
+                    None => envelope.verify_signature(&algorithm, &public_key_der)?,
+                    Some(cosigner) => envelope.verify_hybrid_signature(
+                        &algorithm,
+                        &public_key_der,
+                        &decode_public_key_pem(&cosigner.public_key_pem)?,
+                    )?,
+                };
+                Ok(verified.map_err(|e| format!("Signature is invalid: {}", e)))
+            }
+        }
+    };
+    inner().map_err(|e| format!("{:#}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_vectors_check_out() {
+        let mut vectors = known_answer_vectors().unwrap();
+        assert_eq!(vectors.len(), 22);
+        assert!(vectors.iter().map(check_vector).all(|c| c.passed));
+
+        // 篡改消息后，标记为无效的向量仍算通过
+        for vector in &mut vectors {
+            vector.message_hex = hex::encode(b"tampered");
+            vector.invalid = true;
+        }
+        assert!(vectors.iter().map(check_vector).all(|c| c.passed));
+        vectors[0].invalid = false;
+        assert!(!check_vector(&vectors[0]).passed);
+    }
+}
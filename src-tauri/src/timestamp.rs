@@ -0,0 +1,811 @@
+// src-tauri/src/timestamp.rs
+// RFC 3161 time-stamp protocol client: requests a token for a SHA-256 digest from a
+// time-stamping authority (TSA) and checks tokens later on.
+//
+// Token checks cover the message imprint, the request nonce and the CMS signature with
+// the certificate embedded in the token. The TSA is trusted when its certificate chains,
+// through certificates the token carries, to one of the TSA certificates configured in the
+// settings (the TSA's own certificate or a CA above it). Tokens from other TSAs are
+// invalid, and there are no trusted TSAs until the user adds one.
+//
+// Revocation of the TSA certificate is checked against the CRLs the token carries, as of
+// the instant the certificate is judged at. Nothing is fetched, so without such a CRL the
+// status is reported as unknown, never as good.
+use crate::audit;
+use crate::crypto_types::RevocationStatus;
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use cmpv2::status::PkiStatus;
+use cms::cert::CertificateChoices;
+use cms::content_info::ContentInfo;
+use cms::revocation::RevocationInfoChoice;
+use cms::signed_data::{SignedData, SignerIdentifier, SignerInfo};
+use const_oid::db::rfc5280::ID_KP_TIME_STAMPING;
+use const_oid::db::rfc5911::{ID_CONTENT_TYPE, ID_MESSAGE_DIGEST, ID_SIGNED_DATA};
+use const_oid::db::rfc5912::{
+    ECDSA_WITH_SHA_256, ID_SHA_256, ID_SHA_384, ID_SHA_512, RSA_ENCRYPTION,
+    SHA_256_WITH_RSA_ENCRYPTION, SHA_384_WITH_RSA_ENCRYPTION, SHA_512_WITH_RSA_ENCRYPTION,
+};
+use der::asn1::{Int, OctetString};
+use der::oid::AssociatedOid;
+use der::oid::ObjectIdentifier;
+use der::{Any, Decode, Encode};
+use rsa::pkcs1v15::{Signature as RsaSignature, VerifyingKey as RsaVerifyingKey};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::rand_core::{OsRng, RngCore};
+use rsa::RsaPublicKey;
+use serde_json::json;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use signature::Verifier;
+use spki::AlgorithmIdentifier;
+use std::time::Duration;
+use x509_cert::crl::CertificateList;
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::ext::pkix::{BasicConstraints, ExtendedKeyUsage};
+use x509_cert::Certificate;
+use x509_tsp::{MessageImprint, TimeStampReq, TimeStampResp, TspVersion, TstInfo};
+
+/// id-ct-TSTInfo (RFC 3161).
+const ID_CT_TST_INFO: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.1.4");
+const TSA_TIMEOUT: Duration = Duration::from_secs(30);
+/// Upper bound for a TSA response body (tokens are a few KiB including certificates).
+const MAX_RESPONSE_SIZE: u64 = 1024 * 1024;
+/// Longest certificate chain followed from the TSA certificate.
+const MAX_CHAIN_LENGTH: usize = 10;
+
+/// A checked time-stamp token.
+#[derive(Debug, Clone)]
+pub struct VerifiedTimestamp {
+    /// Time asserted by the TSA.
+    pub gen_time: DateTime<Utc>,
+    /// Name of the TSA from the token (or the signing certificate's subject).
+    pub tsa_name: Option<String>,
+    /// End of the TSA certificate's validity period.
+    pub certificate_not_after: DateTime<Utc>,
+    /// Instant the TSA certificate validity was checked at.
+    pub checked_at: DateTime<Utc>,
+    /// Revocation status of the TSA certificate at `checked_at`.
+    pub revocation_status: RevocationStatus,
+}
+
+// --- Tauri Commands ---
+
+/// Replaces the certificates (PEM) that time-stamping authorities must chain to.
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_trusted_tsa_certificates(
+    app_handle: tauri::AppHandle,
+    certificates_pem: Vec<String>,
+) -> Result<Vec<String>, String> {
+    log::info!(
+        "Updating trusted TSA certificates ({} given)",
+        certificates_pem.len()
+    );
+    _update_trusted_tsa_certificates(&app_handle, certificates_pem)
+        .map_err(|e| {
+            log::error!("Failed to update trusted TSA certificates: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|subjects| {
+            audit::record(
+                &app_handle,
+                "updateTrustedTsaCertificates",
+                json!({ "subjects": subjects }),
+            )
+        })
+}
+
+// 校验每个证书后写入设置，返回证书主题
+fn _update_trusted_tsa_certificates(
+    app_handle: &tauri::AppHandle,
+    certificates_pem: Vec<String>,
+) -> Result<Vec<String>> {
+    let subjects = certificates_pem
+        .iter()
+        .map(|pem| decode_certificate_pem(pem).map(|cert| cert.tbs_certificate.subject.to_string()))
+        .collect::<Result<Vec<_>>>()?;
+    update_settings(app_handle, |settings| {
+        settings.trusted_tsa_certificates = certificates_pem
+    })?;
+    Ok(subjects)
+}
+
+/// The trusted TSA certificates from the settings.
+pub(crate) fn trusted_tsa_certificates(app_handle: &tauri::AppHandle) -> Result<Vec<Certificate>> {
+    read_settings(&get_settings_path(app_handle)?)?
+        .trusted_tsa_certificates
+        .iter()
+        .map(|pem| decode_certificate_pem(pem))
+        .collect()
+}
+
+/// Requests a time-stamp token over a SHA-256 `digest` and returns the token (DER
+/// `ContentInfo`) after checking it against the request. Whether the TSA is trusted is up
+/// to the caller (see [`check_timestamp_token`]).
+pub fn request_timestamp(tsa_url: &str, digest: &[u8]) -> Result<Vec<u8>> {
+    // 正整数 nonce：最高位清零，且首字节非零以保证 DER 最小编码
+    let mut nonce = [0u8; 8];
+    OsRng.fill_bytes(&mut nonce);
+    nonce[0] = (nonce[0] & 0x7f) | 0x01;
+
+    let request = TimeStampReq {
+        version: TspVersion::V1,
+        message_imprint: sha256_imprint(digest)?,
+        req_policy: None,
+        nonce: Some(Int::new(&nonce).map_err(|e| anyhow!("Invalid nonce: {}", e))?),
+        cert_req: true,
+        extensions: None,
+    };
+    let request_der = request
+        .to_der()
+        .map_err(|e| anyhow!("Failed to encode time-stamp request: {}", e))?;
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(TSA_TIMEOUT))
+        .build()
+        .into();
+    let response_der = agent
+        .post(tsa_url)
+        .header("Content-Type", "application/timestamp-query")
+        .header("Accept", "application/timestamp-reply")
+        .send(&request_der[..])
+        .with_context(|| format!("Time-stamp request to {} failed", tsa_url))?
+        .body_mut()
+        .with_config()
+        .limit(MAX_RESPONSE_SIZE)
+        .read_to_vec()
+        .context("Failed to read time-stamp response")?;
+
+    let response = TimeStampResp::from_der(&response_der)
+        .map_err(|e| anyhow!("Malformed time-stamp response: {}", e))?;
+    if !matches!(
+        response.status.status,
+        PkiStatus::Accepted | PkiStatus::GrantedWithMods
+    ) {
+        bail!(
+            "Time-stamping authority rejected the request ({:?})",
+            response.status.status
+        );
+    }
+    let token = response
+        .time_stamp_token
+        .ok_or_else(|| anyhow!("Time-stamp response contains no token"))?;
+    let token_der = token
+        .to_der()
+        .map_err(|e| anyhow!("Failed to encode time-stamp token: {}", e))?;
+
+    let (tst_info, _) = check_token(&token_der, digest, None, &[])?;
+    let returned_nonce = tst_info.nonce.as_ref().map(|n| n.as_bytes());
+    if returned_nonce != Some(&nonce[..]) {
+        bail!("Time-stamp token nonce does not match the request");
+    }
+    Ok(token_der)
+}
+
+/// Checks a stored time-stamp token against the SHA-256 `digest` it should cover.
+///
+/// The TSA certificate must chain to one of `trusted_tsas`, and the chain must be valid at
+/// `as_of`, or at the time in the token when no date is given, so tokens stay verifiable
+/// after the TSA certificate expires.
+pub fn verify_timestamp_token(
+    token_der: &[u8],
+    digest: &[u8],
+    as_of: Option<DateTime<Utc>>,
+    trusted_tsas: &[Certificate],
+) -> Result<VerifiedTimestamp> {
+    check_timestamp_token(token_der, digest, as_of, trusted_tsas)?.map_err(|e| anyhow!(e))
+}
+
+/// Checks a token like [`verify_timestamp_token`]. Outer error: the token is broken or does
+/// not cover `digest`; inner: it is intact, but its TSA is not trusted.
+pub fn check_timestamp_token(
+    token_der: &[u8],
+    digest: &[u8],
+    as_of: Option<DateTime<Utc>>,
+    trusted_tsas: &[Certificate],
+) -> Result<std::result::Result<VerifiedTimestamp, String>> {
+    check_token(token_der, digest, as_of, trusted_tsas).map(|(_, verified)| verified)
+}
+
+/// Contents of a time-stamp token, read without any checks (for display only).
+pub struct TimestampTokenContents {
+    pub gen_time: DateTime<Utc>,
+    pub tsa_name: Option<String>,
+    pub certificates: Vec<Certificate>,
+}
+
+/// Reads the time and the embedded certificates of a time-stamp token without checking it.
+pub fn read_timestamp_token(token_der: &[u8]) -> Result<TimestampTokenContents> {
+    let (signed_data, tst_info) = decode_token(token_der)?;
+    let certificates = token_certificates(&signed_data);
+    let tsa_name = match &tst_info.tsa {
+        Some(GeneralName::DirectoryName(name)) => Some(name.to_string()),
+        _ => certificates
+            .first()
+            .map(|cert| cert.tbs_certificate.subject.to_string()),
+    };
+    Ok(TimestampTokenContents {
+        gen_time: DateTime::<Utc>::from(tst_info.gen_time.to_system_time()),
+        tsa_name,
+        certificates,
+    })
+}
+
+// --- Helper Functions ---
+
+// 解码令牌：ContentInfo -> SignedData -> TSTInfo
+fn decode_token(token_der: &[u8]) -> Result<(SignedData, TstInfo)> {
+    let content_info = ContentInfo::from_der(token_der)
+        .map_err(|e| anyhow!("Malformed time-stamp token: {}", e))?;
+    if content_info.content_type != ID_SIGNED_DATA {
+        bail!("Time-stamp token is not CMS SignedData");
+    }
+    let signed_data: SignedData = content_info
+        .content
+        .decode_as()
+        .map_err(|e| anyhow!("Malformed time-stamp SignedData: {}", e))?;
+    if signed_data.encap_content_info.econtent_type != ID_CT_TST_INFO {
+        bail!("Time-stamp token does not contain TSTInfo");
+    }
+    let econtent = signed_data
+        .encap_content_info
+        .econtent
+        .as_ref()
+        .ok_or_else(|| anyhow!("Time-stamp token has no content"))?;
+    let tst_info =
+        TstInfo::from_der(econtent.value()).map_err(|e| anyhow!("Malformed TSTInfo: {}", e))?;
+    Ok((signed_data, tst_info))
+}
+
+// 令牌自带的证书
+fn token_certificates(signed_data: &SignedData) -> Vec<Certificate> {
+    signed_data
+        .certificates
+        .iter()
+        .flat_map(|set| set.0.iter())
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(cert) => Some(cert.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+// 解析 PEM 证书
+fn decode_certificate_pem(pem: &str) -> Result<Certificate> {
+    let (label, der) = pem_rfc7468::decode_vec(pem.trim().as_bytes())
+        .map_err(|e| anyhow!("Invalid TSA certificate: {}", e))?;
+    if label != "CERTIFICATE" {
+        bail!("Expected a certificate, found {}", label);
+    }
+    Certificate::from_der(&der).map_err(|e| anyhow!("Invalid TSA certificate: {}", e))
+}
+
+fn sha256_imprint(digest: &[u8]) -> Result<MessageImprint> {
+    Ok(MessageImprint {
+        hash_algorithm: AlgorithmIdentifier {
+            oid: ID_SHA_256,
+            parameters: Some(Any::null()),
+        },
+        hashed_message: OctetString::new(digest)
+            .map_err(|e| anyhow!("Invalid message imprint: {}", e))?,
+    })
+}
+
+// 解析令牌，校验消息摘要、CMS 签名与 TSA 证书有效期，返回 TSTInfo；
+// TSA 证书链不受信任时内层为错误
+fn check_token(
+    token_der: &[u8],
+    digest: &[u8],
+    as_of: Option<DateTime<Utc>>,
+    trusted_tsas: &[Certificate],
+) -> Result<(TstInfo, std::result::Result<VerifiedTimestamp, String>)> {
+    let (signed_data, tst_info) = decode_token(token_der)?;
+    let tst_info_der = signed_data
+        .encap_content_info
+        .econtent
+        .as_ref()
+        .map(|econtent| econtent.value())
+        .unwrap_or_default();
+
+    if tst_info.message_imprint.hash_algorithm.oid != ID_SHA_256
+        || tst_info.message_imprint.hashed_message.as_bytes() != digest
+    {
+        bail!("Time-stamp token does not cover this data");
+    }
+
+    let signer_info = signed_data
+        .signer_infos
+        .0
+        .iter()
+        .next()
+        .ok_or_else(|| anyhow!("Time-stamp token has no signer"))?;
+    let certificate = find_signer_certificate(&signed_data, signer_info)
+        .context("Failed to find the TSA certificate")?;
+    verify_signer_info(signer_info, &certificate, tst_info_der)?;
+
+    let tsa_name = match &tst_info.tsa {
+        Some(GeneralName::DirectoryName(name)) => Some(name.to_string()),
+        _ => Some(certificate.tbs_certificate.subject.to_string()),
+    };
+    let gen_time = DateTime::<Utc>::from(tst_info.gen_time.to_system_time());
+    let validity = &certificate.tbs_certificate.validity;
+    let not_before = DateTime::<Utc>::from(validity.not_before.to_system_time());
+    let not_after = DateTime::<Utc>::from(validity.not_after.to_system_time());
+    let checked_at = as_of.unwrap_or(gen_time);
+    if checked_at < not_before || checked_at > not_after {
+        bail!(
+            "TSA certificate was not valid at {} (valid from {} to {})",
+            checked_at,
+            not_before,
+            not_after
+        );
+    }
+    let pool = token_certificates(&signed_data);
+    let chain = check_tsa_chain(&certificate, &pool, trusted_tsas, checked_at);
+    // 只有链到受信任证书时签发者才可信，才能判断吊销状态
+    let revocation_status = match &chain {
+        Ok(Some(issuer)) => check_revocation(&certificate, issuer, &signed_data, checked_at)?,
+        Ok(None) | Err(_) => RevocationStatus::Unknown,
+    };
+    let verified = VerifiedTimestamp {
+        gen_time,
+        tsa_name,
+        certificate_not_after: not_after,
+        checked_at,
+        revocation_status,
+    };
+    Ok((
+        tst_info,
+        chain.map(|_| verified).map_err(|e| format!("{:#}", e)),
+    ))
+}
+
+// 从 TSA 证书经令牌自带的证书向上查找，直到遇到受信任的证书；签发者须为 CA，
+// 整条链须在 `checked_at` 有效。返回 TSA 证书的签发者（TSA 证书本身受信任时为 None）
+fn check_tsa_chain<'a>(
+    tsa_certificate: &'a Certificate,
+    pool: &'a [Certificate],
+    trusted_tsas: &'a [Certificate],
+    checked_at: DateTime<Utc>,
+) -> Result<Option<&'a Certificate>> {
+    let subject = &tsa_certificate.tbs_certificate.subject;
+    if trusted_tsas.is_empty() {
+        bail!(
+            "No trusted time-stamping authorities are configured, so {} is not trusted",
+            subject
+        );
+    }
+    if !has_time_stamping_usage(tsa_certificate) {
+        bail!("Certificate {} is not issued for time-stamping", subject);
+    }
+    let mut current = tsa_certificate;
+    let mut tsa_issuer = None;
+    for _ in 0..MAX_CHAIN_LENGTH {
+        if trusted_tsas.contains(current) {
+            return Ok(tsa_issuer);
+        }
+        let issuer = trusted_tsas.iter().chain(pool).find(|cert| {
+            *cert != current
+                && cert.tbs_certificate.subject == current.tbs_certificate.issuer
+                && is_ca(cert)
+                && certificate_signed_by(current, cert)
+        });
+        let Some(issuer) = issuer else {
+            bail!(
+                "TSA certificate {} does not chain to a trusted time-stamping authority",
+                subject
+            );
+        };
+        let validity = &issuer.tbs_certificate.validity;
+        if checked_at < DateTime::<Utc>::from(validity.not_before.to_system_time())
+            || checked_at > DateTime::<Utc>::from(validity.not_after.to_system_time())
+        {
+            bail!(
+                "Certificate {} was not valid at {}",
+                issuer.tbs_certificate.subject,
+                checked_at
+            );
+        }
+        tsa_issuer.get_or_insert(issuer);
+        current = issuer;
+    }
+    bail!("The TSA certificate chain of {} is too long", subject)
+}
+
+// 用令牌自带、由签发者签名且在 `checked_at` 有效的 CRL 判断 TSA 证书是否已被吊销
+fn check_revocation(
+    certificate: &Certificate,
+    issuer: &Certificate,
+    signed_data: &SignedData,
+    checked_at: DateTime<Utc>,
+) -> Result<RevocationStatus> {
+    let tbs = &certificate.tbs_certificate;
+    let crls = signed_data
+        .crls
+        .iter()
+        .flat_map(|set| set.0.iter())
+        .filter_map(|choice| match choice {
+            RevocationInfoChoice::Crl(crl) => Some(crl),
+            _ => None,
+        })
+        .filter(|crl| crl.tbs_cert_list.issuer == tbs.issuer && crl_covers(crl, checked_at))
+        .filter(|crl| crl_signed_by(crl, issuer));
+    let mut status = RevocationStatus::Unknown;
+    for crl in crls {
+        let revoked = crl
+            .tbs_cert_list
+            .revoked_certificates
+            .iter()
+            .flatten()
+            .find(|entry| entry.serial_number == tbs.serial_number);
+        match revoked {
+            Some(entry) => {
+                let revoked_at = DateTime::<Utc>::from(entry.revocation_date.to_system_time());
+                if revoked_at <= checked_at {
+                    bail!(
+                        "TSA certificate {} was revoked on {}",
+                        tbs.subject,
+                        revoked_at
+                    );
+                }
+                status = RevocationStatus::NotRevoked;
+            }
+            None => status = RevocationStatus::NotRevoked,
+        }
+    }
+    Ok(status)
+}
+
+// CRL 在 `checked_at` 是否为当期列表
+fn crl_covers(crl: &CertificateList, checked_at: DateTime<Utc>) -> bool {
+    let tbs = &crl.tbs_cert_list;
+    DateTime::<Utc>::from(tbs.this_update.to_system_time()) <= checked_at
+        && tbs.next_update.map_or(true, |next| {
+            checked_at <= DateTime::<Utc>::from(next.to_system_time())
+        })
+}
+
+fn crl_signed_by(crl: &CertificateList, issuer: &Certificate) -> bool {
+    let Ok(tbs_der) = crl.tbs_cert_list.to_der() else {
+        return false;
+    };
+    let algorithm = &crl.signature_algorithm.oid;
+    verify_with_certificate_key(
+        algorithm,
+        algorithm,
+        issuer,
+        &tbs_der,
+        crl.signature.raw_bytes(),
+    )
+    .unwrap_or(false)
+}
+
+// 证书的 ExtendedKeyUsage 是否包含 id-kp-timeStamping
+fn has_time_stamping_usage(cert: &Certificate) -> bool {
+    cert.tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .filter(|ext| ext.extn_id == ExtendedKeyUsage::OID)
+        .filter_map(|ext| ExtendedKeyUsage::from_der(ext.extn_value.as_bytes()).ok())
+        .any(|usage| usage.0.contains(&ID_KP_TIME_STAMPING))
+}
+
+// 证书的 BasicConstraints 是否标明为 CA
+fn is_ca(cert: &Certificate) -> bool {
+    cert.tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .filter(|ext| ext.extn_id == BasicConstraints::OID)
+        .filter_map(|ext| BasicConstraints::from_der(ext.extn_value.as_bytes()).ok())
+        .any(|constraints| constraints.ca)
+}
+
+/// Whether the signature on `certificate` checks with the public key of `issuer`.
+pub(crate) fn certificate_signed_by(certificate: &Certificate, issuer: &Certificate) -> bool {
+    let Ok(tbs_der) = certificate.tbs_certificate.to_der() else {
+        return false;
+    };
+    let algorithm = &certificate.signature_algorithm.oid;
+    verify_with_certificate_key(
+        algorithm,
+        algorithm,
+        issuer,
+        &tbs_der,
+        certificate.signature.raw_bytes(),
+    )
+    .unwrap_or(false)
+}
+
+// 在 SignedData 自带的证书中找到签名者证书
+pub(crate) fn find_signer_certificate(
+    signed_data: &SignedData,
+    signer: &SignerInfo,
+) -> Result<Certificate> {
+    let certificates = signed_data
+        .certificates
+        .as_ref()
+        .ok_or_else(|| anyhow!("The signature does not include certificates"))?;
+    certificates
+        .0
+        .iter()
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(cert) => Some(cert),
+            _ => None,
+        })
+        .find(|cert| match &signer.sid {
+            SignerIdentifier::IssuerAndSerialNumber(id) => {
+                cert.tbs_certificate.issuer == id.issuer
+                    && cert.tbs_certificate.serial_number == id.serial_number
+            }
+            SignerIdentifier::SubjectKeyIdentifier(ski) => {
+                certificate_ski(cert).as_deref() == Some(ski.0.as_bytes())
+            }
+        })
+        .cloned()
+        .ok_or_else(|| anyhow!("Signer certificate not found in the signature"))
+}
+
+// 读取证书的 SubjectKeyIdentifier 扩展
+pub(crate) fn certificate_ski(cert: &Certificate) -> Option<Vec<u8>> {
+    use x509_cert::ext::pkix::SubjectKeyIdentifier;
+    cert.tbs_certificate
+        .extensions
+        .as_ref()?
+        .iter()
+        .find(|ext| ext.extn_id == SubjectKeyIdentifier::OID)
+        .and_then(|ext| SubjectKeyIdentifier::from_der(ext.extn_value.as_bytes()).ok())
+        .map(|ski| ski.0.as_bytes().to_vec())
+}
+
+// 校验 SignerInfo：签名属性中的 messageDigest/contentType，以及对签名属性的签名
+fn verify_signer_info(
+    signer: &SignerInfo,
+    certificate: &Certificate,
+    tst_info_der: &[u8],
+) -> Result<()> {
+    let signed_attrs = signer
+        .signed_attrs
+        .as_ref()
+        .ok_or_else(|| anyhow!("Time-stamp signer has no signed attributes"))?;
+
+    let content_digest = digest_with(&signer.digest_alg.oid, tst_info_der)?;
+    let mut digest_ok = false;
+    let mut content_type_ok = false;
+    for attribute in signed_attrs.iter() {
+        let Some(value) = attribute.values.iter().next() else {
+            continue;
+        };
+        if attribute.oid == ID_MESSAGE_DIGEST {
+            let message_digest: OctetString = value
+                .decode_as()
+                .map_err(|e| anyhow!("Malformed messageDigest attribute: {}", e))?;
+            digest_ok = message_digest.as_bytes() == content_digest.as_slice();
+        } else if attribute.oid == ID_CONTENT_TYPE {
+            let content_type: ObjectIdentifier = value
+                .decode_as()
+                .map_err(|e| anyhow!("Malformed contentType attribute: {}", e))?;
+            content_type_ok = content_type == ID_CT_TST_INFO;
+        }
+    }
+    if !digest_ok || !content_type_ok {
+        bail!("Time-stamp signed attributes do not match the token content");
+    }
+
+    // 签名覆盖的是以 SET OF 编码的签名属性
+    let signed_attrs_der = signed_attrs
+        .to_der()
+        .map_err(|e| anyhow!("Failed to encode signed attributes: {}", e))?;
+    let verified = verify_with_certificate_key(
+        &signer.signature_algorithm.oid,
+        &signer.digest_alg.oid,
+        certificate,
+        &signed_attrs_der,
+        signer.signature.as_bytes(),
+    )
+    .context("Failed to check the time-stamp signature")?;
+    if !verified {
+        bail!("Time-stamp token signature is invalid");
+    }
+    Ok(())
+}
+
+/// Checks a CMS or X.509 `signature` over `data` with the public key of `certificate`.
+/// `digest_alg` picks the hash when the algorithm is plain `rsaEncryption`. Supports RSA
+/// PKCS#1 v1.5 with SHA-256/384/512 and ECDSA P-256 with SHA-256.
+pub(crate) fn verify_with_certificate_key(
+    algorithm: &ObjectIdentifier,
+    digest_alg: &ObjectIdentifier,
+    certificate: &Certificate,
+    data: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    let spki_der = certificate
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .map_err(|e| anyhow!("Failed to encode public key: {}", e))?;
+
+    if *algorithm == ECDSA_WITH_SHA_256 {
+        let key = p256::ecdsa::VerifyingKey::from_public_key_der(&spki_der)
+            .context("Unsupported key (expected P-256)")?;
+        let signature = p256::ecdsa::DerSignature::from_bytes(signature)
+            .map_err(|e| anyhow!("Malformed signature: {}", e))?;
+        return Ok(key.verify(data, &signature).is_ok());
+    }
+    let key = RsaPublicKey::from_public_key_der(&spki_der).context("Unsupported key algorithm")?;
+    let signature =
+        RsaSignature::try_from(signature).map_err(|e| anyhow!("Malformed signature: {}", e))?;
+    // rsaEncryption 时由 digestAlgorithm 决定哈希
+    let hash = if *algorithm == RSA_ENCRYPTION {
+        *digest_alg
+    } else {
+        *algorithm
+    };
+    if hash == ID_SHA_256 || hash == SHA_256_WITH_RSA_ENCRYPTION {
+        Ok(RsaVerifyingKey::<Sha256>::new(key)
+            .verify(data, &signature)
+            .is_ok())
+    } else if hash == ID_SHA_384 || hash == SHA_384_WITH_RSA_ENCRYPTION {
+        Ok(RsaVerifyingKey::<Sha384>::new(key)
+            .verify(data, &signature)
+            .is_ok())
+    } else if hash == ID_SHA_512 || hash == SHA_512_WITH_RSA_ENCRYPTION {
+        Ok(RsaVerifyingKey::<Sha512>::new(key)
+            .verify(data, &signature)
+            .is_ok())
+    } else {
+        bail!("Unsupported signature algorithm: {}", algorithm)
+    }
+}
+
+pub(crate) fn digest_with(oid: &ObjectIdentifier, data: &[u8]) -> Result<Vec<u8>> {
+    if *oid == ID_SHA_256 {
+        Ok(Sha256::digest(data).to_vec())
+    } else if *oid == ID_SHA_384 {
+        Ok(Sha384::digest(data).to_vec())
+    } else if *oid == ID_SHA_512 {
+        Ok(Sha512::digest(data).to_vec())
+    } else {
+        bail!("Unsupported digest algorithm: {}", oid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cms::cert::IssuerAndSerialNumber;
+    use cms::content_info::CmsVersion;
+    use cms::signed_data::{CertificateSet, EncapsulatedContentInfo, SignerInfos};
+    use der::asn1::{GeneralizedTime, SetOfVec};
+    use der::Tag;
+    use p256::ecdsa::{DerSignature, SigningKey};
+    use signature::Signer;
+    use spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned};
+    use std::str::FromStr;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use x509_cert::attr::Attribute;
+    use x509_cert::builder::{Builder, CertificateBuilder, Profile};
+    use x509_cert::name::Name;
+    use x509_cert::serial_number::SerialNumber;
+    use x509_cert::time::Validity;
+
+    // 自签名、带 timeStamping 用途的 TSA 证书
+    fn self_signed_tsa(key: &SigningKey, name: &str) -> Certificate {
+        let subject = Name::from_str(&format!("CN={}", name)).unwrap();
+        let profile = Profile::Leaf {
+            issuer: subject.clone(),
+            enable_key_agreement: false,
+            enable_key_encipherment: false,
+        };
+        let mut builder = CertificateBuilder::new(
+            profile,
+            SerialNumber::from(1u32),
+            Validity::from_now(Duration::from_secs(3600)).unwrap(),
+            subject,
+            SubjectPublicKeyInfoOwned::from_key(*key.verifying_key()).unwrap(),
+            key,
+        )
+        .unwrap();
+        builder
+            .add_extension(&ExtendedKeyUsage(vec![ID_KP_TIME_STAMPING]))
+            .unwrap();
+        builder.build::<DerSignature>().unwrap()
+    }
+
+    // 用 TSA 的密钥为 `digest` 签发时间戳令牌
+    fn issue_token(key: &SigningKey, certificate: &Certificate, digest: &[u8]) -> Vec<u8> {
+        let gen_time =
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + Duration::from_secs(60);
+        let tst_info = TstInfo {
+            version: TspVersion::V1,
+            policy: ObjectIdentifier::new_unwrap("1.2.3.4"),
+            message_imprint: sha256_imprint(digest).unwrap(),
+            serial_number: Int::new(&[1]).unwrap(),
+            gen_time: GeneralizedTime::from_unix_duration(gen_time).unwrap(),
+            accuracy: None,
+            ordering: false,
+            nonce: None,
+            tsa: None,
+            extensions: None,
+        }
+        .to_der()
+        .unwrap();
+        let attribute = |oid, value: Any| Attribute {
+            oid,
+            values: SetOfVec::try_from(vec![value]).unwrap(),
+        };
+        let signed_attrs = SetOfVec::try_from(vec![
+            attribute(ID_CONTENT_TYPE, Any::encode_from(&ID_CT_TST_INFO).unwrap()),
+            attribute(
+                ID_MESSAGE_DIGEST,
+                Any::new(Tag::OctetString, Sha256::digest(&tst_info).to_vec()).unwrap(),
+            ),
+        ])
+        .unwrap();
+        let signature: DerSignature = key.sign(&signed_attrs.to_der().unwrap());
+        let sha256 = AlgorithmIdentifierOwned {
+            oid: ID_SHA_256,
+            parameters: None,
+        };
+        let signer_info = SignerInfo {
+            version: CmsVersion::V1,
+            sid: SignerIdentifier::IssuerAndSerialNumber(IssuerAndSerialNumber {
+                issuer: certificate.tbs_certificate.issuer.clone(),
+                serial_number: certificate.tbs_certificate.serial_number.clone(),
+            }),
+            digest_alg: sha256.clone(),
+            signed_attrs: Some(signed_attrs),
+            signature_algorithm: AlgorithmIdentifierOwned {
+                oid: ECDSA_WITH_SHA_256,
+                parameters: None,
+            },
+            signature: OctetString::new(signature.to_bytes().to_vec()).unwrap(),
+            unsigned_attrs: None,
+        };
+        let signed_data = SignedData {
+            version: CmsVersion::V3,
+            digest_algorithms: SetOfVec::try_from(vec![sha256]).unwrap(),
+            encap_content_info: EncapsulatedContentInfo {
+                econtent_type: ID_CT_TST_INFO,
+                econtent: Some(Any::new(Tag::OctetString, tst_info).unwrap()),
+            },
+            certificates: Some(CertificateSet(
+                SetOfVec::try_from(vec![CertificateChoices::Certificate(certificate.clone())])
+                    .unwrap(),
+            )),
+            crls: None,
+            signer_infos: SignerInfos(SetOfVec::try_from(vec![signer_info]).unwrap()),
+        };
+        ContentInfo {
+            content_type: ID_SIGNED_DATA,
+            content: Any::encode_from(&signed_data).unwrap(),
+        }
+        .to_der()
+        .unwrap()
+    }
+
+    #[test]
+    fn self_signed_tsas_are_only_trusted_when_configured() {
+        let key = SigningKey::random(&mut OsRng);
+        let certificate = self_signed_tsa(&key, "Rogue TSA");
+        let digest = Sha256::digest(b"contract");
+        let token = issue_token(&key, &certificate, &digest);
+
+        assert!(verify_timestamp_token(&token, &digest, None, &[]).is_err());
+        // 令牌本身完好，只是 TSA 不受信任
+        assert!(check_timestamp_token(&token, &digest, None, &[])
+            .unwrap()
+            .is_err());
+        assert!(check_timestamp_token(&token, &Sha256::digest(b"other"), None, &[]).is_err());
+        let other = self_signed_tsa(&SigningKey::random(&mut OsRng), "Company TSA");
+        let error = verify_timestamp_token(&token, &digest, None, &[other]).unwrap_err();
+        assert!(error.to_string().contains("does not chain"), "{:#}", error);
+        // 同一张证书被配置为受信任后令牌有效
+        let verified = verify_timestamp_token(&token, &digest, None, &[certificate]).unwrap();
+        assert_eq!(verified.tsa_name.as_deref(), Some("CN=Rogue TSA"));
+        // 令牌不带 CRL，吊销状态未知
+        assert_eq!(verified.revocation_status, RevocationStatus::Unknown);
+    }
+}
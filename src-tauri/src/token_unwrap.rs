@@ -0,0 +1,278 @@
+// src-tauri/src/token_unwrap.rs
+// Security-key protection for private key files. A key can be tied to a registered passkey
+// whose authenticator supports the FIDO2 hmac-secret extension (WebAuthn `prf`); its file is
+// then wrapped with HMAC-SHA256(KDF(password), token secret), so it can only be decrypted
+// while the security key is present and touched.
+//
+// The webview asks for the token secret: `get_token_unwrap_request` returns the credential
+// and the salt to evaluate, the webview runs `navigator.credentials.get` with the `prf`
+// extension and hands the output to `provide_token_secret`. The secret is held for a short
+// time and used once, by the next decryption of that key.
+//
+// The security key is required in addition to the password, never instead of it: every
+// command that uses a private key asks for its password.
+use crate::audit;
+use crate::crypto_types::{KeyInfo, KeyKdf, TokenUnwrapRequest};
+use crate::device_binding::device_secret;
+use crate::key_management::{
+    find_key_metadata, get_key_storage_dir, get_metadata_path, metadata_json, read_metadata,
+    read_private_key_der_approved,
+};
+use crate::key_wrapping::{wrap_private_key_with_secrets, WrapSecrets};
+use crate::passkeys::find_passkey;
+use crate::settings::recommended_wrap_kdf;
+use crate::store_journal::StoreTransaction;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+/// Token secrets not used within this time have to be requested again.
+const TOKEN_SECRET_TIMEOUT_SECONDS: i64 = 60;
+/// Length of the hmac-secret (`prf`) output.
+const TOKEN_SECRET_LEN: usize = 32;
+const TOKEN_SALT_PREFIX: &str = "sigust-token-unwrap:";
+
+/// Token secrets provided by the webview, waiting for the key to be decrypted.
+static TOKEN_SECRETS: Mutex<Vec<PendingSecret>> = Mutex::new(Vec::new());
+
+struct PendingSecret {
+    key_id: Uuid,
+    secret: Zeroizing<Vec<u8>>,
+    expires_at: DateTime<Utc>,
+}
+
+// --- Tauri Commands ---
+
+/// Returns what the webview needs to ask the security key for the secret of `key_id`.
+/// `passkey_key_id` names the passkey when turning the protection on; otherwise the
+/// passkey the key is tied to is used.
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_token_unwrap_request(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    passkey_key_id: Option<Uuid>,
+) -> Result<TokenUnwrapRequest, String> {
+    log::info!("Preparing security key request for key ID {}", key_id);
+    _get_token_unwrap_request(&app_handle, key_id, passkey_key_id).map_err(|e| {
+        log::error!(
+            "Failed to prepare security key request for {}: {:?}",
+            key_id,
+            e
+        );
+        e.to_string()
+    })
+}
+
+fn _get_token_unwrap_request(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    passkey_key_id: Option<Uuid>,
+) -> Result<TokenUnwrapRequest> {
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let passkey_key_id = passkey_key_id
+        .or(metadata.token_passkey_id)
+        .ok_or_else(|| anyhow!("Key '{}' is not protected by a security key", metadata.name))?;
+    let passkey = find_passkey(app_handle, passkey_key_id)?
+        .ok_or_else(|| anyhow!("Passkey with ID {} not found", passkey_key_id))?;
+    Ok(TokenUnwrapRequest {
+        key_id,
+        credential_id: passkey.credential_id,
+        rp_id: passkey.rp_id,
+        salt: token_salt(key_id),
+    })
+}
+
+/// Hands over the security key's `prf` output (base64url) for the next decryption of
+/// `key_id`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn provide_token_secret(key_id: Uuid, token_secret: String) -> Result<(), String> {
+    log::info!("Received security key secret for key ID {}", key_id);
+    _provide_token_secret(key_id, &token_secret).map_err(|e| {
+        log::error!(
+            "Failed to accept security key secret for {}: {:?}",
+            key_id,
+            e
+        );
+        e.to_string()
+    })
+}
+
+fn _provide_token_secret(key_id: Uuid, token_secret: &str) -> Result<()> {
+    let secret = Zeroizing::new(
+        URL_SAFE_NO_PAD
+            .decode(token_secret)
+            .context("Malformed security key secret")?,
+    );
+    if secret.len() != TOKEN_SECRET_LEN {
+        bail!("The security key returned a secret of unexpected length");
+    }
+    let mut pending = TOKEN_SECRETS
+        .lock()
+        .map_err(|_| anyhow!("Security key secret lock poisoned"))?;
+    let now = Utc::now();
+    pending.retain(|s| s.expires_at > now && s.key_id != key_id);
+    pending.push(PendingSecret {
+        key_id,
+        secret,
+        expires_at: now + Duration::seconds(TOKEN_SECRET_TIMEOUT_SECONDS),
+    });
+    Ok(())
+}
+
+/// Ties a key to a passkey's security key (`passkey_key_id` set) or removes the tie
+/// (`None`). Both need the password and a fresh secret from the security key (see
+/// `provide_token_secret`); to switch to another security key, remove the tie first.
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_token_unwrap(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    password: String,
+    passkey_key_id: Option<Uuid>,
+) -> Result<KeyInfo, String> {
+    log::info!(
+        "Setting security key protection for key ID {} to {:?}",
+        key_id,
+        passkey_key_id
+    );
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _set_token_unwrap(&app_handle, key_id, &password, passkey_key_id)
+        .map_err(|e| {
+            log::error!(
+                "Failed to set security key protection for {}: {:?}",
+                key_id,
+                e
+            );
+            e.to_string()
+        })
+        .inspect(|_| {
+            audit::record(
+                &app_handle,
+                "setTokenUnwrap",
+                json!({ "keyId": key_id, "passkeyKeyId": passkey_key_id }),
+            )
+        })
+}
+
+fn _set_token_unwrap(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    password: &str,
+    passkey_key_id: Option<Uuid>,
+) -> Result<KeyInfo> {
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    match (metadata.token_passkey_id, passkey_key_id) {
+        (Some(current), Some(new)) if current != new => {
+            bail!("Remove the current security key before choosing a different one")
+        }
+        (Some(_), Some(_)) => bail!(
+            "Key '{}' is already protected by this security key",
+            metadata.name
+        ),
+        (None, None) => bail!("Key '{}' is not protected by a security key", metadata.name),
+        (None, Some(new)) => {
+            find_passkey(app_handle, new)?
+                .ok_or_else(|| anyhow!("Passkey with ID {} not found", new))?;
+        }
+        (Some(_), None) => {}
+    }
+
+    // 当前受保护时，解密会用掉令牌秘密；开启保护时再取一次
+    let private_key_der = Zeroizing::new(read_private_key_der_approved(
+        app_handle, &metadata, password,
+    )?);
+    let token_secret = match passkey_key_id {
+        Some(_) => Some(take_token_secret(key_id)?),
+        None => None,
+    };
+    // 保留设备绑定
+    let device_secret = if metadata.device_bound {
+        Some(device_secret()?)
+    } else {
+        None
+    };
+    let kdf = recommended_wrap_kdf(
+        app_handle,
+        metadata
+            .kdf
+            .as_ref()
+            .map_or(KeyKdf::Pbkdf2, |kdf| kdf.kind()),
+    );
+    let wrapped = wrap_private_key_with_secrets(
+        &private_key_der,
+        password,
+        kdf.clone(),
+        WrapSecrets {
+            token: token_secret.as_ref().map(|s| s.as_slice()),
+            device: device_secret.as_ref().map(|s| s.as_slice()),
+        },
+    )?;
+
+    let private_key_path =
+        get_key_storage_dir(app_handle)?.join(&metadata.encrypted_private_key_path);
+    let mut transaction = StoreTransaction::begin(app_handle, "setTokenUnwrap")?;
+    // 旧的私钥文件（按原来的解锁方式加密）及其备份都要擦除
+    transaction
+        .write_wiped(&private_key_path, &wrapped)
+        .with_context(|| {
+            format!(
+                "Failed to write encrypted private key to {:?}",
+                private_key_path
+            )
+        })?;
+    let metadata_path = get_metadata_path(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+    let entry = all_metadata
+        .iter_mut()
+        .find(|m| m.key_id == key_id)
+        .ok_or_else(|| anyhow!("Key with ID {} not found", key_id))?;
+    entry.token_passkey_id = passkey_key_id;
+    // 新文件的盐在文件头中，旧版盐不再需要
+    entry.salt_hex = None;
+    entry.kdf = Some((&kdf).into());
+    let info = KeyInfo::from(&*entry);
+    transaction.write(&metadata_path, metadata_json(&all_metadata)?)?;
+    transaction.commit()?;
+    Ok(info)
+}
+
+// --- Helper Functions ---
+
+/// Takes (and forgets) the security key secret provided for `key_id`.
+pub(crate) fn take_token_secret(key_id: Uuid) -> Result<Zeroizing<Vec<u8>>> {
+    let mut pending = TOKEN_SECRETS
+        .lock()
+        .map_err(|_| anyhow!("Security key secret lock poisoned"))?;
+    let now = Utc::now();
+    pending.retain(|s| s.expires_at > now);
+    let index = pending
+        .iter()
+        .position(|s| s.key_id == key_id)
+        .ok_or_else(|| {
+            anyhow!("This key is protected by a security key; touch the security key and try again")
+        })?;
+    Ok(pending.remove(index).secret)
+}
+
+/// Forgets all security key secrets that were not used yet.
+pub(crate) fn discard_token_secrets() -> Result<()> {
+    TOKEN_SECRETS
+        .lock()
+        .map_err(|_| anyhow!("Security key secret lock poisoned"))?
+        .clear();
+    Ok(())
+}
+
+// 每把密钥使用不同的 hmac-secret 盐
+fn token_salt(key_id: Uuid) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(
+        format!("{}{}", TOKEN_SALT_PREFIX, key_id).as_bytes(),
+    ))
+}
@@ -0,0 +1,285 @@
+// src-tauri/src/tray.rs
+// System tray icon with quick actions, so common tasks work without the main window:
+// "Sign file…" picks a document and hands it to the window for key selection, "Verify
+// file…" checks a signature against our keys and contacts, and "Quick sign" signs with any
+// key that has an unlocked session (see key_sessions.rs). Results are reported through
+// system notifications.
+use crate::audit;
+use crate::crypto_types::{DigestScheme, VerificationResult};
+use crate::key_sessions::{active_sessions, session_key, KeySessions};
+use crate::output_paths::signature_output_path;
+use crate::policy::{check_timestamping, verification_only};
+use crate::signable_inputs::check_signable_input;
+use crate::signing::{sign_bytes, verify_signatures};
+use anyhow::{anyhow, Context, Result};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{Emitter, Manager};
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_notification::NotificationExt;
+use uuid::Uuid;
+
+const TRAY_ID: &str = "main";
+const MAIN_WINDOW: &str = "main";
+/// Event carrying the path of a document picked from the tray's "Sign file…" entry.
+pub const TRAY_SIGN_FILE_EVENT: &str = "tray-sign-file";
+const QUICK_SIGN_PREFIX: &str = "quick-sign:";
+
+// --- Helper Functions ---
+
+/// Creates the tray icon (run at startup on desktop).
+pub(crate) fn setup_tray(app_handle: &tauri::AppHandle) -> tauri::Result<()> {
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("sigust")
+        .menu(&build_menu(app_handle)?)
+        .show_menu_on_left_click(true)
+        .on_menu_event(handle_menu_event);
+    if let Some(icon) = app_handle.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app_handle)?;
+    Ok(())
+}
+
+/// Rebuilds the tray menu, e.g. after key sessions were unlocked or expired.
+pub(crate) fn refresh_tray_menu(app_handle: &tauri::AppHandle) {
+    let Some(tray) = app_handle.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    if let Err(e) = build_menu(app_handle).and_then(|menu| tray.set_menu(Some(menu))) {
+        log::warn!("Failed to refresh tray menu: {}", e);
+    }
+}
+
+// 托盘菜单：快速签名子菜单列出已解锁会话的密钥；仅验证模式下没有签名项
+fn build_menu(app_handle: &tauri::AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    if verification_only() {
+        return Menu::with_items(
+            app_handle,
+            &[
+                &MenuItem::with_id(app_handle, "verify", "Verify file…", true, None::<&str>)?,
+                &PredefinedMenuItem::separator(app_handle)?,
+                &MenuItem::with_id(app_handle, "show", "Open sigust", true, None::<&str>)?,
+                &MenuItem::with_id(app_handle, "quit", "Quit", true, None::<&str>)?,
+            ],
+        );
+    }
+    let sessions = active_sessions(&app_handle.state::<KeySessions>()).unwrap_or_default();
+    let mut quick_sign_items = Vec::new();
+    for session in &sessions {
+        quick_sign_items.push(MenuItem::with_id(
+            app_handle,
+            format!("{}{}", QUICK_SIGN_PREFIX, session.key_id),
+            format!("{}…", session.name),
+            true,
+            None::<&str>,
+        )?);
+    }
+    if quick_sign_items.is_empty() {
+        quick_sign_items.push(MenuItem::with_id(
+            app_handle,
+            "quick-sign-none",
+            "No unlocked keys",
+            false,
+            None::<&str>,
+        )?);
+    }
+    let quick_sign_refs: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> = quick_sign_items
+        .iter()
+        .map(|item| item as &dyn tauri::menu::IsMenuItem<tauri::Wry>)
+        .collect();
+    let quick_sign = Submenu::with_items(app_handle, "Quick sign", true, &quick_sign_refs)?;
+
+    Menu::with_items(
+        app_handle,
+        &[
+            &MenuItem::with_id(app_handle, "sign", "Sign file…", true, None::<&str>)?,
+            &MenuItem::with_id(app_handle, "verify", "Verify file…", true, None::<&str>)?,
+            &quick_sign,
+            &PredefinedMenuItem::separator(app_handle)?,
+            &MenuItem::with_id(app_handle, "show", "Open sigust", true, None::<&str>)?,
+            &MenuItem::with_id(app_handle, "quit", "Quit", true, None::<&str>)?,
+        ],
+    )
+}
+
+// 菜单事件在主线程上触发，阻塞式文件对话框放到后台线程
+fn handle_menu_event(app_handle: &tauri::AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref().to_string();
+    match id.as_str() {
+        "show" => show_main_window(app_handle),
+        "quit" => app_handle.exit(0),
+        "sign" => in_background(app_handle, pick_document_for_window),
+        "verify" => in_background(app_handle, verify_from_tray),
+        _ => {
+            if let Some(key_id) = id
+                .strip_prefix(QUICK_SIGN_PREFIX)
+                .and_then(|id| Uuid::parse_str(id).ok())
+            {
+                in_background(app_handle, move |app_handle| quick_sign(app_handle, key_id));
+            }
+        }
+    }
+}
+
+fn in_background(
+    app_handle: &tauri::AppHandle,
+    action: impl FnOnce(&tauri::AppHandle) -> Result<()> + Send + 'static,
+) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = action(&app_handle) {
+            log::error!("Tray action failed: {:?}", e);
+            notify(&app_handle, "sigust", &format!("{:#}", e));
+        }
+    });
+}
+
+// 选择文档后交给主窗口选择密钥和输入密码
+fn pick_document_for_window(app_handle: &tauri::AppHandle) -> Result<()> {
+    let Some(document_path) = pick_file(app_handle, "Choose a document to sign", false)? else {
+        return Ok(());
+    };
+    show_main_window(app_handle);
+    app_handle
+        .emit(
+            TRAY_SIGN_FILE_EVENT,
+            document_path.to_string_lossy().into_owned(),
+        )
+        .context("Failed to hand the document to the main window")
+}
+
+// 用本地密钥和联系人验证签名，通过通知报告结果
+fn verify_from_tray(app_handle: &tauri::AppHandle) -> Result<()> {
+    let Some(document_path) = pick_file(app_handle, "Choose the signed document", false)? else {
+        return Ok(());
+    };
+    let Some(signature_path) = pick_file(app_handle, "Choose the signature file", true)? else {
+        return Ok(());
+    };
+    let report = verify_signatures(
+        app_handle.clone(),
+        document_path.to_string_lossy().into_owned(),
+        vec![signature_path.to_string_lossy().into_owned()],
+        None,
+        None,
+    )
+    .map_err(|e| anyhow!(e))?;
+    let file_name = display_name(&document_path);
+    match report.signatures.first() {
+        Some(signature) if signature.result.is_valid => notify(
+            app_handle,
+            "Signature valid",
+            &format!(
+                "{} was signed by {}.",
+                file_name,
+                signature.signer_name.as_deref().unwrap_or("a known key")
+            ),
+        ),
+        Some(signature) => notify(
+            app_handle,
+            "Signature not valid",
+            &failure_reason(&file_name, &signature.result),
+        ),
+        None => notify(app_handle, "Signature not valid", "No signature was found."),
+    }
+    Ok(())
+}
+
+// 使用已解锁会话的密钥签名，签名文件按命名模板写在文档旁边
+fn quick_sign(app_handle: &tauri::AppHandle, key_id: Uuid) -> Result<()> {
+    check_timestamping(false)?;
+    let (algorithm, private_key_der) = session_key(&app_handle.state::<KeySessions>(), key_id)?;
+    let Some(document_path) = pick_file(app_handle, "Choose a document to sign", false)? else {
+        return Ok(());
+    };
+    check_signable_input(app_handle, &document_path)?;
+    let document_bytes = fs::read(&document_path)
+        .with_context(|| format!("Failed to read document file: {:?}", document_path))?;
+    let signature_bytes = sign_bytes(
+        &algorithm,
+        &private_key_der,
+        &document_bytes,
+        DigestScheme::Standard,
+    )?;
+    let output_dir = document_path.parent().unwrap_or(Path::new("."));
+    let output_path = signature_output_path(app_handle, output_dir, &document_path, &algorithm)?;
+    fs::write(&output_path, &signature_bytes)
+        .with_context(|| format!("Failed to write signature file: {:?}", output_path))?;
+    log::info!(
+        "Quick-signed {:?} with key {}, signature saved to {:?}",
+        document_path,
+        key_id,
+        output_path
+    );
+    audit::record(
+        app_handle,
+        "quickSign",
+        json!({
+            "keyId": key_id,
+            "documentPath": document_path,
+            "outputPath": output_path,
+        }),
+    );
+    notify(
+        app_handle,
+        "Document signed",
+        &format!("Signature saved as {}.", display_name(&output_path)),
+    );
+    Ok(())
+}
+
+fn pick_file(
+    app_handle: &tauri::AppHandle,
+    title: &str,
+    signature: bool,
+) -> Result<Option<PathBuf>> {
+    let mut dialog = app_handle.dialog().file().set_title(title);
+    if signature {
+        dialog = dialog.add_filter("Signature", &["sig", "json"]);
+    }
+    dialog
+        .blocking_pick_file()
+        .map(|file| {
+            let description = file.to_string();
+            file.into_path()
+                .map_err(|_| anyhow!("Not a local file: {}", description))
+        })
+        .transpose()
+}
+
+pub(crate) fn show_main_window(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn failure_reason(file_name: &str, result: &VerificationResult) -> String {
+    match &result.error_message {
+        Some(error) => format!("{}: {}", file_name, error),
+        None => format!("The signature does not match {}.", file_name),
+    }
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+fn notify(app_handle: &tauri::AppHandle, title: &str, body: &str) {
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+    {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
@@ -0,0 +1,571 @@
+// src-tauri/src/trust_bundle.rs
+// Organization trust bundles. An administrator publishes a signed bundle of trusted keys,
+// CA certificates and, optionally, the metadata schema at an HTTPS URL; the app fetches it
+// on a schedule, checks its signature against the organization key pinned in the settings
+// and applies it to the local trust store (contacts). The file has the same shape as the
+// enterprise policy file: `{ "signed": <body>, "signature": <base64> }`, signed over the
+// canonical JSON of the body.
+//
+// Bundle entries become contacts; a CA certificate is trusted through its public key, as
+// for S/MIME chains. Contacts a bundle added are removed again once a later bundle no
+// longer lists them, while a local decision to distrust a key always wins. A bundle older
+// than the one applied is rejected, so a replayed bundle cannot bring back a revoked key.
+use crate::audit;
+use crate::contacts::{get_contacts_path, read_contacts, write_contacts};
+use crate::crypto_types::{
+    Contact, DigestScheme, MetadataSchema, SignatureAlgorithm, TrustBundleSettings,
+    TrustBundleStatus, TrustLevel,
+};
+use crate::envelope::canonical_json;
+use crate::key_exchange::encode_public_key_pem;
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, detect_public_key_algorithm, get_app_data_file,
+};
+use crate::metadata_schema::check_schema;
+use crate::settings::{get_settings_path, read_settings, update_settings};
+use crate::signing::verify_bytes;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use der::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, TryLockError};
+use std::time::Duration;
+use uuid::Uuid;
+use x509_cert::Certificate;
+
+const STATE_FILENAME: &str = "trust_bundle.json";
+const BUNDLE_FORMAT: &str = "sigust-trust-bundle";
+const BUNDLE_VERSION: u32 = 1;
+/// How often the scheduler checks whether a fetch is due.
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const MAX_INTERVAL_HOURS: u32 = 24 * 365;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_BUNDLE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Held while a bundle is fetched and applied, so scheduled and manual syncs do not overlap.
+static SYNC_LOCK: Mutex<()> = Mutex::new(());
+
+/// A bundle file: the body plus a base64 signature over its canonical JSON form.
+#[derive(Deserialize)]
+struct SignedBundleFile {
+    signed: serde_json::Value,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleBody {
+    format: String,
+    version: u32,
+    #[serde(default)]
+    organization: Option<String>,
+    issued_at: DateTime<Utc>,
+    #[serde(default)]
+    keys: Vec<BundleKey>,
+    /// CA certificates in PEM format.
+    #[serde(default)]
+    certificates: Vec<String>,
+    #[serde(default)]
+    metadata_schema: Option<MetadataSchema>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleKey {
+    name: String,
+    public_key_pem: String,
+    /// Defaults to `verified`.
+    #[serde(default)]
+    trust: Option<TrustLevel>,
+}
+
+/// A key or certificate of the bundle, ready to be merged into the contacts.
+struct BundleEntry {
+    name: String,
+    algorithm: SignatureAlgorithm,
+    public_key_pem: String,
+    fingerprint: String,
+    trust: TrustLevel,
+}
+
+/// Contents of `trust_bundle.json`.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct StoredState {
+    #[serde(flatten)]
+    status: TrustBundleStatus,
+    /// Fingerprints of the contacts the applied bundle added.
+    #[serde(default)]
+    managed_fingerprints: Vec<String>,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_trust_bundle_settings(
+    app_handle: tauri::AppHandle,
+    trust_bundle: TrustBundleSettings,
+) -> Result<TrustBundleSettings, String> {
+    log::info!("Updating trust bundle settings: {:?}", trust_bundle.url);
+    _update_trust_bundle_settings(&app_handle, trust_bundle)
+        .map_err(|e| {
+            log::error!("Failed to update trust bundle settings: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|settings| {
+            audit::record(
+                &app_handle,
+                "updateTrustBundleSettings",
+                json!({
+                    "enabled": settings.enabled,
+                    "url": settings.url,
+                    "intervalHours": settings.interval_hours,
+                    "signerFingerprint": settings
+                        .signer_public_key_pem
+                        .as_deref()
+                        .and_then(|pem| decode_public_key_pem(pem).ok())
+                        .map(|der| compute_fingerprint(&der)),
+                }),
+            )
+        })
+}
+
+fn _update_trust_bundle_settings(
+    app_handle: &tauri::AppHandle,
+    trust_bundle: TrustBundleSettings,
+) -> Result<TrustBundleSettings> {
+    if !(1..=MAX_INTERVAL_HOURS).contains(&trust_bundle.interval_hours) {
+        bail!(
+            "Interval must be between 1 and {} hours.",
+            MAX_INTERVAL_HOURS
+        );
+    }
+    let trust_bundle = TrustBundleSettings {
+        url: trust_bundle
+            .url
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty()),
+        signer_public_key_pem: trust_bundle
+            .signer_public_key_pem
+            .map(|pem| pem.trim().to_string())
+            .filter(|pem| !pem.is_empty()),
+        ..trust_bundle
+    };
+    if trust_bundle.enabled {
+        bundle_source(&trust_bundle)?;
+    }
+    update_settings(app_handle, |settings| settings.trust_bundle = trust_bundle)
+        .map(|settings| settings.trust_bundle)
+}
+
+/// Fetches and applies the bundle now.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn sync_trust_bundle(app_handle: tauri::AppHandle) -> Result<TrustBundleStatus, String> {
+    log::info!("Syncing the trust bundle");
+    bundle_settings(&app_handle)
+        .and_then(|settings| {
+            let _guard = match SYNC_LOCK.try_lock() {
+                Ok(guard) => guard,
+                Err(TryLockError::WouldBlock) => bail!("The trust bundle is already being synced."),
+                Err(TryLockError::Poisoned(_)) => bail!("Trust bundle lock poisoned"),
+            };
+            sync_bundle(&app_handle, &settings)
+        })
+        .and_then(|status| match &status.error {
+            Some(error) => Err(anyhow!("{}", error)),
+            None => Ok(status),
+        })
+        .map_err(|e| {
+            log::error!("Failed to sync the trust bundle: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_trust_bundle_status(app_handle: tauri::AppHandle) -> Result<TrustBundleStatus, String> {
+    log::info!("Getting trust bundle status");
+    get_state_path(&app_handle)
+        .and_then(|path| read_state(&path))
+        .map(|state| state.status)
+        .map_err(|e| {
+            log::error!("Failed to read trust bundle status: {:?}", e);
+            e.to_string()
+        })
+}
+
+// --- Helper Functions ---
+
+/// Starts the scheduler thread.
+pub fn start_trust_bundle_sync(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SCHEDULE_CHECK_INTERVAL);
+        if let Err(e) = sync_if_due(&app_handle) {
+            log::error!("Scheduled trust bundle sync failed: {:?}", e);
+        }
+    });
+}
+
+// 到期才拉取；手动同步进行中时跳过本轮
+fn sync_if_due(app_handle: &tauri::AppHandle) -> Result<()> {
+    let settings = bundle_settings(app_handle)?;
+    if !settings.enabled {
+        return Ok(());
+    }
+    let state = read_state(&get_state_path(app_handle)?)?;
+    let due = state.status.last_checked_at.map_or(true, |checked| {
+        checked + ChronoDuration::hours(settings.interval_hours as i64) <= Utc::now()
+    });
+    if !due {
+        return Ok(());
+    }
+    let _guard = match SYNC_LOCK.try_lock() {
+        Ok(guard) => guard,
+        Err(TryLockError::WouldBlock) => return Ok(()),
+        Err(TryLockError::Poisoned(_)) => bail!("Trust bundle lock poisoned"),
+    };
+    sync_bundle(app_handle, &settings).map(|_| ())
+}
+
+// 拉取并应用；失败时记录错误，先前应用的捆绑继续有效
+fn sync_bundle(
+    app_handle: &tauri::AppHandle,
+    settings: &TrustBundleSettings,
+) -> Result<TrustBundleStatus> {
+    let (url, signer_der) = bundle_source(settings)?;
+    let state_path = get_state_path(app_handle)?;
+    let mut state = read_state(&state_path)?;
+    let result = fetch_bundle(url)
+        .and_then(|bytes| read_bundle(&bytes, &signer_der))
+        .and_then(|body| apply_bundle(app_handle, body, &mut state));
+    state.status.last_checked_at = Some(Utc::now());
+    state.status.error = result.as_ref().err().map(|e| format!("{:#}", e));
+    write_state(&state_path, &state)?;
+    audit::record(
+        app_handle,
+        "syncTrustBundle",
+        json!({
+            "url": url,
+            "organization": state.status.organization,
+            "issuedAt": state.status.issued_at,
+            "error": state.status.error,
+        }),
+    );
+    match &state.status.error {
+        Some(error) => log::warn!("Trust bundle from {} not applied: {}", url, error),
+        None => log::info!(
+            "Trust bundle from {} applied: {} keys, {} certificates",
+            url,
+            state.status.key_count,
+            state.status.certificate_count
+        ),
+    }
+    Ok(state.status)
+}
+
+fn fetch_bundle(url: &str) -> Result<Vec<u8>> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .build()
+        .into();
+    agent
+        .get(url)
+        .call()
+        .with_context(|| format!("Failed to fetch the trust bundle from {}", url))?
+        .body_mut()
+        .with_config()
+        .limit(MAX_BUNDLE_SIZE)
+        .read_to_vec()
+        .context("Failed to read the trust bundle")
+}
+
+// 校验捆绑签名（固定的组织密钥）并解析内容
+fn read_bundle(bytes: &[u8], signer_der: &[u8]) -> Result<BundleBody> {
+    let file: SignedBundleFile = serde_json::from_slice(bytes).context("Malformed trust bundle")?;
+    let signature = BASE64
+        .decode(&file.signature)
+        .context("Malformed trust bundle signature")?;
+    verify_bytes(
+        &detect_public_key_algorithm(signer_der)?,
+        signer_der,
+        &canonical_json(&file.signed)?,
+        &signature,
+        DigestScheme::Standard,
+    )?
+    .map_err(|_| anyhow!("The trust bundle is not signed by the organization key"))?;
+
+    let body: BundleBody = serde_json::from_value(file.signed).context("Malformed trust bundle")?;
+    if body.format != BUNDLE_FORMAT || body.version != BUNDLE_VERSION {
+        bail!(
+            "Unsupported trust bundle: {} v{}",
+            body.format,
+            body.version
+        );
+    }
+    Ok(body)
+}
+
+fn apply_bundle(
+    app_handle: &tauri::AppHandle,
+    body: BundleBody,
+    state: &mut StoredState,
+) -> Result<()> {
+    if let Some(applied) = state.status.issued_at {
+        if body.issued_at < applied {
+            bail!(
+                "The trust bundle was issued at {}, before the one applied ({})",
+                body.issued_at,
+                applied
+            );
+        }
+    }
+    let entries = bundle_entries(&body)?;
+    if let Some(schema) = &body.metadata_schema {
+        check_schema(schema).context("Invalid metadata schema in the trust bundle")?;
+    }
+
+    let contacts_path = get_contacts_path(app_handle)?;
+    let mut contacts = read_contacts(&contacts_path)?;
+    let managed = merge_entries(
+        &mut contacts,
+        entries,
+        &state.managed_fingerprints,
+        Utc::now(),
+    );
+    write_contacts(&contacts_path, &contacts)?;
+    if let Some(schema) = body.metadata_schema.clone() {
+        update_settings(app_handle, |settings| settings.metadata_schema = schema)?;
+    }
+
+    state.managed_fingerprints = managed;
+    state.status = TrustBundleStatus {
+        organization: body.organization,
+        issued_at: Some(body.issued_at),
+        applied_at: Some(Utc::now()),
+        key_count: body.keys.len(),
+        certificate_count: body.certificates.len(),
+        metadata_schema_applied: body.metadata_schema.is_some(),
+        ..Default::default()
+    };
+    Ok(())
+}
+
+// 捆绑中的密钥与 CA 证书（按公钥）；指纹与算法一律重新计算
+fn bundle_entries(body: &BundleBody) -> Result<Vec<BundleEntry>> {
+    let mut entries = Vec::new();
+    for key in &body.keys {
+        let der = decode_public_key_pem(&key.public_key_pem).with_context(|| {
+            format!("Invalid public key for '{}' in the trust bundle", key.name)
+        })?;
+        entries.push(BundleEntry {
+            name: key.name.clone(),
+            algorithm: detect_public_key_algorithm(&der)?,
+            public_key_pem: encode_public_key_pem(&der)?,
+            fingerprint: compute_fingerprint(&der),
+            trust: key.trust.clone().unwrap_or(TrustLevel::Verified),
+        });
+    }
+    for pem in &body.certificates {
+        let (label, der) = pem_rfc7468::decode_vec(pem.trim().as_bytes())
+            .map_err(|e| anyhow!("Invalid certificate in the trust bundle: {}", e))?;
+        if label != "CERTIFICATE" {
+            bail!(
+                "Expected a certificate in the trust bundle, found {}",
+                label
+            );
+        }
+        let certificate = Certificate::from_der(&der)
+            .map_err(|e| anyhow!("Invalid certificate in the trust bundle: {}", e))?;
+        let subject = certificate.tbs_certificate.subject.to_string();
+        let spki_der = certificate
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .map_err(|e| anyhow!("Invalid certificate key: {}", e))?;
+        entries.push(BundleEntry {
+            algorithm: detect_public_key_algorithm(&spki_der)
+                .with_context(|| format!("Unsupported key in certificate {}", subject))?,
+            public_key_pem: encode_public_key_pem(&spki_der)?,
+            fingerprint: compute_fingerprint(&spki_der),
+            name: subject,
+            trust: TrustLevel::Verified,
+        });
+    }
+    Ok(entries)
+}
+
+/// Merges bundle entries into the contacts and returns the fingerprints of the contacts
+/// the bundle now manages.
+fn merge_entries(
+    contacts: &mut Vec<Contact>,
+    entries: Vec<BundleEntry>,
+    previously_managed: &[String],
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    let listed: HashSet<&str> = entries.iter().map(|e| e.fingerprint.as_str()).collect();
+    // 上一版捆绑添加、本版不再列出的联系人移除
+    contacts.retain(|contact| {
+        listed.contains(contact.fingerprint.as_str())
+            || !previously_managed.contains(&contact.fingerprint)
+    });
+
+    let mut managed = Vec::new();
+    for entry in entries {
+        if managed.contains(&entry.fingerprint) {
+            continue;
+        }
+        match contacts
+            .iter_mut()
+            .find(|c| c.fingerprint == entry.fingerprint)
+        {
+            Some(existing) => {
+                if previously_managed.contains(&entry.fingerprint) {
+                    managed.push(entry.fingerprint);
+                }
+                // 本地的不信任决定优先
+                if existing.trust != TrustLevel::Distrusted && existing.trust != entry.trust {
+                    existing.trust = entry.trust;
+                    existing.updated_at = now;
+                }
+            }
+            None => {
+                contacts.push(Contact {
+                    contact_id: Uuid::new_v4(),
+                    name: entry.name,
+                    algorithm: entry.algorithm,
+                    public_key_pem: entry.public_key_pem,
+                    fingerprint: entry.fingerprint.clone(),
+                    trust: entry.trust,
+                    added_at: now,
+                    updated_at: now,
+                });
+                managed.push(entry.fingerprint);
+            }
+        }
+    }
+    managed
+}
+
+fn bundle_settings(app_handle: &tauri::AppHandle) -> Result<TrustBundleSettings> {
+    Ok(read_settings(&get_settings_path(app_handle)?)?.trust_bundle)
+}
+
+// 捆绑地址与固定的组织公钥（DER）
+fn bundle_source(settings: &TrustBundleSettings) -> Result<(&str, Vec<u8>)> {
+    let url = settings
+        .url
+        .as_deref()
+        .ok_or_else(|| anyhow!("No trust bundle URL is configured."))?;
+    if !url.starts_with("https://") {
+        bail!("The trust bundle URL must be an https URL.");
+    }
+    let pem = settings
+        .signer_public_key_pem
+        .as_deref()
+        .ok_or_else(|| anyhow!("No organization key is pinned for the trust bundle."))?;
+    let signer_der = decode_public_key_pem(pem).context("Invalid organization key")?;
+    detect_public_key_algorithm(&signer_der).context("Invalid organization key")?;
+    Ok((url, signer_der))
+}
+
+// 获取捆绑状态文件路径
+fn get_state_path(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    get_app_data_file(app_handle, STATE_FILENAME)
+}
+
+fn read_state(path: &Path) -> Result<StoredState> {
+    if !path.exists() {
+        return Ok(StoredState::default());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read trust bundle state: {:?}", path))?;
+    serde_json::from_str(&content).context("Failed to parse trust bundle state JSON")
+}
+
+fn write_state(path: &Path, state: &StoredState) -> Result<()> {
+    let content =
+        serde_json::to_string_pretty(state).context("Failed to serialize trust bundle state")?;
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write trust bundle state: {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::sign_bytes;
+    use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey};
+    use pkcs8::LineEnding;
+
+    #[test]
+    fn verifies_bundles_and_tracks_the_contacts_they_add() {
+        let organization = ed25519_dalek::SigningKey::from_bytes(&[3; 32]);
+        let organization_der = organization
+            .verifying_key()
+            .to_public_key_der()
+            .unwrap()
+            .into_vec();
+        let key_pem = |seed: u8| {
+            ed25519_dalek::SigningKey::from_bytes(&[seed; 32])
+                .verifying_key()
+                .to_public_key_pem(LineEnding::LF)
+                .unwrap()
+        };
+        let signed = json!({
+            "format": BUNDLE_FORMAT,
+            "version": BUNDLE_VERSION,
+            "issuedAt": "2026-01-01T00:00:00Z",
+            "keys": [
+                { "name": "Release key", "publicKeyPem": key_pem(1) },
+                { "name": "Old key", "publicKeyPem": key_pem(2), "trust": "distrusted" },
+            ],
+        });
+        let signature = sign_bytes(
+            &SignatureAlgorithm::Ed25519,
+            organization.to_pkcs8_der().unwrap().as_bytes(),
+            &canonical_json(&signed).unwrap(),
+            DigestScheme::Standard,
+        )
+        .unwrap();
+        let file = |signed: &serde_json::Value| {
+            json!({ "signed": signed, "signature": BASE64.encode(&signature) }).to_string()
+        };
+        let body = read_bundle(file(&signed).as_bytes(), &organization_der).unwrap();
+        let mut tampered = signed.clone();
+        tampered["keys"][1]["trust"] = json!("verified");
+        assert!(read_bundle(file(&tampered).as_bytes(), &organization_der).is_err());
+
+        // 用户已有的联系人不归捆绑管理，本地不信任决定保留
+        let now = Utc::now();
+        let mut entries = bundle_entries(&body).unwrap();
+        let own = Contact {
+            contact_id: Uuid::new_v4(),
+            name: "Old key".to_string(),
+            algorithm: SignatureAlgorithm::Ed25519,
+            public_key_pem: entries[1].public_key_pem.clone(),
+            fingerprint: entries[1].fingerprint.clone(),
+            trust: TrustLevel::Verified,
+            added_at: now,
+            updated_at: now,
+        };
+        let mut contacts = vec![own];
+        let managed = merge_entries(&mut contacts, entries, &[], now);
+        assert_eq!(managed.len(), 1);
+        assert_eq!(contacts.len(), 2);
+        assert_eq!(contacts[0].trust, TrustLevel::Distrusted);
+        assert_eq!(contacts[1].trust, TrustLevel::Verified);
+
+        // 下一版不再列出发布密钥：由捆绑添加的联系人被移除
+        entries = bundle_entries(&body).unwrap();
+        entries.remove(0);
+        let managed = merge_entries(&mut contacts, entries, &managed, now);
+        assert!(managed.is_empty());
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].name, "Old key");
+    }
+}
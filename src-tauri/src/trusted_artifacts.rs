@@ -0,0 +1,246 @@
+// src-tauri/src/trusted_artifacts.rs
+// Verification of downloaded updates and plugins against the publisher's key. Artifacts
+// are signed with minisign (prehashed Ed25519, `minisign -S -H`); the publisher public key
+// is embedded at build time from the `SIGUST_PUBLISHER_KEY` environment variable (the
+// contents of the minisign `.pub` file or just its key line). Builds without it refuse to
+// trust any artifact.
+//
+// Both signatures in the `.minisig` file are checked: the one over the artifact's
+// BLAKE2b-512 digest and the global one over the trusted comment. A `file:` entry in the
+// trusted comment must name the artifact, so a signed file cannot be passed off under
+// another name.
+use crate::audit;
+use crate::crypto_types::TrustedArtifact;
+use crate::native_paths::native_path;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use blake2::{Blake2b512, Digest as _};
+use chrono::DateTime;
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde_json::json;
+use sha2::Sha256;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Publisher key embedded at build time.
+const PUBLISHER_KEY: Option<&str> = option_env!("SIGUST_PUBLISHER_KEY");
+const MAX_SIGNATURE_FILE_LEN: u64 = 4096;
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+// --- Tauri Commands ---
+
+/// Checks that `artifact_path` was signed by the embedded publisher key. The signature is
+/// read from `signature_path`, or from `<artifact>.minisig` next to the artifact.
+#[tauri::command(rename_all = "camelCase")]
+pub fn verify_trusted_artifact(
+    app_handle: tauri::AppHandle,
+    artifact_path: String,
+    signature_path: Option<String>,
+) -> Result<TrustedArtifact, String> {
+    log::info!("Verifying publisher signature of '{}'", artifact_path);
+    let artifact_path = native_path(&artifact_path).map_err(|e| e.to_string())?;
+    let signature_path = match signature_path {
+        Some(path) => native_path(&path).map_err(|e| e.to_string())?,
+        None => {
+            let mut path = artifact_path.clone().into_os_string();
+            path.push(".minisig");
+            PathBuf::from(path)
+        }
+    };
+    _verify_trusted_artifact(&artifact_path, &signature_path)
+        .map_err(|e| {
+            log::error!(
+                "Publisher signature check failed for {:?}: {:?}",
+                artifact_path,
+                e
+            );
+            e.to_string()
+        })
+        .inspect(|artifact| {
+            audit::record(
+                &app_handle,
+                "verifyTrustedArtifact",
+                json!({
+                    "fileName": artifact.file_name,
+                    "sha256": artifact.sha256,
+                    "keyId": artifact.key_id,
+                }),
+            )
+        })
+}
+
+fn _verify_trusted_artifact(
+    artifact_path: &Path,
+    signature_path: &Path,
+) -> Result<TrustedArtifact> {
+    let publisher_key = PUBLISHER_KEY
+        .ok_or_else(|| anyhow!("This build has no publisher key; updates cannot be verified"))?;
+    if fs::metadata(signature_path)
+        .with_context(|| format!("Failed to read signature file {:?}", signature_path))?
+        .len()
+        > MAX_SIGNATURE_FILE_LEN
+    {
+        bail!("The signature file is too large");
+    }
+    let signature = fs::read_to_string(signature_path)
+        .with_context(|| format!("Failed to read signature file {:?}", signature_path))?;
+    let file_name = artifact_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("Invalid artifact path"))?;
+    let file = File::open(artifact_path)
+        .with_context(|| format!("Failed to open artifact {:?}", artifact_path))?;
+    verify_minisign(publisher_key, &file_name, file, &signature)
+}
+
+// --- Helper Functions ---
+
+/// A minisign Ed25519 public key.
+struct MinisignKey {
+    key_id: [u8; 8],
+    key: VerifyingKey,
+}
+
+// 公钥：base64("Ed" || 密钥 ID (8) || Ed25519 公钥 (32))，可带 untrusted comment 行
+fn parse_minisign_key(text: &str) -> Result<MinisignKey> {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+        .ok_or_else(|| anyhow!("Malformed publisher key"))?;
+    let decoded = BASE64.decode(line).context("Malformed publisher key")?;
+    if decoded.len() != 42 || !decoded.starts_with(b"Ed") {
+        bail!("Unsupported publisher key");
+    }
+    Ok(MinisignKey {
+        key_id: decoded[2..10].try_into()?,
+        key: VerifyingKey::from_bytes(decoded[10..].try_into()?)
+            .context("Invalid publisher key")?,
+    })
+}
+
+fn verify_minisign(
+    public_key: &str,
+    file_name: &str,
+    mut artifact: impl Read,
+    signature_file: &str,
+) -> Result<TrustedArtifact> {
+    let publisher = parse_minisign_key(public_key)?;
+    // 可信注释按原样签名，只去掉行尾的 \r
+    let mut lines = signature_file
+        .lines()
+        .map(|line| line.trim_end_matches('\r'));
+    let signature = lines
+        .nth(1)
+        .map(|line| BASE64.decode(line))
+        .transpose()
+        .context("Malformed minisign signature")?
+        .filter(|decoded| decoded.len() == 74)
+        .ok_or_else(|| anyhow!("Malformed minisign signature"))?;
+    let trusted_comment = lines
+        .next()
+        .and_then(|line| line.strip_prefix("trusted comment: "))
+        .ok_or_else(|| anyhow!("The minisign signature has no trusted comment"))?;
+    let global_signature = lines
+        .next()
+        .map(|line| BASE64.decode(line))
+        .transpose()
+        .context("Malformed minisign signature")?
+        .ok_or_else(|| anyhow!("The minisign signature has no global signature"))?;
+
+    if &signature[..2] != b"ED" {
+        bail!("Only prehashed minisign signatures (minisign -H) are accepted");
+    }
+    if signature[2..10] != publisher.key_id {
+        bail!("The artifact was not signed with the publisher key");
+    }
+
+    // 流式计算 BLAKE2b-512（签名用）和 SHA-256（报告用）
+    let mut blake2 = Blake2b512::new();
+    let mut sha256 = Sha256::new();
+    let mut size = 0u64;
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    loop {
+        let read = artifact
+            .read(&mut buffer)
+            .context("Failed to read artifact")?;
+        if read == 0 {
+            break;
+        }
+        blake2.update(&buffer[..read]);
+        sha256.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    let artifact_signature = Signature::from_slice(&signature[10..])?;
+    publisher
+        .key
+        .verify_strict(&blake2.finalize(), &artifact_signature)
+        .map_err(|_| anyhow!("The publisher signature of the artifact is not valid"))?;
+    let mut signed_comment = artifact_signature.to_bytes().to_vec();
+    signed_comment.extend_from_slice(trusted_comment.as_bytes());
+    publisher
+        .key
+        .verify_strict(&signed_comment, &Signature::from_slice(&global_signature)?)
+        .map_err(|_| anyhow!("The trusted comment of the signature is not valid"))?;
+
+    if let Some(signed_name) = trusted_comment
+        .split('\t')
+        .find_map(|field| field.strip_prefix("file:"))
+    {
+        if signed_name != file_name {
+            bail!(
+                "The signature is for '{}', not '{}'",
+                signed_name,
+                file_name
+            );
+        }
+    }
+    Ok(TrustedArtifact {
+        file_name: file_name.to_string(),
+        size,
+        sha256: hex::encode(sha256.finalize()),
+        key_id: format!("{:016X}", u64::from_le_bytes(publisher.key_id)),
+        trusted_comment: trusted_comment.to_string(),
+        signed_at: trusted_comment
+            .split_whitespace()
+            .find_map(|field| field.strip_prefix("timestamp:"))
+            .and_then(|seconds| seconds.parse::<i64>().ok())
+            .and_then(|seconds| DateTime::from_timestamp(seconds, 0)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn verifies_prehashed_minisign_signatures() {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let key_id = [1, 2, 3, 4, 5, 6, 7, 8];
+        let public_key =
+            BASE64.encode([&b"Ed"[..], &key_id, signing_key.verifying_key().as_bytes()].concat());
+        let artifact = b"sigust update payload";
+        let signature = signing_key.sign(&Blake2b512::digest(artifact));
+        let comment = "timestamp:1700000000\tfile:sigust.tar.gz";
+        let global = signing_key.sign(&[&signature.to_bytes()[..], comment.as_bytes()].concat());
+        let minisig = format!(
+            "untrusted comment: signature\n{}\ntrusted comment: {}\n{}\n",
+            BASE64.encode([&b"ED"[..], &key_id, &signature.to_bytes()].concat()),
+            comment,
+            BASE64.encode(global.to_bytes())
+        );
+
+        let verified =
+            verify_minisign(&public_key, "sigust.tar.gz", &artifact[..], &minisig).unwrap();
+        assert_eq!(verified.size, artifact.len() as u64);
+        assert!(verified.signed_at.is_some());
+        assert!(verify_minisign(&public_key, "other.tar.gz", &artifact[..], &minisig).is_err());
+        assert!(verify_minisign(&public_key, "sigust.tar.gz", &b"tampered"[..], &minisig).is_err());
+        let forged = minisig.replace("sigust.tar.gz", "other.tar.gz");
+        assert!(verify_minisign(&public_key, "other.tar.gz", &artifact[..], &forged).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+// src-tauri/src/unlock_policy.rs
+// Per-key unlock policy. By default a key may be unlocked into a key session (see
+// `key_sessions.rs`) and then used without its password for a while. A stricter policy
+// keeps the key out of sessions, so every use asks for the password; the biometric policy
+// additionally needs a fresh user-verifying passkey assertion (fingerprint, face or PIN on
+// the platform authenticator) for each decryption of the key.
+//
+// The policy is enforced where private keys are decrypted (`read_private_key_der_approved`)
+// and where sessions are created, so every signing path is covered. Biometric confirmation
+// works like a passkey signature: `begin_unlock_confirmation` returns a challenge, the
+// webview asks the authenticator for an assertion and hands it to
+// `finish_unlock_confirmation`; the confirmation is then used once, by the next decryption
+// of that key.
+use crate::audit;
+use crate::crypto_types::{KeyInfo, KeyMetadata, KeyUnlockPolicy, PasskeyChallenge};
+use crate::envelope::WebAuthnAssertion;
+use crate::key_management::{
+    decode_public_key_pem, find_key_metadata, get_metadata_path, read_metadata,
+    read_private_key_der_approved, write_metadata,
+};
+use crate::key_sessions::{end_key_session, KeySessions};
+use crate::passkeys::{find_passkey, user_verified, verify_assertion};
+use aead::OsRng;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use rsa::rand_core::RngCore;
+use serde_json::json;
+use std::sync::Mutex;
+use tauri::Manager;
+use uuid::Uuid;
+
+/// Challenges not answered, and confirmations not used, within this time expire.
+const CONFIRMATION_TIMEOUT_SECONDS: i64 = 120;
+
+/// Biometric confirmations waiting for the authenticator or for the key to be decrypted.
+static UNLOCK_CONFIRMATIONS: Mutex<Vec<UnlockConfirmation>> = Mutex::new(Vec::new());
+
+struct UnlockConfirmation {
+    key_id: Uuid,
+    /// Base64url challenge handed to the webview.
+    challenge: String,
+    confirmed: bool,
+    expires_at: DateTime<Utc>,
+}
+
+// --- Tauri Commands ---
+
+/// Sets the unlock policy of a key (`None` allows key sessions again). Needs the key's
+/// password, and a biometric confirmation if the current policy asks for one.
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_key_unlock_policy(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+    password: String,
+    policy: Option<KeyUnlockPolicy>,
+) -> Result<KeyInfo, String> {
+    log::info!("Setting unlock policy of key ID {} to {:?}", key_id, policy);
+    if password.is_empty() {
+        return Err("Password cannot be empty.".to_string());
+    }
+    _set_key_unlock_policy(&app_handle, key_id, &password, policy.clone())
+        .map_err(|e| {
+            log::error!("Failed to set unlock policy of {}: {:?}", key_id, e);
+            e.to_string()
+        })
+        .inspect(|_| {
+            audit::record(
+                &app_handle,
+                "setKeyUnlockPolicy",
+                json!({ "keyId": key_id, "policy": policy }),
+            )
+        })
+}
+
+fn _set_key_unlock_policy(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+    password: &str,
+    policy: Option<KeyUnlockPolicy>,
+) -> Result<KeyInfo> {
+    if let Some(KeyUnlockPolicy::Biometric { passkey_key_id }) = &policy {
+        find_passkey(app_handle, *passkey_key_id)?
+            .ok_or_else(|| anyhow!("Passkey with ID {} not found", passkey_key_id))?;
+    }
+    // 用当前策略解密一次，证明调用方能使用这把密钥
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    read_private_key_der_approved(app_handle, &metadata, password)?;
+
+    let metadata_path = get_metadata_path(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+    let entry = all_metadata
+        .iter_mut()
+        .find(|m| m.key_id == key_id)
+        .ok_or_else(|| anyhow!("Key with ID {} not found", key_id))?;
+    entry.unlock_policy = policy;
+    let info = KeyInfo::from(&*entry);
+    write_metadata(&metadata_path, &all_metadata)?;
+    if info.unlock_policy.is_some() {
+        end_key_session(app_handle, &app_handle.state::<KeySessions>(), key_id)?;
+    }
+    Ok(info)
+}
+
+/// Starts a biometric confirmation for the next use of `key_id`. The webview passes the
+/// challenge, credential ID and relying party ID to `navigator.credentials.get` with
+/// `userVerification: "required"`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn begin_unlock_confirmation(
+    app_handle: tauri::AppHandle,
+    key_id: Uuid,
+) -> Result<PasskeyChallenge, String> {
+    log::info!("Starting biometric confirmation for key ID {}", key_id);
+    _begin_unlock_confirmation(&app_handle, key_id).map_err(|e| {
+        log::error!(
+            "Failed to start biometric confirmation for {}: {:?}",
+            key_id,
+            e
+        );
+        e.to_string()
+    })
+}
+
+fn _begin_unlock_confirmation(
+    app_handle: &tauri::AppHandle,
+    key_id: Uuid,
+) -> Result<PasskeyChallenge> {
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let passkey_key_id = biometric_passkey(&metadata).ok_or_else(|| {
+        anyhow!(
+            "Key '{}' does not need biometric confirmation",
+            metadata.name
+        )
+    })?;
+    let passkey = find_passkey(app_handle, passkey_key_id)?
+        .ok_or_else(|| anyhow!("Passkey with ID {} not found", passkey_key_id))?;
+
+    let mut challenge = [0u8; 32];
+    OsRng.fill_bytes(&mut challenge);
+    let challenge = URL_SAFE_NO_PAD.encode(challenge);
+    let mut pending = lock_confirmations()?;
+    let now = Utc::now();
+    pending.retain(|c| c.expires_at > now && c.key_id != key_id);
+    pending.push(UnlockConfirmation {
+        key_id,
+        challenge: challenge.clone(),
+        confirmed: false,
+        expires_at: now + Duration::seconds(CONFIRMATION_TIMEOUT_SECONDS),
+    });
+    Ok(PasskeyChallenge {
+        challenge,
+        key_id: passkey.key_id,
+        credential_id: Some(passkey.credential_id),
+        rp_id: Some(passkey.rp_id),
+    })
+}
+
+/// Completes a biometric confirmation with the authenticator's assertion (all base64url).
+#[tauri::command(rename_all = "camelCase")]
+pub fn finish_unlock_confirmation(
+    app_handle: tauri::AppHandle,
+    challenge: String,
+    credential_id: String,
+    authenticator_data: String,
+    client_data_json: String,
+    signature: String,
+) -> Result<(), String> {
+    log::info!("Finishing biometric confirmation");
+    _finish_unlock_confirmation(
+        &app_handle,
+        &challenge,
+        &credential_id,
+        WebAuthnAssertion {
+            rp_id: String::new(),
+            authenticator_data,
+            client_data_json,
+        },
+        &signature,
+    )
+    .map_err(|e| {
+        log::error!("Failed to confirm key use: {:?}", e);
+        e.to_string()
+    })
+    .map(|key_id| audit::record(&app_handle, "confirmKeyUnlock", json!({ "keyId": key_id })))
+}
+
+fn _finish_unlock_confirmation(
+    app_handle: &tauri::AppHandle,
+    challenge: &str,
+    credential_id: &str,
+    mut assertion: WebAuthnAssertion,
+    signature: &str,
+) -> Result<Uuid> {
+    let key_id = lock_confirmations()?
+        .iter()
+        .find(|c| c.challenge == challenge && !c.confirmed && c.expires_at > Utc::now())
+        .map(|c| c.key_id)
+        .ok_or_else(|| anyhow!("The confirmation request expired; start again"))?;
+    let metadata = find_key_metadata(app_handle, key_id)?;
+    let passkey_key_id = biometric_passkey(&metadata).ok_or_else(|| {
+        anyhow!(
+            "Key '{}' does not need biometric confirmation",
+            metadata.name
+        )
+    })?;
+    let passkey = find_passkey(app_handle, passkey_key_id)?
+        .ok_or_else(|| anyhow!("Passkey with ID {} not found", passkey_key_id))?;
+    if passkey.credential_id != credential_id {
+        bail!("The assertion comes from a different passkey");
+    }
+    assertion.rp_id = passkey.rp_id;
+
+    // 必须由认证器验证用户（生物识别或 PIN），仅确认在场不够
+    if !user_verified(&assertion)? {
+        bail!("The authenticator did not verify the user (PIN or biometrics)");
+    }
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .context("Malformed passkey signature")?;
+    let challenge_bytes = URL_SAFE_NO_PAD
+        .decode(challenge)
+        .context("Malformed challenge")?;
+    verify_assertion(
+        &decode_public_key_pem(&passkey.public_key_pem)?,
+        &assertion,
+        &signature,
+        &challenge_bytes,
+    )?
+    .map_err(|_| anyhow!("The passkey assertion is not valid"))?;
+
+    let mut pending = lock_confirmations()?;
+    let confirmation = pending
+        .iter_mut()
+        .find(|c| c.challenge == challenge)
+        .ok_or_else(|| anyhow!("The confirmation request expired; start again"))?;
+    confirmation.confirmed = true;
+    confirmation.expires_at = Utc::now() + Duration::seconds(CONFIRMATION_TIMEOUT_SECONDS);
+    Ok(key_id)
+}
+
+// --- Helper Functions ---
+
+/// Checks the unlock policy before a key is decrypted; uses up a biometric confirmation.
+pub(crate) fn check_unlock_policy(metadata: &KeyMetadata) -> Result<()> {
+    if biometric_passkey(metadata).is_none() {
+        return Ok(());
+    }
+    let mut pending = lock_confirmations()?;
+    let now = Utc::now();
+    pending.retain(|c| c.expires_at > now);
+    let index = pending
+        .iter()
+        .position(|c| c.key_id == metadata.key_id && c.confirmed)
+        .ok_or_else(|| {
+            anyhow!(
+                "Key '{}' needs biometric confirmation; confirm with the passkey and try again",
+                metadata.name
+            )
+        })?;
+    pending.remove(index);
+    Ok(())
+}
+
+/// Drops all pending and unused biometric confirmations.
+pub(crate) fn discard_unlock_confirmations() -> Result<()> {
+    lock_confirmations()?.clear();
+    Ok(())
+}
+
+/// Whether the key may be kept unlocked in a key session.
+pub(crate) fn allows_session(metadata: &KeyMetadata) -> bool {
+    metadata.unlock_policy.is_none()
+}
+
+// 生物识别策略所用的通行密钥
+fn biometric_passkey(metadata: &KeyMetadata) -> Option<Uuid> {
+    match metadata.unlock_policy {
+        Some(KeyUnlockPolicy::Biometric { passkey_key_id }) => Some(passkey_key_id),
+        _ => None,
+    }
+}
+
+fn lock_confirmations() -> Result<std::sync::MutexGuard<'static, Vec<UnlockConfirmation>>> {
+    UNLOCK_CONFIRMATIONS
+        .lock()
+        .map_err(|_| anyhow!("Unlock confirmation lock poisoned"))
+}
@@ -0,0 +1,427 @@
+// src-tauri/src/vault.rs
+// Single-file encrypted key vault: an alternative to copying the loose `.pub.pem` /
+// `.key.enc` files and `key_metadata.json` around for backup.
+//
+// The file starts with a JSON header line (format, version, salt). Every following line
+// is one record, base64 of `nonce || AES-256-GCM(JSON)`, encrypted with a key derived from
+// the vault password. Records are only ever appended: a key that was added or changed gets
+// a `put` record, a removed key a `delete` record, and replaying them gives the vault
+// contents. Each record uses the SHA-256 of the previous line as associated data, so
+// editing, reordering or removing a record in the middle makes the vault fail to open.
+// Dropping records from the end cannot be detected without a trusted record count.
+//
+// Private keys inside the vault stay wrapped with their own passwords.
+use crate::audit;
+use crate::consent::require_consent;
+use crate::crypto_types::{ConsentAction, KeyVaultReport};
+use crate::key_management::{
+    derive_encryption_key, get_key_storage_dir, get_metadata_path, metadata_json, read_metadata,
+    NONCE_LEN, SALT_LEN,
+};
+use crate::native_paths::{native_path, path_string};
+use crate::policy::{check_private_key_export, check_private_keys_allowed};
+use crate::store_journal::StoreTransaction;
+use crate::sync::{hash_synced_key, read_local_keys, store_synced_key, SyncedKey};
+use aead::{AeadMutInPlace, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use rsa::rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use uuid::Uuid;
+
+pub(crate) const VAULT_FORMAT: &str = "sigust-vault";
+const VAULT_VERSION: u32 = 1;
+
+/// First line of a vault file.
+#[derive(Serialize, Deserialize)]
+struct VaultHeader {
+    format: String,
+    version: u32,
+    salt_hex: String,
+}
+
+/// One decrypted vault record.
+#[derive(Serialize, Deserialize)]
+struct VaultRecord {
+    at: DateTime<Utc>,
+    change: VaultChange,
+}
+
+#[derive(Serialize, Deserialize)]
+enum VaultChange {
+    /// First record of every vault; lets an empty vault still check the password.
+    Created,
+    Put(Box<SyncedKey>),
+    Delete(Uuid),
+}
+
+/// An opened vault: replayed contents plus what is needed to append to it.
+struct OpenVault {
+    cipher: Aes256Gcm,
+    keys: HashMap<Uuid, SyncedKey>,
+    /// SHA-256 of the last line, the associated data of the next record.
+    last_line_hash: [u8; 32],
+    records: usize,
+}
+
+impl OpenVault {
+    fn apply(&mut self, change: VaultChange) {
+        match change {
+            VaultChange::Created => {}
+            VaultChange::Put(key) => {
+                self.keys.insert(key.metadata.key_id, *key);
+            }
+            VaultChange::Delete(key_id) => {
+                self.keys.remove(&key_id);
+            }
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn save_key_vault(
+    app_handle: tauri::AppHandle,
+    vault_path: String,
+    vault_password: String,
+) -> Result<KeyVaultReport, String> {
+    log::info!("Saving key store to vault '{}'", vault_path);
+    if vault_password.is_empty() {
+        return Err("Vault password cannot be empty.".to_string());
+    }
+    native_path(&vault_path)
+        .and_then(|path| _save_key_vault(&app_handle, &path, &vault_password))
+        .map_err(|e| {
+            log::error!("Failed to save key vault: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|report| {
+            audit::record(
+                &app_handle,
+                "saveKeyVault",
+                json!({ "vaultPath": vault_path, "appendedRecords": report.appended_records }),
+            )
+        })
+}
+
+fn _save_key_vault(
+    app_handle: &tauri::AppHandle,
+    vault_path: &Path,
+    vault_password: &str,
+) -> Result<KeyVaultReport> {
+    check_private_key_export()?;
+    require_consent(
+        app_handle,
+        ConsentAction::ExportPrivateKey,
+        &path_string(vault_path),
+        format!("Save all private keys to the vault {:?}.", vault_path),
+    )?;
+    let mut vault = open_or_create_vault(vault_path, vault_password)?;
+
+    // 与本地存储比较：新增或改动的密钥写 put，本地已删除的写 delete
+    let local = read_local_keys(app_handle)?;
+    let mut changes = changed_keys(&vault, local.values())?;
+    for key_id in vault.keys.keys() {
+        if !local.contains_key(key_id) {
+            changes.push(VaultChange::Delete(*key_id));
+        }
+    }
+
+    let appended_records = changes.len();
+    append_records(vault_path, &mut vault, changes)?;
+    log::info!(
+        "Appended {} records to key vault {:?}",
+        appended_records,
+        vault_path
+    );
+    Ok(KeyVaultReport {
+        key_count: local.len(),
+        record_count: vault.records,
+        appended_records,
+        restored: Vec::new(),
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn restore_key_vault(
+    app_handle: tauri::AppHandle,
+    vault_path: String,
+    vault_password: String,
+) -> Result<KeyVaultReport, String> {
+    log::info!("Restoring key store from vault '{}'", vault_path);
+    if vault_password.is_empty() {
+        return Err("Vault password cannot be empty.".to_string());
+    }
+    native_path(&vault_path)
+        .and_then(|path| _restore_key_vault(&app_handle, &path, &vault_password))
+        .map_err(|e| {
+            log::error!("Failed to restore key vault: {:?}", e);
+            e.to_string()
+        })
+        .inspect(|report| {
+            audit::record(
+                &app_handle,
+                "restoreKeyVault",
+                json!({ "vaultPath": vault_path, "restored": report.restored }),
+            )
+        })
+}
+
+fn _restore_key_vault(
+    app_handle: &tauri::AppHandle,
+    vault_path: &Path,
+    vault_password: &str,
+) -> Result<KeyVaultReport> {
+    check_private_keys_allowed()?;
+    let vault = open_vault(vault_path, vault_password)?;
+    let local = read_local_keys(app_handle)?;
+    let metadata_path = get_metadata_path(app_handle)?;
+    let key_storage_dir = get_key_storage_dir(app_handle)?;
+    let mut all_metadata = read_metadata(&metadata_path)?;
+
+    // 只写入本地缺失或内容不同的密钥；本地多出的密钥保持不动
+    let mut restored = Vec::new();
+    let mut transaction = StoreTransaction::begin(app_handle, "restoreKeyVault")?;
+    for (key_id, key) in &vault.keys {
+        let local_hash = local.get(key_id).map(|(hash, _)| hash);
+        if local_hash == Some(&hash_synced_key(key)?) {
+            continue;
+        }
+        store_synced_key(
+            &mut transaction,
+            &key_storage_dir,
+            &mut all_metadata,
+            key,
+            None,
+        )?;
+        restored.push(*key_id);
+    }
+    transaction.write(&metadata_path, metadata_json(&all_metadata)?)?;
+    transaction.commit()?;
+
+    log::info!(
+        "Restored {} of {} keys from vault {:?}",
+        restored.len(),
+        vault.keys.len(),
+        vault_path
+    );
+    Ok(KeyVaultReport {
+        key_count: vault.keys.len(),
+        record_count: vault.records,
+        appended_records: 0,
+        restored,
+    })
+}
+
+// --- Helper Functions ---
+
+/// Adds the given keys to a vault (creating it if needed) without touching the other keys
+/// it holds. Returns the number of records appended.
+pub(crate) fn put_keys_in_vault(
+    vault_path: &Path,
+    vault_password: &str,
+    keys: &[(String, SyncedKey)],
+) -> Result<usize> {
+    let mut vault = open_or_create_vault(vault_path, vault_password)?;
+    let changes = changed_keys(&vault, keys)?;
+    let appended_records = changes.len();
+    append_records(vault_path, &mut vault, changes)?;
+    Ok(appended_records)
+}
+
+fn open_or_create_vault(vault_path: &Path, vault_password: &str) -> Result<OpenVault> {
+    if vault_path.exists() {
+        open_vault(vault_path, vault_password)
+    } else {
+        create_vault(vault_path, vault_password)
+    }
+}
+
+// 为保管库中缺失或内容不同的密钥生成 put 记录
+fn changed_keys<'a>(
+    vault: &OpenVault,
+    keys: impl IntoIterator<Item = &'a (String, SyncedKey)>,
+) -> Result<Vec<VaultChange>> {
+    let mut changes = Vec::new();
+    for (hash, key) in keys {
+        let unchanged = match vault.keys.get(&key.metadata.key_id) {
+            Some(stored) => hash_synced_key(stored)? == *hash,
+            None => false,
+        };
+        if !unchanged {
+            changes.push(VaultChange::Put(Box::new(key.clone())));
+        }
+    }
+    Ok(changes)
+}
+
+// 创建只含头部和 Created 记录的新保管库
+fn create_vault(vault_path: &Path, vault_password: &str) -> Result<OpenVault> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let header = serde_json::to_string(&VaultHeader {
+        format: VAULT_FORMAT.to_string(),
+        version: VAULT_VERSION,
+        salt_hex: hex::encode(salt),
+    })
+    .context("Failed to serialize vault header")?;
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(vault_path)
+        .and_then(|mut file| writeln!(file, "{}", header))
+        .with_context(|| format!("Failed to create vault {:?}", vault_path))?;
+
+    let mut vault = OpenVault {
+        cipher: vault_cipher(vault_password, &salt)?,
+        keys: HashMap::new(),
+        last_line_hash: Sha256::digest(header.as_bytes()).into(),
+        records: 0,
+    };
+    append_records(vault_path, &mut vault, vec![VaultChange::Created])?;
+    Ok(vault)
+}
+
+// 打开保管库：校验头部，逐条解密并重放记录
+fn open_vault(vault_path: &Path, vault_password: &str) -> Result<OpenVault> {
+    let content = fs::read_to_string(vault_path)
+        .with_context(|| format!("Failed to read vault {:?}", vault_path))?;
+    let mut lines = content.lines();
+    let header_line = lines.next().unwrap_or_default();
+    let header: VaultHeader = serde_json::from_str(header_line)
+        .ok()
+        .filter(|h: &VaultHeader| h.format == VAULT_FORMAT)
+        .ok_or_else(|| anyhow::anyhow!("Not a sigust key vault"))?;
+    if header.version != VAULT_VERSION {
+        bail!("Unsupported vault version {}", header.version);
+    }
+    let salt = hex::decode(&header.salt_hex).context("Failed to decode vault salt")?;
+
+    let mut vault = OpenVault {
+        cipher: vault_cipher(vault_password, &salt)?,
+        keys: HashMap::new(),
+        last_line_hash: Sha256::digest(header_line.as_bytes()).into(),
+        records: 0,
+    };
+    for (index, line) in lines.enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let record =
+            decrypt_record(&mut vault.cipher, line, &vault.last_line_hash).with_context(|| {
+                match index {
+                    0 => "Failed to decrypt vault (check vault password)".to_string(),
+                    _ => format!("Vault record {} is corrupted or out of order", index + 1),
+                }
+            })?;
+        vault.apply(record.change);
+        vault.last_line_hash = Sha256::digest(line.as_bytes()).into();
+        vault.records += 1;
+    }
+    if vault.records == 0 {
+        bail!("Vault has no records");
+    }
+    Ok(vault)
+}
+
+// 加密并追加记录，同时更新内存中的内容
+fn append_records(
+    vault_path: &Path,
+    vault: &mut OpenVault,
+    changes: Vec<VaultChange>,
+) -> Result<()> {
+    let mut out = String::new();
+    for change in changes {
+        let record = VaultRecord {
+            at: Utc::now(),
+            change,
+        };
+        let line = encrypt_record(&mut vault.cipher, &record, &vault.last_line_hash)?;
+        vault.last_line_hash = Sha256::digest(line.as_bytes()).into();
+        vault.records += 1;
+        out.push_str(&line);
+        out.push('\n');
+        vault.apply(record.change);
+    }
+    if out.is_empty() {
+        return Ok(());
+    }
+    OpenOptions::new()
+        .append(true)
+        .open(vault_path)
+        .and_then(|mut file| file.write_all(out.as_bytes()))
+        .with_context(|| format!("Failed to append to vault {:?}", vault_path))
+}
+
+fn vault_cipher(vault_password: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+    let key = derive_encryption_key(vault_password, salt);
+    Aes256Gcm::new_from_slice(&key)
+        .map_err(|e| anyhow::anyhow!("Failed to create AES cipher: {}", e))
+}
+
+// 记录格式：base64(nonce || 密文)，关联数据为上一行的哈希
+fn encrypt_record(
+    cipher: &mut Aes256Gcm,
+    record: &VaultRecord,
+    previous: &[u8; 32],
+) -> Result<String> {
+    let mut data = serde_json::to_vec(record).context("Failed to serialize vault record")?;
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    cipher
+        .encrypt_in_place(Nonce::from_slice(&nonce), previous, &mut data)
+        .map_err(|e| anyhow::anyhow!("Failed to encrypt vault record: {}", e))?;
+    let mut bytes = nonce.to_vec();
+    bytes.extend_from_slice(&data);
+    Ok(BASE64.encode(bytes))
+}
+
+fn decrypt_record(cipher: &mut Aes256Gcm, line: &str, previous: &[u8; 32]) -> Result<VaultRecord> {
+    let bytes = BASE64
+        .decode(line)
+        .context("Failed to decode vault record")?;
+    if bytes.len() < NONCE_LEN {
+        bail!("Vault record is truncated");
+    }
+    let (nonce, data) = bytes.split_at(NONCE_LEN);
+    let mut data = data.to_vec();
+    cipher
+        .decrypt_in_place(Nonce::from_slice(nonce), previous, &mut data)
+        .map_err(|_| anyhow::anyhow!("Vault record failed authentication"))?;
+    serde_json::from_slice(&data).context("Failed to parse vault record")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_are_bound_to_the_previous_line() {
+        let mut cipher = vault_cipher("vault password", &[7u8; SALT_LEN]).unwrap();
+        let previous: [u8; 32] = Sha256::digest(b"header").into();
+        let record = VaultRecord {
+            at: Utc::now(),
+            change: VaultChange::Delete(Uuid::new_v4()),
+        };
+        let line = encrypt_record(&mut cipher, &record, &previous).unwrap();
+        assert!(decrypt_record(&mut cipher, &line, &previous).is_ok());
+        let other: [u8; 32] = Sha256::digest(b"other line").into();
+        assert!(decrypt_record(&mut cipher, &line, &other).is_err());
+    }
+}
@@ -0,0 +1,734 @@
+// src-tauri/src/verification_kit.rs
+// Verification kits: files and ready-to-run commands that let someone without sigust check
+// a signed document with a common tool. The signature is verified first, and a kit is only
+// written for a valid one, into `<document>.verify` inside the chosen folder.
+//
+// - openssl: the signature itself, mapped to what `openssl dgst` and `openssl pkeyutl`
+//   expect (ECDSA in DER, envelope attributes in their canonical form), with the signer's
+//   PEM key. Detached signatures of the legacy double-hash scheme and RSA/ECDSA envelopes
+//   are checked over the SHA-256 of the signed data. Ed25519ph signatures and passkey
+//   envelopes have no openssl equivalent. ML-DSA needs OpenSSL 3.5, as does the second
+//   (post-quantum) signature of hybrid envelopes, which gets a command of its own.
+// - ssh-keygen and gpg only check their own formats, which sign different data, so the
+//   kit carries a new signature of the document by the same key (one of the user's own
+//   keys, unlocked with its password): an SSHSIG signature in the "file" namespace with an
+//   allowed_signers file, or an OpenPGP signature with the key as a keyring for gpgv. The
+//   OpenPGP key takes the sigust key's creation time, so its fingerprint is the same on
+//   every export.
+//
+// Commands are meant for a POSIX shell started in the kit folder, with a copy of the
+// document next to the kit files.
+use crate::audit;
+use crate::crypto_types::{
+    DigestScheme, EcdsaEncoding, KeyMetadata, SignatureAlgorithm, VerificationKit,
+    VerificationResult, VerificationTool,
+};
+use crate::envelope::{canonical_json, SignatureEnvelope};
+use crate::key_exchange::encode_public_key_pem;
+use crate::key_management::{
+    compute_fingerprint, decode_public_key_pem, get_metadata_path, read_metadata,
+    read_private_key_der, read_public_key_pem,
+};
+use crate::native_paths::{native_path, open_file, path_string};
+use crate::openpgp::{
+    encode_packet, signature_hashed_part, signature_packet, signature_trailer, PublicKeyPacket,
+    SIGNATURE_BINARY, SIGNATURE_POSITIVE_CERTIFICATION,
+};
+use crate::output_paths::resolve_output_path;
+use crate::policy::check_timestamping;
+use crate::signable_inputs::check_signable_input;
+use crate::signing::{
+    encode_ecdsa_signature, read_signature_file, sign_bytes, sign_prehash, signer_candidates,
+    verify_against_candidates, SignerCandidate,
+};
+use crate::sshsig::{armor_signature, public_key_line, signed_data, ssh_fingerprint};
+use crate::timestamp::trusted_tsa_certificates;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::Utc;
+use serde_json::json;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs;
+use std::io;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+const INSTRUCTIONS_FILENAME: &str = "VERIFY.txt";
+const PUBLIC_KEY_FILENAME: &str = "signer.pub.pem";
+const SSH_PUBLIC_KEY_FILENAME: &str = "signer.pub";
+const SIGNATURE_FILENAME: &str = "signature.bin";
+const PQ_PUBLIC_KEY_FILENAME: &str = "cosigner.pub.pem";
+const PQ_SIGNATURE_FILENAME: &str = "pq-signature.bin";
+const ATTRIBUTES_FILENAME: &str = "signed-attributes.json";
+const DIGEST_FILENAME: &str = "signed-data.sha256";
+const ALLOWED_SIGNERS_FILENAME: &str = "allowed_signers";
+const KEYRING_FILENAME: &str = "signer.gpg";
+/// Namespace `ssh-keygen -Y sign` uses for files.
+const SSH_NAMESPACE: &str = "file";
+
+/// A kit being assembled; nothing is written until everything has been produced.
+struct Kit {
+    files: Vec<(String, Vec<u8>)>,
+    /// Paragraphs of `VERIFY.txt` shown before the commands.
+    notes: Vec<String>,
+    commands: Vec<String>,
+}
+
+impl Kit {
+    fn add(&mut self, name: impl Into<String>, content: impl Into<Vec<u8>>) {
+        self.files.push((name.into(), content.into()));
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Writes a kit for checking `signature_path` over `document_path` with `tool`. A password
+/// is needed for ssh-keygen and gpg, whose kits carry a new signature by the same key.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_verification_kit(
+    app_handle: tauri::AppHandle,
+    document_path: String,
+    signature_path: String,
+    output_dir: String,
+    tool: VerificationTool,
+    password: Option<String>,
+) -> Result<VerificationKit, String> {
+    log::info!(
+        "Exporting {:?} verification kit for '{}' and '{}'",
+        tool,
+        document_path,
+        signature_path
+    );
+    _export_verification_kit(
+        &app_handle,
+        &document_path,
+        &signature_path,
+        &output_dir,
+        tool,
+        password.as_deref(),
+    )
+    .map_err(|e| {
+        log::error!("Failed to export verification kit: {:?}", e);
+        e.to_string()
+    })
+    .inspect(|kit| {
+        audit::record(
+            &app_handle,
+            "exportVerificationKit",
+            json!({
+                "documentPath": document_path,
+                "signaturePath": signature_path,
+                "tool": tool,
+                "kitDir": kit.kit_dir,
+            }),
+        )
+    })
+}
+
+// --- Helper Functions ---
+
+fn _export_verification_kit(
+    app_handle: &tauri::AppHandle,
+    document_path: &str,
+    signature_path: &str,
+    output_dir: &str,
+    tool: VerificationTool,
+    password: Option<&str>,
+) -> Result<VerificationKit> {
+    let document_path = native_path(document_path)?;
+    let signature_path = native_path(signature_path)?;
+    let candidates = signer_candidates(app_handle)?;
+    let trusted_tsas = trusted_tsa_certificates(app_handle)?;
+    let report = verify_against_candidates(
+        &document_path,
+        &signature_path,
+        &candidates,
+        &trusted_tsas,
+        None,
+        true,
+    )?;
+    if !report.result.is_valid {
+        bail!(
+            "The signature does not verify, so no kit was written: {}",
+            report.result.error_message.unwrap_or_default()
+        );
+    }
+    let signer = report
+        .signer_fingerprint
+        .as_ref()
+        .and_then(|fingerprint| candidates.iter().find(|c| c.fingerprint == *fingerprint))
+        .ok_or_else(|| anyhow!("The signer's key was not found"))?;
+    let document_name = file_name(&document_path)?;
+
+    let mut kit = Kit {
+        files: Vec::new(),
+        notes: Vec::new(),
+        commands: Vec::new(),
+    };
+    match tool {
+        VerificationTool::Openssl => openssl_kit(
+            &mut kit,
+            &document_name,
+            &signature_path,
+            signer,
+            &report.result,
+        )?,
+        VerificationTool::SshKeygen | VerificationTool::Gpg => {
+            let password = password.filter(|p| !p.is_empty()).ok_or_else(|| {
+                anyhow!(
+                    "A password is required: the kit carries a new signature of the document, \
+                     made with your key"
+                )
+            })?;
+            check_timestamping(false)?;
+            check_signable_input(app_handle, &document_path)?;
+            let (metadata, private_key_der) = unlock_own_key(app_handle, signer, password)?;
+            if tool == VerificationTool::SshKeygen {
+                ssh_kit(
+                    &mut kit,
+                    &document_path,
+                    &document_name,
+                    &metadata,
+                    signer,
+                    &private_key_der,
+                )?;
+            } else {
+                gpg_kit(
+                    &mut kit,
+                    &document_path,
+                    &document_name,
+                    &metadata,
+                    signer,
+                    &private_key_der,
+                )?;
+            }
+        }
+    }
+    kit.add(
+        INSTRUCTIONS_FILENAME,
+        instructions(&kit, tool, &document_name, &signature_path, signer),
+    );
+
+    let requested = native_path(output_dir)?.join(format!("{}.verify", document_name));
+    let kit_dir = resolve_output_path(app_handle, &requested)?;
+    fs::create_dir_all(&kit_dir)
+        .with_context(|| format!("Failed to create kit folder: {:?}", kit_dir))?;
+    for (name, content) in &kit.files {
+        let path = kit_dir.join(name);
+        fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))?;
+    }
+    log::info!("Verification kit written to {:?}", kit_dir);
+    Ok(VerificationKit {
+        kit_dir: path_string(&kit_dir),
+        files: kit.files.into_iter().map(|(name, _)| name).collect(),
+        commands: kit.commands,
+    })
+}
+
+fn openssl_kit(
+    kit: &mut Kit,
+    document_name: &str,
+    signature_path: &Path,
+    signer: &SignerCandidate,
+    result: &VerificationResult,
+) -> Result<()> {
+    kit.add(
+        PUBLIC_KEY_FILENAME,
+        encode_public_key_pem(&signer.public_key_der)?,
+    );
+    kit.notes.push(format!(
+        "The first command prints the SHA-256 of the public key in {}. It must be {}, \
+         the signer's key fingerprint; confirm it with the signer through a channel you trust.",
+        PUBLIC_KEY_FILENAME, signer.fingerprint
+    ));
+    kit.commands.push(format!(
+        "openssl pkey -pubin -in {} -outform DER | openssl dgst -sha256",
+        PUBLIC_KEY_FILENAME
+    ));
+
+    let signature_bytes = read_signature_file(signature_path)?;
+    match SignatureEnvelope::parse(&signature_bytes)? {
+        Some(envelope) => {
+            if envelope.webauthn.is_some() {
+                bail!("Passkey signatures are WebAuthn assertions, which openssl cannot check");
+            }
+            let attributes = envelope.attributes()?;
+            let signature = BASE64
+                .decode(&envelope.signature)
+                .context("Failed to decode envelope signature")?;
+            kit.add(ATTRIBUTES_FILENAME, canonical_json(&envelope.signed)?);
+            kit.add(
+                SIGNATURE_FILENAME,
+                openssl_signature(&signer.algorithm, &signature)?,
+            );
+            kit.notes.push(format!(
+                "The signature covers {} (the signed attributes in canonical form), which \
+                 records the document's SHA-256 digest: {}. The second command must print \
+                 that digest. Time-stamps, approvals and other envelope details are only \
+                 checked by sigust.",
+                ATTRIBUTES_FILENAME, attributes.document_digest
+            ));
+            kit.commands.push(format!(
+                "openssl dgst -sha256 {}",
+                shell_quote(document_name)
+            ));
+            kit.commands
+                .extend(openssl_verify(&signer.algorithm, ATTRIBUTES_FILENAME, true));
+            if let Some(cosigner) = &attributes.pq_cosigner {
+                let pq_signature = envelope
+                    .pq_signature
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("The post-quantum signature is missing"))?;
+                kit.add(PQ_PUBLIC_KEY_FILENAME, cosigner.public_key_pem.clone());
+                kit.add(
+                    PQ_SIGNATURE_FILENAME,
+                    BASE64
+                        .decode(pq_signature)
+                        .context("Failed to decode post-quantum signature")?,
+                );
+                kit.notes.push(format!(
+                    "This is a hybrid envelope: {} is also signed by the {} key in {} \
+                     (fingerprint {}), and both signatures must be valid. The last command \
+                     checks the second one and needs OpenSSL 3.5 or later.",
+                    ATTRIBUTES_FILENAME,
+                    cosigner.algorithm,
+                    PQ_PUBLIC_KEY_FILENAME,
+                    cosigner.fingerprint
+                ));
+                kit.commands.push(format!(
+                    "openssl pkeyutl -verify -pubin -inkey {} -rawin -in {} -sigfile {}",
+                    PQ_PUBLIC_KEY_FILENAME, ATTRIBUTES_FILENAME, PQ_SIGNATURE_FILENAME
+                ));
+            }
+        }
+        None => {
+            if result.prehashed {
+                bail!("Ed25519ph signatures cannot be checked with the openssl command line");
+            }
+            kit.add(
+                SIGNATURE_FILENAME,
+                openssl_signature(&signer.algorithm, &signature_bytes)?,
+            );
+            if result.legacy_digest {
+                kit.notes.push(
+                    "This signature was made by an earlier sigust release, which signed the \
+                     SHA-256 digest of the document rather than the document; the commands \
+                     compute that digest first."
+                        .to_string(),
+                );
+            }
+            kit.commands.extend(openssl_verify(
+                &signer.algorithm,
+                &shell_quote(document_name),
+                result.legacy_digest,
+            ));
+        }
+    }
+    Ok(())
+}
+
+// openssl 校验命令；over_digest 时签名的消息是数据的 SHA-256（Ed25519 与 ML-DSA 不受影响）
+fn openssl_verify(algorithm: &SignatureAlgorithm, data: &str, over_digest: bool) -> Vec<String> {
+    if matches!(
+        algorithm,
+        SignatureAlgorithm::Ed25519 | SignatureAlgorithm::MlDsa65
+    ) {
+        return vec![format!(
+            "openssl pkeyutl -verify -pubin -inkey {} -rawin -in {} -sigfile {}",
+            PUBLIC_KEY_FILENAME, data, SIGNATURE_FILENAME
+        )];
+    }
+    let verify = |input: &str| {
+        format!(
+            "openssl dgst {} -verify {} -signature {} {}",
+            openssl_digest(algorithm),
+            PUBLIC_KEY_FILENAME,
+            SIGNATURE_FILENAME,
+            input
+        )
+    };
+    if over_digest {
+        vec![
+            format!(
+                "openssl dgst -sha256 -binary {} > {}",
+                data, DIGEST_FILENAME
+            ),
+            verify(DIGEST_FILENAME),
+        ]
+    } else {
+        vec![verify(data)]
+    }
+}
+
+// openssl dgst 的哈希选项：签名算法对消息所用的哈希
+fn openssl_digest(algorithm: &SignatureAlgorithm) -> &'static str {
+    match algorithm {
+        SignatureAlgorithm::EcdsaP384Sha384 => "-sha384",
+        SignatureAlgorithm::EcdsaP521Sha512 => "-sha512",
+        _ => "-sha256",
+    }
+}
+
+// openssl 只接受 DER 编码的 ECDSA 签名
+fn openssl_signature(algorithm: &SignatureAlgorithm, signature: &[u8]) -> Result<Vec<u8>> {
+    let raw = matches!(
+        (algorithm, signature.len()),
+        (SignatureAlgorithm::EcdsaP256Sha256, 64)
+            | (SignatureAlgorithm::EcdsaP384Sha384, 96)
+            | (SignatureAlgorithm::EcdsaP521Sha512, 132)
+    );
+    if raw {
+        encode_ecdsa_signature(signature, EcdsaEncoding::Der)
+    } else {
+        Ok(signature.to_vec())
+    }
+}
+
+fn ssh_kit(
+    kit: &mut Kit,
+    document_path: &Path,
+    document_name: &str,
+    metadata: &KeyMetadata,
+    signer: &SignerCandidate,
+    private_key_der: &[u8],
+) -> Result<()> {
+    let document_hash = hash_document(Sha512::new(), document_path, &[])?;
+    let signature = sign_bytes(
+        &signer.algorithm,
+        private_key_der,
+        &signed_data(SSH_NAMESPACE, &document_hash),
+        DigestScheme::Standard,
+    )?;
+    let signature_name = format!("{}.sig", document_name);
+    let principal = ssh_principal(metadata);
+    let key_line = public_key_line(&signer.algorithm, &signer.public_key_der)?;
+    let ssh_fingerprint = ssh_fingerprint(&signer.algorithm, &signer.public_key_der)?;
+
+    kit.add(
+        signature_name.clone(),
+        armor_signature(
+            &signer.algorithm,
+            &signer.public_key_der,
+            SSH_NAMESPACE,
+            &signature,
+        )?,
+    );
+    kit.add(
+        SSH_PUBLIC_KEY_FILENAME,
+        format!("{} {}\n", key_line, principal),
+    );
+    kit.add(
+        ALLOWED_SIGNERS_FILENAME,
+        format!(
+            "{} namespaces=\"{}\" {}\n",
+            principal, SSH_NAMESPACE, key_line
+        ),
+    );
+    kit.notes.push(format!(
+        "{} is a new signature of the document by the same key, in the SSH signature format. \
+         The first command prints the SSH fingerprint of the key, which must be {}; confirm it \
+         with the signer through a channel you trust ({} lists the same key).",
+        signature_name, ssh_fingerprint, ALLOWED_SIGNERS_FILENAME
+    ));
+    kit.commands.push(format!(
+        "ssh-keygen -l -E sha256 -f {}",
+        SSH_PUBLIC_KEY_FILENAME
+    ));
+    kit.commands.push(format!(
+        "ssh-keygen -Y verify -f {} -I {} -n {} -s {} < {}",
+        ALLOWED_SIGNERS_FILENAME,
+        shell_quote(&principal),
+        SSH_NAMESPACE,
+        shell_quote(&signature_name),
+        shell_quote(document_name)
+    ));
+    Ok(())
+}
+
+fn gpg_kit(
+    kit: &mut Kit,
+    document_path: &Path,
+    document_name: &str,
+    metadata: &KeyMetadata,
+    signer: &SignerCandidate,
+    private_key_der: &[u8],
+) -> Result<()> {
+    let key = PublicKeyPacket::new(
+        &signer.algorithm,
+        &signer.public_key_der,
+        metadata.created_at,
+    )?;
+    let user_id = openpgp_user_id(metadata);
+    let signed_at = Utc::now();
+
+    // 自签名将用户 ID 绑定到密钥，gpg 导入密钥时要求
+    let hashed = signature_hashed_part(SIGNATURE_POSITIVE_CERTIFICATION, &key, signed_at)?;
+    let mut hasher = Sha256::new();
+    hasher.update(key.hash_prefix());
+    hasher.update([0xb4]);
+    hasher.update((user_id.len() as u32).to_be_bytes());
+    hasher.update(user_id.as_bytes());
+    hasher.update(&hashed);
+    hasher.update(signature_trailer(&hashed));
+    let digest = hasher.finalize();
+    let values = openpgp_signature_values(&signer.algorithm, private_key_der, &digest)?;
+    let mut keyring = encode_packet(6, &key.body);
+    keyring.extend(encode_packet(13, user_id.as_bytes()));
+    keyring.extend(signature_packet(
+        &hashed,
+        &key,
+        &digest,
+        &values.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+    ));
+
+    let hashed = signature_hashed_part(SIGNATURE_BINARY, &key, signed_at)?;
+    let digest = hash_document(
+        Sha256::new(),
+        document_path,
+        &[&hashed, &signature_trailer(&hashed)],
+    )?;
+    let values = openpgp_signature_values(&signer.algorithm, private_key_der, &digest)?;
+    let signature = signature_packet(
+        &hashed,
+        &key,
+        &digest,
+        &values.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+    );
+
+    let signature_name = format!("{}.sig", document_name);
+    kit.add(KEYRING_FILENAME, keyring);
+    kit.add(signature_name.clone(), signature);
+    kit.notes.push(format!(
+        "{} is a new signature of the document by the same key, in the OpenPGP format; {} \
+         holds the key as \"{}\". gpgv shows the key's OpenPGP fingerprint, which must be {} \
+         (OpenPGP computes fingerprints differently from sigust, whose fingerprint for this \
+         key is {}); confirm it with the signer through a channel you trust. To keep the key, \
+         import it with: gpg --import {}",
+        signature_name,
+        KEYRING_FILENAME,
+        user_id,
+        hex::encode_upper(key.fingerprint),
+        signer.fingerprint,
+        KEYRING_FILENAME
+    ));
+    kit.commands.push(format!(
+        "gpgv --keyring ./{} {} {}",
+        KEYRING_FILENAME,
+        shell_quote(&signature_name),
+        shell_quote(document_name)
+    ));
+    Ok(())
+}
+
+// OpenPGP 签名值：RSA 为一个 MPI，ECDSA 与 EdDSA 为 r、s 两个
+fn openpgp_signature_values(
+    algorithm: &SignatureAlgorithm,
+    private_key_der: &[u8],
+    digest: &[u8],
+) -> Result<Vec<Vec<u8>>> {
+    let signature = match algorithm {
+        SignatureAlgorithm::RsaPkcs1Sha256 => {
+            return Ok(vec![sign_prehash(algorithm, private_key_der, digest)?])
+        }
+        SignatureAlgorithm::EcdsaP256Sha256 => sign_prehash(algorithm, private_key_der, digest)?,
+        SignatureAlgorithm::EcdsaP384Sha384
+        | SignatureAlgorithm::EcdsaP521Sha512
+        | SignatureAlgorithm::MlDsa65 => {
+            bail!("OpenPGP signatures cannot be made with {} keys", algorithm)
+        }
+        // EdDSA 签名的消息就是摘要本身
+        SignatureAlgorithm::Ed25519 => {
+            sign_bytes(algorithm, private_key_der, digest, DigestScheme::Standard)?
+        }
+    };
+    let (r, s) = signature.split_at(signature.len() / 2);
+    Ok(vec![r.to_vec(), s.to_vec()])
+}
+
+// 找到签名者对应的本机密钥并解锁（只有自己的密钥才能重新签名）
+fn unlock_own_key(
+    app_handle: &tauri::AppHandle,
+    signer: &SignerCandidate,
+    password: &str,
+) -> Result<(KeyMetadata, Zeroizing<Vec<u8>>)> {
+    let metadata = read_metadata(&get_metadata_path(app_handle)?)?
+        .into_iter()
+        .filter(|m| m.trashed_at.is_none())
+        .find(|m| {
+            read_public_key_pem(app_handle, m)
+                .and_then(|pem| decode_public_key_pem(&pem))
+                .is_ok_and(|der| compute_fingerprint(&der) == signer.fingerprint)
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "ssh-keygen and gpg only check their own signature formats, so the document \
+                 has to be signed again; this is only possible for signatures made with one \
+                 of your own keys"
+            )
+        })?;
+    let private_key_der = Zeroizing::new(read_private_key_der(app_handle, &metadata, password)?);
+    Ok((metadata, private_key_der))
+}
+
+// 分块读取文档计算摘要，最后追加格式要求的尾部数据
+fn hash_document<D: Digest + io::Write>(
+    mut hasher: D,
+    document_path: &Path,
+    suffixes: &[&[u8]],
+) -> Result<Vec<u8>> {
+    let mut file = open_file(document_path)
+        .with_context(|| format!("Failed to open document: {:?}", document_path))?;
+    io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read document: {:?}", document_path))?;
+    for suffix in suffixes {
+        hasher.update(suffix);
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+// allowed_signers 中的主体名：优先使用邮箱，否则用密钥名（不能含空白和逗号）
+fn ssh_principal(metadata: &KeyMetadata) -> String {
+    let principal = metadata
+        .identity
+        .as_ref()
+        .and_then(|identity| identity.email.clone())
+        .unwrap_or_else(|| metadata.name.clone())
+        .replace(|c: char| c.is_whitespace() || c == ',' || c == '"', "_");
+    if principal.is_empty() {
+        "signer".to_string()
+    } else {
+        principal
+    }
+}
+
+// OpenPGP 用户 ID："姓名 <邮箱>"
+fn openpgp_user_id(metadata: &KeyMetadata) -> String {
+    let identity = metadata.identity.as_ref();
+    let name = identity
+        .and_then(|identity| identity.full_name.clone())
+        .unwrap_or_else(|| metadata.name.clone());
+    match identity.and_then(|identity| identity.email.as_deref()) {
+        Some(email) => format!("{} <{}>", name, email),
+        None => name,
+    }
+}
+
+fn instructions(
+    kit: &Kit,
+    tool: VerificationTool,
+    document_name: &str,
+    signature_path: &Path,
+    signer: &SignerCandidate,
+) -> String {
+    let requirement = match tool {
+        VerificationTool::Openssl if signer.algorithm == SignatureAlgorithm::MlDsa65 => {
+            "OpenSSL 3.5 or later"
+        }
+        VerificationTool::Openssl => "OpenSSL 3.0 or later",
+        VerificationTool::SshKeygen => "OpenSSH 8.1 or later",
+        VerificationTool::Gpg => "GnuPG 2.2 or later",
+    };
+    let mut text = format!(
+        "HOW TO CHECK {document} WITHOUT SIGUST\n\
+         \n\
+         Signature:  {signature}\n\
+         Signed by:  {signer} (key fingerprint {fingerprint})\n\
+         Requires:   {requirement}\n\
+         \n\
+         Copy {document} into this folder, open a POSIX shell here (on Windows: Git Bash or\n\
+         WSL) and run the commands below. The signature is valid if the last command\n\
+         succeeds and the fingerprints match.\n",
+        document = document_name,
+        signature = file_name(signature_path).unwrap_or_default(),
+        signer = signer.name,
+        fingerprint = signer.fingerprint,
+    );
+    for note in &kit.notes {
+        text.push('\n');
+        text.push_str(note);
+        text.push('\n');
+    }
+    text.push_str("\nCommands:\n\n");
+    for command in &kit.commands {
+        text.push_str(&format!("  {}\n", command));
+    }
+    text
+}
+
+fn file_name(path: &Path) -> Result<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("Path has no file name: {:?}", path))
+}
+
+// POSIX shell 引号：只含安全字符时原样输出，否则用单引号包围
+fn shell_quote(text: &str) -> String {
+    if !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-/+@=:".contains(c))
+    {
+        return text.to_string();
+    }
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openpgp::find_signature_packet;
+    use ed25519_dalek::pkcs8::EncodePublicKey;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn writes_ssh_and_openpgp_signatures() {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let public_key_der = signing_key
+            .verifying_key()
+            .to_public_key_der()
+            .unwrap()
+            .into_vec();
+        let algorithm = SignatureAlgorithm::Ed25519;
+
+        // SSH：签名覆盖的是带命名空间的摘要块
+        let data = signed_data(SSH_NAMESPACE, &Sha512::digest(b"contract"));
+        let signature = signing_key.sign(&data).to_bytes();
+        let armored =
+            armor_signature(&algorithm, &public_key_der, SSH_NAMESPACE, &signature).unwrap();
+        let body: String = armored
+            .lines()
+            .skip(1)
+            .take_while(|l| !l.starts_with("-----"))
+            .collect();
+        let blob = BASE64.decode(body).unwrap();
+        assert!(blob.starts_with(b"SSHSIG\0\0\0\x01"));
+        assert!(blob.ends_with(&signature));
+        assert!(public_key_line(&algorithm, &public_key_der)
+            .unwrap()
+            .starts_with("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAI"));
+
+        // OpenPGP：签名包中的签发者指纹与算法
+        let key = PublicKeyPacket::new(&algorithm, &public_key_der, Utc::now()).unwrap();
+        let hashed = signature_hashed_part(SIGNATURE_BINARY, &key, Utc::now()).unwrap();
+        let mut hasher = Sha256::new();
+        hasher.update(b"contract");
+        hasher.update(&hashed);
+        hasher.update(signature_trailer(&hashed));
+        let digest = hasher.finalize();
+        let signature = signing_key.sign(&digest).to_bytes();
+        let (r, s) = signature.split_at(32);
+        let packet = signature_packet(&hashed, &key, &digest, &[r, s]);
+        let parsed = find_signature_packet(&packet).unwrap();
+        assert_eq!(parsed.version, 4);
+        assert_eq!(parsed.public_key_algorithm, 22);
+        assert_eq!(parsed.hash_algorithm, 8);
+        assert_eq!(
+            parsed.issuer_fingerprint.unwrap(),
+            hex::encode_upper(key.fingerprint)
+        );
+
+        assert_eq!(shell_quote("report-2026.pdf"), "report-2026.pdf");
+        assert_eq!(shell_quote("Bob's offer.pdf"), r"'Bob'\''s offer.pdf'");
+    }
+}
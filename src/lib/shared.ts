@@ -22,6 +22,35 @@
 // });
 
 
-import { writable } from "svelte/store";
+import { invoke } from "@tauri-apps/api/core";
+import { derived, writable } from "svelte/store";
+import type { DisplayNames, SignatureAlgorithm } from "./types";
 
 export const navTitle = writable<string>("");
+
+const algorithmNames = writable<Partial<Record<SignatureAlgorithm, string>>>({});
+
+/**
+ * Text to show for an algorithm identifier; the identifier itself until
+ * `loadDisplayNames` has run.
+ */
+export const algorithmName = derived(
+	algorithmNames,
+	($names) => (algorithm: SignatureAlgorithm) => $names[algorithm] ?? algorithm
+);
+
+/**
+ * Fetches the display names for the UI language from `get_display_names`.
+ */
+export async function loadDisplayNames() {
+	try {
+		const names = await invoke<DisplayNames>("get_display_names", {
+			locale: navigator.language
+		});
+		algorithmNames.set(
+			Object.fromEntries(names.algorithms.map((entry) => [entry.algorithm, entry.name]))
+		);
+	} catch (e) {
+		console.error("Failed to load display names", e);
+	}
+}
@@ -14,9 +14,1037 @@ export type KeyInfo = {
     /** User-defined name for the key. */
     name: string;        // <-- Already camelCase
     /** The signature algorithm associated with this key. */
-    algorithm: string;   // <-- Already camelCase
+    algorithm: SignatureAlgorithm;
     /** Timestamp (UTC ISO 8601 string) when the key was generated or imported. */
     createdAt: string;   // <-- Changed to camelCase
+    /** Identity of the key owner, if provided. */
+    identity?: SignerIdentity;
+    /** When the key was moved to the trash (UTC ISO 8601 string); only set for trashed keys. */
+    trashedAt?: string;
+    /** Folder the key is filed under (see `KeyFolder`); absent for the root. */
+    folderId?: string;
+    /** Free-form labels attached with `tag_keys`. */
+    tags?: string[];
+    /** Whether an escrow copy was sealed to the organization's recovery key. */
+    escrowed?: boolean;
+    /** Key that must approve every signature made with this key (two-person control). */
+    approverKeyId?: string;
+    /** ML-DSA key that co-signs hybrid signatures made with this key. */
+    pqKeyId?: string;
+    /** Passkey whose security key must be touched to decrypt this key. */
+    tokenPasskeyId?: string;
+    /** Master seed and path the key was derived from. */
+    derivation?: KeyDerivation;
+    /** Stricter unlock rule; absent when the key may be held in a key session. */
+    unlockPolicy?: KeyUnlockPolicy;
+    /** Whether the private key file can only be decrypted on this device. */
+    deviceBound?: boolean;
+    /** KDF the private key file is wrapped with; absent for keys stored before it was recorded (PBKDF2). */
+    kdf?: KdfParameters;
+    /** Whether the entry holds a key pair or only a public key for verification. */
+    kind: KeyKind;
+    /** SHA-256 of the SPKI DER public key, hex-encoded. */
+    fingerprint?: string;
+    /** The key's fingerprint as `ssh-keygen -l` shows it (`SHA256:` and base64). */
+    sshFingerprint?: string;
+    /** Modulus size of RSA keys, in bits. */
+    keyBits?: number;
+};
+
+/**
+ * Fingerprints of a stored public key, to compare it with its owner out-of-band.
+ * Matches Rust struct `KeyFingerprint`.
+ */
+export type KeyFingerprint = {
+    keyId: string;
+    /** SHA-256 of the SPKI DER public key, hex-encoded. */
+    fingerprint: string;
+    /** The key's fingerprint as `ssh-keygen -l` shows it (`SHA256:` and base64). */
+    sshFingerprint?: string;
+};
+
+/**
+ * What a key store entry holds. Matches Rust enum `KeyKind`.
+ * 'publicOnly' entries are someone else's public key; they verify but cannot sign.
+ */
+export type KeyKind = 'keyPair' | 'publicOnly';
+
+/**
+ * Password-based KDF protecting a stored private key. Matches Rust enum `KeyKdf`.
+ */
+export type KeyKdf = 'pbkdf2' | 'argon2id';
+
+/**
+ * Name and parameters of the KDF a private key file is wrapped with.
+ * Matches Rust enum `KdfParameters`.
+ */
+export type KdfParameters =
+    | { name: 'pbkdf2Sha256'; iterations: number }
+    | { name: 'argon2id'; memoryKib: number; iterations: number; parallelism: number };
+
+/**
+ * Parameters of a newly generated key. Matches Rust struct `KeyGenerationOptions`.
+ */
+export type KeyGenerationOptions = {
+    /** Modulus size of RSA keys: 2048 (the default), 3072 or 4096 bits. */
+    rsaKeyBits?: 2048 | 3072 | 4096;
+};
+
+/**
+ * How a generated or imported private key is stored. Matches Rust struct `KeyStorageOptions`.
+ */
+export type KeyStorageOptions = {
+    /** Seal a copy to the organization's recovery key. */
+    escrow?: boolean;
+    /** Do not bind the key to this device, even when device binding is on. */
+    portable?: boolean;
+    /** KDF that derives the wrapping key from the password (default 'pbkdf2'). */
+    kdf?: KeyKdf;
+};
+
+/**
+ * How a key has to be unlocked. Matches Rust enum `KeyUnlockPolicy`.
+ * 'everyUse' keeps the key out of key sessions; 'biometric' also needs a user-verifying
+ * assertion from the passkey for every use.
+ */
+export type KeyUnlockPolicy =
+    | { kind: 'everyUse' }
+    | { kind: 'biometric'; passkeyKeyId: string };
+
+/**
+ * Per-key outcome of a bulk key operation. Matches Rust struct `KeyOperationResult`.
+ */
+export type KeyOperationResult = {
+    keyId: string;
+    /** File written for this key (exports only). */
+    outputPath?: string;
+    /** Why the operation failed for this key; absent on success. */
+    error?: string;
+};
+
+/**
+ * Key derivation for `export_key_pair`. Matches Rust enum `Pkcs8Kdf`.
+ * `pbkdf2` suits older tools that cannot read scrypt.
+ */
+export type Pkcs8Kdf = 'scrypt' | 'pbkdf2';
+
+/**
+ * Files written by `export_key_pair`. Matches Rust struct `KeyPairExport`.
+ */
+export type KeyPairExport = {
+    /** Encrypted PKCS#8 PEM of the private key. */
+    privateKeyPath: string;
+    publicKeyPath: string;
+};
+
+/**
+ * A key unlocked for quick signing. Matches Rust struct `KeySessionInfo`.
+ */
+export type KeySessionInfo = {
+    keyId: string;
+    name: string;
+    /** ISO 8601 time at which the session ends. */
+    expiresAt: string;
+};
+
+/**
+ * What a dropped file appears to be. Matches Rust enum `FileKind`.
+ */
+export type FileKind =
+    | 'document'
+    | 'sigustSignature'
+    | 'cmsSignature'
+    | 'pgpSignature'
+    | 'minisignSignature'
+    | 'publicKey'
+    | 'backupArchive';
+
+/**
+ * Classification of one dropped file. Matches Rust struct `FileClassification`.
+ */
+export type FileClassification = {
+    path: string;
+    /** Absent if the file could not be read. */
+    kind?: FileKind;
+    /** Sub-format or algorithm, e.g. "envelope", "detached", "Ed25519", "ssh". */
+    detail?: string;
+    error?: string;
+};
+
+/**
+ * A public key decoded for fingerprint confirmation before import.
+ * Matches Rust struct `KeyExchangePreview`.
+ */
+export type KeyExchangePreview = {
+    name?: string;
+    algorithm?: SignatureAlgorithm;
+    publicKeyPem?: string;
+    fingerprint: string;
+    existingContactId?: string;
+};
+
+/**
+ * What a pasted text is. Matches Rust struct `PastedTextPreview`.
+ */
+export type PastedTextPreview = {
+    kind: FileKind;
+    /** Sub-format, e.g. "ssh", "openpgp", "armored". */
+    format?: string;
+    /** The decoded key, for public keys. */
+    key?: KeyExchangePreview;
+};
+
+/**
+ * A pasted signature written to a temporary file. Matches Rust struct `StagedSignature`.
+ */
+export type StagedSignature = {
+    stagedId: string;
+    /** Path of the staged file, to pass to verification. */
+    path: string;
+    kind: FileKind;
+    format?: string;
+};
+
+/**
+ * Unverified description of a signature file. Matches Rust struct `SignatureInspection`.
+ */
+export type SignatureInspection = {
+    path: string;
+    kind: FileKind;
+    /** Sub-format, e.g. "envelope", "detached", "attached", "armored". */
+    format?: string;
+    algorithm?: string;
+    digestAlgorithm?: string;
+    /** sigust key ID of the signer (envelopes). */
+    signerKeyId?: string;
+    signerFingerprint?: string;
+    /** OpenPGP or minisign key ID, CMS issuer and serial number. */
+    issuer?: string;
+    /** Matching own key or contact, or the CMS signer certificate subject. */
+    signerName?: string;
+    signedAt?: string;
+    timeSource?: TimeSource;
+    documentName?: string;
+    /** Free-text comment (minisign trusted comment). */
+    comment?: string;
+    /** Custom metadata signed into an envelope. */
+    metadata?: Record<string, string>;
+    /** Per-key sequence number signed into an envelope. */
+    sequence?: number;
+    timestamps: InspectedTimestamp[];
+    certificates: CertificateSummary[];
+};
+
+/**
+ * A time-stamp found in a signature, not checked. Matches Rust struct `InspectedTimestamp`.
+ */
+export type InspectedTimestamp = {
+    genTime?: string;
+    tsaName?: string;
+    tsaUrl?: string;
+};
+
+/**
+ * Identifying fields of an X.509 certificate. Matches Rust struct `CertificateSummary`.
+ */
+export type CertificateSummary = {
+    subject: string;
+    issuer: string;
+    serialNumber: string;
+    notBefore: string;
+    notAfter: string;
+};
+
+/**
+ * Organization recovery key that new keys can be escrowed to.
+ * Matches Rust struct `RecoveryKeyConfig`.
+ */
+export type RecoveryKeyConfig = {
+    /** RSA public key, SPKI PEM. */
+    publicKeyPem: string;
+    /** SHA-256 fingerprint of the public key, lowercase hex. */
+    fingerprint: string;
+    /** UTC ISO 8601 string. */
+    configuredAt: string;
+};
+
+/**
+ * Rules of an enterprise policy file.
+ * Matches Rust struct `EnterprisePolicy`.
+ */
+export type EnterprisePolicy = {
+    /** Algorithms keys may use; all when absent. */
+    allowedAlgorithms?: string[];
+    /** Minimum RSA modulus size in bits. */
+    minRsaKeyBits?: number;
+    /** Signatures must carry an RFC 3161 time-stamp. */
+    requireTimestamp: boolean;
+    /** Private keys may not leave the key store (vaults, backups, sync). */
+    forbidPrivateKeyExport: boolean;
+    /** Keys may only be used while the audit log can be written. */
+    requireAuditLog: boolean;
+    /** Only verification and trust-store commands work; no private keys on this device. */
+    verificationOnly: boolean;
+    /** Limits on the files that may be signed, on top of the user's own. */
+    signableInputs?: SignableInputRules;
+};
+
+/**
+ * Limits on the files that may be signed (`update_signable_input_rules`). Extensions are
+ * given without the dot; MIME types may end in `/*`. Matches Rust struct `SignableInputRules`.
+ */
+export type SignableInputRules = {
+    /** Largest file that may be signed, in bytes. */
+    maxFileSize?: number;
+    /** Only files with these extensions may be signed. */
+    allowedExtensions?: string[];
+    blockedExtensions?: string[];
+    /** Only files of these (detected) MIME types may be signed. */
+    allowedMimeTypes?: string[];
+    blockedMimeTypes?: string[];
+    /** Signing an executable or script needs a confirmation that shows its SHA-256 digest. */
+    confirmExecutables: boolean;
+};
+
+/**
+ * What signing a file would involve (`preview_signable_input`).
+ * Matches Rust struct `SignableInputPreview`.
+ */
+export type SignableInputPreview = {
+    path: string;
+    size: number;
+    /** MIME type detected from the content. */
+    mimeType: string;
+    /** The file is a native executable or a script. */
+    executable: boolean;
+    /** SHA-256 of the file, lowercase hex. */
+    sha256: string;
+    /** Rules the file breaks; it cannot be signed while this is not empty. */
+    violations: string[];
+    /** Signing it will ask for a confirmation showing `sha256`. */
+    requiresConfirmation: boolean;
+};
+
+/**
+ * The enterprise policy in effect on this device.
+ * Matches Rust struct `PolicyStatus`.
+ */
+export type PolicyStatus = {
+    /** A policy file is installed (even if it failed to load). */
+    managed: boolean;
+    /**
+     * Verification-only mode is in effect, by policy or because of a verification-only
+     * build. Key generation, import and signing are unavailable.
+     */
+    verificationOnly: boolean;
+    organization?: string;
+    /** UTC ISO 8601 string. */
+    issuedAt?: string;
+    /** Fingerprint of the key the policy was signed with. */
+    signerFingerprint?: string;
+    rules?: EnterprisePolicy;
+    /** Why an installed policy could not be loaded; key use is locked until it is fixed. */
+    error?: string;
+};
+
+/**
+ * A saved signing preset (see `sign_with_profile`).
+ * Matches Rust struct `SigningProfile`.
+ */
+export type SigningProfile = {
+    profileId: string;
+    name: string;
+    /** Key the profile signs with. */
+    keyId: string;
+    /** Format, digest scheme, level and time-stamping of the signature. */
+    options: SigningOptions;
+    /** Signature file name template; the configured template when absent. */
+    outputTemplate?: string;
+    /** Folder signatures are written to; next to the document when absent. */
+    outputDir?: string;
+    /** UTC ISO 8601 string. */
+    createdAt: string;
+    /** UTC ISO 8601 string. */
+    updatedAt: string;
+};
+
+/**
+ * Fields of a signing profile as edited by the user; `profileId` is set when updating.
+ * Matches Rust struct `SigningProfileInput`.
+ */
+export type SigningProfileInput = {
+    profileId?: string;
+    name: string;
+    keyId: string;
+    options: SigningOptions;
+    outputTemplate?: string;
+    outputDir?: string;
+};
+
+/**
+ * Schema custom envelope metadata is checked against at signing time.
+ * Matches Rust struct `MetadataSchema`.
+ */
+export type MetadataSchema = {
+    fields: MetadataField[];
+    /** Accept keys that are not listed in `fields`. */
+    allowUnknownFields: boolean;
+};
+
+/**
+ * One field of a `MetadataSchema`. Matches Rust struct `MetadataField`.
+ */
+export type MetadataField = {
+    /** Key of the field in the envelope. */
+    name: string;
+    /** Display name for forms. */
+    label?: string;
+    required?: boolean;
+    kind?: MetadataFieldKind;
+    maxLength?: number;
+    /** If not empty, the value must be one of these. */
+    allowedValues?: string[];
+};
+
+/**
+ * Value type of a metadata field ('date' is YYYY-MM-DD). Matches Rust enum `MetadataFieldKind`.
+ */
+export type MetadataFieldKind = 'text' | 'integer' | 'date';
+
+/**
+ * Documents digested on one machine, to be signed on another.
+ * Matches Rust struct `SigningRequest`.
+ */
+export type SigningRequest = {
+    requestId: string;
+    /** UTC ISO 8601 string. */
+    createdAt: string;
+    /** Context for the signer, e.g. what the documents are for. */
+    note?: string;
+    /** Bind each document's file name and size into its envelope. */
+    bindFileName: boolean;
+    /** Custom metadata signed into every envelope. */
+    metadata?: Record<string, string>;
+    documents: RequestedDocument[];
+};
+
+/**
+ * A document listed in a signing request. Matches Rust struct `RequestedDocument`.
+ */
+export type RequestedDocument = {
+    /** File name of the document (no directories). */
+    name: string;
+    size: number;
+    /** SHA-256 digest, lowercase hex. */
+    digest: string;
+};
+
+/**
+ * Envelope written for one document of a signing request.
+ * Matches Rust struct `SignedRequestDocument`.
+ */
+export type SignedRequestDocument = {
+    name: string;
+    digest: string;
+    signaturePath: string;
+};
+
+/**
+ * A signature from a signature bundle, written next to its original document.
+ * Matches Rust struct `AttachedSignature`.
+ */
+export type AttachedSignature = {
+    /** Document name as listed in the signing request. */
+    name: string;
+    documentPath: string;
+    signaturePath: string;
+    /** Own key or contact that made the signatures in the bundle. */
+    signerName: string;
+};
+
+/**
+ * Approval of a signature by the signing key's approver (two-person control).
+ * Matches Rust struct `SigningApproval`.
+ */
+export type SigningApproval = {
+    approverKeyId: string;
+    /** Approver's full name, or the key name if the key has no identity. */
+    approverName: string;
+    algorithm: string;
+    publicKeyPem: string;
+    /** SHA-256 fingerprint of the approver's public key, lowercase hex. */
+    fingerprint: string;
+    /** UTC ISO 8601 string. */
+    approvedAt: string;
+    /** Approver's signature over the approval statement, base64. */
+    signature: string;
+};
+
+/**
+ * The post-quantum key that co-signs a hybrid envelope.
+ * Matches Rust struct `PqCosigner`.
+ */
+export type PqCosigner = {
+    keyId: string;
+    algorithm: string;
+    publicKeyPem: string;
+    /** SHA-256 fingerprint of the co-signer's public key, lowercase hex. */
+    fingerprint: string;
+};
+
+/**
+ * Who the signer authenticated as with the OpenID Connect provider at signing time.
+ * Matches Rust struct `OidcIdentity`.
+ */
+export type OidcIdentity = {
+    issuer: string;
+    subject: string;
+    email?: string;
+    emailVerified: boolean;
+    /** Organization claim (`org`, `organization` or `hd`), if any. */
+    organization?: string;
+    /** UTC ISO 8601 string. */
+    authenticatedAt: string;
+    /** The ID token, when embedded. */
+    idToken?: string;
+};
+
+/**
+ * OpenID Connect provider used at signing time (`update_oidc_settings`).
+ * Matches Rust struct `OidcSettings`.
+ */
+export type OidcSettings = {
+    issuer?: string;
+    /** Client ID of a public client that allows loopback redirects. */
+    clientId?: string;
+    /** Embed the ID token itself, not only its verified claims. */
+    embedToken: boolean;
+};
+
+/**
+ * A passkey (resident WebAuthn credential, ES256) registered as a signing key.
+ * Matches Rust struct `PasskeyInfo`.
+ */
+export type PasskeyInfo = {
+    /** Key ID used in signature envelopes; also the WebAuthn user handle. */
+    keyId: string;
+    name: string;
+    /** WebAuthn credential ID, base64url. */
+    credentialId: string;
+    /** Relying party ID the credential was created for. */
+    rpId: string;
+    publicKeyPem: string;
+    /** SHA-256 fingerprint of the SPKI DER, hex-encoded. */
+    fingerprint: string;
+    /** UTC ISO 8601 string. */
+    createdAt: string;
+};
+
+/**
+ * A WebAuthn challenge for the webview to pass to the authenticator.
+ * Matches Rust struct `PasskeyChallenge`.
+ */
+export type PasskeyChallenge = {
+    /** Challenge, base64url. */
+    challenge: string;
+    /** Key ID of the passkey (the user handle when registering). */
+    keyId: string;
+    /** Credential to ask for when signing. */
+    credentialId?: string;
+    /** Relying party ID of the credential when signing. */
+    rpId?: string;
+};
+
+/**
+ * Cloud key management service of a KMS key. Matches Rust enum `KmsProvider`.
+ */
+export type KmsProvider = 'aws' | 'gcp' | 'azure' | 'vault';
+
+/**
+ * How to log in to Vault. Matches Rust enum `VaultAuthMethod`.
+ */
+export type VaultAuthMethod = 'token' | 'appRole';
+
+/**
+ * Where a Vault transit key lives and how to reach it. Matches Rust struct `VaultTransitSettings`.
+ */
+export type VaultTransitSettings = {
+    /** Vault address, e.g. `https://vault.example.com:8200`. */
+    address: string;
+    /** Mount path of the transit engine (default `transit`). */
+    mount?: string;
+    authMethod: VaultAuthMethod;
+    /** Mount path of the AppRole auth method (default `approle`). */
+    authMount?: string;
+    namespace?: string;
+    /** Key version signed with; set when the key is added. */
+    keyVersion?: number;
+};
+
+/**
+ * A signing key kept in a cloud KMS. Matches Rust struct `KmsKeyInfo`.
+ */
+export type KmsKeyInfo = {
+    /** Key ID used in signature envelopes. */
+    keyId: string;
+    name: string;
+    provider: KmsProvider;
+    /** The key in the provider's own notation (ARN, resource name, key identifier URL or transit key name). */
+    keyReference: string;
+    /** Connection settings for Vault keys. */
+    vault?: VaultTransitSettings;
+    algorithm: SignatureAlgorithm;
+    /** Public key fetched from the KMS when the key was added. */
+    publicKeyPem: string;
+    fingerprint: string;
+    addedAt: string;
+};
+
+/**
+ * What the webview needs to ask a security key for the secret that unwraps a key.
+ * Matches Rust struct `TokenUnwrapRequest`.
+ */
+export type TokenUnwrapRequest = {
+    keyId: string;
+    /** Credential of the passkey on the security key. */
+    credentialId: string;
+    /** Relying party ID of the credential. */
+    rpId: string;
+    /** Salt to evaluate with the `prf` (hmac-secret) extension, base64url. */
+    salt: string;
+};
+
+/**
+ * A master seed that signing keys are derived from.
+ * Matches Rust struct `MasterSeedInfo`.
+ */
+export type MasterSeedInfo = {
+    seedId: string;
+    name: string;
+    /** Short hash identifying the seed; the same seed always has the same fingerprint. */
+    fingerprint: string;
+    createdAt: string;
+};
+
+/**
+ * Where a derived key comes from.
+ * Matches Rust struct `KeyDerivation`.
+ */
+export type KeyDerivation = {
+    /** Fingerprint of the master seed. */
+    seedFingerprint: string;
+    /** Hardened derivation path, e.g. `m/1'/0'`. */
+    path: string;
+};
+
+/**
+ * A key derived from a master seed, as listed for recovery.
+ * Matches Rust struct `DerivedKeyRecord`.
+ */
+export type DerivedKeyRecord = {
+    name: string;
+    algorithm: SignatureAlgorithm;
+    /** Hardened derivation path, e.g. `m/1'/0'`. */
+    path: string;
+    /** SHA-256 fingerprint of the public key. */
+    fingerprint: string;
+};
+
+/**
+ * An update or plugin whose publisher signature checked out.
+ * Matches Rust struct `TrustedArtifact`.
+ */
+export type TrustedArtifact = {
+    fileName: string;
+    size: number;
+    /** SHA-256 of the artifact, hex. */
+    sha256: string;
+    /** minisign key ID of the publisher key. */
+    keyId: string;
+    /** Signed comment from the signature file. */
+    trustedComment: string;
+    /** Signing time from the trusted comment, when present. */
+    signedAt?: string;
+};
+
+/**
+ * A short signed statement kept in the app.
+ * Matches Rust struct `SignedNote`.
+ */
+export type SignedNote = {
+    noteId: string;
+    text: string;
+    keyId: string;
+    /** Name of the signing key when the note was signed. */
+    keyName: string;
+    algorithm: SignatureAlgorithm;
+    /** Public key of the signer, so the note can be verified after the key is gone. */
+    publicKeyPem: string;
+    /** SHA-256 of the note text (UTF-8), hex. */
+    digest: string;
+    signedAt: string;
+};
+
+/**
+ * Signed list of the files in a directory, for drift detection.
+ * Matches Rust struct `DirectoryBaseline`.
+ */
+export type DirectoryBaseline = {
+    /** Absolute path of the directory; one baseline is kept per directory. */
+    directory: string;
+    keyId: string;
+    /** Name of the signing key when the baseline was signed. */
+    keyName: string;
+    algorithm: SignatureAlgorithm;
+    /** Public key of the signer, so the baseline can be checked after the key is gone. */
+    publicKeyPem: string;
+    fileCount: number;
+    totalBytes: number;
+    /** SHA-256 of the canonical file list, hex. */
+    digest: string;
+    signedAt: string;
+};
+
+/**
+ * Changes in a directory since its signed baseline (`check_drift`). Paths are relative to
+ * the directory, with `/` separators. Matches Rust struct `DriftReport`.
+ */
+export type DriftReport = {
+    baseline: DirectoryBaseline;
+    /** If false, the changes are relative to a baseline that cannot be trusted. */
+    baselineValid: boolean;
+    errorMessage?: string;
+    added: string[];
+    removed: string[];
+    modified: string[];
+    unchanged: number;
+    checkedAt: string;
+};
+
+/**
+ * Kind of operation a consent request is about. Matches Rust enum `ConsentAction`.
+ */
+export type ConsentAction = 'exportPrivateKey' | 'deleteKey' | 'firstKeyUse' | 'signExecutable';
+
+/**
+ * An operation waiting for confirmation, emitted with the `consent-requested` event.
+ * Matches Rust struct `ConsentRequest`.
+ */
+export type ConsentRequest = {
+    requestId: string;
+    action: ConsentAction;
+    title: string;
+    /** What is about to happen, as shown in the dialog. */
+    description: string;
+    expiresAt: string;
+};
+
+/**
+ * Scheduled re-verification of an archive folder (`update_archive_verification`).
+ * Matches Rust struct `ArchiveVerificationSettings`.
+ */
+export type ArchiveVerificationSettings = {
+    enabled: boolean;
+    /** Folder searched recursively for documents with signatures next to them. */
+    folder?: string;
+    /** Hours between two runs. */
+    intervalHours: number;
+};
+
+/**
+ * Where the organization's trust bundle is fetched from (`update_trust_bundle_settings`).
+ * Matches Rust struct `TrustBundleSettings`.
+ */
+export type TrustBundleSettings = {
+    enabled: boolean;
+    /** HTTPS URL of the signed bundle. */
+    url?: string;
+    /** Organization key the bundle must be signed with, in PEM (SPKI) format. */
+    signerPublicKeyPem?: string;
+    /** Hours between two fetches. */
+    intervalHours: number;
+};
+
+/**
+ * Outcome of the last trust bundle fetch and the bundle currently applied
+ * (`sync_trust_bundle`, `get_trust_bundle_status`). Matches Rust struct `TrustBundleStatus`.
+ */
+export type TrustBundleStatus = {
+    /** UTC ISO 8601 string. */
+    lastCheckedAt?: string;
+    /** Error of the last fetch; the previously applied bundle stays in effect. */
+    error?: string;
+    organization?: string;
+    /** `issuedAt` of the applied bundle; older bundles are rejected. */
+    issuedAt?: string;
+    appliedAt?: string;
+    keyCount: number;
+    certificateCount: number;
+    /** Whether the applied bundle set the metadata schema. */
+    metadataSchemaApplied: boolean;
+};
+
+/**
+ * Summary of one re-verification of the archive folder (`run_archive_verification`,
+ * `get_archive_verification_report`). Matches Rust struct `ArchiveVerificationReport`.
+ */
+export type ArchiveVerificationReport = {
+    folder: string;
+    startedAt: string;
+    finishedAt: string;
+    /** Whether the run was started by the scheduler rather than by the user. */
+    scheduled: boolean;
+    /** Number of signatures checked. */
+    checked: number;
+    /** Signatures with at least one problem; the others passed. */
+    failures: ArchiveFailure[];
+};
+
+/**
+ * A signature in the archive that no longer checks out. Matches Rust struct `ArchiveFailure`.
+ */
+export type ArchiveFailure = {
+    documentPath: string;
+    signaturePath: string;
+    signerName?: string;
+    issues: ArchiveIssue[];
+    errorMessage?: string;
+};
+
+/**
+ * Problem found by archive re-verification. Matches Rust enum `ArchiveIssue`.
+ */
+export type ArchiveIssue =
+    | 'documentChanged'
+    | 'signatureInvalid'
+    | 'timestampInvalid'
+    | 'timestampExpired'
+    | 'signerDistrusted';
+
+/**
+ * Which sensitive operations ask for confirmation. Matches Rust struct `ConsentSettings`.
+ */
+export type ConsentSettings = {
+    exportPrivateKey: boolean;
+    deleteKey: boolean;
+    firstKeyUse: boolean;
+};
+
+/**
+ * Why the store was locked. Matches Rust enum `StoreLockReason`.
+ */
+export type StoreLockReason = 'suspend' | 'sessionLock' | 'screensaver';
+
+/**
+ * Payload of the `store://locked` event; all key sessions have ended.
+ * Matches Rust struct `StoreLocked`.
+ */
+export type StoreLocked = {
+    reason: StoreLockReason;
+    lockedAt: string;
+};
+
+/**
+ * Summary of importing a configuration file. Matches Rust struct `ConfigImportReport`.
+ */
+export type ConfigImportReport = {
+    profilesAdded: number;
+    /** Local profiles replaced by one with the same ID or name. */
+    profilesUpdated: number;
+    /** Imported profiles whose key is not on this device. */
+    profilesWithoutKey: string[];
+    /** Whether new keys are now escrowed to a different recovery key (or to none). */
+    recoveryKeyChanged: boolean;
+};
+
+/**
+ * A sign/verify interoperability vector. Matches Rust struct `TestVector`.
+ */
+export type TestVector = {
+    name: string;
+    /** Algorithm display name, e.g. "ECDSA-P256-SHA256". */
+    algorithm: string;
+    format: SignatureFormat;
+    digestScheme: DigestScheme;
+    ecdsaEncoding?: EcdsaEncoding;
+    publicKeyPem: string;
+    /** The signed message, hex. */
+    messageHex: string;
+    /** Detached: the signature, standard base64. Envelope: the envelope JSON. */
+    signature: string;
+    /** The signature is expected to be rejected. */
+    invalid?: boolean;
+};
+
+/**
+ * Outcome of checking one test vector. Matches Rust struct `TestVectorCheck`.
+ */
+export type TestVectorCheck = {
+    name: string;
+    /** Whether verification agreed with the vector's expectation. */
+    passed: boolean;
+    error?: string;
+};
+
+/**
+ * What a benchmark measured. Matches Rust enum `BenchmarkOperation`.
+ */
+export type BenchmarkOperation = 'keyGeneration' | 'kdf' | 'sign' | 'verify';
+
+/**
+ * Timing of one benchmarked operation. Matches Rust struct `BenchmarkResult`.
+ */
+export type BenchmarkResult = {
+    operation: BenchmarkOperation;
+    /** The algorithm, or for KDFs the function and its parameters. */
+    subject: string;
+    /** Signed document size in bytes (sign and verify only). */
+    dataSize?: number;
+    runs: number;
+    meanMs: number;
+    opsPerSecond: number;
+    mibPerSecond?: number;
+};
+
+/**
+ * Results of `run_benchmarks`. Matches Rust struct `BenchmarkReport`.
+ */
+export type BenchmarkReport = {
+    results: BenchmarkResult[];
+    threads: number;
+    durationMs: number;
+    ranAt: string;
+};
+
+/**
+ * Display name and description of a signature algorithm.
+ * Matches Rust struct `AlgorithmDisplayName`.
+ */
+export type AlgorithmDisplayName = {
+    algorithm: SignatureAlgorithm;
+    name: string;
+    description: string;
+};
+
+/**
+ * Display name and description of a signature format.
+ * Matches Rust struct `FormatDisplayName`.
+ */
+export type FormatDisplayName = {
+    format: SignatureFormat;
+    name: string;
+    description: string;
+};
+
+/**
+ * Display names in one language, from `get_display_names`.
+ * Matches Rust struct `DisplayNames`.
+ */
+export type DisplayNames = {
+    /** Language the names are in, e.g. "en"; may differ from the requested locale. */
+    locale: string;
+    algorithms: AlgorithmDisplayName[];
+    formats: FormatDisplayName[];
+};
+
+/**
+ * What a `sigust://` link asks for. Matches Rust enum `DeepLinkAction`.
+ */
+export type DeepLinkAction = 'sign' | 'verify';
+
+/**
+ * A checked `sigust://` link (event `deep-link://request` or `take_deep_link_requests`).
+ * Matches Rust struct `DeepLinkRequest`.
+ */
+export type DeepLinkRequest = {
+    requestId: string;
+    action: DeepLinkAction;
+    /** Documents to sign or verify (absolute paths). */
+    files?: string[];
+    /** Expected SHA-256 of the document; compare it with the digest of the document used. */
+    sha256?: string;
+    signaturePath?: string;
+    /** Key suggested by the link. */
+    keyId?: string;
+    receivedAt: string;
+};
+
+/**
+ * State of the loopback HTTP API (`get_local_api_status`, `update_local_api`,
+ * `rotate_local_api_token`). Matches Rust struct `LocalApiStatus`.
+ */
+export type LocalApiStatus = {
+    enabled: boolean;
+    running: boolean;
+    /** Base URL while running, e.g. `http://127.0.0.1:47615`. */
+    url?: string;
+    /** Clients send it as `Authorization: Bearer <token>`. */
+    token?: string;
+    /** File holding the URL and token while running. */
+    discoveryPath?: string;
+};
+
+/**
+ * Browser for `install_native_messaging_host`. Matches Rust enum `NativeMessagingBrowser`.
+ */
+export type NativeMessagingBrowser = 'chrome' | 'chromium' | 'edge' | 'firefox';
+
+/**
+ * Result of checking a clearsigned text block.
+ * Matches Rust struct `ClearsignedVerification`.
+ */
+export type ClearsignedVerification = {
+    /** The signed text, unescaped, with `\n` line endings. */
+    text: string;
+    keyFingerprint: string;
+    /** Name of the matching own key or contact. */
+    signerName?: string;
+    result: VerificationResult;
+};
+
+/**
+ * Result of checking an S/MIME signed e-mail. Matches Rust struct `SmimeVerification`.
+ */
+export type SmimeVerification = {
+    /** The signature checks with the signer certificate and covers this exact body. */
+    signatureValid: boolean;
+    bodyAltered: boolean;
+    signerSubject?: string;
+    /** E-mail address in the signer certificate. */
+    signerEmail?: string;
+    /** Address in the `From` header. */
+    from?: string;
+    fromMatchesSigner?: boolean;
+    subject?: string;
+    signedAt?: string;
+    /** Certificates from the signer upwards, as far as each link checks. */
+    chain: CertificateSummary[];
+    chainComplete: boolean;
+    /** A certificate in the chain belongs to a verified contact or an own key. */
+    chainTrusted: boolean;
+    trustedBy?: string;
+    problems: string[];
+};
+
+/**
+ * A folder for organizing keys; folders nest through `parentId`.
+ * Matches Rust struct `KeyFolder`.
+ */
+export type KeyFolder = {
+    folderId: string;
+    name: string;
+    parentId?: string;
+    createdAt: string;
+};
+
+/**
+ * Owner identity attached to a key pair.
+ * Matches Rust struct `SignerIdentity`. Uses camelCase.
+ */
+export type SignerIdentity = {
+    fullName?: string;
+    email?: string;
+    organization?: string;
 };
 
 /**
@@ -31,18 +1059,22 @@ export type KeyDetails = {
 };
 
 /**
- * Enumerates the supported signature algorithms (string literals matching Rust Display).
+ * Identifiers of the supported signature algorithms. Matches Rust enum `SignatureAlgorithm`;
+ * get text to show from `get_display_names`.
  */
 export type SignatureAlgorithm =
-    | "RSA-PKCS1-SHA256"
-    | "ECDSA-P256-SHA256"
-    | "Ed25519";
+    | 'rsaPkcs1Sha256'
+    | 'ecdsaP256Sha256'
+    | 'ecdsaP384Sha384'
+    | 'ecdsaP521Sha512'
+    | 'ed25519'
+    | 'mlDsa65';
 
 /**
  * Specifies the desired format for the output signature.
  * Matches Rust enum `SignatureFormat`. Uses camelCase if specified in Rust.
  */
-export type SignatureFormat = 'detached'; // <-- Value likely remains lowercase
+export type SignatureFormat = 'detached' | 'envelope' | 'hybrid'; // <-- Value likely remains lowercase
 
 /**
  * Options provided when invoking the signing command.
@@ -51,8 +1083,55 @@ export type SignatureFormat = 'detached'; // <-- Value likely remains lowercase
 export type SigningOptions = {
     /** The desired output format for the signature. */
     format: SignatureFormat; // <-- Already camelCase
+    /** Bind the document's file name into the signature (envelope format only). */
+    bindFileName?: boolean;
+    /** Also bind the document's size and modification time (envelope format only). */
+    bindFileAttributes?: boolean;
+    /**
+     * Baseline level; 'T' adds an RFC 3161 time-stamp from a trusted TSA (envelope format
+     * only).
+     */
+    signatureLevel?: SignatureLevel;
+    /** Time-stamping authority URL, required for level 'T' and `trustedTime`. */
+    tsaUrl?: string;
+    /** Take the signing time from the TSA instead of the local clock (envelope format only). */
+    trustedTime?: boolean;
+    /** Encoding of ECDSA signatures in detached files; verification accepts both. */
+    ecdsaEncoding?: EcdsaEncoding;
+    /** File encoding of detached signatures; verification detects it. Defaults to 'binary'. */
+    signatureEncoding?: SignatureEncoding;
+    /** How detached RSA/ECDSA signatures digest the document; defaults to 'standard'. */
+    digestScheme?: DigestScheme;
+    /** Custom fields signed into the envelope, checked against the `MetadataSchema`. */
+    metadata?: Record<string, string>;
+    /** Also write a clearsigned certificate of signing next to the signature. */
+    completionCertificate?: boolean;
+    /** Log in with the configured OpenID Connect provider and sign the identity into the envelope. */
+    oidcIdentity?: boolean;
 };
 
+/**
+ * 'standard' signatures verify with OpenSSL; 'legacy' is the double-hash scheme of earlier
+ * releases. Matches Rust enum `DigestScheme`.
+ */
+export type DigestScheme = 'standard' | 'legacy';
+
+/**
+ * ECDSA signature byte encoding. Matches Rust enum `EcdsaEncoding`.
+ */
+export type EcdsaEncoding = 'raw' | 'der';
+
+/**
+ * File encoding of detached signatures ('pem' is `-----BEGIN SIGNATURE-----` armor).
+ * Matches Rust enum `SignatureEncoding`.
+ */
+export type SignatureEncoding = 'binary' | 'base64' | 'pem';
+
+/**
+ * Signature baseline level. Matches Rust enum `SignatureLevel`.
+ */
+export type SignatureLevel = 'B' | 'T';
+
 /**
  * Represents the outcome of a signature verification attempt.
  * Matches Rust struct `VerificationResult`. Uses camelCase.
@@ -62,13 +1141,308 @@ export type VerificationResult = {
     isValid: boolean;      // <-- Changed to camelCase
     /** Provides details if verification failed or if a processing error occurred. */
     errorMessage?: string | null; // <-- Changed to camelCase, made optional '?' due to skip_serializing_if
+    /** Bound file attributes that differ from the verified file. */
+    bindingMismatches?: FileBindingMismatch[];
+    /** Hash algorithm of the digests below (e.g. "sha256"). */
+    digestAlgorithm?: string;
+    /** Digest of the document computed during verification (hex). */
+    computedDigest?: string;
+    /** Digest recorded in the signature envelope, when present (hex). */
+    expectedDigest?: string;
+    /** RFC 3161 time-stamps attached to the envelope. */
+    timestamps?: TimestampReport[];
+    /** Signing time recorded in the envelope (ISO 8601). */
+    signedAt?: string;
+    /** Where the signing time came from; 'localClock' is only the signer's claim. */
+    signingTimeSource?: TimeSource;
+    /** The detached signature only verified with the legacy double-hash scheme. */
+    legacyDigest?: boolean;
+    /** The detached Ed25519 signature is Ed25519ph (over the SHA-512 of the document). */
+    prehashed?: boolean;
+    /** Custom metadata signed into the envelope. */
+    metadata?: Record<string, string>;
+    /** Second-person approval recorded in the envelope (checked as part of verification). */
+    approval?: SigningApproval;
+    /** Per-key sequence number recorded in the envelope. */
+    sequence?: number;
+    /** Signer identity from an OpenID Connect login, recorded in the envelope. */
+    oidcIdentity?: OidcIdentity;
+    /** Post-quantum co-signer of a hybrid envelope (its signature is checked as well). */
+    pqCosigner?: PqCosigner;
+    /** How long reading, hashing and checking took. */
+    metrics?: OperationMetrics;
+};
+
+/**
+ * Timing of a sign or verify operation; times are in milliseconds.
+ * Matches Rust struct `OperationMetrics`.
+ */
+export type OperationMetrics = {
+    bytesProcessed: number;
+    /** Unlocking the private key, mostly the password KDF (signing only). */
+    kdfMs?: number;
+    /** Reading and hashing the document. */
+    hashMs: number;
+    /** Creating or checking the signature, time-stamps and the output file. */
+    signatureMs: number;
+    totalMs: number;
+    mibPerSecond: number;
+};
+
+/**
+ * Result of `sign_document`. Matches Rust struct `SigningResult`.
+ */
+export type SigningResult = {
+    outputPath: string;
+    /** Certificate of signing, when one was requested. */
+    certificatePath?: string;
+    metrics: OperationMetrics;
+};
+
+/**
+ * Source of an envelope's signing time. Matches Rust enum `TimeSource`.
+ */
+export type TimeSource = 'localClock' | 'tsa';
+
+/**
+ * Check result for one RFC 3161 time-stamp attached to a signature envelope.
+ * Matches Rust struct `TimestampReport`.
+ */
+export type TimestampReport = {
+    tsaUrl: string;
+    /** Time asserted by the time-stamping authority (ISO 8601). */
+    genTime: string;
+    tsaName?: string;
+    /** End of the TSA certificate's validity period (ISO 8601). */
+    certificateNotAfter?: string;
+    /** Instant the TSA certificate was judged at: the requested date or the token time. */
+    checkedAt?: string;
+    /** Whether the TSA certificate was revoked at `checkedAt`, as far as is known. */
+    revocationStatus?: RevocationStatus;
+    isValid: boolean;
+    errorMessage?: string;
+};
+
+/**
+ * Revocation status of a TSA certificate, from CRLs carried in the time-stamp token only.
+ * Matches Rust enum `RevocationStatus`.
+ */
+export type RevocationStatus = 'unknown' | 'notRevoked';
+
+/**
+ * Outcome for one signature file of `verify_signatures`.
+ * Matches Rust struct `SignatureFileReport`.
+ */
+export type SignatureFileReport = {
+    signaturePath: string;
+    /** Name of the key or contact whose key validated (or is named by) the signature. */
+    signerName?: string;
+    signerFingerprint?: string;
+    result: VerificationResult;
 };
 
+/**
+ * Aggregated report for several signature files over one document.
+ * Matches Rust struct `MultiVerificationReport`.
+ */
+export type MultiVerificationReport = {
+    documentPath: string;
+    validCount: number;
+    signatures: SignatureFileReport[];
+};
+
+/**
+ * A file attribute bound into a signature that does not match the verified file.
+ * Matches Rust struct `FileBindingMismatch`.
+ */
+export type FileBindingMismatch = {
+    field: 'fileName' | 'fileSize' | 'fileModifiedAt';
+    expected: string;
+    actual: string;
+};
+
+/**
+ * Prefix of a path the backend sent as base64url of its native bytes, because the path is
+ * not valid Unicode. Pass such strings back unchanged; they are not meant for display.
+ * Matches Rust constant `ENCODED_PATH_PREFIX`.
+ */
+export const ENCODED_PATH_PREFIX = 'sigust-path:';
+
 /**
  * Represents the list of algorithms supported by the frontend for generation.
  */
 export const supportedAlgorithmsForGeneration: { value: SignatureAlgorithm; label: string }[] = [
-    { value: "RSA-PKCS1-SHA256", label: "RSA 2048 (PKCS#1 SHA-256)" },
-    { value: "ECDSA-P256-SHA256", label: "ECDSA P-256 (SHA-256)" },
-    { value: "Ed25519", label: "Ed25519" },
+    { value: 'rsaPkcs1Sha256', label: "RSA 2048 (PKCS#1 SHA-256)" },
+    { value: 'ecdsaP256Sha256', label: "ECDSA P-256 (SHA-256)" },
+    { value: 'ecdsaP384Sha384', label: "ECDSA P-384 (SHA-384)" },
+    { value: 'ecdsaP521Sha512', label: "ECDSA P-521 (SHA-512)" },
+    { value: 'ed25519', label: "Ed25519" },
+    { value: 'mlDsa65', label: "ML-DSA-65 (post-quantum)" },
 ];
+
+/**
+ * One entry of the hash-chained audit log. Matches Rust struct `AuditEntry`.
+ */
+export type AuditEntry = {
+    seq: number;
+    /** ISO 8601 timestamp. */
+    timestamp: string;
+    /** What happened, e.g. "signDocument". */
+    action: string;
+    details: Record<string, unknown>;
+    prevHash: string;
+    hash: string;
+};
+
+/** File format for `export_audit_log`. Matches Rust enum `AuditExportFormat`. */
+export type AuditExportFormat = 'json' | 'csv';
+
+/** File format for `generate_verification_report` and `export_keycard`. Matches Rust enum `ReportFormat`. */
+export type ReportFormat = 'html' | 'pdf';
+
+/** External tool a verification kit is prepared for. Matches Rust enum `VerificationTool`. */
+export type VerificationTool = 'openssl' | 'sshKeygen' | 'gpg';
+
+/**
+ * Files and commands written by `export_verification_kit`. Matches Rust struct `VerificationKit`.
+ */
+export type VerificationKit = {
+    kitDir: string;
+    files: string[];
+    /** Shell commands that check the document, to be run in the kit folder. */
+    commands: string[];
+};
+
+/**
+ * Result of `verify_audit_log`. Matches Rust struct `AuditVerificationReport`.
+ */
+export type AuditVerificationReport = {
+    isValid: boolean;
+    entries: number;
+    firstInvalidSeq?: number;
+    errorMessage?: string;
+};
+
+/**
+ * Time window for `get_statistics`. Matches Rust struct `StatisticsRange`.
+ */
+export type StatisticsRange = {
+    /** ISO 8601 timestamp; omitted or null leaves the window open. */
+    from?: string | null;
+    to?: string | null;
+};
+
+/**
+ * Signing and verification counts. Matches Rust struct `UsageCounts`.
+ */
+export type UsageCounts = {
+    signatures: number;
+    /** Signatures checked, including failed ones. */
+    verifications: number;
+    failedVerifications: number;
+};
+
+/**
+ * Usage of one key. Matches Rust struct `KeyUsage`.
+ */
+export type KeyUsage = {
+    keyId: string;
+    /** Null once the key has been purged. */
+    name: string | null;
+    algorithm: SignatureAlgorithm | null;
+    counts: UsageCounts;
+};
+
+/**
+ * Usage per signature algorithm. Matches Rust struct `AlgorithmUsage`.
+ */
+export type AlgorithmUsage = {
+    /** Null for operations whose key is unknown, e.g. batch verification. */
+    algorithm: SignatureAlgorithm | null;
+    counts: UsageCounts;
+};
+
+/**
+ * Usage on one UTC day. Matches Rust struct `DailyUsage`.
+ */
+export type DailyUsage = {
+    /** "YYYY-MM-DD". */
+    day: string;
+    counts: UsageCounts;
+};
+
+/**
+ * Result of `get_statistics`. Matches Rust struct `UsageStatistics`.
+ */
+export type UsageStatistics = {
+    range: StatisticsRange;
+    totals: UsageCounts;
+    /** Most used first. */
+    byKey: KeyUsage[];
+    byAlgorithm: AlgorithmUsage[];
+    /** Oldest first; days without activity are left out. */
+    byDay: DailyUsage[];
+};
+
+/**
+ * One parsed line of the application log. Matches Rust struct `LogEntry`.
+ */
+export type LogEntry = {
+    timestamp: string;
+    level: 'ERROR' | 'WARN' | 'INFO' | 'DEBUG' | 'TRACE';
+    target: string;
+    message: string;
+};
+
+/**
+ * Result of `save_key_vault` / `restore_key_vault`. Matches Rust struct `KeyVaultReport`.
+ */
+export type KeyVaultReport = {
+    keyCount: number;
+    recordCount: number;
+    appendedRecords: number;
+    restored: string[]; // key IDs
+};
+
+/**
+ * Handling of an output path that already exists. Matches Rust enum `CollisionPolicy`.
+ */
+export type CollisionPolicy = 'error' | 'autoNumber' | 'overwrite';
+
+/**
+ * How commands name and write output files. Matches Rust struct `OutputSettings`.
+ */
+export type OutputSettings = {
+    /** Placeholders: {name}, {stem}, {ext}, {alg}, {date}. */
+    signatureFileTemplate: string;
+    collisionPolicy: CollisionPolicy;
+};
+
+/**
+ * Operation carried out by a resumable batch job. Matches Rust enum `BatchJobKind`.
+ */
+export type BatchJobKind = 'sign' | 'verify';
+
+/**
+ * One document of a batch job. Matches Rust struct `BatchJobItem`.
+ */
+export type BatchJobItem = {
+    documentPath: string;
+    /** Required for verification jobs. */
+    signaturePath?: string;
+};
+
+/**
+ * A batch job that has not run to the end. Matches Rust struct `BatchJobInfo`.
+ */
+export type BatchJobInfo = {
+    jobId: string; // UUID
+    kind: BatchJobKind;
+    keyId?: string; // UUID
+    outputDir?: string;
+    createdAt: string; // ISO 8601
+    total: number;
+    processed: number;
+    failed: number;
+    taskId?: string; // UUID of the task running it
+};